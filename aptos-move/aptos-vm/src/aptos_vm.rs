@@ -9,7 +9,8 @@ use crate::{
     data_cache::{AsMoveResolver, StorageAdapter},
     errors::expect_only_successful_execution,
     move_vm_ext::{
-        get_max_binary_format_version, AptosMoveResolver, RespawnedSession, SessionExt, SessionId,
+        get_max_binary_format_version, AptosMoveResolver, MoveVmExt, RespawnedSession, SessionExt,
+        SessionId,
     },
     sharded_block_executor::{executor_client::ExecutorClient, ShardedBlockExecutor},
     system_module_names::*,
@@ -233,6 +234,12 @@ impl AptosVM {
         }
     }
 
+    /// Drops the cached warm VMs, so that the next block is executed with a cold code cache.
+    /// Only meant for benchmarking the cost of loading modules.
+    pub fn flush_warm_vm_cache() {
+        MoveVmExt::flush_warm_vm_cache();
+    }
+
     pub fn internals(&self) -> AptosVMInternals {
         AptosVMInternals::new(&self.vm_impl)
     }
@@ -138,6 +138,11 @@ impl MoveVmExt {
         })
     }
 
+    /// Drops the warm VMs, so that VMs created afterwards start with an empty code cache.
+    pub(crate) fn flush_warm_vm_cache() {
+        WarmVmCache::flush();
+    }
+
     pub fn new(
         native_gas_params: NativeGasParameters,
         misc_gas_params: MiscGasParameters,
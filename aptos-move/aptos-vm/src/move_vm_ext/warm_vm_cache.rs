@@ -39,6 +39,10 @@ impl WarmVmCache {
         WARM_VM_CACHE.get(native_builder, vm_config, resolver)
     }
 
+    pub(crate) fn flush() {
+        WARM_VM_CACHE.cache.write().clear();
+    }
+
     fn get(
         &self,
         mut native_builder: SafeNativeBuilder,
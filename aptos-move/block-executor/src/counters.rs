@@ -72,6 +72,16 @@ pub static SPECULATIVE_ABORT_COUNT: Lazy<IntCounter> = Lazy::new(|| {
     .unwrap()
 });
 
+/// Count of failed validations, including the ones that didn't lead to an abort because the
+/// incarnation was already aborted.
+pub static VALIDATION_FAILURE_COUNT: Lazy<IntCounter> = Lazy::new(|| {
+    register_int_counter!(
+        "aptos_execution_validation_failure_count",
+        "Number of failed validations in parallel execution"
+    )
+    .unwrap()
+});
+
 /// Count of times the BlockSTM is early halted due to exceeding the per-block gas limit.
 pub static EXCEED_PER_BLOCK_GAS_LIMIT_COUNT: Lazy<IntCounterVec> = Lazy::new(|| {
     register_int_counter_vec!(
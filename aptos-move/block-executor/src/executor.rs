@@ -22,6 +22,7 @@ use aptos_aggregator::{
     types::{code_invariant_error, expect_ok, PanicOr},
 };
 use aptos_drop_helper::DEFAULT_DROPPER;
+use aptos_infallible::Mutex;
 use aptos_logger::{debug, error, info};
 use aptos_mvhashmap::{
     types::{Incarnation, MVDelayedFieldsError, TxnIndex, ValueWithLayout},
@@ -53,6 +54,29 @@ use std::{
     sync::{atomic::AtomicU32, Arc},
 };
 
+/// Scheduler-level stats of a block executed in parallel, tallied locally by each worker and
+/// summed once it is done, to log the stats of every block without contending on counters.
+#[derive(Debug, Default)]
+struct ParallelBlockStats {
+    executions: u64,
+    /// Executions of incarnations after the first one, i.e. after an abort.
+    reexecutions: u64,
+    validations: u64,
+    validation_failures: u64,
+    /// Executions resumed after waiting on a dependency.
+    dependency_waits: u64,
+}
+
+impl ParallelBlockStats {
+    fn merge(&mut self, other: &Self) {
+        self.executions += other.executions;
+        self.reexecutions += other.reexecutions;
+        self.validations += other.validations;
+        self.validation_failures += other.validation_failures;
+        self.dependency_waits += other.dependency_waits;
+    }
+}
+
 pub struct BlockExecutor<T, E, S, L, X> {
     // Number of active concurrent tasks, corresponding to the maximum number of rayon
     // threads that may be concurrently participating in parallel execution.
@@ -356,6 +380,9 @@ where
         versioned_cache: &MVHashMap<T::Key, T::Tag, T::Value, X, T::Identifier>,
         scheduler: &Scheduler,
     ) -> SchedulerTask {
+        if !valid {
+            counters::VALIDATION_FAILURE_COUNT.inc();
+        }
         let aborted = !valid && scheduler.try_abort(txn_idx, incarnation);
 
         if aborted {
@@ -949,6 +976,7 @@ where
             Option<Error<E::Error>>,
         )>,
         final_results: &ExplicitSyncWrapper<Vec<E::Output>>,
+        block_stats: &Mutex<ParallelBlockStats>,
     ) -> ::std::result::Result<(), PanicOr<IntentionalFallbackToSequential>> {
         // Make executor for each task. TODO: fast concurrent executor.
         let init_timer = VM_INIT_SECONDS.start_timer();
@@ -957,6 +985,7 @@ where
 
         let _timer = WORK_WITH_TASK_SECONDS.start_timer();
         let mut scheduler_task = SchedulerTask::NoTask;
+        let mut stats = ParallelBlockStats::default();
 
         let drain_commit_queue =
             || -> ::std::result::Result<(), PanicOr<IntentionalFallbackToSequential>> {
@@ -999,6 +1028,10 @@ where
             scheduler_task = match scheduler_task {
                 SchedulerTask::ValidationTask(txn_idx, incarnation, wave) => {
                     let valid = Self::validate(txn_idx, last_input_output, versioned_cache)?;
+                    stats.validations += 1;
+                    if !valid {
+                        stats.validation_failures += 1;
+                    }
                     Self::update_on_validation(
                         txn_idx,
                         incarnation,
@@ -1014,6 +1047,10 @@ where
                     incarnation,
                     ExecutionTaskType::Execution,
                 ) => {
+                    stats.executions += 1;
+                    if incarnation > 0 {
+                        stats.reexecutions += 1;
+                    }
                     let updates_outside = Self::execute(
                         txn_idx,
                         incarnation,
@@ -1032,6 +1069,7 @@ where
                     scheduler.finish_execution(txn_idx, incarnation, updates_outside)
                 },
                 SchedulerTask::ExecutionTask(_, _, ExecutionTaskType::Wakeup(condvar)) => {
+                    stats.dependency_waits += 1;
                     let (lock, cvar) = &*condvar;
                     // Mark dependency resolved.
                     let mut lock = lock.lock();
@@ -1044,6 +1082,7 @@ where
                 SchedulerTask::NoTask => scheduler.next_task(),
                 SchedulerTask::Done => {
                     drain_commit_queue()?;
+                    block_stats.lock().merge(&stats);
                     break Ok(());
                 },
             }
@@ -1091,6 +1130,7 @@ where
 
         let last_input_output = TxnLastInputOutput::new(num_txns);
         let scheduler = Scheduler::new(num_txns);
+        let block_stats = Mutex::new(ParallelBlockStats::default());
 
         let timer = RAYON_EXECUTION_SECONDS.start_timer();
         self.executor_thread_pool.scope(|s| {
@@ -1107,6 +1147,7 @@ where
                         &shared_counter,
                         &shared_commit_state,
                         &final_results,
+                        &block_stats,
                     ) {
                         if scheduler.halt() {
                             let mut shared_commit_state_guard = shared_commit_state.acquire();
@@ -1118,6 +1159,11 @@ where
             }
         });
         drop(timer);
+        debug!(
+            "[BlockSTM]: Executed block of {} txns: {:?}",
+            num_txns,
+            block_stats.into_inner()
+        );
         // Explicit async drops.
         DEFAULT_DROPPER.schedule_drop((last_input_output, scheduler, versioned_cache));
         let (_, _, maybe_error) = shared_commit_state.into_inner();
@@ -94,6 +94,8 @@ pub struct RocksdbConfig {
     pub block_size: u64,
     /// Whether cache index and filter blocks into block cache.
     pub cache_index_and_filter_blocks: bool,
+    /// How durable a write is once it returns.
+    pub durability: RocksdbDurability,
 }
 
 impl Default for RocksdbConfig {
@@ -113,10 +115,27 @@ impl Default for RocksdbConfig {
             block_size: 4 * (1u64 << 10),
             // Whether cache index and filter blocks into block cache.
             cache_index_and_filter_blocks: false,
+            durability: RocksdbDurability::default(),
         }
     }
 }
 
+/// Trades the durability of the writes to RocksDB for their latency. Anything but `Strict` may
+/// lose committed data on a crash, and is only meant for benchmarks and tests: the config
+/// sanitizer rejects it for nodes.
+#[derive(Clone, Copy, Debug, Default, Deserialize, PartialEq, Eq, Serialize)]
+#[serde(rename_all = "snake_case")]
+pub enum RocksdbDurability {
+    /// The WAL is fsynced before a write returns, so it survives a machine crash.
+    #[default]
+    Strict,
+    /// The WAL is written but not fsynced, so a write survives a crash of the process, but not of
+    /// the machine.
+    Relaxed,
+    /// No WAL, a write is only persisted once its memtable is flushed.
+    None,
+}
+
 #[derive(Clone, Copy, Debug, Deserialize, PartialEq, Eq, Serialize)]
 #[serde(default, deny_unknown_fields)]
 pub struct RocksdbConfigs {
@@ -516,6 +535,28 @@ impl ConfigSanitizer for StorageConfig {
             ));
         }
 
+        // Relaxed durability is for benchmarks, which open the DB without going through here.
+        let rocksdb_configs = &config.rocksdb_configs;
+        for (name, rocksdb_config) in [
+            ("ledger_db_config", &rocksdb_configs.ledger_db_config),
+            (
+                "state_merkle_db_config",
+                &rocksdb_configs.state_merkle_db_config,
+            ),
+            ("state_kv_db_config", &rocksdb_configs.state_kv_db_config),
+            ("index_db_config", &rocksdb_configs.index_db_config),
+        ] {
+            if rocksdb_config.durability != RocksdbDurability::Strict {
+                return Err(Error::ConfigSanitizerFailed(
+                    sanitizer_name,
+                    format!(
+                        "{}.durability is {:?}, nodes could lose committed data on a crash. Set it to strict.",
+                        name, rocksdb_config.durability
+                    ),
+                ));
+            }
+        }
+
         if let Some(db_path_overrides) = config.db_path_overrides.as_ref() {
             if !config.rocksdb_configs.enable_storage_sharding {
                 return Err(Error::ConfigSanitizerFailed(
@@ -573,7 +614,10 @@ impl ConfigSanitizer for StorageConfig {
 
 #[cfg(test)]
 mod test {
-    use crate::config::{PrunerConfig, ShardPathConfig, ShardedDbPathConfig};
+    use crate::config::{
+        config_sanitizer::ConfigSanitizer, node_config_loader::NodeType, NodeConfig, PrunerConfig,
+        RocksdbDurability, ShardPathConfig, ShardedDbPathConfig, StorageConfig,
+    };
 
     #[test]
     pub fn test_default_prune_window() {
@@ -585,6 +629,26 @@ mod test {
         assert!(config.epoch_snapshot_pruner_config.prune_window > 50_000_000);
     }
 
+    #[test]
+    pub fn test_sanitize_relaxed_durability() {
+        let mut node_config = NodeConfig::default();
+        assert!(StorageConfig::sanitize(&node_config, NodeType::Validator, None).is_ok());
+
+        // Relaxed durability is rejected for every node type
+        node_config
+            .storage
+            .rocksdb_configs
+            .state_kv_db_config
+            .durability = RocksdbDurability::Relaxed;
+        for node_type in [
+            NodeType::Validator,
+            NodeType::ValidatorFullnode,
+            NodeType::PublicFullnode,
+        ] {
+            assert!(StorageConfig::sanitize(&node_config, node_type, None).is_err());
+        }
+    }
+
     #[test]
     pub fn test_sharded_db_path_config() {
         let path_overrides = ShardedDbPathConfig {
@@ -626,6 +626,7 @@ impl TxnEmitter {
             &txn_factory,
             &init_txn_factory,
             stats.get_cur_phase_obj(),
+            None,
         )
         .await;
 
@@ -1,6 +1,8 @@
 // Copyright © Aptos Foundation
 // SPDX-License-Identifier: Apache-2.0
-use crate::{create_account_transaction, TransactionGenerator, TransactionGeneratorCreator};
+use crate::{
+    create_account_transaction, AccountsPool, TransactionGenerator, TransactionGeneratorCreator,
+};
 use aptos_infallible::RwLock;
 use aptos_logger::{info, sample, sample::SampleRate};
 use aptos_sdk::{
@@ -15,7 +17,7 @@ pub struct AccountGenerator {
     rng: StdRng,
     txn_factory: TransactionFactory,
     addresses_pool: Arc<RwLock<Vec<AccountAddress>>>,
-    accounts_pool: Arc<RwLock<Vec<LocalAccount>>>,
+    accounts_pool: AccountsPool,
     add_created_accounts_to_pool: bool,
     max_working_set: usize,
     creation_balance: u64,
@@ -26,7 +28,7 @@ impl AccountGenerator {
         rng: StdRng,
         txn_factory: TransactionFactory,
         addresses_pool: Arc<RwLock<Vec<AccountAddress>>>,
-        accounts_pool: Arc<RwLock<Vec<LocalAccount>>>,
+        accounts_pool: AccountsPool,
         add_created_accounts_to_pool: bool,
         max_working_set: usize,
         creation_balance: u64,
@@ -115,7 +117,7 @@ impl TransactionGenerator for AccountGenerator {
 pub struct AccountGeneratorCreator {
     txn_factory: TransactionFactory,
     addresses_pool: Arc<RwLock<Vec<AccountAddress>>>,
-    accounts_pool: Arc<RwLock<Vec<LocalAccount>>>,
+    accounts_pool: AccountsPool,
     add_created_accounts_to_pool: bool,
     max_working_set: usize,
     creation_balance: u64,
@@ -125,7 +127,7 @@ impl AccountGeneratorCreator {
     pub fn new(
         txn_factory: TransactionFactory,
         addresses_pool: Arc<RwLock<Vec<AccountAddress>>>,
-        accounts_pool: Arc<RwLock<Vec<LocalAccount>>>,
+        accounts_pool: AccountsPool,
         add_created_accounts_to_pool: bool,
         max_working_set: usize,
         creation_balance: u64,
@@ -1,10 +1,10 @@
 // Copyright © Aptos Foundation
 // SPDX-License-Identifier: Apache-2.0
 
-use crate::{get_account_to_burn_from_pool, TransactionGenerator, TransactionGeneratorCreator};
-use aptos_infallible::RwLock;
+use crate::{
+    get_account_to_burn_from_pool, AccountsPool, TransactionGenerator, TransactionGeneratorCreator,
+};
 use aptos_sdk::types::{transaction::SignedTransaction, LocalAccount};
-use std::sync::Arc;
 
 /// Wrapper that allows inner transaction generator to have unique accounts
 /// for all transactions (instead of having 5-20 transactions per account, as default)
@@ -13,14 +13,11 @@ use std::sync::Arc;
 /// (we cannot use more as sequence number is not updated on failure)
 pub struct AccountsPoolWrapperGenerator {
     creator: Box<dyn TransactionGenerator>,
-    accounts_pool: Arc<RwLock<Vec<LocalAccount>>>,
+    accounts_pool: AccountsPool,
 }
 
 impl AccountsPoolWrapperGenerator {
-    pub fn new(
-        creator: Box<dyn TransactionGenerator>,
-        accounts_pool: Arc<RwLock<Vec<LocalAccount>>>,
-    ) -> Self {
+    pub fn new(creator: Box<dyn TransactionGenerator>, accounts_pool: AccountsPool) -> Self {
         Self {
             creator,
             accounts_pool,
@@ -48,14 +45,11 @@ impl TransactionGenerator for AccountsPoolWrapperGenerator {
 
 pub struct AccountsPoolWrapperCreator {
     creator: Box<dyn TransactionGeneratorCreator>,
-    accounts_pool: Arc<RwLock<Vec<LocalAccount>>>,
+    accounts_pool: AccountsPool,
 }
 
 impl AccountsPoolWrapperCreator {
-    pub fn new(
-        creator: Box<dyn TransactionGeneratorCreator>,
-        accounts_pool: Arc<RwLock<Vec<LocalAccount>>>,
-    ) -> Self {
+    pub fn new(creator: Box<dyn TransactionGeneratorCreator>, accounts_pool: AccountsPool) -> Self {
         Self {
             creator,
             accounts_pool,
@@ -1,7 +1,7 @@
 // Copyright © Aptos Foundation
 // SPDX-License-Identifier: Apache-2.0
 
-use crate::{EntryPoints, TransactionType};
+use crate::{EntryPoints, KeyScheme, TableKind, TransactionType, MAX_CHAIN_MODULES};
 use clap::{Parser, ValueEnum};
 use serde::{Deserialize, Serialize};
 
@@ -35,6 +35,15 @@ pub enum TransactionTypeArg {
     SmartTablePicture30KWith200Change,
     SmartTablePicture1MWith1KChange,
     SmartTablePicture1BWith1KChange,
+    ResourceBloat,
+    TableItems1KKeys,
+    TableItems1MKeys,
+    SmartTableItems1KKeys,
+    SmartTableItems1MKeys,
+    Ed25519SignerCoinTransfer,
+    Secp256k1SignerCoinTransfer,
+    ComposabilityChain,
+    ComposabilityChainDeep,
 }
 
 impl TransactionTypeArg {
@@ -78,6 +87,7 @@ impl TransactionTypeArg {
             TransactionTypeArg::AccountResource32B => TransactionType::CallCustomModules {
                 entry_point: EntryPoints::BytesMakeOrChange {
                     data_length: Some(32),
+                    entropy: None,
                 },
                 num_modules: module_working_set_size,
                 use_account_pool: sender_use_account_pool,
@@ -85,6 +95,7 @@ impl TransactionTypeArg {
             TransactionTypeArg::AccountResource1KB => TransactionType::CallCustomModules {
                 entry_point: EntryPoints::BytesMakeOrChange {
                     data_length: Some(1024),
+                    entropy: None,
                 },
                 num_modules: module_working_set_size,
                 use_account_pool: sender_use_account_pool,
@@ -92,6 +103,7 @@ impl TransactionTypeArg {
             TransactionTypeArg::AccountResource10KB => TransactionType::CallCustomModules {
                 entry_point: EntryPoints::BytesMakeOrChange {
                     data_length: Some(10 * 1024),
+                    entropy: None,
                 },
                 num_modules: module_working_set_size,
                 use_account_pool: sender_use_account_pool,
@@ -202,6 +214,64 @@ impl TransactionTypeArg {
                     use_account_pool: sender_use_account_pool,
                 }
             },
+            TransactionTypeArg::ResourceBloat => TransactionType::ResourceBloat {
+                bloat_percentage: 10,
+                entries_per_txn: 10,
+                num_modules: module_working_set_size,
+                use_account_pool: sender_use_account_pool,
+            },
+            TransactionTypeArg::TableItems1KKeys => TransactionType::TableItems {
+                kind: TableKind::Table,
+                key_space_size: 1024,
+                entries_per_txn: 10,
+                removal_percentage: 10,
+                num_modules: module_working_set_size,
+                use_account_pool: sender_use_account_pool,
+            },
+            TransactionTypeArg::TableItems1MKeys => TransactionType::TableItems {
+                kind: TableKind::Table,
+                key_space_size: 1024 * 1024,
+                entries_per_txn: 10,
+                removal_percentage: 10,
+                num_modules: module_working_set_size,
+                use_account_pool: sender_use_account_pool,
+            },
+            TransactionTypeArg::SmartTableItems1KKeys => TransactionType::TableItems {
+                kind: TableKind::SmartTable,
+                key_space_size: 1024,
+                entries_per_txn: 10,
+                removal_percentage: 10,
+                num_modules: module_working_set_size,
+                use_account_pool: sender_use_account_pool,
+            },
+            TransactionTypeArg::SmartTableItems1MKeys => TransactionType::TableItems {
+                kind: TableKind::SmartTable,
+                key_space_size: 1024 * 1024,
+                entries_per_txn: 10,
+                removal_percentage: 10,
+                num_modules: module_working_set_size,
+                use_account_pool: sender_use_account_pool,
+            },
+            TransactionTypeArg::Ed25519SignerCoinTransfer => TransactionType::KeySchemeTransfer {
+                key_scheme: KeyScheme::Ed25519,
+                num_signers: 20_000,
+            },
+            TransactionTypeArg::Secp256k1SignerCoinTransfer => TransactionType::KeySchemeTransfer {
+                key_scheme: KeyScheme::Secp256k1Ecdsa,
+                num_signers: 20_000,
+            },
+            TransactionTypeArg::ComposabilityChain => TransactionType::ComposabilityChain {
+                num_modules: MAX_CHAIN_MODULES,
+                call_depth: 1,
+                num_chains: module_working_set_size,
+                use_account_pool: sender_use_account_pool,
+            },
+            TransactionTypeArg::ComposabilityChainDeep => TransactionType::ComposabilityChain {
+                num_modules: MAX_CHAIN_MODULES,
+                call_depth: 100,
+                num_chains: module_working_set_size,
+                use_account_pool: sender_use_account_pool,
+            },
         }
     }
 
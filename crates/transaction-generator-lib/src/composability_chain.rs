@@ -0,0 +1,193 @@
+// Copyright © Aptos Foundation
+// SPDX-License-Identifier: Apache-2.0
+
+use crate::{
+    create_account_transaction, publishing::publish_util::Package, ReliableTransactionSubmitter,
+    TransactionGenerator, TransactionGeneratorCreator,
+};
+use aptos_logger::info;
+use aptos_sdk::{
+    bcs,
+    move_types::{ident_str, identifier::Identifier, language_storage::ModuleId},
+    transaction_builder::TransactionFactory,
+    types::{
+        transaction::{EntryFunction, SignedTransaction, TransactionPayload},
+        LocalAccount,
+    },
+};
+use rand::{rngs::StdRng, seq::SliceRandom, SeedableRng};
+use std::sync::Arc;
+
+/// Number of modules in the `composability` package, i.e. the longest chain.
+pub const MAX_CHAIN_MODULES: usize = 8;
+/// The VM aborts transactions with a deeper call stack.
+const MAX_CALL_STACK_DEPTH: u64 = 1000;
+
+fn chain_module_name(link: usize) -> String {
+    format!("chain_{}", link)
+}
+
+/// Transactions calling into a chain of modules, each of them published by a different account,
+/// to benchmark deep call stacks and loading many modules per transaction, which transfers and
+/// single module workloads don't exercise.
+///
+/// Every transaction recurses `call_depth` frames deep into each of the first `num_modules`
+/// modules of a chain, so its call stack is `num_modules * call_depth` frames deep. All
+/// `MAX_CHAIN_MODULES` modules of every chain are published, as a module can only be published
+/// once the module it calls into is.
+pub struct ComposabilityChainGenerator {
+    rng: StdRng,
+    txn_factory: TransactionFactory,
+    entry_modules: Arc<Vec<ModuleId>>,
+    num_modules: usize,
+    call_depth: u64,
+}
+
+impl TransactionGenerator for ComposabilityChainGenerator {
+    fn generate_transactions(
+        &mut self,
+        account: &LocalAccount,
+        num_to_create: usize,
+    ) -> Vec<SignedTransaction> {
+        (0..num_to_create)
+            .map(|_| {
+                let module_id = self.entry_modules.choose(&mut self.rng).unwrap().clone();
+                let payload = TransactionPayload::EntryFunction(EntryFunction::new(
+                    module_id,
+                    ident_str!("run").to_owned(),
+                    vec![],
+                    vec![
+                        bcs::to_bytes(&(self.num_modules as u64)).unwrap(),
+                        bcs::to_bytes(&self.call_depth).unwrap(),
+                    ],
+                ));
+                account.sign_with_transaction_builder(self.txn_factory.payload(payload))
+            })
+            .collect()
+    }
+}
+
+pub struct ComposabilityChainGeneratorCreator {
+    txn_factory: TransactionFactory,
+    entry_modules: Arc<Vec<ModuleId>>,
+    num_modules: usize,
+    call_depth: u64,
+}
+
+impl ComposabilityChainGeneratorCreator {
+    pub async fn new(
+        txn_factory: TransactionFactory,
+        init_txn_factory: TransactionFactory,
+        accounts: &[LocalAccount],
+        txn_executor: &dyn ReliableTransactionSubmitter,
+        num_chains: usize,
+        num_modules: usize,
+        call_depth: u64,
+    ) -> Self {
+        assert!(
+            (1..=MAX_CHAIN_MODULES).contains(&num_modules),
+            "Chains have between 1 and {} modules",
+            MAX_CHAIN_MODULES
+        );
+        assert!(
+            call_depth > 0 && num_modules as u64 * call_depth <= MAX_CALL_STACK_DEPTH,
+            "Call stack of {} modules of depth {} exceeds {} frames",
+            num_modules,
+            call_depth,
+            MAX_CALL_STACK_DEPTH
+        );
+        assert!(!accounts.is_empty());
+        let mut rng = StdRng::from_entropy();
+        let package = Package::by_name("composability");
+
+        let chains = (0..num_chains)
+            .map(|_| {
+                (0..MAX_CHAIN_MODULES)
+                    .map(|_| LocalAccount::generate(&mut rng))
+                    .collect::<Vec<_>>()
+            })
+            .collect::<Vec<_>>();
+        // Spread over the accounts, which may not afford funding all publishers of a chain.
+        let requests_create = chains
+            .iter()
+            .flatten()
+            .zip(accounts.iter().cycle())
+            .map(|(publisher, account)| {
+                create_account_transaction(
+                    account,
+                    publisher.address(),
+                    &init_txn_factory,
+                    2 * init_txn_factory.get_gas_unit_price()
+                        * init_txn_factory.get_max_gas_amount(),
+                )
+            })
+            .collect::<Vec<_>>();
+        info!("Creating {} publisher accounts", requests_create.len());
+        txn_executor
+            .execute_transactions(&requests_create)
+            .await
+            .unwrap();
+
+        // From the end of the chains, as a module links against the next one when published.
+        for link in (0..MAX_CHAIN_MODULES).rev() {
+            let requests_publish = chains
+                .iter()
+                .map(|publishers| {
+                    let dependencies = publishers
+                        .get(link + 1)
+                        .map(|next| vec![(chain_module_name(link + 1), next.address())])
+                        .unwrap_or_default();
+                    package
+                        .split_module(
+                            &chain_module_name(link),
+                            publishers[link].address(),
+                            &dependencies,
+                        )
+                        .publish_transaction(&publishers[link], &init_txn_factory)
+                })
+                .collect::<Vec<_>>();
+            info!(
+                "Publishing module {} of {} chains",
+                link,
+                requests_publish.len()
+            );
+            txn_executor
+                .execute_transactions(&requests_publish)
+                .await
+                .unwrap();
+        }
+
+        let entry_modules = chains
+            .iter()
+            .map(|publishers| {
+                ModuleId::new(
+                    publishers[0].address(),
+                    Identifier::new(chain_module_name(0)).unwrap(),
+                )
+            })
+            .collect();
+        info!(
+            "Done preparing {} chains, calling {} modules of depth {}",
+            num_chains, num_modules, call_depth
+        );
+
+        Self {
+            txn_factory,
+            entry_modules: Arc::new(entry_modules),
+            num_modules,
+            call_depth,
+        }
+    }
+}
+
+impl TransactionGeneratorCreator for ComposabilityChainGeneratorCreator {
+    fn create_transaction_generator(&self) -> Box<dyn TransactionGenerator> {
+        Box::new(ComposabilityChainGenerator {
+            rng: StdRng::from_entropy(),
+            txn_factory: self.txn_factory.clone(),
+            entry_modules: self.entry_modules.clone(),
+            num_modules: self.num_modules,
+            call_depth: self.call_depth,
+        })
+    }
+}
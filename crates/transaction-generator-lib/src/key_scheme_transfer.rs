@@ -0,0 +1,158 @@
+// Copyright © Aptos Foundation
+// SPDX-License-Identifier: Apache-2.0
+
+use crate::{
+    create_account_transaction,
+    key_store::{AccountSigner, KeyScheme},
+    ReliableTransactionSubmitter, TransactionGenerator, TransactionGeneratorCreator,
+};
+use aptos_infallible::Mutex;
+use aptos_logger::info;
+use aptos_sdk::{
+    transaction_builder::{aptos_stdlib, TransactionFactory},
+    types::{transaction::SignedTransaction, LocalAccount},
+};
+use rand::{rngs::StdRng, SeedableRng};
+use std::{collections::VecDeque, sync::Arc};
+
+/// Balance each signer is funded with, enough for thousands of transfers.
+const SIGNER_BALANCE: u64 = 200_000_000;
+
+/// Coin transfers signed by accounts of a given key scheme, so that the cost of verifying
+/// alternative authenticators can be compared against the default Ed25519 transfers.
+///
+/// Signers are created and funded upfront, and then handed out round robin, so as long as
+/// there are more signers than transactions in a block, no signer is reused within a block.
+/// The account passed to the generator only receives the transfer.
+pub struct KeySchemeTransferGenerator {
+    txn_factory: TransactionFactory,
+    send_amount: u64,
+    signers: Arc<Mutex<VecDeque<Arc<dyn AccountSigner>>>>,
+}
+
+impl TransactionGenerator for KeySchemeTransferGenerator {
+    fn generate_transactions(
+        &mut self,
+        account: &LocalAccount,
+        num_to_create: usize,
+    ) -> Vec<SignedTransaction> {
+        let mut signers = self.signers.lock();
+        (0..num_to_create)
+            .map(|_| {
+                let signer = signers.pop_front().expect("Signer pool is never empty");
+                let txn = signer.sign_with_transaction_builder(self.txn_factory.payload(
+                    aptos_stdlib::aptos_account_transfer(account.address(), self.send_amount),
+                ));
+                signers.push_back(signer);
+                txn
+            })
+            .collect()
+    }
+}
+
+pub struct KeySchemeTransferGeneratorCreator {
+    txn_factory: TransactionFactory,
+    send_amount: u64,
+    signers: Arc<Mutex<VecDeque<Arc<dyn AccountSigner>>>>,
+}
+
+impl KeySchemeTransferGeneratorCreator {
+    pub async fn new(
+        txn_factory: TransactionFactory,
+        init_txn_factory: TransactionFactory,
+        source_accounts: &[LocalAccount],
+        txn_executor: &dyn ReliableTransactionSubmitter,
+        key_scheme: KeyScheme,
+        num_signers: usize,
+        send_amount: u64,
+    ) -> Self {
+        assert!(num_signers > 0, "Need at least one signer");
+        assert!(
+            !source_accounts.is_empty(),
+            "Need a source account to fund signers"
+        );
+        let mut rng = StdRng::from_entropy();
+        let key_store = key_scheme.key_store();
+        let signers = (0..num_signers)
+            .map(|_| key_store.generate(&mut rng))
+            .collect::<VecDeque<_>>();
+
+        let requests_create = source_accounts
+            .iter()
+            .cycle()
+            .zip(signers.iter())
+            .map(|(source, signer)| {
+                create_account_transaction(
+                    source,
+                    signer.address(),
+                    &init_txn_factory,
+                    SIGNER_BALANCE,
+                )
+            })
+            .collect::<Vec<_>>();
+        info!(
+            "Creating {} {:?} signer accounts",
+            requests_create.len(),
+            key_store.scheme()
+        );
+        txn_executor
+            .execute_transactions(&requests_create)
+            .await
+            .unwrap();
+
+        Self {
+            txn_factory,
+            send_amount,
+            signers: Arc::new(Mutex::new(signers)),
+        }
+    }
+}
+
+impl TransactionGeneratorCreator for KeySchemeTransferGeneratorCreator {
+    fn create_transaction_generator(&self) -> Box<dyn TransactionGenerator> {
+        Box::new(KeySchemeTransferGenerator {
+            txn_factory: self.txn_factory.clone(),
+            send_amount: self.send_amount,
+            signers: self.signers.clone(),
+        })
+    }
+}
+
+#[test]
+fn test_secp256k1_transfers_verify() {
+    use crate::key_store::Secp256k1Account;
+    use aptos_sdk::types::{
+        chain_id::ChainId,
+        transaction::authenticator::{AccountAuthenticator, AnyPublicKey, AuthenticationKey},
+    };
+
+    let mut rng = StdRng::seed_from_u64(0);
+    let signers = (0..2)
+        .map(|_| Arc::new(Secp256k1Account::generate(&mut rng)) as Arc<dyn AccountSigner>)
+        .collect::<VecDeque<_>>();
+    let mut generator = KeySchemeTransferGenerator {
+        txn_factory: TransactionFactory::new(ChainId::test()),
+        send_amount: 1,
+        signers: Arc::new(Mutex::new(signers)),
+    };
+    let receiver = LocalAccount::generate(&mut rng);
+    let txns = generator.generate_transactions(&receiver, 3);
+    assert_eq!(txns.len(), 3);
+    for txn in txns {
+        match txn.authenticator().sender() {
+            AccountAuthenticator::SingleKey { authenticator } => {
+                assert!(matches!(
+                    authenticator.public_key(),
+                    AnyPublicKey::Secp256k1Ecdsa { .. }
+                ));
+                assert_eq!(
+                    AuthenticationKey::any_key(authenticator.public_key().clone())
+                        .account_address(),
+                    txn.sender()
+                );
+            },
+            authenticator => panic!("Unexpected authenticator {:?}", authenticator),
+        }
+        txn.verify_signature().unwrap();
+    }
+}
@@ -0,0 +1,251 @@
+// Copyright © Aptos Foundation
+// SPDX-License-Identifier: Apache-2.0
+
+use anyhow::Result;
+use aptos_sdk::{
+    crypto::{
+        ed25519::Ed25519PrivateKey,
+        secp256k1_ecdsa::{PrivateKey, PublicKey},
+        SigningKey, Uniform,
+    },
+    move_types::account_address::AccountAddress,
+    transaction_builder::TransactionBuilder,
+    types::{
+        transaction::{
+            authenticator::{
+                AccountAuthenticator, AnyPublicKey, AnySignature, AuthenticationKey,
+                SingleKeyAuthenticator,
+            },
+            SignedTransaction,
+        },
+        LocalAccount,
+    },
+};
+use clap::ValueEnum;
+use rand::rngs::StdRng;
+use serde::{Deserialize, Serialize};
+use std::sync::{
+    atomic::{AtomicU64, Ordering},
+    Arc,
+};
+
+/// Signature scheme used by the accounts signing a workload's transactions.
+///
+/// Only Ed25519 and secp256k1 ECDSA keys, held in memory, are supported. Keyless (federated)
+/// accounts are not, as this tree has no keyless authenticator for the VM to verify yet.
+#[derive(Debug, Copy, Clone, ValueEnum, Default, Deserialize, Serialize)]
+pub enum KeyScheme {
+    #[default]
+    Ed25519,
+    Secp256k1Ecdsa,
+}
+
+impl KeyScheme {
+    pub fn key_store(&self) -> Box<dyn KeyStore> {
+        match self {
+            KeyScheme::Ed25519 => Box::new(Ed25519KeyStore),
+            KeyScheme::Secp256k1Ecdsa => Box::new(Secp256k1KeyStore),
+        }
+    }
+}
+
+/// An account that can sign transactions, independently of how its key is held.
+pub trait AccountSigner: Send + Sync {
+    fn address(&self) -> AccountAddress;
+
+    /// Fills in the sender and next sequence number, and signs the transaction.
+    fn sign_with_transaction_builder(&self, builder: TransactionBuilder) -> SignedTransaction;
+
+    /// The private key, encoded the way its key store loads it back.
+    fn private_key_bytes(&self) -> Vec<u8>;
+}
+
+/// Generates, stores and loads the keys of accounts of type `A` for a given key scheme.
+pub trait KeyStore<A = Arc<dyn AccountSigner>>: Send + Sync {
+    fn scheme(&self) -> KeyScheme;
+
+    fn generate(&self, rng: &mut StdRng) -> A;
+
+    /// Encodes the private key of `account`, to load the account back with `load`.
+    fn store(&self, account: &A) -> Vec<u8>;
+
+    /// The account of `address` with the stored `private_key`, at `sequence_number`.
+    fn load(&self, address: AccountAddress, private_key: &[u8], sequence_number: u64) -> Result<A>;
+}
+
+impl AccountSigner for LocalAccount {
+    fn address(&self) -> AccountAddress {
+        LocalAccount::address(self)
+    }
+
+    fn sign_with_transaction_builder(&self, builder: TransactionBuilder) -> SignedTransaction {
+        LocalAccount::sign_with_transaction_builder(self, builder)
+    }
+
+    fn private_key_bytes(&self) -> Vec<u8> {
+        self.private_key().to_bytes().to_vec()
+    }
+}
+
+pub struct Ed25519KeyStore;
+
+impl KeyStore<LocalAccount> for Ed25519KeyStore {
+    fn scheme(&self) -> KeyScheme {
+        KeyScheme::Ed25519
+    }
+
+    fn generate(&self, rng: &mut StdRng) -> LocalAccount {
+        LocalAccount::generate(rng)
+    }
+
+    fn store(&self, account: &LocalAccount) -> Vec<u8> {
+        account.private_key_bytes()
+    }
+
+    fn load(
+        &self,
+        address: AccountAddress,
+        private_key: &[u8],
+        sequence_number: u64,
+    ) -> Result<LocalAccount> {
+        Ok(LocalAccount::new(
+            address,
+            Ed25519PrivateKey::try_from(private_key)?,
+            sequence_number,
+        ))
+    }
+}
+
+/// Stores of `LocalAccount`s hand them out as signers as well.
+impl<S: KeyStore<LocalAccount>> KeyStore for S {
+    fn scheme(&self) -> KeyScheme {
+        KeyStore::<LocalAccount>::scheme(self)
+    }
+
+    fn generate(&self, rng: &mut StdRng) -> Arc<dyn AccountSigner> {
+        Arc::new(KeyStore::<LocalAccount>::generate(self, rng))
+    }
+
+    fn store(&self, account: &Arc<dyn AccountSigner>) -> Vec<u8> {
+        account.private_key_bytes()
+    }
+
+    fn load(
+        &self,
+        address: AccountAddress,
+        private_key: &[u8],
+        sequence_number: u64,
+    ) -> Result<Arc<dyn AccountSigner>> {
+        let account: LocalAccount = self.load(address, private_key, sequence_number)?;
+        Ok(Arc::new(account))
+    }
+}
+
+/// Account whose transactions carry a secp256k1 ECDSA single key authenticator.
+pub struct Secp256k1Account {
+    address: AccountAddress,
+    private_key: PrivateKey,
+    public_key: PublicKey,
+    sequence_number: AtomicU64,
+}
+
+impl Secp256k1Account {
+    pub fn new(address: AccountAddress, private_key: PrivateKey, sequence_number: u64) -> Self {
+        let public_key = PublicKey::from(&private_key);
+        Self {
+            address,
+            private_key,
+            public_key,
+            sequence_number: AtomicU64::new(sequence_number),
+        }
+    }
+
+    pub fn generate(rng: &mut StdRng) -> Self {
+        let private_key = PrivateKey::generate(rng);
+        let address = AuthenticationKey::any_key(AnyPublicKey::secp256k1_ecdsa(PublicKey::from(
+            &private_key,
+        )))
+        .account_address();
+        Self::new(address, private_key, 0)
+    }
+}
+
+impl AccountSigner for Secp256k1Account {
+    fn address(&self) -> AccountAddress {
+        self.address
+    }
+
+    fn sign_with_transaction_builder(&self, builder: TransactionBuilder) -> SignedTransaction {
+        let raw_txn = builder
+            .sender(self.address)
+            .sequence_number(self.sequence_number.fetch_add(1, Ordering::Relaxed))
+            .build();
+        let signature = self
+            .private_key
+            .sign(&raw_txn)
+            .expect("Signing a transaction can't fail");
+        SignedTransaction::new_single_sender(
+            raw_txn,
+            AccountAuthenticator::single_key(SingleKeyAuthenticator::new(
+                AnyPublicKey::secp256k1_ecdsa(self.public_key.clone()),
+                AnySignature::secp256k1_ecdsa(signature),
+            )),
+        )
+    }
+
+    fn private_key_bytes(&self) -> Vec<u8> {
+        self.private_key.to_bytes()
+    }
+}
+
+pub struct Secp256k1KeyStore;
+
+impl KeyStore for Secp256k1KeyStore {
+    fn scheme(&self) -> KeyScheme {
+        KeyScheme::Secp256k1Ecdsa
+    }
+
+    fn generate(&self, rng: &mut StdRng) -> Arc<dyn AccountSigner> {
+        Arc::new(Secp256k1Account::generate(rng))
+    }
+
+    fn store(&self, account: &Arc<dyn AccountSigner>) -> Vec<u8> {
+        account.private_key_bytes()
+    }
+
+    fn load(
+        &self,
+        address: AccountAddress,
+        private_key: &[u8],
+        sequence_number: u64,
+    ) -> Result<Arc<dyn AccountSigner>> {
+        Ok(Arc::new(Secp256k1Account::new(
+            address,
+            PrivateKey::try_from(private_key)?,
+            sequence_number,
+        )))
+    }
+}
+
+#[test]
+fn test_store_and_load() {
+    use rand::SeedableRng;
+
+    let mut rng = StdRng::seed_from_u64(0);
+    for scheme in [KeyScheme::Ed25519, KeyScheme::Secp256k1Ecdsa] {
+        let key_store = scheme.key_store();
+        let account = key_store.generate(&mut rng);
+        let loaded = key_store
+            .load(account.address(), &key_store.store(&account), 7)
+            .unwrap();
+        assert_eq!(loaded.address(), account.address());
+        assert_eq!(loaded.private_key_bytes(), account.private_key_bytes());
+    }
+
+    let account: LocalAccount = KeyStore::<LocalAccount>::generate(&Ed25519KeyStore, &mut rng);
+    let loaded: LocalAccount = Ed25519KeyStore
+        .load(account.address(), &Ed25519KeyStore.store(&account), 7)
+        .unwrap();
+    assert_eq!(loaded.public_key(), account.public_key());
+    assert_eq!(loaded.sequence_number(), 7);
+}
@@ -27,11 +27,17 @@ mod accounts_pool_wrapper;
 pub mod args;
 mod batch_transfer;
 mod call_custom_modules;
+mod composability_chain;
 mod entry_points;
+mod key_scheme_transfer;
+pub mod key_store;
 mod p2p_transaction_generator;
 pub mod publish_modules;
 mod publishing;
+mod resource_bloat;
+mod table_items;
 mod transaction_mix_generator;
+mod txn_type_tags;
 use self::{
     account_generator::AccountGeneratorCreator,
     call_custom_modules::CustomModulesDelegationGeneratorCreator,
@@ -42,9 +48,17 @@ use self::{
 use crate::{
     accounts_pool_wrapper::AccountsPoolWrapperCreator,
     batch_transfer::BatchTransferTransactionGeneratorCreator,
-    entry_points::EntryPointTransactionGenerator, p2p_transaction_generator::SamplingMode,
+    composability_chain::ComposabilityChainGeneratorCreator,
+    entry_points::EntryPointTransactionGenerator,
+    key_scheme_transfer::KeySchemeTransferGeneratorCreator,
+    p2p_transaction_generator::SamplingMode, resource_bloat::ResourceBloatTransactionGenerator,
+    table_items::TableItemsTransactionGenerator, txn_type_tags::TxnTypeTaggingCreator,
 };
-pub use publishing::module_simple::EntryPoints;
+pub use composability_chain::MAX_CHAIN_MODULES;
+pub use key_store::KeyScheme;
+pub use publishing::module_simple::{EntryPoints, PayloadEntropy};
+pub use table_items::TableKind;
+pub use txn_type_tags::TxnTypeTags;
 
 pub const SEND_AMOUNT: u64 = 1;
 
@@ -74,6 +88,100 @@ pub enum TransactionType {
     BatchTransfer {
         batch_size: usize,
     },
+    ResourceBloat {
+        bloat_percentage: usize,
+        entries_per_txn: u64,
+        num_modules: usize,
+        use_account_pool: bool,
+    },
+    TableItems {
+        kind: TableKind,
+        key_space_size: u64,
+        entries_per_txn: u64,
+        removal_percentage: u64,
+        num_modules: usize,
+        use_account_pool: bool,
+    },
+    KeySchemeTransfer {
+        key_scheme: KeyScheme,
+        num_signers: usize,
+    },
+    /// Calls `call_depth` frames deep into each of `num_modules` modules of a chain, every
+    /// module published by a different account.
+    ComposabilityChain {
+        num_modules: usize,
+        call_depth: u64,
+        num_chains: usize,
+        use_account_pool: bool,
+    },
+}
+
+impl TransactionType {
+    /// See `EntryPoints::with_payload_shape`.
+    pub fn with_payload_shape(
+        self,
+        data_length: Option<usize>,
+        entropy: Option<PayloadEntropy>,
+    ) -> Self {
+        match self {
+            TransactionType::CallCustomModules {
+                entry_point,
+                num_modules,
+                use_account_pool,
+            } => TransactionType::CallCustomModules {
+                entry_point: entry_point.with_payload_shape(data_length, entropy),
+                num_modules,
+                use_account_pool,
+            },
+            transaction_type => transaction_type,
+        }
+    }
+
+    /// Overrides the number of modules and the call depth (if given) of the composability chain
+    /// workloads, other transaction types are returned as is.
+    pub fn with_call_chain(self, num_modules: Option<usize>, call_depth: Option<u64>) -> Self {
+        match self {
+            TransactionType::ComposabilityChain {
+                num_modules: default_num_modules,
+                call_depth: default_call_depth,
+                num_chains,
+                use_account_pool,
+            } => TransactionType::ComposabilityChain {
+                num_modules: num_modules.unwrap_or(default_num_modules),
+                call_depth: call_depth.unwrap_or(default_call_depth),
+                num_chains,
+                use_account_pool,
+            },
+            transaction_type => transaction_type,
+        }
+    }
+
+    /// Overrides the key space size and the removal percentage (if given) of the table items
+    /// workloads, other transaction types are returned as is.
+    pub fn with_table_items(
+        self,
+        key_space_size: Option<u64>,
+        removal_percentage: Option<u64>,
+    ) -> Self {
+        match self {
+            TransactionType::TableItems {
+                kind,
+                key_space_size: default_key_space_size,
+                entries_per_txn,
+                removal_percentage: default_removal_percentage,
+                num_modules,
+                use_account_pool,
+            } => TransactionType::TableItems {
+                kind,
+                key_space_size: key_space_size.unwrap_or(default_key_space_size),
+                entries_per_txn,
+                removal_percentage: removal_percentage.unwrap_or(default_removal_percentage),
+                num_modules,
+                use_account_pool,
+            },
+            transaction_type => transaction_type,
+        }
+    }
 }
 
 impl Default for TransactionType {
@@ -82,6 +190,10 @@ impl Default for TransactionType {
     }
 }
 
+/// Accounts shared by the generators of a run: filled by account creation (or up front, with
+/// `initial_burner_accounts`), and drained by the generators that use every account only once.
+pub type AccountsPool = Arc<RwLock<Vec<LocalAccount>>>;
+
 pub trait TransactionGenerator: Sync + Send {
     fn generate_transactions(
         &mut self,
@@ -179,10 +291,11 @@ pub async fn create_txn_generator_creator(
     txn_factory: &TransactionFactory,
     init_txn_factory: &TransactionFactory,
     cur_phase: Arc<AtomicUsize>,
+    txn_type_tags: Option<Arc<TxnTypeTags>>,
 ) -> (
     Box<dyn TransactionGeneratorCreator>,
     Arc<RwLock<Vec<AccountAddress>>>,
-    Arc<RwLock<Vec<LocalAccount>>>,
+    AccountsPool,
 ) {
     let addresses_pool = Arc::new(RwLock::new(
         source_accounts
@@ -200,7 +313,7 @@ pub async fn create_txn_generator_creator(
     fn wrap_accounts_pool(
         inner: Box<dyn TransactionGeneratorCreator>,
         use_account_pool: bool,
-        accounts_pool: Arc<RwLock<Vec<LocalAccount>>>,
+        accounts_pool: AccountsPool,
     ) -> Box<dyn TransactionGeneratorCreator> {
         if use_account_pool {
             Box::new(AccountsPoolWrapperCreator::new(inner, accounts_pool))
@@ -212,7 +325,7 @@ pub async fn create_txn_generator_creator(
     for transaction_mix in transaction_mix_per_phase {
         let mut txn_generator_creator_mix: Vec<(Box<dyn TransactionGeneratorCreator>, usize)> =
             Vec::new();
-        for (transaction_type, weight) in transaction_mix {
+        for (type_index, (transaction_type, weight)) in transaction_mix.iter().enumerate() {
             let txn_generator_creator: Box<dyn TransactionGeneratorCreator> = match transaction_type
             {
                 TransactionType::NonConflictingCoinTransfer {
@@ -290,6 +403,102 @@ pub async fn create_txn_generator_creator(
                         *batch_size,
                     ))
                 },
+                TransactionType::ResourceBloat {
+                    bloat_percentage,
+                    entries_per_txn,
+                    num_modules,
+                    use_account_pool,
+                } => wrap_accounts_pool(
+                    Box::new(
+                        CustomModulesDelegationGeneratorCreator::new(
+                            txn_factory.clone(),
+                            init_txn_factory.clone(),
+                            source_accounts,
+                            txn_executor,
+                            *num_modules,
+                            "simple",
+                            &mut ResourceBloatTransactionGenerator {
+                                bloat_percentage: *bloat_percentage,
+                                entries_per_txn: *entries_per_txn,
+                            },
+                        )
+                        .await,
+                    ),
+                    *use_account_pool,
+                    accounts_pool.clone(),
+                ),
+                TransactionType::TableItems {
+                    kind,
+                    key_space_size,
+                    entries_per_txn,
+                    removal_percentage,
+                    num_modules,
+                    use_account_pool,
+                } => wrap_accounts_pool(
+                    Box::new(
+                        CustomModulesDelegationGeneratorCreator::new(
+                            txn_factory.clone(),
+                            init_txn_factory.clone(),
+                            source_accounts,
+                            txn_executor,
+                            *num_modules,
+                            kind.package_name(),
+                            &mut TableItemsTransactionGenerator {
+                                kind: *kind,
+                                key_space_size: *key_space_size,
+                                entries_per_txn: *entries_per_txn,
+                                removal_percentage: *removal_percentage,
+                            },
+                        )
+                        .await,
+                    ),
+                    *use_account_pool,
+                    accounts_pool.clone(),
+                ),
+                TransactionType::KeySchemeTransfer {
+                    key_scheme,
+                    num_signers,
+                } => Box::new(
+                    KeySchemeTransferGeneratorCreator::new(
+                        txn_factory.clone(),
+                        init_txn_factory.clone(),
+                        source_accounts,
+                        txn_executor,
+                        *key_scheme,
+                        *num_signers,
+                        SEND_AMOUNT,
+                    )
+                    .await,
+                ),
+                TransactionType::ComposabilityChain {
+                    num_modules,
+                    call_depth,
+                    num_chains,
+                    use_account_pool,
+                } => wrap_accounts_pool(
+                    Box::new(
+                        ComposabilityChainGeneratorCreator::new(
+                            txn_factory.clone(),
+                            init_txn_factory.clone(),
+                            source_accounts,
+                            txn_executor,
+                            *num_chains,
+                            *num_modules,
+                            *call_depth,
+                        )
+                        .await,
+                    ),
+                    *use_account_pool,
+                    accounts_pool.clone(),
+                ),
+            };
+            let txn_generator_creator: Box<dyn TransactionGeneratorCreator> = match &txn_type_tags {
+                Some(txn_type_tags) => Box::new(TxnTypeTaggingCreator::new(
+                    txn_generator_creator,
+                    type_index,
+                    txn_type_tags.clone(),
+                )),
+                None => txn_generator_creator,
             };
             txn_generator_creator_mix.push((txn_generator_creator, *weight));
         }
@@ -306,10 +515,7 @@ pub async fn create_txn_generator_creator(
     )
 }
 
-fn get_account_to_burn_from_pool(
-    accounts_pool: &Arc<RwLock<Vec<LocalAccount>>>,
-    needed: usize,
-) -> Vec<LocalAccount> {
+fn get_account_to_burn_from_pool(accounts_pool: &AccountsPool, needed: usize) -> Vec<LocalAccount> {
     let mut accounts_pool = accounts_pool.write();
     let num_in_pool = accounts_pool.len();
     if num_in_pool < needed {
@@ -11,6 +11,7 @@ use aptos_sdk::{
     },
     types::transaction::{EntryFunction, TransactionPayload},
 };
+use clap::ValueEnum;
 use move_binary_format::{
     file_format::{FunctionHandleIndex, IdentifierIndex, SignatureToken},
     CompiledModule,
@@ -92,6 +93,22 @@ pub enum MultiSigConfig {
     Publisher,
 }
 
+/// Length of the pattern `PayloadEntropy::Compressible` bytes repeat.
+const COMPRESSIBLE_PATTERN_LEN: usize = 16;
+
+/// How random the bytes arguments of the entry points are, so that serializing, hashing and
+/// compressing them costs what realistic payloads would. Without one, the bytes arguments are
+/// empty whatever their length, as they always were.
+#[derive(Debug, Copy, Clone, Eq, PartialEq, ValueEnum)]
+pub enum PayloadEntropy {
+    /// All zero bytes.
+    Zeros,
+    /// A short repeated pattern with some random bytes, compresses well.
+    Compressible,
+    /// Uniformly random bytes, incompressible.
+    Random,
+}
+
 //
 // List of entry points to expose
 //
@@ -139,9 +156,11 @@ pub enum EntryPoints {
     MakeOrChange {
         string_length: Option<usize>,
         data_length: Option<usize>,
+        entropy: Option<PayloadEntropy>,
     },
     BytesMakeOrChange {
         data_length: Option<usize>,
+        entropy: Option<PayloadEntropy>,
     },
     EmitEvents {
         count: u64,
@@ -154,6 +173,11 @@ pub enum EntryPoints {
         max_offset: u64,
         max_count: u64,
     },
+    /// Remove the table items at [offset, offset + count), the ones not set are skipped
+    RemoveFromTable {
+        offset: u64,
+        count: u64,
+    },
     /// Increment destination resource - COUNTER_STEP
     StepDst,
 
@@ -180,9 +204,42 @@ pub enum EntryPoints {
         length: u64,
         num_points_per_txn: usize,
     },
+    /// Remove random points from the picture, the ones not set are skipped
+    SmartTablePictureRemove {
+        length: u64,
+        num_points_per_txn: usize,
+    },
 }
 
 impl EntryPoints {
+    /// Overrides the length and the entropy (if given) of the bytes argument of the entry points
+    /// that take one, other entry points are returned as is.
+    pub fn with_payload_shape(
+        self,
+        data_length: Option<usize>,
+        entropy: Option<PayloadEntropy>,
+    ) -> Self {
+        match self {
+            EntryPoints::MakeOrChange {
+                string_length,
+                data_length: default_data_length,
+                entropy: default_entropy,
+            } => EntryPoints::MakeOrChange {
+                string_length,
+                data_length: data_length.or(default_data_length),
+                entropy: entropy.or(default_entropy),
+            },
+            EntryPoints::BytesMakeOrChange {
+                data_length: default_data_length,
+                entropy: default_entropy,
+            } => EntryPoints::BytesMakeOrChange {
+                data_length: data_length.or(default_data_length),
+                entropy: entropy.or(default_entropy),
+            },
+            entry_point => entry_point,
+        }
+    }
+
     pub fn package_name(&self) -> &'static str {
         match self {
             EntryPoints::Nop
@@ -204,6 +261,7 @@ impl EntryPoints {
             | EntryPoints::EmitEvents { .. }
             | EntryPoints::MakeOrChangeTable { .. }
             | EntryPoints::MakeOrChangeTableRandom { .. }
+            | EntryPoints::RemoveFromTable { .. }
             | EntryPoints::StepDst => "simple",
             EntryPoints::TokenV1InitializeCollection
             | EntryPoints::TokenV1MintAndStoreNFTParallel
@@ -216,7 +274,8 @@ impl EntryPoints {
             EntryPoints::InitializeVectorPicture { .. }
             | EntryPoints::VectorPicture { .. }
             | EntryPoints::InitializeSmartTablePicture
-            | EntryPoints::SmartTablePicture { .. } => "complex",
+            | EntryPoints::SmartTablePicture { .. }
+            | EntryPoints::SmartTablePictureRemove { .. } => "complex",
         }
     }
 
@@ -241,6 +300,7 @@ impl EntryPoints {
             | EntryPoints::EmitEvents { .. }
             | EntryPoints::MakeOrChangeTable { .. }
             | EntryPoints::MakeOrChangeTableRandom { .. }
+            | EntryPoints::RemoveFromTable { .. }
             | EntryPoints::StepDst => "simple",
             EntryPoints::TokenV1InitializeCollection
             | EntryPoints::TokenV1MintAndStoreNFTParallel
@@ -253,9 +313,9 @@ impl EntryPoints {
             EntryPoints::InitializeVectorPicture { .. } | EntryPoints::VectorPicture { .. } => {
                 "vector_picture"
             },
-            EntryPoints::InitializeSmartTablePicture | EntryPoints::SmartTablePicture { .. } => {
-                "smart_table_picture"
-            },
+            EntryPoints::InitializeSmartTablePicture
+            | EntryPoints::SmartTablePicture { .. }
+            | EntryPoints::SmartTablePictureRemove { .. } => "smart_table_picture",
         }
     }
 
@@ -305,16 +365,20 @@ impl EntryPoints {
             EntryPoints::MakeOrChange {
                 string_length,
                 data_length,
+                entropy,
             } => {
                 let rng = rng.expect("Must provide RNG");
                 let str_len = string_length.unwrap_or_else(|| rng.gen_range(0usize, 100usize));
                 let data_len = data_length.unwrap_or_else(|| rng.gen_range(0usize, 1000usize));
-                make_or_change(rng, module_id, str_len, data_len)
+                make_or_change(rng, module_id, str_len, data_len, *entropy)
             },
-            EntryPoints::BytesMakeOrChange { data_length } => {
+            EntryPoints::BytesMakeOrChange {
+                data_length,
+                entropy,
+            } => {
                 let rng = rng.expect("Must provide RNG");
                 let data_len = data_length.unwrap_or_else(|| rng.gen_range(0usize, 1000usize));
-                bytes_make_or_change(rng, module_id, data_len)
+                bytes_make_or_change(rng, module_id, data_len, *entropy)
             },
             EntryPoints::EmitEvents { count } => {
                 get_payload(module_id, ident_str!("emit_events").to_owned(), vec![
@@ -347,6 +411,12 @@ impl EntryPoints {
                     ],
                 )
             },
+            EntryPoints::RemoveFromTable { offset, count } => {
+                get_payload(module_id, ident_str!("remove_from_table").to_owned(), vec![
+                    bcs::to_bytes(offset).unwrap(),
+                    bcs::to_bytes(count).unwrap(),
+                ])
+            },
             EntryPoints::StepDst => step_dst(module_id, other.expect("Must provide other")),
             EntryPoints::TokenV1InitializeCollection => get_payload_void(
                 module_id,
@@ -434,6 +504,21 @@ impl EntryPoints {
                     bcs::to_bytes(&colors).unwrap(),  // colors
                 ])
             },
+            EntryPoints::SmartTablePictureRemove {
+                length,
+                num_points_per_txn,
+            } => {
+                let rng: &mut StdRng = rng.expect("Must provide RNG");
+                u32::try_from(*length).unwrap();
+                let indices = (0..*num_points_per_txn)
+                    .map(|_| rng.gen_range(0u64, length))
+                    .collect::<Vec<_>>();
+                get_payload(module_id, ident_str!("remove").to_owned(), vec![
+                    bcs::to_bytes(&other.expect("Must provide other")).unwrap(),
+                    bcs::to_bytes(&0u64).unwrap(),    // palette_index
+                    bcs::to_bytes(&indices).unwrap(), // indices
+                ])
+            },
         }
     }
 
@@ -450,7 +535,9 @@ impl EntryPoints {
             EntryPoints::VectorPicture { length } => {
                 Some(EntryPoints::InitializeVectorPicture { length: *length })
             },
-            EntryPoints::SmartTablePicture { .. } => Some(EntryPoints::InitializeSmartTablePicture),
+            EntryPoints::SmartTablePicture { .. } | EntryPoints::SmartTablePictureRemove { .. } => {
+                Some(EntryPoints::InitializeSmartTablePicture)
+            },
             _ => None,
         }
     }
@@ -504,8 +591,12 @@ const GEN_ENTRY_POINTS: &[EntryPoints; 12] = &[
     EntryPoints::MakeOrChange {
         string_length: None,
         data_length: None,
+        entropy: None,
+    },
+    EntryPoints::BytesMakeOrChange {
+        data_length: None,
+        entropy: None,
     },
-    EntryPoints::BytesMakeOrChange { data_length: None },
 ];
 
 pub fn rand_simple_function(rng: &mut StdRng, module_id: ModuleId) -> TransactionPayload {
@@ -598,11 +689,11 @@ fn make_or_change(
     module_id: ModuleId,
     str_len: usize,
     data_len: usize,
+    entropy: Option<PayloadEntropy>,
 ) -> TransactionPayload {
     let id: u64 = rng.gen();
     let name: String = rand_string(rng, str_len);
-    let mut bytes = Vec::<u8>::with_capacity(data_len);
-    rng.fill_bytes(&mut bytes);
+    let bytes = payload_bytes(rng, data_len, entropy);
     get_payload(module_id, ident_str!("make_or_change").to_owned(), vec![
         bcs::to_bytes(&id).unwrap(),
         bcs::to_bytes(&name).unwrap(),
@@ -614,9 +705,9 @@ fn bytes_make_or_change(
     rng: &mut StdRng,
     module_id: ModuleId,
     data_len: usize,
+    entropy: Option<PayloadEntropy>,
 ) -> TransactionPayload {
-    let mut bytes = Vec::<u8>::with_capacity(data_len);
-    rng.fill_bytes(&mut bytes);
+    let bytes = payload_bytes(rng, data_len, entropy);
     get_payload(
         module_id,
         ident_str!("bytes_make_or_change").to_owned(),
@@ -624,6 +715,33 @@ fn bytes_make_or_change(
     )
 }
 
+/// Bytes argument of `data_len` bytes, as compressible as `entropy` asks for, or empty without
+/// an entropy.
+fn payload_bytes(rng: &mut StdRng, data_len: usize, entropy: Option<PayloadEntropy>) -> Vec<u8> {
+    let entropy = match entropy {
+        Some(entropy) => entropy,
+        None => return vec![],
+    };
+    let mut bytes = vec![0u8; data_len];
+    match entropy {
+        PayloadEntropy::Zeros => (),
+        PayloadEntropy::Compressible => {
+            // A short random pattern, repeated, with a few random bytes sprinkled in.
+            let mut pattern = [0u8; COMPRESSIBLE_PATTERN_LEN];
+            rng.fill_bytes(&mut pattern);
+            for (i, byte) in bytes.iter_mut().enumerate() {
+                *byte = if rng.gen_ratio(1, 16) {
+                    rng.gen()
+                } else {
+                    pattern[i % COMPRESSIBLE_PATTERN_LEN]
+                };
+            }
+        },
+        PayloadEntropy::Random => rng.fill_bytes(&mut bytes),
+    }
+    bytes
+}
+
 fn get_payload_void(module_id: ModuleId, func: Identifier) -> TransactionPayload {
     get_payload(module_id, func, vec![])
 }
@@ -631,3 +749,19 @@ fn get_payload_void(module_id: ModuleId, func: Identifier) -> TransactionPayload
 fn get_payload(module_id: ModuleId, func: Identifier, args: Vec<Vec<u8>>) -> TransactionPayload {
     TransactionPayload::EntryFunction(EntryFunction::new(module_id, func, vec![], args))
 }
+
+#[test]
+fn test_payload_bytes_empty_without_entropy() {
+    use rand::SeedableRng;
+
+    let mut rng = StdRng::seed_from_u64(0);
+    assert!(payload_bytes(&mut rng, 32, None).is_empty());
+    assert_eq!(
+        payload_bytes(&mut rng, 32, Some(PayloadEntropy::Zeros)),
+        vec![0; 32]
+    );
+    assert_eq!(
+        payload_bytes(&mut rng, 32, Some(PayloadEntropy::Random)).len(),
+        32
+    );
+}
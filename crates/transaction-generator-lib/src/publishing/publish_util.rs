@@ -2,14 +2,18 @@
 // SPDX-License-Identifier: Apache-2.0
 
 use crate::publishing::{module_simple, raw_module_data};
-use aptos_framework::natives::code::PackageMetadata;
+use aptos_framework::natives::code::{PackageDep, PackageMetadata};
 use aptos_sdk::{
     bcs,
     move_types::{identifier::Identifier, language_storage::ModuleId},
     transaction_builder::{aptos_stdlib, TransactionFactory},
     types::{account_address::AccountAddress, transaction::SignedTransaction, LocalAccount},
 };
-use move_binary_format::{access::ModuleAccess, CompiledModule};
+use move_binary_format::{
+    access::ModuleAccess,
+    file_format::{AddressIdentifierIndex, TableIndex},
+    CompiledModule,
+};
 use rand::{rngs::StdRng, Rng};
 
 // Information used to track a publisher and what allows to identify and
@@ -147,6 +151,53 @@ impl Package {
         }
     }
 
+    // Return a package with only the given module, published by `publisher`, whose calls into
+    // the other modules in `dependencies` go to the accounts given for them. This allows
+    // modules of a single package to call into each other across publishers.
+    pub fn split_module(
+        &self,
+        module_name: &str,
+        publisher: AccountAddress,
+        dependencies: &[(String, AccountAddress)],
+    ) -> Self {
+        match self {
+            Self::Simple(modules, metadata) => {
+                let (_, module) = modules
+                    .iter()
+                    .find(|(name, _)| name == module_name)
+                    .unwrap_or_else(|| panic!("Module for {} not found", module_name));
+                let mut new_module = module.clone();
+                let original_address_idx =
+                    new_module.module_handles[module.self_handle_idx().0 as usize].address;
+                let _ = std::mem::replace(
+                    &mut new_module.address_identifiers[original_address_idx.0 as usize],
+                    publisher,
+                );
+                let mut metadata = metadata.clone();
+                metadata.modules.retain(|module| module.name == module_name);
+                for (dependency_name, dependency_publisher) in dependencies {
+                    let handle_idx = new_module.module_handles.iter().position(|handle| {
+                        handle.address == original_address_idx
+                            && new_module.identifiers[handle.name.0 as usize].as_str()
+                                == dependency_name
+                    });
+                    if let Some(handle_idx) = handle_idx {
+                        new_module.address_identifiers.push(*dependency_publisher);
+                        new_module.module_handles[handle_idx].address = AddressIdentifierIndex(
+                            (new_module.address_identifiers.len() - 1) as TableIndex,
+                        );
+                        // The dependency is published on its own, as the same package.
+                        metadata.deps.push(PackageDep {
+                            account: *dependency_publisher,
+                            package_name: metadata.name.clone(),
+                        });
+                    }
+                }
+                Self::Simple(vec![(module_name.to_string(), new_module)], metadata)
+            },
+        }
+    }
+
     // Change package "version"
     pub fn version(&mut self, rng: &mut StdRng) {
         module_simple::version(self.get_mut_module("simple"), rng)
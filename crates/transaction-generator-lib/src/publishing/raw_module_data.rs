@@ -19,10 +19,10 @@ use std::collections::HashMap;
 pub static PACKAGE_COMPLEX_METADATA: Lazy<Vec<u8>> = Lazy::new(|| {
 	vec![
 		7, 99, 111, 109, 112, 108, 101, 120, 1, 0, 0, 0, 0, 0, 0, 0, 0, 64,
-		49, 67, 53, 70, 53, 55, 70, 70, 50, 55, 65, 66, 54, 65, 50, 57, 68, 65,
-		51, 70, 49, 49, 48, 57, 68, 48, 49, 70, 55, 66, 69, 66, 70, 49, 48, 55,
-		69, 50, 65, 53, 69, 54, 57, 57, 51, 53, 52, 52, 53, 70, 55, 56, 52, 54,
-		52, 51, 54, 69, 51, 54, 51, 56, 69, 65, 131, 1, 31, 139, 8, 0, 0, 0,
+		66, 49, 69, 53, 56, 48, 55, 54, 67, 49, 48, 67, 50, 56, 57, 65, 52, 70,
+		52, 68, 50, 66, 55, 67, 51, 68, 49, 54, 57, 51, 49, 70, 51, 48, 50, 51,
+		50, 49, 70, 52, 53, 57, 55, 53, 67, 50, 69, 55, 48, 49, 66, 56, 70, 65,
+		65, 69, 53, 54, 57, 53, 48, 56, 54, 66, 131, 1, 31, 139, 8, 0, 0, 0,
 		0, 0, 2, 255, 77, 139, 65, 10, 131, 48, 20, 68, 247, 255, 20, 193, 77, 86,
 		141, 246, 0, 93, 116, 211, 75, 136, 139, 79, 156, 22, 49, 201, 15, 73, 177, 5,
 		241, 238, 70, 80, 17, 102, 49, 51, 188, 215, 70, 182, 35, 127, 208, 81, 96, 15,
@@ -46,66 +46,74 @@ pub static PACKAGE_COMPLEX_METADATA: Lazy<Vec<u8>> = Lazy::new(|| {
 #[rustfmt::skip]
 pub static MODULE_COMPLEX_SMART_TABLE_PICTURE: Lazy<Vec<u8>> = Lazy::new(|| {
 	vec![
-		161, 28, 235, 11, 6, 0, 0, 0, 12, 1, 0, 12, 2, 12, 20, 3, 32, 60,
-		4, 92, 8, 5, 100, 72, 7, 172, 1, 249, 1, 8, 165, 3, 64, 6, 229, 3,
-		20, 16, 249, 3, 255, 1, 10, 248, 5, 15, 12, 135, 6, 227, 1, 13, 234, 7,
+		161, 28, 235, 11, 6, 0, 0, 0, 12, 1, 0, 12, 2, 12, 20, 3, 32, 79,
+		4, 111, 12, 5, 123, 107, 7, 230, 1, 137, 2, 8, 239, 3, 64, 6, 175, 4,
+		20, 16, 195, 4, 255, 1, 10, 194, 6, 15, 12, 209, 6, 163, 2, 13, 244, 8,
 		4, 0, 0, 1, 1, 1, 2, 1, 3, 1, 4, 1, 5, 0, 6, 8, 0, 0,
-		7, 8, 0, 4, 13, 4, 2, 0, 0, 0, 0, 2, 14, 2, 0, 0, 8, 0,
-		1, 0, 0, 9, 0, 1, 0, 0, 10, 2, 1, 0, 3, 15, 0, 4, 0, 4,
-		16, 1, 6, 2, 7, 4, 2, 17, 4, 7, 0, 2, 18, 8, 9, 0, 1, 19,
-		10, 10, 0, 2, 20, 8, 4, 0, 5, 21, 13, 14, 1, 0, 4, 22, 15, 1,
-		2, 3, 2, 4, 5, 9, 10, 9, 12, 10, 5, 1, 6, 12, 0, 4, 5, 3,
-		10, 3, 10, 2, 4, 5, 8, 3, 12, 8, 1, 1, 5, 2, 14, 2, 1, 11,
-		2, 2, 9, 0, 9, 1, 1, 8, 3, 1, 6, 8, 3, 1, 12, 1, 3, 5,
-		2, 3, 14, 3, 7, 8, 1, 1, 2, 1, 6, 10, 9, 0, 1, 1, 3, 7,
-		11, 2, 2, 9, 0, 9, 1, 9, 0, 9, 1, 19, 115, 109, 97, 114, 116, 95,
-		116, 97, 98, 108, 101, 95, 112, 105, 99, 116, 117, 114, 101, 5, 101, 114, 114, 111,
-		114, 6, 111, 98, 106, 101, 99, 116, 6, 115, 105, 103, 110, 101, 114, 11, 115, 109,
-		97, 114, 116, 95, 116, 97, 98, 108, 101, 6, 118, 101, 99, 116, 111, 114, 11, 65,
-		108, 108, 80, 97, 108, 101, 116, 116, 101, 115, 7, 80, 97, 108, 101, 116, 116, 101,
-		6, 99, 114, 101, 97, 116, 101, 11, 105, 110, 105, 116, 95, 109, 111, 100, 117, 108,
-		101, 6, 117, 112, 100, 97, 116, 101, 3, 97, 108, 108, 6, 112, 105, 120, 101, 108,
-		115, 10, 83, 109, 97, 114, 116, 84, 97, 98, 108, 101, 14, 67, 111, 110, 115, 116,
-		114, 117, 99, 116, 111, 114, 82, 101, 102, 10, 97, 100, 100, 114, 101, 115, 115, 95,
-		111, 102, 3, 110, 101, 119, 13, 99, 114, 101, 97, 116, 101, 95, 111, 98, 106, 101,
-		99, 116, 15, 103, 101, 110, 101, 114, 97, 116, 101, 95, 115, 105, 103, 110, 101, 114,
-		16, 105, 110, 118, 97, 108, 105, 100, 95, 97, 114, 103, 117, 109, 101, 110, 116, 28,
-		97, 100, 100, 114, 101, 115, 115, 95, 102, 114, 111, 109, 95, 99, 111, 110, 115, 116,
-		114, 117, 99, 116, 111, 114, 95, 114, 101, 102, 8, 105, 115, 95, 101, 109, 112, 116,
-		121, 6, 117, 112, 115, 101, 114, 116, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0,
-		0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0,
-		0, 0, 171, 205, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0,
-		0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 1,
-		3, 8, 2, 0, 0, 0, 0, 0, 0, 0, 3, 8, 1, 0, 0, 0, 0, 0,
-		0, 0, 18, 97, 112, 116, 111, 115, 58, 58, 109, 101, 116, 97, 100, 97, 116, 97,
-		95, 118, 49, 234, 1, 2, 1, 0, 0, 0, 0, 0, 0, 0, 21, 69, 95, 73,
-		78, 68, 69, 88, 95, 79, 85, 84, 95, 79, 70, 95, 66, 79, 85, 78, 68, 83,
-		68, 84, 104, 101, 32, 99, 97, 108, 108, 101, 114, 32, 116, 114, 105, 101, 100, 32,
-		116, 111, 32, 109, 117, 116, 97, 116, 101, 32, 97, 110, 32, 105, 116, 101, 109, 32,
-		111, 117, 116, 115, 105, 100, 101, 32, 116, 104, 101, 32, 98, 111, 117, 110, 100, 115,
-		32, 111, 102, 32, 116, 104, 101, 32, 118, 101, 99, 116, 111, 114, 46, 2, 0, 0,
-		0, 0, 0, 0, 0, 23, 69, 95, 65, 76, 76, 95, 80, 65, 76, 69, 84, 84,
-		69, 83, 95, 78, 79, 84, 95, 73, 78, 73, 84, 63, 84, 104, 101, 32, 99, 97,
-		108, 108, 101, 114, 32, 116, 114, 105, 101, 100, 32, 116, 111, 32, 99, 114, 101, 97,
-		116, 101, 32, 112, 97, 108, 101, 116, 116, 101, 32, 102, 111, 114, 32, 110, 111, 110,
-		45, 105, 110, 105, 116, 105, 97, 108, 105, 122, 101, 100, 32, 97, 99, 99, 111, 117,
-		110, 116, 46, 1, 7, 80, 97, 108, 101, 116, 116, 101, 1, 3, 1, 24, 48, 120,
-		49, 58, 58, 111, 98, 106, 101, 99, 116, 58, 58, 79, 98, 106, 101, 99, 116, 71,
-		114, 111, 117, 112, 0, 0, 2, 1, 11, 10, 5, 1, 2, 1, 12, 11, 2, 2,
-		14, 2, 0, 1, 4, 1, 0, 3, 29, 11, 0, 17, 3, 12, 1, 56, 0, 18,
-		1, 12, 4, 10, 1, 17, 5, 12, 2, 14, 2, 17, 6, 12, 3, 14, 3, 11,
-		4, 45, 1, 10, 1, 41, 0, 4, 19, 5, 22, 7, 0, 17, 7, 39, 11, 1,
-		42, 0, 15, 0, 14, 2, 17, 8, 68, 4, 2, 1, 0, 0, 0, 1, 5, 11,
-		0, 64, 4, 0, 0, 0, 0, 0, 0, 0, 0, 18, 0, 45, 0, 2, 2, 1,
-		4, 2, 0, 1, 11, 67, 11, 0, 43, 0, 16, 0, 11, 1, 66, 4, 20, 42,
-		1, 12, 8, 14, 2, 65, 10, 14, 3, 65, 12, 33, 4, 15, 5, 19, 11, 8,
-		1, 7, 1, 39, 6, 0, 0, 0, 0, 0, 0, 0, 0, 12, 5, 14, 2, 65,
-		10, 12, 7, 10, 5, 10, 7, 35, 4, 64, 5, 29, 14, 2, 56, 1, 32, 4,
-		34, 5, 38, 11, 8, 1, 7, 1, 39, 13, 2, 69, 10, 76, 12, 6, 14, 3,
-		56, 2, 32, 4, 47, 5, 51, 11, 8, 1, 7, 1, 39, 13, 3, 69, 12, 12,
-		4, 10, 8, 15, 1, 11, 6, 11, 4, 56, 3, 11, 5, 6, 1, 0, 0, 0,
-		0, 0, 0, 0, 22, 12, 5, 5, 24, 11, 8, 1, 2, 0, 0, 1, 0, 0,
-			]
+		7, 8, 0, 4, 14, 4, 2, 0, 0, 0, 0, 2, 15, 2, 0, 0, 8, 0,
+		1, 0, 0, 9, 0, 1, 0, 0, 10, 2, 1, 0, 0, 11, 3, 1, 0, 3,
+		16, 0, 5, 0, 4, 17, 1, 7, 2, 7, 4, 2, 18, 5, 8, 0, 2, 19,
+		9, 10, 0, 1, 20, 11, 11, 0, 2, 21, 9, 5, 0, 5, 22, 13, 14, 1,
+		0, 4, 23, 15, 14, 2, 2, 0, 4, 10, 16, 17, 2, 3, 0, 4, 24, 20,
+		1, 2, 3, 2, 5, 6, 10, 11, 11, 6, 12, 6, 10, 19, 13, 6, 1, 6,
+		12, 0, 3, 5, 3, 10, 3, 4, 5, 3, 10, 3, 10, 2, 4, 5, 8, 3,
+		12, 8, 1, 1, 5, 2, 14, 2, 1, 11, 2, 2, 9, 0, 9, 1, 1, 8,
+		3, 1, 6, 8, 3, 1, 12, 1, 3, 2, 14, 7, 8, 1, 1, 6, 10, 9,
+		0, 1, 1, 2, 6, 11, 2, 2, 9, 0, 9, 1, 9, 0, 2, 7, 11, 2,
+		2, 9, 0, 9, 1, 9, 0, 1, 9, 1, 5, 2, 3, 14, 3, 7, 8, 1,
+		1, 2, 3, 7, 11, 2, 2, 9, 0, 9, 1, 9, 0, 9, 1, 19, 115, 109,
+		97, 114, 116, 95, 116, 97, 98, 108, 101, 95, 112, 105, 99, 116, 117, 114, 101, 5,
+		101, 114, 114, 111, 114, 6, 111, 98, 106, 101, 99, 116, 6, 115, 105, 103, 110, 101,
+		114, 11, 115, 109, 97, 114, 116, 95, 116, 97, 98, 108, 101, 6, 118, 101, 99, 116,
+		111, 114, 11, 65, 108, 108, 80, 97, 108, 101, 116, 116, 101, 115, 7, 80, 97, 108,
+		101, 116, 116, 101, 6, 99, 114, 101, 97, 116, 101, 11, 105, 110, 105, 116, 95, 109,
+		111, 100, 117, 108, 101, 6, 114, 101, 109, 111, 118, 101, 6, 117, 112, 100, 97, 116,
+		101, 3, 97, 108, 108, 6, 112, 105, 120, 101, 108, 115, 10, 83, 109, 97, 114, 116,
+		84, 97, 98, 108, 101, 14, 67, 111, 110, 115, 116, 114, 117, 99, 116, 111, 114, 82,
+		101, 102, 10, 97, 100, 100, 114, 101, 115, 115, 95, 111, 102, 3, 110, 101, 119, 13,
+		99, 114, 101, 97, 116, 101, 95, 111, 98, 106, 101, 99, 116, 15, 103, 101, 110, 101,
+		114, 97, 116, 101, 95, 115, 105, 103, 110, 101, 114, 16, 105, 110, 118, 97, 108, 105,
+		100, 95, 97, 114, 103, 117, 109, 101, 110, 116, 28, 97, 100, 100, 114, 101, 115, 115,
+		95, 102, 114, 111, 109, 95, 99, 111, 110, 115, 116, 114, 117, 99, 116, 111, 114, 95,
+		114, 101, 102, 8, 105, 115, 95, 101, 109, 112, 116, 121, 8, 99, 111, 110, 116, 97,
+		105, 110, 115, 6, 117, 112, 115, 101, 114, 116, 0, 0, 0, 0, 0, 0, 0, 0,
+		0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0,
+		0, 0, 0, 0, 171, 205, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0,
+		0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0,
+		0, 1, 3, 8, 2, 0, 0, 0, 0, 0, 0, 0, 3, 8, 1, 0, 0, 0,
+		0, 0, 0, 0, 18, 97, 112, 116, 111, 115, 58, 58, 109, 101, 116, 97, 100, 97,
+		116, 97, 95, 118, 49, 234, 1, 2, 1, 0, 0, 0, 0, 0, 0, 0, 21, 69,
+		95, 73, 78, 68, 69, 88, 95, 79, 85, 84, 95, 79, 70, 95, 66, 79, 85, 78,
+		68, 83, 68, 84, 104, 101, 32, 99, 97, 108, 108, 101, 114, 32, 116, 114, 105, 101,
+		100, 32, 116, 111, 32, 109, 117, 116, 97, 116, 101, 32, 97, 110, 32, 105, 116, 101,
+		109, 32, 111, 117, 116, 115, 105, 100, 101, 32, 116, 104, 101, 32, 98, 111, 117, 110,
+		100, 115, 32, 111, 102, 32, 116, 104, 101, 32, 118, 101, 99, 116, 111, 114, 46, 2,
+		0, 0, 0, 0, 0, 0, 0, 23, 69, 95, 65, 76, 76, 95, 80, 65, 76, 69,
+		84, 84, 69, 83, 95, 78, 79, 84, 95, 73, 78, 73, 84, 63, 84, 104, 101, 32,
+		99, 97, 108, 108, 101, 114, 32, 116, 114, 105, 101, 100, 32, 116, 111, 32, 99, 114,
+		101, 97, 116, 101, 32, 112, 97, 108, 101, 116, 116, 101, 32, 102, 111, 114, 32, 110,
+		111, 110, 45, 105, 110, 105, 116, 105, 97, 108, 105, 122, 101, 100, 32, 97, 99, 99,
+		111, 117, 110, 116, 46, 1, 7, 80, 97, 108, 101, 116, 116, 101, 1, 3, 1, 24,
+		48, 120, 49, 58, 58, 111, 98, 106, 101, 99, 116, 58, 58, 79, 98, 106, 101, 99,
+		116, 71, 114, 111, 117, 112, 0, 0, 2, 1, 12, 10, 5, 1, 2, 1, 13, 11,
+		2, 2, 14, 2, 0, 1, 4, 1, 0, 4, 29, 11, 0, 17, 4, 12, 1, 56,
+		0, 18, 1, 12, 4, 10, 1, 17, 6, 12, 2, 14, 2, 17, 7, 12, 3, 14,
+		3, 11, 4, 45, 1, 10, 1, 41, 0, 4, 19, 5, 22, 7, 0, 17, 8, 39,
+		11, 1, 42, 0, 15, 0, 14, 2, 17, 9, 68, 5, 2, 1, 0, 0, 0, 1,
+		5, 11, 0, 64, 5, 0, 0, 0, 0, 0, 0, 0, 0, 18, 0, 45, 0, 2,
+		2, 1, 4, 2, 0, 1, 12, 31, 11, 0, 43, 0, 16, 0, 11, 1, 66, 5,
+		20, 42, 1, 12, 4, 14, 2, 56, 1, 32, 4, 28, 5, 13, 13, 2, 69, 11,
+		76, 12, 3, 10, 4, 16, 1, 10, 3, 56, 2, 4, 27, 10, 4, 15, 1, 11,
+		3, 56, 3, 1, 5, 8, 11, 4, 1, 2, 3, 1, 4, 2, 0, 1, 18, 67,
+		11, 0, 43, 0, 16, 0, 11, 1, 66, 5, 20, 42, 1, 12, 8, 14, 2, 65,
+		11, 14, 3, 65, 19, 33, 4, 15, 5, 19, 11, 8, 1, 7, 1, 39, 6, 0,
+		0, 0, 0, 0, 0, 0, 0, 12, 5, 14, 2, 65, 11, 12, 7, 10, 5, 10,
+		7, 35, 4, 64, 5, 29, 14, 2, 56, 1, 32, 4, 34, 5, 38, 11, 8, 1,
+		7, 1, 39, 13, 2, 69, 11, 76, 12, 6, 14, 3, 56, 4, 32, 4, 47, 5,
+		51, 11, 8, 1, 7, 1, 39, 13, 3, 69, 19, 12, 4, 10, 8, 15, 1, 11,
+		6, 11, 4, 56, 5, 11, 5, 6, 1, 0, 0, 0, 0, 0, 0, 0, 22, 12,
+		5, 5, 24, 11, 8, 1, 2, 0, 0, 1, 0, 0,
+	]
 });
 
 #[rustfmt::skip]
@@ -172,15 +180,14 @@ pub static MODULES_COMPLEX: Lazy<Vec<Vec<u8>>> = Lazy::new(|| { vec![
 	MODULE_COMPLEX_SMART_TABLE_PICTURE.to_vec(),
 	MODULE_COMPLEX_VECTOR_PICTURE.to_vec(),
 ]});
-
 #[rustfmt::skip]
 pub static PACKAGE_SIMPLE_METADATA: Lazy<Vec<u8>> = Lazy::new(|| {
 	vec![
 		13, 71, 101, 110, 101, 114, 105, 99, 77, 111, 100, 117, 108, 101, 1, 0, 0, 0,
-		0, 0, 0, 0, 0, 64, 66, 51, 69, 68, 51, 54, 54, 50, 70, 54, 65, 65,
-		66, 52, 70, 65, 68, 49, 51, 51, 56, 70, 68, 48, 56, 54, 65, 68, 68, 52,
-		52, 67, 51, 55, 51, 70, 67, 65, 52, 48, 49, 52, 52, 52, 55, 57, 70, 66,
-		51, 50, 50, 70, 67, 68, 57, 48, 70, 65, 51, 57, 51, 49, 48, 69, 132, 1,
+		0, 0, 0, 0, 0, 64, 67, 53, 67, 54, 52, 49, 56, 56, 51, 48, 54, 55,
+		68, 70, 57, 57, 67, 65, 70, 55, 68, 52, 52, 57, 50, 70, 70, 66, 68, 50,
+		49, 69, 68, 56, 67, 55, 50, 50, 54, 65, 52, 69, 55, 50, 66, 56, 69, 57,
+		56, 69, 57, 57, 50, 70, 51, 49, 68, 56, 67, 52, 48, 53, 66, 54, 132, 1,
 		31, 139, 8, 0, 0, 0, 0, 0, 2, 255, 77, 139, 59, 14, 194, 48, 16, 68,
 		251, 61, 133, 229, 30, 135, 11, 80, 208, 64, 197, 9, 162, 20, 43, 123, 64, 86,
 		156, 93, 203, 134, 80, 32, 238, 142, 45, 1, 138, 102, 154, 249, 188, 49, 179, 159,
@@ -203,174 +210,185 @@ pub static PACKAGE_SIMPLE_METADATA: Lazy<Vec<u8>> = Lazy::new(|| {
 #[rustfmt::skip]
 pub static MODULE_SIMPLE_SIMPLE: Lazy<Vec<u8>> = Lazy::new(|| {
 	vec![
-		161, 28, 235, 11, 6, 0, 0, 0, 12, 1, 0, 14, 2, 14, 46, 3, 60, 151,
-		1, 4, 211, 1, 8, 5, 219, 1, 240, 1, 7, 203, 3, 168, 4, 8, 243, 7,
-		64, 6, 179, 8, 115, 16, 166, 9, 62, 10, 228, 9, 51, 12, 151, 10, 220, 12,
-		13, 243, 22, 16, 0, 0, 1, 1, 1, 2, 1, 3, 1, 4, 1, 5, 1, 6,
+		161, 28, 235, 11, 6, 0, 0, 0, 12, 1, 0, 14, 2, 14, 46, 3, 60, 170,
+		1, 4, 230, 1, 12, 5, 242, 1, 148, 2, 7, 134, 4, 202, 4, 8, 208, 8,
+		64, 6, 144, 9, 115, 16, 131, 10, 62, 10, 193, 10, 51, 12, 244, 10, 183, 13,
+		13, 171, 24, 16, 0, 0, 1, 1, 1, 2, 1, 3, 1, 4, 1, 5, 1, 6,
 		0, 7, 8, 0, 0, 8, 8, 0, 0, 9, 7, 0, 0, 10, 8, 0, 0, 11,
-		8, 0, 0, 12, 6, 0, 0, 13, 8, 0, 5, 24, 7, 0, 3, 40, 4, 1,
-		6, 1, 6, 45, 4, 2, 3, 1, 0, 1, 0, 14, 0, 1, 0, 0, 15, 2,
+		8, 0, 0, 12, 6, 0, 0, 13, 8, 0, 5, 24, 7, 0, 3, 41, 4, 1,
+		6, 1, 6, 46, 4, 2, 3, 1, 0, 1, 0, 14, 0, 1, 0, 0, 15, 2,
 		1, 0, 0, 16, 3, 4, 0, 0, 17, 5, 1, 0, 0, 18, 6, 1, 0, 0,
 		19, 5, 1, 0, 0, 20, 6, 1, 0, 0, 21, 5, 1, 0, 0, 22, 5, 1,
 		0, 0, 23, 6, 1, 0, 0, 25, 7, 1, 0, 0, 26, 8, 1, 0, 0, 27,
 		9, 1, 0, 0, 28, 9, 1, 0, 0, 29, 5, 1, 0, 0, 30, 10, 1, 0,
-		0, 31, 11, 1, 0, 0, 32, 5, 1, 0, 0, 33, 6, 1, 0, 0, 34, 12,
-		1, 0, 0, 35, 9, 1, 0, 0, 36, 5, 1, 0, 4, 46, 5, 16, 0, 5,
-		47, 19, 20, 0, 1, 48, 5, 23, 1, 6, 3, 49, 24, 1, 1, 6, 6, 50,
-		1, 29, 2, 3, 4, 6, 51, 30, 31, 2, 3, 2, 2, 52, 13, 13, 0, 24,
-		22, 25, 22, 26, 28, 27, 28, 2, 7, 10, 2, 6, 10, 2, 0, 2, 6, 12,
-		10, 2, 4, 6, 8, 4, 6, 8, 4, 6, 8, 1, 6, 8, 1, 1, 6, 3,
-		1, 6, 12, 2, 6, 12, 3, 4, 6, 12, 3, 8, 7, 10, 2, 3, 6, 12,
-		3, 3, 2, 6, 12, 5, 2, 6, 12, 6, 12, 5, 6, 12, 6, 12, 6, 12,
-		6, 12, 6, 12, 2, 6, 12, 8, 7, 1, 3, 1, 2, 2, 7, 8, 0, 8,
-		0, 1, 5, 3, 6, 3, 6, 3, 6, 3, 3, 3, 8, 4, 7, 8, 4, 1,
-		10, 2, 1, 8, 7, 2, 7, 8, 3, 5, 1, 8, 5, 1, 11, 8, 1, 9,
-		0, 2, 7, 11, 8, 1, 9, 0, 9, 0, 3, 10, 3, 10, 3, 3, 3, 8,
-		2, 7, 8, 4, 8, 4, 3, 5, 7, 11, 9, 2, 3, 3, 7, 3, 2, 3,
-		3, 1, 11, 9, 2, 9, 0, 9, 1, 3, 7, 11, 9, 2, 9, 0, 9, 1,
-		9, 0, 9, 1, 1, 7, 9, 1, 8, 1, 10, 2, 7, 8, 4, 10, 2, 3,
-		3, 8, 4, 7, 8, 4, 9, 3, 7, 8, 4, 3, 3, 3, 8, 4, 7, 8,
-		4, 6, 8, 4, 6, 8, 4, 2, 7, 8, 4, 8, 4, 2, 8, 4, 7, 8,
-		4, 3, 7, 8, 1, 7, 8, 1, 3, 1, 7, 8, 1, 6, 115, 105, 109, 112,
-		108, 101, 7, 97, 99, 99, 111, 117, 110, 116, 5, 101, 114, 114, 111, 114, 5, 101,
-		118, 101, 110, 116, 6, 115, 105, 103, 110, 101, 114, 6, 115, 116, 114, 105, 110, 103,
-		5, 116, 97, 98, 108, 101, 12, 66, 121, 116, 101, 82, 101, 115, 111, 117, 114, 99,
-		101, 7, 67, 111, 117, 110, 116, 101, 114, 4, 68, 97, 116, 97, 10, 69, 118, 101,
-		110, 116, 83, 116, 111, 114, 101, 8, 82, 101, 115, 111, 117, 114, 99, 101, 11, 83,
-		105, 109, 112, 108, 101, 69, 118, 101, 110, 116, 10, 84, 97, 98, 108, 101, 83, 116,
-		111, 114, 101, 11, 97, 112, 112, 101, 110, 100, 95, 100, 97, 116, 97, 20, 98, 121,
-		116, 101, 115, 95, 109, 97, 107, 101, 95, 111, 114, 95, 99, 104, 97, 110, 103, 101,
-		14, 99, 111, 112, 121, 95, 112, 97, 115, 116, 97, 95, 114, 101, 102, 6, 100, 111,
-		117, 98, 108, 101, 11, 101, 109, 105, 116, 95, 101, 118, 101, 110, 116, 115, 11, 103,
-		101, 116, 95, 99, 111, 117, 110, 116, 101, 114, 21, 103, 101, 116, 95, 102, 114, 111,
-		109, 95, 114, 97, 110, 100, 111, 109, 95, 99, 111, 110, 115, 116, 4, 104, 97, 108,
-		102, 11, 105, 110, 105, 116, 95, 109, 111, 100, 117, 108, 101, 5, 108, 111, 111, 112,
-		121, 6, 83, 116, 114, 105, 110, 103, 14, 109, 97, 107, 101, 95, 111, 114, 95, 99,
-		104, 97, 110, 103, 101, 20, 109, 97, 107, 101, 95, 111, 114, 95, 99, 104, 97, 110,
-		103, 101, 95, 116, 97, 98, 108, 101, 8, 109, 97, 120, 105, 109, 105, 122, 101, 8,
-		109, 105, 110, 105, 109, 105, 122, 101, 3, 110, 111, 112, 13, 110, 111, 112, 95, 50,
-		95, 115, 105, 103, 110, 101, 114, 115, 13, 110, 111, 112, 95, 53, 95, 115, 105, 103,
-		110, 101, 114, 115, 10, 114, 101, 115, 101, 116, 95, 100, 97, 116, 97, 6, 115, 101,
-		116, 95, 105, 100, 8, 115, 101, 116, 95, 110, 97, 109, 101, 16, 115, 116, 101, 112,
-		95, 100, 101, 115, 116, 105, 110, 97, 116, 105, 111, 110, 11, 115, 116, 101, 112, 95,
-		115, 105, 103, 110, 101, 114, 4, 100, 97, 116, 97, 5, 99, 111, 117, 110, 116, 13,
-		115, 105, 109, 112, 108, 101, 95, 101, 118, 101, 110, 116, 115, 11, 69, 118, 101, 110,
-		116, 72, 97, 110, 100, 108, 101, 2, 105, 100, 4, 110, 97, 109, 101, 8, 101, 118,
-		101, 110, 116, 95, 105, 100, 13, 116, 97, 98, 108, 101, 95, 101, 110, 116, 114, 105,
-		101, 115, 5, 84, 97, 98, 108, 101, 10, 97, 100, 100, 114, 101, 115, 115, 95, 111,
-		102, 4, 117, 116, 102, 56, 16, 110, 101, 119, 95, 101, 118, 101, 110, 116, 95, 104,
-		97, 110, 100, 108, 101, 10, 101, 109, 105, 116, 95, 101, 118, 101, 110, 116, 3, 110,
-		101, 119, 23, 98, 111, 114, 114, 111, 119, 95, 109, 117, 116, 95, 119, 105, 116, 104,
-		95, 100, 101, 102, 97, 117, 108, 116, 16, 105, 110, 118, 97, 108, 105, 100, 95, 97,
-		114, 103, 117, 109, 101, 110, 116, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0,
-		0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0,
-		0, 171, 205, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0,
-		0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 1, 3,
-		8, 1, 0, 0, 0, 0, 0, 0, 0, 10, 2, 9, 8, 1, 35, 69, 103, 137,
-		171, 205, 239, 10, 2, 6, 5, 104, 101, 108, 108, 111, 10, 3, 81, 10, 0, 0,
-		0, 0, 0, 0, 0, 0, 1, 0, 0, 0, 0, 0, 0, 0, 2, 0, 0, 0,
-		0, 0, 0, 0, 3, 0, 0, 0, 0, 0, 0, 0, 4, 0, 0, 0, 0, 0,
-		0, 0, 5, 0, 0, 0, 0, 0, 0, 0, 6, 0, 0, 0, 0, 0, 0, 0,
-		7, 0, 0, 0, 0, 0, 0, 0, 8, 0, 0, 0, 0, 0, 0, 0, 9, 0,
-		0, 0, 0, 0, 0, 0, 18, 97, 112, 116, 111, 115, 58, 58, 109, 101, 116, 97,
-		100, 97, 116, 97, 95, 118, 49, 42, 1, 1, 0, 0, 0, 0, 0, 0, 0, 29,
-		69, 67, 79, 85, 78, 84, 69, 82, 95, 82, 69, 83, 79, 85, 82, 67, 69, 95,
-		78, 79, 84, 95, 80, 82, 69, 83, 69, 78, 84, 0, 0, 0, 0, 2, 1, 37,
-		10, 2, 1, 2, 1, 38, 3, 2, 2, 1, 37, 10, 2, 3, 2, 1, 39, 11,
-		8, 1, 8, 5, 4, 2, 3, 41, 3, 42, 8, 7, 37, 8, 2, 5, 2, 1,
-		43, 3, 6, 2, 1, 44, 11, 9, 2, 3, 3, 0, 0, 0, 0, 13, 26, 10,
-		1, 65, 14, 12, 2, 10, 2, 6, 0, 0, 0, 0, 0, 0, 0, 0, 36, 4,
-		21, 5, 8, 10, 0, 10, 1, 10, 2, 6, 1, 0, 0, 0, 0, 0, 0, 0,
-		23, 66, 14, 20, 68, 14, 11, 2, 6, 1, 0, 0, 0, 0, 0, 0, 0, 23,
-		12, 2, 5, 3, 11, 1, 1, 11, 0, 1, 2, 1, 1, 4, 1, 0, 15, 20,
-		10, 0, 17, 22, 41, 0, 4, 13, 11, 0, 17, 22, 42, 0, 12, 2, 11, 1,
-		11, 2, 15, 0, 21, 5, 19, 11, 1, 18, 0, 12, 3, 11, 0, 11, 3, 45,
-		0, 2, 2, 0, 0, 0, 17, 103, 10, 0, 16, 1, 12, 5, 10, 1, 16, 1,
-		12, 6, 11, 5, 20, 10, 6, 20, 35, 4, 18, 11, 6, 12, 5, 10, 2, 16,
-		2, 12, 6, 5, 26, 11, 6, 1, 10, 1, 16, 1, 12, 5, 10, 3, 16, 2,
-		12, 6, 10, 6, 20, 10, 1, 16, 1, 20, 35, 4, 47, 11, 5, 1, 11, 1,
-		1, 11, 0, 1, 11, 2, 1, 11, 6, 12, 5, 11, 3, 16, 2, 12, 6, 5,
-		69, 11, 3, 1, 10, 5, 11, 0, 16, 1, 34, 4, 65, 11, 6, 1, 11, 5,
-		1, 11, 2, 16, 2, 12, 5, 11, 1, 16, 1, 12, 6, 5, 69, 11, 1, 1,
-		11, 2, 1, 10, 5, 20, 10, 6, 20, 35, 4, 82, 11, 6, 1, 10, 5, 12,
-		6, 10, 5, 1, 5, 88, 11, 5, 1, 10, 6, 12, 5, 10, 6, 1, 10, 5,
-		10, 6, 33, 4, 97, 11, 6, 1, 11, 5, 12, 4, 5, 101, 11, 5, 1, 11,
-		6, 12, 4, 11, 4, 2, 3, 1, 4, 1, 4, 18, 44, 10, 0, 17, 22, 41,
-		4, 32, 4, 16, 6, 0, 0, 0, 0, 0, 0, 0, 0, 7, 2, 17, 23, 7,
-		1, 18, 2, 18, 4, 12, 2, 11, 0, 11, 2, 45, 4, 5, 43, 11, 0, 17,
-		22, 42, 4, 12, 3, 10, 3, 16, 3, 16, 4, 65, 14, 6, 2, 0, 0, 0,
-		0, 0, 0, 0, 24, 12, 1, 10, 3, 16, 3, 16, 4, 65, 14, 10, 1, 35,
-		4, 41, 5, 35, 10, 3, 15, 3, 15, 4, 49, 255, 68, 14, 5, 27, 11, 3,
-		1, 2, 4, 0, 0, 1, 3, 21, 36, 10, 0, 17, 22, 12, 3, 10, 3, 41,
-		3, 32, 4, 13, 10, 0, 11, 0, 56, 0, 18, 3, 45, 3, 5, 15, 11, 0,
-		1, 11, 3, 42, 3, 12, 2, 10, 1, 6, 0, 0, 0, 0, 0, 0, 0, 0,
-		36, 4, 33, 5, 23, 11, 1, 6, 1, 0, 0, 0, 0, 0, 0, 0, 23, 12,
-		1, 10, 2, 15, 5, 10, 1, 18, 5, 56, 1, 5, 18, 11, 2, 1, 2, 5,
-		1, 4, 1, 1, 1, 7, 11, 0, 17, 22, 43, 1, 16, 2, 20, 1, 2, 6,
-		1, 4, 0, 25, 25, 7, 3, 12, 2, 14, 2, 65, 13, 12, 4, 10, 4, 6,
-		0, 0, 0, 0, 0, 0, 0, 0, 34, 4, 24, 10, 1, 10, 4, 38, 4, 17,
-		11, 4, 6, 1, 0, 0, 0, 0, 0, 0, 0, 23, 12, 1, 7, 3, 12, 3,
-		14, 3, 11, 1, 66, 13, 20, 1, 2, 7, 1, 4, 1, 4, 18, 44, 10, 0,
-		17, 22, 41, 4, 32, 4, 16, 6, 0, 0, 0, 0, 0, 0, 0, 0, 7, 2,
-		17, 23, 7, 1, 18, 2, 18, 4, 12, 2, 11, 0, 11, 2, 45, 4, 5, 43,
-		11, 0, 17, 22, 42, 4, 12, 3, 10, 3, 16, 3, 16, 4, 65, 14, 6, 2,
-		0, 0, 0, 0, 0, 0, 0, 26, 12, 1, 10, 3, 16, 3, 16, 4, 65, 14,
-		10, 1, 36, 4, 41, 5, 35, 10, 3, 15, 3, 15, 4, 69, 14, 1, 5, 27,
-		11, 3, 1, 2, 8, 0, 0, 0, 1, 5, 11, 0, 6, 0, 0, 0, 0, 0,
-		0, 0, 0, 18, 1, 45, 1, 2, 9, 1, 4, 0, 1, 11, 10, 1, 6, 0,
-		0, 0, 0, 0, 0, 0, 0, 36, 4, 10, 5, 5, 11, 1, 6, 1, 0, 0,
-		0, 0, 0, 0, 0, 23, 12, 1, 5, 0, 2, 10, 1, 4, 1, 4, 26, 34,
-		10, 0, 17, 22, 41, 4, 4, 22, 11, 0, 17, 22, 42, 4, 12, 5, 11, 1,
-		10, 5, 15, 1, 21, 11, 2, 10, 5, 15, 6, 21, 11, 3, 11, 5, 15, 3,
-		15, 4, 21, 5, 33, 11, 3, 18, 2, 12, 4, 11, 1, 11, 2, 11, 4, 18,
-		4, 12, 6, 11, 0, 11, 6, 45, 4, 2, 11, 0, 0, 1, 6, 27, 44, 10,
-		0, 17, 22, 12, 3, 10, 3, 41, 6, 32, 4, 12, 11, 0, 56, 2, 18, 6,
-		45, 6, 5, 14, 11, 0, 1, 11, 3, 42, 6, 15, 7, 12, 4, 10, 2, 6,
-		0, 0, 0, 0, 0, 0, 0, 0, 36, 4, 41, 5, 23, 11, 2, 6, 1, 0,
-		0, 0, 0, 0, 0, 0, 23, 12, 2, 10, 4, 10, 1, 10, 2, 22, 6, 0,
-		0, 0, 0, 0, 0, 0, 0, 56, 3, 12, 5, 10, 5, 20, 6, 1, 0, 0,
-		0, 0, 0, 0, 0, 22, 11, 5, 21, 5, 18, 11, 4, 1, 2, 12, 1, 4,
-		1, 4, 32, 93, 10, 1, 41, 4, 4, 6, 11, 0, 1, 2, 10, 0, 17, 22,
-		41, 4, 32, 4, 21, 6, 0, 0, 0, 0, 0, 0, 0, 0, 7, 2, 17, 23,
-		7, 1, 18, 2, 18, 4, 12, 8, 10, 0, 11, 8, 45, 4, 10, 0, 17, 22,
-		43, 4, 16, 3, 16, 4, 65, 14, 12, 6, 10, 1, 43, 4, 16, 3, 16, 4,
-		65, 14, 12, 7, 11, 6, 11, 7, 36, 4, 49, 11, 0, 17, 22, 43, 4, 16,
-		3, 16, 4, 20, 11, 1, 42, 4, 12, 4, 12, 3, 5, 59, 11, 1, 43, 4,
-		16, 3, 16, 4, 20, 11, 0, 17, 22, 42, 4, 12, 4, 12, 3, 11, 3, 11,
-		4, 12, 9, 12, 5, 14, 5, 65, 14, 10, 9, 16, 3, 16, 4, 65, 14, 36,
-		4, 75, 5, 72, 8, 12, 2, 5, 82, 10, 9, 16, 3, 16, 4, 65, 14, 6,
-		16, 39, 0, 0, 0, 0, 0, 0, 35, 12, 2, 11, 2, 4, 90, 10, 9, 15,
-		3, 15, 4, 14, 5, 17, 0, 5, 63, 11, 9, 1, 2, 13, 1, 4, 1, 4,
-		33, 81, 10, 1, 41, 4, 4, 6, 11, 0, 1, 2, 10, 0, 17, 22, 41, 4,
-		32, 4, 21, 6, 0, 0, 0, 0, 0, 0, 0, 0, 7, 2, 17, 23, 7, 1,
-		18, 2, 18, 4, 12, 7, 10, 0, 11, 7, 45, 4, 10, 0, 17, 22, 43, 4,
-		12, 9, 10, 1, 43, 4, 12, 10, 11, 9, 16, 3, 16, 4, 65, 14, 11, 10,
-		16, 3, 16, 4, 65, 14, 12, 5, 12, 4, 10, 4, 10, 5, 36, 4, 51, 11,
-		5, 6, 2, 0, 0, 0, 0, 0, 0, 0, 26, 11, 0, 17, 22, 42, 4, 12,
-		3, 12, 2, 5, 60, 11, 0, 1, 11, 4, 6, 2, 0, 0, 0, 0, 0, 0,
-		0, 26, 11, 1, 42, 4, 12, 3, 12, 2, 11, 2, 11, 3, 12, 8, 12, 6,
-		10, 8, 16, 3, 16, 4, 65, 14, 10, 6, 36, 4, 78, 5, 72, 10, 8, 15,
-		3, 15, 4, 69, 14, 1, 5, 64, 11, 8, 1, 2, 14, 1, 4, 0, 1, 1,
-		2, 15, 1, 4, 0, 1, 1, 2, 16, 1, 4, 0, 1, 1, 2, 17, 1, 4,
-		1, 4, 34, 34, 10, 0, 17, 22, 41, 4, 4, 23, 11, 0, 17, 22, 42, 4,
-		12, 1, 6, 0, 0, 0, 0, 0, 0, 0, 0, 10, 1, 15, 1, 21, 7, 2,
-		17, 23, 10, 1, 15, 6, 21, 7, 1, 11, 1, 15, 3, 15, 4, 21, 5, 33,
-		6, 0, 0, 0, 0, 0, 0, 0, 0, 7, 2, 17, 23, 7, 1, 18, 2, 18,
-		4, 12, 2, 11, 0, 11, 2, 45, 4, 2, 18, 1, 4, 1, 4, 35, 25, 10,
-		0, 17, 22, 41, 4, 32, 4, 16, 11, 1, 7, 2, 17, 23, 7, 1, 18, 2,
-		18, 4, 12, 2, 11, 0, 11, 2, 45, 4, 5, 24, 11, 0, 17, 22, 42, 4,
-		12, 3, 11, 1, 11, 3, 15, 1, 21, 2, 19, 1, 4, 1, 4, 35, 24, 10,
-		0, 17, 22, 41, 4, 32, 4, 15, 6, 0, 0, 0, 0, 0, 0, 0, 0, 11,
-		1, 7, 1, 18, 2, 18, 4, 12, 2, 11, 0, 11, 2, 45, 4, 5, 23, 11,
-		0, 17, 22, 42, 4, 12, 3, 11, 1, 11, 3, 15, 6, 21, 2, 20, 1, 4,
-		1, 1, 36, 42, 10, 1, 41, 1, 4, 4, 5, 9, 11, 0, 1, 7, 0, 17,
-		28, 39, 11, 1, 42, 1, 12, 2, 10, 2, 16, 2, 20, 7, 0, 22, 10, 2,
-		15, 2, 21, 11, 2, 16, 2, 20, 12, 4, 10, 0, 17, 22, 41, 1, 4, 37,
-		11, 0, 17, 22, 42, 1, 12, 3, 11, 4, 11, 3, 15, 2, 21, 5, 41, 11,
-		0, 11, 4, 18, 1, 45, 1, 2, 21, 1, 4, 1, 1, 37, 13, 11, 0, 17,
-		22, 42, 1, 12, 1, 10, 1, 16, 2, 20, 7, 0, 22, 11, 1, 15, 2, 21,
-		2, 0, 0, 4, 0, 1, 0, 4, 2, 2, 0, 3, 0, 4, 1, 6, 0, 0,
-			]
+		0, 31, 11, 1, 0, 0, 32, 8, 1, 0, 0, 33, 5, 1, 0, 0, 34, 6,
+		1, 0, 0, 35, 12, 1, 0, 0, 36, 9, 1, 0, 0, 37, 5, 1, 0, 4,
+		47, 5, 16, 0, 5, 48, 19, 20, 0, 1, 49, 5, 23, 1, 6, 3, 50, 24,
+		1, 1, 6, 6, 51, 1, 29, 2, 3, 4, 6, 52, 30, 31, 2, 3, 2, 6,
+		53, 35, 36, 2, 3, 0, 6, 54, 37, 38, 2, 3, 0, 2, 55, 13, 13, 0,
+		25, 22, 26, 22, 27, 28, 28, 28, 29, 28, 30, 28, 2, 7, 10, 2, 6, 10,
+		2, 0, 2, 6, 12, 10, 2, 4, 6, 8, 4, 6, 8, 4, 6, 8, 1, 6,
+		8, 1, 1, 6, 3, 1, 6, 12, 2, 6, 12, 3, 4, 6, 12, 3, 8, 7,
+		10, 2, 3, 6, 12, 3, 3, 2, 6, 12, 5, 2, 6, 12, 6, 12, 5, 6,
+		12, 6, 12, 6, 12, 6, 12, 6, 12, 2, 6, 12, 8, 7, 1, 3, 1, 2,
+		2, 7, 8, 0, 8, 0, 1, 5, 3, 6, 3, 6, 3, 6, 3, 3, 3, 8,
+		4, 7, 8, 4, 1, 10, 2, 1, 8, 7, 2, 7, 8, 3, 5, 1, 8, 5,
+		1, 11, 8, 1, 9, 0, 2, 7, 11, 8, 1, 9, 0, 9, 0, 3, 10, 3,
+		10, 3, 3, 3, 8, 2, 7, 8, 4, 8, 4, 3, 5, 7, 11, 9, 2, 3,
+		3, 7, 3, 2, 3, 3, 1, 11, 9, 2, 9, 0, 9, 1, 3, 7, 11, 9,
+		2, 9, 0, 9, 1, 9, 0, 9, 1, 1, 7, 9, 1, 8, 1, 10, 2, 7,
+		8, 4, 10, 2, 3, 3, 8, 4, 7, 8, 4, 9, 3, 7, 8, 4, 3, 3,
+		3, 8, 4, 7, 8, 4, 6, 8, 4, 6, 8, 4, 3, 3, 5, 7, 11, 9,
+		2, 3, 3, 2, 6, 11, 9, 2, 9, 0, 9, 1, 9, 0, 1, 1, 2, 7,
+		11, 9, 2, 9, 0, 9, 1, 9, 0, 1, 9, 1, 2, 7, 8, 4, 8, 4,
+		2, 8, 4, 7, 8, 4, 3, 7, 8, 1, 7, 8, 1, 3, 1, 7, 8, 1,
+		6, 115, 105, 109, 112, 108, 101, 7, 97, 99, 99, 111, 117, 110, 116, 5, 101, 114,
+		114, 111, 114, 5, 101, 118, 101, 110, 116, 6, 115, 105, 103, 110, 101, 114, 6, 115,
+		116, 114, 105, 110, 103, 5, 116, 97, 98, 108, 101, 12, 66, 121, 116, 101, 82, 101,
+		115, 111, 117, 114, 99, 101, 7, 67, 111, 117, 110, 116, 101, 114, 4, 68, 97, 116,
+		97, 10, 69, 118, 101, 110, 116, 83, 116, 111, 114, 101, 8, 82, 101, 115, 111, 117,
+		114, 99, 101, 11, 83, 105, 109, 112, 108, 101, 69, 118, 101, 110, 116, 10, 84, 97,
+		98, 108, 101, 83, 116, 111, 114, 101, 11, 97, 112, 112, 101, 110, 100, 95, 100, 97,
+		116, 97, 20, 98, 121, 116, 101, 115, 95, 109, 97, 107, 101, 95, 111, 114, 95, 99,
+		104, 97, 110, 103, 101, 14, 99, 111, 112, 121, 95, 112, 97, 115, 116, 97, 95, 114,
+		101, 102, 6, 100, 111, 117, 98, 108, 101, 11, 101, 109, 105, 116, 95, 101, 118, 101,
+		110, 116, 115, 11, 103, 101, 116, 95, 99, 111, 117, 110, 116, 101, 114, 21, 103, 101,
+		116, 95, 102, 114, 111, 109, 95, 114, 97, 110, 100, 111, 109, 95, 99, 111, 110, 115,
+		116, 4, 104, 97, 108, 102, 11, 105, 110, 105, 116, 95, 109, 111, 100, 117, 108, 101,
+		5, 108, 111, 111, 112, 121, 6, 83, 116, 114, 105, 110, 103, 14, 109, 97, 107, 101,
+		95, 111, 114, 95, 99, 104, 97, 110, 103, 101, 20, 109, 97, 107, 101, 95, 111, 114,
+		95, 99, 104, 97, 110, 103, 101, 95, 116, 97, 98, 108, 101, 8, 109, 97, 120, 105,
+		109, 105, 122, 101, 8, 109, 105, 110, 105, 109, 105, 122, 101, 3, 110, 111, 112, 13,
+		110, 111, 112, 95, 50, 95, 115, 105, 103, 110, 101, 114, 115, 13, 110, 111, 112, 95,
+		53, 95, 115, 105, 103, 110, 101, 114, 115, 17, 114, 101, 109, 111, 118, 101, 95, 102,
+		114, 111, 109, 95, 116, 97, 98, 108, 101, 10, 114, 101, 115, 101, 116, 95, 100, 97,
+		116, 97, 6, 115, 101, 116, 95, 105, 100, 8, 115, 101, 116, 95, 110, 97, 109, 101,
+		16, 115, 116, 101, 112, 95, 100, 101, 115, 116, 105, 110, 97, 116, 105, 111, 110, 11,
+		115, 116, 101, 112, 95, 115, 105, 103, 110, 101, 114, 4, 100, 97, 116, 97, 5, 99,
+		111, 117, 110, 116, 13, 115, 105, 109, 112, 108, 101, 95, 101, 118, 101, 110, 116, 115,
+		11, 69, 118, 101, 110, 116, 72, 97, 110, 100, 108, 101, 2, 105, 100, 4, 110, 97,
+		109, 101, 8, 101, 118, 101, 110, 116, 95, 105, 100, 13, 116, 97, 98, 108, 101, 95,
+		101, 110, 116, 114, 105, 101, 115, 5, 84, 97, 98, 108, 101, 10, 97, 100, 100, 114,
+		101, 115, 115, 95, 111, 102, 4, 117, 116, 102, 56, 16, 110, 101, 119, 95, 101, 118,
+		101, 110, 116, 95, 104, 97, 110, 100, 108, 101, 10, 101, 109, 105, 116, 95, 101, 118,
+		101, 110, 116, 3, 110, 101, 119, 23, 98, 111, 114, 114, 111, 119, 95, 109, 117, 116,
+		95, 119, 105, 116, 104, 95, 100, 101, 102, 97, 117, 108, 116, 8, 99, 111, 110, 116,
+		97, 105, 110, 115, 6, 114, 101, 109, 111, 118, 101, 16, 105, 110, 118, 97, 108, 105,
+		100, 95, 97, 114, 103, 117, 109, 101, 110, 116, 0, 0, 0, 0, 0, 0, 0, 0,
+		0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0,
+		0, 0, 0, 0, 171, 205, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0,
+		0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0,
+		0, 1, 3, 8, 1, 0, 0, 0, 0, 0, 0, 0, 10, 2, 9, 8, 1, 35,
+		69, 103, 137, 171, 205, 239, 10, 2, 6, 5, 104, 101, 108, 108, 111, 10, 3, 81,
+		10, 0, 0, 0, 0, 0, 0, 0, 0, 1, 0, 0, 0, 0, 0, 0, 0, 2,
+		0, 0, 0, 0, 0, 0, 0, 3, 0, 0, 0, 0, 0, 0, 0, 4, 0, 0,
+		0, 0, 0, 0, 0, 5, 0, 0, 0, 0, 0, 0, 0, 6, 0, 0, 0, 0,
+		0, 0, 0, 7, 0, 0, 0, 0, 0, 0, 0, 8, 0, 0, 0, 0, 0, 0,
+		0, 9, 0, 0, 0, 0, 0, 0, 0, 18, 97, 112, 116, 111, 115, 58, 58, 109,
+		101, 116, 97, 100, 97, 116, 97, 95, 118, 49, 42, 1, 1, 0, 0, 0, 0, 0,
+		0, 0, 29, 69, 67, 79, 85, 78, 84, 69, 82, 95, 82, 69, 83, 79, 85, 82,
+		67, 69, 95, 78, 79, 84, 95, 80, 82, 69, 83, 69, 78, 84, 0, 0, 0, 0,
+		2, 1, 38, 10, 2, 1, 2, 1, 39, 3, 2, 2, 1, 38, 10, 2, 3, 2,
+		1, 40, 11, 8, 1, 8, 5, 4, 2, 3, 42, 3, 43, 8, 7, 38, 8, 2,
+		5, 2, 1, 44, 3, 6, 2, 1, 45, 11, 9, 2, 3, 3, 0, 0, 0, 0,
+		13, 26, 10, 1, 65, 14, 12, 2, 10, 2, 6, 0, 0, 0, 0, 0, 0, 0,
+		0, 36, 4, 21, 5, 8, 10, 0, 10, 1, 10, 2, 6, 1, 0, 0, 0, 0,
+		0, 0, 0, 23, 66, 14, 20, 68, 14, 11, 2, 6, 1, 0, 0, 0, 0, 0,
+		0, 0, 23, 12, 2, 5, 3, 11, 1, 1, 11, 0, 1, 2, 1, 1, 4, 1,
+		0, 15, 20, 10, 0, 17, 23, 41, 0, 4, 13, 11, 0, 17, 23, 42, 0, 12,
+		2, 11, 1, 11, 2, 15, 0, 21, 5, 19, 11, 1, 18, 0, 12, 3, 11, 0,
+		11, 3, 45, 0, 2, 2, 0, 0, 0, 17, 103, 10, 0, 16, 1, 12, 5, 10,
+		1, 16, 1, 12, 6, 11, 5, 20, 10, 6, 20, 35, 4, 18, 11, 6, 12, 5,
+		10, 2, 16, 2, 12, 6, 5, 26, 11, 6, 1, 10, 1, 16, 1, 12, 5, 10,
+		3, 16, 2, 12, 6, 10, 6, 20, 10, 1, 16, 1, 20, 35, 4, 47, 11, 5,
+		1, 11, 1, 1, 11, 0, 1, 11, 2, 1, 11, 6, 12, 5, 11, 3, 16, 2,
+		12, 6, 5, 69, 11, 3, 1, 10, 5, 11, 0, 16, 1, 34, 4, 65, 11, 6,
+		1, 11, 5, 1, 11, 2, 16, 2, 12, 5, 11, 1, 16, 1, 12, 6, 5, 69,
+		11, 1, 1, 11, 2, 1, 10, 5, 20, 10, 6, 20, 35, 4, 82, 11, 6, 1,
+		10, 5, 12, 6, 10, 5, 1, 5, 88, 11, 5, 1, 10, 6, 12, 5, 10, 6,
+		1, 10, 5, 10, 6, 33, 4, 97, 11, 6, 1, 11, 5, 12, 4, 5, 101, 11,
+		5, 1, 11, 6, 12, 4, 11, 4, 2, 3, 1, 4, 1, 4, 18, 44, 10, 0,
+		17, 23, 41, 4, 32, 4, 16, 6, 0, 0, 0, 0, 0, 0, 0, 0, 7, 2,
+		17, 24, 7, 1, 18, 2, 18, 4, 12, 2, 11, 0, 11, 2, 45, 4, 5, 43,
+		11, 0, 17, 23, 42, 4, 12, 3, 10, 3, 16, 3, 16, 4, 65, 14, 6, 2,
+		0, 0, 0, 0, 0, 0, 0, 24, 12, 1, 10, 3, 16, 3, 16, 4, 65, 14,
+		10, 1, 35, 4, 41, 5, 35, 10, 3, 15, 3, 15, 4, 49, 255, 68, 14, 5,
+		27, 11, 3, 1, 2, 4, 0, 0, 1, 3, 21, 36, 10, 0, 17, 23, 12, 3,
+		10, 3, 41, 3, 32, 4, 13, 10, 0, 11, 0, 56, 0, 18, 3, 45, 3, 5,
+		15, 11, 0, 1, 11, 3, 42, 3, 12, 2, 10, 1, 6, 0, 0, 0, 0, 0,
+		0, 0, 0, 36, 4, 33, 5, 23, 11, 1, 6, 1, 0, 0, 0, 0, 0, 0,
+		0, 23, 12, 1, 10, 2, 15, 5, 10, 1, 18, 5, 56, 1, 5, 18, 11, 2,
+		1, 2, 5, 1, 4, 1, 1, 1, 7, 11, 0, 17, 23, 43, 1, 16, 2, 20,
+		1, 2, 6, 1, 4, 0, 25, 25, 7, 3, 12, 2, 14, 2, 65, 13, 12, 4,
+		10, 4, 6, 0, 0, 0, 0, 0, 0, 0, 0, 34, 4, 24, 10, 1, 10, 4,
+		38, 4, 17, 11, 4, 6, 1, 0, 0, 0, 0, 0, 0, 0, 23, 12, 1, 7,
+		3, 12, 3, 14, 3, 11, 1, 66, 13, 20, 1, 2, 7, 1, 4, 1, 4, 18,
+		44, 10, 0, 17, 23, 41, 4, 32, 4, 16, 6, 0, 0, 0, 0, 0, 0, 0,
+		0, 7, 2, 17, 24, 7, 1, 18, 2, 18, 4, 12, 2, 11, 0, 11, 2, 45,
+		4, 5, 43, 11, 0, 17, 23, 42, 4, 12, 3, 10, 3, 16, 3, 16, 4, 65,
+		14, 6, 2, 0, 0, 0, 0, 0, 0, 0, 26, 12, 1, 10, 3, 16, 3, 16,
+		4, 65, 14, 10, 1, 36, 4, 41, 5, 35, 10, 3, 15, 3, 15, 4, 69, 14,
+		1, 5, 27, 11, 3, 1, 2, 8, 0, 0, 0, 1, 5, 11, 0, 6, 0, 0,
+		0, 0, 0, 0, 0, 0, 18, 1, 45, 1, 2, 9, 1, 4, 0, 1, 11, 10,
+		1, 6, 0, 0, 0, 0, 0, 0, 0, 0, 36, 4, 10, 5, 5, 11, 1, 6,
+		1, 0, 0, 0, 0, 0, 0, 0, 23, 12, 1, 5, 0, 2, 10, 1, 4, 1,
+		4, 26, 34, 10, 0, 17, 23, 41, 4, 4, 22, 11, 0, 17, 23, 42, 4, 12,
+		5, 11, 1, 10, 5, 15, 1, 21, 11, 2, 10, 5, 15, 6, 21, 11, 3, 11,
+		5, 15, 3, 15, 4, 21, 5, 33, 11, 3, 18, 2, 12, 4, 11, 1, 11, 2,
+		11, 4, 18, 4, 12, 6, 11, 0, 11, 6, 45, 4, 2, 11, 1, 4, 1, 6,
+		27, 44, 10, 0, 17, 23, 12, 3, 10, 3, 41, 6, 32, 4, 12, 11, 0, 56,
+		2, 18, 6, 45, 6, 5, 14, 11, 0, 1, 11, 3, 42, 6, 15, 7, 12, 4,
+		10, 2, 6, 0, 0, 0, 0, 0, 0, 0, 0, 36, 4, 41, 5, 23, 11, 2,
+		6, 1, 0, 0, 0, 0, 0, 0, 0, 23, 12, 2, 10, 4, 10, 1, 10, 2,
+		22, 6, 0, 0, 0, 0, 0, 0, 0, 0, 56, 3, 12, 5, 10, 5, 20, 6,
+		1, 0, 0, 0, 0, 0, 0, 0, 22, 11, 5, 21, 5, 18, 11, 4, 1, 2,
+		12, 1, 4, 1, 4, 32, 93, 10, 1, 41, 4, 4, 6, 11, 0, 1, 2, 10,
+		0, 17, 23, 41, 4, 32, 4, 21, 6, 0, 0, 0, 0, 0, 0, 0, 0, 7,
+		2, 17, 24, 7, 1, 18, 2, 18, 4, 12, 8, 10, 0, 11, 8, 45, 4, 10,
+		0, 17, 23, 43, 4, 16, 3, 16, 4, 65, 14, 12, 6, 10, 1, 43, 4, 16,
+		3, 16, 4, 65, 14, 12, 7, 11, 6, 11, 7, 36, 4, 49, 11, 0, 17, 23,
+		43, 4, 16, 3, 16, 4, 20, 11, 1, 42, 4, 12, 4, 12, 3, 5, 59, 11,
+		1, 43, 4, 16, 3, 16, 4, 20, 11, 0, 17, 23, 42, 4, 12, 4, 12, 3,
+		11, 3, 11, 4, 12, 9, 12, 5, 14, 5, 65, 14, 10, 9, 16, 3, 16, 4,
+		65, 14, 36, 4, 75, 5, 72, 8, 12, 2, 5, 82, 10, 9, 16, 3, 16, 4,
+		65, 14, 6, 16, 39, 0, 0, 0, 0, 0, 0, 35, 12, 2, 11, 2, 4, 90,
+		10, 9, 15, 3, 15, 4, 14, 5, 17, 0, 5, 63, 11, 9, 1, 2, 13, 1,
+		4, 1, 4, 33, 81, 10, 1, 41, 4, 4, 6, 11, 0, 1, 2, 10, 0, 17,
+		23, 41, 4, 32, 4, 21, 6, 0, 0, 0, 0, 0, 0, 0, 0, 7, 2, 17,
+		24, 7, 1, 18, 2, 18, 4, 12, 7, 10, 0, 11, 7, 45, 4, 10, 0, 17,
+		23, 43, 4, 12, 9, 10, 1, 43, 4, 12, 10, 11, 9, 16, 3, 16, 4, 65,
+		14, 11, 10, 16, 3, 16, 4, 65, 14, 12, 5, 12, 4, 10, 4, 10, 5, 36,
+		4, 51, 11, 5, 6, 2, 0, 0, 0, 0, 0, 0, 0, 26, 11, 0, 17, 23,
+		42, 4, 12, 3, 12, 2, 5, 60, 11, 0, 1, 11, 4, 6, 2, 0, 0, 0,
+		0, 0, 0, 0, 26, 11, 1, 42, 4, 12, 3, 12, 2, 11, 2, 11, 3, 12,
+		8, 12, 6, 10, 8, 16, 3, 16, 4, 65, 14, 10, 6, 36, 4, 78, 5, 72,
+		10, 8, 15, 3, 15, 4, 69, 14, 1, 5, 64, 11, 8, 1, 2, 14, 1, 4,
+		0, 1, 1, 2, 15, 1, 4, 0, 1, 1, 2, 16, 1, 4, 0, 1, 1, 2,
+		17, 1, 4, 1, 6, 34, 40, 11, 0, 17, 23, 12, 4, 10, 4, 41, 6, 32,
+		4, 8, 2, 11, 4, 42, 6, 15, 7, 12, 5, 10, 2, 6, 0, 0, 0, 0,
+		0, 0, 0, 0, 36, 4, 37, 5, 17, 11, 2, 6, 1, 0, 0, 0, 0, 0,
+		0, 0, 23, 12, 2, 10, 5, 10, 1, 10, 2, 22, 12, 3, 46, 11, 3, 56,
+		4, 4, 36, 10, 5, 10, 1, 10, 2, 22, 56, 5, 1, 5, 12, 11, 5, 1,
+		2, 18, 1, 4, 1, 4, 39, 34, 10, 0, 17, 23, 41, 4, 4, 23, 11, 0,
+		17, 23, 42, 4, 12, 1, 6, 0, 0, 0, 0, 0, 0, 0, 0, 10, 1, 15,
+		1, 21, 7, 2, 17, 24, 10, 1, 15, 6, 21, 7, 1, 11, 1, 15, 3, 15,
+		4, 21, 5, 33, 6, 0, 0, 0, 0, 0, 0, 0, 0, 7, 2, 17, 24, 7,
+		1, 18, 2, 18, 4, 12, 2, 11, 0, 11, 2, 45, 4, 2, 19, 1, 4, 1,
+		4, 40, 25, 10, 0, 17, 23, 41, 4, 32, 4, 16, 11, 1, 7, 2, 17, 24,
+		7, 1, 18, 2, 18, 4, 12, 2, 11, 0, 11, 2, 45, 4, 5, 24, 11, 0,
+		17, 23, 42, 4, 12, 3, 11, 1, 11, 3, 15, 1, 21, 2, 20, 1, 4, 1,
+		4, 40, 24, 10, 0, 17, 23, 41, 4, 32, 4, 15, 6, 0, 0, 0, 0, 0,
+		0, 0, 0, 11, 1, 7, 1, 18, 2, 18, 4, 12, 2, 11, 0, 11, 2, 45,
+		4, 5, 23, 11, 0, 17, 23, 42, 4, 12, 3, 11, 1, 11, 3, 15, 6, 21,
+		2, 21, 1, 4, 1, 1, 41, 42, 10, 1, 41, 1, 4, 4, 5, 9, 11, 0,
+		1, 7, 0, 17, 31, 39, 11, 1, 42, 1, 12, 2, 10, 2, 16, 2, 20, 7,
+		0, 22, 10, 2, 15, 2, 21, 11, 2, 16, 2, 20, 12, 4, 10, 0, 17, 23,
+		41, 1, 4, 37, 11, 0, 17, 23, 42, 1, 12, 3, 11, 4, 11, 3, 15, 2,
+		21, 5, 41, 11, 0, 11, 4, 18, 1, 45, 1, 2, 22, 1, 4, 1, 1, 42,
+		13, 11, 0, 17, 23, 42, 1, 12, 1, 10, 1, 16, 2, 20, 7, 0, 22, 11,
+		1, 15, 2, 21, 2, 0, 0, 4, 0, 1, 0, 4, 2, 2, 0, 3, 0, 4,
+		1, 6, 0, 0,
+	]
 });
 
 #[rustfmt::skip]
@@ -553,6 +571,7 @@ pub static MODULE_FRAMEWORK_USECASES_TOKEN_V1: Lazy<Vec<u8>> = Lazy::new(|| {
 pub static MODULES_FRAMEWORK_USECASES: Lazy<Vec<Vec<u8>>> = Lazy::new(|| { vec![
 	MODULE_FRAMEWORK_USECASES_TOKEN_V1.to_vec(),
 ]});
+
 #[rustfmt::skip]
 pub static PACKAGE_AMBASSADOR_TOKEN_METADATA: Lazy<Vec<u8>> = Lazy::new(|| {
 	vec![
@@ -785,12 +804,214 @@ pub static MODULES_AMBASSADOR_TOKEN: Lazy<Vec<Vec<u8>>> = Lazy::new(|| { vec![
 	MODULE_AMBASSADOR_TOKEN_AMBASSADOR.to_vec(),
 ]});
 
+#[rustfmt::skip]
+pub static PACKAGE_COMPOSABILITY_METADATA: Lazy<Vec<u8>> = Lazy::new(|| {
+	vec![
+		13, 99, 111, 109, 112, 111, 115, 97, 98, 105, 108, 105, 116, 121, 1, 0, 0, 0,
+		0, 0, 0, 0, 0, 64, 67, 69, 50, 53, 48, 70, 55, 48, 51, 69, 55, 70,
+		54, 69, 50, 68, 52, 50, 49, 67, 48, 56, 66, 57, 50, 50, 48, 52, 54, 67,
+		50, 66, 49, 67, 54, 48, 57, 66, 65, 49, 68, 54, 56, 54, 55, 56, 66, 50,
+		66, 67, 55, 69, 52, 49, 68, 54, 53, 66, 53, 67, 52, 56, 52, 69, 134, 1,
+		31, 139, 8, 0, 0, 0, 0, 0, 2, 255, 77, 139, 61, 10, 132, 48, 16, 133,
+		251, 57, 69, 176, 73, 181, 209, 61, 192, 22, 219, 236, 37, 196, 98, 54, 142, 18,
+		76, 50, 33, 17, 69, 196, 187, 27, 65, 69, 120, 197, 251, 251, 234, 128, 122, 192,
+		158, 26, 240, 232, 72, 124, 132, 212, 236, 2, 39, 252, 27, 107, 198, 69, 194, 68,
+		49, 25, 246, 199, 242, 86, 149, 170, 36, 64, 221, 82, 32, 223, 146, 215, 134, 82,
+		3, 223, 48, 114, 250, 197, 140, 207, 28, 135, 124, 92, 133, 101, 141, 54, 187, 66,
+		169, 242, 41, 60, 174, 47, 199, 19, 149, 221, 5, 156, 229, 157, 11, 177, 193, 14,
+		199, 186, 46, 183, 150, 0, 0, 0, 8, 7, 99, 104, 97, 105, 110, 95, 55, 0,
+		0, 0, 7, 99, 104, 97, 105, 110, 95, 54, 0, 0, 0, 7, 99, 104, 97, 105,
+		110, 95, 53, 0, 0, 0, 7, 99, 104, 97, 105, 110, 95, 52, 0, 0, 0, 7,
+		99, 104, 97, 105, 110, 95, 51, 0, 0, 0, 7, 99, 104, 97, 105, 110, 95, 50,
+		0, 0, 0, 7, 99, 104, 97, 105, 110, 95, 49, 0, 0, 0, 7, 99, 104, 97,
+		105, 110, 95, 48, 0, 0, 0, 3, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0,
+		0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0,
+		0, 0, 0, 1, 14, 65, 112, 116, 111, 115, 70, 114, 97, 109, 101, 119, 111, 114,
+		107, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0,
+		0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 1, 11, 65, 112,
+		116, 111, 115, 83, 116, 100, 108, 105, 98, 0, 0, 0, 0, 0, 0, 0, 0, 0,
+		0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0,
+		0, 0, 0, 0, 1, 10, 77, 111, 118, 101, 83, 116, 100, 108, 105, 98, 0,
+	]
+});
+
+#[rustfmt::skip]
+pub static MODULE_COMPOSABILITY_CHAIN_7: Lazy<Vec<u8>> = Lazy::new(|| {
+	vec![
+		161, 28, 235, 11, 6, 0, 0, 0, 6, 1, 0, 2, 3, 2, 10, 5, 12, 6,
+		7, 18, 24, 8, 42, 32, 12, 74, 73, 0, 0, 0, 1, 0, 1, 0, 0, 2,
+		1, 1, 0, 2, 3, 3, 1, 3, 0, 7, 99, 104, 97, 105, 110, 95, 55, 4,
+		99, 97, 108, 108, 10, 99, 97, 108, 108, 95, 108, 111, 99, 97, 108, 0, 0, 0,
+		0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0,
+		0, 0, 0, 0, 0, 0, 0, 0, 0, 171, 205, 0, 1, 0, 0, 2, 3, 11,
+		1, 17, 1, 2, 1, 0, 0, 0, 1, 16, 10, 0, 6, 1, 0, 0, 0, 0,
+		0, 0, 0, 36, 4, 12, 11, 0, 6, 1, 0, 0, 0, 0, 0, 0, 0, 23,
+		17, 1, 6, 1, 0, 0, 0, 0, 0, 0, 0, 22, 12, 1, 5, 14, 6, 1,
+		0, 0, 0, 0, 0, 0, 0, 12, 1, 11, 1, 2, 0,
+	]
+});
+
+#[rustfmt::skip]
+pub static MODULE_COMPOSABILITY_CHAIN_6: Lazy<Vec<u8>> = Lazy::new(|| {
+	vec![
+		161, 28, 235, 11, 6, 0, 0, 0, 6, 1, 0, 4, 3, 4, 15, 5, 19, 10,
+		7, 29, 32, 8, 61, 32, 12, 93, 129, 1, 0, 0, 0, 1, 0, 2, 0, 1,
+		0, 0, 3, 2, 1, 0, 1, 2, 0, 1, 0, 2, 3, 3, 1, 3, 3, 3,
+		3, 3, 0, 7, 99, 104, 97, 105, 110, 95, 54, 7, 99, 104, 97, 105, 110, 95,
+		55, 4, 99, 97, 108, 108, 10, 99, 97, 108, 108, 95, 108, 111, 99, 97, 108, 0,
+		0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0,
+		0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 171, 205, 0, 1, 0, 0, 3,
+		5, 11, 0, 10, 1, 11, 1, 17, 1, 2, 1, 0, 0, 0, 0, 33, 10, 2,
+		6, 1, 0, 0, 0, 0, 0, 0, 0, 36, 4, 14, 11, 0, 11, 1, 11, 2,
+		6, 1, 0, 0, 0, 0, 0, 0, 0, 23, 17, 1, 6, 1, 0, 0, 0, 0,
+		0, 0, 0, 22, 12, 4, 5, 31, 10, 0, 6, 1, 0, 0, 0, 0, 0, 0,
+		0, 36, 4, 27, 11, 0, 6, 1, 0, 0, 0, 0, 0, 0, 0, 23, 11, 1,
+		17, 2, 6, 1, 0, 0, 0, 0, 0, 0, 0, 22, 12, 3, 5, 29, 6, 1,
+		0, 0, 0, 0, 0, 0, 0, 12, 3, 11, 3, 12, 4, 11, 4, 2, 0,
+	]
+});
+
+#[rustfmt::skip]
+pub static MODULE_COMPOSABILITY_CHAIN_5: Lazy<Vec<u8>> = Lazy::new(|| {
+	vec![
+		161, 28, 235, 11, 6, 0, 0, 0, 6, 1, 0, 4, 3, 4, 15, 5, 19, 10,
+		7, 29, 32, 8, 61, 32, 12, 93, 129, 1, 0, 0, 0, 1, 0, 2, 0, 1,
+		0, 0, 3, 2, 1, 0, 1, 2, 0, 1, 0, 2, 3, 3, 1, 3, 3, 3,
+		3, 3, 0, 7, 99, 104, 97, 105, 110, 95, 53, 7, 99, 104, 97, 105, 110, 95,
+		54, 4, 99, 97, 108, 108, 10, 99, 97, 108, 108, 95, 108, 111, 99, 97, 108, 0,
+		0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0,
+		0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 171, 205, 0, 1, 0, 0, 3,
+		5, 11, 0, 10, 1, 11, 1, 17, 1, 2, 1, 0, 0, 0, 0, 33, 10, 2,
+		6, 1, 0, 0, 0, 0, 0, 0, 0, 36, 4, 14, 11, 0, 11, 1, 11, 2,
+		6, 1, 0, 0, 0, 0, 0, 0, 0, 23, 17, 1, 6, 1, 0, 0, 0, 0,
+		0, 0, 0, 22, 12, 4, 5, 31, 10, 0, 6, 1, 0, 0, 0, 0, 0, 0,
+		0, 36, 4, 27, 11, 0, 6, 1, 0, 0, 0, 0, 0, 0, 0, 23, 11, 1,
+		17, 2, 6, 1, 0, 0, 0, 0, 0, 0, 0, 22, 12, 3, 5, 29, 6, 1,
+		0, 0, 0, 0, 0, 0, 0, 12, 3, 11, 3, 12, 4, 11, 4, 2, 0,
+	]
+});
+
+#[rustfmt::skip]
+pub static MODULE_COMPOSABILITY_CHAIN_4: Lazy<Vec<u8>> = Lazy::new(|| {
+	vec![
+		161, 28, 235, 11, 6, 0, 0, 0, 6, 1, 0, 4, 3, 4, 15, 5, 19, 10,
+		7, 29, 32, 8, 61, 32, 12, 93, 129, 1, 0, 0, 0, 1, 0, 2, 0, 1,
+		0, 0, 3, 2, 1, 0, 1, 2, 0, 1, 0, 2, 3, 3, 1, 3, 3, 3,
+		3, 3, 0, 7, 99, 104, 97, 105, 110, 95, 52, 7, 99, 104, 97, 105, 110, 95,
+		53, 4, 99, 97, 108, 108, 10, 99, 97, 108, 108, 95, 108, 111, 99, 97, 108, 0,
+		0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0,
+		0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 171, 205, 0, 1, 0, 0, 3,
+		5, 11, 0, 10, 1, 11, 1, 17, 1, 2, 1, 0, 0, 0, 0, 33, 10, 2,
+		6, 1, 0, 0, 0, 0, 0, 0, 0, 36, 4, 14, 11, 0, 11, 1, 11, 2,
+		6, 1, 0, 0, 0, 0, 0, 0, 0, 23, 17, 1, 6, 1, 0, 0, 0, 0,
+		0, 0, 0, 22, 12, 4, 5, 31, 10, 0, 6, 1, 0, 0, 0, 0, 0, 0,
+		0, 36, 4, 27, 11, 0, 6, 1, 0, 0, 0, 0, 0, 0, 0, 23, 11, 1,
+		17, 2, 6, 1, 0, 0, 0, 0, 0, 0, 0, 22, 12, 3, 5, 29, 6, 1,
+		0, 0, 0, 0, 0, 0, 0, 12, 3, 11, 3, 12, 4, 11, 4, 2, 0,
+	]
+});
+
+#[rustfmt::skip]
+pub static MODULE_COMPOSABILITY_CHAIN_3: Lazy<Vec<u8>> = Lazy::new(|| {
+	vec![
+		161, 28, 235, 11, 6, 0, 0, 0, 6, 1, 0, 4, 3, 4, 15, 5, 19, 10,
+		7, 29, 32, 8, 61, 32, 12, 93, 129, 1, 0, 0, 0, 1, 0, 2, 0, 1,
+		0, 0, 3, 2, 1, 0, 1, 2, 0, 1, 0, 2, 3, 3, 1, 3, 3, 3,
+		3, 3, 0, 7, 99, 104, 97, 105, 110, 95, 51, 7, 99, 104, 97, 105, 110, 95,
+		52, 4, 99, 97, 108, 108, 10, 99, 97, 108, 108, 95, 108, 111, 99, 97, 108, 0,
+		0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0,
+		0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 171, 205, 0, 1, 0, 0, 3,
+		5, 11, 0, 10, 1, 11, 1, 17, 1, 2, 1, 0, 0, 0, 0, 33, 10, 2,
+		6, 1, 0, 0, 0, 0, 0, 0, 0, 36, 4, 14, 11, 0, 11, 1, 11, 2,
+		6, 1, 0, 0, 0, 0, 0, 0, 0, 23, 17, 1, 6, 1, 0, 0, 0, 0,
+		0, 0, 0, 22, 12, 4, 5, 31, 10, 0, 6, 1, 0, 0, 0, 0, 0, 0,
+		0, 36, 4, 27, 11, 0, 6, 1, 0, 0, 0, 0, 0, 0, 0, 23, 11, 1,
+		17, 2, 6, 1, 0, 0, 0, 0, 0, 0, 0, 22, 12, 3, 5, 29, 6, 1,
+		0, 0, 0, 0, 0, 0, 0, 12, 3, 11, 3, 12, 4, 11, 4, 2, 0,
+	]
+});
+
+#[rustfmt::skip]
+pub static MODULE_COMPOSABILITY_CHAIN_2: Lazy<Vec<u8>> = Lazy::new(|| {
+	vec![
+		161, 28, 235, 11, 6, 0, 0, 0, 6, 1, 0, 4, 3, 4, 15, 5, 19, 10,
+		7, 29, 32, 8, 61, 32, 12, 93, 129, 1, 0, 0, 0, 1, 0, 2, 0, 1,
+		0, 0, 3, 2, 1, 0, 1, 2, 0, 1, 0, 2, 3, 3, 1, 3, 3, 3,
+		3, 3, 0, 7, 99, 104, 97, 105, 110, 95, 50, 7, 99, 104, 97, 105, 110, 95,
+		51, 4, 99, 97, 108, 108, 10, 99, 97, 108, 108, 95, 108, 111, 99, 97, 108, 0,
+		0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0,
+		0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 171, 205, 0, 1, 0, 0, 3,
+		5, 11, 0, 10, 1, 11, 1, 17, 1, 2, 1, 0, 0, 0, 0, 33, 10, 2,
+		6, 1, 0, 0, 0, 0, 0, 0, 0, 36, 4, 14, 11, 0, 11, 1, 11, 2,
+		6, 1, 0, 0, 0, 0, 0, 0, 0, 23, 17, 1, 6, 1, 0, 0, 0, 0,
+		0, 0, 0, 22, 12, 4, 5, 31, 10, 0, 6, 1, 0, 0, 0, 0, 0, 0,
+		0, 36, 4, 27, 11, 0, 6, 1, 0, 0, 0, 0, 0, 0, 0, 23, 11, 1,
+		17, 2, 6, 1, 0, 0, 0, 0, 0, 0, 0, 22, 12, 3, 5, 29, 6, 1,
+		0, 0, 0, 0, 0, 0, 0, 12, 3, 11, 3, 12, 4, 11, 4, 2, 0,
+	]
+});
+
+#[rustfmt::skip]
+pub static MODULE_COMPOSABILITY_CHAIN_1: Lazy<Vec<u8>> = Lazy::new(|| {
+	vec![
+		161, 28, 235, 11, 6, 0, 0, 0, 6, 1, 0, 4, 3, 4, 15, 5, 19, 10,
+		7, 29, 32, 8, 61, 32, 12, 93, 129, 1, 0, 0, 0, 1, 0, 2, 0, 1,
+		0, 0, 3, 2, 1, 0, 1, 2, 0, 1, 0, 2, 3, 3, 1, 3, 3, 3,
+		3, 3, 0, 7, 99, 104, 97, 105, 110, 95, 49, 7, 99, 104, 97, 105, 110, 95,
+		50, 4, 99, 97, 108, 108, 10, 99, 97, 108, 108, 95, 108, 111, 99, 97, 108, 0,
+		0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0,
+		0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 171, 205, 0, 1, 0, 0, 3,
+		5, 11, 0, 10, 1, 11, 1, 17, 1, 2, 1, 0, 0, 0, 0, 33, 10, 2,
+		6, 1, 0, 0, 0, 0, 0, 0, 0, 36, 4, 14, 11, 0, 11, 1, 11, 2,
+		6, 1, 0, 0, 0, 0, 0, 0, 0, 23, 17, 1, 6, 1, 0, 0, 0, 0,
+		0, 0, 0, 22, 12, 4, 5, 31, 10, 0, 6, 1, 0, 0, 0, 0, 0, 0,
+		0, 36, 4, 27, 11, 0, 6, 1, 0, 0, 0, 0, 0, 0, 0, 23, 11, 1,
+		17, 2, 6, 1, 0, 0, 0, 0, 0, 0, 0, 22, 12, 3, 5, 29, 6, 1,
+		0, 0, 0, 0, 0, 0, 0, 12, 3, 11, 3, 12, 4, 11, 4, 2, 0,
+	]
+});
+
+#[rustfmt::skip]
+pub static MODULE_COMPOSABILITY_CHAIN_0: Lazy<Vec<u8>> = Lazy::new(|| {
+	vec![
+		161, 28, 235, 11, 6, 0, 0, 0, 6, 1, 0, 4, 3, 4, 20, 5, 24, 15,
+		7, 39, 36, 8, 75, 32, 12, 107, 143, 1, 0, 0, 0, 1, 0, 2, 0, 1,
+		0, 0, 3, 2, 1, 0, 0, 4, 3, 4, 0, 1, 2, 0, 1, 0, 2, 3,
+		3, 1, 3, 3, 3, 3, 3, 3, 6, 12, 3, 3, 0, 7, 99, 104, 97, 105,
+		110, 95, 48, 7, 99, 104, 97, 105, 110, 95, 49, 4, 99, 97, 108, 108, 10, 99,
+		97, 108, 108, 95, 108, 111, 99, 97, 108, 3, 114, 117, 110, 0, 0, 0, 0, 0,
+		0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0,
+		0, 0, 0, 0, 0, 0, 0, 171, 205, 0, 1, 0, 0, 4, 5, 11, 0, 10,
+		1, 11, 1, 17, 1, 2, 1, 0, 0, 0, 0, 33, 10, 2, 6, 1, 0, 0,
+		0, 0, 0, 0, 0, 36, 4, 14, 11, 0, 11, 1, 11, 2, 6, 1, 0, 0,
+		0, 0, 0, 0, 0, 23, 17, 1, 6, 1, 0, 0, 0, 0, 0, 0, 0, 22,
+		12, 4, 5, 31, 10, 0, 6, 1, 0, 0, 0, 0, 0, 0, 0, 36, 4, 27,
+		11, 0, 6, 1, 0, 0, 0, 0, 0, 0, 0, 23, 11, 1, 17, 3, 6, 1,
+		0, 0, 0, 0, 0, 0, 0, 22, 12, 3, 5, 29, 6, 1, 0, 0, 0, 0,
+		0, 0, 0, 12, 3, 11, 3, 12, 4, 11, 4, 2, 2, 1, 4, 0, 4, 5,
+		11, 1, 11, 2, 17, 0, 1, 2, 0,
+	]
+});
+
+#[rustfmt::skip]
+pub static MODULES_COMPOSABILITY: Lazy<Vec<Vec<u8>>> = Lazy::new(|| { vec![
+	MODULE_COMPOSABILITY_CHAIN_7.to_vec(),
+	MODULE_COMPOSABILITY_CHAIN_6.to_vec(),
+	MODULE_COMPOSABILITY_CHAIN_5.to_vec(),
+	MODULE_COMPOSABILITY_CHAIN_4.to_vec(),
+	MODULE_COMPOSABILITY_CHAIN_3.to_vec(),
+	MODULE_COMPOSABILITY_CHAIN_2.to_vec(),
+	MODULE_COMPOSABILITY_CHAIN_1.to_vec(),
+	MODULE_COMPOSABILITY_CHAIN_0.to_vec(),
+]});
+
 #[rustfmt::skip]
 pub static PACKAGE_TO_METADATA: Lazy<HashMap<String, Vec<u8>>> = Lazy::new(|| { HashMap::from([
 	("complex".to_string(), PACKAGE_COMPLEX_METADATA.to_vec()),
 	("simple".to_string(), PACKAGE_SIMPLE_METADATA.to_vec()),
 	("framework_usecases".to_string(), PACKAGE_FRAMEWORK_USECASES_METADATA.to_vec()),
 	("ambassador_token".to_string(), PACKAGE_AMBASSADOR_TOKEN_METADATA.to_vec()),
+	("composability".to_string(), PACKAGE_COMPOSABILITY_METADATA.to_vec()),
 ])});
 
 #[rustfmt::skip]
@@ -799,4 +1020,5 @@ pub static PACKAGE_TO_MODULES: Lazy<HashMap<String, Vec<Vec<u8>>>> = Lazy::new(|
 	("simple".to_string(), MODULES_SIMPLE.to_vec()),
 	("framework_usecases".to_string(), MODULES_FRAMEWORK_USECASES.to_vec()),
 	("ambassador_token".to_string(), MODULES_AMBASSADOR_TOKEN.to_vec()),
+	("composability".to_string(), MODULES_COMPOSABILITY.to_vec()),
 ])});
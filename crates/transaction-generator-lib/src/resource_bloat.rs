@@ -0,0 +1,124 @@
+// Copyright © Aptos Foundation
+// SPDX-License-Identifier: Apache-2.0
+
+use super::{publishing::publish_util::Package, ReliableTransactionSubmitter};
+use crate::{
+    call_custom_modules::{TransactionGeneratorWorker, UserModuleTransactionGenerator},
+    EntryPoints,
+};
+use aptos_sdk::{
+    move_types::account_address::AccountAddress,
+    transaction_builder::TransactionFactory,
+    types::{transaction::SignedTransaction, LocalAccount},
+};
+use async_trait::async_trait;
+use rand::rngs::StdRng;
+use std::sync::Arc;
+
+/// Workload where a fraction of the accounts keeps appending new entries to a table they own,
+/// so their state footprint grows over the run, while the rest of the accounts keep rewriting
+/// the same entries. Both do the same amount of work per transaction, so any slowdown comes
+/// from the growing state.
+pub struct ResourceBloatTransactionGenerator {
+    /// Percentage (0-100) of accounts whose state grows.
+    pub bloat_percentage: usize,
+    /// Number of table entries each transaction writes.
+    pub entries_per_txn: u64,
+}
+
+/// Whether the account is one of the growing ones, chosen deterministically from its address,
+/// so that the same accounts keep growing for the whole run.
+fn is_bloating_account(address: AccountAddress, bloat_percentage: usize) -> bool {
+    let bytes = address.into_bytes();
+    let bucket = u16::from_le_bytes([bytes[0], bytes[1]]) as usize % 100;
+    bucket < bloat_percentage
+}
+
+/// Table entries written by the transaction of the account with the given sequence number.
+fn table_entry_point(
+    address: AccountAddress,
+    sequence_number: u64,
+    bloat_percentage: usize,
+    count: u64,
+) -> EntryPoints {
+    // Sequence number is different for every transaction of the account, so growing accounts
+    // write a fresh range of entries each time.
+    let offset = if is_bloating_account(address, bloat_percentage) {
+        sequence_number * count
+    } else {
+        0
+    };
+    EntryPoints::MakeOrChangeTable { offset, count }
+}
+
+#[async_trait]
+impl UserModuleTransactionGenerator for ResourceBloatTransactionGenerator {
+    fn initialize_package(
+        &mut self,
+        _package: &Package,
+        _publisher: &mut LocalAccount,
+        _txn_factory: &TransactionFactory,
+        _rng: &mut StdRng,
+    ) -> Vec<SignedTransaction> {
+        vec![]
+    }
+
+    async fn create_generator_fn(
+        &self,
+        _init_accounts: &mut [LocalAccount],
+        _txn_factory: &TransactionFactory,
+        _txn_executor: &dyn ReliableTransactionSubmitter,
+        _rng: &mut StdRng,
+    ) -> Arc<TransactionGeneratorWorker> {
+        let bloat_percentage = self.bloat_percentage;
+        let count = self.entries_per_txn;
+
+        Arc::new(move |account, package, _publisher, txn_factory, rng| {
+            let entry_point = table_entry_point(
+                account.address(),
+                account.sequence_number(),
+                bloat_percentage,
+                count,
+            );
+            let payload = entry_point.create_payload(
+                package.get_module_id(entry_point.module_name()),
+                Some(rng),
+                None,
+            );
+            account.sign_with_transaction_builder(txn_factory.payload(payload))
+        })
+    }
+}
+
+#[test]
+fn test_bloating_accounts_write_fresh_entries() {
+    use aptos_sdk::{
+        bcs, move_types::language_storage::ModuleId, types::transaction::TransactionPayload,
+    };
+
+    let args = |bloat_percentage, sequence_number| {
+        let entry_point =
+            table_entry_point(AccountAddress::ONE, sequence_number, bloat_percentage, 5);
+        let module_id = ModuleId::new(
+            AccountAddress::ONE,
+            entry_point.module_name().parse().unwrap(),
+        );
+        match entry_point.create_payload(module_id, None, None) {
+            TransactionPayload::EntryFunction(entry_function) => {
+                let (_module, function, _ty_args, args) = entry_function.into_inner();
+                assert_eq!(function.as_str(), "make_or_change_table");
+                args.iter()
+                    .map(|arg| bcs::from_bytes::<u64>(arg).unwrap())
+                    .collect::<Vec<_>>()
+            },
+            payload => panic!("Unexpected payload {:?}", payload),
+        }
+    };
+    // Growing accounts move on to the next range with every transaction.
+    assert_eq!(args(100, 0), vec![0, 5]);
+    assert_eq!(args(100, 1), vec![5, 5]);
+    assert_eq!(args(100, 7), vec![35, 5]);
+    // The others keep rewriting the same entries.
+    assert_eq!(args(0, 0), vec![0, 5]);
+    assert_eq!(args(0, 7), vec![0, 5]);
+}
@@ -0,0 +1,135 @@
+// Copyright © Aptos Foundation
+// SPDX-License-Identifier: Apache-2.0
+
+use super::{publishing::publish_util::Package, ReliableTransactionSubmitter};
+use crate::{
+    call_custom_modules::{TransactionGeneratorWorker, UserModuleTransactionGenerator},
+    EntryPoints,
+};
+use aptos_sdk::{
+    transaction_builder::TransactionFactory,
+    types::{transaction::SignedTransaction, LocalAccount},
+};
+use async_trait::async_trait;
+use rand::{rngs::StdRng, Rng};
+use std::sync::Arc;
+
+/// Which Move collection the table items workload goes through.
+#[derive(Debug, Copy, Clone, Eq, PartialEq)]
+pub enum TableKind {
+    /// `aptos_std::table::Table`, one table per sender account.
+    Table,
+    /// `aptos_std::smart_table::SmartTable`, one table per published module.
+    SmartTable,
+}
+
+impl TableKind {
+    pub fn package_name(&self) -> &'static str {
+        self.entry_point(1, 1, false).package_name()
+    }
+
+    fn entry_point(&self, key_space_size: u64, entries_per_txn: u64, remove: bool) -> EntryPoints {
+        match (self, remove) {
+            // Offset is picked for each transaction, in the generator.
+            (TableKind::Table, false) => EntryPoints::MakeOrChangeTable {
+                offset: 0,
+                count: entries_per_txn,
+            },
+            (TableKind::Table, true) => EntryPoints::RemoveFromTable {
+                offset: 0,
+                count: entries_per_txn,
+            },
+            (TableKind::SmartTable, false) => EntryPoints::SmartTablePicture {
+                length: key_space_size,
+                num_points_per_txn: entries_per_txn as usize,
+            },
+            (TableKind::SmartTable, true) => EntryPoints::SmartTablePictureRemove {
+                length: key_space_size,
+                num_points_per_txn: entries_per_txn as usize,
+            },
+        }
+    }
+}
+
+/// Workload writing and removing random table items over a fixed key space, so that it first
+/// mostly inserts new items and then mostly updates (and removes) existing ones. Table items live
+/// under their own hashed state keys, unlike resources, a storage pattern transfer only workloads
+/// never exercise.
+pub struct TableItemsTransactionGenerator {
+    pub kind: TableKind,
+    /// Number of distinct keys each table is written at.
+    pub key_space_size: u64,
+    /// Number of table items each transaction writes or removes.
+    pub entries_per_txn: u64,
+    /// Percentage of the transactions removing items instead of writing them.
+    pub removal_percentage: u64,
+}
+
+#[async_trait]
+impl UserModuleTransactionGenerator for TableItemsTransactionGenerator {
+    fn initialize_package(
+        &mut self,
+        package: &Package,
+        publisher: &mut LocalAccount,
+        txn_factory: &TransactionFactory,
+        rng: &mut StdRng,
+    ) -> Vec<SignedTransaction> {
+        match self
+            .kind
+            .entry_point(self.key_space_size, self.entries_per_txn, false)
+            .initialize_entry_point()
+        {
+            Some(initial_entry_point) => {
+                let payload = initial_entry_point.create_payload(
+                    package.get_module_id(initial_entry_point.module_name()),
+                    Some(rng),
+                    Some(&publisher.address()),
+                );
+                vec![publisher.sign_with_transaction_builder(txn_factory.payload(payload))]
+            },
+            None => vec![],
+        }
+    }
+
+    async fn create_generator_fn(
+        &self,
+        _init_accounts: &mut [LocalAccount],
+        _txn_factory: &TransactionFactory,
+        _txn_executor: &dyn ReliableTransactionSubmitter,
+        _rng: &mut StdRng,
+    ) -> Arc<TransactionGeneratorWorker> {
+        assert!(
+            self.entries_per_txn > 0 && self.entries_per_txn <= self.key_space_size,
+            "Table items per transaction must be in [1, key space size]."
+        );
+        assert!(
+            self.removal_percentage <= 100,
+            "Table items removal percentage must be at most 100."
+        );
+        let kind = self.kind;
+        let key_space_size = self.key_space_size;
+        let count = self.entries_per_txn;
+        let removal_percentage = self.removal_percentage;
+
+        Arc::new(move |account, package, publisher, txn_factory, rng| {
+            let remove = rng.gen_range(0, 100) < removal_percentage;
+            let entry_point = match kind.entry_point(key_space_size, count, remove) {
+                EntryPoints::MakeOrChangeTable { count, .. } => EntryPoints::MakeOrChangeTable {
+                    offset: rng.gen_range(0, key_space_size - count + 1),
+                    count,
+                },
+                EntryPoints::RemoveFromTable { count, .. } => EntryPoints::RemoveFromTable {
+                    offset: rng.gen_range(0, key_space_size - count + 1),
+                    count,
+                },
+                entry_point => entry_point,
+            };
+            let payload = entry_point.create_payload(
+                package.get_module_id(entry_point.module_name()),
+                Some(rng),
+                Some(&publisher.address()),
+            );
+            account.sign_with_transaction_builder(txn_factory.payload(payload))
+        })
+    }
+}
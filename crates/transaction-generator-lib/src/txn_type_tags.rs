@@ -0,0 +1,98 @@
+// Copyright © Aptos Foundation
+// SPDX-License-Identifier: Apache-2.0
+
+use crate::{TransactionGenerator, TransactionGeneratorCreator};
+use aptos_infallible::Mutex;
+use aptos_sdk::{
+    move_types::account_address::AccountAddress,
+    types::{transaction::SignedTransaction, LocalAccount},
+};
+use std::{collections::HashMap, sync::Arc};
+
+/// Type of every transaction generated by a transaction mix, as the index of its type within the
+/// mix of its phase, keyed by sender and sequence number, so that the transactions can be
+/// attributed to their type once executed (or discarded).
+pub struct TxnTypeTags {
+    tags: Mutex<HashMap<(AccountAddress, u64), usize>>,
+}
+
+impl Default for TxnTypeTags {
+    fn default() -> Self {
+        Self {
+            tags: Mutex::new(HashMap::new()),
+        }
+    }
+}
+
+impl TxnTypeTags {
+    fn tag(&self, type_index: usize, txns: &[SignedTransaction]) {
+        let mut tags = self.tags.lock();
+        for txn in txns {
+            tags.insert((txn.sender(), txn.sequence_number()), type_index);
+        }
+    }
+
+    /// Index of the type of the transaction sent by `sender` with `sequence_number`, if it was
+    /// generated by the mix.
+    pub fn get(&self, sender: AccountAddress, sequence_number: u64) -> Option<usize> {
+        self.tags.lock().get(&(sender, sequence_number)).copied()
+    }
+
+    /// Number of transactions generated of each type, by index.
+    pub fn num_generated(&self, num_types: usize) -> Vec<u64> {
+        let mut num_generated = vec![0; num_types];
+        for type_index in self.tags.lock().values() {
+            num_generated[*type_index] += 1;
+        }
+        num_generated
+    }
+}
+
+struct TxnTypeTaggingGenerator {
+    generator: Box<dyn TransactionGenerator>,
+    type_index: usize,
+    tags: Arc<TxnTypeTags>,
+}
+
+impl TransactionGenerator for TxnTypeTaggingGenerator {
+    fn generate_transactions(
+        &mut self,
+        account: &LocalAccount,
+        num_to_create: usize,
+    ) -> Vec<SignedTransaction> {
+        let txns = self.generator.generate_transactions(account, num_to_create);
+        self.tags.tag(self.type_index, &txns);
+        txns
+    }
+}
+
+/// Wrapper tagging the transactions of the inner generator with the type at `type_index`.
+pub(crate) struct TxnTypeTaggingCreator {
+    creator: Box<dyn TransactionGeneratorCreator>,
+    type_index: usize,
+    tags: Arc<TxnTypeTags>,
+}
+
+impl TxnTypeTaggingCreator {
+    pub(crate) fn new(
+        creator: Box<dyn TransactionGeneratorCreator>,
+        type_index: usize,
+        tags: Arc<TxnTypeTags>,
+    ) -> Self {
+        Self {
+            creator,
+            type_index,
+            tags,
+        }
+    }
+}
+
+impl TransactionGeneratorCreator for TxnTypeTaggingCreator {
+    fn create_transaction_generator(&self) -> Box<dyn TransactionGenerator> {
+        Box::new(TxnTypeTaggingGenerator {
+            generator: self.creator.create_transaction_generator(),
+            type_index: self.type_index,
+            tags: self.tags.clone(),
+        })
+    }
+}
@@ -0,0 +1,81 @@
+// Copyright © Aptos Foundation
+// SPDX-License-Identifier: Apache-2.0
+
+//! Benchmarks of single building blocks of the executor, on the fixtures of the macro benchmark,
+//! see `bench_fixtures`.
+
+#[macro_use]
+extern crate criterion;
+
+use aptos_executor::{
+    block_executor::TransactionBlockExecutor, components::chunk_output::ChunkOutput,
+};
+use aptos_executor_benchmark::bench_fixtures::BenchFixture;
+use aptos_vm::AptosVM;
+use criterion::{BatchSize, Criterion};
+
+const NUM_ACCOUNTS: usize = 10000;
+const INIT_ACCOUNT_BALANCE: u64 = 10_000_000_000;
+const BLOCK_SIZE: usize = 1000;
+
+fn bench_group(c: &mut Criterion) {
+    let mut group = c.benchmark_group("executor_paths");
+    let mut fixture = BenchFixture::new::<AptosVM>(NUM_ACCOUNTS, INIT_ACCOUNT_BALANCE);
+    let block = fixture.transfer_block(BLOCK_SIZE);
+    let execute = |fixture: &BenchFixture| {
+        AptosVM::execute_transaction_block(
+            BenchFixture::executable(&block),
+            fixture.state_view(),
+            None, /* maybe_block_gas_limit */
+        )
+        .expect("Failed to execute the block.")
+    };
+
+    group.bench_function(format!("execute_block/blk={}", BLOCK_SIZE), |b| {
+        b.iter_batched(
+            || (BenchFixture::executable(&block), fixture.state_view()),
+            |(txns, state_view)| {
+                AptosVM::execute_transaction_block(txns, state_view, None)
+                    .expect("Failed to execute the block.")
+            },
+            BatchSize::SmallInput,
+        )
+    });
+
+    let chunk_output = execute(&fixture);
+    let txns_and_outputs = chunk_output
+        .transactions
+        .into_iter()
+        .zip(chunk_output.transaction_outputs)
+        .collect::<Vec<_>>();
+    group.bench_function(format!("aggregate_outputs/blk={}", BLOCK_SIZE), |b| {
+        b.iter_batched(
+            || (txns_and_outputs.clone(), fixture.state_view()),
+            |(txns_and_outputs, state_view)| {
+                ChunkOutput::by_transaction_output(txns_and_outputs, state_view)
+                    .expect("Failed to aggregate the outputs.")
+            },
+            BatchSize::SmallInput,
+        )
+    });
+
+    let executed_trees = fixture.executed_trees();
+    group.bench_function(format!("state_checkpoint/blk={}", BLOCK_SIZE), |b| {
+        b.iter_batched(
+            || execute(&fixture),
+            |chunk_output| {
+                chunk_output
+                    .into_state_checkpoint_output(executed_trees.state(), None)
+                    .expect("Failed to compute the state checkpoint.")
+            },
+            BatchSize::LargeInput,
+        )
+    });
+    group.finish();
+}
+
+criterion_group!(
+    name = executor_paths_benches;
+    config = Criterion::default();
+    targets = bench_group);
+criterion_main!(executor_paths_benches);
@@ -1,12 +1,17 @@
 // Copyright © Aptos Foundation
 // SPDX-License-Identifier: Apache-2.0
 
-use crate::transaction_generator::get_progress_bar;
+use crate::transaction_generator::{get_progress_bar, get_seed};
 use aptos_sdk::types::LocalAccount;
+use aptos_transaction_generator_lib::key_store::{Ed25519KeyStore, KeyStore};
 use rand::{rngs::StdRng, RngCore, SeedableRng};
 use std::{collections::VecDeque, sync::mpsc};
 
-type Seed = [u8; 32];
+/// The key store of the accounts of the DB, which generates them from the seed, and stores and
+/// loads the ones saved next to the DB.
+pub fn key_store() -> &'static dyn KeyStore<LocalAccount> {
+    &Ed25519KeyStore
+}
 
 pub struct AccountGenerator {
     receiver: mpsc::Receiver<LocalAccount>,
@@ -35,14 +40,14 @@ impl AccountGenerator {
         let mut active_rng_quota = Self::MAX_ACCOUNT_GEN_PER_RNG - active_rng_to_skip;
         let mut active_rng = StdRng::seed_from_u64(root_rng.next_u64());
         for _ in 0..active_rng_to_skip {
-            LocalAccount::generate(&mut active_rng);
+            key_store().generate(&mut active_rng);
         }
         let (sender, receiver) = mpsc::sync_channel(100 /* bound */);
 
         std::thread::Builder::new()
             .name("account_generator".to_string())
             .spawn(move || {
-                while sender.send(LocalAccount::generate(&mut active_rng)).is_ok() {
+                while sender.send(key_store().generate(&mut active_rng)).is_ok() {
                     active_rng_quota -= 1;
                     if active_rng_quota == 0 {
                         active_rng = StdRng::seed_from_u64(root_rng.next_u64());
@@ -66,8 +71,6 @@ pub struct AccountCache {
 }
 
 impl AccountCache {
-    const SEED: Seed = [1; 32];
-
     pub fn new(mut generator: AccountGenerator, num_accounts: usize) -> Self {
         let bar = get_progress_bar(num_accounts);
         let accounts = (0..num_accounts)
@@ -80,7 +83,9 @@ impl AccountCache {
         bar.finish();
         Self {
             accounts,
-            rng: StdRng::from_seed(Self::SEED),
+            // The senders and receivers of transfers are drawn from the cache, so that they are
+            // seeded along with the rest of the workload.
+            rng: StdRng::seed_from_u64(get_seed()),
         }
     }
 
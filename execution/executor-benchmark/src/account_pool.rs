@@ -0,0 +1,137 @@
+// Copyright © Aptos Foundation
+// SPDX-License-Identifier: Apache-2.0
+
+use crate::{account_generator::key_store, transaction_generator::META_FILENAME};
+use anyhow::{Context, Result};
+use aptos_logger::{info, warn};
+use aptos_sdk::types::LocalAccount;
+use aptos_state_view::account_with_state_view::AsAccountWithStateView;
+use aptos_storage_interface::{state_view::LatestDbStateCheckpointView, DbReader};
+use aptos_transaction_generator_lib::AccountsPool;
+use aptos_types::{account_address::AccountAddress, account_view::AccountView};
+use std::{collections::HashSet, fs, path::Path, sync::Arc};
+
+/// Accounts left in the accounts pool by the workload run on a DB, stored next to it.
+pub const ACCOUNTS_POOL_FILENAME: &str = "accounts_pool.bcs";
+
+/// The accounts pool of a workload, with the same semantics as in the transaction emitter: the
+/// burner accounts loaded from the DB, accounts left over by earlier runs, and accounts minted
+/// during the run (by account generation with `add_created_accounts_to_pool`).
+///
+/// The accounts minted by a run can't be derived from the seed like the accounts of the DB, so
+/// the ones left in the pool at the end of the run are saved into the DB dir, and handed to the
+/// workload of every later subcommand run on that DB.
+pub struct WorkloadAccountsPool {
+    /// The accounts derived from the seed, which later runs load from the DB anyway.
+    seed_addresses: HashSet<AccountAddress>,
+    accounts_pool: Option<AccountsPool>,
+}
+
+impl WorkloadAccountsPool {
+    pub fn new(seed_accounts: &[LocalAccount]) -> Self {
+        Self {
+            seed_addresses: seed_accounts.iter().map(LocalAccount::address).collect(),
+            accounts_pool: None,
+        }
+    }
+
+    /// Sets the pool the generators of the workload use, as returned by
+    /// `create_txn_generator_creator`.
+    pub fn set_accounts_pool(&mut self, accounts_pool: AccountsPool) {
+        self.accounts_pool = Some(accounts_pool);
+    }
+
+    /// Saves the accounts left in the pool that are not derived from the seed into `db_dir`,
+    /// along with the accounts metadata of `source_dir`, so that `db_dir` can be the source of
+    /// later subcommands.
+    pub fn save(&self, source_dir: &Path, db_dir: &Path) -> Result<()> {
+        let accounts_pool = match &self.accounts_pool {
+            Some(accounts_pool) => accounts_pool.read(),
+            None => return Ok(()),
+        };
+        let accounts = accounts_pool
+            .iter()
+            .filter(|account| !self.seed_addresses.contains(&account.address()))
+            .map(|account| (account.address(), key_store().store(account)))
+            .collect::<Vec<_>>();
+        if accounts.is_empty() {
+            return Ok(());
+        }
+        fs::write(
+            db_dir.join(ACCOUNTS_POOL_FILENAME),
+            bcs::to_bytes(&accounts)?,
+        )?;
+        if source_dir != db_dir {
+            fs::copy(source_dir.join(META_FILENAME), db_dir.join(META_FILENAME))
+                .context("Cannot copy the accounts metadata")?;
+        }
+        info!(
+            "Saved {} accounts of the accounts pool into {:?}",
+            accounts.len(),
+            db_dir
+        );
+        Ok(())
+    }
+}
+
+/// Loads the accounts saved by an earlier run into `source_dir`, with their sequence numbers in
+/// the DB. Accounts that don't exist in the DB are skipped, and a missing file means no accounts
+/// were saved.
+pub fn load_saved_accounts(
+    source_dir: &Path,
+    reader: Arc<dyn DbReader>,
+) -> Result<Vec<LocalAccount>> {
+    let path = source_dir.join(ACCOUNTS_POOL_FILENAME);
+    let bytes = match fs::read(&path) {
+        Ok(bytes) => bytes,
+        Err(err) if err.kind() == std::io::ErrorKind::NotFound => return Ok(Vec::new()),
+        Err(err) => return Err(err).with_context(|| format!("Cannot read {:?}", path)),
+    };
+    let saved: Vec<(AccountAddress, Vec<u8>)> = bcs::from_bytes(&bytes)
+        .with_context(|| format!("Corrupt accounts pool file {:?}", path))?;
+    let num_saved = saved.len();
+    let db_state_view = reader.latest_state_checkpoint_view()?;
+    let mut accounts = Vec::with_capacity(num_saved);
+    for (address, private_key) in saved {
+        let account_resource = match db_state_view
+            .as_account_with_state_view(&address)
+            .get_account_resource()?
+        {
+            Some(account_resource) => account_resource,
+            None => continue,
+        };
+        accounts.push(
+            key_store()
+                .load(address, &private_key, account_resource.sequence_number())
+                .with_context(|| format!("Invalid key of account {} in {:?}", address, path))?,
+        );
+    }
+    if accounts.len() < num_saved {
+        warn!(
+            "{} of the {} saved pool accounts don't exist in the DB, skipping them.",
+            num_saved - accounts.len(),
+            num_saved
+        );
+    }
+    info!(
+        "Reusing {} accounts saved into the accounts pool by an earlier run",
+        accounts.len()
+    );
+    Ok(accounts)
+}
+
+#[test]
+fn test_load_saved_accounts_from_corrupt_file() {
+    use aptos_storage_interface::mock::MockDbReaderWriter;
+
+    let dir = aptos_temppath::TempPath::new();
+    dir.create_as_dir().unwrap();
+    let reader: Arc<dyn DbReader> = Arc::new(MockDbReaderWriter);
+    assert!(load_saved_accounts(dir.path(), reader.clone())
+        .unwrap()
+        .is_empty());
+
+    fs::write(dir.path().join(ACCOUNTS_POOL_FILENAME), [0xFF; 7]).unwrap();
+    let err = load_saved_accounts(dir.path(), reader).unwrap_err();
+    assert!(err.to_string().starts_with("Corrupt accounts pool file"));
+}
@@ -0,0 +1,125 @@
+// Copyright © Aptos Foundation
+// SPDX-License-Identifier: Apache-2.0
+
+use aptos_logger::info;
+use std::{
+    sync::{
+        atomic::{AtomicUsize, Ordering},
+        Mutex,
+    },
+    time::Duration,
+};
+
+/// Bound on how much the block size changes from one block to the next, so that a single
+/// outlier block doesn't throw the controller off.
+const MAX_STEP_FACTOR: f64 = 2.0;
+
+/// Experimental controller that adjusts the size of generated blocks to target a fixed per-block
+/// execution latency, to gather executor-side data for consensus block sizing heuristics.
+///
+/// The execution stage reports the latency of every block, and the generator picks up the size
+/// for the next block it generates. Blocks already queued in the pipeline were generated with
+/// older sizes, so the controller reacts with a lag of the pipeline depth.
+pub struct AdaptiveBlockSize {
+    target_latency: Duration,
+    /// 0 until the first block is executed, the configured block size is used until then.
+    next_block_size: AtomicUsize,
+    /// Size and execution latency of each executed block.
+    trajectory: Mutex<Vec<(usize, Duration)>>,
+}
+
+impl AdaptiveBlockSize {
+    pub fn new(target_latency: Duration) -> Self {
+        Self {
+            target_latency,
+            next_block_size: AtomicUsize::new(0),
+            trajectory: Mutex::new(Vec::new()),
+        }
+    }
+
+    /// Size of the next block to generate, if the controller has made a decision yet.
+    pub fn next_block_size(&self) -> Option<usize> {
+        match self.next_block_size.load(Ordering::Relaxed) {
+            0 => None,
+            block_size => Some(block_size),
+        }
+    }
+
+    /// Records the execution latency of a block, and updates the size of the next block.
+    pub fn record_block(&self, block_size: usize, latency: Duration) {
+        let next_block_size = next_block_size(block_size, latency, self.target_latency);
+        self.next_block_size
+            .store(next_block_size, Ordering::Relaxed);
+        self.trajectory.lock().unwrap().push((block_size, latency));
+        info!(
+            "Adaptive block size: block of {} txns executed in {} ms ({:.0} TPS), next block size {}",
+            block_size,
+            latency.as_millis(),
+            block_size as f64 / latency.as_secs_f64(),
+            next_block_size
+        );
+    }
+
+    /// Logs the block size trajectory, and the throughput achieved once it has settled.
+    pub fn log_trajectory(&self) {
+        let trajectory = self.trajectory.lock().unwrap();
+        if trajectory.is_empty() {
+            return;
+        }
+        info!(
+            "Adaptive block size trajectory (target {} ms): {:?}",
+            self.target_latency.as_millis(),
+            trajectory
+                .iter()
+                .map(|(block_size, _)| *block_size)
+                .collect::<Vec<_>>()
+        );
+        // Leave out the first half, while the controller converges.
+        let settled = &trajectory[trajectory.len() / 2..];
+        let num_txns: usize = settled.iter().map(|(block_size, _)| block_size).sum();
+        let latency: Duration = settled.iter().map(|(_, latency)| *latency).sum();
+        info!(
+            "Adaptive block size settled: avg block size {}, avg latency {} ms, execution TPS {:.0} (over the last {} blocks)",
+            num_txns / settled.len(),
+            latency.as_millis() / settled.len() as u128,
+            num_txns as f64 / latency.as_secs_f64(),
+            settled.len()
+        );
+    }
+}
+
+/// Scales the block size by how far its latency was from the target, assuming latency grows
+/// linearly with the block size.
+fn next_block_size(block_size: usize, latency: Duration, target_latency: Duration) -> usize {
+    let factor = (target_latency.as_secs_f64() / latency.as_secs_f64().max(f64::EPSILON))
+        .clamp(1.0 / MAX_STEP_FACTOR, MAX_STEP_FACTOR);
+    ((block_size as f64 * factor).round() as usize).max(1)
+}
+
+#[test]
+fn test_next_block_size() {
+    let target = Duration::from_millis(250);
+    // On target.
+    assert_eq!(
+        next_block_size(1000, Duration::from_millis(250), target),
+        1000
+    );
+    // Too slow, shrink proportionally.
+    assert_eq!(
+        next_block_size(1000, Duration::from_millis(400), target),
+        625
+    );
+    // Too fast, grow proportionally.
+    assert_eq!(
+        next_block_size(1000, Duration::from_millis(200), target),
+        1250
+    );
+    // Steps are bounded.
+    assert_eq!(
+        next_block_size(1000, Duration::from_millis(10), target),
+        2000
+    );
+    assert_eq!(next_block_size(1000, Duration::from_secs(10), target), 500);
+    // Never below one transaction.
+    assert_eq!(next_block_size(1, Duration::from_secs(1), target), 1);
+}
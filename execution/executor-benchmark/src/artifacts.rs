@@ -0,0 +1,185 @@
+// Copyright © Aptos Foundation
+// SPDX-License-Identifier: Apache-2.0
+
+use anyhow::{Context, Result};
+use aptos_logger::{info, warn};
+use chrono::Local;
+use serde::Serialize;
+use std::{
+    fs::{self, File},
+    path::{Path, PathBuf},
+    time::SystemTime,
+};
+
+/// Prefix of the run directories, the rest of their name is the time the run started, so that
+/// they sort chronologically.
+const RUN_DIR_PREFIX: &str = "run-";
+/// Where the profiler writes flamegraphs, relative to the working directory.
+const PROFILING_RESULTS_DIR: &str = "profiling_results";
+
+pub const RESULTS_JSON: &str = "results.json";
+pub const PROGRESS_EVENTS: &str = "progress.jsonl";
+pub const THREAD_UTILIZATION_CSV: &str = "thread_utilization.csv";
+pub const EXPERIMENT_CSV: &str = "experiment.csv";
+pub const METRICS: &str = "metrics.txt";
+pub const LOG: &str = "benchmark.log";
+const MANIFEST: &str = "manifest.json";
+
+#[derive(Serialize)]
+struct Manifest<'a> {
+    command_line: &'a [String],
+    started_at: String,
+    finished_at: Option<String>,
+    /// Paths of the outputs of the run, relative to the run directory.
+    files: Vec<String>,
+}
+
+/// Directory collecting all outputs of a run, `<artifacts_dir>/run-<start time>`, with a
+/// `manifest.json` listing them, so that they can be collected by copying a single directory.
+pub struct RunArtifacts {
+    run_dir: PathBuf,
+    command_line: Vec<String>,
+    started_at: String,
+    start_time: SystemTime,
+}
+
+impl RunArtifacts {
+    /// Creates the directory of this run, and deletes the oldest runs in `artifacts_dir` so that
+    /// at most `keep_runs` of them, including this one, are left.
+    pub fn create(artifacts_dir: &Path, keep_runs: Option<usize>) -> Result<Self> {
+        let started_at = Local::now();
+        let timestamp = started_at.format("%Y%m%d-%H%M%S").to_string();
+        let mut run_dir = artifacts_dir.join(format!("{}{}", RUN_DIR_PREFIX, timestamp));
+        // Runs started within the same second.
+        let mut attempt = 1;
+        while run_dir.exists() {
+            run_dir = artifacts_dir.join(format!("{}{}.{}", RUN_DIR_PREFIX, timestamp, attempt));
+            attempt += 1;
+        }
+        fs::create_dir_all(&run_dir)
+            .with_context(|| format!("Cannot create artifacts dir {:?}", run_dir))?;
+
+        if let Some(keep_runs) = keep_runs {
+            apply_retention(artifacts_dir, keep_runs)?;
+        }
+
+        let artifacts = Self {
+            run_dir,
+            command_line: std::env::args().collect(),
+            started_at: started_at.to_rfc3339(),
+            start_time: SystemTime::now(),
+        };
+        // Written right away, so that a run that crashes still says what it was.
+        artifacts.write_manifest(None)?;
+        Ok(artifacts)
+    }
+
+    pub fn path(&self, name: &str) -> PathBuf {
+        self.run_dir.join(name)
+    }
+
+    /// Copies the flamegraphs written during the run into the run directory, and lists all
+    /// outputs in the manifest.
+    pub fn finish(&self) -> Result<()> {
+        self.collect_profiling_results()?;
+        self.write_manifest(Some(Local::now().to_rfc3339()))?;
+        info!("Artifacts of the run are in {:?}", self.run_dir);
+        Ok(())
+    }
+
+    fn collect_profiling_results(&self) -> Result<()> {
+        let entries = match fs::read_dir(PROFILING_RESULTS_DIR) {
+            Ok(entries) => entries,
+            Err(_) => return Ok(()),
+        };
+        for entry in entries {
+            let entry = entry?;
+            let written_during_run = entry
+                .metadata()
+                .and_then(|metadata| metadata.modified())
+                .map_or(false, |modified| modified >= self.start_time);
+            if !written_during_run || !entry.file_type()?.is_file() {
+                continue;
+            }
+            let target_dir = self.run_dir.join(PROFILING_RESULTS_DIR);
+            fs::create_dir_all(&target_dir)?;
+            fs::copy(entry.path(), target_dir.join(entry.file_name()))?;
+        }
+        Ok(())
+    }
+
+    fn write_manifest(&self, finished_at: Option<String>) -> Result<()> {
+        let mut files = vec![];
+        list_files(&self.run_dir, &self.run_dir, &mut files)?;
+        files.retain(|file| file != MANIFEST);
+        files.sort();
+        let manifest = Manifest {
+            command_line: &self.command_line,
+            started_at: self.started_at.clone(),
+            finished_at,
+            files,
+        };
+        serde_json::to_writer_pretty(File::create(self.path(MANIFEST))?, &manifest)?;
+        Ok(())
+    }
+}
+
+fn list_files(root: &Path, dir: &Path, files: &mut Vec<String>) -> Result<()> {
+    for entry in fs::read_dir(dir)? {
+        let entry = entry?;
+        let path = entry.path();
+        if entry.file_type()?.is_dir() {
+            list_files(root, &path, files)?;
+        } else if let Ok(relative) = path.strip_prefix(root) {
+            files.push(relative.to_string_lossy().into_owned());
+        }
+    }
+    Ok(())
+}
+
+/// Deletes the oldest run directories in `artifacts_dir`, leaving the `keep_runs` most recent
+/// ones. Other files and directories are left alone.
+fn apply_retention(artifacts_dir: &Path, keep_runs: usize) -> Result<()> {
+    let mut run_dirs = fs::read_dir(artifacts_dir)?
+        .filter_map(|entry| entry.ok())
+        .filter(|entry| {
+            entry
+                .file_type()
+                .map_or(false, |file_type| file_type.is_dir())
+                && entry
+                    .file_name()
+                    .to_string_lossy()
+                    .starts_with(RUN_DIR_PREFIX)
+        })
+        .map(|entry| entry.path())
+        .collect::<Vec<_>>();
+    run_dirs.sort();
+    let num_to_delete = run_dirs.len().saturating_sub(keep_runs);
+    for run_dir in run_dirs.into_iter().take(num_to_delete) {
+        info!("Deleting the artifacts of an old run, {:?}", run_dir);
+        if let Err(err) = fs::remove_dir_all(&run_dir) {
+            warn!("Failed to delete {:?}: {}", run_dir, err);
+        }
+    }
+    Ok(())
+}
+
+#[test]
+fn test_retention() {
+    let dir = aptos_temppath::TempPath::new();
+    dir.create_as_dir().unwrap();
+    for name in ["run-20240101-000000", "run-20240102-000000", "other"] {
+        fs::create_dir(dir.path().join(name)).unwrap();
+    }
+    let artifacts = RunArtifacts::create(dir.path(), Some(2)).unwrap();
+    assert!(!dir.path().join("run-20240101-000000").exists());
+    assert!(dir.path().join("run-20240102-000000").exists());
+    assert!(dir.path().join("other").exists());
+    assert!(artifacts.path(MANIFEST).exists());
+
+    fs::write(artifacts.path(RESULTS_JSON), "{}").unwrap();
+    artifacts.finish().unwrap();
+    let manifest: serde_json::Value =
+        serde_json::from_reader(File::open(artifacts.path(MANIFEST)).unwrap()).unwrap();
+    assert_eq!(manifest["files"], serde_json::json!([RESULTS_JSON]));
+}
@@ -0,0 +1,272 @@
+// Copyright © Aptos Foundation
+// SPDX-License-Identifier: Apache-2.0
+
+use anyhow::{anyhow, Result};
+use aptos_logger::{info, warn};
+use aptos_storage_interface::DbReaderWriter;
+use clap::ValueEnum;
+use std::{
+    fs::{self, File},
+    io::{BufWriter, Write},
+    path::{Path, PathBuf},
+    sync::{Arc, Mutex, MutexGuard},
+    thread::{self, JoinHandle},
+    time::{Duration, Instant},
+};
+
+/// Number of state values read from the DB at a time, when streaming a state snapshot.
+const STATE_SNAPSHOT_CHUNK_SIZE: usize = 4000;
+
+#[derive(Clone, Copy, Debug, Eq, PartialEq, ValueEnum)]
+pub enum BackupKind {
+    /// RocksDB checkpoint of the whole DB, hard links where possible.
+    Checkpoint,
+    /// Stream the latest state snapshot, chunk by chunk, into a file, as a state snapshot backup
+    /// does.
+    StateSnapshot,
+}
+
+#[derive(Clone, Copy, Debug, Default)]
+struct ThroughputWindow {
+    num_txns: u64,
+    elapsed: Duration,
+}
+
+impl ThroughputWindow {
+    fn tps(&self) -> Option<f64> {
+        (!self.elapsed.is_zero()).then(|| self.num_txns as f64 / self.elapsed.as_secs_f64())
+    }
+}
+
+/// Takes a backup of the DB once, after the given number of blocks is committed, on a background
+/// thread while the workload keeps running, to model operators backing up live validators.
+/// Reports how long the backup took, and the committed TPS before, during and after it.
+///
+/// A checkpoint must not be taken while a block is being saved, so the committer holds
+/// [`Self::lock_commit`] around every commit and the checkpoint waits for it.
+pub struct BackupUnderLoad {
+    dir: PathBuf,
+    kind: BackupKind,
+    commit_lock: Arc<Mutex<()>>,
+    // Number of committed blocks after which the backup is taken.
+    at_block: usize,
+    num_committed_blocks: usize,
+    last_commit: Option<Instant>,
+    backup_thread: Option<JoinHandle<Result<Duration>>>,
+    backup_duration: Option<Duration>,
+    // Before, during and after the backup.
+    windows: [ThroughputWindow; 3],
+}
+
+impl BackupUnderLoad {
+    pub fn new(dir: &Path, kind: BackupKind, at_block: usize) -> Self {
+        fs::create_dir_all(dir)
+            .unwrap_or_else(|err| panic!("Failed to create backup dir {:?}: {}", dir, err));
+        Self {
+            dir: dir.to_path_buf(),
+            kind,
+            commit_lock: Arc::new(Mutex::new(())),
+            at_block,
+            num_committed_blocks: 0,
+            last_commit: None,
+            backup_thread: None,
+            backup_duration: None,
+            windows: Default::default(),
+        }
+    }
+
+    /// To be held while committing a block, so that no checkpoint is taken in the middle of it.
+    pub fn lock_commit(&self) -> MutexGuard<'_, ()> {
+        self.commit_lock.lock().expect("Commit lock poisoned.")
+    }
+
+    /// Called after every committed block.
+    pub fn record_block(&mut self, db: &DbReaderWriter, num_txns: usize) {
+        if self.count_block(num_txns) {
+            info!(
+                "Starting {:?} backup into {:?} after {} blocks",
+                self.kind, self.dir, self.num_committed_blocks
+            );
+            let db = db.clone();
+            let dir = self.dir.clone();
+            let kind = self.kind;
+            let commit_lock = self.commit_lock.clone();
+            self.backup_thread = Some(
+                thread::Builder::new()
+                    .name("backup_under_load".to_string())
+                    .spawn(move || take_backup(&db, &dir, kind, &commit_lock))
+                    .expect("Failed to spawn backup thread."),
+            );
+        }
+    }
+
+    /// Counts a committed block of `num_txns` towards the throughput of the current window, and
+    /// returns whether the backup is to be taken now.
+    fn count_block(&mut self, num_txns: usize) -> bool {
+        self.num_committed_blocks += 1;
+
+        self.poll_backup();
+        let window = match (&self.backup_thread, self.backup_duration) {
+            (Some(_), _) => 1,
+            (None, Some(_)) => 2,
+            (None, None) => 0,
+        };
+        let now = Instant::now();
+        // The time until the first commit includes warming up the pipeline, don't count it.
+        if let Some(last_commit) = self.last_commit.replace(now) {
+            self.windows[window].num_txns += num_txns as u64;
+            self.windows[window].elapsed += now.duration_since(last_commit);
+        }
+        self.num_committed_blocks == self.at_block
+    }
+
+    fn poll_backup(&mut self) {
+        if !self
+            .backup_thread
+            .as_ref()
+            .map_or(false, |thread| thread.is_finished())
+        {
+            return;
+        }
+        self.join_backup();
+    }
+
+    fn join_backup(&mut self) {
+        if let Some(thread) = self.backup_thread.take() {
+            match thread.join().expect("Backup thread panicked.") {
+                Ok(duration) => self.backup_duration = Some(duration),
+                Err(err) => {
+                    warn!("{:?} backup failed: {:?}", self.kind, err);
+                    // Still tell apart the blocks committed after the attempt.
+                    self.backup_duration = Some(Duration::ZERO);
+                },
+            }
+        }
+    }
+
+    /// Waits for the backup, if it is still running, and reports its impact on throughput.
+    pub fn report(mut self) {
+        if self.num_committed_blocks < self.at_block {
+            warn!(
+                "The run ended after {} blocks, before the backup after {} blocks was taken.",
+                self.num_committed_blocks, self.at_block
+            );
+            return;
+        }
+        if self.backup_thread.is_some() {
+            warn!("The backup outlasted the workload, waiting for it to finish.");
+            self.join_backup();
+        }
+        let format_tps = |window: &ThroughputWindow| {
+            window
+                .tps()
+                .map_or("-".to_string(), |tps| format!("{:.0}", tps))
+        };
+        let [before, during, after] = &self.windows;
+        info!(
+            "{:?} backup after {} blocks took {:.3} s. Committed TPS before: {}, during: {}, after: {}",
+            self.kind,
+            self.at_block,
+            self.backup_duration.unwrap_or_default().as_secs_f64(),
+            format_tps(before),
+            format_tps(during),
+            format_tps(after),
+        );
+        if let (Some(before), Some(during)) = (before.tps(), during.tps()) {
+            info!(
+                "Foreground TPS during the backup changed by {:.1}%",
+                (during / before - 1.0) * 100.0
+            );
+        }
+    }
+}
+
+fn take_backup(
+    db: &DbReaderWriter,
+    dir: &Path,
+    kind: BackupKind,
+    commit_lock: &Mutex<()>,
+) -> Result<Duration> {
+    let start_time = Instant::now();
+    match kind {
+        BackupKind::Checkpoint => {
+            // Commits stall for as long as the checkpoint takes, which is part of what is measured.
+            let _commit_guard = commit_lock.lock().expect("Commit lock poisoned.");
+            db.writer.create_db_checkpoint(&dir.join("checkpoint"))?
+        },
+        // Only reads an already persisted snapshot, which commits don't touch.
+        BackupKind::StateSnapshot => {
+            let latest_version = db.reader.get_latest_version()?;
+            // The latest state checkpoint may not be in the merkle tree yet, take the latest
+            // persisted snapshot.
+            let (version, _root_hash) = db
+                .reader
+                .get_state_snapshot_before(latest_version + 1)?
+                .ok_or_else(|| anyhow!("No state snapshot to back up"))?;
+            let num_items = db.reader.get_state_leaf_count(version)?;
+            let mut writer = BufWriter::new(File::create(
+                dir.join(format!("state_snapshot_{}.bcs", version)),
+            )?);
+            let mut num_bytes = 0;
+            for start_idx in (0..num_items).step_by(STATE_SNAPSHOT_CHUNK_SIZE) {
+                let chunk = db.reader.get_state_value_chunk_with_proof(
+                    version,
+                    start_idx,
+                    STATE_SNAPSHOT_CHUNK_SIZE,
+                )?;
+                let bytes = bcs::to_bytes(&chunk.raw_values)?;
+                num_bytes += bytes.len();
+                writer.write_all(&bytes)?;
+            }
+            writer.flush()?;
+            info!(
+                "Backed up {} state items ({} bytes) of version {}",
+                num_items, num_bytes, version
+            );
+        },
+    }
+    Ok(start_time.elapsed())
+}
+
+#[test]
+fn test_backup_taken_after_at_block_blocks() {
+    let dir = aptos_temppath::TempPath::new();
+    let mut backup = BackupUnderLoad::new(dir.path(), BackupKind::Checkpoint, 3);
+    assert!(!backup.count_block(10));
+    assert!(!backup.count_block(10));
+    // Right after the third block.
+    assert!(backup.count_block(10));
+    assert!(!backup.count_block(10));
+    // The first block only starts the clock.
+    assert_eq!(backup.windows[0].num_txns, 30);
+}
+
+#[test]
+fn test_checkpoint_waits_for_commit() {
+    use aptos_storage_interface::{DbReader, DbWriter};
+    use std::sync::atomic::{AtomicBool, Ordering};
+
+    #[derive(Default)]
+    struct CheckpointRecorder(AtomicBool);
+    impl DbReader for CheckpointRecorder {}
+    impl DbWriter for CheckpointRecorder {
+        fn create_db_checkpoint(&self, _cp_path: &Path) -> Result<()> {
+            self.0.store(true, Ordering::SeqCst);
+            Ok(())
+        }
+    }
+
+    let dir = aptos_temppath::TempPath::new();
+    let (recorder, db) = DbReaderWriter::wrap(CheckpointRecorder::default());
+    let backup = BackupUnderLoad::new(dir.path(), BackupKind::Checkpoint, 1);
+    let commit_guard = backup.lock_commit();
+    let commit_lock = backup.commit_lock.clone();
+    let backup_thread = thread::spawn(move || {
+        take_backup(&db, dir.path(), BackupKind::Checkpoint, &commit_lock).unwrap()
+    });
+    thread::sleep(Duration::from_millis(100));
+    assert!(!recorder.0.load(Ordering::SeqCst));
+    drop(commit_guard);
+    backup_thread.join().unwrap();
+    assert!(recorder.0.load(Ordering::SeqCst));
+}
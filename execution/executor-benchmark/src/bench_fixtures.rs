@@ -0,0 +1,120 @@
+// Copyright © Aptos Foundation
+// SPDX-License-Identifier: Apache-2.0
+
+//! Fixtures of the criterion benchmarks in `benches/`, which measure single building blocks of the
+//! executor. They are set up the way the macro benchmark sets up its runs, a DB created by
+//! `create_temp_db` and transfers between the accounts of `TransactionGenerator`, so that micro and
+//! macro results are measured on the same state and workload.
+
+use crate::{create_temp_db, init_db_and_executor, transaction_generator::TransactionGenerator};
+use aptos_crypto::HashValue;
+use aptos_executor::block_executor::TransactionBlockExecutor;
+use aptos_sdk::{transaction_builder::TransactionFactory, types::LocalAccount};
+use aptos_state_view::StateViewId;
+use aptos_storage_interface::{
+    async_proof_fetcher::AsyncProofFetcher, cached_state_view::CachedStateView, DbReaderWriter,
+    ExecutedTrees,
+};
+use aptos_temppath::TempPath;
+use aptos_types::{
+    block_executor::partitioner::ExecutableTransactions,
+    transaction::{signature_verified_transaction::SignatureVerifiedTransaction, Transaction},
+};
+use rand::{rngs::StdRng, seq::SliceRandom, SeedableRng};
+use std::{iter::once, sync::Arc};
+
+pub struct BenchFixture {
+    // Removes the DB when the fixture is dropped.
+    _db_dir: TempPath,
+    db: DbReaderWriter,
+    accounts: Vec<LocalAccount>,
+    transaction_factory: TransactionFactory,
+    rng: StdRng,
+}
+
+impl BenchFixture {
+    /// Creates a DB with `num_accounts` accounts in a temporary directory.
+    pub fn new<V>(num_accounts: usize, init_account_balance: u64) -> Self
+    where
+        V: TransactionBlockExecutor + 'static,
+    {
+        let db_dir = create_temp_db::<V>(num_accounts, init_account_balance, 10000);
+        let (mut config, _genesis_key) = aptos_genesis::test_utils::test_config();
+        config.storage.dir = db_dir.path().to_path_buf();
+        let (db, _executor) = init_db_and_executor::<V>(&config);
+        let accounts = TransactionGenerator::gen_user_account_cache(
+            db.reader.clone(),
+            num_accounts,
+            0, /* num_to_skip */
+        )
+        .accounts
+        .into();
+        Self {
+            _db_dir: db_dir,
+            db,
+            accounts,
+            transaction_factory: TransactionGenerator::create_transaction_factory(),
+            rng: StdRng::seed_from_u64(0),
+        }
+    }
+
+    /// Returns a block of `block_size` transfers between random accounts, followed by a state
+    /// checkpoint, that applies to the state of the DB. Every account sends at most one of them,
+    /// and the sequence numbers are not advanced, so the block can be executed any number of
+    /// times against `state_view`.
+    pub fn transfer_block(&mut self, block_size: usize) -> Vec<Transaction> {
+        assert!(
+            block_size <= self.accounts.len(),
+            "A block can't have more transfers than there are accounts."
+        );
+        let senders = self
+            .accounts
+            .choose_multiple(&mut self.rng, block_size)
+            .collect::<Vec<_>>();
+        senders
+            .iter()
+            .map(|sender| {
+                let receiver = self.accounts.choose(&mut self.rng).unwrap();
+                let txn = sender.sign_with_transaction_builder(
+                    self.transaction_factory.transfer(receiver.address(), 1),
+                );
+                sender.decrement_sequence_number();
+                Transaction::UserTransaction(txn)
+            })
+            .chain(once(Transaction::StateCheckpoint(HashValue::random())))
+            .collect()
+    }
+
+    /// Returns the block, with signatures verified, the way the pipeline hands it to the executor.
+    pub fn executable(block: &[Transaction]) -> ExecutableTransactions {
+        ExecutableTransactions::Unsharded(
+            block
+                .iter()
+                .cloned()
+                .map(SignatureVerifiedTransaction::from)
+                .collect(),
+        )
+    }
+
+    /// The trees of the latest committed state, the parent of the blocks executed against
+    /// `state_view`.
+    pub fn executed_trees(&self) -> ExecutedTrees {
+        self.db
+            .reader
+            .get_latest_executed_trees()
+            .expect("Failed to read the latest executed trees.")
+    }
+
+    /// A state view of the latest committed state, as the block executor prepares it for a block.
+    pub fn state_view(&self) -> CachedStateView {
+        let executed_trees = self.executed_trees();
+        CachedStateView::new(
+            StateViewId::Miscellaneous,
+            self.db.reader.clone(),
+            executed_trees.num_transactions(),
+            executed_trees.state().current.clone(),
+            Arc::new(AsyncProofFetcher::new(self.db.reader.clone())),
+        )
+        .expect("Failed to create the state view.")
+    }
+}
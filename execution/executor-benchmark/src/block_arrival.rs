@@ -0,0 +1,63 @@
+// Copyright © Aptos Foundation
+// SPDX-License-Identifier: Apache-2.0
+
+use rand::{thread_rng, Rng};
+use std::time::{Duration, Instant};
+
+/// Models blocks arriving from consensus, instead of being fed to the pipeline back-to-back.
+///
+/// Bursts of `burst_size` blocks arrive together, with exponentially distributed gaps between
+/// bursts (i.e. bursts form a Poisson process), such that on average `blocks_per_sec` blocks
+/// arrive each second.
+pub struct BlockArrivalSchedule {
+    mean_burst_interval: Duration,
+    burst_size: usize,
+    remaining_in_burst: usize,
+    next_arrival: Option<Instant>,
+}
+
+impl BlockArrivalSchedule {
+    pub fn new(blocks_per_sec: f64, burst_size: usize) -> Self {
+        assert!(blocks_per_sec > 0.0, "Block arrival rate must be positive.");
+        assert!(burst_size > 0, "Block arrival burst size must be positive.");
+        Self {
+            mean_burst_interval: Duration::from_secs_f64(burst_size as f64 / blocks_per_sec),
+            burst_size,
+            remaining_in_burst: 0,
+            next_arrival: None,
+        }
+    }
+
+    fn next_arrival(&mut self) -> Instant {
+        let next_arrival = match self.next_arrival {
+            // The first burst arrives as soon as the first block is available.
+            None => {
+                self.remaining_in_burst = self.burst_size;
+                Instant::now()
+            },
+            Some(prev_arrival) if self.remaining_in_burst == 0 => {
+                self.remaining_in_burst = self.burst_size;
+                // Inverse transform sampling of the exponential distribution.
+                let u: f64 = thread_rng().gen();
+                prev_arrival + self.mean_burst_interval.mul_f64(-(1.0 - u).ln())
+            },
+            Some(prev_arrival) => prev_arrival,
+        };
+        self.remaining_in_burst -= 1;
+        self.next_arrival = Some(next_arrival);
+        next_arrival
+    }
+
+    /// Blocks until the next block is scheduled to arrive, and returns the time it became ready
+    /// for processing. If the pipeline is lagging behind the schedule, the block has been ready
+    /// since its scheduled arrival, so no waiting happens. This assumes the generator keeps up
+    /// with the arrival rate, otherwise the generation lag is reported as queueing delay too.
+    pub fn wait_for_next_arrival(&mut self) -> Instant {
+        let arrival = self.next_arrival();
+        let now = Instant::now();
+        if arrival > now {
+            std::thread::sleep(arrival - now);
+        }
+        arrival
+    }
+}
@@ -1,6 +1,6 @@
 // Copyright © Aptos Foundation
 
-use crate::{metrics::TIMER, pipeline::ExecuteBlockMessage};
+use crate::{metrics::TIMER, pipeline::ExecuteBlockMessage, rw_set_estimation::RwSetEstimates};
 use aptos_block_partitioner::{BlockPartitioner, PartitionerConfig};
 use aptos_crypto::HashValue;
 use aptos_experimental_runtimes::thread_manager::optimal_min_len;
@@ -27,10 +27,15 @@ pub(crate) struct BlockPreparationStage {
     num_executor_shards: usize,
     num_blocks_processed: usize,
     maybe_partitioner: Option<Box<dyn BlockPartitioner>>,
+    rw_set_estimates: Option<Arc<RwSetEstimates>>,
 }
 
 impl BlockPreparationStage {
-    pub fn new(num_shards: usize, partitioner_config: &dyn PartitionerConfig) -> Self {
+    pub fn new(
+        num_shards: usize,
+        partitioner_config: &dyn PartitionerConfig,
+        rw_set_estimates: Option<Arc<RwSetEstimates>>,
+    ) -> Self {
         let maybe_partitioner = if num_shards == 0 {
             None
         } else {
@@ -42,6 +47,7 @@ impl BlockPreparationStage {
             num_executor_shards: num_shards,
             num_blocks_processed: 0,
             maybe_partitioner,
+            rw_set_estimates,
         }
     }
 
@@ -61,6 +67,9 @@ impl BlockPreparationStage {
                     .map(|t| t.into())
                     .collect::<Vec<_>>()
             });
+        if let Some(rw_set_estimates) = &self.rw_set_estimates {
+            sig_verified_txns = rw_set_estimates.estimate(sig_verified_txns);
+        }
         let block: ExecutableBlock = match &self.maybe_partitioner {
             None => (block_id, sig_verified_txns).into(),
             Some(partitioner) => {
@@ -79,7 +88,9 @@ impl BlockPreparationStage {
         ExecuteBlockMessage {
             current_block_start_time,
             partition_time: Instant::now().duration_since(current_block_start_time),
+            block_ready_time: current_block_start_time,
             block,
+            retry_block: None,
         }
     }
 }
@@ -0,0 +1,45 @@
+// Copyright © Aptos Foundation
+// SPDX-License-Identifier: Apache-2.0
+
+use crate::metrics::BLOCK_RETRIES;
+use aptos_crypto::HashValue;
+use aptos_logger::warn;
+use std::{fmt::Debug, time::Duration};
+
+/// Base of the linear backoff between attempts.
+const RETRY_BACKOFF: Duration = Duration::from_millis(100);
+
+/// Runs `attempt` (given the attempt number, starting from 0) until it succeeds, retrying a failed
+/// block at most `max_retries` times, and panics if the last attempt fails too.
+pub(crate) fn with_block_retries<T, E: Debug>(
+    stage: &'static str,
+    block_id: HashValue,
+    max_retries: usize,
+    mut attempt: impl FnMut(usize) -> Result<T, E>,
+) -> T {
+    for attempt_number in 0..=max_retries {
+        match attempt(attempt_number) {
+            Ok(result) => return result,
+            Err(err) if attempt_number < max_retries => {
+                BLOCK_RETRIES.with_label_values(&[stage]).inc();
+                warn!(
+                    "Block {} failed in {} (attempt {} of {}), retrying: {:?}",
+                    block_id,
+                    stage,
+                    attempt_number + 1,
+                    max_retries + 1,
+                    err
+                );
+                std::thread::sleep(RETRY_BACKOFF * (attempt_number as u32 + 1));
+            },
+            Err(err) => panic!(
+                "Block {} failed in {} after {} attempts: {:?}",
+                block_id,
+                stage,
+                max_retries + 1,
+                err
+            ),
+        }
+    }
+    unreachable!()
+}
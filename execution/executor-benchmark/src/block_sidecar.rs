@@ -0,0 +1,164 @@
+// Copyright © Aptos Foundation
+// SPDX-License-Identifier: Apache-2.0
+
+use anyhow::{ensure, Context, Result};
+use aptos_crypto::HashValue;
+use aptos_logger::info;
+use aptos_storage_interface::DbReader;
+use aptos_types::transaction::Version;
+use serde::{Deserialize, Serialize};
+use std::{
+    fs::File,
+    io::{BufReader, BufWriter, ErrorKind, Read, Write},
+    path::Path,
+};
+
+/// Where a committed block landed in the DB, and the roots it produced.
+#[derive(Clone, Debug, Deserialize, Eq, PartialEq, Serialize)]
+pub struct BlockSidecarEntry {
+    pub block_index: u64,
+    pub first_version: Version,
+    pub last_version: Version,
+    /// State root at the state checkpoint ending the block, if there is one.
+    pub state_root: Option<HashValue>,
+    pub txn_accumulator_root: HashValue,
+}
+
+/// Writes a sidecar file next to a benchmark DB, so that it can be verified or cross-referenced
+/// (e.g. two runs compared) without opening RocksDB.
+///
+/// The file is a stream of entries, one per committed block, each a little-endian `u32` length
+/// followed by the bcs encoding of a `BlockSidecarEntry`.
+pub struct BlockSidecarWriter {
+    writer: BufWriter<File>,
+    next_block_index: u64,
+}
+
+impl BlockSidecarWriter {
+    pub fn new(path: &Path) -> Self {
+        let file = File::create(path)
+            .unwrap_or_else(|err| panic!("Failed to create block sidecar {:?}: {}", path, err));
+        Self {
+            writer: BufWriter::new(file),
+            next_block_index: 0,
+        }
+    }
+
+    /// Records a block, after it has been committed.
+    pub fn record_block(
+        &mut self,
+        db: &dyn DbReader,
+        first_version: Version,
+        last_version: Version,
+        txn_accumulator_root: HashValue,
+    ) {
+        let state_root = db
+            .get_transaction_info_iterator(last_version, 1)
+            .and_then(|mut infos| infos.next().context("Missing transaction info.")?)
+            .expect("Failed to read committed transaction info.")
+            .state_checkpoint_hash();
+        let entry = BlockSidecarEntry {
+            block_index: self.next_block_index,
+            first_version,
+            last_version,
+            state_root,
+            txn_accumulator_root,
+        };
+        self.next_block_index += 1;
+        let bytes = bcs::to_bytes(&entry).expect("Sidecar entry must serialize.");
+        self.writer
+            .write_all(&(bytes.len() as u32).to_le_bytes())
+            .and_then(|_| self.writer.write_all(&bytes))
+            .expect("Failed to write to block sidecar.");
+    }
+}
+
+impl Drop for BlockSidecarWriter {
+    fn drop(&mut self) {
+        self.writer.flush().expect("Failed to flush block sidecar.");
+    }
+}
+
+pub fn read_block_sidecar(path: &Path) -> Result<Vec<BlockSidecarEntry>> {
+    let mut reader = BufReader::new(
+        File::open(path).with_context(|| format!("Failed to open block sidecar {:?}", path))?,
+    );
+    let mut entries = Vec::new();
+    loop {
+        let mut len_bytes = [0u8; 4];
+        match reader.read_exact(&mut len_bytes) {
+            Ok(()) => {},
+            Err(err) if err.kind() == ErrorKind::UnexpectedEof => break,
+            Err(err) => return Err(err.into()),
+        }
+        let mut bytes = vec![0u8; u32::from_le_bytes(len_bytes) as usize];
+        reader
+            .read_exact(&mut bytes)
+            .context("Truncated block sidecar entry")?;
+        let entry: BlockSidecarEntry = bcs::from_bytes(&bytes)?;
+        ensure!(
+            entry.block_index == entries.len() as u64,
+            "Unexpected block index {} in block sidecar, expected {}",
+            entry.block_index,
+            entries.len()
+        );
+        entries.push(entry);
+    }
+    Ok(entries)
+}
+
+/// Returns the index of the first block in which the two sidecars diverge, including one of them
+/// ending before the other, or `None` if they are identical.
+pub fn first_divergent_block(
+    left: &[BlockSidecarEntry],
+    right: &[BlockSidecarEntry],
+) -> Option<usize> {
+    left.iter()
+        .zip(right.iter())
+        .position(|(l, r)| l != r)
+        .or_else(|| (left.len() != right.len()).then(|| left.len().min(right.len())))
+}
+
+/// Compares the sidecars of two benchmark DBs, logging where they diverge. Returns whether they
+/// are identical.
+pub fn compare_block_sidecars(left_path: &Path, right_path: &Path) -> Result<bool> {
+    let left = read_block_sidecar(left_path)?;
+    let right = read_block_sidecar(right_path)?;
+    match first_divergent_block(&left, &right) {
+        None => {
+            info!("Block sidecars match, {} blocks compared.", left.len());
+            Ok(true)
+        },
+        Some(index) => {
+            info!(
+                "Block sidecars diverge at block {} ({} vs {} blocks): {:?} vs {:?}",
+                index,
+                left.len(),
+                right.len(),
+                left.get(index),
+                right.get(index)
+            );
+            Ok(false)
+        },
+    }
+}
+
+#[test]
+fn test_first_divergent_block() {
+    let entries: Vec<_> = (0..3)
+        .map(|i| BlockSidecarEntry {
+            block_index: i,
+            first_version: i * 10 + 1,
+            last_version: i * 10 + 10,
+            state_root: Some(HashValue::random()),
+            txn_accumulator_root: HashValue::random(),
+        })
+        .collect();
+    assert_eq!(first_divergent_block(&entries, &entries), None);
+    assert_eq!(first_divergent_block(&entries, &entries[..2]), Some(2));
+    assert_eq!(first_divergent_block(&entries[..1], &entries), Some(1));
+
+    let mut diverged = entries.clone();
+    diverged[1].state_root = None;
+    assert_eq!(first_divergent_block(&entries, &diverged), Some(1));
+}
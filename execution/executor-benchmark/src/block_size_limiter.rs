@@ -0,0 +1,242 @@
+// Copyright © Aptos Foundation
+// SPDX-License-Identifier: Apache-2.0
+
+use crate::{adaptive_block_size::AdaptiveBlockSize, pipeline::PipelineConfig};
+use aptos_executor_service::resource_limits::process_rss_bytes;
+use aptos_logger::warn;
+use aptos_types::transaction::Transaction;
+use std::{
+    sync::{
+        atomic::{AtomicU64, AtomicUsize, Ordering},
+        Arc,
+    },
+    time::Duration,
+};
+
+/// How long generation pauses after skipping a block, so that the pipeline gets a chance to drain
+/// the blocks that are already queued before we check memory again.
+const GUARDRAIL_BACKOFF: Duration = Duration::from_millis(100);
+
+/// Size assumed for every transaction until one is generated, the most a user transaction can
+/// take by default, so that the first block stays under the limit whatever the workload.
+const MAX_TXN_BYTES: f64 = 64.0 * 1024.0;
+
+/// Decides how many transactions go into the next generated block.
+///
+/// Blocks are kept under `max_block_bytes` (or, in stress mode, grown up to it), based on the
+/// average serialized size of the user transactions of the workload observed so far. If the process RSS goes above the memory
+/// guardrail, the block is skipped instead of being generated, so that exploring large block sizes
+/// degrades gracefully rather than getting the process OOM-killed.
+pub struct BlockSizeLimiter {
+    max_block_bytes: Option<usize>,
+    fill_to_max_block_bytes: bool,
+    memory_guardrail_bytes: Option<u64>,
+    total_txn_bytes: AtomicU64,
+    total_txns: AtomicU64,
+    num_skipped_blocks: AtomicUsize,
+    adaptive_block_size: Option<Arc<AdaptiveBlockSize>>,
+}
+
+impl BlockSizeLimiter {
+    pub fn new(config: &PipelineConfig) -> Self {
+        if config.memory_guardrail_bytes.is_some() && process_rss_bytes().is_none() {
+            warn!("Cannot read process RSS on this platform, memory guardrail is disabled.");
+        }
+        Self {
+            max_block_bytes: config.max_block_bytes,
+            fill_to_max_block_bytes: config.large_block_stress,
+            memory_guardrail_bytes: config.memory_guardrail_bytes,
+            total_txn_bytes: AtomicU64::new(0),
+            total_txns: AtomicU64::new(0),
+            num_skipped_blocks: AtomicUsize::new(0),
+            adaptive_block_size: None,
+        }
+    }
+
+    /// Lets the controller pick the block size, in place of the configured one.
+    pub fn set_adaptive_block_size(&mut self, adaptive_block_size: Arc<AdaptiveBlockSize>) {
+        self.adaptive_block_size = Some(adaptive_block_size);
+    }
+
+    /// Returns the number of transactions to generate for the next block, or `None` if the
+    /// block should be skipped because the memory guardrail was hit.
+    pub fn next_block_size(&self, block_size: usize, max_block_size: usize) -> Option<usize> {
+        if let (Some(limit), Some(rss)) = (self.memory_guardrail_bytes, process_rss_bytes()) {
+            if rss > limit {
+                let num_skipped = self.num_skipped_blocks.fetch_add(1, Ordering::Relaxed) + 1;
+                warn!(
+                    "Process RSS {} bytes is above the memory guardrail of {} bytes, skipping block ({} skipped so far).",
+                    rss, limit, num_skipped
+                );
+                std::thread::sleep(GUARDRAIL_BACKOFF);
+                return None;
+            }
+        }
+        let block_size = self
+            .adaptive_block_size
+            .as_ref()
+            .and_then(|adaptive_block_size| adaptive_block_size.next_block_size())
+            .map_or(block_size, |adaptive| adaptive.min(max_block_size.max(1)));
+        Some(target_block_size(
+            block_size,
+            max_block_size,
+            self.max_block_bytes,
+            self.fill_to_max_block_bytes,
+            self.avg_txn_bytes(),
+        ))
+    }
+
+    /// Records the serialized size of the user transactions of a generated block, to refine the
+    /// transaction size estimate.
+    pub fn record_block(&self, transactions: &[Transaction]) {
+        if self.max_block_bytes.is_none() {
+            return;
+        }
+        let (num_txns, bytes) = transactions
+            .iter()
+            .filter(|txn| matches!(txn, Transaction::UserTransaction(_)))
+            .fold((0, 0), |(num_txns, bytes), txn| {
+                (
+                    num_txns + 1,
+                    bytes + bcs::serialized_size(txn).expect("Transaction must serialize."),
+                )
+            });
+        self.total_txn_bytes
+            .fetch_add(bytes as u64, Ordering::Relaxed);
+        self.total_txns.fetch_add(num_txns, Ordering::Relaxed);
+    }
+
+    pub fn avg_txn_bytes(&self) -> Option<f64> {
+        let total_txns = self.total_txns.load(Ordering::Relaxed);
+        if total_txns == 0 {
+            None
+        } else {
+            Some(self.total_txn_bytes.load(Ordering::Relaxed) as f64 / total_txns as f64)
+        }
+    }
+
+    pub fn num_skipped_blocks(&self) -> usize {
+        self.num_skipped_blocks.load(Ordering::Relaxed)
+    }
+}
+
+fn target_block_size(
+    block_size: usize,
+    max_block_size: usize,
+    max_block_bytes: Option<usize>,
+    fill_to_max_block_bytes: bool,
+    avg_txn_bytes: Option<f64>,
+) -> usize {
+    match max_block_bytes {
+        Some(max_block_bytes) => {
+            // Until the first block is generated we have no size estimate, so assume the largest
+            // transactions.
+            let avg_txn_bytes = avg_txn_bytes.unwrap_or(MAX_TXN_BYTES);
+            let fitting = (max_block_bytes as f64 / avg_txn_bytes).floor() as usize;
+            let target = if fill_to_max_block_bytes {
+                fitting
+            } else {
+                fitting.min(block_size)
+            };
+            target.clamp(1, max_block_size.max(1))
+        },
+        None => block_size,
+    }
+}
+
+#[test]
+fn test_target_block_size() {
+    // No limit configured.
+    assert_eq!(
+        target_block_size(100, usize::MAX, None, false, Some(10.0)),
+        100
+    );
+    // No estimate yet, assume the largest transactions.
+    assert_eq!(
+        target_block_size(100, usize::MAX, Some(500), false, None),
+        1
+    );
+    assert_eq!(
+        target_block_size(100, usize::MAX, Some(1 << 30), false, None),
+        100
+    );
+    // Limit caps the configured block size.
+    assert_eq!(
+        target_block_size(100, usize::MAX, Some(500), false, Some(10.0)),
+        50
+    );
+    // Limit above the configured block size is not used unless in stress mode.
+    assert_eq!(
+        target_block_size(100, usize::MAX, Some(5000), false, Some(10.0)),
+        100
+    );
+    assert_eq!(
+        target_block_size(100, usize::MAX, Some(5000), true, Some(10.0)),
+        500
+    );
+    // Stress mode is still bounded by what the generator can produce.
+    assert_eq!(
+        target_block_size(100, 300, Some(5000), true, Some(10.0)),
+        300
+    );
+    // Always generate at least one transaction.
+    assert_eq!(
+        target_block_size(100, usize::MAX, Some(5), false, Some(10.0)),
+        1
+    );
+}
+
+#[test]
+fn test_next_block_size_shrinks_for_large_transactions() {
+    use crate::transaction_generator::TransactionGenerator;
+    use aptos_crypto::HashValue;
+    use aptos_sdk::types::LocalAccount;
+    use aptos_types::transaction::{Script, TransactionPayload};
+    use rand::{rngs::StdRng, SeedableRng};
+
+    let transaction_factory = TransactionGenerator::create_transaction_factory();
+    let account = LocalAccount::generate(&mut StdRng::seed_from_u64(0));
+    let gen_block = |num_txns: usize, code_len: usize| {
+        (0..num_txns)
+            .map(|_| {
+                Transaction::UserTransaction(account.sign_with_transaction_builder(
+                    transaction_factory.payload(TransactionPayload::Script(Script::new(
+                        vec![0; code_len],
+                        vec![],
+                        vec![],
+                    ))),
+                ))
+            })
+            .chain(std::iter::once(Transaction::StateCheckpoint(
+                HashValue::zero(),
+            )))
+            .collect::<Vec<_>>()
+    };
+    let limiter = BlockSizeLimiter::new(&PipelineConfig {
+        max_block_bytes: Some(1 << 20),
+        ..Default::default()
+    });
+    let block_size = 1000;
+    let first_block_size = limiter.next_block_size(block_size, usize::MAX).unwrap();
+    assert!(first_block_size <= block_size);
+    assert!(first_block_size as f64 * MAX_TXN_BYTES <= (1 << 20) as f64);
+
+    // Small transactions fill the configured block size, but not beyond.
+    limiter.record_block(&gen_block(10, 10));
+    assert_eq!(
+        limiter.next_block_size(block_size, usize::MAX),
+        Some(block_size)
+    );
+    // The state checkpoints are left out of the estimate.
+    let small_txn_bytes = limiter.avg_txn_bytes().unwrap();
+    assert_eq!(
+        small_txn_bytes,
+        bcs::serialized_size(&gen_block(1, 10)[0]).unwrap() as f64
+    );
+
+    // Large transactions bring the average, and the block size, down.
+    limiter.record_block(&gen_block(10, 10 * 1024));
+    let shrunk_block_size = limiter.next_block_size(block_size, usize::MAX).unwrap();
+    assert!(shrunk_block_size < block_size);
+    assert!(shrunk_block_size as f64 * limiter.avg_txn_bytes().unwrap() <= (1 << 20) as f64);
+}
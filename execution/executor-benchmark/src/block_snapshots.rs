@@ -0,0 +1,90 @@
+// Copyright © Aptos Foundation
+// SPDX-License-Identifier: Apache-2.0
+
+use crate::transaction_generator::META_FILENAME;
+use aptos_logger::{info, warn};
+use aptos_storage_interface::DbWriter;
+use aptos_types::transaction::Version;
+use std::{
+    fs,
+    path::{Path, PathBuf},
+    time::{Duration, Instant},
+};
+
+/// Takes a checkpoint of the DB every `every_blocks` committed blocks, so that a run can be
+/// resumed from right before a block range that showed a performance anomaly, and that range
+/// profiled in isolation.
+///
+/// Snapshots are RocksDB checkpoints (hard links where possible), written to
+/// `<dir>/block_<index>_version_<version>`. Each one can be passed as `--data-dir` of a later run.
+/// State merkle nodes are persisted asynchronously, so the latest state snapshot in a checkpoint
+/// can be behind its latest version; it is caught up when the checkpoint is opened.
+///
+/// The commit stage is blocked while a snapshot is taken. The time it takes is left out of the
+/// accumulative TPS, but the blocks queued behind it see a higher latency.
+pub struct BlockSnapshotter {
+    dir: PathBuf,
+    every_blocks: usize,
+    next_block_index: usize,
+}
+
+impl BlockSnapshotter {
+    pub fn new(dir: &Path, every_blocks: usize) -> Self {
+        assert!(every_blocks > 0, "Snapshot interval must be positive.");
+        fs::create_dir_all(dir)
+            .unwrap_or_else(|err| panic!("Failed to create snapshot dir {:?}: {}", dir, err));
+        Self {
+            dir: dir.to_path_buf(),
+            every_blocks,
+            next_block_index: 0,
+        }
+    }
+
+    /// Copies the accounts metadata of the source DB into the snapshot dir, from where it is
+    /// copied into every snapshot, as generating transactions on a snapshot needs it.
+    pub fn save_accounts_meta(dir: &Path, source_dir: &Path) {
+        fs::create_dir_all(dir)
+            .unwrap_or_else(|err| panic!("Failed to create snapshot dir {:?}: {}", dir, err));
+        if let Err(err) = fs::copy(source_dir.join(META_FILENAME), dir.join(META_FILENAME)) {
+            warn!(
+                "Failed to copy the accounts metadata into snapshot dir {:?}: {}",
+                dir, err
+            );
+        }
+    }
+
+    /// Called after every committed block, must not be called concurrently with commits.
+    /// Returns how long taking the snapshot took, if one was taken.
+    pub fn record_block(&mut self, db: &dyn DbWriter, version: Version) -> Duration {
+        let block_index = self.next_block_index;
+        self.next_block_index += 1;
+        if (block_index + 1) % self.every_blocks != 0 {
+            return Duration::ZERO;
+        }
+
+        let snapshot_dir = self
+            .dir
+            .join(format!("block_{}_version_{}", block_index, version));
+        let start_time = Instant::now();
+        if let Err(err) = db.create_db_checkpoint(&snapshot_dir) {
+            warn!(
+                "Failed to snapshot the DB after block {}: {:?}",
+                block_index, err
+            );
+            return start_time.elapsed();
+        }
+        let meta_file = self.dir.join(META_FILENAME);
+        if meta_file.exists() {
+            fs::copy(&meta_file, snapshot_dir.join(META_FILENAME))
+                .expect("Failed to copy the accounts metadata into the snapshot.");
+        }
+        info!(
+            "Snapshot of the DB after block {} (version {}) taken in {} ms: {:?}",
+            block_index,
+            version,
+            start_time.elapsed().as_millis(),
+            snapshot_dir
+        );
+        start_time.elapsed()
+    }
+}
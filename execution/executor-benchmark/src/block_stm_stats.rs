@@ -0,0 +1,106 @@
+// Copyright © Aptos Foundation
+// SPDX-License-Identifier: Apache-2.0
+
+use aptos_block_executor::counters::{
+    DEPENDENCY_WAIT_SECONDS, SPECULATIVE_ABORT_COUNT, TASK_EXECUTE_SECONDS, TASK_VALIDATE_SECONDS,
+    VALIDATION_FAILURE_COUNT,
+};
+use serde::Serialize;
+
+/// Scheduler-level counters of the parallel executor (Block-STM).
+///
+/// The underlying counters are process wide, so stats of a run are the difference between
+/// snapshots taken around it. The block executor logs the stats of every block at debug level.
+/// Blocks executed on remote shards are not covered.
+#[derive(Clone, Copy, Debug, Default, Serialize)]
+pub struct BlockStmStats {
+    /// Executed incarnations, i.e. transactions plus re-executions.
+    pub executions: u64,
+    pub validations: u64,
+    pub validation_failures: u64,
+    /// Incarnations aborted after a failed validation, each of which is re-executed.
+    pub aborts: u64,
+    pub dependency_waits: u64,
+    pub dependency_wait_secs: f64,
+}
+
+impl BlockStmStats {
+    pub fn snapshot() -> Self {
+        Self {
+            executions: TASK_EXECUTE_SECONDS.get_sample_count(),
+            validations: TASK_VALIDATE_SECONDS.get_sample_count(),
+            validation_failures: VALIDATION_FAILURE_COUNT.get(),
+            aborts: SPECULATIVE_ABORT_COUNT.get(),
+            dependency_waits: DEPENDENCY_WAIT_SECONDS.get_sample_count(),
+            dependency_wait_secs: DEPENDENCY_WAIT_SECONDS.get_sample_sum(),
+        }
+    }
+
+    pub fn since(&self, start: &Self) -> Self {
+        Self {
+            executions: self.executions - start.executions,
+            validations: self.validations - start.validations,
+            validation_failures: self.validation_failures - start.validation_failures,
+            aborts: self.aborts - start.aborts,
+            dependency_waits: self.dependency_waits - start.dependency_waits,
+            dependency_wait_secs: self.dependency_wait_secs - start.dependency_wait_secs,
+        }
+    }
+
+    /// Re-executions per transaction.
+    pub fn reexecution_ratio(&self, num_txns: u64) -> f64 {
+        self.aborts as f64 / num_txns.max(1) as f64
+    }
+
+    pub fn validation_failure_rate(&self) -> f64 {
+        self.validation_failures as f64 / self.validations.max(1) as f64
+    }
+
+    /// Whether the block was executed by Block-STM at all, as opposed to sequentially, natively
+    /// or on remote shards.
+    pub fn is_empty(&self) -> bool {
+        self.executions == 0
+    }
+
+    pub fn describe(&self, num_txns: u64) -> String {
+        format!(
+            "{} executions for {} txns (re-execution ratio {:.3}), {} of {} validations failed ({:.3}), {} dependency waits ({:.3} secs)",
+            self.executions,
+            num_txns,
+            self.reexecution_ratio(num_txns),
+            self.validation_failures,
+            self.validations,
+            self.validation_failure_rate(),
+            self.dependency_waits,
+            self.dependency_wait_secs,
+        )
+    }
+}
+
+#[test]
+fn test_block_stm_stats_ratios() {
+    let start = BlockStmStats {
+        executions: 100,
+        validations: 100,
+        validation_failures: 10,
+        aborts: 5,
+        dependency_waits: 3,
+        dependency_wait_secs: 1.0,
+    };
+    let end = BlockStmStats {
+        executions: 230,
+        validations: 260,
+        validation_failures: 50,
+        aborts: 35,
+        dependency_waits: 13,
+        dependency_wait_secs: 1.5,
+    };
+    let delta = end.since(&start);
+    assert_eq!(delta.executions, 130);
+    assert_eq!(delta.aborts, 30);
+    assert!((delta.reexecution_ratio(100) - 0.3).abs() < 1e-9);
+    assert!((delta.validation_failure_rate() - 0.25).abs() < 1e-9);
+    assert!(BlockStmStats::default()
+        .since(&BlockStmStats::default())
+        .is_empty());
+}
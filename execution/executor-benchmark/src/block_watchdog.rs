@@ -0,0 +1,146 @@
+// Copyright © Aptos Foundation
+// SPDX-License-Identifier: Apache-2.0
+
+use crate::{
+    metrics::STUCK_BLOCKS,
+    progress_events::{self, ProgressEvent},
+};
+use aptos_crypto::HashValue;
+use aptos_logger::{error, warn};
+use std::{
+    sync::{
+        atomic::{AtomicBool, Ordering},
+        Arc, Mutex,
+    },
+    thread,
+    time::{Duration, Instant},
+};
+
+/// Argument the binary is started with as the child process that traces the threads of its
+/// parent, see `rstack_self`.
+pub const STACKTRACE_ARG: &str = "--stacktrace";
+
+#[derive(Default)]
+struct WatchdogState {
+    /// The block being executed, and when its execution started.
+    current_block: Mutex<Option<(HashValue, Instant)>>,
+    stopped: AtomicBool,
+}
+
+/// Watches the execution of every block on a background thread, and when a block takes longer
+/// than the timeout, logs a warning and the stacks of all threads, so that a hung run leaves
+/// something to diagnose it with. Unlike a CPU profile, the stacks show the threads blocked on a
+/// lock or a channel as well, which is where a deadlocked executor is.
+pub struct BlockWatchdog {
+    state: Arc<WatchdogState>,
+}
+
+impl BlockWatchdog {
+    /// If `abort_on_timeout` is set, the process is aborted once the stacks are dumped, instead of
+    /// waiting for the block indefinitely.
+    pub fn new(timeout: Duration, abort_on_timeout: bool) -> Self {
+        let state = Arc::new(WatchdogState::default());
+        let state_clone = state.clone();
+        thread::Builder::new()
+            .name("block_watchdog".to_string())
+            .spawn(move || watch(state_clone, timeout, abort_on_timeout))
+            .expect("Failed to spawn block watchdog thread.");
+        Self { state }
+    }
+
+    /// Starts watching the execution of a block, until the returned guard is dropped.
+    pub fn watch_block(&self, block_id: HashValue) -> WatchedBlock<'_> {
+        *self.state.current_block.lock().unwrap() = Some((block_id, Instant::now()));
+        WatchedBlock { watchdog: self }
+    }
+}
+
+impl Drop for BlockWatchdog {
+    fn drop(&mut self) {
+        self.state.stopped.store(true, Ordering::Relaxed);
+    }
+}
+
+pub struct WatchedBlock<'a> {
+    watchdog: &'a BlockWatchdog,
+}
+
+impl Drop for WatchedBlock<'_> {
+    fn drop(&mut self) {
+        *self.watchdog.state.current_block.lock().unwrap() = None;
+    }
+}
+
+fn watch(state: Arc<WatchdogState>, timeout: Duration, abort_on_timeout: bool) {
+    let poll_interval = (timeout / 4).clamp(Duration::from_millis(10), Duration::from_secs(1));
+    let mut last_reported = None;
+    while !state.stopped.load(Ordering::Relaxed) {
+        thread::sleep(poll_interval);
+        let (block_id, start_time) = match *state.current_block.lock().unwrap() {
+            Some(current_block) => current_block,
+            None => continue,
+        };
+        let elapsed = start_time.elapsed();
+        if elapsed < timeout || last_reported == Some(block_id) {
+            continue;
+        }
+        last_reported = Some(block_id);
+        STUCK_BLOCKS.inc();
+        warn!(
+            "Block {} has been executing for {:.1} s, over the timeout of {:.1} s. Dumping stacks.",
+            block_id,
+            elapsed.as_secs_f64(),
+            timeout.as_secs_f64(),
+        );
+        progress_events::emit(ProgressEvent::StageStalled {
+            stage: "execution",
+            block_id: block_id.to_hex(),
+            elapsed_secs: elapsed.as_secs_f64(),
+        });
+        dump_stacks();
+        if abort_on_timeout {
+            error!(
+                "Aborting the run, block {} is stuck in execution.",
+                block_id
+            );
+            std::process::abort();
+        }
+    }
+}
+
+#[cfg(target_os = "linux")]
+fn dump_stacks() {
+    let trace = std::env::current_exe()
+        .map_err(|err| err.to_string())
+        .and_then(|exe| {
+            rstack_self::TraceOptions::new()
+                .trace(std::process::Command::new(exe).arg(STACKTRACE_ARG))
+                .map_err(|err| err.to_string())
+        });
+    let trace = match trace {
+        Ok(trace) => trace,
+        Err(err) => {
+            warn!("Failed to dump the stacks of the stuck block: {}", err);
+            return;
+        },
+    };
+    let mut dump = String::new();
+    for thread in trace.threads() {
+        dump.push_str(&format!("Thread {} ({}):\n", thread.id(), thread.name()));
+        for symbol in thread.frames().iter().flat_map(|frame| frame.symbols()) {
+            let name = symbol.name().unwrap_or("(unknown)");
+            match (symbol.file(), symbol.line()) {
+                (Some(file), Some(line)) => {
+                    dump.push_str(&format!("    {} at {}:{}\n", name, file.display(), line))
+                },
+                _ => dump.push_str(&format!("    {}\n", name)),
+            }
+        }
+    }
+    warn!("Stacks of the threads while the block is stuck:\n{}", dump);
+}
+
+#[cfg(not(target_os = "linux"))]
+fn dump_stacks() {
+    warn!("Dumping the stacks of the stuck block is only supported on Linux.");
+}
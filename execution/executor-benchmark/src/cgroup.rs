@@ -0,0 +1,201 @@
+// Copyright © Aptos Foundation
+// SPDX-License-Identifier: Apache-2.0
+
+use aptos_logger::{info, warn};
+use std::{
+    collections::HashMap,
+    fs, io,
+    path::{Path, PathBuf},
+};
+
+const CGROUP_ROOT: &str = "/sys/fs/cgroup";
+/// Period of the CPU bandwidth controller, the quota is expressed relative to it.
+const CPU_PERIOD_US: u64 = 100_000;
+
+/// Runs the benchmark under self-imposed CPU and memory limits, through a cgroup v2 created for
+/// the process, to approximate resource-constrained validator hardware on big machines.
+///
+/// Requires a unified (v2) cgroup hierarchy with the current cgroup delegated to the user running
+/// the benchmark, e.g. by running it under `systemd-run --user --scope -p Delegate=yes`. The
+/// cgroup is removed on drop, the process moved back to the cgroup it started in.
+pub struct CgroupLimits {
+    path: PathBuf,
+    parent: PathBuf,
+    /// Controllers enabled for the children of `parent`.
+    controllers: Vec<&'static str>,
+}
+
+impl CgroupLimits {
+    /// Moves the current process into a new child cgroup with the given limits. Returns `None`,
+    /// after logging why, if the limits cannot be applied on this host.
+    pub fn apply(cpu_quota_cores: Option<f64>, memory_limit_bytes: Option<u64>) -> Option<Self> {
+        if cpu_quota_cores.is_none() && memory_limit_bytes.is_none() {
+            return None;
+        }
+        match Self::try_apply(cpu_quota_cores, memory_limit_bytes) {
+            Ok(limits) => {
+                info!(
+                    "Running in cgroup {:?} with CPU quota {:?} cores, memory limit {:?} bytes",
+                    limits.path, cpu_quota_cores, memory_limit_bytes
+                );
+                Some(limits)
+            },
+            Err(err) => {
+                warn!(
+                    "Failed to apply cgroup limits, running unconstrained: {}. \
+                     A delegated cgroup v2 is required, e.g. run under `systemd-run --user --scope -p Delegate=yes`.",
+                    err
+                );
+                None
+            },
+        }
+    }
+
+    fn try_apply(
+        cpu_quota_cores: Option<f64>,
+        memory_limit_bytes: Option<u64>,
+    ) -> io::Result<Self> {
+        let root = Path::new(CGROUP_ROOT);
+        if !root.join("cgroup.controllers").exists() {
+            return Err(io::Error::new(
+                io::ErrorKind::Unsupported,
+                "cgroup v2 hierarchy not found",
+            ));
+        }
+        let parent = root.join(current_cgroup()?.trim_start_matches('/'));
+        let path = parent.join(format!("executor-benchmark-{}", std::process::id()));
+        fs::create_dir_all(&path)?;
+        // Removed on drop, also if the limits fail to apply.
+        let mut limits = Self {
+            path,
+            parent,
+            controllers: vec![],
+        };
+
+        // A cgroup with processes cannot enable controllers for its children, so move ourselves
+        // out of the parent first.
+        fs::write(
+            limits.path.join("cgroup.procs"),
+            std::process::id().to_string(),
+        )?;
+        let mut controllers = vec![];
+        if cpu_quota_cores.is_some() {
+            controllers.push("cpu");
+        }
+        if memory_limit_bytes.is_some() {
+            controllers.push("memory");
+        }
+        fs::write(
+            limits.parent.join("cgroup.subtree_control"),
+            subtree_control(&controllers, '+'),
+        )?;
+        limits.controllers = controllers;
+
+        if let Some(cores) = cpu_quota_cores {
+            let quota_us = (cores * CPU_PERIOD_US as f64).round() as u64;
+            fs::write(
+                limits.path.join("cpu.max"),
+                format!("{} {}", quota_us, CPU_PERIOD_US),
+            )?;
+        }
+        if let Some(bytes) = memory_limit_bytes {
+            fs::write(limits.path.join("memory.max"), bytes.to_string())?;
+        }
+        Ok(limits)
+    }
+
+    fn remove(&self) -> io::Result<()> {
+        // Controllers enabled for the children of a cgroup keep processes out of it.
+        if !self.controllers.is_empty() {
+            fs::write(
+                self.parent.join("cgroup.subtree_control"),
+                subtree_control(&self.controllers, '-'),
+            )?;
+        }
+        // A cgroup with processes cannot be removed.
+        fs::write(
+            self.parent.join("cgroup.procs"),
+            std::process::id().to_string(),
+        )?;
+        fs::remove_dir(&self.path)
+    }
+
+    /// Logs how much the process was throttled by the limits.
+    pub fn report(&self) {
+        let cpu_stat = self.read_stat("cpu.stat");
+        let memory_events = self.read_stat("memory.events");
+        let get = |stat: &HashMap<String, u64>, key: &str| stat.get(key).copied().unwrap_or(0);
+
+        let nr_periods = get(&cpu_stat, "nr_periods");
+        let nr_throttled = get(&cpu_stat, "nr_throttled");
+        info!(
+            "cgroup CPU throttling: throttled in {} of {} periods ({:.1}%), for a total of {:.3} secs",
+            nr_throttled,
+            nr_periods,
+            100.0 * nr_throttled as f64 / (nr_periods as f64).max(1.0),
+            get(&cpu_stat, "throttled_usec") as f64 / 1_000_000.0,
+        );
+        info!(
+            "cgroup memory: peak {} bytes, {} high events, {} max events, {} OOM kills",
+            fs::read_to_string(self.path.join("memory.peak"))
+                .map(|s| s.trim().to_string())
+                .unwrap_or_else(|_| "unknown".to_string()),
+            get(&memory_events, "high"),
+            get(&memory_events, "max"),
+            get(&memory_events, "oom_kill"),
+        );
+    }
+
+    fn read_stat(&self, file: &str) -> HashMap<String, u64> {
+        fs::read_to_string(self.path.join(file))
+            .map(|content| parse_flat_keyed(&content))
+            .unwrap_or_default()
+    }
+}
+
+impl Drop for CgroupLimits {
+    fn drop(&mut self) {
+        if let Err(err) = self.remove() {
+            warn!("Failed to remove cgroup {:?}: {}", self.path, err);
+        }
+    }
+}
+
+/// Enables (`+`) or disables (`-`) `controllers`, as written to `cgroup.subtree_control`.
+fn subtree_control(controllers: &[&str], op: char) -> String {
+    controllers
+        .iter()
+        .map(|controller| format!("{}{}", op, controller))
+        .collect::<Vec<_>>()
+        .join(" ")
+}
+
+/// Returns the cgroup v2 path of the current process, relative to the hierarchy root.
+fn current_cgroup() -> io::Result<String> {
+    fs::read_to_string("/proc/self/cgroup")?
+        .lines()
+        .find_map(|line| line.strip_prefix("0::").map(str::to_string))
+        .ok_or_else(|| io::Error::new(io::ErrorKind::NotFound, "not in a cgroup v2 hierarchy"))
+}
+
+/// Parses the "flat keyed" format of cgroup stat files, i.e. `key value` lines.
+fn parse_flat_keyed(content: &str) -> HashMap<String, u64> {
+    content
+        .lines()
+        .filter_map(|line| {
+            let (key, value) = line.split_once(' ')?;
+            Some((key.to_string(), value.trim().parse().ok()?))
+        })
+        .collect()
+}
+
+#[test]
+fn test_parse_flat_keyed() {
+    let stat = parse_flat_keyed(
+        "usage_usec 1234\nnr_periods 10\nnr_throttled 3\nthrottled_usec 4500\nbogus\n",
+    );
+    assert_eq!(stat.get("nr_periods"), Some(&10));
+    assert_eq!(stat.get("nr_throttled"), Some(&3));
+    assert_eq!(stat.get("throttled_usec"), Some(&4500));
+    assert_eq!(stat.get("bogus"), None);
+}
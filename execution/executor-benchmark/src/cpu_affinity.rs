@@ -0,0 +1,242 @@
+// Copyright © Aptos Foundation
+// SPDX-License-Identifier: Apache-2.0
+
+use crate::{
+    perf_counters::stage_of_thread,
+    thread_utilization::{parse_stat, read_thread_files, read_thread_stats},
+};
+use anyhow::{ensure, Result};
+use aptos_logger::{info, warn};
+use std::{
+    collections::{BTreeMap, HashMap, HashSet},
+    str::FromStr,
+    sync::{
+        atomic::{AtomicBool, Ordering},
+        Arc,
+    },
+    thread::JoinHandle,
+    time::Duration,
+};
+
+/// How often new threads are looked for, e.g. RocksDB growing its background pools, or the
+/// executor pools being created lazily with the first block.
+const RESCAN_INTERVAL: Duration = Duration::from_millis(500);
+const EXECUTION_STAGE: &str = "execution";
+const ROCKSDB_BACKGROUND_STAGE: &str = "rocksdb_background";
+
+/// A list of cores, like `0-3,8,10-11`.
+#[derive(Clone, Debug, Eq, PartialEq)]
+pub struct CoreList(pub Vec<usize>);
+
+impl FromStr for CoreList {
+    type Err = anyhow::Error;
+
+    fn from_str(s: &str) -> Result<Self> {
+        let mut cores = vec![];
+        for range in s.split(',') {
+            let range = range.trim();
+            match range.split_once('-') {
+                Some((first, last)) => {
+                    let (first, last) = (first.parse::<usize>()?, last.parse::<usize>()?);
+                    ensure!(first <= last, "Empty core range {}", range);
+                    cores.extend(first..=last);
+                },
+                None => cores.push(range.parse::<usize>()?),
+            }
+        }
+        cores.sort_unstable();
+        cores.dedup();
+        Ok(Self(cores))
+    }
+}
+
+/// Keeps the execution threads and the RocksDB background (compaction and flush) threads on
+/// separate sets of cores, so that compactions don't steal CPU from execution, which pollutes
+/// the results on machines with few cores.
+///
+/// Threads are found by name, as RocksDB creates its threads itself. The threads are pinned as
+/// they show up, until the pinner is stopped.
+pub struct CorePinner {
+    stop: Arc<AtomicBool>,
+    handle: JoinHandle<()>,
+}
+
+impl CorePinner {
+    pub fn start(execution_cores: Option<Vec<usize>>, rocksdb_cores: Option<Vec<usize>>) -> Self {
+        if let (Some(execution_cores), Some(rocksdb_cores)) = (&execution_cores, &rocksdb_cores) {
+            if execution_cores
+                .iter()
+                .any(|core| rocksdb_cores.contains(core))
+            {
+                warn!("The execution and RocksDB background core sets overlap.");
+            }
+        }
+        info!(
+            "Pinning execution threads to cores {:?}, and RocksDB background threads to cores {:?}",
+            execution_cores, rocksdb_cores
+        );
+        let stop = Arc::new(AtomicBool::new(false));
+        let stop_clone = stop.clone();
+        let handle = std::thread::Builder::new()
+            .name("core_pinner".to_string())
+            .spawn(move || {
+                pin_until_stopped(
+                    execution_cores.as_deref(),
+                    rocksdb_cores.as_deref(),
+                    &stop_clone,
+                )
+            })
+            .expect("Failed to spawn core pinner.");
+        Self { stop, handle }
+    }
+
+    pub fn stop(self) {
+        self.stop.store(true, Ordering::Relaxed);
+        self.handle.join().expect("Core pinner panicked.");
+    }
+}
+
+fn pin_until_stopped(
+    execution_cores: Option<&[usize]>,
+    rocksdb_cores: Option<&[usize]>,
+    stop: &AtomicBool,
+) {
+    let mut pinned = HashSet::new();
+    let mut num_failures = 0;
+    while !stop.load(Ordering::Relaxed) {
+        for (tid, stat) in read_thread_stats().unwrap_or_default() {
+            let name = match parse_stat(&stat) {
+                Some((name, _)) => name,
+                None => continue,
+            };
+            let cores = match stage_of_thread(&name) {
+                EXECUTION_STAGE => execution_cores,
+                ROCKSDB_BACKGROUND_STAGE => rocksdb_cores,
+                _ => None,
+            };
+            // Thread ids are reused, so a new thread can show up under the id of an old one.
+            let cores = match cores {
+                Some(cores) if pinned.insert((tid, name.clone())) => cores,
+                _ => continue,
+            };
+            if let Err(err) = sys::pin_thread(tid, cores) {
+                num_failures += 1;
+                if num_failures == 1 {
+                    warn!("Failed to pin thread {} ({}): {}", tid, name, err);
+                }
+            }
+        }
+        std::thread::sleep(RESCAN_INTERVAL);
+    }
+    if num_failures > 0 {
+        warn!("Failed to pin {} threads to their cores.", num_failures);
+    }
+}
+
+/// Involuntary context switches of every thread, i.e. how often it was preempted while it could
+/// have kept running, which goes up when other threads compete for its cores.
+pub struct ContentionSnapshot {
+    threads: HashMap<u64, (String, u64)>,
+}
+
+impl ContentionSnapshot {
+    /// Returns `None` if the context switches of threads are not available, e.g. not on Linux.
+    pub fn take() -> Option<Self> {
+        let threads = read_thread_files("status")?
+            .into_iter()
+            .filter_map(|(tid, status)| Some((tid, parse_status(&status)?)))
+            .collect();
+        Some(Self { threads })
+    }
+
+    /// Returns the involuntary context switches per second since `start`, by pipeline stage.
+    pub fn since(&self, start: &ContentionSnapshot, elapsed_secs: f64) -> BTreeMap<String, f64> {
+        let mut by_stage: BTreeMap<String, f64> = BTreeMap::new();
+        for (tid, (name, switches)) in &self.threads {
+            let prev = match start.threads.get(tid) {
+                Some((prev_name, prev)) if prev_name == name => *prev,
+                _ => 0,
+            };
+            *by_stage
+                .entry(stage_of_thread(name).to_string())
+                .or_default() +=
+                switches.saturating_sub(prev) as f64 / elapsed_secs.max(f64::EPSILON);
+        }
+        by_stage
+    }
+}
+
+/// Parses the thread name and the involuntary context switches out of a /proc status file.
+fn parse_status(status: &str) -> Option<(String, u64)> {
+    let get = |key: &str| {
+        status
+            .lines()
+            .find_map(|line| line.strip_prefix(key))
+            .map(str::trim)
+    };
+    Some((
+        get("Name:")?.to_string(),
+        get("nonvoluntary_ctxt_switches:")?.parse::<u64>().ok()?,
+    ))
+}
+
+/// Logs how often the threads of the execution and RocksDB background stages were preempted.
+pub fn log_contention(switches_per_sec: &BTreeMap<String, f64>) {
+    for stage in [EXECUTION_STAGE, ROCKSDB_BACKGROUND_STAGE] {
+        if let Some(switches) = switches_per_sec.get(stage) {
+            info!(
+                "Contention: {} threads were preempted {:.0} times per second",
+                stage, switches
+            );
+        }
+    }
+}
+
+#[cfg(target_os = "linux")]
+mod sys {
+    use anyhow::{bail, Result};
+    use libc::{cpu_set_t, CPU_SET, CPU_SETSIZE};
+    use std::io;
+
+    pub(super) fn pin_thread(tid: u64, cores: &[usize]) -> Result<()> {
+        // SAFETY: cpu_set_t is a plain bit set, for which all zeros is the empty set.
+        let mut cpu_set: cpu_set_t = unsafe { std::mem::zeroed() };
+        for &core in cores {
+            if core >= CPU_SETSIZE as usize {
+                bail!("Core {} is out of range", core);
+            }
+            // SAFETY: the core is in range of the set.
+            unsafe { CPU_SET(core, &mut cpu_set) };
+        }
+        // SAFETY: `cpu_set` outlives the call, and its size is given.
+        let result = unsafe {
+            libc::sched_setaffinity(
+                tid as libc::pid_t,
+                std::mem::size_of::<cpu_set_t>(),
+                &cpu_set,
+            )
+        };
+        if result != 0 {
+            return Err(io::Error::last_os_error().into());
+        }
+        Ok(())
+    }
+}
+
+#[cfg(not(target_os = "linux"))]
+mod sys {
+    use anyhow::{bail, Result};
+
+    pub(super) fn pin_thread(_tid: u64, _cores: &[usize]) -> Result<()> {
+        bail!("Pinning threads to cores is only supported on Linux")
+    }
+}
+
+#[test]
+fn test_parse_core_list() {
+    let parse = |s: &str| s.parse::<CoreList>().map(|cores| cores.0);
+    assert_eq!(parse("0-3,8,10-11").unwrap(), vec![0, 1, 2, 3, 8, 10, 11]);
+    assert_eq!(parse("5, 2-3, 3").unwrap(), vec![2, 3, 5]);
+    assert!(parse("3-1").is_err());
+    assert!(parse("a").is_err());
+}
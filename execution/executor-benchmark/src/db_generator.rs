@@ -3,21 +3,162 @@
 // SPDX-License-Identifier: Apache-2.0
 
 use crate::{add_accounts_impl, PipelineConfig};
-use aptos_config::{
-    config::{
-        PrunerConfig, RocksdbConfigs, StorageDirPaths, BUFFERED_STATE_TARGET_ITEMS,
-        DEFAULT_MAX_NUM_NODES_PER_LRU_CACHE_SHARD, NO_OP_STORAGE_PRUNER_CONFIG,
-    },
-    utils::get_genesis_txn,
+use aptos_config::config::{
+    PrunerConfig, RocksdbConfigs, StorageDirPaths, BUFFERED_STATE_TARGET_ITEMS,
+    DEFAULT_MAX_NUM_NODES_PER_LRU_CACHE_SHARD, NO_OP_STORAGE_PRUNER_CONFIG,
 };
 use aptos_db::AptosDB;
 use aptos_executor::{
     block_executor::TransactionBlockExecutor,
     db_bootstrapper::{generate_waypoint, maybe_bootstrap},
 };
+use aptos_gas_schedule::{AptosGasParameters, InitialGasSchedule, ToOnChainGasSchedule};
+use aptos_genesis::builder::{Builder, GenesisConfiguration};
 use aptos_storage_interface::DbReaderWriter;
+use aptos_temppath::TempPath;
+use aptos_types::{
+    on_chain_config::{Features, GasScheduleV2, OnChainConfig},
+    state_store::state_key::StateKey,
+    transaction::{ChangeSet, Transaction, WriteSetPayload},
+    write_set::WriteOp,
+};
 use aptos_vm::AptosVM;
-use std::{fs, path::Path};
+use rand::{rngs::StdRng, SeedableRng};
+use std::{
+    fs,
+    path::{Path, PathBuf},
+    sync::Arc,
+};
+
+/// Customization of the genesis a benchmark DB is bootstrapped with, so that the benchmark chain
+/// can match the on-chain configs of a specific network instead of the test genesis defaults.
+#[derive(Clone, Debug, Default)]
+pub struct GenesisOptions {
+    /// Bootstrap with this bcs serialized genesis transaction (e.g. a genesis.blob) instead.
+    /// Accounts are funded from the core resources account, so it has to be a test genesis using
+    /// the test root key.
+    pub genesis_blob: Option<PathBuf>,
+    pub epoch_duration_secs: Option<u64>,
+    /// Feature version of the gas schedule, the parameters are the initial ones of that version.
+    pub gas_feature_version: Option<u64>,
+    /// Feature flags (by number, as in features.move) to enable on top of the defaults.
+    pub enable_features: Vec<u64>,
+    /// Feature flags (by number, as in features.move) to disable.
+    pub disable_features: Vec<u64>,
+}
+
+impl GenesisOptions {
+    fn has_overrides(&self) -> bool {
+        self.epoch_duration_secs.is_some()
+            || self.gas_feature_version.is_some()
+            || !self.enable_features.is_empty()
+            || !self.disable_features.is_empty()
+    }
+
+    fn genesis_txn(&self) -> Transaction {
+        if let Some(path) = &self.genesis_blob {
+            assert!(
+                !self.has_overrides(),
+                "On-chain config overrides cannot be applied to a custom genesis blob."
+            );
+            let bytes = fs::read(path).expect("Failed to read genesis blob.");
+            return bcs::from_bytes(&bytes).expect("Failed to deserialize genesis blob.");
+        }
+
+        let epoch_duration_secs = self.epoch_duration_secs;
+        let gas_feature_version = self.gas_feature_version;
+        let path = TempPath::new();
+        path.create_as_dir().unwrap();
+        // Same seed as the test config, so the root key the benchmark uses to fund accounts
+        // matches.
+        let (_root_key, genesis, _waypoint, _validators) = Builder::new(
+            path.path(),
+            aptos_cached_packages::head_release_bundle().clone(),
+        )
+        .unwrap()
+        .with_init_genesis_config(Some(Arc::new(
+            move |genesis_config: &mut GenesisConfiguration| {
+                if let Some(epoch_duration_secs) = epoch_duration_secs {
+                    genesis_config.epoch_duration_secs = epoch_duration_secs;
+                }
+                if let Some(feature_version) = gas_feature_version {
+                    genesis_config.gas_schedule = GasScheduleV2 {
+                        feature_version,
+                        entries: AptosGasParameters::initial()
+                            .to_on_chain_gas_schedule(feature_version),
+                    };
+                }
+            },
+        )))
+        .build(StdRng::from_seed([0; 32]))
+        .unwrap();
+
+        if self.enable_features.is_empty() && self.disable_features.is_empty() {
+            genesis
+        } else {
+            self.override_features(genesis)
+        }
+    }
+
+    /// Genesis initializes the feature flags to a hard-coded default set, so they are overridden
+    /// directly in its write set.
+    fn override_features(&self, genesis: Transaction) -> Transaction {
+        let change_set = match genesis {
+            Transaction::GenesisTransaction(WriteSetPayload::Direct(change_set)) => change_set,
+            _ => panic!("Genesis must be a direct write set."),
+        };
+        let (write_set, events) = change_set.into_inner();
+        let state_key = StateKey::access_path(Features::access_path().unwrap());
+        let mut features: Features = bcs::from_bytes(
+            write_set
+                .get(&state_key)
+                .and_then(|op| op.bytes())
+                .expect("Genesis must initialize the feature flags."),
+        )
+        .unwrap();
+        for flag in &self.enable_features {
+            set_feature(&mut features, *flag, true);
+        }
+        for flag in &self.disable_features {
+            set_feature(&mut features, *flag, false);
+        }
+        let data = bcs::to_bytes(&features).unwrap().into();
+        let op = match write_set.get(&state_key).unwrap() {
+            WriteOp::Creation(_) => WriteOp::Creation(data),
+            WriteOp::Modification(_) => WriteOp::Modification(data),
+            WriteOp::CreationWithMetadata { metadata, .. } => WriteOp::CreationWithMetadata {
+                data,
+                metadata: metadata.clone(),
+            },
+            WriteOp::ModificationWithMetadata { metadata, .. } => {
+                WriteOp::ModificationWithMetadata {
+                    data,
+                    metadata: metadata.clone(),
+                }
+            },
+            _ => unreachable!("Feature flags have bytes."),
+        };
+        let mut write_set = write_set.into_mut();
+        write_set.insert((state_key, op));
+        Transaction::GenesisTransaction(WriteSetPayload::Direct(ChangeSet::new(
+            write_set.freeze().unwrap(),
+            events,
+        )))
+    }
+}
+
+fn set_feature(features: &mut Features, flag: u64, enabled: bool) {
+    let byte_index = (flag / 8) as usize;
+    let bit_mask = 1 << (flag % 8);
+    if features.features.len() <= byte_index {
+        features.features.resize(byte_index + 1, 0);
+    }
+    if enabled {
+        features.features[byte_index] |= bit_mask;
+    } else {
+        features.features[byte_index] &= !bit_mask;
+    }
+}
 
 pub fn create_db_with_accounts<V>(
     num_accounts: usize,
@@ -28,6 +169,7 @@ pub fn create_db_with_accounts<V>(
     verify_sequence_numbers: bool,
     enable_storage_sharding: bool,
     pipeline_config: PipelineConfig,
+    genesis_options: &GenesisOptions,
 ) where
     V: TransactionBlockExecutor + 'static,
 {
@@ -39,7 +181,7 @@ pub fn create_db_with_accounts<V>(
     // create if not exists
     fs::create_dir_all(db_dir.as_ref()).unwrap();
 
-    bootstrap_with_genesis(&db_dir, enable_storage_sharding);
+    bootstrap_with_genesis(&db_dir, enable_storage_sharding, genesis_options);
 
     println!(
         "Finished empty DB creation, DB dir: {}. Creating accounts now...",
@@ -59,8 +201,12 @@ pub fn create_db_with_accounts<V>(
     );
 }
 
-fn bootstrap_with_genesis(db_dir: impl AsRef<Path>, enable_storage_sharding: bool) {
-    let (config, _genesis_key) = aptos_genesis::test_utils::test_config();
+fn bootstrap_with_genesis(
+    db_dir: impl AsRef<Path>,
+    enable_storage_sharding: bool,
+    genesis_options: &GenesisOptions,
+) {
+    let genesis_txn = genesis_options.genesis_txn();
 
     let mut rocksdb_configs = RocksdbConfigs::default();
     rocksdb_configs.state_merkle_db_config.max_open_files = -1;
@@ -79,6 +225,6 @@ fn bootstrap_with_genesis(db_dir: impl AsRef<Path>, enable_storage_sharding: boo
     );
 
     // Bootstrap db with genesis
-    let waypoint = generate_waypoint::<AptosVM>(&db_rw, get_genesis_txn(&config).unwrap()).unwrap();
-    maybe_bootstrap::<AptosVM>(&db_rw, get_genesis_txn(&config).unwrap(), waypoint).unwrap();
+    let waypoint = generate_waypoint::<AptosVM>(&db_rw, &genesis_txn).unwrap();
+    maybe_bootstrap::<AptosVM>(&db_rw, &genesis_txn, waypoint).unwrap();
 }
@@ -0,0 +1,222 @@
+// Copyright © Aptos Foundation
+// SPDX-License-Identifier: Apache-2.0
+
+use crate::metrics::INJECTED_TXNS;
+use aptos_crypto::HashValue;
+use aptos_logger::{info, warn};
+use aptos_types::transaction::{Transaction, TransactionStatus};
+use rand::{rngs::StdRng, Rng, SeedableRng};
+use std::{
+    collections::{BTreeMap, HashMap, VecDeque},
+    sync::Mutex,
+};
+
+/// Number of committed user transactions kept around to be replayed in later blocks.
+const MAX_REPLAY_CANDIDATES: usize = 10_000;
+
+/// How many duplicate and replayed transactions are injected into the generated blocks, to
+/// exercise how the executor deduplicates and validates them under load.
+#[derive(Clone, Copy, Debug, Default)]
+pub struct DuplicateInjection {
+    /// Fraction of user transactions that are sent twice in their block, the copy (with the same
+    /// sender and sequence number) placed somewhere after the original.
+    pub duplicate_rate: f64,
+    /// Number of transactions of earlier blocks replayed in every block, as a fraction of its
+    /// user transactions.
+    pub replay_rate: f64,
+}
+
+impl DuplicateInjection {
+    pub fn is_enabled(&self) -> bool {
+        self.duplicate_rate > 0.0 || self.replay_rate > 0.0
+    }
+}
+
+#[derive(Clone, Copy, Debug)]
+pub(crate) enum InjectedKind {
+    Duplicate,
+    Replay,
+}
+
+impl InjectedKind {
+    fn name(&self) -> &'static str {
+        match self {
+            Self::Duplicate => "duplicate",
+            Self::Replay => "replay",
+        }
+    }
+}
+
+/// Injects the transactions into the blocks on their way to partitioning.
+pub(crate) struct DuplicateInjector {
+    config: DuplicateInjection,
+    rng: StdRng,
+    replay_candidates: VecDeque<Transaction>,
+}
+
+impl DuplicateInjector {
+    pub(crate) fn new(config: DuplicateInjection) -> Self {
+        Self {
+            config,
+            rng: StdRng::from_entropy(),
+            replay_candidates: VecDeque::new(),
+        }
+    }
+
+    /// Returns the block with the injected transactions, and the positions they ended up at. The
+    /// last transaction of the block (the state checkpoint) stays last.
+    pub(crate) fn inject(
+        &mut self,
+        mut txns: Vec<Transaction>,
+    ) -> (Vec<Transaction>, Vec<(usize, InjectedKind)>) {
+        let last_txn = txns.pop();
+        let num_txns = txns.len() as f64;
+        // The originals keep their order, and the injected transactions are sorted in at random
+        // keys, a duplicate after its original. The sort is stable, so a duplicate with the key of
+        // its original still comes after it.
+        let mut keyed = Vec::with_capacity(txns.len());
+        for (index, txn) in txns.iter().enumerate() {
+            keyed.push((index as f64, None, txn.clone()));
+            if !matches!(txn, Transaction::UserTransaction(_)) {
+                continue;
+            }
+            if self.rng.gen_bool(self.config.duplicate_rate) {
+                let key = self.rng.gen_range(index as f64, num_txns);
+                keyed.push((key, Some(InjectedKind::Duplicate), txn.clone()));
+            }
+            if !self.replay_candidates.is_empty() && self.rng.gen_bool(self.config.replay_rate) {
+                let replayed =
+                    &self.replay_candidates[self.rng.gen_range(0, self.replay_candidates.len())];
+                let key = self.rng.gen_range(0.0, num_txns);
+                keyed.push((key, Some(InjectedKind::Replay), replayed.clone()));
+            }
+        }
+        keyed.sort_by(|(a, _, _), (b, _, _)| a.total_cmp(b));
+
+        // The transactions of this block are replayed in later blocks, once they are committed.
+        for txn in txns {
+            if matches!(txn, Transaction::UserTransaction(_)) {
+                if self.replay_candidates.len() == MAX_REPLAY_CANDIDATES {
+                    self.replay_candidates.pop_front();
+                }
+                self.replay_candidates.push_back(txn);
+            }
+        }
+
+        let mut injected = vec![];
+        let mut block = Vec::with_capacity(keyed.len() + 1);
+        for (index, (_, kind, txn)) in keyed.into_iter().enumerate() {
+            if let Some(kind) = kind {
+                injected.push((index, kind));
+            }
+            block.push(txn);
+        }
+        block.extend(last_txn);
+        (block, injected)
+    }
+}
+
+/// Outcomes of the injected transactions, by kind.
+#[derive(Default)]
+pub struct InjectedTxns {
+    pending: Mutex<HashMap<HashValue, Vec<(usize, InjectedKind)>>>,
+    outcomes: Mutex<BTreeMap<String, u64>>,
+}
+
+impl InjectedTxns {
+    /// Remembers the positions of the transactions injected into the block with `block_id`, to
+    /// be looked at once the block is through the ledger update.
+    pub(crate) fn add_block(&self, block_id: HashValue, injected: Vec<(usize, InjectedKind)>) {
+        if !injected.is_empty() {
+            self.pending.lock().unwrap().insert(block_id, injected);
+        }
+    }
+
+    /// Records how the ledger update of the block handled the transactions injected into it.
+    pub(crate) fn record_statuses(&self, block_id: HashValue, statuses: &[TransactionStatus]) {
+        let injected = match self.pending.lock().unwrap().remove(&block_id) {
+            Some(injected) => injected,
+            None => return,
+        };
+        let mut outcomes = self.outcomes.lock().unwrap();
+        for (index, kind) in injected {
+            let outcome = match &statuses[index] {
+                // A transaction the executor should have rejected.
+                TransactionStatus::Keep(_) => "executed".to_string(),
+                TransactionStatus::Discard(status_code) => format!("discarded_{:?}", status_code),
+                TransactionStatus::Retry => "retried".to_string(),
+            };
+            INJECTED_TXNS
+                .with_label_values(&[kind.name(), &outcome])
+                .inc();
+            *outcomes
+                .entry(format!("{}/{}", kind.name(), outcome))
+                .or_default() += 1;
+        }
+    }
+
+    /// Returns the number of injected transactions by kind and outcome, e.g.
+    /// `duplicate/discarded_SEQUENCE_NUMBER_TOO_OLD`.
+    pub fn outcomes(&self) -> BTreeMap<String, u64> {
+        self.outcomes.lock().unwrap().clone()
+    }
+}
+
+pub fn log_injected_txn_outcomes(outcomes: &BTreeMap<String, u64>) {
+    for (outcome, count) in outcomes {
+        if outcome.ends_with("/executed") {
+            warn!(
+                "Injected transactions: {} {}, the executor didn't reject them",
+                count, outcome
+            );
+        } else {
+            info!("Injected transactions: {} {}", count, outcome);
+        }
+    }
+}
+
+#[test]
+fn test_inject_duplicates() {
+    use crate::transaction_generator::TransactionGenerator;
+    use aptos_sdk::types::LocalAccount;
+
+    let mut rng = StdRng::seed_from_u64(0);
+    let transaction_factory = TransactionGenerator::create_transaction_factory();
+    let account = LocalAccount::generate(&mut rng);
+    let gen_block = |num_txns: usize| {
+        (0..num_txns)
+            .map(|_| {
+                Transaction::UserTransaction(account.sign_with_transaction_builder(
+                    transaction_factory.transfer(account.address(), 1),
+                ))
+            })
+            .chain(std::iter::once(Transaction::StateCheckpoint(
+                HashValue::zero(),
+            )))
+            .collect::<Vec<_>>()
+    };
+    let mut injector = DuplicateInjector::new(DuplicateInjection {
+        duplicate_rate: 0.5,
+        replay_rate: 0.5,
+    });
+    let first_block = gen_block(50);
+    let (block, injected) = injector.inject(first_block.clone());
+    // Nothing to replay in the first block.
+    assert!(injected
+        .iter()
+        .all(|(_, kind)| matches!(kind, InjectedKind::Duplicate)));
+    assert_eq!(block.len(), first_block.len() + injected.len());
+    assert_eq!(block.last(), first_block.last());
+    for (index, _) in injected {
+        // Every copy comes after its original.
+        let original = block.iter().position(|txn| *txn == block[index]).unwrap();
+        assert!(original < index);
+    }
+
+    let (block, injected) = injector.inject(gen_block(50));
+    for (index, kind) in injected {
+        if let InjectedKind::Replay = kind {
+            assert!(first_block.contains(&block[index]));
+        }
+    }
+}
@@ -0,0 +1,50 @@
+// Copyright © Aptos Foundation
+// SPDX-License-Identifier: Apache-2.0
+
+use aptos_config::config::{RocksdbConfigs, RocksdbDurability};
+use clap::ValueEnum;
+use once_cell::sync::OnceCell;
+use serde::{Deserialize, Serialize};
+
+static DURABILITY: OnceCell<Durability> = OnceCell::new();
+
+/// How durable the commits to the DB are, to measure what the fsyncs of the WAL cost on a given
+/// storage device.
+#[derive(Clone, Copy, Debug, Default, Deserialize, Eq, PartialEq, Serialize, ValueEnum)]
+#[serde(rename_all = "snake_case")]
+pub enum Durability {
+    /// Fsync the WAL on every write, as nodes do.
+    #[default]
+    Strict,
+    /// Write the WAL without fsyncing it.
+    Relaxed,
+    /// Don't write the WAL at all.
+    None,
+}
+
+impl From<Durability> for RocksdbDurability {
+    fn from(durability: Durability) -> Self {
+        match durability {
+            Durability::Strict => RocksdbDurability::Strict,
+            Durability::Relaxed => RocksdbDurability::Relaxed,
+            Durability::None => RocksdbDurability::None,
+        }
+    }
+}
+
+pub fn set_durability_once(durability: Durability) {
+    DURABILITY.set(durability).ok();
+}
+
+pub fn get_durability() -> Durability {
+    DURABILITY.get().copied().unwrap_or_default()
+}
+
+/// Sets the durability set with `set_durability_once` on all the DBs.
+pub fn apply_durability(rocksdb_configs: &mut RocksdbConfigs) {
+    let durability = get_durability().into();
+    rocksdb_configs.ledger_db_config.durability = durability;
+    rocksdb_configs.state_merkle_db_config.durability = durability;
+    rocksdb_configs.state_kv_db_config.durability = durability;
+    rocksdb_configs.index_db_config.durability = durability;
+}
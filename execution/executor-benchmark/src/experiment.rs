@@ -0,0 +1,252 @@
+// Copyright © Aptos Foundation
+// SPDX-License-Identifier: Apache-2.0
+
+use crate::durability::Durability;
+use anyhow::{ensure, Context, Result};
+use aptos_logger::{info, warn};
+use clap::ValueEnum;
+use itertools::iproduct;
+use serde::Deserialize;
+use std::{
+    fs::{self, File},
+    io::{BufWriter, Write},
+    path::Path,
+    process::Command,
+};
+
+/// Parameter grid of an experiment, read from YAML, e.g.:
+///
+/// ```yaml
+/// block_sizes: [1000, 10000]
+/// execution_threads: [8, 16, 32]
+/// num_executor_shards: [0, 4]
+/// durability: [strict, relaxed]
+/// blocks: 100
+/// extra_args: ["--transactions-per-sender", "1"]
+/// ```
+#[derive(Debug, Deserialize)]
+pub struct ExperimentGrid {
+    pub block_sizes: Vec<usize>,
+    pub execution_threads: Vec<usize>,
+    #[serde(default = "default_num_executor_shards")]
+    pub num_executor_shards: Vec<usize>,
+    #[serde(default = "default_durability")]
+    pub durability: Vec<Durability>,
+    /// Number of blocks of every run.
+    pub blocks: usize,
+    /// Passed as is to every run, before the subcommand.
+    #[serde(default)]
+    pub extra_args: Vec<String>,
+}
+
+fn default_num_executor_shards() -> Vec<usize> {
+    vec![0]
+}
+
+fn default_durability() -> Vec<Durability> {
+    vec![Durability::Strict]
+}
+
+impl ExperimentGrid {
+    pub fn load(path: &Path) -> Result<Self> {
+        let file = File::open(path).with_context(|| format!("Cannot open grid {:?}", path))?;
+        let grid: Self = serde_yaml::from_reader(file)?;
+        ensure!(
+            !grid.block_sizes.is_empty()
+                && !grid.execution_threads.is_empty()
+                && !grid.num_executor_shards.is_empty()
+                && !grid.durability.is_empty(),
+            "Every dimension of the grid needs at least one value."
+        );
+        Ok(grid)
+    }
+
+    fn points(&self) -> Vec<GridPoint> {
+        iproduct!(
+            &self.block_sizes,
+            &self.execution_threads,
+            &self.num_executor_shards,
+            &self.durability
+        )
+        .map(
+            |(&block_size, &execution_threads, &num_executor_shards, &durability)| GridPoint {
+                block_size,
+                execution_threads,
+                num_executor_shards,
+                durability,
+            },
+        )
+        .collect()
+    }
+}
+
+#[derive(Clone, Copy, Debug)]
+struct GridPoint {
+    block_size: usize,
+    execution_threads: usize,
+    num_executor_shards: usize,
+    durability: Durability,
+}
+
+/// Headline numbers of a run, read back from the results it wrote.
+struct ExperimentRow {
+    point: GridPoint,
+    /// `None` if the run failed.
+    results: Option<serde_json::Value>,
+}
+
+const RESULT_COLUMNS: [&str; 6] = [
+    "tps",
+    "vm_tps",
+    "gps",
+    "gas_per_txn",
+    "commit_ms_per_block",
+    "elapsed_secs",
+];
+
+/// Runs every combination of the grid as a separate run of this binary, since the concurrency
+/// level and the number of shards can only be set once per process. Every run benchmarks a fresh
+/// checkpoint of the same DB in `data_dir`. The results are printed as a single table, and also
+/// written as CSV to `output_csv`, if given.
+pub fn run_experiment(
+    grid: &ExperimentGrid,
+    data_dir: &Path,
+    checkpoint_dir: &Path,
+    output_csv: Option<&Path>,
+) -> Result<()> {
+    let binary = std::env::current_exe()?;
+    let results_dir = checkpoint_dir.with_extension("experiment_results");
+    fs::create_dir_all(&results_dir)?;
+
+    let points = grid.points();
+    let mut rows = Vec::with_capacity(points.len());
+    for (index, point) in points.into_iter().enumerate() {
+        let results_path = results_dir.join(format!("run_{}.json", index));
+        info!("Experiment run {}: {:?}", index, point);
+        let status = Command::new(&binary)
+            .arg("--block-size")
+            .arg(point.block_size.to_string())
+            .arg("--execution-threads")
+            .arg(point.execution_threads.to_string())
+            .arg("--num-executor-shards")
+            .arg(point.num_executor_shards.to_string())
+            .arg("--durability")
+            .arg(durability_arg(point.durability))
+            .arg("--results-json")
+            .arg(&results_path)
+            .args(&grid.extra_args)
+            .arg("run-executor")
+            .arg("--blocks")
+            .arg(grid.blocks.to_string())
+            .arg("--data-dir")
+            .arg(data_dir)
+            .arg("--checkpoint-dir")
+            .arg(checkpoint_dir)
+            .status()?;
+        let results = if status.success() {
+            read_results(&results_path)
+                .map_err(|err| warn!("Cannot read the results of run {}: {:?}", index, err))
+                .ok()
+        } else {
+            warn!("Experiment run {} failed with {}", index, status);
+            None
+        };
+        rows.push(ExperimentRow { point, results });
+    }
+
+    print_table(&rows);
+    if let Some(output_csv) = output_csv {
+        write_csv(&rows, output_csv)?;
+        info!("Wrote the experiment results to {:?}", output_csv);
+    }
+    Ok(())
+}
+
+fn durability_arg(durability: Durability) -> String {
+    durability
+        .to_possible_value()
+        .expect("Durability values are not skipped.")
+        .get_name()
+        .to_string()
+}
+
+fn read_results(path: &Path) -> Result<serde_json::Value> {
+    Ok(serde_json::from_reader(File::open(path)?)?)
+}
+
+fn result_cell(row: &ExperimentRow, column: &str) -> String {
+    row.results
+        .as_ref()
+        .and_then(|results| results[column].as_f64())
+        .map_or_else(|| "-".to_string(), |value| format!("{:.1}", value))
+}
+
+fn print_table(rows: &[ExperimentRow]) {
+    println!(
+        "{:>10} {:>8} {:>7} {:>10} {}",
+        "block_size",
+        "threads",
+        "shards",
+        "durability",
+        RESULT_COLUMNS
+            .iter()
+            .map(|column| format!("{:>14}", column))
+            .collect::<Vec<_>>()
+            .join(" ")
+    );
+    for row in rows {
+        println!(
+            "{:>10} {:>8} {:>7} {:>10} {}",
+            row.point.block_size,
+            row.point.execution_threads,
+            row.point.num_executor_shards,
+            durability_arg(row.point.durability),
+            RESULT_COLUMNS
+                .iter()
+                .map(|column| format!("{:>14}", result_cell(row, column)))
+                .collect::<Vec<_>>()
+                .join(" ")
+        );
+    }
+}
+
+fn write_csv(rows: &[ExperimentRow], path: &Path) -> Result<()> {
+    let mut writer = BufWriter::new(File::create(path)?);
+    writeln!(
+        writer,
+        "block_size,execution_threads,num_executor_shards,durability,{}",
+        RESULT_COLUMNS.join(",")
+    )?;
+    for row in rows {
+        writeln!(
+            writer,
+            "{},{},{},{},{}",
+            row.point.block_size,
+            row.point.execution_threads,
+            row.point.num_executor_shards,
+            durability_arg(row.point.durability),
+            RESULT_COLUMNS
+                .iter()
+                .map(|column| result_cell(row, column))
+                .collect::<Vec<_>>()
+                .join(",")
+        )?;
+    }
+    writer.flush()?;
+    Ok(())
+}
+
+#[test]
+fn test_grid_points() {
+    let grid: ExperimentGrid = serde_yaml::from_str(
+        "block_sizes: [100, 1000]\nexecution_threads: [4, 8, 16]\nblocks: 10\n",
+    )
+    .unwrap();
+    let points = grid.points();
+    assert_eq!(points.len(), 6);
+    assert!(points
+        .iter()
+        .all(|point| point.num_executor_shards == 0 && point.durability == Durability::Strict));
+    assert_eq!(points[1].block_size, 100);
+    assert_eq!(points[1].execution_threads, 8);
+}
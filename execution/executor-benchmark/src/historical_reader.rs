@@ -0,0 +1,141 @@
+// Copyright © Aptos Foundation
+// SPDX-License-Identifier: Apache-2.0
+
+use crate::metrics::TIMER;
+use aptos_logger::{info, warn};
+use aptos_storage_interface::DbReader;
+use aptos_types::{state_store::state_key::StateKey, transaction::Version};
+use rand::{seq::IteratorRandom, thread_rng, Rng};
+use std::{
+    sync::{
+        atomic::{AtomicBool, Ordering},
+        Arc,
+    },
+    thread::JoinHandle,
+    time::{Duration, Instant},
+};
+
+/// Issues historical state reads against past versions at a fixed rate while the benchmark is
+/// writing, to model fullnode API load coexisting with execution.
+///
+/// Each read picks a random recent version, one of the state keys written there, and reads the
+/// value of that key at a random version between then and the latest one.
+pub struct HistoricalReader {
+    stop: Arc<AtomicBool>,
+    join_handle: JoinHandle<ReadStats>,
+}
+
+#[derive(Default)]
+struct ReadStats {
+    latencies: Vec<Duration>,
+    num_errors: usize,
+    elapsed: Duration,
+}
+
+impl HistoricalReader {
+    pub fn start(db: Arc<dyn DbReader>, qps: f64, max_lag_versions: u64) -> Self {
+        assert!(qps > 0.0, "Historical read QPS must be positive.");
+        let stop = Arc::new(AtomicBool::new(false));
+        let stop_clone = stop.clone();
+        let join_handle = std::thread::Builder::new()
+            .name("historical_reader".to_string())
+            .spawn(move || Self::run(db, qps, max_lag_versions, stop_clone))
+            .expect("Failed to spawn historical reader thread.");
+        Self { stop, join_handle }
+    }
+
+    fn run(
+        db: Arc<dyn DbReader>,
+        qps: f64,
+        max_lag_versions: u64,
+        stop: Arc<AtomicBool>,
+    ) -> ReadStats {
+        let interval = Duration::from_secs_f64(1.0 / qps);
+        let start = Instant::now();
+        let mut next_read = start;
+        let mut stats = ReadStats::default();
+        while !stop.load(Ordering::Relaxed) {
+            let now = Instant::now();
+            if next_read > now {
+                std::thread::sleep(next_read - now);
+            }
+            // If reads are slower than the requested rate, we don't try to catch up.
+            next_read = next_read.max(now) + interval;
+
+            match Self::read_once(db.as_ref(), max_lag_versions) {
+                Ok(Some(latency)) => {
+                    TIMER
+                        .with_label_values(&["historical_read"])
+                        .observe(latency.as_secs_f64());
+                    stats.latencies.push(latency);
+                },
+                Ok(None) => {},
+                Err(err) => {
+                    stats.num_errors += 1;
+                    warn!("Historical read failed: {}", err);
+                },
+            }
+        }
+        stats.elapsed = start.elapsed();
+        stats
+    }
+
+    /// Returns the latency of the historical read, or `None` if no key to read was found.
+    fn read_once(db: &dyn DbReader, max_lag_versions: u64) -> anyhow::Result<Option<Duration>> {
+        let mut rng = thread_rng();
+        let latest_version = db.get_latest_version()?;
+        let written_version = rng.gen_range(
+            latest_version.saturating_sub(max_lag_versions),
+            latest_version + 1,
+        );
+        let state_key = match Self::written_key(db, written_version)? {
+            Some(state_key) => state_key,
+            None => return Ok(None),
+        };
+        let read_version: Version = rng.gen_range(written_version, latest_version + 1);
+
+        let read_start = Instant::now();
+        db.get_state_value_by_version(&state_key, read_version)?;
+        Ok(Some(read_start.elapsed()))
+    }
+
+    fn written_key(db: &dyn DbReader, version: Version) -> anyhow::Result<Option<StateKey>> {
+        let write_set = match db.get_write_set_iterator(version, 1)?.next() {
+            Some(write_set) => write_set?,
+            None => return Ok(None),
+        };
+        Ok(write_set
+            .iter()
+            .map(|(state_key, _)| state_key)
+            .choose(&mut thread_rng())
+            .cloned())
+    }
+
+    pub fn stop_and_report(self) {
+        self.stop.store(true, Ordering::Relaxed);
+        let ReadStats {
+            mut latencies,
+            num_errors,
+            elapsed,
+        } = self
+            .join_handle
+            .join()
+            .expect("Historical reader thread panicked.");
+        latencies.sort();
+        let percentile = |p: usize| {
+            latencies
+                .get((latencies.len() * p / 100).min(latencies.len().saturating_sub(1)))
+                .map_or(0.0, |latency| latency.as_secs_f64() * 1000.0)
+        };
+        info!(
+            "Historical reads: {} reads ({:.1} QPS), {} errors, latency p50 {:.3} ms, p90 {:.3} ms, p99 {:.3} ms, max {:.3} ms",
+            latencies.len(),
+            latencies.len() as f64 / elapsed.as_secs_f64(),
+            num_errors,
+            percentile(50),
+            percentile(90),
+            percentile(99),
+            percentile(100),
+        );
+    }
+}
@@ -0,0 +1,186 @@
+// Copyright © Aptos Foundation
+// SPDX-License-Identifier: Apache-2.0
+
+use crate::thread_utilization::{parse_stat, CLOCK_TICKS_PER_SEC};
+use aptos_logger::info;
+use serde::Serialize;
+use std::{collections::BTreeMap, fs};
+
+/// Bucket for the I/O of threads that exited during the run, only known from the process totals.
+const EXITED_THREADS: &str = "exited_threads";
+
+/// Disk I/O and CPU time of the threads of a pipeline stage over the run.
+#[derive(Clone, Debug, Default, Serialize)]
+pub struct StageIo {
+    pub read_bytes: u64,
+    pub write_bytes: u64,
+    pub cpu_secs: f64,
+}
+
+#[derive(Clone, Debug, Default)]
+struct ThreadIo {
+    name: String,
+    read_bytes: u64,
+    write_bytes: u64,
+    cpu_ticks: u64,
+}
+
+/// Per-thread I/O counters of the process, from /proc/self/task/<tid>/io, which count the bytes
+/// actually fetched from and sent to the storage layer (i.e. not page cache hits).
+#[derive(Debug, Default)]
+pub struct IoSnapshot {
+    threads: BTreeMap<u64, ThreadIo>,
+    process_read_bytes: u64,
+    process_write_bytes: u64,
+}
+
+impl IoSnapshot {
+    /// Returns `None` if per-thread I/O accounting is not available, e.g. not on Linux or
+    /// without task I/O accounting in the kernel.
+    pub fn take() -> Option<Self> {
+        let (process_read_bytes, process_write_bytes) =
+            parse_io(&fs::read_to_string("/proc/self/io").ok()?)?;
+        let mut threads = BTreeMap::new();
+        for entry in fs::read_dir("/proc/self/task").ok()? {
+            let entry = entry.ok()?;
+            let tid = match entry
+                .file_name()
+                .to_str()
+                .and_then(|s| s.parse::<u64>().ok())
+            {
+                Some(tid) => tid,
+                None => continue,
+            };
+            // Threads can exit between listing and reading, skip them.
+            let io = fs::read_to_string(entry.path().join("io"))
+                .ok()
+                .and_then(|io| parse_io(&io));
+            let stat = fs::read_to_string(entry.path().join("stat"))
+                .ok()
+                .and_then(|stat| parse_stat(&stat));
+            if let (Some((read_bytes, write_bytes)), Some((name, cpu_ticks))) = (io, stat) {
+                threads.insert(tid, ThreadIo {
+                    name,
+                    read_bytes,
+                    write_bytes,
+                    cpu_ticks,
+                });
+            }
+        }
+        Some(Self {
+            threads,
+            process_read_bytes,
+            process_write_bytes,
+        })
+    }
+
+    /// Attributes the I/O since `start` to pipeline stages, by thread name.
+    pub fn since(&self, start: &IoSnapshot) -> BTreeMap<String, StageIo> {
+        let mut by_stage: BTreeMap<String, StageIo> = BTreeMap::new();
+        let (mut attributed_read, mut attributed_write) = (0, 0);
+        for (tid, thread) in &self.threads {
+            let prev = start.threads.get(tid).cloned().unwrap_or_default();
+            let read_bytes = thread.read_bytes.saturating_sub(prev.read_bytes);
+            let write_bytes = thread.write_bytes.saturating_sub(prev.write_bytes);
+            attributed_read += read_bytes;
+            attributed_write += write_bytes;
+            let stage = by_stage
+                .entry(stage_of_thread(&thread.name).to_string())
+                .or_default();
+            stage.read_bytes += read_bytes;
+            stage.write_bytes += write_bytes;
+            stage.cpu_secs +=
+                thread.cpu_ticks.saturating_sub(prev.cpu_ticks) as f64 / CLOCK_TICKS_PER_SEC;
+        }
+        // The process counters include threads that are gone by now.
+        let exited = StageIo {
+            read_bytes: (self.process_read_bytes - start.process_read_bytes)
+                .saturating_sub(attributed_read),
+            write_bytes: (self.process_write_bytes - start.process_write_bytes)
+                .saturating_sub(attributed_write),
+            cpu_secs: 0.0,
+        };
+        if exited.read_bytes > 0 || exited.write_bytes > 0 {
+            by_stage.insert(EXITED_THREADS.to_string(), exited);
+        }
+        by_stage
+    }
+}
+
+/// Parses read_bytes and write_bytes out of a /proc io file.
+fn parse_io(io: &str) -> Option<(u64, u64)> {
+    let get = |key: &str| {
+        io.lines()
+            .find_map(|line| line.strip_prefix(key))
+            .and_then(|value| value.trim().parse::<u64>().ok())
+    };
+    Some((get("read_bytes:")?, get("write_bytes:")?))
+}
+
+/// Maps a thread to the pipeline stage it works for. Names are truncated to 15 characters.
+pub(crate) fn stage_of_thread(name: &str) -> &'static str {
+    match name {
+        "block_partition" => "partitioning",
+        "txn_executor" => "execution",
+        "ledger_update" => "ledger_update",
+        "txn_committer" => "commit",
+        "state-committer" | "state_batch_com" => "state_snapshot_commit",
+        "output_exporter" => "output_export",
+        _ if name.starts_with("rocksdb:") => "rocksdb_background",
+        _ if name.ends_with("_pruner") => "pruning",
+        _ => "other",
+    }
+}
+
+pub fn log_stage_io(stage_io: &BTreeMap<String, StageIo>, elapsed_secs: f64) {
+    const MB: f64 = 1024.0 * 1024.0;
+    for (stage, io) in stage_io {
+        if io.read_bytes == 0 && io.write_bytes == 0 {
+            continue;
+        }
+        info!(
+            "Disk I/O of {}: read {:.1} MB ({:.1} MB/s), written {:.1} MB ({:.1} MB/s), with {:.1} CPU secs",
+            stage,
+            io.read_bytes as f64 / MB,
+            io.read_bytes as f64 / MB / elapsed_secs,
+            io.write_bytes as f64 / MB,
+            io.write_bytes as f64 / MB / elapsed_secs,
+            io.cpu_secs,
+        );
+    }
+}
+
+#[test]
+fn test_io_attribution() {
+    let io = "rchar: 100\nwchar: 200\nsyscr: 1\nsyscw: 2\nread_bytes: 4096\nwrite_bytes: 8192\ncancelled_write_bytes: 0\n";
+    assert_eq!(parse_io(io), Some((4096, 8192)));
+
+    let thread = |name: &str, read_bytes, write_bytes| ThreadIo {
+        name: name.to_string(),
+        read_bytes,
+        write_bytes,
+        cpu_ticks: 0,
+    };
+    let start = IoSnapshot {
+        threads: [(1, thread("txn_committer", 10, 100))]
+            .into_iter()
+            .collect(),
+        process_read_bytes: 10,
+        process_write_bytes: 100,
+    };
+    let end = IoSnapshot {
+        threads: [
+            (1, thread("txn_committer", 10, 600)),
+            (2, thread("rocksdb:low", 50, 1000)),
+        ]
+        .into_iter()
+        .collect(),
+        process_read_bytes: 100,
+        process_write_bytes: 1700,
+    };
+    let by_stage = end.since(&start);
+    assert_eq!(by_stage["commit"].write_bytes, 500);
+    assert_eq!(by_stage["rocksdb_background"].read_bytes, 50);
+    assert_eq!(by_stage[EXITED_THREADS].read_bytes, 40);
+    assert_eq!(by_stage[EXITED_THREADS].write_bytes, 100);
+}
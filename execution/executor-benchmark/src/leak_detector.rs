@@ -0,0 +1,352 @@
+// Copyright © Aptos Foundation
+// SPDX-License-Identifier: Apache-2.0
+
+use aptos_logger::{info, warn};
+use std::{
+    collections::{BTreeMap, VecDeque},
+    path::{Path, PathBuf},
+    process::Command,
+    sync::mpsc,
+    thread::JoinHandle,
+    time::Duration,
+};
+
+/// A size class is reported once its allocated bytes didn't decrease over this many samples.
+const GROWTH_WINDOW: usize = 10;
+/// Growth over the window below this is not reported, e.g. caches warming up.
+const MIN_GROWTH_BYTES: u64 = 16 << 20;
+/// Number of allocation sites listed when a growth is reported with heap profiling enabled.
+const NUM_TOP_GROWTH_SITES: usize = 10;
+/// Where the heap profiles are dumped, collected into the run artifacts like the profiler's.
+const HEAP_PROFILE_DIR: &str = "profiling_results";
+
+/// A size class whose allocated bytes grew over the whole window.
+#[derive(Debug, Eq, PartialEq)]
+struct SizeClassGrowth {
+    /// Size of the allocations of the class, in bytes.
+    size: u64,
+    allocated_bytes: u64,
+    growth_bytes: u64,
+}
+
+/// Tracks the allocated bytes of every jemalloc size class over the last samples.
+struct GrowthTracker {
+    window: usize,
+    min_growth_bytes: u64,
+    history: BTreeMap<u64, VecDeque<u64>>,
+}
+
+impl GrowthTracker {
+    fn new(window: usize, min_growth_bytes: u64) -> Self {
+        Self {
+            window,
+            min_growth_bytes,
+            history: BTreeMap::new(),
+        }
+    }
+
+    /// Records the allocated bytes by size class, and returns the classes that grew
+    /// monotonically over the window, by most growth first. The history of a returned class is
+    /// reset, so that it is only reported again after growing over another whole window.
+    fn record(&mut self, allocated_by_size: &BTreeMap<u64, u64>) -> Vec<SizeClassGrowth> {
+        let mut growths = vec![];
+        for (&size, &allocated_bytes) in allocated_by_size {
+            let history = self.history.entry(size).or_default();
+            if history.back().map_or(false, |last| allocated_bytes < *last) {
+                history.clear();
+            }
+            history.push_back(allocated_bytes);
+            if history.len() > self.window {
+                history.pop_front();
+            }
+            let growth_bytes = allocated_bytes - history.front().copied().unwrap_or_default();
+            if history.len() == self.window && growth_bytes >= self.min_growth_bytes {
+                growths.push(SizeClassGrowth {
+                    size,
+                    allocated_bytes,
+                    growth_bytes,
+                });
+                history.clear();
+                history.push_back(allocated_bytes);
+            }
+        }
+        growths.sort_by(|a, b| b.growth_bytes.cmp(&a.growth_bytes));
+        growths
+    }
+}
+
+/// Samples the jemalloc stats in the background during soak runs, and warns about size classes
+/// whose allocated bytes keep growing, so slow leaks in the executor and storage show up in
+/// benchmarks. With heap profiling enabled (`--memory-profiling`, with jemalloc started with
+/// `prof:true`), a heap profile is dumped along with every warning, and the allocation sites
+/// that grew the most since the previous dump are listed.
+pub struct LeakDetector {
+    stop_sender: mpsc::Sender<()>,
+    handle: JoinHandle<()>,
+}
+
+impl LeakDetector {
+    pub fn start(interval: Duration) -> Option<Self> {
+        if jemalloc::allocated_by_size_class().is_none() {
+            warn!("Cannot read the jemalloc stats, not checking for memory leaks.");
+            return None;
+        }
+        let (stop_sender, stop_receiver) = mpsc::channel();
+        let handle = std::thread::Builder::new()
+            .name("leak_detector".to_string())
+            .spawn(move || sample_until_stopped(interval, stop_receiver))
+            .expect("Failed to spawn leak detector.");
+        Some(Self {
+            stop_sender,
+            handle,
+        })
+    }
+
+    /// Stops sampling right away, without waiting for the end of the current interval.
+    pub fn stop(self) {
+        drop(self.stop_sender);
+        self.handle.join().expect("Leak detector panicked.")
+    }
+}
+
+/// Samples every `interval` until the sender of `stop_receiver` is dropped.
+fn sample_until_stopped(interval: Duration, stop_receiver: mpsc::Receiver<()>) {
+    let mut tracker = GrowthTracker::new(GROWTH_WINDOW, MIN_GROWTH_BYTES);
+    let mut heap_profiles = HeapProfiles::new();
+    let mut num_reported = 0;
+    while let Err(mpsc::RecvTimeoutError::Timeout) = stop_receiver.recv_timeout(interval) {
+        let allocated_by_size = match jemalloc::allocated_by_size_class() {
+            Some(allocated_by_size) => allocated_by_size,
+            None => continue,
+        };
+        let growths = tracker.record(&allocated_by_size);
+        if growths.is_empty() {
+            continue;
+        }
+        num_reported += growths.len();
+        let total_allocated_mb = allocated_by_size.values().sum::<u64>() >> 20;
+        for growth in &growths {
+            warn!(
+                "Possible memory leak: allocations of {} bytes grew by {} MB over the last {} samples, to {} MB ({} MB allocated in total).",
+                growth.size,
+                growth.growth_bytes >> 20,
+                GROWTH_WINDOW,
+                growth.allocated_bytes >> 20,
+                total_allocated_mb,
+            );
+        }
+        if let Some(heap_profiles) = &mut heap_profiles {
+            heap_profiles.report_growth_sites();
+        }
+    }
+    if num_reported == 0 {
+        info!("Leak detector: no size class kept growing.");
+    } else {
+        warn!(
+            "Leak detector: {} growths of size classes reported during the run.",
+            num_reported
+        );
+    }
+}
+
+/// Heap profiles dumped along with the growth warnings, each of them diffed against the
+/// previous one, or against the one dumped when the detector started.
+struct HeapProfiles {
+    binary_path: PathBuf,
+    previous: PathBuf,
+    num_dumped: usize,
+}
+
+impl HeapProfiles {
+    /// Returns `None` if heap profiling is not enabled.
+    fn new() -> Option<Self> {
+        if !jemalloc::heap_profiling_active() {
+            return None;
+        }
+        let binary_path = std::env::current_exe().ok()?;
+        let previous = Self::dump(0)?;
+        Some(Self {
+            binary_path,
+            previous,
+            num_dumped: 1,
+        })
+    }
+
+    fn dump(index: usize) -> Option<PathBuf> {
+        std::fs::create_dir_all(HEAP_PROFILE_DIR).ok()?;
+        let path = Path::new(HEAP_PROFILE_DIR).join(format!("leak_detector_{}.heap", index));
+        if let Err(err) = jemalloc::dump_heap_profile(&path) {
+            warn!("Failed to dump the heap profile to {:?}: {}", path, err);
+            return None;
+        }
+        Some(path)
+    }
+
+    fn report_growth_sites(&mut self) {
+        let current = match Self::dump(self.num_dumped) {
+            Some(current) => current,
+            None => return,
+        };
+        self.num_dumped += 1;
+        let output = Command::new("jeprof")
+            .arg("--show_bytes")
+            .arg("--text")
+            .arg(format!("--base={}", self.previous.display()))
+            .arg(&self.binary_path)
+            .arg(&current)
+            .output();
+        match output {
+            Ok(output) if output.status.success() => {
+                let text = String::from_utf8_lossy(&output.stdout);
+                let top_sites = text
+                    .lines()
+                    .skip_while(|line| !line.starts_with("Total:"))
+                    .take(NUM_TOP_GROWTH_SITES + 1)
+                    .collect::<Vec<_>>()
+                    .join("\n");
+                warn!(
+                    "Top allocation sites by growth since {:?}:\n{}",
+                    self.previous, top_sites
+                );
+            },
+            _ => warn!(
+                "Cannot run jeprof, diff the heap profiles with `jeprof --text --base={} <binary> {}`.",
+                self.previous.display(),
+                current.display()
+            ),
+        }
+        self.previous = current;
+    }
+}
+
+#[cfg(unix)]
+mod jemalloc {
+    use anyhow::{bail, Result};
+    use std::{collections::BTreeMap, ffi::CString, os::unix::ffi::OsStrExt, path::Path};
+
+    /// Stands for all arenas in the `stats.arenas.<i>` names.
+    const MALLCTL_ARENAS_ALL: u32 = 4096;
+
+    fn read<T: Copy + Default>(name: &str) -> Option<T> {
+        let name = CString::new(name).ok()?;
+        let mut value = T::default();
+        let mut len = std::mem::size_of::<T>();
+        // SAFETY: `value` is as large as `len` says, and both outlive the call.
+        let result = unsafe {
+            jemalloc_sys::mallctl(
+                name.as_ptr(),
+                &mut value as *mut T as *mut _,
+                &mut len,
+                std::ptr::null_mut(),
+                0,
+            )
+        };
+        (result == 0 && len == std::mem::size_of::<T>()).then_some(value)
+    }
+
+    fn write<T>(name: &str, mut value: T) -> Result<()> {
+        let name = CString::new(name)?;
+        // SAFETY: `value` is as large as the given size, and outlives the call.
+        let result = unsafe {
+            jemalloc_sys::mallctl(
+                name.as_ptr(),
+                std::ptr::null_mut(),
+                std::ptr::null_mut(),
+                &mut value as *mut T as *mut _,
+                std::mem::size_of::<T>(),
+            )
+        };
+        if result != 0 {
+            bail!("mallctl {:?} failed with {}", name, result);
+        }
+        Ok(())
+    }
+
+    /// Returns the bytes currently allocated in every size class, small (bins) and large
+    /// (extents), over all arenas.
+    pub(super) fn allocated_by_size_class() -> Option<BTreeMap<u64, u64>> {
+        // The stats are only updated when the epoch is advanced.
+        write("epoch", 1u64).ok()?;
+        let mut allocated_by_size = BTreeMap::new();
+        for bin in 0..read::<u32>("arenas.nbins")? {
+            let size = read::<usize>(&format!("arenas.bin.{}.size", bin))?;
+            let regions = read::<usize>(&format!(
+                "stats.arenas.{}.bins.{}.curregs",
+                MALLCTL_ARENAS_ALL, bin
+            ))?;
+            allocated_by_size.insert(size as u64, (size * regions) as u64);
+        }
+        for extent in 0..read::<u32>("arenas.nlextents")? {
+            let size = read::<usize>(&format!("arenas.lextent.{}.size", extent))?;
+            let extents = read::<usize>(&format!(
+                "stats.arenas.{}.lextents.{}.curlextents",
+                MALLCTL_ARENAS_ALL, extent
+            ))?;
+            allocated_by_size.insert(size as u64, (size * extents) as u64);
+        }
+        Some(allocated_by_size)
+    }
+
+    pub(super) fn heap_profiling_active() -> bool {
+        read::<bool>("opt.prof").unwrap_or(false) && read::<bool>("prof.active").unwrap_or(false)
+    }
+
+    pub(super) fn dump_heap_profile(path: &Path) -> Result<()> {
+        let path = CString::new(path.as_os_str().as_bytes())?;
+        write("prof.dump", path.as_ptr())
+    }
+}
+
+#[cfg(not(unix))]
+mod jemalloc {
+    use anyhow::{bail, Result};
+    use std::{collections::BTreeMap, path::Path};
+
+    pub(super) fn allocated_by_size_class() -> Option<BTreeMap<u64, u64>> {
+        None
+    }
+
+    pub(super) fn heap_profiling_active() -> bool {
+        false
+    }
+
+    pub(super) fn dump_heap_profile(_path: &Path) -> Result<()> {
+        bail!("jemalloc is only used on unix")
+    }
+}
+
+#[test]
+fn test_leak_detector_stops_right_away() {
+    if let Some(leak_detector) = LeakDetector::start(Duration::from_secs(3600)) {
+        let start = std::time::Instant::now();
+        leak_detector.stop();
+        assert!(start.elapsed() < Duration::from_secs(60));
+    }
+}
+
+#[test]
+fn test_growth_tracker() {
+    let mut tracker = GrowthTracker::new(3, 100);
+    let sample = |small: u64, large: u64| BTreeMap::from([(8, small), (4096, large)]);
+
+    assert_eq!(tracker.record(&sample(0, 1000)), vec![]);
+    assert_eq!(tracker.record(&sample(50, 1100)), vec![]);
+    // Only the small class grew monotonically over the window, but too little.
+    assert_eq!(tracker.record(&sample(60, 900)), vec![]);
+    assert_eq!(tracker.record(&sample(200, 1000)), vec![SizeClassGrowth {
+        size: 8,
+        allocated_bytes: 200,
+        growth_bytes: 150,
+    }]);
+    assert_eq!(tracker.record(&sample(400, 1200)), vec![SizeClassGrowth {
+        size: 4096,
+        allocated_bytes: 1200,
+        growth_bytes: 300,
+    }]);
+    // Reported again only after growing over another whole window.
+    assert_eq!(tracker.record(&sample(500, 1300)), vec![SizeClassGrowth {
+        size: 8,
+        allocated_bytes: 500,
+        growth_bytes: 300,
+    }]);
+}
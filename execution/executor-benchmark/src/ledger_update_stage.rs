@@ -2,7 +2,11 @@
 // Parts of the project are originally copyright © Meta Platforms, Inc.
 // SPDX-License-Identifier: Apache-2.0
 
-use crate::pipeline::{CommitBlockMessage, LedgerUpdateMessage};
+use crate::{
+    block_retry::with_block_retries,
+    duplicate_injection::InjectedTxns,
+    pipeline::{CommitBlockMessage, LedgerUpdateMessage},
+};
 use aptos_executor::block_executor::{BlockExecutor, TransactionBlockExecutor};
 use aptos_executor_types::BlockExecutorTrait;
 use aptos_types::transaction::Version;
@@ -15,6 +19,8 @@ pub struct LedgerUpdateStage<V> {
     version: Version,
     allow_discards: bool,
     allow_aborts: bool,
+    injected_txns: Option<Arc<InjectedTxns>>,
+    max_block_retries: usize,
 }
 
 impl<V> LedgerUpdateStage<V>
@@ -27,6 +33,8 @@ where
         version: Version,
         allow_discards: bool,
         allow_aborts: bool,
+        injected_txns: Option<Arc<InjectedTxns>>,
+        max_block_retries: usize,
     ) -> Self {
         Self {
             executor,
@@ -34,6 +42,8 @@ where
             commit_sender,
             allow_discards,
             allow_aborts,
+            injected_txns,
+            max_block_retries,
         }
     }
 
@@ -47,13 +57,35 @@ where
             parent_block_id,
             state_checkpoint_output,
             first_block_start_time,
+            retry_block,
         } = ledger_update_message;
 
-        let output = self
-            .executor
-            .ledger_update(block_id, parent_block_id, state_checkpoint_output)
-            .unwrap();
+        let mut maybe_state_checkpoint_output = Some(state_checkpoint_output);
+        let output = with_block_retries(
+            "ledger_update",
+            block_id,
+            self.max_block_retries,
+            |_attempt_number| {
+                // The output of the execution is consumed by a failed ledger update, so the
+                // block is executed again (from its retry copy) to retry it.
+                let state_checkpoint_output = match maybe_state_checkpoint_output.take() {
+                    Some(state_checkpoint_output) => state_checkpoint_output,
+                    None => self.executor.execute_and_state_checkpoint(
+                        retry_block
+                            .clone()
+                            .expect("Retried blocks must have a retry copy."),
+                        parent_block_id,
+                        None,
+                    )?,
+                };
+                self.executor
+                    .ledger_update(block_id, parent_block_id, state_checkpoint_output)
+            },
+        );
 
+        if let Some(injected_txns) = &self.injected_txns {
+            injected_txns.record_statuses(block_id, output.compute_status());
+        }
         let num_txns = output.compute_status().len();
         self.version += num_txns as Version;
         let discards = output
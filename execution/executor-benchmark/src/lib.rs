@@ -3,26 +3,87 @@
 // SPDX-License-Identifier: Apache-2.0
 
 mod account_generator;
+pub mod account_pool;
+pub mod adaptive_block_size;
+pub mod artifacts;
+pub mod backup_under_load;
+pub mod bench_fixtures;
+mod block_arrival;
 pub mod block_preparation;
+mod block_retry;
+pub mod block_sidecar;
+mod block_size_limiter;
+pub mod block_snapshots;
+pub mod block_stm_stats;
+pub mod block_watchdog;
+pub mod cgroup;
+pub mod cpu_affinity;
 pub mod db_access;
 pub mod db_generator;
 mod db_reliable_submitter;
+pub mod duplicate_injection;
+pub mod durability;
+pub mod experiment;
+mod historical_reader;
+pub mod io_accounting;
+pub mod leak_detector;
 mod ledger_update_stage;
-mod metrics;
+pub mod mempool_capture;
+pub mod metrics;
+pub mod metrics_db;
+pub mod module_cache;
 pub mod native_executor;
+mod output_exporter;
+pub mod perf_counters;
 pub mod pipeline;
+pub mod post_commit;
+pub mod progress_events;
+pub mod query_state;
+pub mod repro_bundle;
+pub mod results;
+pub mod rw_set_estimation;
+pub mod serve;
+mod shadow_verifier;
+mod sharded_verifier;
+pub mod slow_storage;
+pub mod stage_delay;
+mod starvation_detector;
+pub mod striped_storage;
+pub mod thread_utilization;
 pub mod transaction_committer;
 pub mod transaction_executor;
 pub mod transaction_generator;
+pub mod txn_type_stats;
+pub mod txns_per_sender;
+pub mod verify_db;
 
 use crate::{
-    db_access::DbAccessUtil, pipeline::Pipeline, transaction_committer::TransactionCommitter,
-    transaction_executor::TransactionExecutor, transaction_generator::TransactionGenerator,
+    account_pool::WorkloadAccountsPool,
+    block_snapshots::BlockSnapshotter,
+    block_stm_stats::BlockStmStats,
+    cpu_affinity::{ContentionSnapshot, CorePinner},
+    db_access::DbAccessUtil,
+    db_generator::GenesisOptions,
+    duplicate_injection::log_injected_txn_outcomes,
+    historical_reader::HistoricalReader,
+    io_accounting::IoSnapshot,
+    leak_detector::LeakDetector,
+    metrics::BLOCK_RETRIES,
+    module_cache::log_module_load_stats,
+    perf_counters::PerfCounterSampler,
+    pipeline::Pipeline,
+    progress_events::Phase,
+    results::BenchmarkResults,
+    thread_utilization::ThreadUtilizationSampler,
+    transaction_committer::TransactionCommitter,
+    transaction_executor::TransactionExecutor,
+    transaction_generator::TransactionGenerator,
+    txns_per_sender::{log_txns_per_sender, TxnsPerSenderPolicy},
 };
 use aptos_block_executor::counters::{self as block_executor_counters, GasType};
 use aptos_block_partitioner::v2::counters::BLOCK_PARTITIONING_SECONDS;
-use aptos_config::config::{NodeConfig, PrunerConfig};
-use aptos_db::AptosDB;
+use aptos_config::config::{NodeConfig, PrunerConfig, NO_OP_STORAGE_PRUNER_CONFIG};
+use aptos_db::{db_debugger::truncate, AptosDB};
 use aptos_executor::{
     block_executor::{BlockExecutor, TransactionBlockExecutor},
     metrics::{
@@ -31,6 +92,8 @@ use aptos_executor::{
         APTOS_EXECUTOR_VM_EXECUTE_BLOCK_SECONDS, APTOS_PROCESSED_TXNS_OUTPUT_SIZE,
     },
 };
+use aptos_executor_service::{hedging, remote_executor_client, warm_standby};
+use aptos_executor_types::BlockExecutorTrait;
 use aptos_jellyfish_merkle::metrics::{
     APTOS_JELLYFISH_INTERNAL_ENCODED_BYTES, APTOS_JELLYFISH_LEAF_ENCODED_BYTES,
 };
@@ -38,16 +101,20 @@ use aptos_logger::{info, warn};
 use aptos_metrics_core::Histogram;
 use aptos_sdk::types::LocalAccount;
 use aptos_storage_interface::{state_view::LatestDbStateCheckpointView, DbReader, DbReaderWriter};
+use aptos_temppath::TempPath;
 use aptos_transaction_generator_lib::{
-    create_txn_generator_creator, TransactionGeneratorCreator, TransactionType,
-    TransactionType::NonConflictingCoinTransfer,
+    create_txn_generator_creator, AccountsPool, TransactionGeneratorCreator, TransactionType,
+    TransactionType::NonConflictingCoinTransfer, TxnTypeTags,
 };
+use aptos_types::transaction::Version;
+use clap::Parser;
 use db_reliable_submitter::DbReliableTransactionSubmitter;
+use move_vm_runtime::module_load_stats::ModuleLoadStats;
 use pipeline::PipelineConfig;
 use std::{
-    collections::HashMap,
+    collections::{BTreeMap, HashMap},
     fs,
-    path::Path,
+    path::{Path, PathBuf},
     sync::{atomic::AtomicUsize, Arc},
     time::Instant,
 };
@@ -57,18 +124,20 @@ pub fn init_db_and_executor<V>(config: &NodeConfig) -> (DbReaderWriter, BlockExe
 where
     V: TransactionBlockExecutor,
 {
-    let db = DbReaderWriter::new(
+    let mut rocksdb_configs = config.storage.rocksdb_configs;
+    durability::apply_durability(&mut rocksdb_configs);
+    let db = slow_storage::maybe_slow_down(DbReaderWriter::new(
         AptosDB::open(
             config.storage.get_dir_paths(),
             false, /* readonly */
             config.storage.storage_pruner_config,
-            config.storage.rocksdb_configs,
+            rocksdb_configs,
             false,
             config.storage.buffered_state_target_items,
             config.storage.max_num_nodes_per_lru_cache_shard,
         )
         .expect("DB should open."),
-    );
+    ));
 
     let executor = BlockExecutor::new(db.clone());
 
@@ -90,13 +159,60 @@ fn create_checkpoint(
         .expect("db checkpoint creation fails.");
 }
 
+/// Deletes everything committed after `version` from the checkpoint, so that the run continues
+/// from a mid-history state of the DB. Falls back to the closest earlier version a block ended
+/// at, if `version` is in the middle of a block. The metadata of the checkpoint is written with
+/// the number of accounts of the source DB that are left.
+fn truncate_checkpoint(
+    checkpoint_dir: &Path,
+    source_dir: &Path,
+    version: Version,
+    enable_storage_sharding: bool,
+) {
+    let db_dir = checkpoint_dir.to_string_lossy().to_string();
+    let target_version = version.to_string();
+    let mut args = vec![
+        "truncate",
+        "--db-dir",
+        &db_dir,
+        "--target-version",
+        &target_version,
+        // The checkpoint is a copy already.
+        "--opt-out-backup-checkpoint",
+    ];
+    if enable_storage_sharding {
+        args.push("--enable-storage-sharding");
+    }
+    truncate::Cmd::try_parse_from(args)
+        .expect("Truncate arguments must be valid.")
+        .run()
+        .unwrap_or_else(|err| {
+            panic!(
+                "Failed to truncate the DB to version {}: {:?}",
+                version, err
+            )
+        });
+
+    let num_accounts = TransactionGenerator::count_existing_accounts(
+        verify_db::open_readonly_db(checkpoint_dir, enable_storage_sharding)
+            .unwrap_or_else(|err| panic!("Cannot open the truncated DB: {:#}", err)),
+        TransactionGenerator::read_meta(&source_dir)
+            .unwrap_or_else(|err| panic!("Cannot read the accounts metadata: {:#}", err)),
+    );
+    info!(
+        "{} accounts are left in the DB truncated to version {}",
+        num_accounts, version
+    );
+    TransactionGenerator::write_num_accounts(&checkpoint_dir, num_accounts);
+}
+
 /// Runs the benchmark with given parameters.
 #[allow(clippy::too_many_arguments)]
 pub fn run_benchmark<V>(
     block_size: usize,
     num_blocks: usize,
     transaction_mix: Option<Vec<(TransactionType, usize)>>,
-    mut transactions_per_sender: usize,
+    mut transactions_per_sender: TxnsPerSenderPolicy,
     connected_tx_grps: usize,
     shuffle_connected_txns: bool,
     hotspot_probability: Option<f32>,
@@ -104,27 +220,64 @@ pub fn run_benchmark<V>(
     num_additional_dst_pool_accounts: usize,
     source_dir: impl AsRef<Path>,
     checkpoint_dir: impl AsRef<Path>,
+    stripe_dirs: &[PathBuf],
+    start_version: Option<Version>,
     verify_sequence_numbers: bool,
     pruner_config: PrunerConfig,
     enable_storage_sharding: bool,
     pipeline_config: PipelineConfig,
-) where
+) -> BenchmarkResults
+where
     V: TransactionBlockExecutor + 'static,
 {
+    progress_events::phase_changed(Phase::PrepareDb);
     create_checkpoint(
         source_dir.as_ref(),
         checkpoint_dir.as_ref(),
         enable_storage_sharding,
     );
+    // The metadata of a truncated checkpoint is of its own.
+    let meta_dir = match start_version {
+        Some(start_version) => {
+            truncate_checkpoint(
+                checkpoint_dir.as_ref(),
+                source_dir.as_ref(),
+                start_version,
+                enable_storage_sharding,
+            );
+            checkpoint_dir.as_ref()
+        },
+        None => source_dir.as_ref(),
+    };
+    if let Some(snapshot_dir) = &pipeline_config.snapshot_dir {
+        BlockSnapshotter::save_accounts_meta(snapshot_dir, meta_dir);
+    }
 
     let (mut config, genesis_key) = aptos_genesis::test_utils::test_config();
     config.storage.dir = checkpoint_dir.as_ref().to_path_buf();
     config.storage.storage_pruner_config = pruner_config;
     config.storage.rocksdb_configs.enable_storage_sharding = enable_storage_sharding;
+    if !stripe_dirs.is_empty() {
+        assert!(
+            enable_storage_sharding,
+            "Striping the DB across directories requires storage sharding."
+        );
+        let dirs = std::iter::once(checkpoint_dir.as_ref().to_path_buf())
+            .chain(stripe_dirs.iter().cloned())
+            .collect::<Vec<_>>();
+        config.storage.db_path_overrides = Some(striped_storage::stripe_db(&dirs));
+    }
 
     let (db, executor) = init_db_and_executor::<V>(&config);
+    // Transactions of mixed workloads are tagged with their type, to break the results down.
+    let txn_type_tags = transaction_mix
+        .as_ref()
+        .filter(|mix| mix.len() > 1)
+        .map(|_| Arc::new(TxnTypeTags::default()));
     let transaction_generator_creator = transaction_mix.clone().map(|transaction_mix| {
-        let num_existing_accounts = TransactionGenerator::read_meta(&source_dir);
+        progress_events::phase_changed(Phase::InitWorkload);
+        let num_existing_accounts = TransactionGenerator::read_meta(&meta_dir)
+            .unwrap_or_else(|err| panic!("Cannot read the accounts metadata: {:#}", err));
         let num_accounts_to_be_loaded = std::cmp::min(
             num_existing_accounts,
             num_main_signer_accounts + num_additional_dst_pool_accounts,
@@ -145,10 +298,15 @@ pub fn run_benchmark<V>(
 
         let accounts_cache =
             TransactionGenerator::gen_user_account_cache(db.reader.clone(), num_accounts_to_be_loaded, num_accounts_to_skip);
-        let (main_signer_accounts, burner_accounts) =
+        let (main_signer_accounts, mut burner_accounts) =
             accounts_cache.split(num_main_signer_accounts);
+        let mut accounts_pool = WorkloadAccountsPool::new(&burner_accounts);
+        burner_accounts.extend(
+            account_pool::load_saved_accounts(source_dir.as_ref(), db.reader.clone())
+                .unwrap_or_else(|err| panic!("Cannot reuse the saved pool accounts: {:#}", err)),
+        );
 
-        init_workload::<V>(
+        let (transaction_generator_creator, pool) = init_workload::<V>(
             transaction_mix,
             main_signer_accounts,
             burner_accounts,
@@ -156,13 +314,28 @@ pub fn run_benchmark<V>(
             // Initialization pipeline is temporary, so needs to be fully committed.
             // No discards/aborts allowed during initialization, even if they are allowed later.
             &PipelineConfig::default(),
-        )
+            txn_type_tags.clone(),
+        );
+        accounts_pool.set_accounts_pool(pool);
+        (transaction_generator_creator, accounts_pool)
     });
+    let (transaction_generator_creator, workload_accounts_pool) =
+        transaction_generator_creator.unzip();
 
     let version = db.reader.get_latest_version().unwrap();
+    if start_version.is_some() {
+        info!("Continuing the DB from version {}", version);
+    }
 
     let (pipeline, block_sender) =
         Pipeline::new(executor, version, &pipeline_config, Some(num_blocks));
+    let historical_reader = pipeline_config.historical_read_qps.map(|qps| {
+        HistoricalReader::start(
+            db.reader.clone(),
+            qps,
+            pipeline_config.historical_read_max_lag_versions,
+        )
+    });
 
     let mut num_accounts_to_load = num_main_signer_accounts;
     if let Some(mix) = &transaction_mix {
@@ -172,11 +345,11 @@ pub fn run_benchmark<V>(
                 // `aptos_executor_benchmark::transaction_generator::TransactionGenerator` needs to hold
                 // at least `block_size` number of accounts, all as signer only.
                 num_accounts_to_load = block_size;
-                if transactions_per_sender > 1 {
+                if transactions_per_sender != TxnsPerSenderPolicy::Fixed(1) {
                     warn!(
                     "Overriding transactions_per_sender to 1 for non_conflicting_txns_per_block workload"
                 );
-                    transactions_per_sender = 1;
+                    transactions_per_sender = TxnsPerSenderPolicy::Fixed(1);
                 }
             }
         }
@@ -185,11 +358,35 @@ pub fn run_benchmark<V>(
         db.clone(),
         genesis_key,
         block_sender,
-        source_dir,
+        source_dir.as_ref(),
         Some(num_accounts_to_load),
-        pipeline_config.num_generator_workers,
+        &pipeline_config,
     );
-
+    if let Some(adaptive_block_size) = pipeline.adaptive_block_size() {
+        generator.set_adaptive_block_size(adaptive_block_size);
+    }
+    let injected_txns = pipeline.injected_txns();
+
+    let thread_utilization_sampler = pipeline_config
+        .thread_utilization_sample_interval
+        .and_then(ThreadUtilizationSampler::start);
+    let leak_detector = pipeline_config
+        .leak_check_interval
+        .and_then(LeakDetector::start);
+    let core_pinner =
+        if pipeline_config.execution_cores.is_some() || pipeline_config.rocksdb_cores.is_some() {
+            Some(CorePinner::start(
+                pipeline_config.execution_cores.clone(),
+                pipeline_config.rocksdb_cores.clone(),
+            ))
+        } else {
+            None
+        };
+    let perf_counter_sampler = if pipeline_config.perf_counters {
+        PerfCounterSampler::start()
+    } else {
+        None
+    };
     let mut start_time = Instant::now();
     let start_gas_measurement = GasMeasuring::start();
     let start_output_size = APTOS_PROCESSED_TXNS_OUTPUT_SIZE.get();
@@ -220,20 +417,43 @@ pub fn run_benchmark<V>(
         .collect::<HashMap<_, _>>();
     let start_ledger_update_total = APTOS_EXECUTOR_LEDGER_UPDATE_SECONDS.get_sample_sum();
     let start_commit_total = APTOS_EXECUTOR_COMMIT_BLOCKS_SECONDS.get_sample_sum();
+    let (start_get_results_total, start_post_last_result_total) =
+        remote_executor_client::result_aggregation_seconds();
 
     let start_vm_time = APTOS_EXECUTOR_VM_EXECUTE_BLOCK_SECONDS.get_sample_sum();
-    if let Some(transaction_generator_creator) = transaction_generator_creator {
+    let start_block_stm = BlockStmStats::snapshot();
+    let start_module_load = ModuleLoadStats::snapshot();
+    let start_num_failovers = warm_standby::failovers().len();
+    let start_io = IoSnapshot::take();
+    if start_io.is_none() {
+        warn!("Per-thread I/O accounting is not available, not attributing disk I/O to stages.");
+    }
+    let start_contention = ContentionSnapshot::take();
+    if let Some(path) = &pipeline_config.record_workload_path {
+        generator
+            .record_workload(path)
+            .expect("Failed to create the workload recording.");
+    }
+    progress_events::phase_changed(Phase::Run);
+    if let Some(capture_path) = &pipeline_config.mempool_capture_path {
+        generator.run_mempool_capture(
+            capture_path,
+            pipeline_config.mempool_capture_window,
+            block_size,
+            num_blocks,
+        );
+    } else if let Some(transaction_generator_creator) = transaction_generator_creator {
         generator.run_workload(
             block_size,
             num_blocks,
             transaction_generator_creator,
-            transactions_per_sender,
+            &transactions_per_sender,
         );
     } else {
         generator.run_transfer(
             block_size,
             num_blocks,
-            transactions_per_sender,
+            &transactions_per_sender,
             connected_tx_grps,
             shuffle_connected_txns,
             hotspot_probability,
@@ -244,7 +464,46 @@ pub fn run_benchmark<V>(
     }
     pipeline.start_execution();
     generator.drop_sender();
+    if let Err(err) = generator.finish_workload_recording() {
+        // Leave no partial recording behind, that would be replayed as the whole workload.
+        let path = pipeline_config.record_workload_path.as_ref().unwrap();
+        warn!("{:#}, removing the partial recording {:?}", err, path);
+        fs::remove_file(path).ok();
+    }
     pipeline.join();
+    let stage_perf = perf_counter_sampler.map_or_else(BTreeMap::new, PerfCounterSampler::stop);
+    progress_events::phase_changed(Phase::Report);
+    if let Some(historical_reader) = historical_reader {
+        historical_reader.stop_and_report();
+    }
+    if let Some(leak_detector) = leak_detector {
+        leak_detector.stop();
+    }
+    if let Some(core_pinner) = core_pinner {
+        core_pinner.stop();
+    }
+    let thread_utilization = thread_utilization_sampler.map_or_else(Vec::new, |sampler| {
+        let samples = sampler.stop();
+        thread_utilization::log_thread_utilization(&samples);
+        if let Some(path) = &pipeline_config.thread_utilization_csv_path {
+            if let Err(err) = thread_utilization::write_thread_utilization_csv(path, &samples) {
+                warn!(
+                    "Failed to write thread utilization to {}: {}",
+                    path.display(),
+                    err
+                );
+            }
+        }
+        samples
+    });
+
+    if generator.num_skipped_blocks() > 0 {
+        warn!(
+            "Skipped {} out of {} blocks because of the memory guardrail",
+            generator.num_skipped_blocks(),
+            num_blocks
+        );
+    }
 
     let elapsed = start_time.elapsed().as_secs_f64();
     let delta_v = (db.reader.get_latest_version().unwrap() - version) as f64;
@@ -258,14 +517,15 @@ pub fn run_benchmark<V>(
         delta_v,
         delta_vm_time
     );
-    info!(
-        "Executed workload {}",
-        if let Some(mix) = transaction_mix {
-            format!("{:?} via txn generator", mix)
-        } else {
-            "raw transfer".to_string()
-        }
-    );
+    let txn_type_labels = transaction_mix
+        .as_deref()
+        .map(txn_type_stats::txn_type_labels);
+    let workload = if let Some(mix) = transaction_mix {
+        format!("{:?} via txn generator", mix)
+    } else {
+        "raw transfer".to_string()
+    };
+    info!("Executed workload {}", workload);
     info!("Overall TPS: {} txn/s", delta_v / elapsed);
     info!("Overall GPS: {} gas/s", delta_gas.gas / elapsed);
     info!("Overall ioGPS: {} gas/s", delta_gas.io_gas / elapsed);
@@ -334,11 +594,164 @@ pub fn run_benchmark<V>(
         time_in_commit / elapsed,
         delta_v / time_in_commit
     );
+    let durability = durability::get_durability();
+    let commit_ms_per_block = time_in_commit * 1000.0 / (num_blocks as f64).max(1.0);
+    info!(
+        "Overall commit latency with {:?} durability: {:.2} ms per block",
+        durability, commit_ms_per_block
+    );
+
+    if !remote_executor_client::get_remote_addresses().is_empty() {
+        let (get_results_total, post_last_result_total) =
+            remote_executor_client::result_aggregation_seconds();
+        let time_in_get_results = get_results_total - start_get_results_total;
+        let time_after_last_result = post_last_result_total - start_post_last_result_total;
+        info!(
+            "Overall fraction of execution {:.3} in getting remote results ({} aggregation), {:.3} after the last shard result arrived",
+            time_in_get_results / time_in_execution,
+            if remote_executor_client::get_async_result_aggregation() {
+                "async"
+            } else {
+                "sync"
+            },
+            time_after_last_result / time_in_execution,
+        );
+        if remote_executor_client::get_hedging().is_some() {
+            let (num_dispatched, num_hedged, num_standby_won) =
+                hedging::hedging_stats(remote_executor_client::get_remote_addresses().len());
+            info!(
+                "Hedged {} of {} shard commands ({:.2}%), {} of them won by the standby",
+                num_hedged,
+                num_dispatched,
+                num_hedged as f64 * 100.0 / (num_dispatched as f64).max(1.0),
+                num_standby_won
+            );
+        }
+    }
+    let failovers = warm_standby::failovers().split_off(start_num_failovers);
+    for failover in &failovers {
+        info!(
+            "Shard {} failed over to its warm standby, which returned the block in flight after {:.3} secs",
+            failover.shard_id, failover.failover_secs
+        );
+    }
+    let injected_txn_outcomes =
+        injected_txns.map_or_else(BTreeMap::new, |injected_txns| injected_txns.outcomes());
+    log_injected_txn_outcomes(&injected_txn_outcomes);
+    let txns_per_sender_distribution = generator.txns_per_sender_distribution().clone();
+    log_txns_per_sender(&txns_per_sender_distribution);
+
+    let stage_io = match (start_io, IoSnapshot::take()) {
+        (Some(start_io), Some(end_io)) => {
+            let stage_io = end_io.since(&start_io);
+            io_accounting::log_stage_io(&stage_io, elapsed);
+            stage_io
+        },
+        _ => BTreeMap::new(),
+    };
+    let involuntary_switches_per_sec = match (start_contention, ContentionSnapshot::take()) {
+        (Some(start_contention), Some(end_contention)) => {
+            let switches_per_sec = end_contention.since(&start_contention, elapsed);
+            cpu_affinity::log_contention(&switches_per_sec);
+            switches_per_sec
+        },
+        _ => BTreeMap::new(),
+    };
+    if !stage_perf.is_empty() {
+        perf_counters::log_stage_perf(&stage_perf, delta_v as u64);
+    }
+
+    let block_stm = BlockStmStats::snapshot().since(&start_block_stm);
+    if !block_stm.is_empty() {
+        info!("Overall Block STM: {}", block_stm.describe(delta_v as u64));
+    }
+    log_module_load_stats(
+        &ModuleLoadStats::snapshot().since(&start_module_load),
+        time_in_execution,
+    );
+    slow_storage::log_injected_latency();
+
+    for stage in ["execution", "commit", "commit_already_applied"] {
+        let num_retries = BLOCK_RETRIES.with_label_values(&[stage]).get();
+        if num_retries > 0 {
+            info!("Overall block retries in {}: {}", stage, num_retries);
+        }
+    }
 
     if verify_sequence_numbers {
         generator.verify_sequence_numbers(db.reader.clone());
     }
+    if let Some(accounts_pool) = workload_accounts_pool {
+        if let Err(err) = accounts_pool.save(source_dir.as_ref(), checkpoint_dir.as_ref()) {
+            warn!("Failed to save the accounts pool: {:?}", err);
+        }
+    }
     log_total_supply(&db.reader);
+
+    let txn_type_stats = if let Some((txn_type_tags, txn_type_labels)) =
+        txn_type_tags.as_ref().zip(txn_type_labels.as_ref())
+    {
+        match txn_type_stats::compute_txn_type_stats(
+            db.reader.as_ref(),
+            version,
+            version + delta_v as u64,
+            elapsed,
+            txn_type_tags,
+            txn_type_labels,
+        ) {
+            Ok(stats) => {
+                txn_type_stats::log_txn_type_stats(&stats);
+                stats
+            },
+            Err(err) => {
+                warn!(
+                    "Failed to attribute throughput to transaction types: {}",
+                    err
+                );
+                BTreeMap::new()
+            },
+        }
+    } else {
+        BTreeMap::new()
+    };
+
+    progress_events::phase_changed(Phase::Done);
+    BenchmarkResults {
+        workload,
+        block_size,
+        num_blocks,
+        num_txns: delta_v as u64,
+        elapsed_secs: elapsed,
+        tps: delta_v / elapsed,
+        vm_tps: delta_v / delta_vm_time,
+        gps: delta_gas.gas / elapsed,
+        io_gps: delta_gas.io_gas / elapsed,
+        execution_gps: delta_gas.execution_gas / elapsed,
+        gas_per_txn: delta_gas.gas / (delta_gas.gas_count as f64).max(1.0),
+        output_bytes_per_sec: delta_output_size as f64 / elapsed,
+        durability,
+        commit_ms_per_block,
+        stage_fractions: [
+            ("partitioning", time_in_partitioning),
+            ("execution", time_in_execution),
+            ("ledger_update", time_in_ledger_update),
+            ("commit", time_in_commit),
+        ]
+        .into_iter()
+        .map(|(stage, time)| (stage.to_string(), time / elapsed))
+        .collect(),
+        txn_type_stats,
+        thread_utilization,
+        reexecution_ratio: block_stm.reexecution_ratio(delta_v as u64),
+        validation_failure_rate: block_stm.validation_failure_rate(),
+        block_stm,
+        stage_io,
+        stage_perf,
+        involuntary_switches_per_sec,
+        failovers,
+        injected_txn_outcomes,
+        txns_per_sender_distribution,
+    }
 }
 
 fn init_workload<V>(
@@ -347,7 +760,8 @@ fn init_workload<V>(
     burner_accounts: Vec<LocalAccount>,
     db: DbReaderWriter,
     pipeline_config: &PipelineConfig,
-) -> Box<dyn TransactionGeneratorCreator>
+    txn_type_tags: Option<Arc<TxnTypeTags>>,
+) -> (Box<dyn TransactionGeneratorCreator>, AccountsPool)
 where
     V: TransactionBlockExecutor + 'static,
 {
@@ -362,7 +776,7 @@ where
     let runtime = Runtime::new().unwrap();
     let transaction_factory = TransactionGenerator::create_transaction_factory();
 
-    let (txn_generator_creator, _address_pool, _account_pool) = runtime.block_on(async {
+    let (txn_generator_creator, _address_pool, accounts_pool) = runtime.block_on(async {
         let phase = Arc::new(AtomicUsize::new(0));
 
         let db_gen_init_transaction_executor = DbReliableTransactionSubmitter {
@@ -378,13 +792,14 @@ where
             &transaction_factory,
             &transaction_factory,
             phase,
+            txn_type_tags,
         )
         .await
     });
 
     pipeline.join();
 
-    txn_generator_creator
+    (txn_generator_creator, accounts_pool)
 }
 
 pub fn add_accounts<V>(
@@ -453,7 +868,7 @@ fn add_accounts_impl<V>(
         block_sender,
         &source_dir,
         None,
-        pipeline_config.num_generator_workers,
+        &pipeline_config,
     );
 
     let start_time = Instant::now();
@@ -504,6 +919,178 @@ fn add_accounts_impl<V>(
     );
 }
 
+/// Measures how long a node takes to come back after a restart on an existing DB: opening the DB
+/// (which replays the write sets committed after the latest state snapshot), recovering the
+/// committed ledger state in the executor, and executing and committing the first block.
+/// The DB is copied to `checkpoint_dir` first, so `source_dir` is left untouched.
+///
+/// The files of the DB are likely in the page cache already (e.g. after creating it), unlike
+/// after a node restart following a reboot, which makes the DB open time optimistic. With
+/// `drop_page_cache`, the page cache of the whole host is dropped before opening the DB, which
+/// requires root on Linux.
+pub fn bench_restart<V>(
+    block_size: usize,
+    source_dir: impl AsRef<Path>,
+    checkpoint_dir: impl AsRef<Path>,
+    pruner_config: PrunerConfig,
+    enable_storage_sharding: bool,
+    drop_page_cache: bool,
+    mut pipeline_config: PipelineConfig,
+) where
+    V: TransactionBlockExecutor + 'static,
+{
+    assert!(source_dir.as_ref() != checkpoint_dir.as_ref());
+    create_checkpoint(
+        source_dir.as_ref(),
+        checkpoint_dir.as_ref(),
+        enable_storage_sharding,
+    );
+
+    let (mut config, genesis_key) = aptos_genesis::test_utils::test_config();
+    config.storage.dir = checkpoint_dir.as_ref().to_path_buf();
+    config.storage.storage_pruner_config = pruner_config;
+    config.storage.rocksdb_configs.enable_storage_sharding = enable_storage_sharding;
+
+    if drop_page_cache {
+        drop_caches().expect("Failed to drop the page cache, which requires root on Linux.");
+    } else {
+        warn!("The page cache is not dropped, the DB open time is of a warm page cache.");
+    }
+    let start_time = Instant::now();
+    let (db, executor) = init_db_and_executor::<V>(&config);
+    let db_open_time = start_time.elapsed();
+
+    let start_time = Instant::now();
+    executor
+        .reset()
+        .expect("Failed to recover the committed ledger state.");
+    let ledger_recovery_time = start_time.elapsed();
+
+    let version = db.reader.get_latest_version().unwrap();
+    // Generate the first block upfront, so only its execution is timed.
+    pipeline_config.delay_execution_start = true;
+    let (pipeline, block_sender) = Pipeline::new(executor, version, &pipeline_config, Some(1));
+    let mut generator = TransactionGenerator::new_with_existing_db(
+        db.clone(),
+        genesis_key,
+        block_sender,
+        &source_dir,
+        Some(block_size),
+        &pipeline_config,
+    );
+    generator.run_transfer(
+        block_size,
+        1,
+        &TxnsPerSenderPolicy::Fixed(1),
+        0,
+        false,
+        None,
+    );
+
+    let start_execution_total = APTOS_EXECUTOR_EXECUTE_BLOCK_SECONDS.get_sample_sum();
+    let start_time = Instant::now();
+    pipeline.start_execution();
+    generator.drop_sender();
+    pipeline.join();
+    let first_block_time = start_time.elapsed();
+    let first_block_execution_secs =
+        APTOS_EXECUTOR_EXECUTE_BLOCK_SECONDS.get_sample_sum() - start_execution_total;
+
+    info!(
+        "Restart at version {}: DB open {:.3} s, ledger recovery {:.3} s, first block of {} txns {:.3} s ({:.3} s in execution).",
+        version,
+        db_open_time.as_secs_f64(),
+        ledger_recovery_time.as_secs_f64(),
+        db.reader.get_latest_version().unwrap() - version,
+        first_block_time.as_secs_f64(),
+        first_block_execution_secs,
+    );
+}
+
+/// Writes back the dirty pages and drops the page cache (and the dentries and inodes) of the host.
+fn drop_caches() -> anyhow::Result<()> {
+    let status = std::process::Command::new("sync").status()?;
+    anyhow::ensure!(status.success(), "sync failed: {}", status);
+    fs::write("/proc/sys/vm/drop_caches", "3")?;
+    Ok(())
+}
+
+/// Creates a DB with `num_accounts` accounts in a temporary directory, which is removed when the
+/// returned path is dropped.
+pub fn create_temp_db<V>(
+    num_accounts: usize,
+    init_account_balance: u64,
+    block_size: usize,
+) -> TempPath
+where
+    V: TransactionBlockExecutor + 'static,
+{
+    let db_dir = TempPath::new();
+    info!(
+        "Creating a DB with {} accounts in {}",
+        num_accounts,
+        db_dir.path().display()
+    );
+    let start_time = Instant::now();
+    db_generator::create_db_with_accounts::<V>(
+        num_accounts,
+        init_account_balance,
+        block_size,
+        &db_dir,
+        NO_OP_STORAGE_PRUNER_CONFIG,
+        false, /* verify_sequence_numbers */
+        false, /* enable_storage_sharding */
+        PipelineConfig::default(),
+        &GenesisOptions::default(),
+    );
+    info!(
+        "Created the DB in {:.1} s",
+        start_time.elapsed().as_secs_f64()
+    );
+    db_dir
+}
+
+/// Creates a DB with `num_accounts` accounts in a temporary directory, runs the workload on it,
+/// prints a summary of the results, and removes the DB again, as a quick local sanity check of
+/// executor changes.
+pub fn quick_run<V>(
+    num_accounts: usize,
+    init_account_balance: u64,
+    block_size: usize,
+    num_blocks: usize,
+    transaction_mix: Option<Vec<(TransactionType, usize)>>,
+    transactions_per_sender: TxnsPerSenderPolicy,
+    pipeline_config: PipelineConfig,
+) -> BenchmarkResults
+where
+    V: TransactionBlockExecutor + 'static,
+{
+    let db_dir = create_temp_db::<V>(num_accounts, init_account_balance, block_size);
+    let checkpoint_dir = TempPath::new();
+    let results = run_benchmark::<V>(
+        block_size,
+        num_blocks,
+        transaction_mix,
+        transactions_per_sender,
+        0,     /* connected_tx_grps */
+        false, /* shuffle_connected_txns */
+        None,  /* hotspot_probability */
+        num_accounts,
+        0, /* num_additional_dst_pool_accounts */
+        &db_dir,
+        &checkpoint_dir,
+        &[],
+        None, /* start_version */
+        true, /* verify_sequence_numbers */
+        NO_OP_STORAGE_PRUNER_CONFIG,
+        false, /* enable_storage_sharding */
+        pipeline_config,
+    );
+    results.print_summary();
+    // The temporary directories are removed when dropped.
+    results
+}
+
 struct GasMeasurement {
     pub gas: f64,
 
@@ -569,7 +1156,10 @@ impl GasMeasuring {
 
 #[cfg(test)]
 mod tests {
-    use crate::{native_executor::NativeExecutor, pipeline::PipelineConfig};
+    use crate::{
+        db_generator::GenesisOptions, native_executor::NativeExecutor, pipeline::PipelineConfig,
+        txns_per_sender::TxnsPerSenderPolicy,
+    };
     use aptos_config::config::NO_OP_STORAGE_PRUNER_CONFIG;
     use aptos_executor::block_executor::TransactionBlockExecutor;
     use aptos_temppath::TempPath;
@@ -599,6 +1189,7 @@ mod tests {
             verify_sequence_numbers,
             false,
             PipelineConfig::default(),
+            &GenesisOptions::default(),
         );
 
         println!("run_benchmark");
@@ -607,14 +1198,16 @@ mod tests {
             6, /* block_size */
             5, /* num_blocks */
             transaction_type.map(|t| vec![(t.materialize(2, false), 1)]),
-            2,     /* transactions per sender */
-            0,     /* connected txn groups in a block */
-            false, /* shuffle the connected txns in a block */
-            None,  /* maybe_hotspot_probability */
-            25,    /* num_main_signer_accounts */
-            30,    /* num_dst_pool_accounts */
+            TxnsPerSenderPolicy::Fixed(2), /* transactions per sender */
+            0,                             /* connected txn groups in a block */
+            false,                         /* shuffle the connected txns in a block */
+            None,                          /* maybe_hotspot_probability */
+            25,                            /* num_main_signer_accounts */
+            30,                            /* num_dst_pool_accounts */
             storage_dir.as_ref(),
             checkpoint_dir,
+            &[],
+            None, /* start_version */
             verify_sequence_numbers,
             NO_OP_STORAGE_PRUNER_CONFIG,
             false,
@@ -633,6 +1226,16 @@ mod tests {
         test_generic_benchmark::<AptosVM>(Some(TransactionTypeArg::TokenV2AmbassadorMint), true);
     }
 
+    #[test]
+    fn test_benchmark_composability_chain() {
+        test_generic_benchmark::<AptosVM>(Some(TransactionTypeArg::ComposabilityChain), true);
+    }
+
+    #[test]
+    fn test_benchmark_composability_chain_deep() {
+        test_generic_benchmark::<AptosVM>(Some(TransactionTypeArg::ComposabilityChainDeep), true);
+    }
+
     #[test]
     fn test_native_benchmark() {
         // correct execution not yet implemented, so cannot be checked for validity
@@ -13,23 +13,60 @@ use aptos_block_partitioner::{
 use aptos_config::config::{
     EpochSnapshotPrunerConfig, LedgerPrunerConfig, PrunerConfig, StateMerklePrunerConfig,
 };
-use aptos_executor::block_executor::TransactionBlockExecutor;
-use aptos_executor_benchmark::{native_executor::NativeExecutor, pipeline::PipelineConfig};
-use aptos_executor_service::remote_executor_client;
+use aptos_executor::block_executor::{self, TransactionBlockExecutor};
+use aptos_executor_benchmark::{
+    artifacts::{self, RunArtifacts},
+    backup_under_load::BackupKind,
+    cgroup::CgroupLimits,
+    cpu_affinity::CoreList,
+    db_generator::GenesisOptions,
+    duplicate_injection::DuplicateInjection,
+    durability::{self, Durability},
+    experiment::ExperimentGrid,
+    metrics,
+    metrics_db::{self, StatsGroupBy},
+    module_cache::{self, ModuleCacheMode},
+    native_executor::{NativeExecutionStrategy, NativeExecutor},
+    pipeline::PipelineConfig,
+    post_commit::PostCommitCheck,
+    progress_events,
+    query_state::{QueryPoint, StateQuery},
+    repro_bundle::{self, ReproBundle, WorkloadManifest},
+    results::{read_auth_token, UPLOAD_RESULTS_AUTH_TOKEN_ENV},
+    serve::SERVE_AUTH_TOKEN_ENV,
+    slow_storage::{self, StorageLatency},
+    stage_delay::StageDelays,
+    striped_storage::NUM_STATE_SHARDS,
+    transaction_generator,
+    txns_per_sender::TxnsPerSenderPolicy,
+};
+use aptos_executor_service::{
+    circuit_breaker::CircuitBreakerConfig,
+    cross_shard_relay::CrossShardRouting,
+    fragmentation,
+    hedging::HedgingConfig,
+    remote_executor_client, replay_bundle,
+    transport::Transport,
+    warm_standby::WarmStandbyConfig,
+    wire_trace::{self, WireTraceConfig},
+};
 use aptos_experimental_ptx_executor::PtxBlockExecutor;
 #[cfg(target_os = "linux")]
 use aptos_experimental_runtimes::thread_manager::{ThreadConfigStrategy, ThreadManagerBuilder};
+use aptos_logger::{aptos_logger::FileWriter, info, warn};
 use aptos_metrics_core::{register_int_gauge, IntGauge};
 use aptos_profiler::{ProfilerConfig, ProfilerHandler};
 use aptos_push_metrics::MetricsPusher;
-use aptos_transaction_generator_lib::args::TransactionTypeArg;
+use aptos_transaction_generator_lib::{args::TransactionTypeArg, PayloadEntropy};
+use aptos_types::{account_address::AccountAddress, state_store::table::TableHandle};
 use aptos_vm::AptosVM;
 use clap::{ArgGroup, Parser, Subcommand};
+use move_core_types::language_storage::{ModuleId, StructTag, TypeTag};
 use once_cell::sync::Lazy;
 use std::{
     net::SocketAddr,
     path::PathBuf,
-    time::{SystemTime, UNIX_EPOCH},
+    time::{Duration, SystemTime, UNIX_EPOCH},
 };
 
 #[cfg(unix)]
@@ -108,6 +145,153 @@ pub struct PipelineOpt {
     allow_aborts: bool,
     #[clap(long, default_value = "4")]
     num_generator_workers: usize,
+    /// Upper bound on the serialized size of generated blocks, in bytes.
+    #[clap(long)]
+    max_block_bytes: Option<usize>,
+    /// Stress preset: grow generated blocks until they are close to --max-block-bytes,
+    /// instead of only capping them.
+    #[clap(long, requires = "max_block_bytes")]
+    large_block_stress: bool,
+    /// Skip generating blocks (and log it) while the process RSS is above this many MB,
+    /// instead of risking the process being OOM-killed.
+    #[clap(long)]
+    memory_guardrail_mb: Option<u64>,
+    /// Feed blocks into the pipeline as a Poisson process with this mean rate (blocks/s),
+    /// instead of back-to-back, to model consensus-induced burstiness.
+    #[clap(long, conflicts_with = "generate_then_execute")]
+    block_arrival_rate: Option<f64>,
+    /// Number of blocks arriving together in each burst, with --block-arrival-rate.
+    #[clap(long, default_value_t = 1, requires = "block_arrival_rate")]
+    block_arrival_burst_size: usize,
+    /// Hold every block back this many milliseconds between partitioning and execution, to model
+    /// consensus delays. Blocks are held back concurrently, the way consensus rounds overlap.
+    #[clap(long)]
+    inject_delay_before_execution_ms: Option<u64>,
+    /// Hold every block back this many milliseconds between execution and ledger update.
+    #[clap(long)]
+    inject_delay_before_ledger_update_ms: Option<u64>,
+    /// Hold every block back this many milliseconds between ledger update and commit, to model
+    /// the quorum on the execution result before a block is committed.
+    #[clap(long)]
+    inject_delay_before_commit_ms: Option<u64>,
+    /// Send this fraction of the user transactions twice in their block, with the same sender and
+    /// sequence number, to exercise how the executor deduplicates them. Needs --allow-discards.
+    #[clap(long, default_value_t = 0.0)]
+    inject_duplicate_rate: f64,
+    /// Replay already committed transactions of earlier blocks in every block, this many per user
+    /// transaction of the block. Needs --allow-discards.
+    #[clap(long, default_value_t = 0.0)]
+    inject_replay_rate: f64,
+    /// Run the transactions of this mempool capture (see `mempool_capture`), in broadcast order,
+    /// instead of a generated workload, so that blocks are ordered the way consensus gets them.
+    /// The DB must have the senders of the capture.
+    #[clap(long)]
+    mempool_capture: Option<PathBuf>,
+    /// Cut mempool capture blocks out of the transactions broadcast within this many
+    /// milliseconds of each other, up to --block-size of them.
+    #[clap(long, default_value_t = 250, requires = "mempool_capture")]
+    mempool_capture_window_ms: u64,
+    /// Write the user transactions of the generated blocks to this file, as a mempool capture
+    /// that --mempool-capture replays the same blocks from.
+    #[clap(long, conflicts_with = "mempool_capture")]
+    record_workload: Option<PathBuf>,
+    /// Export committed transactions, events and write sets to this file, to benchmark indexer
+    /// processors downstream of the executor.
+    #[clap(long, conflicts_with = "skip_commit")]
+    export_outputs_path: Option<PathBuf>,
+    /// Issue historical state reads at this rate (reads/s) in the background while the benchmark
+    /// writes, and report their latency.
+    #[clap(long)]
+    historical_read_qps: Option<f64>,
+    /// Upper bound on how many versions behind the latest one historical reads go.
+    #[clap(long, default_value_t = 100_000, requires = "historical_read_qps")]
+    historical_read_max_lag_versions: u64,
+    /// Retry a block that failed to execute, update the ledger or commit (e.g. on a transient
+    /// remote executor error) up to this many times, instead of aborting the benchmark.
+    #[clap(long, default_value_t = 0)]
+    max_block_retries: usize,
+    /// Write the version range, state root and transaction accumulator root of each committed
+    /// block to this file, to cross-reference benchmark DBs without opening them.
+    #[clap(long, conflicts_with = "skip_commit")]
+    block_sidecar_path: Option<PathBuf>,
+    /// Take a checkpoint of the DB every this many committed blocks, into --snapshot-dir, so that
+    /// a later run can resume (with --data-dir) right before a block range showing an anomaly.
+    #[clap(long, requires = "snapshot_dir", conflicts_with = "skip_commit")]
+    snapshot_every: Option<usize>,
+    #[clap(long, requires = "snapshot_every")]
+    snapshot_dir: Option<PathBuf>,
+    /// Take a backup of the DB into --backup-dir after this many committed blocks, while the
+    /// workload keeps running, and report its duration and impact on the committed TPS.
+    #[clap(long, requires = "backup_dir", conflicts_with = "skip_commit")]
+    backup_at_block: Option<usize>,
+    /// What the backup taken with --backup-at-block consists of.
+    #[clap(long, value_enum, default_value_t = BackupKind::Checkpoint, ignore_case = true)]
+    backup_kind: BackupKind,
+    #[clap(long, requires = "backup_at_block")]
+    backup_dir: Option<PathBuf>,
+    /// Every this many committed blocks, compare the values the block wrote in the in-memory
+    /// state that the next blocks execute on with the ones read back from the DB, in the
+    /// background. The run fails if any of them diverged.
+    #[clap(long, conflicts_with = "skip_commit")]
+    shadow_verify_every: Option<usize>,
+    /// Sample how many workers of each thread pool are busy every this many milliseconds, and
+    /// report the utilization timeline, to spot serial stages and lock contention.
+    #[clap(long)]
+    thread_utilization_sample_ms: Option<u64>,
+    /// Write the thread utilization timeline to this file, as CSV.
+    #[clap(long, requires = "thread_utilization_sample_ms")]
+    thread_utilization_csv: Option<PathBuf>,
+    /// Sample the jemalloc stats every this many seconds during the run, and warn about size
+    /// classes whose allocated bytes keep growing, to catch slow leaks in soak runs. With
+    /// --memory-profiling, the allocation sites that grew the most are listed too.
+    #[clap(long)]
+    leak_check_interval_secs: Option<u64>,
+    /// Count CPU cycles, instructions and cache misses of each pipeline stage with hardware
+    /// performance counters (Linux perf events), and report cycles per transaction and IPC.
+    #[clap(long)]
+    perf_counters: bool,
+    /// Pin the execution threads to these cores, e.g. `0-7`. Along with --rocksdb-cores, keeps
+    /// compactions from stealing CPU from execution. The involuntary context switches of both
+    /// are reported, to compare the contention with and without pinning.
+    #[clap(long)]
+    execution_cores: Option<CoreList>,
+    /// Pin the RocksDB background compaction and flush threads to these cores, e.g. `8-11`.
+    #[clap(long)]
+    rocksdb_cores: Option<CoreList>,
+    /// Assert that every committed user transaction emitted exactly this many events.
+    #[clap(long, conflicts_with = "skip_commit")]
+    assert_events_per_txn: Option<usize>,
+    /// Check after each committed block that balances plus burned fees still add up to the
+    /// initial supply, for transfer workloads.
+    #[clap(long, conflicts_with = "skip_commit")]
+    check_balance_conservation: bool,
+    /// Estimate the read and write sets of transactions from their payloads before execution, as
+    /// the partitioner does, and report what it costs and how the estimated write sets compare to
+    /// the committed ones.
+    #[clap(long, conflicts_with = "skip_commit")]
+    estimate_rw_sets: bool,
+    /// Dump the stacks of all threads when executing a block takes longer than this many
+    /// seconds, to diagnose hung runs.
+    #[clap(long)]
+    block_execution_timeout_secs: Option<u64>,
+    /// Abort the run after dumping the stacks of a block over the execution timeout.
+    #[clap(long, requires = "block_execution_timeout_secs")]
+    abort_on_stuck_block: bool,
+    /// Report a stage as starved when a block is in it, but neither the stage nor any worker of
+    /// its pools has run (e.g. all blocked on I/O or locks) for this many milliseconds.
+    #[clap(long)]
+    starvation_threshold_ms: Option<u64>,
+    /// Experimental: adjust the block size after every block to target this block execution
+    /// latency, logging the block size trajectory and the throughput achieved.
+    #[clap(long, conflicts_with = "generate_then_execute")]
+    target_block_latency_ms: Option<u64>,
+    /// How the VM module cache is treated: kept across blocks (warm), flushed before every block
+    /// (cold), or flushed before the run and prewarmed with --prewarm-modules (prewarm-list).
+    #[clap(long, value_enum, default_value_t = ModuleCacheMode::Warm, ignore_case = true)]
+    module_cache: ModuleCacheMode,
+    /// Modules loaded before the run with --module-cache prewarm-list, as <address>::<module>.
+    #[clap(long, num_args = 1.., value_parser = module_cache::parse_module_id)]
+    prewarm_modules: Vec<ModuleId>,
     #[clap(flatten)]
     sharding_opt: ShardingOpt,
 }
@@ -124,7 +308,75 @@ impl PipelineOpt {
             use_global_executor: self.sharding_opt.use_global_executor,
             num_generator_workers: self.num_generator_workers,
             partitioner_config: self.sharding_opt.partitioner_config(),
+            max_block_bytes: self.max_block_bytes,
+            large_block_stress: self.large_block_stress,
+            memory_guardrail_bytes: self.memory_guardrail_mb.map(|mb| mb * 1024 * 1024),
+            block_arrival_rate: self.block_arrival_rate,
+            block_arrival_burst_size: self.block_arrival_burst_size,
+            stage_delays: StageDelays {
+                before_execution: self
+                    .inject_delay_before_execution_ms
+                    .map(Duration::from_millis),
+                before_ledger_update: self
+                    .inject_delay_before_ledger_update_ms
+                    .map(Duration::from_millis),
+                before_commit: self
+                    .inject_delay_before_commit_ms
+                    .map(Duration::from_millis),
+            },
+            duplicate_injection: DuplicateInjection {
+                duplicate_rate: self.inject_duplicate_rate,
+                replay_rate: self.inject_replay_rate,
+            },
+            mempool_capture_path: self.mempool_capture.clone(),
+            mempool_capture_window: Duration::from_millis(self.mempool_capture_window_ms),
+            record_workload_path: self.record_workload.clone(),
+            export_outputs_path: self.export_outputs_path.clone(),
+            historical_read_qps: self.historical_read_qps,
+            historical_read_max_lag_versions: self.historical_read_max_lag_versions,
+            max_block_retries: self.max_block_retries,
+            block_sidecar_path: self.block_sidecar_path.clone(),
+            snapshot_every: self.snapshot_every,
+            snapshot_dir: self.snapshot_dir.clone(),
+            backup_at_block: self.backup_at_block,
+            backup_kind: self.backup_kind,
+            backup_dir: self.backup_dir.clone(),
+            shadow_verify_every: self.shadow_verify_every,
+            thread_utilization_sample_interval: self
+                .thread_utilization_sample_ms
+                .map(Duration::from_millis),
+            thread_utilization_csv_path: self.thread_utilization_csv.clone(),
+            leak_check_interval: self.leak_check_interval_secs.map(Duration::from_secs),
+            perf_counters: self.perf_counters,
+            execution_cores: self.execution_cores.clone().map(|cores| cores.0),
+            rocksdb_cores: self.rocksdb_cores.clone().map(|cores| cores.0),
+            post_commit_checks: self.post_commit_checks(),
+            estimate_rw_sets: self.estimate_rw_sets,
+            block_execution_timeout: self.block_execution_timeout_secs.map(Duration::from_secs),
+            abort_on_stuck_block: self.abort_on_stuck_block,
+            starvation_threshold: self.starvation_threshold_ms.map(Duration::from_millis),
+            target_block_latency: self.target_block_latency_ms.map(Duration::from_millis),
+            module_cache_mode: self.module_cache,
+            prewarm_modules: self.prewarm_modules.clone(),
+            // Depends on the checkpoint dir, set by run-executor.
+            metrics_db_path: None,
+        }
+    }
+
+    fn post_commit_checks(&self) -> Vec<PostCommitCheck> {
+        let mut checks = Vec::new();
+        if let Some(expected) = self.assert_events_per_txn {
+            checks.push(PostCommitCheck::EventsPerTxn(expected));
+        }
+        if self.check_balance_conservation {
+            checks.push(PostCommitCheck::BalanceConservation);
         }
+        if self.sharding_opt.verify_sharded {
+            checks.push(PostCommitCheck::ShardedOutputs(
+                self.sharding_opt.verify_sharded_every,
+            ));
+        }
+        checks
     }
 }
 
@@ -141,6 +393,106 @@ struct ShardingOpt {
     remote_executor_addresses: Option<Vec<SocketAddr>>,
     #[clap(long)]
     coordinator_address: Option<SocketAddr>,
+    /// Receive and decode remote shard results on the serialization pool as they arrive,
+    /// overlapping with the dispatch of the same block, instead of sequentially after
+    /// dispatching it. Aggregation does not overlap with the dispatch of the next block.
+    #[clap(long, requires = "remote_executor_addresses")]
+    async_result_aggregation: bool,
+    /// Transport used to talk to the remote executor shards, has to match theirs.
+    #[clap(
+        long,
+        value_enum,
+        default_value_t = Transport::Tcp,
+        ignore_case = true,
+        requires = "remote_executor_addresses"
+    )]
+    transport: Transport,
+    /// Mark a remote shard as degraded after this long without a heartbeat from it, 0 disables
+    /// monitoring heartbeats.
+    #[clap(long, default_value_t = 5000, requires = "remote_executor_addresses")]
+    shard_heartbeat_timeout_ms: u64,
+    /// Log the type, size, checksum and time of every protocol message exchanged with the remote
+    /// shards.
+    #[clap(long, requires = "remote_executor_addresses")]
+    trace_wire: bool,
+    /// With --trace-wire, also hex dump messages up to this many bytes.
+    #[clap(long, default_value_t = 0, requires = "trace_wire")]
+    trace_wire_hex_dump_max_bytes: usize,
+    /// Open the circuit breaker of a remote shard after this many consecutive failed blocks,
+    /// failing blocks right away instead of dispatching them while it is open. A shard marked
+    /// degraded by its heartbeats opens its breaker right away. 0 disables the circuit breakers.
+    #[clap(long, default_value_t = 0, requires = "remote_executor_addresses")]
+    circuit_breaker_failure_threshold: usize,
+    /// How long a circuit breaker stays open before letting a probe block through.
+    #[clap(long, default_value_t = 10000)]
+    circuit_breaker_open_ms: u64,
+    /// With --circuit-breaker-failure-threshold, fail a block on a remote shard that doesn't
+    /// return its result within this long, and open its circuit breaker right away. Not applied
+    /// with standbys. 0 waits forever.
+    #[clap(long, default_value_t = 0)]
+    circuit_breaker_result_timeout_ms: u64,
+    /// Standby executor services, one per remote shard and in the same order (started with
+    /// --standby-address). The command of a shard slower than the hedge delay is also sent to its
+    /// standby, and the first result is taken.
+    #[clap(
+        long,
+        num_args = 1..,
+        requires = "remote_executor_addresses",
+        conflicts_with = "async_result_aggregation"
+    )]
+    standby_executor_addresses: Option<Vec<SocketAddr>>,
+    /// Warm standby executor services, one per remote shard and in the same order (started with
+    /// --standby-address and --warm-standby). They receive the block stream of their shard without
+    /// executing it, and a shard whose heartbeats stop while its result is outstanding fails over
+    /// to its standby. The failover times are reported with the results. Only commands without
+    /// cross-shard dependencies fail over, and after a failover the blocks in which the shard has
+    /// cross-shard dependencies fail.
+    #[clap(
+        long,
+        num_args = 1..,
+        requires = "remote_executor_addresses",
+        conflicts_with_all = &["async_result_aggregation", "standby_executor_addresses"]
+    )]
+    warm_standby_executor_addresses: Option<Vec<SocketAddr>>,
+    /// Hedge the command of a shard once it is slower than this percentile of the recent shard
+    /// latencies.
+    #[clap(long, default_value_t = 0.95)]
+    hedge_delay_percentile: f64,
+    /// Never hedge the command of a shard earlier than this.
+    #[clap(long, default_value_t = 10)]
+    hedge_min_delay_ms: u64,
+    /// Write a replay bundle (the command, the state values the shard read, and its error) into
+    /// this directory for every remote shard command that fails, to reproduce the failure with
+    /// `aptos-executor-service replay-bundle`.
+    #[clap(long, requires = "remote_executor_addresses")]
+    replay_bundle_dir: Option<PathBuf>,
+    /// How the remote shards send each other the cross-shard messages of multi-round blocks:
+    /// directly, or through the coordinator for shards that can't reach each other.
+    #[clap(
+        long,
+        value_enum,
+        default_value_t = CrossShardRouting::Direct,
+        ignore_case = true,
+        requires = "remote_executor_addresses"
+    )]
+    cross_shard_routing: CrossShardRouting,
+    /// Largest message sent to a remote shard, defaults to the limit of the transport. Larger
+    /// commands are sent in fragments to the shards that reassemble them, and fail the block for
+    /// the others.
+    #[clap(long, requires = "remote_executor_addresses")]
+    remote_max_payload_bytes: Option<usize>,
+    /// Number of threads encoding the commands and decoding the results of the remote shards,
+    /// off the dispatching thread. Defaults to one per shard.
+    #[clap(long, requires = "remote_executor_addresses")]
+    remote_serialization_threads: Option<usize>,
+    /// Also execute a sample of the committed sharded blocks unsharded, in the background on the
+    /// state before them, and fail the run if their outputs differ, to keep checking the
+    /// partitioner and cross-shard messaging.
+    #[clap(long, conflicts_with = "skip_commit")]
+    verify_sharded: bool,
+    /// With --verify-sharded, verify every this many committed blocks.
+    #[clap(long, default_value_t = 10, requires = "verify_sharded")]
+    verify_sharded_every: usize,
     #[clap(long, default_value = "4")]
     max_partitioning_rounds: usize,
     #[clap(long, default_value = "0.90")]
@@ -197,6 +549,117 @@ struct ProfilerOpt {
     memory_profiling: bool,
 }
 
+#[derive(Debug, Parser)]
+struct ResourceLimitOpt {
+    /// Limit the benchmark to this many cores worth of CPU time (cgroup v2 CPU quota).
+    #[clap(long)]
+    cpu_quota_cores: Option<f64>,
+
+    /// Limit the memory of the benchmark to this many MB (cgroup v2 memory limit).
+    #[clap(long)]
+    cgroup_memory_limit_mb: Option<u64>,
+}
+
+#[derive(Debug, Parser)]
+struct ResultsOpt {
+    /// POST the results of the run as JSON to this URL, e.g. a results collection service.
+    #[clap(long)]
+    upload_results: Option<String>,
+
+    /// File holding the bearer token to authenticate the results upload with. Defaults to the
+    /// token in the UPLOAD_RESULTS_AUTH_TOKEN environment variable, if set.
+    #[clap(long, requires = "upload_results")]
+    upload_results_auth_token_file: Option<PathBuf>,
+
+    /// Write the results of the run as JSON to this file.
+    #[clap(long)]
+    results_json: Option<PathBuf>,
+
+    /// Write the benchmark's own metrics to this file in the OpenMetrics text format at the end
+    /// of the run, or to stdout if `-`.
+    #[clap(long)]
+    dump_metrics: Option<PathBuf>,
+
+    /// Write progress events (phase changed, block committed, stage stalled) as JSON lines to
+    /// this file descriptor, inherited from the parent process.
+    #[clap(long, conflicts_with = "progress_file")]
+    progress_fd: Option<i32>,
+
+    /// Write progress events as JSON lines to this file.
+    #[clap(long)]
+    progress_file: Option<PathBuf>,
+
+    /// Collect all outputs of the run (results, CSVs, progress events, flamegraphs, metrics,
+    /// logs, and a manifest listing them) in a timestamped directory under this one. Outputs
+    /// given a path with their own option are still written there instead.
+    #[clap(long)]
+    artifacts_dir: Option<PathBuf>,
+
+    /// Keep only this many most recent runs in --artifacts-dir, deleting the older ones.
+    #[clap(long, requires = "artifacts_dir")]
+    artifacts_keep_runs: Option<usize>,
+
+    /// Write a bundle reproducing the run to this file, for run-bundle (see `repro_bundle`): a
+    /// .tar.gz archive of its arguments, seed, workload, options and git revision.
+    #[clap(long)]
+    repro_bundle: Option<PathBuf>,
+
+    /// Include the transactions of the run in the bundle, so that running it executes the very
+    /// same blocks. They are recorded to --record-workload, or next to the bundle.
+    #[clap(long, requires = "repro_bundle")]
+    repro_bundle_include_workload: bool,
+
+    /// Record the metrics of every committed block in a metrics DB (see `metrics_db`), next to
+    /// the checkpoint dir, where the runs using the same checkpoint dir accumulate. Query it with
+    /// the stats subcommand.
+    #[clap(long)]
+    metrics_db: bool,
+
+    /// Keep the metrics DB at this path instead, e.g. to share it between checkpoint dirs. Runs
+    /// started while another one records into it record into a DB of their own next to it,
+    /// which the stats subcommand reads along with it.
+    #[clap(long, requires = "metrics_db")]
+    metrics_db_path: Option<PathBuf>,
+}
+
+#[derive(Debug, Parser)]
+struct GenesisOpt {
+    /// Bootstrap the DB with this bcs serialized genesis transaction instead of the test genesis.
+    #[clap(
+        long,
+        value_parser,
+        conflicts_with_all = &["epoch_duration_secs", "gas_feature_version", "enable_features", "disable_features"]
+    )]
+    genesis_blob: Option<PathBuf>,
+
+    #[clap(long)]
+    epoch_duration_secs: Option<u64>,
+
+    /// Use the initial gas schedule of this gas feature version.
+    #[clap(long)]
+    gas_feature_version: Option<u64>,
+
+    /// Feature flags (by number) to enable on top of the genesis defaults.
+    #[clap(long, num_args = 0..)]
+    enable_features: Vec<u64>,
+
+    /// Feature flags (by number) to disable.
+    #[clap(long, num_args = 0..)]
+    disable_features: Vec<u64>,
+}
+
+impl GenesisOpt {
+    fn genesis_options(&self) -> GenesisOptions {
+        GenesisOptions {
+            genesis_blob: self.genesis_blob.clone(),
+            epoch_duration_secs: self.epoch_duration_secs,
+            gas_feature_version: self.gas_feature_version,
+            enable_features: self.enable_features.clone(),
+            disable_features: self.disable_features.clone(),
+        }
+    }
+}
+
 #[derive(Parser, Debug)]
 #[clap(group(
     ArgGroup::new("vm_selection")
@@ -206,6 +669,16 @@ pub struct VmSelectionOpt {
     #[clap(long)]
     use_native_executor: bool,
 
+    /// How the native executor spreads the transactions of a block over its threads.
+    #[clap(
+        long,
+        value_enum,
+        default_value_t = NativeExecutionStrategy::PerTransaction,
+        ignore_case = true,
+        requires = "use_native_executor"
+    )]
+    native_strategy: NativeExecutionStrategy,
+
     #[clap(long)]
     use_ptx_executor: bool,
 }
@@ -215,8 +688,10 @@ struct Opt {
     #[clap(long, default_value_t = 10000)]
     block_size: usize,
 
-    #[clap(long, default_value_t = 5)]
-    transactions_per_sender: usize,
+    /// Number of transactions each sender of a block sends: a fixed number like `5`, or a policy
+    /// drawing it per sender or per block, `uniform:1-8`, `geometric:2.5` or `ramp:1-16`.
+    #[clap(long, default_value = "5")]
+    transactions_per_sender: TxnsPerSenderPolicy,
 
     /// 0 implies random TX generation; if non-zero, then 'transactions_per_sender is ignored
     /// 'connected_tx_grps' should be less than 'block_size'
@@ -229,18 +704,44 @@ struct Opt {
     #[clap(long, conflicts_with_all = &["connected_tx_grps", "transactions_per_sender"])]
     hotspot_probability: Option<f32>,
 
+    /// Seed of the random choices of the workload generator (senders, receivers and transactions
+    /// per sender), to generate the same blocks again. Drawn at random and logged if not set.
+    #[clap(long)]
+    seed: Option<u64>,
+
     #[clap(
         long,
         help = "Number of threads to use for execution. Generally replaces --concurrency-level flag (directly for default case, and as a total across all shards for sharded case)"
     )]
     execution_threads: Option<usize>,
 
+    /// Number of threads computing the state checkpoints, i.e. updating and hashing the in-memory
+    /// state tree, on a pool of their own. By default they run on the execution threads.
+    #[clap(long)]
+    checkpoint_workers: Option<usize>,
+
     #[clap(flatten)]
     pruner_opt: PrunerOpt,
 
     #[clap(long)]
     enable_storage_sharding: bool,
 
+    /// Delay every state read that reaches the DB by this many microseconds, to model slow disks
+    /// or remote storage.
+    #[clap(long)]
+    storage_latency_us: Option<u64>,
+
+    /// With --storage-latency-us, delay every read by up to this many extra microseconds,
+    /// uniformly distributed.
+    #[clap(long, default_value_t = 0, requires = "storage_latency_us")]
+    storage_latency_jitter_us: u64,
+
+    /// How durable the commits to the DB are: `strict` fsyncs the RocksDB WAL on every write, as
+    /// nodes do, `relaxed` writes the WAL without fsyncing it, and `none` skips the WAL. Compare
+    /// runs with different values to measure the cost of durability on a storage device.
+    #[clap(long, value_enum, default_value_t = Durability::Strict, ignore_case = true)]
+    durability: Durability,
+
     #[clap(flatten)]
     pipeline_opt: PipelineOpt,
 
@@ -256,9 +757,535 @@ struct Opt {
 
     #[clap(flatten)]
     profiler_opt: ProfilerOpt,
+
+    #[clap(flatten)]
+    resource_limit_opt: ResourceLimitOpt,
+
+    #[clap(flatten)]
+    results_opt: ResultsOpt,
+}
+
+/// An incompatible or suspicious combination of options, found before starting the run.
+#[derive(Debug)]
+struct ConfigProblem {
+    /// Errors abort the run, warnings are only reported.
+    is_error: bool,
+    problem: String,
+    suggestion: String,
+}
+
+impl ConfigProblem {
+    fn error(problem: impl Into<String>, suggestion: impl Into<String>) -> Self {
+        Self {
+            is_error: true,
+            problem: problem.into(),
+            suggestion: suggestion.into(),
+        }
+    }
+
+    fn warning(problem: impl Into<String>, suggestion: impl Into<String>) -> Self {
+        Self {
+            is_error: false,
+            problem: problem.into(),
+            suggestion: suggestion.into(),
+        }
+    }
 }
 
 impl Opt {
+    /// Checks the options for combinations that would fail (or silently measure the wrong thing)
+    /// mid-run, so that all of them can be reported at once upfront.
+    fn validate(&self) -> Vec<ConfigProblem> {
+        let mut problems = Vec::new();
+        let pipeline_opt = &self.pipeline_opt;
+        let sharding_opt = &pipeline_opt.sharding_opt;
+
+        if pipeline_opt.skip_commit && self.verify_sequence_numbers {
+            problems.push(ConfigProblem::error(
+                "--verify-sequence-numbers reads committed state, but --skip-commit is set.",
+                "Drop one of --skip-commit and --verify-sequence-numbers.",
+            ));
+        }
+        if sharding_opt.num_executor_shards > 1 && self.vm_selection_opt.use_native_executor {
+            problems.push(ConfigProblem::error(
+                format!(
+                    "The native executor doesn't support sharded execution, but --num-executor-shards is {}.",
+                    sharding_opt.num_executor_shards
+                ),
+                "Drop --use-native-executor, or set --num-executor-shards to 0.",
+            ));
+        }
+        if sharding_opt.num_executor_shards > 1 {
+            let execution_threads = self.execution_threads.unwrap_or_else(num_cpus::get);
+            if execution_threads % sharding_opt.num_executor_shards != 0 {
+                problems.push(ConfigProblem::error(
+                    format!(
+                        "Execution threads ({}) are not divisible by the number of execution shards ({}).",
+                        execution_threads, sharding_opt.num_executor_shards
+                    ),
+                    "Set --execution-threads to a multiple of --num-executor-shards.",
+                ));
+            }
+        }
+        if let Some(remote_executor_addresses) = &sharding_opt.remote_executor_addresses {
+            if remote_executor_addresses.len() != sharding_opt.num_executor_shards {
+                problems.push(ConfigProblem::error(
+                    format!(
+                        "{} remote executor addresses are given for {} execution shards.",
+                        remote_executor_addresses.len(),
+                        sharding_opt.num_executor_shards
+                    ),
+                    "Pass one --remote-executor-addresses entry per execution shard.",
+                ));
+            }
+            if sharding_opt.coordinator_address.is_none() {
+                problems.push(ConfigProblem::error(
+                    "Remote executor addresses are given without a coordinator address.",
+                    "Set --coordinator-address to an address the shards can reach.",
+                ));
+            }
+            if let Some(standby_executor_addresses) = &sharding_opt.standby_executor_addresses {
+                if standby_executor_addresses.len() != remote_executor_addresses.len() {
+                    problems.push(ConfigProblem::error(
+                        format!(
+                            "{} standby executor addresses are given for {} remote shards.",
+                            standby_executor_addresses.len(),
+                            remote_executor_addresses.len()
+                        ),
+                        "Pass one --standby-executor-addresses entry per remote shard.",
+                    ));
+                }
+                if !(sharding_opt.hedge_delay_percentile > 0.0
+                    && sharding_opt.hedge_delay_percentile <= 1.0)
+                {
+                    problems.push(ConfigProblem::error(
+                        format!(
+                            "--hedge-delay-percentile is {}, outside of (0, 1].",
+                            sharding_opt.hedge_delay_percentile
+                        ),
+                        "Set --hedge-delay-percentile to e.g. 0.95.",
+                    ));
+                }
+            }
+            if let Some(warm_standby_executor_addresses) =
+                &sharding_opt.warm_standby_executor_addresses
+            {
+                if warm_standby_executor_addresses.len() != remote_executor_addresses.len() {
+                    problems.push(ConfigProblem::error(
+                        format!(
+                            "{} warm standby executor addresses are given for {} remote shards.",
+                            warm_standby_executor_addresses.len(),
+                            remote_executor_addresses.len()
+                        ),
+                        "Pass one --warm-standby-executor-addresses entry per remote shard.",
+                    ));
+                }
+                if sharding_opt.shard_heartbeat_timeout_ms == 0 {
+                    problems.push(ConfigProblem::error(
+                        "Warm standbys are given, but shard heartbeats are not monitored, so no shard ever fails over.",
+                        "Set --shard-heartbeat-timeout-ms above 0.",
+                    ));
+                }
+                if sharding_opt.circuit_breaker_failure_threshold > 0 {
+                    problems.push(ConfigProblem::error(
+                        "The circuit breaker of a dead shard stays open after it failed over to its warm standby.",
+                        "Drop --circuit-breaker-failure-threshold when using warm standbys.",
+                    ));
+                }
+                problems.push(ConfigProblem::warning(
+                    "Warm standbys only take over commands without cross-shard dependencies, and after a failover every block in which the shard has cross-shard dependencies fails.",
+                    "Use --standby-executor-addresses to hedge instead, unless the blocks of the workload partition without cross-shard dependencies.",
+                ));
+            }
+            if let Some(max_payload_bytes) = sharding_opt.remote_max_payload_bytes {
+                if !(fragmentation::MIN_PAYLOAD_BYTES..=fragmentation::MAX_PAYLOAD_BYTES)
+                    .contains(&max_payload_bytes)
+                {
+                    problems.push(ConfigProblem::error(
+                        format!(
+                            "--remote-max-payload-bytes is {}, outside of what the transport takes.",
+                            max_payload_bytes
+                        ),
+                        format!(
+                            "Set --remote-max-payload-bytes between {} and {}.",
+                            fragmentation::MIN_PAYLOAD_BYTES,
+                            fragmentation::MAX_PAYLOAD_BYTES
+                        ),
+                    ));
+                }
+            }
+            if sharding_opt.circuit_breaker_result_timeout_ms > 0
+                && sharding_opt.circuit_breaker_failure_threshold == 0
+            {
+                problems.push(ConfigProblem::error(
+                    "--circuit-breaker-result-timeout-ms is set, but the circuit breakers are disabled.",
+                    "Set --circuit-breaker-failure-threshold above 0, or drop the result timeout.",
+                ));
+            }
+            if sharding_opt.remote_serialization_threads == Some(0) {
+                problems.push(ConfigProblem::error(
+                    "--remote-serialization-threads is 0, which leaves no threads to encode the commands.",
+                    "Set --remote-serialization-threads to at least 1, or drop it.",
+                ));
+            }
+        }
+        if sharding_opt.verify_sharded {
+            if sharding_opt.num_executor_shards <= 1 {
+                problems.push(ConfigProblem::warning(
+                    "--verify-sharded is set, but blocks are only executed sharded with more than one shard.",
+                    "Set --num-executor-shards above 1, or drop --verify-sharded.",
+                ));
+            }
+            if sharding_opt.verify_sharded_every == 0 {
+                problems.push(ConfigProblem::error(
+                    "--verify-sharded-every is 0.",
+                    "Set --verify-sharded-every to 1 to verify every block, or higher to sample.",
+                ));
+            }
+        }
+        if let Some(hotspot_probability) = self.hotspot_probability {
+            if !(0.5..1.0).contains(&hotspot_probability) {
+                problems.push(ConfigProblem::error(
+                    format!(
+                        "--hotspot-probability is {}, outside of [0.5, 1.0).",
+                        hotspot_probability
+                    ),
+                    "Set --hotspot-probability to a decimal number in [0.5, 1.0).",
+                ));
+            }
+        }
+        match pipeline_opt.module_cache {
+            ModuleCacheMode::PrewarmList if pipeline_opt.prewarm_modules.is_empty() => {
+                problems.push(ConfigProblem::error(
+                    "--module-cache prewarm-list is set, but no modules to prewarm are given.",
+                    "Pass the modules to load upfront with --prewarm-modules.",
+                ));
+            },
+            ModuleCacheMode::Warm | ModuleCacheMode::Cold
+                if !pipeline_opt.prewarm_modules.is_empty() =>
+            {
+                problems.push(ConfigProblem::warning(
+                    "--prewarm-modules is only used with --module-cache prewarm-list.",
+                    "Set --module-cache prewarm-list, or drop --prewarm-modules.",
+                ));
+            },
+            _ => {},
+        }
+        if pipeline_opt.module_cache != ModuleCacheMode::Warm
+            && sharding_opt.remote_executor_addresses.is_some()
+        {
+            problems.push(ConfigProblem::warning(
+                "--module-cache only controls the module cache of this process, not of the remote shards.",
+                "Run without --remote-executor-addresses to measure module loading.",
+            ));
+        }
+        if self.connected_tx_grps > 0 && self.connected_tx_grps >= self.block_size {
+            problems.push(ConfigProblem::error(
+                format!(
+                    "--connected-tx-grps ({}) is not less than --block-size ({}).",
+                    self.connected_tx_grps, self.block_size
+                ),
+                "Use fewer connected transaction groups than transactions per block.",
+            ));
+        }
+
+        if let Command::CreateDb { genesis_opt, .. } = &self.cmd {
+            if let Some(flag) = genesis_opt
+                .enable_features
+                .iter()
+                .find(|flag| genesis_opt.disable_features.contains(flag))
+            {
+                problems.push(ConfigProblem::error(
+                    format!("Feature flag {} is both enabled and disabled.", flag),
+                    "Pass each feature flag to only one of --enable-features and --disable-features.",
+                ));
+            }
+        }
+
+        for (flag, rate) in [
+            (
+                "--inject-duplicate-rate",
+                pipeline_opt.inject_duplicate_rate,
+            ),
+            ("--inject-replay-rate", pipeline_opt.inject_replay_rate),
+        ] {
+            if !(0.0..=1.0).contains(&rate) {
+                problems.push(ConfigProblem::error(
+                    format!("{} is {}, which is not a fraction.", flag, rate),
+                    format!("Set {} between 0 and 1.", flag),
+                ));
+            } else if rate > 0.0 && !pipeline_opt.allow_discards {
+                problems.push(ConfigProblem::error(
+                    format!(
+                        "{} injects transactions the executor discards, which fails the run.",
+                        flag
+                    ),
+                    "Add --allow-discards.",
+                ));
+            }
+        }
+        if (pipeline_opt.inject_duplicate_rate > 0.0 || pipeline_opt.inject_replay_rate > 0.0)
+            && sharding_opt.num_executor_shards > 0
+        {
+            problems.push(ConfigProblem::error(
+                "Injected transactions are tracked by their position in the block, which the partitioner of sharded execution changes.",
+                "Set --num-executor-shards to 0, or drop --inject-duplicate-rate and --inject-replay-rate.",
+            ));
+        }
+
+        if pipeline_opt.mempool_capture.is_some() && !pipeline_opt.allow_discards {
+            problems.push(ConfigProblem::warning(
+                "Captured transactions that don't apply to the DB (e.g. with stale sequence numbers) are discarded, which fails the run.",
+                "Add --allow-discards, unless the capture was taken on this very DB.",
+            ));
+        }
+
+        if self.checkpoint_workers == Some(0) {
+            problems.push(ConfigProblem::error(
+                "--checkpoint-workers is 0, which leaves no threads to compute the state checkpoints.",
+                "Set --checkpoint-workers to at least 1, or drop it to use the execution threads.",
+            ));
+        }
+
+        if pipeline_opt.shadow_verify_every == Some(0) {
+            problems.push(ConfigProblem::error(
+                "--shadow-verify-every is 0, which samples no blocks.",
+                "Set --shadow-verify-every to at least 1, or drop it.",
+            ));
+        }
+
+        if let Some(progress_fd) = self.results_opt.progress_fd {
+            if let Err(err) = progress_events::check_fd(progress_fd) {
+                problems.push(ConfigProblem::error(
+                    format!(
+                        "--progress-fd {} is not an open file descriptor: {}.",
+                        progress_fd, err
+                    ),
+                    "Pass a file descriptor the parent process keeps open for the run, e.g. 3 with `3>events.jsonl`.",
+                ));
+            }
+        }
+
+        if self.results_opt.artifacts_keep_runs == Some(0) {
+            problems.push(ConfigProblem::error(
+                "--artifacts-keep-runs is 0, which would delete the artifacts of this very run.",
+                "Set --artifacts-keep-runs to at least 1.",
+            ));
+        }
+
+        if self.results_opt.repro_bundle.is_some() {
+            match &self.cmd {
+                Command::RunExecutor {
+                    transaction_type, ..
+                } => {
+                    if !transaction_type.is_empty()
+                        && !self.results_opt.repro_bundle_include_workload
+                    {
+                        problems.push(ConfigProblem::warning(
+                            "The payloads of --transaction-type workloads aren't seeded, so running the bundle generates different transactions.",
+                            "Add --repro-bundle-include-workload to run the very same transactions.",
+                        ));
+                    }
+                },
+                _ => problems.push(ConfigProblem::error(
+                    "--repro-bundle is given, but only run-executor runs can be bundled.",
+                    "Drop --repro-bundle, or bundle a run-executor run.",
+                )),
+            }
+        }
+
+        if self.results_opt.metrics_db {
+            if !matches!(self.cmd, Command::RunExecutor { .. }) {
+                problems.push(ConfigProblem::error(
+                    "--metrics-db is given, but only run-executor runs record their blocks.",
+                    "Drop --metrics-db.",
+                ));
+            } else if pipeline_opt.skip_commit {
+                problems.push(ConfigProblem::error(
+                    "--metrics-db records the committed blocks, but --skip-commit is set.",
+                    "Drop one of --skip-commit and --metrics-db.",
+                ));
+            }
+        }
+
+        if let Command::RunExecutor {
+            blocks,
+            data_dir,
+            entry_function_data_length,
+            payload_entropy,
+            ..
+        } = &self.cmd
+        {
+            if entry_function_data_length.is_some() && payload_entropy.is_none() {
+                problems.push(ConfigProblem::warning(
+                    "--entry-function-data-length is set, but without --payload-entropy the bytes argument is left empty.",
+                    "Add --payload-entropy, e.g. random.",
+                ));
+            }
+
+            if data_dir.len() > 1 && !self.enable_storage_sharding {
+                problems.push(ConfigProblem::error(
+                    "Multiple --data-dir are given, but only a sharded DB can be striped.",
+                    "Add --enable-storage-sharding, or pass a single --data-dir.",
+                ));
+            }
+            if data_dir.len() > NUM_STATE_SHARDS {
+                problems.push(ConfigProblem::error(
+                    format!(
+                        "{} --data-dir are given, but the DB only has {} storage shards to stripe across them.",
+                        data_dir.len(),
+                        NUM_STATE_SHARDS
+                    ),
+                    format!("Pass at most {} --data-dir.", NUM_STATE_SHARDS),
+                ));
+            }
+
+            if let Some(backup_at_block) = pipeline_opt.backup_at_block {
+                if backup_at_block == 0 {
+                    problems.push(ConfigProblem::error(
+                        "--backup-at-block is 0, but the backup is taken after a committed block.",
+                        "Set --backup-at-block to at least 1.",
+                    ));
+                } else if backup_at_block >= *blocks {
+                    problems.push(ConfigProblem::warning(
+                        format!(
+                            "The backup is taken after {} blocks, leaving no blocks of the {} to run during it.",
+                            backup_at_block, blocks
+                        ),
+                        "Set --backup-at-block well below --blocks, to measure the impact of the backup.",
+                    ));
+                }
+            }
+
+            let run_length = (*blocks * self.block_size) as u64;
+            let pruner_opt = &self.pruner_opt;
+            for (name, enabled, prune_window) in [
+                (
+                    "state",
+                    pruner_opt.enable_state_pruner,
+                    pruner_opt.state_prune_window,
+                ),
+                (
+                    "epoch-snapshot",
+                    pruner_opt.enable_epoch_snapshot_pruner,
+                    pruner_opt.epoch_snapshot_prune_window,
+                ),
+                (
+                    "ledger",
+                    pruner_opt.enable_ledger_pruner,
+                    pruner_opt.ledger_prune_window,
+                ),
+            ] {
+                if !enabled {
+                    continue;
+                }
+                if pipeline_opt.historical_read_qps.is_some()
+                    && name != "epoch-snapshot"
+                    && prune_window < pipeline_opt.historical_read_max_lag_versions
+                {
+                    problems.push(ConfigProblem::error(
+                        format!(
+                            "Historical reads go up to {} versions back, but the {} prune window is only {} versions.",
+                            pipeline_opt.historical_read_max_lag_versions, name, prune_window
+                        ),
+                        format!(
+                            "Raise --{}-prune-window, or lower --historical-read-max-lag-versions.",
+                            name
+                        ),
+                    ));
+                } else if prune_window < run_length {
+                    problems.push(ConfigProblem::warning(
+                        format!(
+                            "The {} prune window ({} versions) is smaller than the run ({} transactions), so data written by the run itself gets pruned.",
+                            name, prune_window, run_length
+                        ),
+                        format!(
+                            "Raise --{}-prune-window to at least {} if the run needs its whole history.",
+                            name, run_length
+                        ),
+                    ));
+                }
+            }
+        }
+
+        problems
+    }
+
+    /// Sends the outputs that weren't given a path of their own to the artifacts directory.
+    fn use_artifacts(&mut self, artifacts: &RunArtifacts) {
+        let results_opt = &mut self.results_opt;
+        results_opt
+            .results_json
+            .get_or_insert_with(|| artifacts.path(artifacts::RESULTS_JSON));
+        if results_opt.progress_fd.is_none() {
+            results_opt
+                .progress_file
+                .get_or_insert_with(|| artifacts.path(artifacts::PROGRESS_EVENTS));
+        }
+        let pipeline_opt = &mut self.pipeline_opt;
+        if pipeline_opt.thread_utilization_sample_ms.is_some() {
+            pipeline_opt
+                .thread_utilization_csv
+                .get_or_insert_with(|| artifacts.path(artifacts::THREAD_UTILIZATION_CSV));
+        }
+        if let Command::Experiment { output_csv, .. } = &mut self.cmd {
+            output_csv.get_or_insert_with(|| artifacts.path(artifacts::EXPERIMENT_CSV));
+        }
+    }
+
+    /// Starts the bundle of this run, if asked for, and makes the run record its workload for it
+    /// if the workload is to be included. Returns the bundle, and the path of the workload to
+    /// include.
+    fn start_repro_bundle(&mut self) -> Option<(ReproBundle, Option<PathBuf>)> {
+        let bundle_path = self.results_opt.repro_bundle.clone()?;
+        let workload = match &self.cmd {
+            Command::RunExecutor {
+                blocks,
+                main_signer_accounts,
+                transaction_type,
+                transaction_weights,
+                ..
+            } => WorkloadManifest {
+                block_size: self.block_size,
+                blocks: *blocks,
+                transaction_mix: transaction_type
+                    .iter()
+                    .enumerate()
+                    .map(|(index, transaction_type)| {
+                        (
+                            format!("{:?}", transaction_type),
+                            transaction_weights.get(index).copied().unwrap_or(1),
+                        )
+                    })
+                    .collect(),
+                transactions_per_sender: self.transactions_per_sender.to_string(),
+                main_signer_accounts: *main_signer_accounts,
+                mempool_capture: self.pipeline_opt.mempool_capture.clone(),
+            },
+            _ => return None,
+        };
+        let workload_path = if !self.results_opt.repro_bundle_include_workload {
+            None
+        } else if let Some(capture_path) = &self.pipeline_opt.mempool_capture {
+            Some(capture_path.clone())
+        } else {
+            Some(
+                self.pipeline_opt
+                    .record_workload
+                    .get_or_insert_with(|| bundle_path.with_extension("workload"))
+                    .clone(),
+            )
+        };
+        let config = format!("{:#?}", self);
+        let bundle = ReproBundle::new(
+            &std::env::args().collect::<Vec<_>>(),
+            transaction_generator::get_seed(),
+            workload,
+            config,
+        );
+        Some((bundle, workload_path))
+    }
+
     fn execution_threads(&self) -> usize {
         match self.execution_threads {
             None => {
@@ -282,6 +1309,9 @@ enum Command {
 
         #[clap(long, default_value_t = 10000000000)]
         init_account_balance: u64,
+
+        #[clap(flatten)]
+        genesis_opt: GenesisOpt,
     },
     RunExecutor {
         /// number of transfer blocks to run
@@ -310,11 +1340,51 @@ enum Command {
         #[clap(long, default_value_t = 1)]
         module_working_set_size: usize,
 
-        #[clap(long, value_parser)]
-        data_dir: PathBuf,
+        /// Length in bytes of the bytes argument of the entry functions that take one (e.g. of
+        /// the account-resource workloads), instead of the one of the transaction type. Only
+        /// used along with --payload-entropy.
+        #[clap(long)]
+        entry_function_data_length: Option<usize>,
+
+        /// How random the bytes argument of the entry functions is, which matters for the cost
+        /// of serializing, hashing and compressing the transactions. Without it, the bytes
+        /// argument is empty.
+        #[clap(long, value_enum, ignore_case = true)]
+        payload_entropy: Option<PayloadEntropy>,
+
+        /// Number of modules the composability chain workloads call into, instead of the one of
+        /// the transaction type.
+        #[clap(long)]
+        call_chain_modules: Option<usize>,
+
+        /// Number of frames the composability chain workloads recurse into each module, instead
+        /// of the one of the transaction type.
+        #[clap(long)]
+        call_chain_depth: Option<u64>,
+
+        /// Number of distinct keys the table items workloads write and remove at, instead of the
+        /// one of the transaction type.
+        #[clap(long)]
+        table_key_space_size: Option<u64>,
+
+        /// Percentage of the transactions of the table items workloads removing items instead of
+        /// writing them, instead of the one of the transaction type.
+        #[clap(long)]
+        table_removal_percentage: Option<u64>,
+
+        /// The DB to run from. Repeat to stripe the storage shards of the benchmarked copy of it
+        /// across the other directories given (e.g. one per NVMe device) and --checkpoint-dir,
+        /// which keeps the ledger DB.
+        #[clap(long, value_parser, required = true)]
+        data_dir: Vec<PathBuf>,
 
         #[clap(long, value_parser)]
         checkpoint_dir: PathBuf,
+
+        /// Truncate the copy of the DB to this version before the run, to continue from a
+        /// mid-history state. Accounts created after it cannot be used by the workload.
+        #[clap(long)]
+        start_version: Option<u64>,
     },
     AddAccounts {
         #[clap(long, value_parser)]
@@ -329,6 +1399,176 @@ enum Command {
         #[clap(long, default_value_t = 1000000)]
         init_account_balance: u64,
     },
+    /// Measures DB open time, ledger recovery and the latency of the first block on an existing
+    /// DB, as a node would go through them when restarting.
+    BenchRestart {
+        #[clap(long, value_parser)]
+        data_dir: PathBuf,
+
+        /// Where the DB is copied to and reopened, so that the DB in --data-dir is not modified.
+        #[clap(long, value_parser)]
+        checkpoint_dir: PathBuf,
+
+        /// Drop the page cache of the host before opening the DB, as after a reboot, instead of
+        /// measuring the DB open time with its files likely cached. Requires root on Linux.
+        #[clap(long)]
+        drop_page_cache: bool,
+    },
+    /// Creates a temporary DB, runs the workload on it, prints the results and removes the DB, as
+    /// a quick sanity check of executor changes in a single command.
+    QuickRun {
+        #[clap(long, default_value_t = 10000)]
+        num_accounts: usize,
+
+        #[clap(long, default_value_t = 10000000000)]
+        init_account_balance: u64,
+
+        /// number of blocks to run
+        #[clap(long, default_value_t = 10)]
+        blocks: usize,
+
+        /// Workload (transaction type). Uses raw coin transfer if not set.
+        #[clap(long, value_enum, ignore_case = true)]
+        transaction_type: Option<TransactionTypeArg>,
+    },
+    /// Runs every combination of a YAML grid of block sizes, execution threads and shard counts
+    /// (see `experiment::ExperimentGrid`) on checkpoints of the same DB, each as a run-executor
+    /// run of its own, and prints the results of all of them as a single table.
+    Experiment {
+        #[clap(long, value_parser)]
+        grid: PathBuf,
+
+        #[clap(long, value_parser)]
+        data_dir: PathBuf,
+
+        #[clap(long, value_parser)]
+        checkpoint_dir: PathBuf,
+
+        /// Also write the combined results to this CSV file.
+        #[clap(long, value_parser)]
+        output_csv: Option<PathBuf>,
+    },
+    /// Compares the block sidecars (see --block-sidecar-path) of two runs, exiting with an error
+    /// if they diverge.
+    CompareBlockSidecars {
+        #[clap(long, value_parser)]
+        left: PathBuf,
+
+        #[clap(long, value_parser)]
+        right: PathBuf,
+    },
+    /// Verifies an existing DB without executing any blocks on it: the sequence numbers of the
+    /// accounts against their transactions, the balances against the total supply, proofs of
+    /// randomly picked transactions and state values, and the consistency of the ledger and state
+    /// storage. Exits with an error if any check fails.
+    VerifyDb {
+        #[clap(long, value_parser)]
+        data_dir: PathBuf,
+
+        /// Number of transactions, and of state values, whose proofs are verified.
+        #[clap(long, default_value_t = 100)]
+        num_proof_samples: usize,
+    },
+    /// Prints an account resource (--address and --resource-type) or a table item (--table-handle
+    /// and --key-hex) of an existing DB as JSON, as of the latest version or the given --version
+    /// or --block.
+    #[clap(group(
+        ArgGroup::new("query")
+        .required(true)
+        .args(&["resource_type", "table_handle"]),
+    ))]
+    QueryState {
+        #[clap(long, value_parser)]
+        data_dir: PathBuf,
+
+        #[clap(long, requires = "resource_type")]
+        address: Option<AccountAddress>,
+
+        /// e.g. 0x1::coin::CoinStore<0x1::aptos_coin::AptosCoin>
+        #[clap(long, requires = "address", conflicts_with = "table_handle")]
+        resource_type: Option<StructTag>,
+
+        #[clap(long, requires = "key_hex")]
+        table_handle: Option<TableHandle>,
+
+        /// BCS encoded key of the table item, hex encoded.
+        #[clap(long, requires = "table_handle")]
+        key_hex: Option<String>,
+
+        /// Type of the table item, to print it as JSON instead of as raw bytes.
+        #[clap(long, requires = "table_handle")]
+        value_type: Option<TypeTag>,
+
+        #[clap(long)]
+        version: Option<u64>,
+
+        /// Height of the block after which to read the state, counting the blocks that created
+        /// the accounts of the DB, and the genesis as block 0.
+        #[clap(long, conflicts_with = "version")]
+        block: Option<u64>,
+    },
+    /// Serves a REST API to start benchmark runs (each one a run of this binary with the given
+    /// arguments), follow their progress, fetch their results and abort them, until killed. See
+    /// `serve` for the endpoints.
+    Serve {
+        #[clap(long, default_value = "127.0.0.1:9107")]
+        address: SocketAddr,
+
+        /// Directory the outputs of the runs are kept in, one subdirectory per run.
+        #[clap(long, value_parser)]
+        runs_dir: PathBuf,
+
+        /// File holding the bearer token every request has to carry, instead of the
+        /// SERVE_AUTH_TOKEN environment variable. Required unless serving on a loopback address.
+        #[clap(long, value_parser)]
+        auth_token_file: Option<PathBuf>,
+    },
+    /// Runs a bundle made with --repro-bundle again: the same run-executor arguments, with the
+    /// same seed, on the transactions of the bundle if it includes them. Exits the way the run
+    /// does. The DB to run on has to be created the same way as for the original run.
+    RunBundle {
+        #[clap(long, value_parser)]
+        bundle: PathBuf,
+
+        /// Run on these DB directories instead of the --data-dir of the original run.
+        #[clap(long, value_parser)]
+        data_dir: Vec<PathBuf>,
+
+        /// Copy the DB to this directory instead of the --checkpoint-dir of the original run.
+        #[clap(long, value_parser)]
+        checkpoint_dir: Option<PathBuf>,
+
+        /// Options added to the ones of the original run, e.g. outputs, after `--`.
+        #[clap(last = true)]
+        extra_args: Vec<String>,
+    },
+    /// Aggregates the blocks recorded in a metrics DB (see --metrics-db) per run or per git
+    /// revision, and prints the throughput and latencies of each. The DB can be queried while a
+    /// run is recording into it.
+    Stats {
+        /// The metrics DB, e.g. `<checkpoint dir>.metrics_db`.
+        #[clap(long, value_parser)]
+        db: PathBuf,
+
+        #[clap(long, value_enum, default_value_t = StatsGroupBy::Run, ignore_case = true)]
+        group_by: StatsGroupBy,
+
+        /// Only the runs started at or after this run (run ids are start times, in ms).
+        #[clap(long)]
+        since_run: Option<u64>,
+
+        /// Only the runs of binaries of this git revision, or of revisions starting with it.
+        #[clap(long)]
+        git_revision: Option<String>,
+
+        /// Only the most recent this many runs.
+        #[clap(long)]
+        last: Option<usize>,
+
+        /// Print the rows as JSON instead of as a table.
+        #[clap(long)]
+        json: bool,
+    },
 }
 
 fn run<E>(opt: Opt)
@@ -340,6 +1580,7 @@ where
             data_dir,
             num_accounts,
             init_account_balance,
+            genesis_opt,
         } => {
             aptos_executor_benchmark::db_generator::create_db_with_accounts::<E>(
                 num_accounts,
@@ -350,6 +1591,7 @@ where
                 opt.verify_sequence_numbers,
                 opt.enable_storage_sharding,
                 opt.pipeline_opt.pipeline_config(),
+                &genesis_opt.genesis_options(),
             );
         },
         Command::RunExecutor {
@@ -359,8 +1601,15 @@ where
             transaction_type,
             transaction_weights,
             module_working_set_size,
+            entry_function_data_length,
+            payload_entropy,
+            call_chain_modules,
+            call_chain_depth,
+            table_key_space_size,
+            table_removal_percentage,
             data_dir,
             checkpoint_dir,
+            start_version,
         } => {
             let transaction_mix = if transaction_type.is_empty() {
                 None
@@ -373,7 +1622,23 @@ where
                     false,
                 );
                 assert!(mix_per_phase.len() == 1);
-                Some(mix_per_phase[0].clone())
+                Some(
+                    mix_per_phase[0]
+                        .iter()
+                        .map(|(transaction_type, weight)| {
+                            (
+                                transaction_type
+                                    .with_payload_shape(entry_function_data_length, payload_entropy)
+                                    .with_call_chain(call_chain_modules, call_chain_depth)
+                                    .with_table_items(
+                                        table_key_space_size,
+                                        table_removal_percentage,
+                                    ),
+                                *weight,
+                            )
+                        })
+                        .collect(),
+                )
             };
 
             if let Some(hotspot_probability) = opt.hotspot_probability {
@@ -382,23 +1647,50 @@ where
                 }
             }
 
-            aptos_executor_benchmark::run_benchmark::<E>(
+            let results = aptos_executor_benchmark::run_benchmark::<E>(
                 opt.block_size,
                 blocks,
                 transaction_mix,
-                opt.transactions_per_sender,
+                opt.transactions_per_sender.clone(),
                 opt.connected_tx_grps,
                 opt.shuffle_connected_txns,
                 opt.hotspot_probability,
                 main_signer_accounts,
                 additional_dst_pool_accounts,
-                data_dir,
-                checkpoint_dir,
+                &data_dir[0],
+                &checkpoint_dir,
+                &data_dir[1..],
+                start_version,
                 opt.verify_sequence_numbers,
                 opt.pruner_opt.pruner_config(),
                 opt.enable_storage_sharding,
-                opt.pipeline_opt.pipeline_config(),
+                PipelineConfig {
+                    metrics_db_path: opt.results_opt.metrics_db.then(|| {
+                        opt.results_opt
+                            .metrics_db_path
+                            .clone()
+                            .unwrap_or_else(|| metrics_db::default_path(&checkpoint_dir))
+                    }),
+                    ..opt.pipeline_opt.pipeline_config()
+                },
             );
+            if let Some(path) = &opt.results_opt.results_json {
+                results
+                    .write_json(path)
+                    .unwrap_or_else(|err| panic!("Failed to write results: {:?}", err));
+            }
+            if let Some(url) = &opt.results_opt.upload_results {
+                // The run is over, failing to report it doesn't fail it.
+                match read_auth_token(
+                    opt.results_opt.upload_results_auth_token_file.as_deref(),
+                    UPLOAD_RESULTS_AUTH_TOKEN_ENV,
+                )
+                .and_then(|auth_token| results.upload(url, auth_token.as_deref()))
+                {
+                    Ok(()) => info!("Uploaded results to {}", url),
+                    Err(err) => warn!("Failed to upload results to {}: {:?}", url, err),
+                }
+            }
         },
         Command::AddAccounts {
             data_dir,
@@ -418,12 +1710,230 @@ where
                 opt.pipeline_opt.pipeline_config(),
             );
         },
+        Command::BenchRestart {
+            data_dir,
+            checkpoint_dir,
+            drop_page_cache,
+        } => {
+            aptos_executor_benchmark::bench_restart::<E>(
+                opt.block_size,
+                data_dir,
+                checkpoint_dir,
+                opt.pruner_opt.pruner_config(),
+                opt.enable_storage_sharding,
+                drop_page_cache,
+                opt.pipeline_opt.pipeline_config(),
+            );
+        },
+        Command::QuickRun {
+            num_accounts,
+            init_account_balance,
+            blocks,
+            transaction_type,
+        } => {
+            aptos_executor_benchmark::quick_run::<E>(
+                num_accounts,
+                init_account_balance,
+                opt.block_size,
+                blocks,
+                transaction_type.map(|t| vec![(t.materialize_default(), 1)]),
+                opt.transactions_per_sender.clone(),
+                opt.pipeline_opt.pipeline_config(),
+            );
+        },
+        Command::Experiment {
+            grid,
+            data_dir,
+            checkpoint_dir,
+            output_csv,
+        } => {
+            let grid = ExperimentGrid::load(&grid).expect("Failed to load the experiment grid.");
+            aptos_executor_benchmark::experiment::run_experiment(
+                &grid,
+                &data_dir,
+                &checkpoint_dir,
+                output_csv.as_deref(),
+            )
+            .expect("Experiment failed.");
+        },
+        Command::CompareBlockSidecars { left, right } => {
+            let identical =
+                aptos_executor_benchmark::block_sidecar::compare_block_sidecars(&left, &right)
+                    .expect("Failed to read block sidecars.");
+            if !identical {
+                std::process::exit(1);
+            }
+        },
+        Command::VerifyDb {
+            data_dir,
+            num_proof_samples,
+        } => {
+            let passed = aptos_executor_benchmark::verify_db::verify_db(
+                &data_dir,
+                opt.enable_storage_sharding,
+                num_proof_samples,
+            );
+            if !passed {
+                std::process::exit(1);
+            }
+        },
+        Command::QueryState {
+            data_dir,
+            address,
+            resource_type,
+            table_handle,
+            key_hex,
+            value_type,
+            version,
+            block,
+        } => {
+            let query = match (address, resource_type, table_handle, key_hex) {
+                (Some(address), Some(resource_type), _, _) => StateQuery::Resource {
+                    address,
+                    resource_type,
+                },
+                (_, _, Some(handle), Some(key_hex)) => {
+                    match hex::decode(key_hex.trim_start_matches("0x")) {
+                        Ok(key) => StateQuery::TableItem {
+                            handle,
+                            key,
+                            value_type,
+                        },
+                        Err(err) => {
+                            eprintln!("--key-hex must be hex encoded: {}", err);
+                            std::process::exit(1);
+                        },
+                    }
+                },
+                _ => unreachable!("Clap requires a resource or a table item."),
+            };
+            let at = match (version, block) {
+                (Some(version), _) => QueryPoint::Version(version),
+                (_, Some(height)) => QueryPoint::Block(height),
+                (None, None) => QueryPoint::Latest,
+            };
+            match aptos_executor_benchmark::query_state::query_state(
+                &data_dir,
+                opt.enable_storage_sharding,
+                &query,
+                at,
+            ) {
+                Ok(result) => println!("{}", serde_json::to_string_pretty(&result).unwrap()),
+                Err(err) => {
+                    eprintln!("Failed to query the state: {}", err);
+                    std::process::exit(1);
+                },
+            }
+        },
+        Command::Serve {
+            address,
+            runs_dir,
+            auth_token_file,
+        } => {
+            if let Err(err) = read_auth_token(auth_token_file.as_deref(), SERVE_AUTH_TOKEN_ENV)
+                .and_then(|auth_token| {
+                    aptos_executor_benchmark::serve::serve(address, runs_dir, auth_token)
+                })
+            {
+                eprintln!("Benchmark service failed: {:#}", err);
+                std::process::exit(1);
+            }
+        },
+        Command::RunBundle {
+            bundle,
+            data_dir,
+            checkpoint_dir,
+            extra_args,
+        } => {
+            let status = repro_bundle::run_bundle(
+                &bundle,
+                &data_dir,
+                checkpoint_dir.as_deref(),
+                &extra_args,
+            )
+            .expect("Failed to run the repro bundle.");
+            if !status.success() {
+                std::process::exit(status.code().unwrap_or(1));
+            }
+        },
+        Command::Stats {
+            db,
+            group_by,
+            since_run,
+            git_revision,
+            last,
+            json,
+        } => {
+            let rows =
+                match metrics_db::stats(&db, group_by, since_run, git_revision.as_deref(), last) {
+                    Ok(rows) => rows,
+                    Err(err) => {
+                        eprintln!("Failed to query the metrics DB: {}", err);
+                        std::process::exit(1);
+                    },
+                };
+            if json {
+                println!("{}", serde_json::to_string_pretty(&rows).unwrap());
+            } else {
+                metrics_db::print_stats(&rows);
+            }
+        },
+    }
+}
+
+fn check_config(opt: &Opt) {
+    let problems = opt.validate();
+    for problem in &problems {
+        eprintln!(
+            "{}: {}\n  suggestion: {}",
+            if problem.is_error { "error" } else { "warning" },
+            problem.problem,
+            problem.suggestion
+        );
+    }
+    let num_errors = problems.iter().filter(|problem| problem.is_error).count();
+    if num_errors > 0 {
+        eprintln!(
+            "Found {} invalid option combination(s), not starting the run.",
+            num_errors
+        );
+        std::process::exit(2);
     }
 }
 
 fn main() {
-    let opt = Opt::parse();
-    aptos_logger::Logger::new().init();
+    #[cfg(target_os = "linux")]
+    if std::env::args().nth(1).as_deref()
+        == Some(aptos_executor_benchmark::block_watchdog::STACKTRACE_ARG)
+    {
+        // Started by the block watchdog to trace the threads of a run with a stuck block.
+        let _ = rstack_self::child();
+        return;
+    }
+
+    let mut opt = Opt::parse();
+    check_config(&opt);
+    let artifacts = opt.results_opt.artifacts_dir.as_ref().map(|artifacts_dir| {
+        RunArtifacts::create(artifacts_dir, opt.results_opt.artifacts_keep_runs)
+            .expect("Failed to create the artifacts dir.")
+    });
+    match &artifacts {
+        Some(artifacts) => {
+            let log_path = artifacts.path(artifacts::LOG);
+            println!("Logging to {:?}", log_path);
+            aptos_logger::Logger::new()
+                .printer(Box::new(FileWriter::new(log_path)))
+                .init();
+            opt.use_artifacts(artifacts);
+        },
+        None => aptos_logger::Logger::new().init(),
+    }
+    let cgroup_limits = CgroupLimits::apply(
+        opt.resource_limit_opt.cpu_quota_cores,
+        opt.resource_limit_opt
+            .cgroup_memory_limit_mb
+            .map(|mb| mb * 1024 * 1024),
+    );
     START_TIME.set(
         SystemTime::now()
             .duration_since(UNIX_EPOCH)
@@ -474,6 +1984,79 @@ fn main() {
         remote_executor_client::set_coordinator_address(
             opt.pipeline_opt.sharding_opt.coordinator_address.unwrap(),
         );
+        remote_executor_client::set_async_result_aggregation(
+            opt.pipeline_opt.sharding_opt.async_result_aggregation,
+        );
+        remote_executor_client::set_heartbeat_timeout(
+            (opt.pipeline_opt.sharding_opt.shard_heartbeat_timeout_ms > 0).then(|| {
+                Duration::from_millis(opt.pipeline_opt.sharding_opt.shard_heartbeat_timeout_ms)
+            }),
+        );
+        remote_executor_client::set_circuit_breaker(
+            (opt.pipeline_opt
+                .sharding_opt
+                .circuit_breaker_failure_threshold
+                > 0)
+            .then(|| CircuitBreakerConfig {
+                failure_threshold: opt
+                    .pipeline_opt
+                    .sharding_opt
+                    .circuit_breaker_failure_threshold,
+                open_duration: Duration::from_millis(
+                    opt.pipeline_opt.sharding_opt.circuit_breaker_open_ms,
+                ),
+                result_timeout: (opt
+                    .pipeline_opt
+                    .sharding_opt
+                    .circuit_breaker_result_timeout_ms
+                    > 0)
+                .then(|| {
+                    Duration::from_millis(
+                        opt.pipeline_opt
+                            .sharding_opt
+                            .circuit_breaker_result_timeout_ms,
+                    )
+                }),
+            }),
+        );
+        remote_executor_client::set_hedging(
+            opt.pipeline_opt
+                .sharding_opt
+                .standby_executor_addresses
+                .clone()
+                .map(|standby_addresses| HedgingConfig {
+                    standby_addresses,
+                    delay_percentile: opt.pipeline_opt.sharding_opt.hedge_delay_percentile,
+                    min_delay: Duration::from_millis(
+                        opt.pipeline_opt.sharding_opt.hedge_min_delay_ms,
+                    ),
+                }),
+        );
+        remote_executor_client::set_warm_standby(
+            opt.pipeline_opt
+                .sharding_opt
+                .warm_standby_executor_addresses
+                .clone()
+                .map(|standby_addresses| WarmStandbyConfig { standby_addresses }),
+        );
+        remote_executor_client::set_cross_shard_routing(
+            opt.pipeline_opt.sharding_opt.cross_shard_routing,
+        );
+        if let Some(max_payload_bytes) = opt.pipeline_opt.sharding_opt.remote_max_payload_bytes {
+            remote_executor_client::set_max_payload_bytes(max_payload_bytes);
+        }
+        if let Some(num_threads) = opt.pipeline_opt.sharding_opt.remote_serialization_threads {
+            remote_executor_client::set_serialization_threads(num_threads);
+        }
+        if let Some(replay_bundle_dir) = &opt.pipeline_opt.sharding_opt.replay_bundle_dir {
+            replay_bundle::set_replay_bundle_dir_once(replay_bundle_dir.clone());
+        }
+        remote_executor_client::set_transport(opt.pipeline_opt.sharding_opt.transport);
+        if opt.pipeline_opt.sharding_opt.trace_wire {
+            wire_trace::enable(WireTraceConfig {
+                hex_dump_max_bytes: opt.pipeline_opt.sharding_opt.trace_wire_hex_dump_max_bytes,
+            });
+        }
         // it does not matter because shards are on remote node, but for sake of correctness lets
         // set it
         execution_threads_per_shard = execution_threads;
@@ -482,13 +2065,39 @@ fn main() {
     AptosVM::set_num_shards_once(execution_shards);
     AptosVM::set_concurrency_level_once(execution_threads_per_shard);
     NativeExecutor::set_concurrency_level_once(execution_threads_per_shard);
+    NativeExecutor::set_strategy_once(opt.vm_selection_opt.native_strategy);
+    if let Some(checkpoint_workers) = opt.checkpoint_workers {
+        block_executor::set_checkpoint_workers_once(checkpoint_workers);
+    }
+    if let Some(storage_latency_us) = opt.storage_latency_us {
+        slow_storage::set_storage_latency_once(StorageLatency {
+            latency: Duration::from_micros(storage_latency_us),
+            jitter: Duration::from_micros(opt.storage_latency_jitter_us),
+        });
+    }
+    durability::set_durability_once(opt.durability);
     AptosVM::set_processed_transactions_detailed_counters();
+    if let Some(seed) = opt.seed {
+        transaction_generator::set_seed_once(seed);
+    }
+    let repro_bundle = opt.start_repro_bundle();
+    let repro_bundle_path = opt.results_opt.repro_bundle.clone();
+    if let Some(progress_fd) = opt.results_opt.progress_fd {
+        let sink = progress_events::sink_from_fd(progress_fd)
+            .unwrap_or_else(|err| panic!("Cannot write to --progress-fd {}: {}", progress_fd, err));
+        progress_events::set_progress_sink_once(sink);
+    }
+    if let Some(progress_file) = &opt.results_opt.progress_file {
+        let sink = std::fs::File::create(progress_file).expect("Failed to create progress file.");
+        progress_events::set_progress_sink_once(sink);
+    }
 
     let config = ProfilerConfig::new_with_defaults();
     let handler = ProfilerHandler::new(config);
 
     let cpu_profiling = opt.profiler_opt.cpu_profiling;
     let memory_profiling = opt.profiler_opt.memory_profiling;
+    let dump_metrics = opt.results_opt.dump_metrics.clone();
 
     let mut cpu_profiler = handler.get_cpu_profiler();
     let mut memory_profiler = handler.get_mem_profiler();
@@ -516,6 +2125,41 @@ fn main() {
     if memory_profiling {
         let _mem_end = memory_profiler.end_profiling("./target/release/aptos-executor-benchmark");
     }
+    if let Some(cgroup_limits) = cgroup_limits {
+        cgroup_limits.report();
+    }
+    if let (Some((mut bundle, workload_path)), Some(bundle_path)) =
+        (repro_bundle, repro_bundle_path)
+    {
+        if let Some(workload_path) = &workload_path {
+            // The run goes on when recording the workload fails, and so is still reproduced by
+            // its seed.
+            if let Err(err) = bundle.include_transactions(workload_path) {
+                warn!(
+                    "Writing the repro bundle without the workload of the run: {:#}",
+                    err
+                );
+            }
+        }
+        bundle
+            .write(&bundle_path)
+            .expect("Failed to write the repro bundle.");
+        info!(
+            "Wrote repro bundle {:?} (seed {}), run it with run-bundle --bundle {:?}",
+            bundle_path, bundle.seed, bundle_path
+        );
+    }
+    if let Some(path) = dump_metrics {
+        metrics::dump_metrics(&path).expect("Failed to dump metrics.");
+    }
+    if let Some(artifacts) = artifacts {
+        let mut file = std::fs::File::create(artifacts.path(artifacts::METRICS))
+            .expect("Failed to create metrics file.");
+        metrics::write_metrics(&mut file).expect("Failed to write metrics.");
+        artifacts
+            .finish()
+            .expect("Failed to write the artifacts manifest.");
+    }
 }
 
 #[test]
@@ -523,3 +2167,52 @@ fn verify_tool() {
     use clap::CommandFactory;
     Opt::command().debug_assert()
 }
+
+#[test]
+fn test_validate_reports_all_problems() {
+    let opt = Opt::parse_from([
+        "aptos-executor-benchmark",
+        "--skip-commit",
+        "--verify-sequence-numbers",
+        "--num-executor-shards",
+        "2",
+        "--execution-threads",
+        "4",
+        "--use-native-executor",
+        "--enable-ledger-pruner",
+        "--ledger-prune-window",
+        "10",
+        "run-executor",
+        "--data-dir",
+        "/tmp/data",
+        "--checkpoint-dir",
+        "/tmp/checkpoint",
+    ]);
+    let problems = opt.validate();
+    assert_eq!(problems.iter().filter(|p| p.is_error).count(), 2);
+    assert_eq!(problems.iter().filter(|p| !p.is_error).count(), 1);
+
+    let opt = Opt::parse_from([
+        "aptos-executor-benchmark",
+        "create-db",
+        "--data-dir",
+        "/tmp/data",
+    ]);
+    assert!(opt.validate().is_empty());
+
+    let data_dirs = (0..=NUM_STATE_SHARDS).map(|i| format!("/tmp/data_{}", i));
+    let opt = Opt::parse_from(
+        [
+            "aptos-executor-benchmark".to_string(),
+            "--enable-storage-sharding".to_string(),
+            "run-executor".to_string(),
+            "--checkpoint-dir".to_string(),
+            "/tmp/checkpoint".to_string(),
+        ]
+        .into_iter()
+        .chain(data_dirs.flat_map(|dir| ["--data-dir".to_string(), dir])),
+    );
+    let problems = opt.validate();
+    assert_eq!(problems.len(), 1);
+    assert!(problems[0].is_error && problems[0].problem.contains("storage shards"));
+}
@@ -0,0 +1,221 @@
+// Copyright © Aptos Foundation
+// SPDX-License-Identifier: Apache-2.0
+
+use anyhow::{Context, Result};
+use aptos_types::transaction::SignedTransaction;
+use serde::{Deserialize, Serialize};
+use std::{
+    fs::File,
+    io::{BufReader, BufWriter, ErrorKind, Read, Write},
+    iter::Peekable,
+    path::Path,
+    time::Duration,
+};
+
+/// A transaction as broadcast by mempool, with the time it was broadcast at.
+#[derive(Clone, Debug, Deserialize, Eq, PartialEq, Serialize)]
+pub struct CapturedTransaction {
+    pub timestamp_usecs: u64,
+    pub txn: SignedTransaction,
+}
+
+/// Reads the transactions of a mempool capture file, in broadcast order. The file is a sequence
+/// of BCS serialized `CapturedTransaction`s, each prefixed with its length as a little endian u32.
+pub struct MempoolCaptureReader {
+    reader: BufReader<File>,
+}
+
+impl MempoolCaptureReader {
+    pub fn open(path: &Path) -> Result<Self> {
+        let file =
+            File::open(path).with_context(|| format!("Cannot open mempool capture {:?}", path))?;
+        Ok(Self {
+            reader: BufReader::new(file),
+        })
+    }
+}
+
+impl Iterator for MempoolCaptureReader {
+    type Item = Result<CapturedTransaction>;
+
+    fn next(&mut self) -> Option<Self::Item> {
+        let mut len = [0u8; 4];
+        match self.reader.read_exact(&mut len) {
+            Ok(()) => {},
+            Err(err) if err.kind() == ErrorKind::UnexpectedEof => return None,
+            Err(err) => return Some(Err(err.into())),
+        }
+        let mut bytes = vec![0u8; u32::from_le_bytes(len) as usize];
+        Some(
+            self.reader
+                .read_exact(&mut bytes)
+                .context("Truncated mempool capture record")
+                .and_then(|()| Ok(bcs::from_bytes(&bytes)?)),
+        )
+    }
+}
+
+/// Writes mempool capture files, in the format `MempoolCaptureReader` reads.
+pub struct MempoolCaptureWriter {
+    writer: BufWriter<File>,
+}
+
+impl MempoolCaptureWriter {
+    pub fn create(path: &Path) -> Result<Self> {
+        let file = File::create(path)
+            .with_context(|| format!("Cannot create mempool capture {:?}", path))?;
+        Ok(Self {
+            writer: BufWriter::new(file),
+        })
+    }
+
+    pub fn write(&mut self, captured: &CapturedTransaction) -> Result<()> {
+        let bytes = bcs::to_bytes(captured)?;
+        self.writer.write_all(&(bytes.len() as u32).to_le_bytes())?;
+        self.writer.write_all(&bytes)?;
+        Ok(())
+    }
+
+    pub fn finish(mut self) -> Result<()> {
+        Ok(self.writer.flush()?)
+    }
+}
+
+/// Time between the blocks written by `BlockCaptureWriter`, well above any window they would be
+/// replayed with.
+const BLOCK_INTERVAL_USECS: u64 = 60_000_000;
+
+/// Writes blocks of transactions as a mempool capture, the transactions of a block broadcast at
+/// the same time and the blocks far apart, so that replaying it cuts the same blocks out of it,
+/// as long as they fit in the block size of the replay.
+pub struct BlockCaptureWriter {
+    writer: MempoolCaptureWriter,
+    num_blocks: u64,
+}
+
+impl BlockCaptureWriter {
+    pub fn create(path: &Path) -> Result<Self> {
+        Ok(Self {
+            writer: MempoolCaptureWriter::create(path)?,
+            num_blocks: 0,
+        })
+    }
+
+    pub fn write_block<'a>(
+        &mut self,
+        txns: impl IntoIterator<Item = &'a SignedTransaction>,
+    ) -> Result<()> {
+        let timestamp_usecs = self.num_blocks * BLOCK_INTERVAL_USECS;
+        self.num_blocks += 1;
+        for txn in txns {
+            self.writer.write(&CapturedTransaction {
+                timestamp_usecs,
+                txn: txn.clone(),
+            })?;
+        }
+        Ok(())
+    }
+
+    pub fn finish(self) -> Result<()> {
+        self.writer.finish()
+    }
+}
+
+/// Re-batches timestamped items into blocks, the way consensus would pull them out of mempool:
+/// a block takes the items within `window` of the timestamp of its first item, up to
+/// `max_block_size` items, keeping them in the order they were received.
+pub struct TimestampWindowBatcher<I: Iterator> {
+    items: Peekable<I>,
+    window_usecs: u64,
+    max_block_size: usize,
+}
+
+impl<T, I: Iterator<Item = (u64, T)>> TimestampWindowBatcher<I> {
+    pub fn new(items: I, window: Duration, max_block_size: usize) -> Self {
+        assert!(max_block_size > 0, "Max block size must be positive.");
+        Self {
+            items: items.peekable(),
+            window_usecs: window.as_micros() as u64,
+            max_block_size,
+        }
+    }
+}
+
+impl<T, I: Iterator<Item = (u64, T)>> Iterator for TimestampWindowBatcher<I> {
+    type Item = Vec<T>;
+
+    fn next(&mut self) -> Option<Self::Item> {
+        let (block_start, first) = self.items.next()?;
+        let mut block = vec![first];
+        while block.len() < self.max_block_size {
+            // Broadcast order is kept, items timestamped before the start of the block (e.g.
+            // relayed late by another peer) join it.
+            match self.items.peek() {
+                Some((timestamp, _)) if *timestamp < block_start + self.window_usecs => {
+                    block.push(self.items.next().unwrap().1);
+                },
+                _ => break,
+            }
+        }
+        Some(block)
+    }
+}
+
+#[test]
+fn test_timestamp_window_batcher() {
+    let items = [
+        (0, 'a'),
+        (100, 'b'),
+        (250, 'c'),
+        (240, 'd'),
+        (600, 'e'),
+        (601, 'f'),
+    ];
+    let blocks = TimestampWindowBatcher::new(items.into_iter(), Duration::from_micros(250), 10)
+        .collect::<Vec<_>>();
+    assert_eq!(blocks, vec![vec!['a', 'b'], vec!['c', 'd'], vec!['e', 'f']]);
+
+    let blocks = TimestampWindowBatcher::new(items.into_iter(), Duration::from_secs(1), 4)
+        .collect::<Vec<_>>();
+    assert_eq!(blocks, vec![vec!['a', 'b', 'c', 'd'], vec!['e', 'f']]);
+}
+
+#[test]
+fn test_mempool_capture_round_trip() {
+    use aptos_sdk::{
+        transaction_builder::TransactionFactory,
+        types::{account_address::AccountAddress, chain_id::ChainId, LocalAccount},
+    };
+    use rand::{rngs::StdRng, SeedableRng};
+
+    let sender = LocalAccount::generate(&mut StdRng::seed_from_u64(0));
+    let transaction_factory = TransactionFactory::new(ChainId::test());
+    let captured: Vec<_> = (0..5)
+        .map(|i| CapturedTransaction {
+            timestamp_usecs: i * 100,
+            txn: sender.sign_with_transaction_builder(
+                transaction_factory.transfer(AccountAddress::ONE, i + 1),
+            ),
+        })
+        .collect();
+
+    let path = aptos_temppath::TempPath::new();
+    let mut writer = MempoolCaptureWriter::create(path.path()).unwrap();
+    for captured in &captured {
+        writer.write(captured).unwrap();
+    }
+    writer.finish().unwrap();
+    let read = MempoolCaptureReader::open(path.path())
+        .unwrap()
+        .collect::<Result<Vec<_>>>()
+        .unwrap();
+    assert_eq!(read, captured);
+
+    // A record cut short is an error, not the end of the capture.
+    let bytes = std::fs::read(path.path()).unwrap();
+    std::fs::write(path.path(), &bytes[..bytes.len() - 1]).unwrap();
+    let read: Vec<_> = MempoolCaptureReader::open(path.path()).unwrap().collect();
+    assert_eq!(read.len(), captured.len());
+    assert!(read[..captured.len() - 1].iter().all(Result::is_ok));
+    assert!(read[captured.len() - 1].is_err());
+}
@@ -3,11 +3,18 @@
 
 #![forbid(unsafe_code)]
 
+use anyhow::Result;
 use aptos_metrics_core::{
-    exponential_buckets, register_histogram_vec, register_int_counter_vec, HistogramVec,
-    IntCounterVec,
+    exponential_buckets, register_histogram_vec, register_int_counter, register_int_counter_vec,
+    register_int_gauge_vec, HistogramVec, IntCounter, IntCounterVec, IntGaugeVec,
 };
 use once_cell::sync::Lazy;
+use prometheus::proto::{LabelPair, MetricFamily, MetricType};
+use std::{
+    fs::File,
+    io::{self, BufWriter, Write},
+    path::Path,
+};
 
 pub static TIMER: Lazy<HistogramVec> = Lazy::new(|| {
     register_histogram_vec!(
@@ -19,6 +26,15 @@ pub static TIMER: Lazy<HistogramVec> = Lazy::new(|| {
     .unwrap()
 });
 
+pub static BLOCK_RETRIES: Lazy<IntCounterVec> = Lazy::new(|| {
+    register_int_counter_vec!(
+        "aptos_executor_benchmark_block_retries",
+        "# of block retries in each stage, and of commits found already applied when retrying.",
+        &["stage"]
+    )
+    .unwrap()
+});
+
 pub static NUM_TXNS: Lazy<IntCounterVec> = Lazy::new(|| {
     register_int_counter_vec!(
         "aptos_executor_benchmark_num_txns",
@@ -27,3 +43,225 @@ pub static NUM_TXNS: Lazy<IntCounterVec> = Lazy::new(|| {
     )
     .unwrap()
 });
+
+pub static STUCK_BLOCKS: Lazy<IntCounter> = Lazy::new(|| {
+    register_int_counter!(
+        "aptos_executor_benchmark_stuck_blocks",
+        "# of blocks whose execution took longer than the block execution timeout."
+    )
+    .unwrap()
+});
+
+pub static STARVED_STAGES: Lazy<IntCounterVec> = Lazy::new(|| {
+    register_int_counter_vec!(
+        "aptos_executor_benchmark_starved_stages",
+        "# of times a stage and all the workers of its pools were blocked for longer than the starvation threshold.",
+        &["stage"]
+    )
+    .unwrap()
+});
+
+pub static DELAYED_BLOCKS: Lazy<IntGaugeVec> = Lazy::new(|| {
+    register_int_gauge_vec!(
+        "aptos_executor_benchmark_delayed_blocks",
+        "# of blocks held back by the delay injected in front of each stage.",
+        &["stage"]
+    )
+    .unwrap()
+});
+
+pub static INJECTED_TXNS: Lazy<IntCounterVec> = Lazy::new(|| {
+    register_int_counter_vec!(
+        "aptos_executor_benchmark_injected_txns",
+        "# of duplicate and replayed transactions injected into blocks, by how the ledger update handled them.",
+        &["kind", "outcome"]
+    )
+    .unwrap()
+});
+
+pub static SHADOW_VERIFIED_KEYS: Lazy<IntCounterVec> = Lazy::new(|| {
+    register_int_counter_vec!(
+        "aptos_executor_benchmark_shadow_verified_keys",
+        "# of keys written by sampled blocks, by how their in-memory value compared with the persisted one.",
+        &["result"]
+    )
+    .unwrap()
+});
+
+/// Prefix of the names of the benchmark's own metrics.
+const BENCHMARK_METRICS_PREFIX: &str = "aptos_executor_benchmark";
+
+/// Writes the final state of the benchmark's own metrics (not the executor's, storage's or VM's)
+/// to `path` in the OpenMetrics text format, or to stdout if `path` is `-`, so wrapper scripts can
+/// capture them without a push gateway.
+pub fn dump_metrics(path: &Path) -> Result<()> {
+    let families: Vec<_> = aptos_metrics_core::gather()
+        .into_iter()
+        .filter(|family| family.get_name().starts_with(BENCHMARK_METRICS_PREFIX))
+        .collect();
+    if path == Path::new("-") {
+        encode_open_metrics(&families, &mut io::stdout().lock())
+    } else {
+        let mut file = BufWriter::new(File::create(path)?);
+        encode_open_metrics(&families, &mut file)
+    }
+}
+
+/// Writes all metrics registered in the process in the OpenMetrics text format.
+pub fn write_metrics(writer: &mut impl Write) -> Result<()> {
+    encode_open_metrics(&aptos_metrics_core::gather(), writer)
+}
+
+/// Encodes `families` in the OpenMetrics text format. Unlike the Prometheus text format, counters
+/// are exposed as `<name>_total` samples of a `<name>` family, histograms always end with a
+/// `+Inf` bucket, and the exposition is terminated with `# EOF`.
+fn encode_open_metrics(families: &[MetricFamily], writer: &mut impl Write) -> Result<()> {
+    for family in families {
+        let metric_type = family.get_field_type();
+        let (name, type_name) = match metric_type {
+            MetricType::COUNTER => (family.get_name().trim_end_matches("_total"), "counter"),
+            MetricType::GAUGE => (family.get_name(), "gauge"),
+            MetricType::HISTOGRAM => (family.get_name(), "histogram"),
+            MetricType::SUMMARY => (family.get_name(), "summary"),
+            MetricType::UNTYPED => (family.get_name(), "unknown"),
+        };
+        writeln!(writer, "# TYPE {} {}", name, type_name)?;
+        writeln!(writer, "# HELP {} {}", name, escape(family.get_help()))?;
+        for metric in family.get_metric() {
+            let labels = metric.get_label();
+            match metric_type {
+                MetricType::COUNTER => {
+                    let value = metric.get_counter().get_value();
+                    write_sample(writer, name, "_total", labels, None, value)?;
+                },
+                MetricType::GAUGE => {
+                    let value = metric.get_gauge().get_value();
+                    write_sample(writer, name, "", labels, None, value)?;
+                },
+                MetricType::UNTYPED => {
+                    let value = metric.get_untyped().get_value();
+                    write_sample(writer, name, "", labels, None, value)?;
+                },
+                MetricType::HISTOGRAM => {
+                    let histogram = metric.get_histogram();
+                    let mut has_inf_bucket = false;
+                    for bucket in histogram.get_bucket() {
+                        let upper_bound = bucket.get_upper_bound();
+                        has_inf_bucket |= upper_bound == f64::INFINITY;
+                        let le = ("le", format_value(upper_bound));
+                        let count = bucket.get_cumulative_count() as f64;
+                        write_sample(writer, name, "_bucket", labels, Some(le), count)?;
+                    }
+                    let count = histogram.get_sample_count() as f64;
+                    if !has_inf_bucket {
+                        let le = ("le", format_value(f64::INFINITY));
+                        write_sample(writer, name, "_bucket", labels, Some(le), count)?;
+                    }
+                    write_sample(writer, name, "_count", labels, None, count)?;
+                    let sum = histogram.get_sample_sum();
+                    write_sample(writer, name, "_sum", labels, None, sum)?;
+                },
+                MetricType::SUMMARY => {
+                    let summary = metric.get_summary();
+                    for quantile in summary.get_quantile() {
+                        let label = ("quantile", format_value(quantile.get_quantile()));
+                        let value = quantile.get_value();
+                        write_sample(writer, name, "", labels, Some(label), value)?;
+                    }
+                    let count = summary.get_sample_count() as f64;
+                    write_sample(writer, name, "_count", labels, None, count)?;
+                    let sum = summary.get_sample_sum();
+                    write_sample(writer, name, "_sum", labels, None, sum)?;
+                },
+            }
+        }
+    }
+    writeln!(writer, "# EOF")?;
+    writer.flush()?;
+    Ok(())
+}
+
+fn write_sample(
+    writer: &mut impl Write,
+    name: &str,
+    suffix: &str,
+    labels: &[LabelPair],
+    extra_label: Option<(&str, String)>,
+    value: f64,
+) -> Result<()> {
+    let labels: Vec<_> = labels
+        .iter()
+        .map(|label| format!("{}=\"{}\"", label.get_name(), escape(label.get_value())))
+        .chain(extra_label.map(|(name, value)| format!("{}=\"{}\"", name, value)))
+        .collect();
+    if labels.is_empty() {
+        writeln!(writer, "{}{} {}", name, suffix, format_value(value))?;
+    } else {
+        writeln!(
+            writer,
+            "{}{}{{{}}} {}",
+            name,
+            suffix,
+            labels.join(","),
+            format_value(value)
+        )?;
+    }
+    Ok(())
+}
+
+fn format_value(value: f64) -> String {
+    if value == f64::INFINITY {
+        "+Inf".to_string()
+    } else if value == f64::NEG_INFINITY {
+        "-Inf".to_string()
+    } else {
+        value.to_string()
+    }
+}
+
+/// Escapes label values and help texts, as OpenMetrics requires.
+fn escape(value: &str) -> String {
+    value
+        .replace('\\', "\\\\")
+        .replace('"', "\\\"")
+        .replace('\n', "\\n")
+}
+
+#[test]
+fn test_encode_open_metrics() {
+    use prometheus::{Histogram, HistogramOpts, IntCounterVec, Opts, Registry};
+
+    let registry = Registry::new();
+    let counter = IntCounterVec::new(Opts::new("test_txns_total", "Transactions \"seen\"."), &[
+        "stage",
+    ])
+    .unwrap();
+    counter.with_label_values(&["exe\ncute"]).inc_by(3);
+    registry.register(Box::new(counter)).unwrap();
+    let histogram =
+        Histogram::with_opts(HistogramOpts::new("test_seconds", "Time.").buckets(vec![1.0]))
+            .unwrap();
+    histogram.observe(0.5);
+    histogram.observe(2.0);
+    registry.register(Box::new(histogram)).unwrap();
+
+    let mut buffer = vec![];
+    encode_open_metrics(&registry.gather(), &mut buffer).unwrap();
+    assert_eq!(
+        String::from_utf8(buffer).unwrap(),
+        [
+            "# TYPE test_seconds histogram",
+            "# HELP test_seconds Time.",
+            "test_seconds_bucket{le=\"1\"} 1",
+            "test_seconds_bucket{le=\"+Inf\"} 2",
+            "test_seconds_count 2",
+            "test_seconds_sum 2.5",
+            "# TYPE test_txns counter",
+            "# HELP test_txns Transactions \\\"seen\\\".",
+            "test_txns_total{stage=\"exe\\ncute\"} 3",
+            "# EOF",
+            "",
+        ]
+        .join("\n")
+    );
+}
@@ -0,0 +1,570 @@
+// Copyright © Aptos Foundation
+// SPDX-License-Identifier: Apache-2.0
+
+//! Per-block metrics of benchmark runs, kept in a small RocksDB of their own, so that runs can be
+//! compared over time without depending on the retention of an external metrics store.
+//!
+//! With `--metrics-db`, a run-executor run records itself, and then every block it commits, from a
+//! background thread. Runs using the same metrics DB (by default, the one next to their checkpoint
+//! dir) accumulate in it. Only one run at a time can write to a RocksDB, so runs started while
+//! another one is recording into the metrics DB record into a DB of their own next to it instead,
+//! named after it and the run. `stats` opens a metrics DB and those next to it as RocksDB
+//! secondary instances, so that they can be queried while runs are still writing to them, and
+//! aggregates the blocks of all of their runs per run or per git revision.
+//!
+//! The schema is stable: keys are big endian, so that runs and blocks iterate in order, and values
+//! are BCS encoded versioned enums, to which new versions are only ever added.
+//!
+//! ```text
+//! run:           | run id (start time in ms) |               RunRecord          |
+//! block_metrics: | run id                    | block index | BlockMetricsRecord |
+//! ```
+
+use anyhow::{bail, ensure, Context, Result};
+use aptos_build_info::get_git_hash;
+use aptos_logger::{info, warn};
+use aptos_schemadb::{
+    define_schema,
+    schema::{KeyCodec, ValueCodec},
+    ColumnFamilyName, Options, ReadOptions, WriteDurability, DB, DEFAULT_COLUMN_FAMILY_NAME,
+};
+use aptos_temppath::TempPath;
+use chrono::{Local, TimeZone};
+use clap::ValueEnum;
+use serde::{Deserialize, Serialize};
+use std::{
+    collections::BTreeMap,
+    fs,
+    path::{Path, PathBuf},
+    sync::mpsc,
+    thread::JoinHandle,
+    time::{Duration, SystemTime, UNIX_EPOCH},
+};
+
+const METRICS_DB_NAME: &str = "benchmark_metrics_db";
+/// Infix of the names of the DBs of the runs that could not record into the metrics DB they are
+/// next to.
+const RUN_DB_INFIX: &str = ".run_";
+const RUN_CF_NAME: ColumnFamilyName = "run";
+const BLOCK_METRICS_CF_NAME: ColumnFamilyName = "block_metrics";
+
+/// Runs are identified by the time they started at, in milliseconds since the epoch.
+pub type RunId = u64;
+
+#[derive(Clone, Debug, Deserialize, Eq, PartialEq, Serialize)]
+pub struct RunInfo {
+    pub git_revision: String,
+    pub command_line: Vec<String>,
+}
+
+#[derive(Clone, Debug, Deserialize, Eq, PartialEq, Serialize)]
+pub enum RunRecord {
+    V1(RunInfo),
+}
+
+#[derive(Clone, Debug, Default, Deserialize, Eq, PartialEq, Serialize)]
+pub struct BlockMetrics {
+    /// Last version of the block.
+    pub version: u64,
+    pub num_txns: u64,
+    /// From the block entering the pipeline to it being committed.
+    pub latency_us: u64,
+    pub partition_us: u64,
+    pub execution_us: u64,
+    pub commit_us: u64,
+    /// From the first block of the run entering the pipeline to this one being committed.
+    pub elapsed_us: u64,
+}
+
+#[derive(Clone, Debug, Deserialize, Eq, PartialEq, Serialize)]
+pub enum BlockMetricsRecord {
+    V1(BlockMetrics),
+}
+
+define_schema!(RunSchema, RunId, RunRecord, RUN_CF_NAME);
+define_schema!(
+    BlockMetricsSchema,
+    (RunId, u64),
+    BlockMetricsRecord,
+    BLOCK_METRICS_CF_NAME
+);
+
+impl KeyCodec<RunSchema> for RunId {
+    fn encode_key(&self) -> Result<Vec<u8>> {
+        Ok(self.to_be_bytes().to_vec())
+    }
+
+    fn decode_key(data: &[u8]) -> Result<Self> {
+        ensure!(data.len() == 8, "Unexpected run key length {}", data.len());
+        Ok(u64::from_be_bytes(data.try_into()?))
+    }
+}
+
+impl ValueCodec<RunSchema> for RunRecord {
+    fn encode_value(&self) -> Result<Vec<u8>> {
+        Ok(bcs::to_bytes(self)?)
+    }
+
+    fn decode_value(data: &[u8]) -> Result<Self> {
+        Ok(bcs::from_bytes(data)?)
+    }
+}
+
+impl KeyCodec<BlockMetricsSchema> for (RunId, u64) {
+    fn encode_key(&self) -> Result<Vec<u8>> {
+        let mut encoded = self.0.to_be_bytes().to_vec();
+        encoded.extend_from_slice(&self.1.to_be_bytes());
+        Ok(encoded)
+    }
+
+    fn decode_key(data: &[u8]) -> Result<Self> {
+        ensure!(
+            data.len() == 16,
+            "Unexpected block metrics key length {}",
+            data.len()
+        );
+        Ok((
+            u64::from_be_bytes(data[..8].try_into()?),
+            u64::from_be_bytes(data[8..].try_into()?),
+        ))
+    }
+}
+
+impl ValueCodec<BlockMetricsSchema> for BlockMetricsRecord {
+    fn encode_value(&self) -> Result<Vec<u8>> {
+        Ok(bcs::to_bytes(self)?)
+    }
+
+    fn decode_value(data: &[u8]) -> Result<Self> {
+        Ok(bcs::from_bytes(data)?)
+    }
+}
+
+fn column_families() -> Vec<ColumnFamilyName> {
+    vec![
+        DEFAULT_COLUMN_FAMILY_NAME,
+        RUN_CF_NAME,
+        BLOCK_METRICS_CF_NAME,
+    ]
+}
+
+/// Records the blocks of a run into a metrics DB, from a thread of its own so that the commits
+/// aren't held up by it.
+pub struct MetricsDbRecorder {
+    run_id: RunId,
+    block_sender: mpsc::Sender<BlockMetrics>,
+    join_handle: JoinHandle<()>,
+}
+
+impl MetricsDbRecorder {
+    /// Opens the metrics DB at `path`, creating it if needed, and records the start of this run.
+    /// If another run is recording into it, records into a DB of this run next to it instead.
+    pub fn start(path: &Path) -> Result<Self> {
+        let mut run_id = SystemTime::now().duration_since(UNIX_EPOCH)?.as_millis() as RunId;
+        let (db, path) = match Self::open(path) {
+            Ok(db) => (db, path.to_path_buf()),
+            Err(err) if format!("{:#}", err).to_lowercase().contains("lock") => {
+                let run_db_path = run_db_path(path, run_id)?;
+                warn!(
+                    "Metrics DB {:?} is locked by another run, recording into {:?}: {:#}",
+                    path, run_db_path, err
+                );
+                (Self::open(&run_db_path)?, run_db_path)
+            },
+            Err(err) => return Err(err),
+        };
+
+        // Runs started within the same millisecond.
+        while db.get::<RunSchema>(&run_id)?.is_some() {
+            run_id += 1;
+        }
+        db.put::<RunSchema>(
+            &run_id,
+            &RunRecord::V1(RunInfo {
+                git_revision: get_git_hash(),
+                command_line: std::env::args().collect(),
+            }),
+        )?;
+        info!(
+            "Recording the blocks as run {} of metrics DB {:?}",
+            run_id, path
+        );
+
+        let (block_sender, block_receiver) = mpsc::channel::<BlockMetrics>();
+        let join_handle = std::thread::Builder::new()
+            .name("metrics_db".to_string())
+            .spawn(move || {
+                for (block_index, metrics) in block_receiver.into_iter().enumerate() {
+                    if let Err(err) = db.put::<BlockMetricsSchema>(
+                        &(run_id, block_index as u64),
+                        &BlockMetricsRecord::V1(metrics),
+                    ) {
+                        warn!(
+                            "Failed to record block {} in the metrics DB: {}",
+                            block_index, err
+                        );
+                    }
+                }
+            })
+            .expect("Failed to spawn metrics DB thread.");
+        Ok(Self {
+            run_id,
+            block_sender,
+            join_handle,
+        })
+    }
+
+    fn open(path: &Path) -> Result<DB> {
+        let mut opts = Options::default();
+        opts.create_if_missing(true);
+        opts.create_missing_column_families(true);
+        // The metrics are only as durable as the run they measure.
+        Ok(DB::open(path, METRICS_DB_NAME, column_families(), &opts)?
+            .with_write_durability(WriteDurability::NoSync))
+    }
+
+    pub fn run_id(&self) -> RunId {
+        self.run_id
+    }
+
+    /// Called after every committed block, in commit order.
+    pub fn record_block(&self, metrics: BlockMetrics) {
+        self.block_sender.send(metrics).ok();
+    }
+
+    /// Waits for the recorded blocks to be written.
+    pub fn finish(self) {
+        drop(self.block_sender);
+        self.join_handle
+            .join()
+            .expect("Metrics DB thread panicked.");
+    }
+}
+
+/// Path of the metrics DB of the runs on `checkpoint_dir`: next to it, named after it.
+pub fn default_path(checkpoint_dir: &Path) -> PathBuf {
+    let name = checkpoint_dir
+        .file_name()
+        .map_or_else(String::new, |name| name.to_string_lossy().into_owned());
+    checkpoint_dir.with_file_name(format!("{}.metrics_db", name))
+}
+
+/// Path of the DB of a run that cannot record into the metrics DB at `path`, next to it.
+fn run_db_path(path: &Path, run_id: RunId) -> Result<PathBuf> {
+    let name = path
+        .file_name()
+        .with_context(|| format!("Metrics DB path {:?} has no name", path))?;
+    Ok(path.with_file_name(format!(
+        "{}{}{}_{}",
+        name.to_string_lossy(),
+        RUN_DB_INFIX,
+        run_id,
+        std::process::id()
+    )))
+}
+
+/// The metrics DB at `path`, if any, and the DBs of the runs next to it.
+fn metrics_db_paths(path: &Path) -> Result<Vec<PathBuf>> {
+    let mut paths = vec![];
+    if path.exists() {
+        paths.push(path.to_path_buf());
+    }
+    if let (Some(name), Some(parent)) = (path.file_name(), path.parent()) {
+        let prefix = format!("{}{}", name.to_string_lossy(), RUN_DB_INFIX);
+        let parent = if parent.as_os_str().is_empty() {
+            Path::new(".")
+        } else {
+            parent
+        };
+        if parent.exists() {
+            for entry in fs::read_dir(parent)? {
+                let entry = entry?;
+                if entry.file_name().to_string_lossy().starts_with(&prefix) {
+                    paths.push(entry.path());
+                }
+            }
+        }
+    }
+    ensure!(!paths.is_empty(), "No metrics DB at {:?}", path);
+    paths.sort();
+    Ok(paths)
+}
+
+/// Reads a metrics DB, possibly while a run is writing to it.
+pub struct MetricsDbReader {
+    db: DB,
+    /// Where the secondary instance keeps its own logs, deleted on drop.
+    _secondary_dir: TempPath,
+}
+
+impl MetricsDbReader {
+    pub fn open(path: &Path) -> Result<Self> {
+        ensure!(path.exists(), "No metrics DB at {:?}", path);
+        let secondary_dir = TempPath::new();
+        secondary_dir.create_as_dir()?;
+        let mut opts = Options::default();
+        // Required by secondary instances, which can't tell which files the primary keeps open.
+        opts.set_max_open_files(-1);
+        let db = DB::open_cf_as_secondary(
+            &opts,
+            path,
+            secondary_dir.path(),
+            METRICS_DB_NAME,
+            column_families(),
+        )?;
+        Ok(Self {
+            db,
+            _secondary_dir: secondary_dir,
+        })
+    }
+
+    /// All runs of the DB, oldest first.
+    pub fn runs(&self) -> Result<Vec<(RunId, RunInfo)>> {
+        let mut iter = self.db.iter::<RunSchema>(ReadOptions::default())?;
+        iter.seek_to_first();
+        iter.map(|entry| {
+            let (run_id, RunRecord::V1(info)) = entry?;
+            Ok((run_id, info))
+        })
+        .collect()
+    }
+
+    /// The blocks of a run, in commit order.
+    pub fn blocks(&self, run_id: RunId) -> Result<Vec<BlockMetrics>> {
+        let mut iter = self.db.iter::<BlockMetricsSchema>(ReadOptions::default())?;
+        iter.seek(&(run_id, 0))?;
+        let mut blocks = vec![];
+        for entry in iter {
+            let ((entry_run_id, _), BlockMetricsRecord::V1(metrics)) = entry?;
+            if entry_run_id != run_id {
+                break;
+            }
+            blocks.push(metrics);
+        }
+        Ok(blocks)
+    }
+}
+
+#[derive(Clone, Copy, Debug, Eq, PartialEq, ValueEnum)]
+pub enum StatsGroupBy {
+    Run,
+    GitRevision,
+}
+
+/// Aggregated metrics of a group of runs (or of a single run).
+#[derive(Debug, Serialize)]
+pub struct StatsRow {
+    /// The run id and start time, or the git revision.
+    pub group: String,
+    pub num_runs: usize,
+    pub num_blocks: usize,
+    pub num_txns: u64,
+    /// Committed TPS of the runs: the mean, and the slowest and fastest run.
+    pub tps_mean: f64,
+    pub tps_min: f64,
+    pub tps_max: f64,
+    /// Block latencies over all blocks of the runs.
+    pub latency_ms_p50: f64,
+    pub latency_ms_p99: f64,
+    pub execution_ms_mean: f64,
+    pub commit_ms_mean: f64,
+}
+
+/// Aggregates the runs of the metrics DB at `path` started since `since_run_id` (if given), of
+/// `git_revision` (if given), keeping the `last` most recent ones (if given). Runs without blocks,
+/// e.g. still setting up, are left out.
+pub fn stats(
+    path: &Path,
+    group_by: StatsGroupBy,
+    since_run_id: Option<RunId>,
+    git_revision: Option<&str>,
+    last: Option<usize>,
+) -> Result<Vec<StatsRow>> {
+    let mut runs = vec![];
+    for db_path in metrics_db_paths(path)? {
+        let reader = MetricsDbReader::open(&db_path)?;
+        for (run_id, info) in reader.runs()? {
+            if since_run_id.map_or(false, |since| run_id < since)
+                || git_revision.map_or(false, |revision| !info.git_revision.starts_with(revision))
+            {
+                continue;
+            }
+            let blocks = reader.blocks(run_id)?;
+            if !blocks.is_empty() {
+                runs.push((run_id, info, blocks));
+            }
+        }
+    }
+    // Oldest first, whichever DB they are in.
+    runs.sort_by_key(|(run_id, _, _)| *run_id);
+    if let Some(last) = last {
+        runs.drain(..runs.len().saturating_sub(last));
+    }
+    if runs.is_empty() {
+        bail!("No runs with blocks in the metrics DB {:?} match", path);
+    }
+
+    // Groups keep the order of their first run.
+    let mut groups: Vec<(String, Vec<Vec<BlockMetrics>>)> = vec![];
+    let mut group_indices = BTreeMap::new();
+    for (run_id, info, blocks) in runs {
+        let group = match group_by {
+            StatsGroupBy::Run => format!("{} ({})", run_id, format_run_id(run_id)),
+            StatsGroupBy::GitRevision => info.git_revision,
+        };
+        let index = *group_indices.entry(group.clone()).or_insert_with(|| {
+            groups.push((group, vec![]));
+            groups.len() - 1
+        });
+        groups[index].1.push(blocks);
+    }
+    Ok(groups
+        .into_iter()
+        .map(|(group, runs)| aggregate(group, &runs))
+        .collect())
+}
+
+fn format_run_id(run_id: RunId) -> String {
+    Local
+        .timestamp_millis_opt(run_id as i64)
+        .single()
+        .map_or_else(
+            || "-".to_string(),
+            |time| time.format("%Y-%m-%d %H:%M:%S").to_string(),
+        )
+}
+
+fn aggregate(group: String, runs: &[Vec<BlockMetrics>]) -> StatsRow {
+    let tps = runs
+        .iter()
+        .map(|blocks| {
+            let num_txns = blocks.iter().map(|block| block.num_txns).sum::<u64>();
+            let elapsed = blocks.last().map_or(0, |block| block.elapsed_us);
+            num_txns as f64 / Duration::from_micros(elapsed.max(1)).as_secs_f64()
+        })
+        .collect::<Vec<_>>();
+    let blocks = runs.iter().flatten().collect::<Vec<_>>();
+    let mut latencies = blocks
+        .iter()
+        .map(|block| block.latency_us)
+        .collect::<Vec<_>>();
+    latencies.sort_unstable();
+    let percentile = |p: usize| {
+        latencies
+            .get((latencies.len() * p / 100).min(latencies.len().saturating_sub(1)))
+            .map_or(0.0, |latency| *latency as f64 / 1000.0)
+    };
+    let mean_ms = |us: fn(&BlockMetrics) -> u64| {
+        blocks.iter().map(|block| us(block)).sum::<u64>() as f64
+            / 1000.0
+            / (blocks.len() as f64).max(1.0)
+    };
+    StatsRow {
+        group,
+        num_runs: runs.len(),
+        num_blocks: blocks.len(),
+        num_txns: blocks.iter().map(|block| block.num_txns).sum(),
+        tps_mean: tps.iter().sum::<f64>() / tps.len() as f64,
+        tps_min: tps.iter().copied().fold(f64::INFINITY, f64::min),
+        tps_max: tps.iter().copied().fold(0.0, f64::max),
+        latency_ms_p50: percentile(50),
+        latency_ms_p99: percentile(99),
+        execution_ms_mean: mean_ms(|block| block.execution_us),
+        commit_ms_mean: mean_ms(|block| block.commit_us),
+    }
+}
+
+pub fn print_stats(rows: &[StatsRow]) {
+    println!(
+        "{:<40} {:>5} {:>7} {:>11} {:>10} {:>10} {:>10} {:>9} {:>9} {:>9} {:>9}",
+        "group",
+        "runs",
+        "blocks",
+        "txns",
+        "tps_mean",
+        "tps_min",
+        "tps_max",
+        "lat_p50",
+        "lat_p99",
+        "exec_ms",
+        "commit_ms"
+    );
+    for row in rows {
+        println!(
+            "{:<40} {:>5} {:>7} {:>11} {:>10.1} {:>10.1} {:>10.1} {:>9.1} {:>9.1} {:>9.1} {:>9.1}",
+            row.group,
+            row.num_runs,
+            row.num_blocks,
+            row.num_txns,
+            row.tps_mean,
+            row.tps_min,
+            row.tps_max,
+            row.latency_ms_p50,
+            row.latency_ms_p99,
+            row.execution_ms_mean,
+            row.commit_ms_mean
+        );
+    }
+}
+
+#[test]
+fn test_metrics_db() {
+    let temp_dir = TempPath::new();
+    temp_dir.create_as_dir().unwrap();
+    let dir = temp_dir.path().join("metrics_db");
+    let block = |num_txns, elapsed_us| BlockMetrics {
+        num_txns,
+        latency_us: 2000,
+        elapsed_us,
+        ..Default::default()
+    };
+    for _ in 0..2 {
+        let recorder = MetricsDbRecorder::start(&dir).unwrap();
+        recorder.record_block(block(100, 500_000));
+        recorder.record_block(block(100, 1_000_000));
+        recorder.finish();
+    }
+
+    let reader = MetricsDbReader::open(&dir).unwrap();
+    let runs = reader.runs().unwrap();
+    assert_eq!(runs.len(), 2);
+    assert_eq!(reader.blocks(runs[1].0).unwrap(), vec![
+        block(100, 500_000),
+        block(100, 1_000_000)
+    ]);
+
+    let rows = stats(&dir, StatsGroupBy::Run, None, None, Some(1)).unwrap();
+    assert_eq!(rows.len(), 1);
+    assert_eq!(rows[0].num_txns, 200);
+    assert_eq!(rows[0].tps_mean, 200.0);
+    let rows = stats(&dir, StatsGroupBy::GitRevision, None, None, None).unwrap();
+    assert_eq!(rows.len(), 1);
+    assert_eq!((rows[0].num_runs, rows[0].num_blocks), (2, 4));
+    assert_eq!(rows[0].latency_ms_p99, 2.0);
+    drop(reader);
+
+    // A run started while another one records into the DB records into a DB of its own, which
+    // stats reads along with it.
+    let recorder = MetricsDbRecorder::start(&dir).unwrap();
+    let concurrent_recorder = MetricsDbRecorder::start(&dir).unwrap();
+    recorder.record_block(block(100, 1_000_000));
+    concurrent_recorder.record_block(block(300, 1_000_000));
+    recorder.finish();
+    concurrent_recorder.finish();
+    assert_eq!(metrics_db_paths(&dir).unwrap().len(), 2);
+    let rows = stats(&dir, StatsGroupBy::Run, None, None, Some(2)).unwrap();
+    assert_eq!(rows.iter().map(|row| row.num_txns).sum::<u64>(), 400);
+}
+
+#[test]
+fn test_default_path() {
+    // Dots in the checkpoint dir name are kept, so that every checkpoint dir has its own.
+    assert_eq!(
+        default_path(Path::new("/data/checkpoint.v1")),
+        PathBuf::from("/data/checkpoint.v1.metrics_db")
+    );
+    assert_eq!(
+        default_path(Path::new("/data/checkpoint/")),
+        PathBuf::from("/data/checkpoint.metrics_db")
+    );
+}
@@ -0,0 +1,72 @@
+// Copyright © Aptos Foundation
+// SPDX-License-Identifier: Apache-2.0
+
+use aptos_logger::{info, warn};
+use aptos_storage_interface::{state_view::LatestDbStateCheckpointView, DbReader};
+use aptos_vm::{data_cache::AsMoveResolver, AptosVM};
+use clap::ValueEnum;
+use move_core_types::{
+    account_address::AccountAddress, identifier::Identifier, language_storage::ModuleId,
+};
+use move_vm_runtime::module_load_stats::ModuleLoadStats;
+use std::{sync::Arc, time::Instant};
+
+/// How the VM module cache is treated during the run, to quantify the cost of loading modules.
+#[derive(Clone, Copy, Debug, Default, Eq, PartialEq, ValueEnum)]
+pub enum ModuleCacheMode {
+    /// Modules stay cached across blocks, as on a node.
+    #[default]
+    Warm,
+    /// The cache is flushed before every block, so that every block loads its modules again.
+    Cold,
+    /// The cache is flushed before the run, and only the listed modules are loaded upfront.
+    PrewarmList,
+}
+
+/// Flushes the module cache, and loads the given modules (and their dependencies) into the VM
+/// that the first block will use.
+/// Creating the VM always loads `0x1::account`, so it ends up in the cache as well.
+pub fn prewarm_modules(db: &Arc<dyn DbReader>, modules: &[ModuleId]) {
+    AptosVM::flush_warm_vm_cache();
+    let state_view = db.latest_state_checkpoint_view().unwrap();
+    let resolver = state_view.as_move_resolver();
+    let start_time = Instant::now();
+    let start_stats = ModuleLoadStats::snapshot();
+    let vm = AptosVM::new(&resolver);
+    for module in modules {
+        if let Err(err) = vm.internals().move_vm().load_module(module, &resolver) {
+            warn!("Failed to prewarm module {}: {:?}", module, err);
+        }
+    }
+    info!(
+        "Prewarmed {} modules in {} ms ({} modules loaded, including dependencies).",
+        modules.len(),
+        start_time.elapsed().as_millis(),
+        ModuleLoadStats::snapshot().since(&start_stats).num_modules
+    );
+}
+
+/// Parses a module id given as `<address>::<module>`, e.g. `0x1::coin`.
+pub fn parse_module_id(s: &str) -> anyhow::Result<ModuleId> {
+    let (address, name) = s
+        .split_once("::")
+        .ok_or_else(|| anyhow::anyhow!("Expected <address>::<module>, got {}", s))?;
+    Ok(ModuleId::new(
+        AccountAddress::from_hex_literal(address)?,
+        Identifier::new(name)?,
+    ))
+}
+
+pub fn log_module_load_stats(stats: &ModuleLoadStats, time_in_execution: f64) {
+    if stats.num_modules == 0 {
+        return;
+    }
+    info!(
+        "Overall fraction of execution {:.3} in loading {} modules: fetch {:.3} s, deserialize {:.3} s, verify {:.3} s",
+        stats.total().as_secs_f64() / time_in_execution,
+        stats.num_modules,
+        stats.fetch.as_secs_f64(),
+        stats.deserialize.as_secs_f64(),
+        stats.verify.as_secs_f64(),
+    );
+}
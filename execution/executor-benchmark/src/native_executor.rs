@@ -17,10 +17,14 @@ use aptos_types::{
     contract_event::ContractEvent,
     event::EventKey,
     state_store::state_key::StateKey,
-    transaction::{ExecutionStatus, Transaction, TransactionOutput, TransactionStatus},
+    transaction::{
+        signature_verified_transaction::SignatureVerifiedTransaction, ExecutionStatus, Transaction,
+        TransactionOutput, TransactionStatus,
+    },
     vm_status::AbortLocation,
     write_set::{WriteOp, WriteSet, WriteSetMut},
 };
+use clap::ValueEnum;
 use move_core_types::{
     ident_str,
     language_storage::{ModuleId, TypeTag},
@@ -28,7 +32,10 @@ use move_core_types::{
 };
 use once_cell::sync::{Lazy, OnceCell};
 use rayon::{prelude::*, ThreadPool, ThreadPoolBuilder};
-use std::collections::HashMap;
+use std::{
+    collections::{hash_map::DefaultHasher, HashMap},
+    hash::{Hash, Hasher},
+};
 
 struct IncrementalOutput {
     write_set: Vec<(StateKey, WriteOp)>,
@@ -55,9 +62,82 @@ impl IncrementalOutput {
     }
 }
 
+/// How the native executor spreads the transactions of a block over its threads.
+#[derive(Clone, Copy, Debug, Default, Eq, PartialEq, ValueEnum)]
+pub enum NativeExecutionStrategy {
+    /// A rayon task per transaction.
+    #[default]
+    PerTransaction,
+    /// Contiguous chunks of transactions, each executed sequentially by a single task.
+    Chunked,
+    /// Transactions grouped by sender, each group executed sequentially by a single task, so that
+    /// transactions of the same account never run concurrently.
+    ShardedBySender,
+}
+
+impl NativeExecutionStrategy {
+    /// Chunks per thread with the chunked strategy, to leave some room for work stealing.
+    const CHUNKS_PER_THREAD: usize = 4;
+
+    fn execute<F>(
+        self,
+        transactions: &[SignatureVerifiedTransaction],
+        execute_txn: F,
+    ) -> Result<Vec<TransactionOutput>>
+    where
+        F: Fn(&SignatureVerifiedTransaction) -> Result<TransactionOutput> + Send + Sync,
+    {
+        let num_threads = NativeExecutor::get_concurrency_level();
+        match self {
+            Self::PerTransaction => transactions.par_iter().map(execute_txn).collect(),
+            Self::Chunked => {
+                let chunk_size =
+                    (transactions.len() / (num_threads * Self::CHUNKS_PER_THREAD)).max(1);
+                let chunk_outputs = transactions
+                    .par_chunks(chunk_size)
+                    .map(|chunk| chunk.iter().map(&execute_txn).collect::<Result<Vec<_>>>())
+                    .collect::<Result<Vec<_>>>()?;
+                Ok(chunk_outputs.into_iter().flatten().collect())
+            },
+            Self::ShardedBySender => {
+                let mut shards = vec![vec![]; num_threads];
+                for (idx, txn) in transactions.iter().enumerate() {
+                    let shard = match txn.expect_valid() {
+                        Transaction::UserTransaction(user_txn) => {
+                            let mut hasher = DefaultHasher::new();
+                            user_txn.sender().hash(&mut hasher);
+                            hasher.finish() as usize % num_threads
+                        },
+                        _ => 0,
+                    };
+                    shards[shard].push(idx);
+                }
+                let shard_outputs = shards
+                    .into_par_iter()
+                    .map(|indices| {
+                        indices
+                            .into_iter()
+                            .map(|idx| Ok((idx, execute_txn(&transactions[idx])?)))
+                            .collect::<Result<Vec<_>>>()
+                    })
+                    .collect::<Result<Vec<_>>>()?;
+                let mut outputs = (0..transactions.len()).map(|_| None).collect::<Vec<_>>();
+                for (idx, output) in shard_outputs.into_iter().flatten() {
+                    outputs[idx] = Some(output);
+                }
+                Ok(outputs
+                    .into_iter()
+                    .map(|output| output.expect("Every transaction must be executed."))
+                    .collect())
+            },
+        }
+    }
+}
+
 pub struct NativeExecutor {}
 
 static NATIVE_EXECUTOR_CONCURRENCY_LEVEL: OnceCell<usize> = OnceCell::new();
+static NATIVE_EXECUTOR_STRATEGY: OnceCell<NativeExecutionStrategy> = OnceCell::new();
 static NATIVE_EXECUTOR_POOL: Lazy<ThreadPool> = Lazy::new(|| {
     ThreadPoolBuilder::new()
         .num_threads(NativeExecutor::get_concurrency_level())
@@ -80,6 +160,14 @@ impl NativeExecutor {
         }
     }
 
+    pub fn set_strategy_once(strategy: NativeExecutionStrategy) {
+        NATIVE_EXECUTOR_STRATEGY.set(strategy).ok();
+    }
+
+    pub fn get_strategy() -> NativeExecutionStrategy {
+        NATIVE_EXECUTOR_STRATEGY.get().copied().unwrap_or_default()
+    }
+
     fn withdraw_from_signer(
         sender_address: AccountAddress,
         transfer_amount: u64,
@@ -325,6 +413,73 @@ impl NativeExecutor {
         output.into_success_output()
     }
 
+    fn execute_transaction(
+        txn: &Transaction,
+        state_view: &CachedStateView,
+    ) -> Result<TransactionOutput> {
+        match txn {
+            Transaction::StateCheckpoint(_) => Self::handle_state_checkpoint(),
+            Transaction::UserTransaction(user_txn) => match user_txn.payload() {
+                aptos_types::transaction::TransactionPayload::EntryFunction(f) => {
+                    match (
+                        *f.module().address(),
+                        f.module().name().as_str(),
+                        f.function().as_str(),
+                    ) {
+                        (AccountAddress::ONE, "coin", "transfer") => {
+                            Self::handle_account_creation_and_transfer(
+                                user_txn.sender(),
+                                bcs::from_bytes(&f.args()[0]).unwrap(),
+                                bcs::from_bytes(&f.args()[1]).unwrap(),
+                                state_view,
+                                false,
+                                true,
+                            )
+                        },
+                        (AccountAddress::ONE, "aptos_account", "transfer") => {
+                            Self::handle_account_creation_and_transfer(
+                                user_txn.sender(),
+                                bcs::from_bytes(&f.args()[0]).unwrap(),
+                                bcs::from_bytes(&f.args()[1]).unwrap(),
+                                state_view,
+                                false,
+                                false,
+                            )
+                        },
+                        (AccountAddress::ONE, "aptos_account", "create_account") => {
+                            Self::handle_account_creation_and_transfer(
+                                user_txn.sender(),
+                                bcs::from_bytes(&f.args()[0]).unwrap(),
+                                0,
+                                state_view,
+                                true,
+                                false,
+                            )
+                        },
+                        (AccountAddress::ONE, "aptos_account", "batch_transfer") => {
+                            Self::handle_batch_account_creation_and_transfer(
+                                user_txn.sender(),
+                                bcs::from_bytes(&f.args()[0]).unwrap(),
+                                bcs::from_bytes(&f.args()[1]).unwrap(),
+                                state_view,
+                                false,
+                                true,
+                            )
+                        },
+                        _ => unimplemented!(
+                            "{} {}::{}",
+                            *f.module().address(),
+                            f.module().name().as_str(),
+                            f.function().as_str()
+                        ),
+                    }
+                },
+                _ => unimplemented!(),
+            },
+            _ => unimplemented!(),
+        }
+    }
+
     fn handle_state_checkpoint() -> Result<TransactionOutput> {
         Ok(TransactionOutput::new(
             WriteSet::default(),
@@ -346,70 +501,9 @@ impl TransactionBlockExecutor for NativeExecutor {
             _ => todo!("sharded execution not yet supported"),
         };
         let transaction_outputs = NATIVE_EXECUTOR_POOL.install(|| {
-            transactions
-                .par_iter()
-                .map(|txn| match &txn.expect_valid() {
-                    Transaction::StateCheckpoint(_) => Self::handle_state_checkpoint(),
-                    Transaction::UserTransaction(user_txn) => match user_txn.payload() {
-                        aptos_types::transaction::TransactionPayload::EntryFunction(f) => {
-                            match (
-                                *f.module().address(),
-                                f.module().name().as_str(),
-                                f.function().as_str(),
-                            ) {
-                                (AccountAddress::ONE, "coin", "transfer") => {
-                                    Self::handle_account_creation_and_transfer(
-                                        user_txn.sender(),
-                                        bcs::from_bytes(&f.args()[0]).unwrap(),
-                                        bcs::from_bytes(&f.args()[1]).unwrap(),
-                                        &state_view,
-                                        false,
-                                        true,
-                                    )
-                                },
-                                (AccountAddress::ONE, "aptos_account", "transfer") => {
-                                    Self::handle_account_creation_and_transfer(
-                                        user_txn.sender(),
-                                        bcs::from_bytes(&f.args()[0]).unwrap(),
-                                        bcs::from_bytes(&f.args()[1]).unwrap(),
-                                        &state_view,
-                                        false,
-                                        false,
-                                    )
-                                },
-                                (AccountAddress::ONE, "aptos_account", "create_account") => {
-                                    Self::handle_account_creation_and_transfer(
-                                        user_txn.sender(),
-                                        bcs::from_bytes(&f.args()[0]).unwrap(),
-                                        0,
-                                        &state_view,
-                                        true,
-                                        false,
-                                    )
-                                },
-                                (AccountAddress::ONE, "aptos_account", "batch_transfer") => {
-                                    Self::handle_batch_account_creation_and_transfer(
-                                        user_txn.sender(),
-                                        bcs::from_bytes(&f.args()[0]).unwrap(),
-                                        bcs::from_bytes(&f.args()[1]).unwrap(),
-                                        &state_view,
-                                        false,
-                                        true,
-                                    )
-                                },
-                                _ => unimplemented!(
-                                    "{} {}::{}",
-                                    *f.module().address(),
-                                    f.module().name().as_str(),
-                                    f.function().as_str()
-                                ),
-                            }
-                        },
-                        _ => unimplemented!(),
-                    },
-                    _ => unimplemented!(),
-                })
-                .collect::<Result<Vec<_>>>()
+            Self::get_strategy().execute(&transactions, |txn| {
+                Self::execute_transaction(txn.expect_valid(), &state_view)
+            })
         })?;
         Ok(ChunkOutput {
             transactions: transactions.into_iter().map(|t| t.into_inner()).collect(),
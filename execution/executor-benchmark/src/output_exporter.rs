@@ -0,0 +1,150 @@
+// Copyright © Aptos Foundation
+// SPDX-License-Identifier: Apache-2.0
+
+use aptos_logger::info;
+use aptos_storage_interface::{DbReader, MAX_REQUEST_LIMIT};
+use aptos_types::{
+    contract_event::ContractEvent,
+    transaction::{Transaction, TransactionInfo, Version},
+    write_set::WriteSet,
+};
+use serde::{Deserialize, Serialize};
+use std::{
+    fs::File,
+    io::{self, BufWriter, Write},
+    path::Path,
+    sync::{mpsc, Arc},
+};
+
+/// A committed transaction, with everything an indexer processor consumes from a fullnode.
+#[derive(Debug, Deserialize, PartialEq, Serialize)]
+pub struct ExportedTransaction {
+    pub version: Version,
+    pub transaction: Transaction,
+    pub info: TransactionInfo,
+    pub events: Vec<ContractEvent>,
+    pub write_set: WriteSet,
+}
+
+/// Message from commit stage to the exporter: a committed range of versions.
+pub struct ExportBlockMessage {
+    pub first_version: Version,
+    pub num_txns: usize,
+}
+
+/// Writes committed transactions, events and write sets to a file, so indexer processors can be
+/// benchmarked downstream of realistic executor output.
+///
+/// The file is a stream of batches, one per committed block. Each batch is a little-endian `u32`
+/// length followed by the bcs encoding of a `Vec<ExportedTransaction>`, mirroring how the indexer
+/// grpc stream delivers transactions in batches.
+/// Outputs are read back from the DB after commit, on a separate thread. The commit stage only
+/// waits on it when it falls behind by more than a few blocks, and reports for how long it did.
+pub struct OutputExporter {
+    db: Arc<dyn DbReader>,
+    writer: BufWriter<File>,
+    block_receiver: mpsc::Receiver<ExportBlockMessage>,
+}
+
+impl OutputExporter {
+    pub fn new(
+        db: Arc<dyn DbReader>,
+        path: &Path,
+        block_receiver: mpsc::Receiver<ExportBlockMessage>,
+    ) -> Self {
+        let file = File::create(path)
+            .unwrap_or_else(|err| panic!("Failed to create export file {:?}: {}", path, err));
+        Self {
+            db,
+            writer: BufWriter::new(file),
+            block_receiver,
+        }
+    }
+
+    pub fn run(&mut self) {
+        let mut num_exported = 0;
+        while let Ok(msg) = self.block_receiver.recv() {
+            let ExportBlockMessage {
+                first_version,
+                num_txns,
+            } = msg;
+            let batch = self.read_committed(first_version, num_txns);
+            write_batch(&mut self.writer, &batch).expect("Failed to write to export file.");
+            num_exported += batch.len();
+        }
+        self.writer.flush().expect("Failed to flush export file.");
+        info!("Exported {} committed transactions.", num_exported);
+    }
+
+    fn read_committed(&self, first_version: Version, num_txns: usize) -> Vec<ExportedTransaction> {
+        let ledger_version = first_version + num_txns as u64 - 1;
+        let mut exported = Vec::with_capacity(num_txns);
+        let mut version = first_version;
+        while version <= ledger_version {
+            let limit = (ledger_version - version + 1).min(MAX_REQUEST_LIMIT);
+            let outputs = self
+                .db
+                .get_transaction_outputs(version, limit, ledger_version)
+                .expect("Failed to read committed transaction outputs.");
+            for ((transaction, output), info) in outputs
+                .transactions_and_outputs
+                .into_iter()
+                .zip(outputs.proof.transaction_infos.into_iter())
+            {
+                let (write_set, events, _, _) = output.unpack();
+                exported.push(ExportedTransaction {
+                    version,
+                    transaction,
+                    info,
+                    events,
+                    write_set,
+                });
+                version += 1;
+            }
+        }
+        exported
+    }
+}
+
+fn write_batch(writer: &mut impl Write, batch: &[ExportedTransaction]) -> io::Result<()> {
+    let bytes = bcs::to_bytes(batch).expect("Exported transactions must serialize.");
+    writer.write_all(&(bytes.len() as u32).to_le_bytes())?;
+    writer.write_all(&bytes)
+}
+
+#[test]
+fn test_batches_round_trip() {
+    use aptos_crypto::HashValue;
+    use aptos_types::transaction::ExecutionStatus;
+
+    let exported = |version| ExportedTransaction {
+        version,
+        transaction: Transaction::StateCheckpoint(HashValue::random()),
+        info: TransactionInfo::new(
+            HashValue::random(),
+            HashValue::random(),
+            HashValue::random(),
+            None,
+            version,
+            ExecutionStatus::Success,
+        ),
+        events: vec![],
+        write_set: WriteSet::default(),
+    };
+    let batches = vec![vec![exported(1), exported(2)], vec![], vec![exported(3)]];
+    let mut file = vec![];
+    for batch in &batches {
+        write_batch(&mut file, batch).unwrap();
+    }
+
+    let mut read_batches = vec![];
+    let mut rest = file.as_slice();
+    while !rest.is_empty() {
+        let (len, tail) = rest.split_at(4);
+        let len = u32::from_le_bytes(len.try_into().unwrap()) as usize;
+        let (bytes, tail) = tail.split_at(len);
+        read_batches.push(bcs::from_bytes::<Vec<ExportedTransaction>>(bytes).unwrap());
+        rest = tail;
+    }
+    assert_eq!(read_batches, batches);
+}
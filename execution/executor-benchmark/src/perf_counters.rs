@@ -0,0 +1,317 @@
+// Copyright © Aptos Foundation
+// SPDX-License-Identifier: Apache-2.0
+
+use crate::{io_accounting, thread_utilization};
+use aptos_logger::{info, warn};
+use serde::Serialize;
+use std::{
+    collections::{BTreeMap, HashMap},
+    fs::File,
+    sync::{
+        atomic::{AtomicBool, Ordering},
+        Arc,
+    },
+    thread::JoinHandle,
+    time::Duration,
+};
+
+/// How often new threads are looked for, to count them from their first blocks on.
+const NEW_THREAD_SCAN_INTERVAL: Duration = Duration::from_millis(200);
+
+/// Hardware events counted for every thread.
+#[derive(Clone, Copy, Debug)]
+enum Event {
+    Cycles,
+    Instructions,
+    CacheMisses,
+}
+
+const EVENTS: [Event; 3] = [Event::Cycles, Event::Instructions, Event::CacheMisses];
+
+/// Hardware counters of the threads of a pipeline stage over the run, `None` for the events the
+/// CPU or the kernel do not count.
+#[derive(Clone, Debug, Default, Serialize)]
+pub struct StagePerf {
+    pub cycles: Option<u64>,
+    pub instructions: Option<u64>,
+    pub cache_misses: Option<u64>,
+}
+
+impl StagePerf {
+    /// Instructions per cycle.
+    pub fn ipc(&self) -> Option<f64> {
+        Some(self.instructions? as f64 / (self.cycles? as f64).max(1.0))
+    }
+
+    fn count(&self, event: Event) -> Option<u64> {
+        match event {
+            Event::Cycles => self.cycles,
+            Event::Instructions => self.instructions,
+            Event::CacheMisses => self.cache_misses,
+        }
+    }
+
+    fn count_mut(&mut self, event: Event) -> &mut Option<u64> {
+        match event {
+            Event::Cycles => &mut self.cycles,
+            Event::Instructions => &mut self.instructions,
+            Event::CacheMisses => &mut self.cache_misses,
+        }
+    }
+
+    fn add(&mut self, event: Event, count: u64) {
+        *self.count_mut(event).get_or_insert(0) += count;
+    }
+
+    fn is_empty(&self) -> bool {
+        EVENTS
+            .iter()
+            .all(|event| self.count(*event).unwrap_or(0) == 0)
+    }
+}
+
+struct ThreadCounters {
+    name: String,
+    counters: Vec<(Event, File)>,
+}
+
+/// Counts CPU cycles, instructions and cache misses of every thread of the process with perf
+/// events, user space only, and attributes them to pipeline stages by thread name. Threads are
+/// counted from when they are first seen, threads that exit keep their counts. Events that cannot
+/// be counted, e.g. cache misses in many VMs, are left out on their own.
+pub struct PerfCounterSampler {
+    stop: Arc<AtomicBool>,
+    handle: JoinHandle<HashMap<u64, ThreadCounters>>,
+}
+
+impl PerfCounterSampler {
+    pub fn start() -> Option<Self> {
+        let events: Vec<Event> = EVENTS
+            .into_iter()
+            .filter(|event| match sys::open_counter(0, *event) {
+                Ok(_) => true,
+                Err(err) => {
+                    warn!("Cannot count {:?} per stage: {}", event, err);
+                    false
+                },
+            })
+            .collect();
+        if events.is_empty() {
+            warn!(
+                "Hardware performance counters are not available, not counting cycles per stage. Lowering kernel.perf_event_paranoid may help."
+            );
+            return None;
+        }
+        let stop = Arc::new(AtomicBool::new(false));
+        let stop_clone = stop.clone();
+        let handle = std::thread::Builder::new()
+            .name("perf_counters".to_string())
+            .spawn(move || count_until_stopped(&stop_clone, &events))
+            .expect("Failed to spawn perf counter thread.");
+        Some(Self { stop, handle })
+    }
+
+    pub fn stop(self) -> BTreeMap<String, StagePerf> {
+        self.stop.store(true, Ordering::Relaxed);
+        let threads = self.handle.join().expect("Perf counter thread panicked.");
+        let mut by_stage: BTreeMap<String, StagePerf> = BTreeMap::new();
+        for thread in threads.values() {
+            let stage = by_stage
+                .entry(stage_of_thread(&thread.name).to_string())
+                .or_default();
+            for (event, counter) in &thread.counters {
+                match sys::read_counter(counter) {
+                    Ok(count) => stage.add(*event, count),
+                    Err(err) => warn!("Failed to read {:?} of {}: {}", event, thread.name, err),
+                }
+            }
+        }
+        by_stage
+    }
+}
+
+fn count_until_stopped(stop: &AtomicBool, events: &[Event]) -> HashMap<u64, ThreadCounters> {
+    let mut threads = HashMap::new();
+    loop {
+        for (tid, stat) in thread_utilization::read_thread_stats().unwrap_or_default() {
+            if threads.contains_key(&tid) {
+                continue;
+            }
+            let name = match thread_utilization::parse_stat(&stat) {
+                Some((name, _)) => name,
+                None => continue,
+            };
+            // Threads can exit between listing and opening, skip them.
+            let counters: Vec<_> = events
+                .iter()
+                .filter_map(|event| Some((*event, sys::open_counter(tid, *event).ok()?)))
+                .collect();
+            if !counters.is_empty() {
+                threads.insert(tid, ThreadCounters { name, counters });
+            }
+        }
+        if stop.load(Ordering::Relaxed) {
+            return threads;
+        }
+        std::thread::sleep(NEW_THREAD_SCAN_INTERVAL);
+    }
+}
+
+/// Maps a thread to the pipeline stage it works for, counting the workers of the executors as
+/// execution, since their cycles are what tells executor implementations apart.
+pub(crate) fn stage_of_thread(name: &str) -> &'static str {
+    match thread_utilization::pool_name(name).as_str() {
+        "par_exec" | "native_exe" | "sharded-executo" => "execution",
+        _ => io_accounting::stage_of_thread(name),
+    }
+}
+
+/// Logs the cycles and cache misses per transaction and the IPC of every stage, and of the whole
+/// process, with "-" for the events that were not counted.
+pub fn log_stage_perf(stage_perf: &BTreeMap<String, StagePerf>, num_txns: u64) {
+    let num_txns = num_txns.max(1) as f64;
+    let per_txn = |count: Option<u64>, precision: usize| {
+        count.map_or("-".to_string(), |count| {
+            format!("{:.*}", precision, count as f64 / num_txns)
+        })
+    };
+    let log = |stage: &str, perf: &StagePerf| {
+        info!(
+            "CPU efficiency of {}: {} cycles/txn, {} instructions/txn, IPC {}, {} cache misses/txn",
+            stage,
+            per_txn(perf.cycles, 0),
+            per_txn(perf.instructions, 0),
+            perf.ipc()
+                .map_or("-".to_string(), |ipc| format!("{:.2}", ipc)),
+            per_txn(perf.cache_misses, 1),
+        );
+    };
+    let mut total = StagePerf::default();
+    for (stage, perf) in stage_perf {
+        if perf.is_empty() {
+            continue;
+        }
+        log(stage, perf);
+        for event in EVENTS {
+            if let Some(count) = perf.count(event) {
+                total.add(event, count);
+            }
+        }
+    }
+    log("all threads", &total);
+}
+
+#[cfg(target_os = "linux")]
+mod sys {
+    use super::Event;
+    use std::{
+        fs::File,
+        io::{self, Read},
+        os::unix::io::FromRawFd,
+    };
+
+    const PERF_TYPE_HARDWARE: u32 = 0;
+    const PERF_COUNT_HW_CPU_CYCLES: u64 = 0;
+    const PERF_COUNT_HW_INSTRUCTIONS: u64 = 1;
+    const PERF_COUNT_HW_CACHE_MISSES: u64 = 3;
+    /// Size of the first version of `perf_event_attr`, which has all the fields needed here.
+    const PERF_ATTR_SIZE_VER0: u32 = 64;
+    const EXCLUDE_KERNEL: u64 = 1 << 5;
+    const EXCLUDE_HV: u64 = 1 << 6;
+    const PERF_FLAG_FD_CLOEXEC: libc::c_ulong = 1 << 3;
+
+    /// `struct perf_event_attr` of linux/perf_event.h, up to `config1`. The bit fields are
+    /// folded into `flags`.
+    #[repr(C)]
+    #[derive(Default)]
+    struct PerfEventAttr {
+        type_: u32,
+        size: u32,
+        config: u64,
+        sample_period: u64,
+        sample_type: u64,
+        read_format: u64,
+        flags: u64,
+        wakeup_events: u32,
+        bp_type: u32,
+        config1: u64,
+    }
+
+    /// Opens a counter of `event` for the thread `tid` (0 for the calling thread), on any CPU.
+    /// Counting starts right away.
+    pub(super) fn open_counter(tid: u64, event: Event) -> io::Result<File> {
+        let attr = PerfEventAttr {
+            type_: PERF_TYPE_HARDWARE,
+            size: PERF_ATTR_SIZE_VER0,
+            config: match event {
+                Event::Cycles => PERF_COUNT_HW_CPU_CYCLES,
+                Event::Instructions => PERF_COUNT_HW_INSTRUCTIONS,
+                Event::CacheMisses => PERF_COUNT_HW_CACHE_MISSES,
+            },
+            // Counting user space only is allowed with the default perf_event_paranoid.
+            flags: EXCLUDE_KERNEL | EXCLUDE_HV,
+            ..Default::default()
+        };
+        // SAFETY: `attr` outlives the call, and its size is given in `attr.size`.
+        let fd = unsafe {
+            libc::syscall(
+                libc::SYS_perf_event_open,
+                &attr as *const PerfEventAttr,
+                tid as libc::pid_t,
+                -1 as libc::c_int,
+                -1 as libc::c_int,
+                PERF_FLAG_FD_CLOEXEC,
+            )
+        };
+        if fd < 0 {
+            return Err(io::Error::last_os_error());
+        }
+        // SAFETY: the fd was just opened, and is owned by nothing else.
+        Ok(unsafe { File::from_raw_fd(fd as libc::c_int) })
+    }
+
+    pub(super) fn read_counter(mut counter: &File) -> io::Result<u64> {
+        let mut count = [0u8; 8];
+        counter.read_exact(&mut count)?;
+        Ok(u64::from_ne_bytes(count))
+    }
+}
+
+#[cfg(not(target_os = "linux"))]
+mod sys {
+    use super::Event;
+    use std::{fs::File, io};
+
+    pub(super) fn open_counter(_tid: u64, _event: Event) -> io::Result<File> {
+        Err(io::Error::new(
+            io::ErrorKind::Unsupported,
+            "perf events are only available on Linux",
+        ))
+    }
+
+    pub(super) fn read_counter(_counter: &File) -> io::Result<u64> {
+        Ok(0)
+    }
+}
+
+#[test]
+fn test_stage_perf_with_missing_events() {
+    let mut perf = StagePerf::default();
+    assert!(perf.is_empty());
+    perf.add(Event::Cycles, 100);
+    perf.add(Event::Cycles, 100);
+    assert!(!perf.is_empty());
+    assert_eq!(perf.cycles, Some(200));
+    assert_eq!(perf.ipc(), None);
+    perf.add(Event::Instructions, 300);
+    assert_eq!(perf.ipc(), Some(1.5));
+    assert_eq!(perf.cache_misses, None);
+}
+
+#[test]
+fn test_stage_of_thread() {
+    assert_eq!(stage_of_thread("par_exec-12"), "execution");
+    assert_eq!(stage_of_thread("txn_executor"), "execution");
+    assert_eq!(stage_of_thread("txn_committer"), "commit");
+    assert_eq!(stage_of_thread("rayon-global-3"), "other");
+}
@@ -2,8 +2,24 @@
 // SPDX-License-Identifier: Apache-2.0
 
 use crate::{
-    block_preparation::BlockPreparationStage, ledger_update_stage::LedgerUpdateStage,
-    metrics::NUM_TXNS, GasMeasuring, TransactionCommitter, TransactionExecutor,
+    adaptive_block_size::AdaptiveBlockSize,
+    backup_under_load::{BackupKind, BackupUnderLoad},
+    block_arrival::BlockArrivalSchedule,
+    block_preparation::BlockPreparationStage,
+    block_sidecar::BlockSidecarWriter,
+    block_snapshots::BlockSnapshotter,
+    block_watchdog::BlockWatchdog,
+    duplicate_injection::{DuplicateInjection, DuplicateInjector, InjectedTxns},
+    ledger_update_stage::LedgerUpdateStage,
+    metrics::{NUM_TXNS, TIMER},
+    metrics_db::MetricsDbRecorder,
+    module_cache::{self, ModuleCacheMode},
+    output_exporter::{ExportBlockMessage, OutputExporter},
+    post_commit::{PostCommitCheck, PostCommitPlugins},
+    rw_set_estimation::{RwSetAccuracyChecker, RwSetEstimates},
+    shadow_verifier::ShadowVerifier,
+    stage_delay::{self, StageDelays},
+    starvation_detector, GasMeasuring, TransactionCommitter, TransactionExecutor,
 };
 use aptos_block_partitioner::v2::config::PartitionerV2Config;
 use aptos_crypto::HashValue;
@@ -17,9 +33,12 @@ use aptos_types::{
     block_executor::partitioner::ExecutableBlock,
     transaction::{Transaction, Version},
 };
+use aptos_vm::AptosVM;
 use derivative::Derivative;
+use move_core_types::language_storage::ModuleId;
 use std::{
     marker::PhantomData,
+    path::PathBuf,
     sync::{
         mpsc::{self, SyncSender},
         Arc,
@@ -28,6 +47,9 @@ use std::{
     time::{Duration, Instant},
 };
 
+/// Number of committed blocks the output exporter can fall behind the commit stage by.
+const EXPORT_CHANNEL_BOUND: usize = 16;
+
 #[derive(Debug, Derivative)]
 #[derivative(Default)]
 pub struct PipelineConfig {
@@ -42,12 +64,96 @@ pub struct PipelineConfig {
     #[derivative(Default(value = "4"))]
     pub num_generator_workers: usize,
     pub partitioner_config: PartitionerV2Config,
+    /// Upper bound on the serialized size of generated blocks.
+    pub max_block_bytes: Option<usize>,
+    /// Grow generated blocks up to `max_block_bytes`, instead of only capping them.
+    pub large_block_stress: bool,
+    /// Skip generating blocks while the process RSS is above this limit.
+    pub memory_guardrail_bytes: Option<u64>,
+    /// If set, blocks enter the pipeline as a Poisson process with this mean rate (blocks/s),
+    /// instead of back-to-back.
+    pub block_arrival_rate: Option<f64>,
+    /// Number of blocks arriving together in each burst, when `block_arrival_rate` is set.
+    #[derivative(Default(value = "1"))]
+    pub block_arrival_burst_size: usize,
+    /// Delays every block is held back for in front of the stages, to model consensus timing.
+    pub stage_delays: StageDelays,
+    /// Duplicate and replayed transactions injected into every block.
+    pub duplicate_injection: DuplicateInjection,
+    /// If set, blocks are cut out of the transactions of this mempool capture, instead of being
+    /// generated.
+    pub mempool_capture_path: Option<PathBuf>,
+    /// Blocks of a mempool capture take the transactions broadcast within this window.
+    #[derivative(Default(value = "Duration::from_millis(250)"))]
+    pub mempool_capture_window: Duration,
+    /// If set, the user transactions of the generated blocks are written to this file, as a
+    /// mempool capture that replays the same blocks.
+    pub record_workload_path: Option<PathBuf>,
+    /// If set, committed transactions, events and write sets are exported to this file.
+    pub export_outputs_path: Option<PathBuf>,
+    /// If set, historical state reads are issued at this rate in the background while the
+    /// benchmark runs.
+    pub historical_read_qps: Option<f64>,
+    /// How far behind the latest version historical reads go.
+    #[derivative(Default(value = "100_000"))]
+    pub historical_read_max_lag_versions: u64,
+    /// How many times a block that failed to execute, update the ledger or commit is retried,
+    /// before giving up.
+    pub max_block_retries: usize,
+    /// If set, the version range and roots of each committed block are written to this file.
+    pub block_sidecar_path: Option<PathBuf>,
+    /// If set, a checkpoint of the DB is taken into `snapshot_dir` every this many blocks.
+    pub snapshot_every: Option<usize>,
+    pub snapshot_dir: Option<PathBuf>,
+    /// If set, a backup of the DB is taken into `backup_dir` after this block, while the
+    /// workload keeps running.
+    pub backup_at_block: Option<usize>,
+    #[derivative(Default(value = "BackupKind::Checkpoint"))]
+    pub backup_kind: BackupKind,
+    pub backup_dir: Option<PathBuf>,
+    /// If set, the in-memory state of every this many committed blocks is compared with what was
+    /// persisted to the DB, in the background.
+    pub shadow_verify_every: Option<usize>,
+    /// If set, the metrics of every committed block are recorded in the metrics DB at this path.
+    pub metrics_db_path: Option<PathBuf>,
+    /// If set, the CPU utilization of each thread pool is sampled at this interval during the run.
+    pub thread_utilization_sample_interval: Option<Duration>,
+    /// If set, the thread utilization timeline is also written to this file, as CSV.
+    pub thread_utilization_csv_path: Option<PathBuf>,
+    /// If set, the jemalloc stats are sampled at this interval during the run, and size classes
+    /// that keep growing are reported as possible leaks.
+    pub leak_check_interval: Option<Duration>,
+    /// Count CPU cycles, instructions and cache misses of each stage with hardware counters.
+    pub perf_counters: bool,
+    /// If set, the execution threads are pinned to these cores.
+    pub execution_cores: Option<Vec<usize>>,
+    /// If set, the RocksDB background (compaction and flush) threads are pinned to these cores.
+    pub rocksdb_cores: Option<Vec<usize>>,
+    /// Checks run on the outputs of every committed block.
+    pub post_commit_checks: Vec<PostCommitCheck>,
+    /// Estimate the read and write sets of transactions before execution, and compare them to
+    /// the actual write sets once committed.
+    pub estimate_rw_sets: bool,
+    /// If set, the stacks are dumped when executing a block takes longer than this.
+    pub block_execution_timeout: Option<Duration>,
+    /// Abort the run once the stacks of a block over the execution timeout are dumped.
+    pub abort_on_stuck_block: bool,
+    /// If set, stages whose threads are all blocked for longer than this are reported.
+    pub starvation_threshold: Option<Duration>,
+    /// If set, the size of generated blocks is adjusted to target this block execution latency.
+    pub target_block_latency: Option<Duration>,
+    /// How the VM module cache is treated across blocks.
+    pub module_cache_mode: ModuleCacheMode,
+    /// Modules loaded before the run, with `ModuleCacheMode::PrewarmList`.
+    pub prewarm_modules: Vec<ModuleId>,
 }
 
 pub struct Pipeline<V> {
     join_handles: Vec<JoinHandle<()>>,
     phantom: PhantomData<V>,
     start_execution_tx: Option<SyncSender<()>>,
+    adaptive_block_size: Option<Arc<AdaptiveBlockSize>>,
+    injected_txns: Option<Arc<InjectedTxns>>,
 }
 
 impl<V> Pipeline<V>
@@ -110,10 +216,60 @@ where
 
         let mut join_handles = vec![];
 
-        let mut partitioning_stage =
-            BlockPreparationStage::new(num_partitioner_shards, &config.partitioner_config);
+        let ledger_update_sender = match config.stage_delays.before_ledger_update {
+            Some(delay) => stage_delay::spawn_delay_line(
+                "ledger_update",
+                delay,
+                ledger_update_sender,
+                &mut join_handles,
+            ),
+            None => ledger_update_sender,
+        };
+        let commit_sender = match config.stage_delays.before_commit {
+            Some(delay) => {
+                stage_delay::spawn_delay_line("commit", delay, commit_sender, &mut join_handles)
+            },
+            None => commit_sender,
+        };
 
-        let mut exe = TransactionExecutor::new(executor_1, parent_block_id, ledger_update_sender);
+        if let Some(threshold) = config.starvation_threshold {
+            starvation_detector::start_once(threshold);
+        }
+
+        let rw_set_estimates = config
+            .estimate_rw_sets
+            .then(|| Arc::new(RwSetEstimates::default()));
+        let mut partitioning_stage = BlockPreparationStage::new(
+            num_partitioner_shards,
+            &config.partitioner_config,
+            rw_set_estimates.clone(),
+        );
+        let injected_txns = config
+            .duplicate_injection
+            .is_enabled()
+            .then(|| Arc::new(InjectedTxns::default()));
+        let mut duplicate_injector = config
+            .duplicate_injection
+            .is_enabled()
+            .then(|| DuplicateInjector::new(config.duplicate_injection));
+        let mut arrival_schedule = config
+            .block_arrival_rate
+            .map(|rate| BlockArrivalSchedule::new(rate, config.block_arrival_burst_size));
+
+        if config.module_cache_mode == ModuleCacheMode::PrewarmList {
+            module_cache::prewarm_modules(&executor_1.db.reader, &config.prewarm_modules);
+        }
+        let flush_module_cache = config.module_cache_mode == ModuleCacheMode::Cold;
+
+        let mut exe = TransactionExecutor::new(
+            executor_1,
+            parent_block_id,
+            ledger_update_sender,
+            config.max_block_retries,
+            config
+                .block_execution_timeout
+                .map(|timeout| BlockWatchdog::new(timeout, config.abort_on_stuck_block)),
+        );
 
         let mut ledger_update_stage = LedgerUpdateStage::new(
             executor_2,
@@ -121,47 +277,111 @@ where
             version,
             config.allow_discards,
             config.allow_aborts,
+            injected_txns.clone(),
+            config.max_block_retries,
         );
+        let partitioning_injected_txns = injected_txns.clone();
 
         let (executable_block_sender, executable_block_receiver) =
             mpsc::sync_channel::<ExecuteBlockMessage>(3);
+        let executable_block_sender = match config.stage_delays.before_execution {
+            Some(delay) => stage_delay::spawn_delay_line(
+                "execution",
+                delay,
+                executable_block_sender,
+                &mut join_handles,
+            ),
+            None => executable_block_sender,
+        };
 
+        let max_block_retries = config.max_block_retries;
         let partitioning_thread = std::thread::Builder::new()
             .name("block_partitioning".to_string())
             .spawn(move || {
-                while let Ok(txns) = raw_block_receiver.recv() {
+                while let Ok(mut txns) = raw_block_receiver.recv() {
+                    let mut injected = vec![];
+                    if let Some(duplicate_injector) = &mut duplicate_injector {
+                        (txns, injected) = duplicate_injector.inject(txns);
+                    }
+                    let block_ready_time = arrival_schedule
+                        .as_mut()
+                        .map(|schedule| schedule.wait_for_next_arrival());
                     NUM_TXNS
                         .with_label_values(&["partition"])
                         .inc_by(txns.len() as u64);
-                    let exe_block_msg = partitioning_stage.process(txns);
+                    let active_stage = starvation_detector::enter_stage("partitioning");
+                    let mut exe_block_msg = partitioning_stage.process(txns);
+                    drop(active_stage);
+                    if let Some(injected_txns) = &partitioning_injected_txns {
+                        injected_txns.add_block(exe_block_msg.block.block_id, injected);
+                    }
+                    if let Some(block_ready_time) = block_ready_time {
+                        exe_block_msg.block_ready_time = block_ready_time;
+                    }
+                    if max_block_retries > 0 {
+                        exe_block_msg.retry_block = Some(exe_block_msg.block.clone());
+                    }
                     executable_block_sender.send(exe_block_msg).unwrap();
                 }
             })
             .expect("Failed to spawn block partitioner thread.");
         join_handles.push(partitioning_thread);
 
+        let adaptive_block_size = config
+            .target_block_latency
+            .map(|target_latency| Arc::new(AdaptiveBlockSize::new(target_latency)));
+        let exe_adaptive_block_size = adaptive_block_size.clone();
+
         let exe_thread = std::thread::Builder::new()
             .name("txn_executor".to_string())
             .spawn(move || {
                 start_execution_rx.map(|rx| rx.recv());
                 let start_time = Instant::now();
                 let mut executed = 0;
+                let mut num_blocks = 0;
+                let mut total_queueing_delay = Duration::ZERO;
+                let mut max_queueing_delay = Duration::ZERO;
                 let start_gas_measurement = GasMeasuring::start();
                 let start_output_size = APTOS_PROCESSED_TXNS_OUTPUT_SIZE.get();
                 while let Ok(msg) = executable_block_receiver.recv() {
                     let ExecuteBlockMessage {
                         current_block_start_time,
                         partition_time,
+                        block_ready_time,
                         block,
+                        retry_block,
                     } = msg;
+                    // Time the block spent waiting between being ready and the start of its
+                    // execution, not counting the time spent partitioning it.
+                    let queueing_delay = block_ready_time.elapsed().saturating_sub(partition_time);
+                    TIMER
+                        .with_label_values(&["block_queueing_delay"])
+                        .observe(queueing_delay.as_secs_f64());
+                    total_queueing_delay += queueing_delay;
+                    max_queueing_delay = max_queueing_delay.max(queueing_delay);
+                    num_blocks += 1;
                     let block_size = block.transactions.num_transactions();
                     NUM_TXNS
                         .with_label_values(&["execution"])
                         .inc_by(block_size as u64);
-                    info!("Received block of size {:?} to execute", block_size);
+                    info!(
+                        "Received block of size {:?} to execute, queueing delay {} ms",
+                        block_size,
+                        queueing_delay.as_millis()
+                    );
                     executed += block_size;
-                    exe.execute_block(current_block_start_time, partition_time, block);
+                    if flush_module_cache {
+                        AptosVM::flush_warm_vm_cache();
+                    }
+                    let block_execution_start = Instant::now();
+                    let active_stage = starvation_detector::enter_stage("execution");
+                    exe.execute_block(current_block_start_time, partition_time, block, retry_block);
+                    drop(active_stage);
                     info!("Finished executing block");
+                    if let Some(adaptive_block_size) = &exe_adaptive_block_size {
+                        adaptive_block_size
+                            .record_block(block_size, block_execution_start.elapsed());
+                    }
                 }
 
                 let delta_gas = start_gas_measurement.end();
@@ -198,6 +418,15 @@ where
                     "Overall execution output: {} bytes/s",
                     delta_output_size as f64 / elapsed
                 );
+                info!(
+                    "Overall block queueing delay: avg {} ms, max {} ms (over {} blocks)",
+                    total_queueing_delay.as_millis() / (num_blocks as u128).max(1),
+                    max_queueing_delay.as_millis(),
+                    num_blocks
+                );
+                if let Some(adaptive_block_size) = &exe_adaptive_block_size {
+                    adaptive_block_size.log_trajectory();
+                }
 
                 start_commit_tx.map(|tx| tx.send(()));
             })
@@ -215,6 +444,7 @@ where
                     NUM_TXNS
                         .with_label_values(&["ledger_update"])
                         .inc_by(block_size as u64);
+                    let _active_stage = starvation_detector::enter_stage("ledger_update");
                     ledger_update_stage.ledger_update(ledger_update_msg);
                 }
             })
@@ -222,6 +452,62 @@ where
         join_handles.push(ledger_update_thread);
 
         let skip_commit = config.skip_commit;
+        let sidecar_writer = config
+            .block_sidecar_path
+            .as_deref()
+            .map(BlockSidecarWriter::new);
+        let snapshotter = config
+            .snapshot_every
+            .zip(config.snapshot_dir.as_deref())
+            .map(|(every_blocks, dir)| BlockSnapshotter::new(dir, every_blocks));
+        let backup = config
+            .backup_at_block
+            .zip(config.backup_dir.as_deref())
+            .map(|(at_block, dir)| BackupUnderLoad::new(dir, config.backup_kind, at_block));
+        let shadow_verifier = config
+            .shadow_verify_every
+            .map(|every_blocks| ShadowVerifier::new(executor_3.db.reader.clone(), every_blocks));
+        let metrics_db_recorder = config.metrics_db_path.as_deref().map(|path| {
+            MetricsDbRecorder::start(path)
+                .unwrap_or_else(|err| panic!("Failed to open metrics DB {:?}: {}", path, err))
+        });
+
+        let export_sender = config.export_outputs_path.as_ref().map(|path| {
+            // Bounded, so that a slow export file back-pressures the commit stage, rather than
+            // queueing up blocks without limit.
+            let (export_sender, export_receiver) =
+                mpsc::sync_channel::<ExportBlockMessage>(EXPORT_CHANNEL_BOUND);
+            let mut exporter =
+                OutputExporter::new(executor_3.db.reader.clone(), path, export_receiver);
+            let export_thread = std::thread::Builder::new()
+                .name("output_exporter".to_string())
+                .spawn(move || exporter.run())
+                .expect("Failed to spawn output exporter thread.");
+            join_handles.push(export_thread);
+            export_sender
+        });
+
+        let post_commit_sender = if config.post_commit_checks.is_empty()
+            && rw_set_estimates.is_none()
+        {
+            None
+        } else {
+            let (post_commit_sender, post_commit_receiver) = mpsc::channel::<ExportBlockMessage>();
+            let mut plugins = PostCommitPlugins::new(
+                executor_3.db.reader.clone(),
+                &config.post_commit_checks,
+                post_commit_receiver,
+            );
+            if let Some(rw_set_estimates) = rw_set_estimates {
+                plugins.register(Box::new(RwSetAccuracyChecker::new(rw_set_estimates)));
+            }
+            let post_commit_thread = std::thread::Builder::new()
+                .name("post_commit".to_string())
+                .spawn(move || plugins.run())
+                .expect("Failed to spawn post-commit plugin thread.");
+            join_handles.push(post_commit_thread);
+            Some(post_commit_sender)
+        };
 
         let commit_thread = std::thread::Builder::new()
             .name("txn_committer".to_string())
@@ -229,8 +515,19 @@ where
                 start_commit_rx.map(|rx| rx.recv());
                 info!("Starting commit thread");
                 if !skip_commit {
-                    let mut committer =
-                        TransactionCommitter::new(executor_3, version, commit_receiver);
+                    let mut committer = TransactionCommitter::new(
+                        executor_3,
+                        version,
+                        commit_receiver,
+                        export_sender,
+                        post_commit_sender,
+                        max_block_retries,
+                        sidecar_writer,
+                        snapshotter,
+                        backup,
+                        shadow_verifier,
+                        metrics_db_recorder,
+                    );
                     committer.run();
                 }
             })
@@ -242,6 +539,8 @@ where
                 join_handles,
                 phantom: PhantomData,
                 start_execution_tx,
+                adaptive_block_size,
+                injected_txns,
             },
             raw_block_sender,
         )
@@ -251,6 +550,16 @@ where
         self.start_execution_tx.as_ref().map(|tx| tx.send(()));
     }
 
+    /// The block size controller, if the pipeline targets a block latency.
+    pub fn adaptive_block_size(&self) -> Option<Arc<AdaptiveBlockSize>> {
+        self.adaptive_block_size.clone()
+    }
+
+    /// Outcomes of the transactions injected into the blocks, if any are injected.
+    pub fn injected_txns(&self) -> Option<Arc<InjectedTxns>> {
+        self.injected_txns.clone()
+    }
+
     pub fn join(self) {
         for handle in self.join_handles {
             handle.join().unwrap()
@@ -262,7 +571,13 @@ where
 pub struct ExecuteBlockMessage {
     pub current_block_start_time: Instant,
     pub partition_time: Duration,
+    /// When the block became ready for processing; with a block arrival schedule this is its
+    /// (simulated) arrival time, otherwise when partitioning started.
+    pub block_ready_time: Instant,
     pub block: ExecutableBlock,
+    /// Copy of the block to retry it with, made off the critical path of execution when block
+    /// retries are enabled.
+    pub retry_block: Option<ExecutableBlock>,
 }
 
 pub struct LedgerUpdateMessage {
@@ -273,6 +588,8 @@ pub struct LedgerUpdateMessage {
     pub parent_block_id: HashValue,
     pub state_checkpoint_output: StateCheckpointOutput,
     pub first_block_start_time: Instant,
+    /// Copy of the block, to execute it again if its ledger update fails.
+    pub retry_block: Option<ExecutableBlock>,
 }
 
 /// Message from execution stage to commit stage.
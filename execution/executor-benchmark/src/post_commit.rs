@@ -0,0 +1,349 @@
+// Copyright © Aptos Foundation
+// SPDX-License-Identifier: Apache-2.0
+
+use crate::{
+    db_access::{CoinStore, DbAccessUtil},
+    output_exporter::ExportBlockMessage,
+    sharded_verifier::ShardedOutputVerifier,
+};
+use anyhow::{anyhow, ensure, Result};
+use aptos_logger::info;
+use aptos_storage_interface::{
+    state_view::LatestDbStateCheckpointView, DbReader, MAX_REQUEST_LIMIT,
+};
+use aptos_types::{
+    access_path::AccessPath,
+    account_address::AccountAddress,
+    state_store::state_key::{StateKey, StateKeyInner},
+    transaction::{Transaction, TransactionOutput, Version},
+};
+use move_core_types::language_storage::TypeTag;
+use std::{
+    collections::HashMap,
+    sync::{mpsc, Arc},
+};
+
+/// A built-in post-commit check, selectable from the command line.
+#[derive(Clone, Debug)]
+pub enum PostCommitCheck {
+    /// Fails if a committed user transaction emitted a different number of events.
+    EventsPerTxn(usize),
+    /// Fails if coins are created or destroyed other than by burning fees, i.e. if the balances
+    /// stop adding up to the total supply. Meant for transfer workloads, as minting legitimately
+    /// increases the supply. Balances can't go negative, Move aborts the withdrawals they don't
+    /// cover, but a balance written from a stale read (e.g. by a parallel execution bug) breaks
+    /// the conservation. All balances are kept in memory for the duration of the run.
+    BalanceConservation,
+    /// Fails if a committed block executes to different outputs unsharded, checking every this
+    /// many blocks.
+    ShardedOutputs(usize),
+}
+
+impl PostCommitCheck {
+    fn create_plugin(&self, db: &Arc<dyn DbReader>) -> Box<dyn PostCommitPlugin> {
+        match self {
+            Self::EventsPerTxn(expected) => Box::new(EventCountAsserter::new(*expected)),
+            Self::BalanceConservation => Box::new(
+                BalanceConservationChecker::new(db)
+                    .expect("Failed to snapshot the balances before the run."),
+            ),
+            Self::ShardedOutputs(every_blocks) => {
+                Box::new(ShardedOutputVerifier::new(db.clone(), *every_blocks))
+            },
+        }
+    }
+}
+
+/// Workload-specific validation that runs on the outputs of every committed block.
+pub trait PostCommitPlugin: Send {
+    fn name(&self) -> &'static str;
+
+    /// Called with the transactions and outputs of each committed block, in commit order.
+    /// Returning an error aborts the benchmark.
+    fn process_block(
+        &mut self,
+        first_version: Version,
+        txns_and_outputs: &[(Transaction, TransactionOutput)],
+    ) -> Result<()>;
+
+    /// Called once after the last block, e.g. to report a summary.
+    fn finish(&mut self) {}
+}
+
+/// Runs the registered plugins on every committed block.
+/// Like the output exporter, the outputs are read back from the DB on a separate thread, so the
+/// plugins don't affect the commit stage timings.
+pub struct PostCommitPlugins {
+    db: Arc<dyn DbReader>,
+    plugins: Vec<Box<dyn PostCommitPlugin>>,
+    block_receiver: mpsc::Receiver<ExportBlockMessage>,
+}
+
+impl PostCommitPlugins {
+    pub fn new(
+        db: Arc<dyn DbReader>,
+        checks: &[PostCommitCheck],
+        block_receiver: mpsc::Receiver<ExportBlockMessage>,
+    ) -> Self {
+        let plugins = checks
+            .iter()
+            .map(|check| check.create_plugin(&db))
+            .collect();
+        Self {
+            db,
+            plugins,
+            block_receiver,
+        }
+    }
+
+    pub fn register(&mut self, plugin: Box<dyn PostCommitPlugin>) {
+        self.plugins.push(plugin);
+    }
+
+    pub fn run(&mut self) {
+        while let Ok(msg) = self.block_receiver.recv() {
+            let ExportBlockMessage {
+                first_version,
+                num_txns,
+            } = msg;
+            let txns_and_outputs = read_committed(&self.db, first_version, num_txns)
+                .expect("Failed to read committed transaction outputs.");
+            for plugin in &mut self.plugins {
+                if let Err(err) = plugin.process_block(first_version, &txns_and_outputs) {
+                    panic!(
+                        "Post-commit plugin {} failed on block starting at version {}: {}",
+                        plugin.name(),
+                        first_version,
+                        err
+                    );
+                }
+            }
+        }
+        for plugin in &mut self.plugins {
+            plugin.finish();
+        }
+    }
+}
+
+pub(crate) fn read_committed(
+    db: &Arc<dyn DbReader>,
+    first_version: Version,
+    num_txns: usize,
+) -> Result<Vec<(Transaction, TransactionOutput)>> {
+    let ledger_version = first_version + num_txns as u64 - 1;
+    let mut txns_and_outputs = Vec::with_capacity(num_txns);
+    let mut version = first_version;
+    while version <= ledger_version {
+        let limit = (ledger_version - version + 1).min(MAX_REQUEST_LIMIT);
+        let outputs = db.get_transaction_outputs(version, limit, ledger_version)?;
+        version += outputs.transactions_and_outputs.len() as u64;
+        txns_and_outputs.extend(outputs.transactions_and_outputs);
+    }
+    Ok(txns_and_outputs)
+}
+
+pub(crate) fn aptos_coin_store_path() -> Vec<u8> {
+    AccessPath::resource_path_vec(DbAccessUtil::new_struct_tag(
+        AccountAddress::ONE,
+        "coin",
+        "CoinStore",
+        vec![TypeTag::Struct(Box::new(DbAccessUtil::new_struct_tag(
+            AccountAddress::ONE,
+            "aptos_coin",
+            "AptosCoin",
+            vec![],
+        )))],
+    ))
+    .expect("CoinStore must have a resource path.")
+}
+
+fn get_total_supply(db: &Arc<dyn DbReader>) -> u128 {
+    DbAccessUtil::get_total_supply(&db.latest_state_checkpoint_view().unwrap())
+        .unwrap()
+        .expect("Total supply must be tracked to check balances against it.")
+}
+
+pub struct EventCountAsserter {
+    expected_per_txn: usize,
+    num_checked: usize,
+}
+
+impl EventCountAsserter {
+    pub fn new(expected_per_txn: usize) -> Self {
+        Self {
+            expected_per_txn,
+            num_checked: 0,
+        }
+    }
+}
+
+impl PostCommitPlugin for EventCountAsserter {
+    fn name(&self) -> &'static str {
+        "events_per_txn"
+    }
+
+    fn process_block(
+        &mut self,
+        first_version: Version,
+        txns_and_outputs: &[(Transaction, TransactionOutput)],
+    ) -> Result<()> {
+        for (idx, (txn, output)) in txns_and_outputs.iter().enumerate() {
+            if !matches!(txn, Transaction::UserTransaction(_)) {
+                continue;
+            }
+            ensure!(
+                output.events().len() == self.expected_per_txn,
+                "Transaction at version {} emitted {} events, expected {}.",
+                first_version + idx as u64,
+                output.events().len(),
+                self.expected_per_txn,
+            );
+            self.num_checked += 1;
+        }
+        Ok(())
+    }
+
+    fn finish(&mut self) {
+        info!(
+            "Post-commit check: {} user transactions emitted {} events each.",
+            self.num_checked, self.expected_per_txn
+        );
+    }
+}
+
+/// Checks that the balances add up to the total supply after every block, the supply only going
+/// down by the fees burned. The balances of all accounts are snapshotted before the run and kept
+/// up to date with the committed write sets, so no state older than the latest version is read
+/// from the DB, where the pruner may have removed it.
+pub struct BalanceConservationChecker {
+    coin_store_path: Vec<u8>,
+    balances: HashMap<StateKey, u64>,
+    total_balance: u128,
+    /// Coins held outside of the coin stores (e.g. staked), which transfers don't move.
+    held_elsewhere: i128,
+    initial_supply: u128,
+    total_supply: u128,
+    num_blocks: usize,
+}
+
+impl BalanceConservationChecker {
+    pub fn new(db: &Arc<dyn DbReader>) -> Result<Self> {
+        let mut checker = Self {
+            coin_store_path: aptos_coin_store_path(),
+            balances: HashMap::new(),
+            total_balance: 0,
+            held_elsewhere: 0,
+            initial_supply: 0,
+            total_supply: 0,
+            num_blocks: 0,
+        };
+        let latest_version = db.get_latest_version()?;
+        let (snapshot_version, _) = db
+            .get_state_snapshot_before(latest_version + 1)?
+            .ok_or_else(|| anyhow!("No state snapshot to read the balances from."))?;
+        let num_leaves = db.get_state_leaf_count(snapshot_version)?;
+        let mut num_read = 0;
+        while num_read < num_leaves {
+            let chunk = db.get_state_value_chunk_with_proof(
+                snapshot_version,
+                num_read,
+                MAX_REQUEST_LIMIT as usize,
+            )?;
+            ensure!(
+                !chunk.raw_values.is_empty(),
+                "No state values at index {}",
+                num_read
+            );
+            num_read += chunk.raw_values.len();
+            for (state_key, state_value) in chunk.raw_values {
+                checker.apply_write(state_key, Some(state_value.bytes()))?;
+            }
+        }
+        // Catch up with the transactions committed after the snapshot.
+        if latest_version > snapshot_version {
+            let num_txns = (latest_version - snapshot_version) as usize;
+            for (_, output) in read_committed(db, snapshot_version + 1, num_txns)? {
+                checker.apply_write_set(&output)?;
+            }
+        }
+        checker.initial_supply = get_total_supply(db);
+        checker.total_supply = checker.initial_supply;
+        checker.held_elsewhere = checker.initial_supply as i128 - checker.total_balance as i128;
+        info!(
+            "Post-commit check: snapshotted {} balances adding up to {} at version {}, {} octas of the supply are held elsewhere.",
+            checker.balances.len(),
+            checker.total_balance,
+            latest_version,
+            checker.held_elsewhere,
+        );
+        Ok(checker)
+    }
+
+    fn apply_write_set(&mut self, output: &TransactionOutput) -> Result<()> {
+        if let Some(total_supply) = output.write_set().get_total_supply() {
+            self.total_supply = total_supply;
+        }
+        for (state_key, op) in output.write_set() {
+            self.apply_write(state_key.clone(), op.bytes().map(|bytes| bytes.as_ref()))?;
+        }
+        Ok(())
+    }
+
+    /// Applies a write of `state_key`, `None` deleting it, if it is a coin store.
+    fn apply_write(&mut self, state_key: StateKey, bytes: Option<&[u8]>) -> Result<()> {
+        if !matches!(
+            state_key.inner(),
+            StateKeyInner::AccessPath(access_path) if access_path.path == self.coin_store_path
+        ) {
+            return Ok(());
+        }
+        let old_balance = match bytes {
+            Some(bytes) => {
+                let balance = bcs::from_bytes::<CoinStore>(bytes)?.coin;
+                self.total_balance += balance as u128;
+                self.balances.insert(state_key, balance)
+            },
+            None => self.balances.remove(&state_key),
+        };
+        self.total_balance -= old_balance.unwrap_or(0) as u128;
+        Ok(())
+    }
+}
+
+impl PostCommitPlugin for BalanceConservationChecker {
+    fn name(&self) -> &'static str {
+        "balance_conservation"
+    }
+
+    fn process_block(
+        &mut self,
+        first_version: Version,
+        txns_and_outputs: &[(Transaction, TransactionOutput)],
+    ) -> Result<()> {
+        for (_, output) in txns_and_outputs {
+            self.apply_write_set(output)?;
+        }
+        let total = self.total_balance as i128 + self.held_elsewhere;
+        ensure!(
+            total == self.total_supply as i128,
+            "Balances add up to {} (with the {} octas held elsewhere) after the block starting at version {}, but the total supply is {}: {} octas were created or destroyed other than by burning fees.",
+            total,
+            self.held_elsewhere,
+            first_version,
+            self.total_supply,
+            total - self.total_supply as i128,
+        );
+        self.num_blocks += 1;
+        Ok(())
+    }
+
+    fn finish(&mut self) {
+        info!(
+            "Post-commit check: balances add up to {} and {} octas of fees were burned over {} blocks, total supply of {} conserved.",
+            self.total_balance,
+            self.initial_supply - self.total_supply,
+            self.num_blocks,
+            self.total_supply,
+        );
+    }
+}
@@ -0,0 +1,129 @@
+// Copyright © Aptos Foundation
+// SPDX-License-Identifier: Apache-2.0
+
+use aptos_logger::warn;
+use once_cell::sync::OnceCell;
+use serde::Serialize;
+use std::{
+    fs::File,
+    io::{self, LineWriter, Write},
+    os::unix::io::{FromRawFd, RawFd},
+    sync::Mutex,
+    time::{SystemTime, UNIX_EPOCH},
+};
+
+static PROGRESS_SINK: OnceCell<Mutex<LineWriter<File>>> = OnceCell::new();
+
+/// Machine-readable progress of a run, for orchestration tools to follow it without parsing the
+/// logs. Emitted as one JSON object per line, e.g.
+/// `{"timestamp_ms":1700000000000,"event":"block_committed","block_index":3,...}`.
+#[derive(Debug, Serialize)]
+#[serde(tag = "event", rename_all = "snake_case")]
+pub enum ProgressEvent {
+    PhaseChanged {
+        phase: Phase,
+    },
+    BlockCommitted {
+        block_index: usize,
+        version: u64,
+        num_txns: usize,
+        latency_ms: u64,
+        /// Committed TPS since the first block.
+        accumulative_tps: f64,
+    },
+    /// A pipeline stage has been working on a block for longer than its timeout.
+    StageStalled {
+        stage: &'static str,
+        block_id: String,
+        elapsed_secs: f64,
+    },
+}
+
+#[derive(Clone, Copy, Debug, Serialize)]
+#[serde(rename_all = "snake_case")]
+pub enum Phase {
+    /// Checkpointing and opening the DB.
+    PrepareDb,
+    /// Creating the accounts and publishing the packages of the workload.
+    InitWorkload,
+    /// Generating, executing and committing the measured blocks.
+    Run,
+    /// The run is over, and its results are being reported.
+    Report,
+    Done,
+}
+
+#[derive(Serialize)]
+struct ProgressRecord<'a> {
+    timestamp_ms: u64,
+    #[serde(flatten)]
+    event: &'a ProgressEvent,
+}
+
+/// Sets where the progress events are written to, e.g. a file or an inherited file descriptor.
+/// Events are dropped if it is not set.
+pub fn set_progress_sink_once(sink: File) {
+    PROGRESS_SINK.set(Mutex::new(LineWriter::new(sink))).ok();
+}
+
+/// Checks that `fd` is an open file descriptor, e.g. one inherited from the parent process.
+pub fn check_fd(fd: RawFd) -> io::Result<()> {
+    // SAFETY: F_GETFD only reads the flags of the fd, and fails if it isn't open.
+    if unsafe { libc::fcntl(fd, libc::F_GETFD) } < 0 {
+        return Err(io::Error::last_os_error());
+    }
+    Ok(())
+}
+
+/// Takes over the file descriptor `fd` inherited from the parent process, to write the progress
+/// events to.
+pub fn sink_from_fd(fd: RawFd) -> io::Result<File> {
+    check_fd(fd)?;
+    // SAFETY: the fd is open, and handed to us by the parent process for this purpose, nothing
+    // else in the process uses it.
+    Ok(unsafe { File::from_raw_fd(fd) })
+}
+
+pub fn emit(event: ProgressEvent) {
+    let sink = match PROGRESS_SINK.get() {
+        Some(sink) => sink,
+        None => return,
+    };
+    let record = ProgressRecord {
+        timestamp_ms: SystemTime::now()
+            .duration_since(UNIX_EPOCH)
+            .map_or(0, |duration| duration.as_millis() as u64),
+        event: &event,
+    };
+    let line = serde_json::to_string(&record).expect("Progress event must serialize.");
+    if let Err(err) = writeln!(sink.lock().unwrap(), "{}", line) {
+        warn!("Failed to write progress event {:?}: {}", event, err);
+    }
+}
+
+pub fn phase_changed(phase: Phase) {
+    emit(ProgressEvent::PhaseChanged { phase });
+}
+
+#[test]
+fn test_progress_event_format() {
+    let record = ProgressRecord {
+        timestamp_ms: 1,
+        event: &ProgressEvent::PhaseChanged { phase: Phase::Run },
+    };
+    assert_eq!(
+        serde_json::to_string(&record).unwrap(),
+        r#"{"timestamp_ms":1,"event":"phase_changed","phase":"run"}"#
+    );
+}
+
+#[test]
+fn test_check_fd() {
+    use std::os::unix::io::AsRawFd;
+
+    let file = File::open("/dev/null").unwrap();
+    assert!(check_fd(file.as_raw_fd()).is_ok());
+    // Way above the open file limit.
+    assert!(check_fd(RawFd::MAX).is_err());
+    assert!(check_fd(-1).is_err());
+}
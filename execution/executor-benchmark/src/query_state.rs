@@ -0,0 +1,176 @@
+// Copyright © Aptos Foundation
+// SPDX-License-Identifier: Apache-2.0
+
+//! Reads an account resource or a table item of a benchmark DB as of a past version or block, and
+//! renders it as JSON the way the REST API does.
+
+use crate::verify_db::open_readonly_db;
+use anyhow::{anyhow, ensure, Result};
+use aptos_api_types::AsConverter;
+use aptos_storage_interface::{state_view::DbStateViewAtVersion, DbReader};
+use aptos_types::{
+    access_path::AccessPath,
+    account_address::AccountAddress,
+    state_store::{state_key::StateKey, table::TableHandle},
+    transaction::Version,
+};
+use aptos_vm::data_cache::AsMoveResolver;
+use move_core_types::language_storage::{StructTag, TypeTag};
+use serde_json::{json, Value};
+use std::{path::Path, sync::Arc};
+
+/// Number of transaction infos read at a time, when looking for the version of a block.
+const TXN_INFO_CHUNK_SIZE: u64 = 10000;
+
+#[derive(Debug)]
+pub enum StateQuery {
+    Resource {
+        address: AccountAddress,
+        resource_type: StructTag,
+    },
+    TableItem {
+        handle: TableHandle,
+        /// BCS encoded key of the item.
+        key: Vec<u8>,
+        /// Type of the item, to render it as JSON. Without it, the raw bytes are printed.
+        value_type: Option<TypeTag>,
+    },
+}
+
+impl StateQuery {
+    fn state_key(&self) -> Result<StateKey> {
+        Ok(match self {
+            StateQuery::Resource {
+                address,
+                resource_type,
+            } => StateKey::access_path(AccessPath::resource_access_path(
+                *address,
+                resource_type.clone(),
+            )?),
+            StateQuery::TableItem { handle, key, .. } => StateKey::table_item(*handle, key.clone()),
+        })
+    }
+}
+
+/// The state a query looks at.
+#[derive(Clone, Copy, Debug)]
+pub enum QueryPoint {
+    Latest,
+    Version(Version),
+    /// The state after the block with this height committed. Every block the benchmark commits,
+    /// including the ones that created the accounts of the DB, ends with a state checkpoint; block
+    /// 0 is the genesis.
+    Block(u64),
+}
+
+/// Runs `query` on the DB in `data_dir`, which is opened read-only, and returns the version it
+/// was answered at along with the value, `null` if the item didn't exist at that version.
+pub fn query_state(
+    data_dir: &Path,
+    enable_storage_sharding: bool,
+    query: &StateQuery,
+    at: QueryPoint,
+) -> Result<Value> {
+    let db = open_readonly_db(data_dir, enable_storage_sharding)?;
+    let latest_version = db.get_latest_version()?;
+    let version = match at {
+        QueryPoint::Latest => latest_version,
+        QueryPoint::Version(version) => {
+            ensure!(
+                version <= latest_version,
+                "Version {} is beyond the latest version {} of the DB",
+                version,
+                latest_version
+            );
+            version
+        },
+        QueryPoint::Block(height) => block_version(&db, height, latest_version)?,
+    };
+
+    let state_key = query.state_key()?;
+    let value = match db.get_state_value_by_version(&state_key, version)? {
+        None => Value::Null,
+        Some(state_value) => {
+            let bytes = state_value.bytes();
+            let state_view = db.state_view_at_version(Some(version))?;
+            let resolver = state_view.as_move_resolver();
+            let converter = resolver.as_converter(db.clone());
+            match query {
+                StateQuery::Resource { resource_type, .. } => {
+                    serde_json::to_value(converter.try_into_resource(resource_type, bytes)?)?
+                },
+                StateQuery::TableItem {
+                    value_type: Some(value_type),
+                    ..
+                } => serde_json::to_value(converter.try_into_move_value(value_type, bytes)?)?,
+                StateQuery::TableItem {
+                    value_type: None, ..
+                } => json!({ "bytes": format!("0x{}", hex::encode(bytes)) }),
+            }
+        },
+    };
+    Ok(json!({
+        "version": version,
+        "block": match at {
+            QueryPoint::Block(height) => Some(height),
+            _ => None,
+        },
+        "state_key": format!("{:?}", state_key),
+        "value": value,
+    }))
+}
+
+/// Returns the version of the state checkpoint that ends the block with `height`.
+fn block_version(db: &Arc<dyn DbReader>, height: u64, latest_version: Version) -> Result<Version> {
+    let mut num_checkpoints = 0;
+    let mut start_version = 0;
+    while start_version <= latest_version {
+        let limit = TXN_INFO_CHUNK_SIZE.min(latest_version - start_version + 1);
+        for (offset, txn_info) in db
+            .get_transaction_info_iterator(start_version, limit)?
+            .enumerate()
+        {
+            if txn_info?.state_checkpoint_hash().is_some() {
+                if num_checkpoints == height {
+                    return Ok(start_version + offset as u64);
+                }
+                num_checkpoints += 1;
+            }
+        }
+        start_version += limit;
+    }
+    Err(anyhow!(
+        "Block {} is beyond the last block {} of the DB",
+        height,
+        num_checkpoints.saturating_sub(1)
+    ))
+}
+
+#[test]
+fn test_block_version() {
+    use crate::create_temp_db;
+    use aptos_vm::AptosVM;
+
+    let db_dir = create_temp_db::<AptosVM>(10, 100_000_000, 5);
+    let db = open_readonly_db(db_dir.path(), false).unwrap();
+    let latest_version = db.get_latest_version().unwrap();
+
+    // Block 0 is the genesis, and the DB ends with a block.
+    assert_eq!(block_version(&db, 0, latest_version).unwrap(), 0);
+    let mut versions = vec![];
+    while let Ok(version) = block_version(&db, versions.len() as u64, latest_version) {
+        versions.push(version);
+    }
+    assert!(versions.len() > 2);
+    assert!(versions.windows(2).all(|pair| pair[0] < pair[1]));
+    assert_eq!(*versions.last().unwrap(), latest_version);
+    for version in versions {
+        let txn_info = db
+            .get_transaction_info_iterator(version, 1)
+            .unwrap()
+            .next()
+            .unwrap()
+            .unwrap();
+        assert!(txn_info.state_checkpoint_hash().is_some());
+    }
+}
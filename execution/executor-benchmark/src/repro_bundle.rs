@@ -0,0 +1,431 @@
+// Copyright © Aptos Foundation
+// SPDX-License-Identifier: Apache-2.0
+
+//! Reproduction bundles of benchmark runs, to ship along with a performance report.
+//!
+//! With `--repro-bundle`, a run-executor run writes a single archive (a gzipped tar, which
+//! `tar -xzf` extracts) with the arguments it was started with, the seed of its workload
+//! generator, a summary of its workload and the git revision of the binary in `bundle.json`, and
+//! all of its options as parsed in `config.txt`. The seed only reproduces the random
+//! choices of the generator (the senders and receivers, and how many transactions each sender
+//! sends), not the transactions themselves, e.g. their expiration times or the payloads of the
+//! transaction generator library. With `--repro-bundle-include-workload`, the transactions of the
+//! run are included as well, as a mempool capture in `workload.capture` (see `mempool_capture`),
+//! and replayed instead.
+//!
+//! `run-bundle` runs the arguments of a bundle again, with its seed and its transactions, as a run
+//! of this binary of its own. The DB the run starts from is not part of the bundle: it has to be
+//! created the same way as for the original run.
+
+use crate::mempool_capture::MempoolCaptureReader;
+use anyhow::{bail, ensure, Context, Result};
+use aptos_logger::{info, warn};
+use aptos_temppath::TempPath;
+use chrono::Local;
+use flate2::{read::GzDecoder, write::GzEncoder, Compression};
+use serde::{Deserialize, Serialize};
+use std::{
+    collections::HashMap,
+    fs::{self, File},
+    io::{Read, Write},
+    path::{Path, PathBuf},
+    process::{Command, ExitStatus},
+};
+
+pub const REPRO_BUNDLE_FORMAT_VERSION: u32 = 2;
+/// The subcommand bundles are made of.
+pub const RUN_EXECUTOR: &str = "run-executor";
+
+const MANIFEST_ENTRY: &str = "bundle.json";
+const CONFIG_ENTRY: &str = "config.txt";
+const TRANSACTIONS_ENTRY: &str = "workload.capture";
+/// Size of the headers and data blocks of tar archives.
+const TAR_BLOCK_SIZE: usize = 512;
+
+/// Options left out of the arguments recorded in a bundle, and whether they take a value: the
+/// ones making the bundle itself, and the outputs of the original run, which would overwrite (or
+/// upload, with its credentials) the ones of the original run when the bundle is run.
+const OPTIONS_NOT_BUNDLED: &[(&str, bool)] = &[
+    ("--repro-bundle", true),
+    ("--repro-bundle-include-workload", false),
+    ("--record-workload", true),
+    ("--seed", true),
+    ("--results-json", true),
+    ("--artifacts-dir", true),
+    ("--artifacts-keep-runs", true),
+    ("--progress-fd", true),
+    ("--progress-file", true),
+    ("--upload-results", true),
+    ("--upload-results-auth-token-file", true),
+    ("--metrics-db", false),
+    ("--metrics-db-path", true),
+];
+
+#[derive(Debug, Deserialize, Serialize)]
+pub struct ReproBundle {
+    pub format_version: u32,
+    /// Git revision of the binary that made the run.
+    pub git_revision: String,
+    /// When the bundle was made, in RFC 3339.
+    pub created_at: String,
+    /// Seed of the workload generator of the run.
+    pub seed: u64,
+    /// Arguments of the run, without the binary and the options in `OPTIONS_NOT_BUNDLED`.
+    pub args: Vec<String>,
+    pub workload: WorkloadManifest,
+    /// All options of the run as parsed, defaults included, for the readers of the bundle.
+    #[serde(skip)]
+    pub config: String,
+    /// The transactions of the run, as a mempool capture.
+    #[serde(skip)]
+    pub transactions: Option<Vec<u8>>,
+}
+
+/// What the run executed, for the readers of the bundle.
+#[derive(Clone, Debug, Default, Deserialize, Serialize, PartialEq)]
+pub struct WorkloadManifest {
+    pub block_size: usize,
+    pub blocks: usize,
+    /// Transaction types of the workload, with their weights. Empty for coin transfers.
+    pub transaction_mix: Vec<(String, usize)>,
+    pub transactions_per_sender: String,
+    pub main_signer_accounts: usize,
+    /// Mempool capture the original run replayed, instead of generating the workload.
+    pub mempool_capture: Option<PathBuf>,
+}
+
+impl ReproBundle {
+    /// Bundles a run started with `command_line`, the binary included.
+    pub fn new(
+        command_line: &[String],
+        seed: u64,
+        workload: WorkloadManifest,
+        config: String,
+    ) -> Self {
+        Self {
+            format_version: REPRO_BUNDLE_FORMAT_VERSION,
+            git_revision: aptos_build_info::get_git_hash(),
+            created_at: Local::now().to_rfc3339(),
+            seed,
+            args: remove_options(
+                command_line.get(1..).unwrap_or_default(),
+                OPTIONS_NOT_BUNDLED,
+            ),
+            workload,
+            config,
+            transactions: None,
+        }
+    }
+
+    /// Includes the transactions of the run, from the mempool capture at `path`.
+    pub fn include_transactions(&mut self, path: &Path) -> Result<()> {
+        let bytes =
+            fs::read(path).with_context(|| format!("Cannot read the workload {:?}", path))?;
+        self.transactions = Some(bytes);
+        Ok(())
+    }
+
+    pub fn write(&self, path: &Path) -> Result<()> {
+        let file =
+            File::create(path).with_context(|| format!("Cannot create repro bundle {:?}", path))?;
+        let mut encoder = GzEncoder::new(file, Compression::default());
+        write_tar_entry(
+            &mut encoder,
+            MANIFEST_ENTRY,
+            &serde_json::to_vec_pretty(self)?,
+        )?;
+        write_tar_entry(&mut encoder, CONFIG_ENTRY, self.config.as_bytes())?;
+        if let Some(transactions) = &self.transactions {
+            write_tar_entry(&mut encoder, TRANSACTIONS_ENTRY, transactions)?;
+        }
+        // The end of the archive.
+        encoder.write_all(&[0; 2 * TAR_BLOCK_SIZE])?;
+        encoder.finish()?.sync_all()?;
+        Ok(())
+    }
+
+    pub fn load(path: &Path) -> Result<Self> {
+        let file =
+            File::open(path).with_context(|| format!("Cannot open repro bundle {:?}", path))?;
+        let mut bytes = vec![];
+        GzDecoder::new(file)
+            .read_to_end(&mut bytes)
+            .with_context(|| format!("Repro bundle {:?} is not gzipped", path))?;
+        let mut entries = read_tar_entries(&bytes)
+            .with_context(|| format!("Repro bundle {:?} is not a tar archive", path))?;
+        let manifest = entries
+            .remove(MANIFEST_ENTRY)
+            .with_context(|| format!("Repro bundle {:?} has no {}", path, MANIFEST_ENTRY))?;
+        let mut bundle: Self = serde_json::from_slice(&manifest)
+            .with_context(|| format!("Invalid {} in repro bundle {:?}", MANIFEST_ENTRY, path))?;
+        bundle.config =
+            String::from_utf8_lossy(&entries.remove(CONFIG_ENTRY).unwrap_or_default()).into_owned();
+        bundle.transactions = entries.remove(TRANSACTIONS_ENTRY);
+        ensure!(
+            bundle.format_version == REPRO_BUNDLE_FORMAT_VERSION,
+            "Repro bundle {:?} is of format version {}, expected {}",
+            path,
+            bundle.format_version,
+            REPRO_BUNDLE_FORMAT_VERSION
+        );
+        Ok(bundle)
+    }
+
+    /// Arguments to run the bundle with, its transactions (if any) read from `capture_path`.
+    /// `global_args` go before the subcommand, and the `--data-dir` and `--checkpoint-dir` of the
+    /// original run are replaced with `data_dirs` and `checkpoint_dir`, if given.
+    fn run_args(
+        &self,
+        capture_path: Option<&Path>,
+        data_dirs: &[PathBuf],
+        checkpoint_dir: Option<&Path>,
+        global_args: &[String],
+    ) -> Result<Vec<String>> {
+        let mut removed = vec![];
+        if capture_path.is_some() {
+            removed.push(("--mempool-capture", true));
+        }
+        if !data_dirs.is_empty() {
+            removed.push(("--data-dir", true));
+        }
+        if checkpoint_dir.is_some() {
+            removed.push(("--checkpoint-dir", true));
+        }
+        let args = remove_options(&self.args, &removed);
+        let subcommand_index = args
+            .iter()
+            .position(|arg| arg == RUN_EXECUTOR)
+            .with_context(|| format!("The bundle is not of a {} run", RUN_EXECUTOR))?;
+
+        let mut run_args = args[..subcommand_index].to_vec();
+        run_args.extend(["--seed".to_string(), self.seed.to_string()]);
+        if let Some(capture_path) = capture_path {
+            run_args.extend([
+                "--mempool-capture".to_string(),
+                capture_path.display().to_string(),
+            ]);
+        }
+        run_args.extend_from_slice(global_args);
+        run_args.extend_from_slice(&args[subcommand_index..]);
+        for data_dir in data_dirs {
+            run_args.extend(["--data-dir".to_string(), data_dir.display().to_string()]);
+        }
+        if let Some(checkpoint_dir) = checkpoint_dir {
+            run_args.extend([
+                "--checkpoint-dir".to_string(),
+                checkpoint_dir.display().to_string(),
+            ]);
+        }
+        Ok(run_args)
+    }
+}
+
+/// Runs the bundle at `bundle_path` again, as a run of this binary, and returns how it exited.
+/// See `ReproBundle::run_args` for the arguments.
+pub fn run_bundle(
+    bundle_path: &Path,
+    data_dirs: &[PathBuf],
+    checkpoint_dir: Option<&Path>,
+    global_args: &[String],
+) -> Result<ExitStatus> {
+    let bundle = ReproBundle::load(bundle_path)?;
+    info!(
+        "Running repro bundle {:?}, made at {} by revision {}: {:?}",
+        bundle_path, bundle.created_at, bundle.git_revision, bundle.workload
+    );
+    let git_revision = aptos_build_info::get_git_hash();
+    if bundle.git_revision != git_revision {
+        warn!(
+            "The bundle was made by revision {}, but this binary is of revision {}, the results may differ.",
+            bundle.git_revision, git_revision
+        );
+    }
+
+    // Kept until the run is over, and deleted on drop.
+    let capture = match &bundle.transactions {
+        Some(transactions) => {
+            let capture = TempPath::new();
+            fs::write(capture.path(), transactions)?;
+            let num_txns = MempoolCaptureReader::open(capture.path())?.count();
+            info!("Replaying the {} transactions of the bundle.", num_txns);
+            Some(capture)
+        },
+        None => None,
+    };
+    let args = bundle.run_args(
+        capture.as_ref().map(TempPath::path),
+        data_dirs,
+        checkpoint_dir,
+        global_args,
+    )?;
+    info!("Running {:?}", args);
+    Ok(Command::new(std::env::current_exe()?)
+        .args(&args)
+        .status()?)
+}
+
+/// Writes a file entry of a tar archive, in the ustar format.
+fn write_tar_entry(writer: &mut impl Write, name: &str, data: &[u8]) -> Result<()> {
+    let mut header = [0u8; TAR_BLOCK_SIZE];
+    let mut set = |offset: usize, value: &[u8]| {
+        header[offset..offset + value.len()].copy_from_slice(value);
+    };
+    set(0, name.as_bytes());
+    set(100, b"0000644\0");
+    set(108, b"0000000\0");
+    set(116, b"0000000\0");
+    set(124, format!("{:011o}\0", data.len()).as_bytes());
+    set(
+        136,
+        format!("{:011o}\0", chrono::Utc::now().timestamp()).as_bytes(),
+    );
+    // The checksum is computed with its own field as spaces.
+    set(148, b"        ");
+    set(156, b"0");
+    set(257, b"ustar\0");
+    set(263, b"00");
+    let checksum = header.iter().map(|byte| *byte as u32).sum::<u32>();
+    header[148..156].copy_from_slice(format!("{:06o}\0 ", checksum).as_bytes());
+    writer.write_all(&header)?;
+    writer.write_all(data)?;
+    let padding = (TAR_BLOCK_SIZE - data.len() % TAR_BLOCK_SIZE) % TAR_BLOCK_SIZE;
+    writer.write_all(&vec![0; padding])?;
+    Ok(())
+}
+
+/// Reads the file entries of a tar archive, by name.
+fn read_tar_entries(bytes: &[u8]) -> Result<HashMap<String, Vec<u8>>> {
+    let mut entries = HashMap::new();
+    let mut offset = 0;
+    while offset + TAR_BLOCK_SIZE <= bytes.len() {
+        let header = &bytes[offset..offset + TAR_BLOCK_SIZE];
+        if header.iter().all(|byte| *byte == 0) {
+            return Ok(entries);
+        }
+        let field = |range: std::ops::Range<usize>| {
+            let field = &header[range];
+            let end = field
+                .iter()
+                .position(|byte| *byte == 0)
+                .unwrap_or(field.len());
+            String::from_utf8_lossy(&field[..end]).trim().to_string()
+        };
+        let name = field(0..100);
+        let size = usize::from_str_radix(&field(124..136), 8)
+            .with_context(|| format!("Invalid size of entry {}", name))?;
+        let start = offset + TAR_BLOCK_SIZE;
+        ensure!(start + size <= bytes.len(), "Entry {} is cut short", name);
+        entries.insert(name, bytes[start..start + size].to_vec());
+        offset = start + (size + TAR_BLOCK_SIZE - 1) / TAR_BLOCK_SIZE * TAR_BLOCK_SIZE;
+    }
+    bail!("The archive is cut short")
+}
+
+/// Removes the `options` from `args`, given as `--name value` or `--name=value` for the ones that
+/// take a value.
+fn remove_options(args: &[String], options: &[(&str, bool)]) -> Vec<String> {
+    let mut kept = vec![];
+    let mut args = args.iter();
+    while let Some(arg) = args.next() {
+        let (name, inline_value) = match arg.split_once('=') {
+            Some((name, _)) => (name, true),
+            None => (arg.as_str(), false),
+        };
+        match options.iter().find(|(option, _)| *option == name) {
+            Some((_, takes_value)) => {
+                if *takes_value && !inline_value {
+                    args.next();
+                }
+            },
+            None => kept.push(arg.clone()),
+        }
+    }
+    kept
+}
+
+#[test]
+fn test_repro_bundle() {
+    let command_line = [
+        "aptos-executor-benchmark",
+        "--block-size",
+        "100",
+        "--repro-bundle",
+        "/tmp/bundle",
+        "--upload-results-auth-token-file=/tmp/token",
+        "--repro-bundle-include-workload",
+        "run-executor",
+        "--blocks",
+        "10",
+        "--data-dir",
+        "/tmp/data",
+        "--checkpoint-dir",
+        "/tmp/checkpoint",
+    ]
+    .map(String::from);
+    let mut bundle = ReproBundle::new(&command_line, 42, WorkloadManifest::default(), "".into());
+    assert_eq!(
+        bundle.args,
+        command_line[1..3]
+            .iter()
+            .chain(&command_line[7..])
+            .cloned()
+            .collect::<Vec<_>>()
+    );
+
+    let dir = TempPath::new();
+    dir.create_as_dir().unwrap();
+    let capture_path = dir.path().join("capture");
+    fs::write(&capture_path, [1, 2, 3]).unwrap();
+    bundle.include_transactions(&capture_path).unwrap();
+    let bundle_path = dir.path().join("bundle");
+    bundle.write(&bundle_path).unwrap();
+    let loaded = ReproBundle::load(&bundle_path).unwrap();
+    assert_eq!(loaded.args, bundle.args);
+    assert_eq!(loaded.transactions, Some(vec![1, 2, 3]));
+
+    // The bundle is an archive the usual tools extract.
+    let extracted = dir.path().join("extracted");
+    fs::create_dir(&extracted).unwrap();
+    let status = Command::new("tar")
+        .arg("-xzf")
+        .arg(&bundle_path)
+        .arg("-C")
+        .arg(&extracted)
+        .status()
+        .unwrap();
+    assert!(status.success());
+    assert_eq!(fs::read(extracted.join(TRANSACTIONS_ENTRY)).unwrap(), [
+        1, 2, 3
+    ]);
+    let manifest: serde_json::Value =
+        serde_json::from_slice(&fs::read(extracted.join(MANIFEST_ENTRY)).unwrap()).unwrap();
+    assert_eq!(manifest["seed"], 42);
+
+    let run_args = loaded
+        .run_args(
+            Some(&capture_path),
+            &[PathBuf::from("/tmp/data0"), PathBuf::from("/tmp/data1")],
+            None,
+            &["--results-json".to_string(), "/tmp/results".to_string()],
+        )
+        .unwrap();
+    assert_eq!(run_args, [
+        "--block-size",
+        "100",
+        "--seed",
+        "42",
+        "--mempool-capture",
+        &capture_path.display().to_string(),
+        "--results-json",
+        "/tmp/results",
+        "run-executor",
+        "--blocks",
+        "10",
+        "--checkpoint-dir",
+        "/tmp/checkpoint",
+        "--data-dir",
+        "/tmp/data0",
+        "--data-dir",
+        "/tmp/data1",
+    ]);
+}
@@ -0,0 +1,149 @@
+// Copyright © Aptos Foundation
+// SPDX-License-Identifier: Apache-2.0
+
+use crate::{
+    block_stm_stats::BlockStmStats, durability::Durability, io_accounting::StageIo,
+    perf_counters::StagePerf, thread_utilization::UtilizationSample, txn_type_stats::TxnTypeStats,
+};
+use anyhow::{ensure, Context, Result};
+use aptos_executor_service::warm_standby::Failover;
+use serde::Serialize;
+use std::{
+    collections::BTreeMap,
+    fs::{self, File},
+    io::{BufWriter, Write},
+    path::Path,
+    time::Duration,
+};
+
+const UPLOAD_TIMEOUT: Duration = Duration::from_secs(30);
+/// Environment variable the results upload token is read from, when not given in a file.
+pub const UPLOAD_RESULTS_AUTH_TOKEN_ENV: &str = "UPLOAD_RESULTS_AUTH_TOKEN";
+
+/// Reads a bearer token from `file` if given, or else from the `env_var` environment variable, so
+/// that it doesn't show up in the command line of the process.
+pub fn read_auth_token(file: Option<&Path>, env_var: &str) -> Result<Option<String>> {
+    let auth_token = match file {
+        Some(file) => Some(
+            fs::read_to_string(file)
+                .with_context(|| format!("Cannot read auth token file {:?}", file))?,
+        ),
+        None => std::env::var(env_var).ok(),
+    };
+    Ok(auth_token
+        .map(|auth_token| auth_token.trim().to_string())
+        .filter(|auth_token| !auth_token.is_empty()))
+}
+
+/// Summary of a benchmark run, in a form that can be collected and compared across machines.
+#[derive(Debug, Default, Serialize)]
+pub struct BenchmarkResults {
+    pub workload: String,
+    pub block_size: usize,
+    pub num_blocks: usize,
+    pub num_txns: u64,
+    pub elapsed_secs: f64,
+    pub tps: f64,
+    pub vm_tps: f64,
+    pub gps: f64,
+    pub io_gps: f64,
+    pub execution_gps: f64,
+    pub gas_per_txn: f64,
+    pub output_bytes_per_sec: f64,
+    /// Durability of the commits, see `Durability`.
+    pub durability: Durability,
+    /// Average time spent committing a block.
+    pub commit_ms_per_block: f64,
+    /// Fraction of the total time spent in each stage of the pipeline.
+    pub stage_fractions: BTreeMap<String, f64>,
+    /// For mixed workloads, the breakdown by transaction type.
+    pub txn_type_stats: BTreeMap<String, TxnTypeStats>,
+    /// Busy workers of each thread pool over time, if sampled.
+    pub thread_utilization: Vec<UtilizationSample>,
+    /// Scheduler counters of the parallel executor over the run.
+    pub block_stm: BlockStmStats,
+    pub reexecution_ratio: f64,
+    pub validation_failure_rate: f64,
+    /// Disk I/O and CPU time of each pipeline stage, by the threads working for it.
+    pub stage_io: BTreeMap<String, StageIo>,
+    /// Hardware counters of each pipeline stage, if counted.
+    pub stage_perf: BTreeMap<String, StagePerf>,
+    /// Involuntary context switches per second of the threads of each pipeline stage.
+    pub involuntary_switches_per_sec: BTreeMap<String, f64>,
+    /// Remote shards that failed over to their warm standbys during the run.
+    pub failovers: Vec<Failover>,
+    /// Duplicate and replayed transactions injected into the blocks, by kind and outcome.
+    pub injected_txn_outcomes: BTreeMap<String, u64>,
+    /// Number of senders of the generated blocks, by the number of transactions they sent in
+    /// their block. Empty for the workloads that don't pick the senders at random.
+    pub txns_per_sender_distribution: BTreeMap<usize, u64>,
+}
+
+impl BenchmarkResults {
+    /// Prints the headline numbers of the run to stdout.
+    pub fn print_summary(&self) {
+        println!(
+            "{}: {} blocks of {} txns, {} txns in {:.1} s",
+            self.workload, self.num_blocks, self.block_size, self.num_txns, self.elapsed_secs
+        );
+        println!(
+            "  TPS {:.0}, VM TPS {:.0}, GPS {:.0}, gas per txn {:.1}",
+            self.tps, self.vm_tps, self.gps, self.gas_per_txn
+        );
+        println!(
+            "  commit {:.2} ms per block with {:?} durability",
+            self.commit_ms_per_block, self.durability
+        );
+        for (stage, fraction) in &self.stage_fractions {
+            println!("  {}: {:.1}% of the time", stage, fraction * 100.0);
+        }
+    }
+
+    /// Writes the results as JSON to a file.
+    pub fn write_json(&self, path: &Path) -> Result<()> {
+        let mut writer = BufWriter::new(File::create(path)?);
+        serde_json::to_writer_pretty(&mut writer, self)?;
+        writer.flush()?;
+        Ok(())
+    }
+
+    /// POSTs the results as JSON to a results collection service.
+    pub fn upload(&self, url: &str, auth_token: Option<&str>) -> Result<()> {
+        let client = reqwest::blocking::Client::builder()
+            .timeout(UPLOAD_TIMEOUT)
+            .build()?;
+        let mut request = client.post(url).json(self);
+        if let Some(auth_token) = auth_token {
+            request = request.bearer_auth(auth_token);
+        }
+        let response = request.send()?;
+        ensure!(
+            response.status().is_success(),
+            "Uploading results to {} failed with status {}",
+            url,
+            response.status()
+        );
+        Ok(())
+    }
+}
+
+#[test]
+fn test_read_auth_token() {
+    let env_var = "TEST_READ_AUTH_TOKEN";
+    assert_eq!(read_auth_token(None, env_var).unwrap(), None);
+    std::env::set_var(env_var, "from-env");
+    assert_eq!(
+        read_auth_token(None, env_var).unwrap(),
+        Some("from-env".to_string())
+    );
+
+    let file = aptos_temppath::TempPath::new();
+    fs::write(file.path(), "from-file\n").unwrap();
+    assert_eq!(
+        read_auth_token(Some(file.path()), env_var).unwrap(),
+        Some("from-file".to_string())
+    );
+    fs::write(file.path(), "\n").unwrap();
+    assert_eq!(read_auth_token(Some(file.path()), env_var).unwrap(), None);
+    assert!(read_auth_token(Some(Path::new("/nonexistent/token")), env_var).is_err());
+}
@@ -0,0 +1,272 @@
+// Copyright © Aptos Foundation
+// SPDX-License-Identifier: Apache-2.0
+
+use crate::{
+    metrics::{NUM_TXNS, TIMER},
+    post_commit::PostCommitPlugin,
+};
+use anyhow::Result;
+use aptos_crypto::{hash::CryptoHash, HashValue};
+use aptos_logger::info;
+use aptos_types::{
+    state_store::state_key::StateKey,
+    transaction::{
+        analyzed_transaction::{AnalyzedTransaction, StorageLocation},
+        signature_verified_transaction::SignatureVerifiedTransaction,
+        Transaction, TransactionOutput, Version,
+    },
+};
+use std::{
+    collections::{HashMap, HashSet, VecDeque},
+    sync::{Arc, Mutex},
+};
+
+/// Read and write sets of transactions estimated from their payloads before execution, the way
+/// the partitioner gets them, to measure what the estimation costs and how close it is to what
+/// the transactions actually do, as data for proposals to schedule transactions based on hints.
+///
+/// The estimates are kept by block until the block is committed, and then compared against the
+/// write sets of its transactions by `RwSetAccuracyChecker`. The estimates of the transactions of
+/// the block that were not committed, e.g. discarded ones, are dropped with it. The VM doesn't
+/// report what a transaction read, so for reads only the number of hints is reported.
+#[derive(Default)]
+pub struct RwSetEstimates {
+    /// Estimates of the blocks not committed yet, by transaction hash, in the order the blocks
+    /// were prepared, which is the order they commit in.
+    blocks: Mutex<VecDeque<HashMap<HashValue, TxnRwSetEstimate>>>,
+}
+
+struct TxnRwSetEstimate {
+    num_read_hints: usize,
+    write_hints: HashSet<StateKey>,
+}
+
+impl RwSetEstimates {
+    /// Estimates the read and write sets of `txns`, and hands the transactions back.
+    pub(crate) fn estimate(
+        &self,
+        txns: Vec<SignatureVerifiedTransaction>,
+    ) -> Vec<SignatureVerifiedTransaction> {
+        NUM_TXNS
+            .with_label_values(&["rw_set_estimation"])
+            .inc_by(txns.len() as u64);
+        let timer = TIMER
+            .with_label_values(&["rw_set_estimation"])
+            .start_timer();
+        let analyzed_txns: Vec<AnalyzedTransaction> = txns.into_iter().map(|t| t.into()).collect();
+        timer.stop_and_record();
+
+        self.push_block(
+            analyzed_txns
+                .iter()
+                .map(|txn| {
+                    (txn.transaction().hash(), TxnRwSetEstimate {
+                        num_read_hints: txn.read_hints().len(),
+                        write_hints: txn
+                            .write_hints()
+                            .iter()
+                            .filter_map(|location| match location {
+                                StorageLocation::Specific(state_key) => Some(state_key.clone()),
+                                // Never emitted by the analyzer for now.
+                                _ => None,
+                            })
+                            .collect(),
+                    })
+                })
+                .collect(),
+        );
+
+        analyzed_txns
+            .into_iter()
+            .map(AnalyzedTransaction::into_txn)
+            .collect()
+    }
+
+    fn push_block(&self, estimates: HashMap<HashValue, TxnRwSetEstimate>) {
+        self.blocks.lock().unwrap().push_back(estimates);
+    }
+
+    /// Takes the estimates of the oldest block not committed yet, called when it commits.
+    fn take_block(&self) -> HashMap<HashValue, TxnRwSetEstimate> {
+        self.blocks.lock().unwrap().pop_front().unwrap_or_default()
+    }
+}
+
+/// How the estimated write sets of the committed user transactions compare to their actual ones.
+#[derive(Debug, Default)]
+struct RwSetAccuracy {
+    num_txns: usize,
+    /// Transactions the analyzer doesn't support, estimated to neither read nor write anything.
+    num_unsupported_txns: usize,
+    /// Supported transactions whose estimated write set is exactly the actual one.
+    num_exact_txns: usize,
+    num_read_hints: usize,
+    num_estimated_writes: usize,
+    num_actual_writes: usize,
+    /// Estimated writes the transaction actually made.
+    num_correct_writes: usize,
+    /// Estimated transactions of the committed blocks that were not committed.
+    num_uncommitted_txns: usize,
+}
+
+impl RwSetAccuracy {
+    fn record(&mut self, estimate: &TxnRwSetEstimate, actual_writes: &HashSet<StateKey>) {
+        self.num_txns += 1;
+        if estimate.num_read_hints == 0 && estimate.write_hints.is_empty() {
+            self.num_unsupported_txns += 1;
+            return;
+        }
+        let num_correct_writes = estimate.write_hints.intersection(actual_writes).count();
+        if num_correct_writes == estimate.write_hints.len()
+            && num_correct_writes == actual_writes.len()
+        {
+            self.num_exact_txns += 1;
+        }
+        self.num_read_hints += estimate.num_read_hints;
+        self.num_estimated_writes += estimate.write_hints.len();
+        self.num_actual_writes += actual_writes.len();
+        self.num_correct_writes += num_correct_writes;
+    }
+
+    /// Fraction of the estimated writes that were made, over the supported transactions.
+    fn write_precision(&self) -> f64 {
+        self.num_correct_writes as f64 / self.num_estimated_writes.max(1) as f64
+    }
+
+    /// Fraction of the writes made that were estimated, over the supported transactions.
+    fn write_recall(&self) -> f64 {
+        self.num_correct_writes as f64 / self.num_actual_writes.max(1) as f64
+    }
+}
+
+/// Compares the estimates of `RwSetEstimates` to the write sets of the committed transactions.
+pub struct RwSetAccuracyChecker {
+    estimates: Arc<RwSetEstimates>,
+    accuracy: RwSetAccuracy,
+}
+
+impl RwSetAccuracyChecker {
+    pub fn new(estimates: Arc<RwSetEstimates>) -> Self {
+        Self {
+            estimates,
+            accuracy: RwSetAccuracy::default(),
+        }
+    }
+}
+
+impl PostCommitPlugin for RwSetAccuracyChecker {
+    fn name(&self) -> &'static str {
+        "RwSetAccuracyChecker"
+    }
+
+    fn process_block(
+        &mut self,
+        _first_version: Version,
+        txns_and_outputs: &[(Transaction, TransactionOutput)],
+    ) -> Result<()> {
+        let mut block_estimates = self.estimates.take_block();
+        for (txn, output) in txns_and_outputs {
+            let estimate = match block_estimates.remove(&txn.hash()) {
+                Some(estimate) => estimate,
+                None => continue,
+            };
+            if !matches!(txn, Transaction::UserTransaction(_)) {
+                continue;
+            }
+            let actual_writes = output
+                .write_set()
+                .into_iter()
+                .map(|(state_key, _)| state_key.clone())
+                .collect();
+            self.accuracy.record(&estimate, &actual_writes);
+        }
+        self.accuracy.num_uncommitted_txns += block_estimates.len();
+        Ok(())
+    }
+
+    fn finish(&mut self) {
+        let num_estimated = NUM_TXNS.with_label_values(&["rw_set_estimation"]).get();
+        let estimation_secs = TIMER
+            .with_label_values(&["rw_set_estimation"])
+            .get_sample_sum();
+        let accuracy = &self.accuracy;
+        let num_supported_txns = accuracy.num_txns - accuracy.num_unsupported_txns;
+        info!(
+            "Read/write set estimation: {:.2} us per txn, {} of {} committed user txns supported, {} of them with the exact write set, {} estimated txns not committed.",
+            estimation_secs * 1e6 / num_estimated.max(1) as f64,
+            num_supported_txns,
+            accuracy.num_txns,
+            accuracy.num_exact_txns,
+            accuracy.num_uncommitted_txns,
+        );
+        info!(
+            "Read/write set estimation of supported txns: {:.2} read hints and {:.2} write hints per txn, {:.2} actual writes per txn, write precision {:.3}, write recall {:.3}.",
+            accuracy.num_read_hints as f64 / num_supported_txns.max(1) as f64,
+            accuracy.num_estimated_writes as f64 / num_supported_txns.max(1) as f64,
+            accuracy.num_actual_writes as f64 / num_supported_txns.max(1) as f64,
+            accuracy.write_precision(),
+            accuracy.write_recall(),
+        );
+    }
+}
+
+#[test]
+fn test_rw_set_accuracy() {
+    let key = |byte: u8| StateKey::raw(vec![byte]);
+    let estimate = |num_read_hints: usize, write_hints: &[u8]| TxnRwSetEstimate {
+        num_read_hints,
+        write_hints: write_hints.iter().map(|byte| key(*byte)).collect(),
+    };
+    let writes = |bytes: &[u8]| bytes.iter().map(|byte| key(*byte)).collect::<HashSet<_>>();
+
+    let mut accuracy = RwSetAccuracy::default();
+    accuracy.record(&estimate(2, &[1, 2]), &writes(&[1, 2]));
+    accuracy.record(&estimate(2, &[3, 4]), &writes(&[3, 5, 6]));
+    accuracy.record(&estimate(0, &[]), &writes(&[7]));
+
+    assert_eq!(accuracy.num_txns, 3);
+    assert_eq!(accuracy.num_unsupported_txns, 1);
+    assert_eq!(accuracy.num_exact_txns, 1);
+    assert_eq!(accuracy.num_read_hints, 4);
+    assert_eq!(accuracy.write_precision(), 0.75);
+    assert_eq!(accuracy.write_recall(), 0.6);
+}
+
+#[test]
+fn test_rw_set_estimates_evicted_per_block() {
+    use aptos_types::{
+        transaction::{ExecutionStatus, TransactionStatus},
+        write_set::WriteSet,
+    };
+
+    let estimate = || TxnRwSetEstimate {
+        num_read_hints: 0,
+        write_hints: HashSet::new(),
+    };
+    let txns: Vec<_> = (0..3)
+        .map(|_| Transaction::StateCheckpoint(HashValue::random()))
+        .collect();
+    let estimates = Arc::new(RwSetEstimates::default());
+    estimates.push_block(txns.iter().map(|txn| (txn.hash(), estimate())).collect());
+    estimates.push_block(HashMap::from([(HashValue::random(), estimate())]));
+    let mut checker = RwSetAccuracyChecker::new(estimates.clone());
+
+    // Only the first two transactions of the first block are committed.
+    let output = TransactionOutput::new(
+        WriteSet::default(),
+        vec![],
+        0,
+        TransactionStatus::Keep(ExecutionStatus::Success),
+    );
+    let committed: Vec<_> = txns[..2]
+        .iter()
+        .map(|txn| (txn.clone(), output.clone()))
+        .collect();
+    checker.process_block(0, &committed).unwrap();
+    assert_eq!(checker.accuracy.num_uncommitted_txns, 1);
+    assert_eq!(estimates.blocks.lock().unwrap().len(), 1);
+
+    checker.process_block(3, &[]).unwrap();
+    assert_eq!(checker.accuracy.num_uncommitted_txns, 2);
+    assert!(estimates.blocks.lock().unwrap().is_empty());
+}
@@ -0,0 +1,725 @@
+// Copyright © Aptos Foundation
+// SPDX-License-Identifier: Apache-2.0
+
+//! A long running service that starts benchmark runs over a small REST API, so that dashboards
+//! and bots can run standardized benchmarks on dedicated hardware:
+//!
+//! * `POST /runs` with a `RunRequest` starts a run, and answers its id as `{"id": 0}`. Runs share
+//!   the hardware, so a run can only be started once the previous one is over;
+//! * `GET /runs` and `GET /runs/{id}` return the `RunStatus` of all runs, or of one;
+//! * `GET /runs/{id}/results` returns the results of a run that succeeded, as written by
+//!   `--results-json`;
+//! * `GET /runs/{id}/output` returns the stdout and stderr of a run so far;
+//! * `POST /runs/{id}/abort` kills a run.
+//!
+//! Like the runs of an experiment, every run is a run of this binary with the arguments of the
+//! request, in a process of its own, since most of the configuration can only be set once per
+//! process. Its outputs are kept in a directory of its own, and so is the copy of the DB it runs
+//! on, which is removed once the run is over. Runs can only be `run-executor` runs, with the
+//! options in `ALLOWED_FLAGS` and `ALLOWED_OPTIONS`: the ones naming other files to write, or
+//! other machines to talk to, are left to the command line.
+//!
+//! Requests have to carry the token of `SERVE_AUTH_TOKEN_ENV` or `--auth-token-file` as a bearer
+//! token, which is only optional when serving on a loopback address.
+
+use anyhow::{bail, Result};
+use aptos_logger::{info, warn};
+use hyper::{
+    body,
+    header::{AUTHORIZATION, CONTENT_TYPE},
+    service::{make_service_fn, service_fn},
+    Body, Method, Request, Response, Server, StatusCode,
+};
+use serde::{Deserialize, Serialize};
+use serde_json::{json, Value};
+use std::{
+    collections::BTreeMap,
+    convert::Infallible,
+    fs::{self, File},
+    net::SocketAddr,
+    path::{Path, PathBuf},
+    process::{Child, Command, Stdio},
+    sync::{Arc, Mutex},
+    thread,
+    time::{Duration, SystemTime, UNIX_EPOCH},
+};
+
+/// Environment variable holding the bearer token requests have to carry, if not read from a file.
+pub const SERVE_AUTH_TOKEN_ENV: &str = "SERVE_AUTH_TOKEN";
+
+const RUN_DIR_PREFIX: &str = "run_";
+const RESULTS_FILE: &str = "results.json";
+const PROGRESS_FILE: &str = "progress.jsonl";
+const OUTPUT_FILE: &str = "output.log";
+const CHECKPOINT_DIR: &str = "checkpoint";
+/// How often the service checks whether the run in progress exited.
+const POLL_INTERVAL: Duration = Duration::from_millis(500);
+/// The only subcommand runs can use.
+const RUN_SUBCOMMAND: &str = "run-executor";
+/// Options without a value runs can use.
+const ALLOWED_FLAGS: [&str; 20] = [
+    "--abort-on-stuck-block",
+    "--allow-aborts",
+    "--allow-discards",
+    "--check-balance-conservation",
+    "--enable-epoch-snapshot-pruner",
+    "--enable-ledger-pruner",
+    "--enable-state-pruner",
+    "--enable-storage-sharding",
+    "--estimate-rw-sets",
+    "--generate-then-execute",
+    "--large-block-stress",
+    "--perf-counters",
+    "--shuffle-connected-txns",
+    "--skip-commit",
+    "--split-stages",
+    "--use-global-executor",
+    "--use-native-executor",
+    "--use-ptx-executor",
+    "--verify-sequence-numbers",
+    "--verify-sharded",
+];
+/// Options with values runs can use. `--data-dir` can only be given once, since the other
+/// directories given are written to.
+const ALLOWED_OPTIONS: [&str; 66] = [
+    "--additional-dst-pool-accounts",
+    "--assert-events-per-txn",
+    "--block-arrival-burst-size",
+    "--block-arrival-rate",
+    "--block-execution-timeout-secs",
+    "--block-size",
+    "--blocks",
+    "--call-chain-depth",
+    "--call-chain-modules",
+    "--cgroup-memory-limit-mb",
+    "--checkpoint-workers",
+    "--connected-tx-grps",
+    "--cpu-quota-cores",
+    "--data-dir",
+    "--durability",
+    "--entry-function-data-length",
+    "--epoch-snapshot-prune-window",
+    "--epoch-snapshot-pruning-batch-size",
+    "--execution-cores",
+    "--execution-threads",
+    "--historical-read-max-lag-versions",
+    "--historical-read-qps",
+    "--hotspot-probability",
+    "--inject-delay-before-commit-ms",
+    "--inject-delay-before-execution-ms",
+    "--inject-delay-before-ledger-update-ms",
+    "--inject-duplicate-rate",
+    "--inject-replay-rate",
+    "--leak-check-interval-secs",
+    "--ledger-prune-window",
+    "--ledger-pruning-batch-size",
+    "--load-imbalance-tolerance",
+    "--main-signer-accounts",
+    "--max-block-bytes",
+    "--max-block-retries",
+    "--max-partitioning-rounds",
+    "--memory-guardrail-mb",
+    "--module-cache",
+    "--module-working-set-size",
+    "--native-strategy",
+    "--num-executor-shards",
+    "--num-generator-workers",
+    "--partitioner-cross-shard-dep-avoid-threshold",
+    "--partitioner-v2-dashmap-num-shards",
+    "--partitioner-v2-num-threads",
+    "--partitioner-version",
+    "--payload-entropy",
+    "--pre-partitioner",
+    "--prewarm-modules",
+    "--rocksdb-cores",
+    "--seed",
+    "--shadow-verify-every",
+    "--start-version",
+    "--starvation-threshold-ms",
+    "--state-prune-window",
+    "--state-pruning-batch-size",
+    "--storage-latency-jitter-us",
+    "--storage-latency-us",
+    "--table-key-space-size",
+    "--table-removal-percentage",
+    "--target-block-latency-ms",
+    "--thread-utilization-sample-ms",
+    "--transaction-type",
+    "--transaction-weights",
+    "--transactions-per-sender",
+    "--verify-sharded-every",
+];
+/// Options of `ALLOWED_OPTIONS` that take any number of values.
+const MULTI_VALUE_OPTIONS: [&str; 3] = [
+    "--prewarm-modules",
+    "--transaction-type",
+    "--transaction-weights",
+];
+
+#[derive(Debug, Deserialize)]
+pub struct RunRequest {
+    /// Arguments of the run, as on the command line, e.g.
+    /// `["--block-size", "1000", "run-executor", "--data-dir", ...]`.
+    pub args: Vec<String>,
+}
+
+#[derive(Clone, Copy, Debug, Eq, PartialEq, Serialize)]
+#[serde(rename_all = "snake_case")]
+pub enum RunState {
+    Running,
+    Succeeded,
+    Failed,
+    Aborted,
+}
+
+#[derive(Debug, Serialize)]
+pub struct RunStatus {
+    pub id: u64,
+    pub state: RunState,
+    pub args: Vec<String>,
+    pub started_at_ms: u64,
+    pub finished_at_ms: Option<u64>,
+    pub exit_code: Option<i32>,
+    /// Last progress event of the run, see `progress_events`.
+    pub last_progress: Option<Value>,
+}
+
+struct Run {
+    args: Vec<String>,
+    dir: PathBuf,
+    state: RunState,
+    started_at_ms: u64,
+    finished_at_ms: Option<u64>,
+    exit_code: Option<i32>,
+    /// The process of the run, until it exits.
+    child: Option<Child>,
+}
+
+impl Run {
+    fn status(&self, id: u64) -> RunStatus {
+        let last_progress = fs::read_to_string(self.dir.join(PROGRESS_FILE))
+            .ok()
+            .and_then(|progress| {
+                progress
+                    .lines()
+                    .rev()
+                    .find_map(|line| serde_json::from_str(line).ok())
+            });
+        RunStatus {
+            id,
+            state: self.state,
+            args: self.args.clone(),
+            started_at_ms: self.started_at_ms,
+            finished_at_ms: self.finished_at_ms,
+            exit_code: self.exit_code,
+            last_progress,
+        }
+    }
+
+    fn finish(&mut self, state: RunState, exit_code: Option<i32>) {
+        self.state = state;
+        self.exit_code = exit_code;
+        self.finished_at_ms = Some(now_ms());
+        self.child = None;
+    }
+}
+
+/// An error answered to a request.
+type ApiError = (StatusCode, String);
+
+struct BenchmarkService {
+    binary: PathBuf,
+    runs_dir: PathBuf,
+    auth_token: Option<String>,
+    /// Id of the first run of this service, after the runs of the previous services on `runs_dir`.
+    first_run_id: u64,
+    runs: Mutex<BTreeMap<u64, Run>>,
+}
+
+impl BenchmarkService {
+    fn start_run(&self, request: RunRequest) -> Result<u64, ApiError> {
+        check_args(&request.args).map_err(|err| (StatusCode::BAD_REQUEST, err))?;
+        let mut runs = self.runs.lock().unwrap();
+        if let Some((id, _)) = runs.iter().find(|(_, run)| run.state == RunState::Running) {
+            return Err((
+                StatusCode::CONFLICT,
+                format!("Run {} is still in progress", id),
+            ));
+        }
+        let id = runs
+            .keys()
+            .next_back()
+            .map_or(self.first_run_id, |id| id + 1);
+        let dir = self.runs_dir.join(format!("{}{}", RUN_DIR_PREFIX, id));
+        let child = self
+            .spawn_run(&request.args, &dir)
+            .map_err(|err| (StatusCode::INTERNAL_SERVER_ERROR, err.to_string()))?;
+        info!("Started run {}: {:?}", id, request.args);
+        runs.insert(id, Run {
+            args: request.args,
+            dir,
+            state: RunState::Running,
+            started_at_ms: now_ms(),
+            finished_at_ms: None,
+            exit_code: None,
+            child: Some(child),
+        });
+        Ok(id)
+    }
+
+    fn spawn_run(&self, args: &[String], dir: &Path) -> Result<Child> {
+        fs::create_dir_all(dir)?;
+        let output = File::create(dir.join(OUTPUT_FILE))?;
+        Ok(Command::new(&self.binary)
+            .arg("--results-json")
+            .arg(dir.join(RESULTS_FILE))
+            .arg("--progress-file")
+            .arg(dir.join(PROGRESS_FILE))
+            .args(args)
+            .arg("--checkpoint-dir")
+            .arg(dir.join(CHECKPOINT_DIR))
+            .stdin(Stdio::null())
+            .stdout(output.try_clone()?)
+            .stderr(output)
+            .spawn()?)
+    }
+
+    fn with_run<T>(
+        &self,
+        id: &str,
+        f: impl FnOnce(u64, &mut Run) -> Result<T, ApiError>,
+    ) -> Result<T, ApiError> {
+        let id = id
+            .parse::<u64>()
+            .map_err(|_| (StatusCode::BAD_REQUEST, format!("Invalid run id {}", id)))?;
+        match self.runs.lock().unwrap().get_mut(&id) {
+            Some(run) => f(id, run),
+            None => Err((StatusCode::NOT_FOUND, format!("Run {} doesn't exist", id))),
+        }
+    }
+
+    fn list(&self) -> Vec<RunStatus> {
+        self.runs
+            .lock()
+            .unwrap()
+            .iter()
+            .map(|(id, run)| run.status(*id))
+            .collect()
+    }
+
+    fn results(id: u64, run: &mut Run) -> Result<Value, ApiError> {
+        if run.state != RunState::Succeeded {
+            return Err((
+                StatusCode::CONFLICT,
+                format!(
+                    "Run {} is {:?}, results are only kept for runs that succeeded",
+                    id, run.state
+                ),
+            ));
+        }
+        fs::read(run.dir.join(RESULTS_FILE))
+            .map_err(|err| err.to_string())
+            .and_then(|results| serde_json::from_slice(&results).map_err(|err| err.to_string()))
+            .map_err(|err| {
+                (
+                    StatusCode::INTERNAL_SERVER_ERROR,
+                    format!("Cannot read the results of run {}: {}", id, err),
+                )
+            })
+    }
+
+    /// Kills the process of a run, and waits for it to exit off the async workers and without
+    /// holding the lock of the runs. The run stays running until then, so that no other run is
+    /// started alongside it.
+    async fn abort(&self, id: &str) -> Result<Value, ApiError> {
+        let (id, mut child, dir) = self.with_run(id, |id, run| match run.child.take() {
+            Some(mut child) => {
+                if let Err(err) = child.kill() {
+                    warn!("Failed to kill run {}: {}", id, err);
+                }
+                Ok((id, child, run.dir.clone()))
+            },
+            None if run.state == RunState::Running => Err((
+                StatusCode::CONFLICT,
+                format!("Run {} is already being aborted", id),
+            )),
+            None => Err((
+                StatusCode::CONFLICT,
+                format!("Run {} is already {:?}", id, run.state),
+            )),
+        })?;
+        let exit_code = tokio::task::spawn_blocking(move || {
+            let exit_code = child.wait().ok().and_then(|status| status.code());
+            remove_checkpoint(&dir);
+            exit_code
+        })
+        .await
+        .unwrap_or_else(|err| {
+            warn!("Failed to wait for run {} to exit: {}", id, err);
+            None
+        });
+        if let Some(run) = self.runs.lock().unwrap().get_mut(&id) {
+            run.finish(RunState::Aborted, exit_code);
+        }
+        info!("Aborted run {}", id);
+        Ok(json!({ "id": id }))
+    }
+
+    /// Records the runs whose process exited, and removes the copies of the DB they ran on.
+    fn poll_runs(&self) {
+        let mut finished_dirs = vec![];
+        for (id, run) in self.runs.lock().unwrap().iter_mut() {
+            let exit_status = match run.child.as_mut().map(Child::try_wait) {
+                Some(Ok(Some(exit_status))) => exit_status,
+                Some(Err(err)) => {
+                    warn!("Cannot check whether run {} exited: {}", id, err);
+                    continue;
+                },
+                _ => continue,
+            };
+            let state = if exit_status.success() {
+                RunState::Succeeded
+            } else {
+                RunState::Failed
+            };
+            info!("Run {} is over: {}", id, exit_status);
+            run.finish(state, exit_status.code());
+            finished_dirs.push(run.dir.clone());
+        }
+        for dir in finished_dirs {
+            remove_checkpoint(&dir);
+        }
+    }
+
+    fn is_authorized(&self, request: &Request<Body>) -> bool {
+        match &self.auth_token {
+            None => true,
+            Some(auth_token) => request
+                .headers()
+                .get(AUTHORIZATION)
+                .and_then(|value| value.to_str().ok())
+                .map_or(false, |value| value == format!("Bearer {}", auth_token)),
+        }
+    }
+
+    async fn handle(&self, request: Request<Body>) -> Response<Body> {
+        if !self.is_authorized(&request) {
+            return error_response((
+                StatusCode::UNAUTHORIZED,
+                "Missing or wrong bearer token".to_string(),
+            ));
+        }
+        let method = request.method().clone();
+        let path = request.uri().path().to_string();
+        let segments = path.trim_matches('/').split('/').collect::<Vec<_>>();
+        let response = match (method, segments.as_slice()) {
+            (Method::GET, ["runs"]) => Ok(json!(self.list())),
+            (Method::POST, ["runs"]) => match body::to_bytes(request.into_body()).await {
+                Ok(body) => serde_json::from_slice::<RunRequest>(&body)
+                    .map_err(|err| {
+                        (
+                            StatusCode::BAD_REQUEST,
+                            format!("Invalid run request: {}", err),
+                        )
+                    })
+                    .and_then(|run_request| self.start_run(run_request))
+                    .map(|id| json!({ "id": id })),
+                Err(err) => Err((StatusCode::BAD_REQUEST, err.to_string())),
+            },
+            (Method::GET, ["runs", id]) => self.with_run(id, |id, run| Ok(json!(run.status(id)))),
+            (Method::GET, ["runs", id, "results"]) => self.with_run(id, Self::results),
+            (Method::GET, ["runs", id, "output"]) => {
+                return match self.with_run(id, |_, run| {
+                    fs::read(run.dir.join(OUTPUT_FILE))
+                        .map_err(|err| (StatusCode::INTERNAL_SERVER_ERROR, err.to_string()))
+                }) {
+                    Ok(output) => Response::builder()
+                        .header(CONTENT_TYPE, "text/plain")
+                        .body(Body::from(output))
+                        .unwrap(),
+                    Err(err) => error_response(err),
+                };
+            },
+            (Method::POST, ["runs", id, "abort"]) => self.abort(id).await,
+            _ => Err((StatusCode::NOT_FOUND, format!("No endpoint {}", path))),
+        };
+        match response {
+            Ok(value) => json_response(StatusCode::OK, &value),
+            Err(err) => error_response(err),
+        }
+    }
+}
+
+fn json_response(status: StatusCode, value: &Value) -> Response<Body> {
+    Response::builder()
+        .status(status)
+        .header(CONTENT_TYPE, "application/json")
+        .body(Body::from(value.to_string()))
+        .unwrap()
+}
+
+fn error_response((status, error): ApiError) -> Response<Body> {
+    json_response(status, &json!({ "error": error }))
+}
+
+fn now_ms() -> u64 {
+    SystemTime::now()
+        .duration_since(UNIX_EPOCH)
+        .map_or(0, |duration| duration.as_millis() as u64)
+}
+
+fn remove_checkpoint(dir: &Path) {
+    let checkpoint_dir = dir.join(CHECKPOINT_DIR);
+    if checkpoint_dir.exists() {
+        if let Err(err) = fs::remove_dir_all(&checkpoint_dir) {
+            warn!("Cannot remove {:?}: {}", checkpoint_dir, err);
+        }
+    }
+}
+
+/// Id following the ones of the runs already in `runs_dir`, so that the outputs of earlier
+/// services are kept.
+fn next_run_id(runs_dir: &Path) -> Result<u64> {
+    let mut next_run_id = 0;
+    for entry in fs::read_dir(runs_dir)? {
+        if let Some(id) = entry?
+            .file_name()
+            .to_str()
+            .and_then(|name| name.strip_prefix(RUN_DIR_PREFIX))
+            .and_then(|id| id.parse::<u64>().ok())
+        {
+            next_run_id = next_run_id.max(id + 1);
+        }
+    }
+    Ok(next_run_id)
+}
+
+/// Checks that the arguments of a run are a `run-executor` run with allowed options only.
+fn check_args(args: &[String]) -> Result<(), String> {
+    let mut has_subcommand = false;
+    let mut has_data_dir = false;
+    let mut args = args.iter().peekable();
+    while let Some(arg) = args.next() {
+        if !arg.starts_with("--") {
+            if arg != RUN_SUBCOMMAND || has_subcommand {
+                return Err(format!(
+                    "Runs can only be {} runs, not {}",
+                    RUN_SUBCOMMAND, arg
+                ));
+            }
+            has_subcommand = true;
+            continue;
+        }
+        let (option, value) = match arg.split_once('=') {
+            Some((option, value)) => (option, Some(value)),
+            None => (arg.as_str(), None),
+        };
+        if ALLOWED_FLAGS.contains(&option) && value.is_none() {
+            continue;
+        }
+        if !ALLOWED_OPTIONS.contains(&option) {
+            return Err(format!("{} is not allowed in runs of the service", option));
+        }
+        if option == "--data-dir" {
+            if has_data_dir {
+                return Err("--data-dir can only be given once".to_string());
+            }
+            has_data_dir = true;
+        }
+        if MULTI_VALUE_OPTIONS.contains(&option) {
+            while args.next_if(|arg| !arg.starts_with("--")).is_some() {}
+        } else if value.is_none() && args.next().is_none() {
+            return Err(format!("{} is missing its value", option));
+        }
+    }
+    if !has_subcommand {
+        return Err(format!("Runs have to be {} runs", RUN_SUBCOMMAND));
+    }
+    Ok(())
+}
+
+/// Serves the API on `address` until the process is killed, keeping the outputs of every run in
+/// a directory of its own under `runs_dir`. If `auth_token` is given, every request has to carry
+/// it as a bearer token, which is required unless `address` is a loopback address.
+pub fn serve(address: SocketAddr, runs_dir: PathBuf, auth_token: Option<String>) -> Result<()> {
+    if auth_token.is_none() && !address.ip().is_loopback() {
+        bail!(
+            "Serving on {}, which is not a loopback address, requires an auth token in {} or --auth-token-file",
+            address,
+            SERVE_AUTH_TOKEN_ENV
+        );
+    }
+    fs::create_dir_all(&runs_dir)?;
+    let service = Arc::new(BenchmarkService {
+        binary: std::env::current_exe()?,
+        first_run_id: next_run_id(&runs_dir)?,
+        runs_dir,
+        auth_token,
+        runs: Mutex::new(BTreeMap::new()),
+    });
+    let poller = service.clone();
+    thread::Builder::new()
+        .name("serve-run-poller".to_string())
+        .spawn(move || loop {
+            poller.poll_runs();
+            thread::sleep(POLL_INTERVAL);
+        })?;
+
+    let runtime = tokio::runtime::Builder::new_multi_thread()
+        .thread_name("serve")
+        .enable_all()
+        .build()?;
+    runtime.block_on(async move {
+        let make_service = make_service_fn(move |_conn| {
+            let service = service.clone();
+            async move {
+                Ok::<_, Infallible>(service_fn(move |request| {
+                    let service = service.clone();
+                    async move { Ok::<_, Infallible>(service.handle(request).await) }
+                }))
+            }
+        });
+        info!("Serving the benchmark API on {}", address);
+        Server::try_bind(&address)?.serve(make_service).await?;
+        Ok::<_, anyhow::Error>(())
+    })
+}
+
+#[cfg(test)]
+fn args(args: &[&str]) -> Vec<String> {
+    args.iter().map(|arg| arg.to_string()).collect()
+}
+
+#[test]
+fn test_check_args() {
+    assert!(check_args(&args(&["--block-size", "1000", "run-executor"])).is_ok());
+    assert!(check_args(&args(&[
+        "--block-size=1000",
+        "--split-stages",
+        "run-executor",
+        "--transaction-type",
+        "no-op",
+        "no-op-fee-payer",
+        "--data-dir",
+        "/dbs/db"
+    ]))
+    .is_ok());
+    assert!(check_args(&args(&[
+        "--results-json",
+        "/tmp/results.json",
+        "run-executor"
+    ]))
+    .is_err());
+    assert!(check_args(&args(&["--progress-fd=3", "run-executor"])).is_err());
+    assert!(check_args(&args(&["run-executor", "--checkpoint-dir", "/tmp/db"])).is_err());
+    assert!(check_args(&args(&["run-executor", "--export-outputs-path", "/tmp/x"])).is_err());
+    // Only run-executor runs, and a flag cannot take a subcommand as its value.
+    assert!(check_args(&args(&["--block-size", "1000"])).is_err());
+    assert!(check_args(&args(&["create-db", "--data-dir", "/tmp/db"])).is_err());
+    assert!(check_args(&args(&["--split-stages", "serve", "run-executor"])).is_err());
+    assert!(check_args(&args(&["--split-stages=serve", "run-executor"])).is_err());
+    assert!(check_args(&args(&[
+        "run-executor",
+        "--data-dir",
+        "/a",
+        "--data-dir",
+        "/b"
+    ]))
+    .is_err());
+    assert!(check_args(&args(&["run-executor", "--blocks"])).is_err());
+}
+
+#[test]
+fn test_next_run_id() {
+    let dir = aptos_temppath::TempPath::new();
+    dir.create_as_dir().unwrap();
+    assert_eq!(next_run_id(dir.path()).unwrap(), 0);
+    for name in ["run_0", "run_7", "run_3", "run_x", "other_9"] {
+        fs::create_dir(dir.path().join(name)).unwrap();
+    }
+    assert_eq!(next_run_id(dir.path()).unwrap(), 8);
+}
+
+#[test]
+fn test_serve_requires_auth_token_off_loopback() {
+    let dir = aptos_temppath::TempPath::new();
+    let err = serve("0.0.0.0:0".parse().unwrap(), dir.path().to_path_buf(), None).unwrap_err();
+    assert!(err.to_string().contains(SERVE_AUTH_TOKEN_ENV));
+    assert!(!dir.path().exists());
+}
+
+#[test]
+fn test_benchmark_service() {
+    let runs_dir = aptos_temppath::TempPath::new();
+    runs_dir.create_as_dir().unwrap();
+    fs::create_dir(runs_dir.path().join("run_4")).unwrap();
+    // Runs of this script run until aborted, whatever their arguments.
+    let binary = runs_dir.path().join("run.sh");
+    fs::write(&binary, "#!/bin/sh\nsleep 60\n").unwrap();
+    fs::set_permissions(&binary, std::os::unix::fs::PermissionsExt::from_mode(0o755)).unwrap();
+    let service = BenchmarkService {
+        binary,
+        runs_dir: runs_dir.path().to_path_buf(),
+        auth_token: Some("token".to_string()),
+        first_run_id: next_run_id(runs_dir.path()).unwrap(),
+        runs: Mutex::new(BTreeMap::new()),
+    };
+    let request = |method: Method, uri: &str, body: &str, auth_token: &str| {
+        Request::builder()
+            .method(method)
+            .uri(uri)
+            .header(AUTHORIZATION, format!("Bearer {}", auth_token))
+            .body(Body::from(body.to_string()))
+            .unwrap()
+    };
+    let runtime = tokio::runtime::Runtime::new().unwrap();
+    let call = |method: Method, uri: &str, body: &str, auth_token: &str| {
+        let response = runtime.block_on(service.handle(request(method, uri, body, auth_token)));
+        let status = response.status();
+        let body = runtime
+            .block_on(body::to_bytes(response.into_body()))
+            .unwrap();
+        (status, serde_json::from_slice::<Value>(&body).unwrap())
+    };
+
+    let (status, _) = call(Method::GET, "/runs", "", "wrong");
+    assert_eq!(status, StatusCode::UNAUTHORIZED);
+    let (status, _) = call(
+        Method::POST,
+        "/runs",
+        r#"{"args": ["create-db", "--data-dir", "/tmp/db"]}"#,
+        "token",
+    );
+    assert_eq!(status, StatusCode::BAD_REQUEST);
+
+    // Ids follow the runs already in the runs dir.
+    let (status, body) = call(
+        Method::POST,
+        "/runs",
+        r#"{"args": ["run-executor", "--blocks", "60"]}"#,
+        "token",
+    );
+    assert_eq!((status, body), (StatusCode::OK, json!({ "id": 5 })));
+    let (status, _) = call(
+        Method::POST,
+        "/runs",
+        r#"{"args": ["run-executor"]}"#,
+        "token",
+    );
+    assert_eq!(status, StatusCode::CONFLICT);
+    let (status, body) = call(Method::GET, "/runs/5", "", "token");
+    assert_eq!(status, StatusCode::OK);
+    assert_eq!(body["state"], json!("running"));
+    let (status, _) = call(Method::GET, "/runs/5/results", "", "token");
+    assert_eq!(status, StatusCode::CONFLICT);
+
+    let (status, _) = call(Method::POST, "/runs/5/abort", "", "token");
+    assert_eq!(status, StatusCode::OK);
+    let (status, body) = call(Method::GET, "/runs/5", "", "token");
+    assert_eq!(status, StatusCode::OK);
+    assert_eq!(body["state"], json!("aborted"));
+    let (status, _) = call(Method::POST, "/runs/5/abort", "", "token");
+    assert_eq!(status, StatusCode::CONFLICT);
+    let (status, _) = call(Method::GET, "/runs/6", "", "token");
+    assert_eq!(status, StatusCode::NOT_FOUND);
+}
@@ -0,0 +1,228 @@
+// Copyright © Aptos Foundation
+// SPDX-License-Identifier: Apache-2.0
+
+use crate::metrics::SHADOW_VERIFIED_KEYS;
+use anyhow::Result;
+use aptos_crypto::hash::CryptoHash;
+use aptos_executor::block_executor::{BlockExecutor, TransactionBlockExecutor};
+use aptos_logger::{error, info, warn};
+use aptos_scratchpad::{FrozenSparseMerkleTree, StateStoreStatus};
+use aptos_storage_interface::DbReader;
+use aptos_types::{
+    state_store::{state_key::StateKey, state_value::StateValue},
+    transaction::Version,
+};
+use std::{
+    collections::HashSet,
+    sync::{mpsc, Arc},
+    thread::JoinHandle,
+};
+
+/// Number of sampled blocks waiting to be verified, beyond which samples are dropped rather than
+/// holding up the commits. Every pending block keeps its in-memory state tree alive.
+const MAX_PENDING_BLOCKS: usize = 2;
+/// Number of mismatches logged in full, the rest are only counted.
+const MAX_LOGGED_MISMATCHES: usize = 10;
+
+/// Compares, for every `every_blocks`th committed block, the in-memory state the executor reads
+/// from when executing the following blocks with what was persisted to the DB, to catch the
+/// in-memory state and the DB diverging during a run.
+///
+/// Right after the block commits, its in-memory state is captured; a background thread then reads
+/// the keys written by the block back from the DB at the version of the block, and compares them
+/// with the values the in-memory state tree holds for them. Keys whose values were already evicted
+/// from the tree, which execution reads from the DB anyway, aren't compared.
+pub struct ShadowVerifier {
+    every_blocks: usize,
+    next_block_index: usize,
+    num_dropped_blocks: usize,
+    block_sender: mpsc::SyncSender<VerifyBlockMessage>,
+    join_handle: JoinHandle<VerifyStats>,
+}
+
+struct VerifyBlockMessage {
+    block_index: usize,
+    first_version: Version,
+    /// Version of the in-memory state, the last one of the block.
+    version: Version,
+    /// The in-memory state, frozen the way execution does, so that values evicted since are not
+    /// mistaken for deleted ones.
+    speculative_state: FrozenSparseMerkleTree<StateValue>,
+}
+
+#[derive(Default)]
+struct VerifyStats {
+    num_blocks: usize,
+    num_matched: usize,
+    num_not_cached: usize,
+    num_mismatched: usize,
+    num_errors: usize,
+}
+
+impl ShadowVerifier {
+    pub fn new(db: Arc<dyn DbReader>, every_blocks: usize) -> Self {
+        assert!(
+            every_blocks > 0,
+            "Shadow verification interval must be positive."
+        );
+        let (block_sender, block_receiver) = mpsc::sync_channel(MAX_PENDING_BLOCKS);
+        let join_handle = std::thread::Builder::new()
+            .name("shadow_verifier".to_string())
+            .spawn(move || Self::run(db, block_receiver))
+            .expect("Failed to spawn shadow verifier thread.");
+        Self {
+            every_blocks,
+            next_block_index: 0,
+            num_dropped_blocks: 0,
+            block_sender,
+            join_handle,
+        }
+    }
+
+    /// Called after every committed block, must not be called concurrently with commits.
+    pub fn record_block<V: TransactionBlockExecutor>(
+        &mut self,
+        executor: &BlockExecutor<V>,
+        first_version: Version,
+    ) {
+        let block_index = self.next_block_index;
+        self.next_block_index += 1;
+        if (block_index + 1) % self.every_blocks != 0 {
+            return;
+        }
+        let state = executor.root_state();
+        let version = match state.current_version {
+            Some(version) => version,
+            None => return,
+        };
+        let base_smt = match executor.db.reader.get_buffered_state_base() {
+            Ok(base_smt) => base_smt,
+            Err(err) => {
+                warn!(
+                    "Failed to capture the in-memory state after block {}: {}",
+                    block_index, err
+                );
+                return;
+            },
+        };
+        let msg = VerifyBlockMessage {
+            block_index,
+            first_version,
+            version,
+            speculative_state: state.current.freeze(&base_smt),
+        };
+        if let Err(mpsc::TrySendError::Full(_)) = self.block_sender.try_send(msg) {
+            self.num_dropped_blocks += 1;
+        }
+    }
+
+    fn run(
+        db: Arc<dyn DbReader>,
+        block_receiver: mpsc::Receiver<VerifyBlockMessage>,
+    ) -> VerifyStats {
+        let mut stats = VerifyStats::default();
+        while let Ok(msg) = block_receiver.recv() {
+            stats.num_blocks += 1;
+            if let Err(err) = Self::verify_block(db.as_ref(), &msg, &mut stats) {
+                stats.num_errors += 1;
+                warn!(
+                    "Failed to shadow verify block {} starting at version {}: {}",
+                    msg.block_index, msg.first_version, err
+                );
+            }
+        }
+        stats
+    }
+
+    fn verify_block(
+        db: &dyn DbReader,
+        msg: &VerifyBlockMessage,
+        stats: &mut VerifyStats,
+    ) -> Result<()> {
+        let version = msg.version;
+        let num_versions = (version + 1).saturating_sub(msg.first_version);
+        let mut written_keys = HashSet::new();
+        for write_set in db.get_write_set_iterator(msg.first_version, num_versions)? {
+            written_keys.extend(write_set?.iter().map(|(state_key, _)| state_key.clone()));
+        }
+
+        for state_key in written_keys {
+            let in_memory = match msg.speculative_state.get(CryptoHash::hash(&state_key)) {
+                StateStoreStatus::ExistsInScratchPad(value) => Some(value),
+                StateStoreStatus::DoesNotExist => None,
+                StateStoreStatus::ExistsInDB | StateStoreStatus::Unknown => {
+                    stats.num_not_cached += 1;
+                    SHADOW_VERIFIED_KEYS
+                        .with_label_values(&["not_cached"])
+                        .inc();
+                    continue;
+                },
+            };
+            let persisted = db.get_state_value_by_version(&state_key, version)?;
+            if in_memory == persisted {
+                stats.num_matched += 1;
+                SHADOW_VERIFIED_KEYS.with_label_values(&["matched"]).inc();
+            } else {
+                stats.num_mismatched += 1;
+                SHADOW_VERIFIED_KEYS
+                    .with_label_values(&["mismatched"])
+                    .inc();
+                if stats.num_mismatched <= MAX_LOGGED_MISMATCHES {
+                    log_mismatch(msg.block_index, version, &state_key, &in_memory, &persisted);
+                }
+            }
+        }
+        Ok(())
+    }
+
+    /// Waits for the pending blocks to be verified, and panics if any of them diverged.
+    pub fn finish(self) {
+        let Self {
+            num_dropped_blocks,
+            block_sender,
+            join_handle,
+            ..
+        } = self;
+        drop(block_sender);
+        let VerifyStats {
+            num_blocks,
+            num_matched,
+            num_not_cached,
+            num_mismatched,
+            num_errors,
+        } = join_handle
+            .join()
+            .expect("Shadow verifier thread panicked.");
+        info!(
+            "Shadow verification: {} blocks ({} dropped while busy), {} keys matched, {} not cached, {} mismatched, {} blocks failed to verify",
+            num_blocks, num_dropped_blocks, num_matched, num_not_cached, num_mismatched, num_errors
+        );
+        assert_eq!(
+            num_mismatched, 0,
+            "In-memory state diverged from the DB for {} keys",
+            num_mismatched
+        );
+    }
+}
+
+fn log_mismatch(
+    block_index: usize,
+    version: Version,
+    state_key: &StateKey,
+    in_memory: &Option<StateValue>,
+    persisted: &Option<StateValue>,
+) {
+    let describe = |value: &Option<StateValue>| {
+        value.as_ref().map_or("none".to_string(), |value| {
+            format!("{} bytes, hash {}", value.size(), CryptoHash::hash(value))
+        })
+    };
+    error!(
+        "In-memory state diverged from the DB after block {} (version {}) for {:?}: in memory {}, persisted {}",
+        block_index,
+        version,
+        state_key,
+        describe(in_memory),
+        describe(persisted)
+    );
+}
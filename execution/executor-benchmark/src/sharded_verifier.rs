@@ -0,0 +1,200 @@
+// Copyright © Aptos Foundation
+// SPDX-License-Identifier: Apache-2.0
+
+use crate::post_commit::PostCommitPlugin;
+use anyhow::{anyhow, bail, Result};
+use aptos_block_executor::txn_commit_hook::NoOpTransactionCommitHook;
+use aptos_logger::info;
+use aptos_storage_interface::{state_view::DbStateViewAtVersion, DbReader};
+use aptos_types::transaction::{
+    signature_verified_transaction::into_signature_verified_block, Transaction, TransactionOutput,
+    Version,
+};
+use aptos_vm::{
+    aptos_vm::RAYON_EXEC_POOL,
+    block_executor::{AptosTransactionOutput, BlockAptosVM},
+};
+use move_core_types::vm_status::VMStatus;
+use std::sync::Arc;
+
+/// Executes every `every_blocks`th committed block again, unsharded, and fails the run if any
+/// output differs from the committed one, to keep checking the partitioner and the cross-shard
+/// messaging during performance runs.
+///
+/// Like the other post-commit plugins, it runs on the post-commit thread, off the timed path: the
+/// committed transactions and outputs are read back from the DB, and executed sequentially on the
+/// state of the DB right before the block, so that the workers of the sharded executor are not
+/// competed with either. The state before the block must not be pruned yet, which the default
+/// prune windows are far from doing for a block that just committed.
+pub struct ShardedOutputVerifier {
+    db: Arc<dyn DbReader>,
+    every_blocks: usize,
+    next_block_index: usize,
+    num_verified_blocks: usize,
+}
+
+impl ShardedOutputVerifier {
+    pub fn new(db: Arc<dyn DbReader>, every_blocks: usize) -> Self {
+        assert!(
+            every_blocks > 0,
+            "Sharded verification interval must be positive."
+        );
+        Self {
+            db,
+            every_blocks,
+            next_block_index: 0,
+            num_verified_blocks: 0,
+        }
+    }
+}
+
+impl PostCommitPlugin for ShardedOutputVerifier {
+    fn name(&self) -> &'static str {
+        "sharded_output_verifier"
+    }
+
+    fn process_block(
+        &mut self,
+        first_version: Version,
+        txns_and_outputs: &[(Transaction, TransactionOutput)],
+    ) -> Result<()> {
+        let block_index = self.next_block_index;
+        self.next_block_index += 1;
+        if block_index % self.every_blocks != 0 {
+            return Ok(());
+        }
+        // The state checkpoints are appended to the block by the executor, not executed.
+        let (txns, committed_outputs): (Vec<_>, Vec<_>) = txns_and_outputs
+            .iter()
+            .filter(|(txn, _)| !matches!(txn, Transaction::StateCheckpoint(_)))
+            .cloned()
+            .unzip();
+        let num_txns = txns.len();
+        let state_view = self
+            .db
+            .state_view_at_version(first_version.checked_sub(1))?;
+        let unsharded_outputs = BlockAptosVM::execute_block::<
+            _,
+            NoOpTransactionCommitHook<AptosTransactionOutput, VMStatus>,
+        >(
+            Arc::clone(&RAYON_EXEC_POOL),
+            &into_signature_verified_block(txns),
+            &state_view,
+            1, /* concurrency_level */
+            None,
+            None,
+        )
+        .map_err(|status| anyhow!("Unsharded execution failed: {:?}", status))?;
+        if let Some(mismatch) = describe_output_mismatch(&committed_outputs, &unsharded_outputs) {
+            bail!(
+                "Sharded execution of the {} transactions of block {} diverged from unsharded execution: {}",
+                num_txns,
+                block_index,
+                mismatch
+            );
+        }
+        self.num_verified_blocks += 1;
+        Ok(())
+    }
+
+    fn finish(&mut self) {
+        info!(
+            "Verified {} of {} blocks against unsharded execution.",
+            self.num_verified_blocks, self.next_block_index
+        );
+    }
+}
+
+/// Describes the first difference between the outputs of two executions of the same transactions,
+/// if any.
+fn describe_output_mismatch(
+    outputs: &[TransactionOutput],
+    expected_outputs: &[TransactionOutput],
+) -> Option<String> {
+    if outputs.len() != expected_outputs.len() {
+        return Some(format!(
+            "{} outputs instead of {}",
+            outputs.len(),
+            expected_outputs.len()
+        ));
+    }
+    let (index, (output, expected)) = outputs
+        .iter()
+        .zip(expected_outputs)
+        .enumerate()
+        .find(|(_, (output, expected))| output != expected)?;
+    let what = if output.status() != expected.status() {
+        format!(
+            "status {:?} instead of {:?}",
+            output.status(),
+            expected.status()
+        )
+    } else if output.write_set() != expected.write_set() {
+        format!(
+            "write set of {} writes instead of {}",
+            output.write_set().iter().count(),
+            expected.write_set().iter().count()
+        )
+    } else if output.events() != expected.events() {
+        format!(
+            "{} events instead of {}",
+            output.events().len(),
+            expected.events().len()
+        )
+    } else {
+        format!(
+            "{} gas used instead of {}",
+            output.gas_used(),
+            expected.gas_used()
+        )
+    };
+    Some(format!("transaction {}: {}", index, what))
+}
+
+#[test]
+fn test_sharded_output_verifier() {
+    use crate::{create_temp_db, init_db_and_executor, post_commit::read_committed};
+    use aptos_vm::AptosVM;
+
+    let db_dir = create_temp_db::<AptosVM>(10, 100_000_000, 5);
+    let (mut config, _genesis_key) = aptos_genesis::test_utils::test_config();
+    config.storage.dir = db_dir.path().to_path_buf();
+    let (db, _executor) = init_db_and_executor::<AptosVM>(&config);
+
+    // The last transactions of the DB, state checkpoints included.
+    let latest_version = db.reader.get_latest_version().unwrap();
+    let first_version = latest_version - 3;
+    let mut txns_and_outputs = read_committed(&db.reader, first_version, 4).unwrap();
+    let mut verifier = ShardedOutputVerifier::new(db.reader.clone(), 2);
+    verifier
+        .process_block(first_version, &txns_and_outputs)
+        .unwrap();
+
+    // Every other block is verified.
+    let index = txns_and_outputs
+        .iter()
+        .position(|(txn, _)| !matches!(txn, Transaction::StateCheckpoint(_)))
+        .unwrap();
+    let (txn, output) = txns_and_outputs[index].clone();
+    txns_and_outputs[index] = (
+        txn,
+        TransactionOutput::new(
+            output.write_set().clone(),
+            output.events().to_vec(),
+            output.gas_used() + 1,
+            output.status().clone(),
+        ),
+    );
+    verifier
+        .process_block(first_version, &txns_and_outputs)
+        .unwrap();
+    let err = verifier
+        .process_block(first_version, &txns_and_outputs)
+        .unwrap_err();
+    assert!(err.to_string().ends_with(&format!(
+        "transaction 0: {} gas used instead of {}",
+        output.gas_used() + 1,
+        output.gas_used()
+    )));
+    assert_eq!(verifier.num_verified_blocks, 1);
+}
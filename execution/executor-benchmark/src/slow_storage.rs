@@ -0,0 +1,117 @@
+// Copyright © Aptos Foundation
+// SPDX-License-Identifier: Apache-2.0
+
+use anyhow::Result;
+use aptos_logger::info;
+use aptos_storage_interface::{DbReader, DbReaderWriter};
+use aptos_types::{
+    state_store::{state_key::StateKey, state_value::StateValue},
+    transaction::Version,
+};
+use once_cell::sync::OnceCell;
+use rand::Rng;
+use std::{
+    sync::{
+        atomic::{AtomicU64, Ordering},
+        Arc,
+    },
+    thread,
+    time::Duration,
+};
+
+static STORAGE_LATENCY: OnceCell<StorageLatency> = OnceCell::new();
+static NUM_DELAYED_READS: AtomicU64 = AtomicU64::new(0);
+static TOTAL_INJECTED_MICROS: AtomicU64 = AtomicU64::new(0);
+
+/// Latency added to every state read that reaches the DB, to model slow disks or remote storage.
+#[derive(Clone, Copy, Debug)]
+pub struct StorageLatency {
+    pub latency: Duration,
+    /// Each read is delayed by an extra amount, uniformly distributed up to this.
+    pub jitter: Duration,
+}
+
+impl StorageLatency {
+    fn sample(&self) -> Duration {
+        if self.jitter.is_zero() {
+            return self.latency;
+        }
+        self.latency
+            + Duration::from_micros(
+                rand::thread_rng().gen_range(0, self.jitter.as_micros() as u64 + 1),
+            )
+    }
+}
+
+pub fn set_storage_latency_once(storage_latency: StorageLatency) {
+    STORAGE_LATENCY.set(storage_latency).ok();
+}
+
+/// Wraps the reader of the DB to inject the latency set with `set_storage_latency_once`, if any.
+pub fn maybe_slow_down(db: DbReaderWriter) -> DbReaderWriter {
+    match STORAGE_LATENCY.get() {
+        Some(storage_latency) => DbReaderWriter {
+            reader: Arc::new(SlowStateReader {
+                inner: db.reader,
+                storage_latency: *storage_latency,
+            }),
+            writer: db.writer,
+        },
+        None => db,
+    }
+}
+
+pub fn log_injected_latency() {
+    let num_reads = NUM_DELAYED_READS.load(Ordering::Relaxed);
+    if num_reads == 0 {
+        return;
+    }
+    let total_secs = TOTAL_INJECTED_MICROS.load(Ordering::Relaxed) as f64 / 1_000_000.0;
+    info!(
+        "Injected storage latency into {} state reads, {:.3} s in total ({:.0} us per read)",
+        num_reads,
+        total_secs,
+        total_secs * 1_000_000.0 / num_reads as f64
+    );
+}
+
+/// Delays state value reads, the ones that miss the in-memory state and go to disk, and delegates
+/// everything else to the wrapped reader as is.
+struct SlowStateReader {
+    inner: Arc<dyn DbReader>,
+    storage_latency: StorageLatency,
+}
+
+impl SlowStateReader {
+    fn delay(&self) {
+        let latency = self.storage_latency.sample();
+        thread::sleep(latency);
+        NUM_DELAYED_READS.fetch_add(1, Ordering::Relaxed);
+        TOTAL_INJECTED_MICROS.fetch_add(latency.as_micros() as u64, Ordering::Relaxed);
+    }
+}
+
+impl DbReader for SlowStateReader {
+    fn get_read_delegatee(&self) -> &dyn DbReader {
+        self.inner.as_ref()
+    }
+
+    fn get_state_value_by_version(
+        &self,
+        state_key: &StateKey,
+        version: Version,
+    ) -> Result<Option<StateValue>> {
+        self.delay();
+        self.inner.get_state_value_by_version(state_key, version)
+    }
+
+    fn get_state_value_with_version_by_version(
+        &self,
+        state_key: &StateKey,
+        version: Version,
+    ) -> Result<Option<(Version, StateValue)>> {
+        self.delay();
+        self.inner
+            .get_state_value_with_version_by_version(state_key, version)
+    }
+}
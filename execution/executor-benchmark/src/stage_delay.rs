@@ -0,0 +1,134 @@
+// Copyright © Aptos Foundation
+// SPDX-License-Identifier: Apache-2.0
+
+use crate::metrics::DELAYED_BLOCKS;
+use aptos_logger::info;
+use std::{
+    collections::VecDeque,
+    sync::mpsc::{Receiver, RecvTimeoutError, SyncSender},
+    thread::JoinHandle,
+    time::{Duration, Instant},
+};
+
+/// Delays injected in front of pipeline stages, to model the consensus and quorum rounds a block
+/// goes through between stages on a validator.
+#[derive(Clone, Copy, Debug, Default)]
+pub struct StageDelays {
+    pub before_execution: Option<Duration>,
+    pub before_ledger_update: Option<Duration>,
+    pub before_commit: Option<Duration>,
+}
+
+/// Holds every block back for `delay` on its way to `stage`, without limiting how many blocks
+/// are held back at a time, the way consensus latency delays blocks without throttling them. The
+/// blocks keep their order. Only once the stage itself falls behind, does the upstream stage wait.
+///
+/// Returns the sender the upstream stage sends its blocks to, in place of `output`.
+pub(crate) fn spawn_delay_line<T: Send + 'static>(
+    stage: &'static str,
+    delay: Duration,
+    output: SyncSender<T>,
+    join_handles: &mut Vec<JoinHandle<()>>,
+) -> SyncSender<T> {
+    // The delay line takes the blocks right away, so a small bound is enough.
+    let (input, input_receiver) = std::sync::mpsc::sync_channel(1);
+    let handle = std::thread::Builder::new()
+        .name(format!("delay_{}", stage))
+        .spawn(move || run_delay_line(stage, delay, input_receiver, output))
+        .expect("Failed to spawn delay line thread.");
+    join_handles.push(handle);
+    input
+}
+
+fn run_delay_line<T>(
+    stage: &'static str,
+    delay: Duration,
+    input: Receiver<T>,
+    output: SyncSender<T>,
+) {
+    let gauge = DELAYED_BLOCKS.with_label_values(&[stage]);
+    let mut held_back: VecDeque<(Instant, T)> = VecDeque::new();
+    let mut input_open = true;
+    let mut num_blocks = 0;
+    let mut max_held_back = 0;
+    let mut total_late = Duration::ZERO;
+    while input_open || !held_back.is_empty() {
+        let received = match held_back.front() {
+            Some((due, _)) => {
+                match input.recv_timeout(due.saturating_duration_since(Instant::now())) {
+                    Ok(block) => Some(block),
+                    Err(RecvTimeoutError::Timeout) => None,
+                    Err(RecvTimeoutError::Disconnected) => {
+                        input_open = false;
+                        None
+                    },
+                }
+            },
+            None if input_open => match input.recv() {
+                Ok(block) => Some(block),
+                Err(_) => {
+                    input_open = false;
+                    None
+                },
+            },
+            None => None,
+        };
+        if let Some(block) = received {
+            held_back.push_back((Instant::now() + delay, block));
+            num_blocks += 1;
+            max_held_back = max_held_back.max(held_back.len());
+        }
+        // With the input closed, the remaining blocks are only waited for.
+        if !input_open {
+            if let Some((due, _)) = held_back.front() {
+                std::thread::sleep(due.saturating_duration_since(Instant::now()));
+            }
+        }
+        while held_back
+            .front()
+            .map_or(false, |(due, _)| *due <= Instant::now())
+        {
+            let (due, block) = held_back.pop_front().unwrap();
+            gauge.set(held_back.len() as i64);
+            // Blocks the delay line while the stage is behind.
+            if output.send(block).is_err() {
+                return;
+            }
+            total_late += due.elapsed();
+        }
+        gauge.set(held_back.len() as i64);
+    }
+    info!(
+        "Injected delay of {} ms before {}: {} blocks, up to {} held back at once, handed to the stage {} ms late on average",
+        delay.as_millis(),
+        stage,
+        num_blocks,
+        max_held_back,
+        total_late.as_millis() / (num_blocks as u128).max(1),
+    );
+}
+
+#[test]
+fn test_delay_line() {
+    let delay = Duration::from_millis(20);
+    let (output, output_receiver) = std::sync::mpsc::sync_channel(10);
+    let mut join_handles = vec![];
+    let input = spawn_delay_line("test", delay, output, &mut join_handles);
+    let sent_at = (0..5)
+        .map(|i| {
+            let sent_at = Instant::now();
+            input.send(i).unwrap();
+            sent_at
+        })
+        .collect::<Vec<_>>();
+    drop(input);
+
+    for (i, sent_at) in sent_at.into_iter().enumerate() {
+        assert_eq!(output_receiver.recv().unwrap(), i);
+        assert!(sent_at.elapsed() >= delay);
+    }
+    assert!(output_receiver.recv().is_err());
+    for handle in join_handles {
+        handle.join().unwrap();
+    }
+}
@@ -0,0 +1,219 @@
+// Copyright © Aptos Foundation
+// SPDX-License-Identifier: Apache-2.0
+
+use crate::{
+    metrics::STARVED_STAGES,
+    thread_utilization::{self, parse_stat, parse_stat_state, pool_name},
+};
+use aptos_logger::warn;
+use once_cell::sync::OnceCell;
+use std::{
+    collections::{BTreeMap, HashMap},
+    sync::{Arc, Mutex},
+    thread,
+    time::{Duration, Instant},
+};
+
+static DETECTOR: OnceCell<Arc<DetectorState>> = OnceCell::new();
+
+/// Thread pools (by thread name prefix, as truncated to 15 characters by the kernel) doing the
+/// work of each stage, next to the thread of the stage itself.
+fn stage_pools(stage: &str) -> &'static [&'static str] {
+    match stage {
+        "partitioning" => &["block_partition", "rayon-global"],
+        "execution" => &[
+            "txn_executor",
+            "par_exec",
+            "native_exe",
+            "sharded-executo",
+            "rayon-global",
+        ],
+        "ledger_update" => &["ledger_update", "rayon-global"],
+        "commit" => &["txn_committer", "rayon-global"],
+        _ => &[],
+    }
+}
+
+struct ActiveStageState {
+    /// Last time a thread of the stage or of its pools was seen running.
+    last_progress: Instant,
+    reported: bool,
+}
+
+#[derive(Default)]
+struct DetectorState {
+    active_stages: Mutex<HashMap<&'static str, ActiveStageState>>,
+}
+
+/// Starts watching the stages for starvation, i.e. a block being in a stage while the thread of
+/// the stage and all the workers of its pools are blocked (on I/O, locks or condition variables)
+/// for longer than `threshold`. Such stalls otherwise only show as unexplained TPS dips. Only the
+/// first call has an effect.
+pub fn start_once(threshold: Duration) {
+    if thread_utilization::read_thread_stats().is_none() {
+        warn!("Cannot read per-thread states on this platform, not detecting starvation.");
+        return;
+    }
+    let mut started = false;
+    let state = DETECTOR.get_or_init(|| {
+        started = true;
+        Arc::new(DetectorState::default())
+    });
+    if started {
+        let state = state.clone();
+        thread::Builder::new()
+            .name("starvation_detector".to_string())
+            .spawn(move || watch(&state, threshold))
+            .expect("Failed to spawn starvation detector thread.");
+    }
+}
+
+/// Marks a block as being in `stage`, until the returned guard is dropped. No-op if the detector
+/// isn't started.
+pub fn enter_stage(stage: &'static str) -> Option<ActiveStage> {
+    let state = DETECTOR.get()?;
+    state
+        .active_stages
+        .lock()
+        .unwrap()
+        .insert(stage, ActiveStageState {
+            last_progress: Instant::now(),
+            reported: false,
+        });
+    Some(ActiveStage { stage })
+}
+
+pub struct ActiveStage {
+    stage: &'static str,
+}
+
+impl Drop for ActiveStage {
+    fn drop(&mut self) {
+        if let Some(state) = DETECTOR.get() {
+            state.active_stages.lock().unwrap().remove(self.stage);
+        }
+    }
+}
+
+struct ThreadSample {
+    pool: String,
+    state: char,
+    cpu_ticks: u64,
+}
+
+fn read_thread_samples() -> HashMap<u64, ThreadSample> {
+    thread_utilization::read_thread_stats()
+        .unwrap_or_default()
+        .into_iter()
+        .filter_map(|(tid, stat)| {
+            let (name, cpu_ticks) = parse_stat(&stat)?;
+            Some((tid, ThreadSample {
+                pool: pool_name(&name),
+                state: parse_stat_state(&stat)?,
+                cpu_ticks,
+            }))
+        })
+        .collect()
+}
+
+/// Whether a thread of the given pools is running, or has used CPU since the previous sample.
+fn made_progress(
+    pools: &[&str],
+    samples: &HashMap<u64, ThreadSample>,
+    prev_samples: &HashMap<u64, ThreadSample>,
+) -> bool {
+    samples.iter().any(|(tid, sample)| {
+        pools.iter().any(|pool| sample.pool.starts_with(pool))
+            && (sample.state == 'R'
+                || prev_samples
+                    .get(tid)
+                    .map_or(true, |prev| prev.cpu_ticks != sample.cpu_ticks))
+    })
+}
+
+/// Number of threads of each of the given pools, and how many of them are waiting on I/O.
+fn describe_blocked_threads(pools: &[&str], samples: &HashMap<u64, ThreadSample>) -> String {
+    let mut by_pool: BTreeMap<&str, (usize, usize)> = BTreeMap::new();
+    for sample in samples.values() {
+        if pools.iter().any(|pool| sample.pool.starts_with(pool)) {
+            let entry = by_pool.entry(&sample.pool).or_default();
+            entry.0 += 1;
+            if sample.state == 'D' {
+                entry.1 += 1;
+            }
+        }
+    }
+    by_pool
+        .into_iter()
+        .map(|(pool, (num_threads, num_io))| {
+            format!(
+                "{}: {} threads, {} waiting on I/O",
+                pool, num_threads, num_io
+            )
+        })
+        .collect::<Vec<_>>()
+        .join("; ")
+}
+
+fn watch(state: &DetectorState, threshold: Duration) {
+    let poll_interval = (threshold / 4).clamp(Duration::from_millis(10), Duration::from_secs(1));
+    let mut prev_samples = read_thread_samples();
+    loop {
+        thread::sleep(poll_interval);
+        let samples = read_thread_samples();
+        let now = Instant::now();
+        for (stage, stage_state) in state.active_stages.lock().unwrap().iter_mut() {
+            let pools = stage_pools(stage);
+            if made_progress(pools, &samples, &prev_samples) {
+                stage_state.last_progress = now;
+                stage_state.reported = false;
+                continue;
+            }
+            let blocked_for = now.duration_since(stage_state.last_progress);
+            if blocked_for < threshold || stage_state.reported {
+                continue;
+            }
+            stage_state.reported = true;
+            STARVED_STAGES.with_label_values(&[stage]).inc();
+            warn!(
+                "Stage {} is starved: no thread of it or of its pools has run for {:.1} s, over the threshold of {:.1} s ({})",
+                stage,
+                blocked_for.as_secs_f64(),
+                threshold.as_secs_f64(),
+                describe_blocked_threads(pools, &samples),
+            );
+        }
+        prev_samples = samples;
+    }
+}
+
+#[test]
+fn test_made_progress() {
+    let sample = |pool: &str, state, cpu_ticks| ThreadSample {
+        pool: pool.to_string(),
+        state,
+        cpu_ticks,
+    };
+    let pools = stage_pools("ledger_update");
+    let prev_samples = HashMap::from([
+        (1, sample("ledger_update", 'S', 10)),
+        (2, sample("rayon-global", 'S', 20)),
+        (3, sample("par_exec", 'S', 30)),
+    ]);
+    let idle = HashMap::from([
+        (1, sample("ledger_update", 'S', 10)),
+        (2, sample("rayon-global", 'D', 20)),
+        (3, sample("par_exec", 'R', 31)),
+    ]);
+    assert!(!made_progress(pools, &idle, &prev_samples));
+    assert_eq!(
+        describe_blocked_threads(pools, &idle),
+        "ledger_update: 1 threads, 0 waiting on I/O; rayon-global: 1 threads, 1 waiting on I/O"
+    );
+
+    let busy = HashMap::from([
+        (1, sample("ledger_update", 'S', 10)),
+        (2, sample("rayon-global", 'S', 21)),
+    ]);
+    assert!(made_progress(pools, &busy, &prev_samples));
+}
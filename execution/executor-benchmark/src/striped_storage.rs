@@ -0,0 +1,111 @@
+// Copyright © Aptos Foundation
+// SPDX-License-Identifier: Apache-2.0
+
+use aptos_config::config::{DbPathConfig, ShardPathConfig, ShardedDbPathConfig};
+use aptos_logger::info;
+use itertools::Itertools;
+use std::{
+    fs,
+    io::Result,
+    path::{Path, PathBuf},
+};
+
+// Mirror the layout of sharded AptosDB.
+/// Number of storage shards, and so the most directories the DB can be striped across.
+pub const NUM_STATE_SHARDS: usize = 16;
+const SHARDED_DB_FOLDER_NAMES: [&str; 2] = ["state_kv_db", "state_merkle_db"];
+
+/// Assigns the storage shards round robin to the directories, the first one being the primary
+/// directory, which also keeps the ledger DB and the metadata DBs.
+fn assign_shards(num_dirs: usize) -> Vec<Vec<u8>> {
+    let mut shards_per_dir = vec![vec![]; num_dirs];
+    for shard_id in 0..NUM_STATE_SHARDS {
+        shards_per_dir[shard_id % num_dirs].push(shard_id as u8);
+    }
+    shards_per_dir
+}
+
+fn move_dir(from: &Path, to: &Path) -> Result<()> {
+    if to.exists() {
+        fs::remove_dir_all(to)?;
+    }
+    fs::create_dir_all(to.parent().expect("Shard path must have a parent."))?;
+    // Renaming fails across devices, which is the point of striping, so fall back to copying.
+    if fs::rename(from, to).is_ok() {
+        return Ok(());
+    }
+    copy_dir(from, to)?;
+    fs::remove_dir_all(from)
+}
+
+fn copy_dir(from: &Path, to: &Path) -> Result<()> {
+    fs::create_dir_all(to)?;
+    for entry in fs::read_dir(from)? {
+        let entry = entry?;
+        let target = to.join(entry.file_name());
+        if entry.file_type()?.is_dir() {
+            copy_dir(&entry.path(), &target)?;
+        } else {
+            fs::copy(entry.path(), target)?;
+        }
+    }
+    Ok(())
+}
+
+/// Spreads the storage shards of the sharded DB in `dirs[0]` across all `dirs` (e.g. one per
+/// NVMe device), and returns the path overrides to open it with.
+pub fn stripe_db(dirs: &[PathBuf]) -> DbPathConfig {
+    assert!(dirs.len() > 1, "Striping needs at least two directories.");
+    assert!(
+        dirs.len() <= NUM_STATE_SHARDS,
+        "Striping across more directories than the {} storage shards leaves some empty.",
+        NUM_STATE_SHARDS
+    );
+    let dirs = dirs
+        .iter()
+        .map(|dir| {
+            fs::create_dir_all(dir)
+                .and_then(|_| fs::canonicalize(dir))
+                .unwrap_or_else(|err| panic!("Invalid stripe directory {:?}: {}", dir, err))
+        })
+        .collect::<Vec<_>>();
+    let shards_per_dir = assign_shards(dirs.len());
+
+    for (dir, shards) in dirs.iter().zip(shards_per_dir.iter()).skip(1) {
+        for folder_name in SHARDED_DB_FOLDER_NAMES {
+            for shard_id in shards {
+                let shard_path = Path::new(folder_name).join(format!("shard_{}", shard_id));
+                move_dir(&dirs[0].join(&shard_path), &dir.join(&shard_path)).unwrap_or_else(
+                    |err| panic!("Failed to move {:?} to {:?}: {}", shard_path, dir, err),
+                );
+            }
+        }
+        info!("Striped storage shards {:?} to {:?}", shards, dir);
+    }
+
+    let sharded_db_path = ShardedDbPathConfig {
+        metadata_path: None,
+        shard_paths: dirs
+            .iter()
+            .zip(shards_per_dir)
+            .map(|(dir, shards)| ShardPathConfig {
+                shards: shards.iter().join(","),
+                path: dir.clone(),
+            })
+            .collect(),
+    };
+    DbPathConfig {
+        ledger_db_path: None,
+        state_kv_db_path: Some(sharded_db_path.clone()),
+        state_merkle_db_path: Some(sharded_db_path),
+    }
+}
+
+#[test]
+fn test_assign_shards() {
+    assert_eq!(assign_shards(1), vec![(0..16).collect::<Vec<u8>>()]);
+    let shards_per_dir = assign_shards(3);
+    assert_eq!(shards_per_dir[0], vec![0, 3, 6, 9, 12, 15]);
+    assert_eq!(shards_per_dir[1], vec![1, 4, 7, 10, 13]);
+    assert_eq!(shards_per_dir[2], vec![2, 5, 8, 11, 14]);
+}
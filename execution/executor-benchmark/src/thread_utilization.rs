@@ -0,0 +1,228 @@
+// Copyright © Aptos Foundation
+// SPDX-License-Identifier: Apache-2.0
+
+use aptos_logger::{info, warn};
+use serde::Serialize;
+use std::{
+    collections::{BTreeMap, HashMap},
+    fs,
+    io::{self, BufWriter, Write},
+    path::Path,
+    sync::{
+        atomic::{AtomicBool, Ordering},
+        Arc,
+    },
+    thread::JoinHandle,
+    time::{Duration, Instant},
+};
+
+/// Unit of the CPU times in /proc/<pid>/task/<tid>/stat (USER_HZ), fixed by the kernel ABI.
+pub(crate) const CLOCK_TICKS_PER_SEC: f64 = 100.0;
+
+/// How many threads of a pool were busy during one sampling interval.
+#[derive(Clone, Debug, Serialize)]
+pub struct UtilizationSample {
+    /// End of the sampling interval, relative to the start of the run.
+    pub elapsed_secs: f64,
+    pub pool: String,
+    pub num_threads: usize,
+    /// CPU time used by the pool during the interval, in units of the interval, i.e. the
+    /// average number of workers that were running.
+    pub busy_threads: f64,
+}
+
+/// Samples per-thread CPU time of the process in the background, and aggregates it by thread
+/// pool, so that serial stages and pools starved on locks show up as low utilization.
+pub struct ThreadUtilizationSampler {
+    stop: Arc<AtomicBool>,
+    handle: JoinHandle<Vec<UtilizationSample>>,
+}
+
+impl ThreadUtilizationSampler {
+    pub fn start(interval: Duration) -> Option<Self> {
+        if read_thread_cpu_ticks().is_none() {
+            warn!("Cannot read per-thread CPU times on this platform, not sampling thread utilization.");
+            return None;
+        }
+        let stop = Arc::new(AtomicBool::new(false));
+        let stop_clone = stop.clone();
+        let handle = std::thread::Builder::new()
+            .name("thread_utilization".to_string())
+            .spawn(move || sample_until_stopped(interval, &stop_clone))
+            .expect("Failed to spawn thread utilization sampler.");
+        Some(Self { stop, handle })
+    }
+
+    pub fn stop(self) -> Vec<UtilizationSample> {
+        self.stop.store(true, Ordering::Relaxed);
+        self.handle
+            .join()
+            .expect("Thread utilization sampler panicked.")
+    }
+}
+
+fn sample_until_stopped(interval: Duration, stop: &AtomicBool) -> Vec<UtilizationSample> {
+    let start = Instant::now();
+    let mut samples = Vec::new();
+    let mut prev_time = start;
+    let mut prev_ticks = read_thread_cpu_ticks().unwrap_or_default();
+    while !stop.load(Ordering::Relaxed) {
+        std::thread::sleep(interval);
+        let now = Instant::now();
+        let ticks = match read_thread_cpu_ticks() {
+            Some(ticks) => ticks,
+            None => continue,
+        };
+        let interval_secs = (now - prev_time).as_secs_f64();
+        let mut by_pool: BTreeMap<String, (usize, f64)> = BTreeMap::new();
+        for (tid, (name, cur)) in &ticks {
+            // Names are truncated to 15 characters.
+            if name.starts_with("thread_utiliza") {
+                continue;
+            }
+            // Threads that started during the interval were busy for all of their ticks.
+            let prev = prev_ticks.get(tid).map_or(0, |(_, prev)| *prev);
+            let entry = by_pool.entry(pool_name(name)).or_default();
+            entry.0 += 1;
+            entry.1 += cur.saturating_sub(prev) as f64 / CLOCK_TICKS_PER_SEC / interval_secs;
+        }
+        let elapsed_secs = (now - start).as_secs_f64();
+        samples.extend(
+            by_pool
+                .into_iter()
+                .map(|(pool, (num_threads, busy_threads))| UtilizationSample {
+                    elapsed_secs,
+                    pool,
+                    num_threads,
+                    busy_threads,
+                }),
+        );
+        prev_time = now;
+        prev_ticks = ticks;
+    }
+    samples
+}
+
+/// Returns, for each thread of the process, its name and its user + system CPU time in ticks.
+fn read_thread_cpu_ticks() -> Option<HashMap<u64, (String, u64)>> {
+    Some(
+        read_thread_stats()?
+            .into_iter()
+            .filter_map(|(tid, stat)| Some((tid, parse_stat(&stat)?)))
+            .collect(),
+    )
+}
+
+/// Returns the /proc stat line of each thread of the process, by thread id.
+pub(crate) fn read_thread_stats() -> Option<HashMap<u64, String>> {
+    read_thread_files("stat")
+}
+
+/// Returns the contents of the /proc file `file_name` of each thread of the process, by thread
+/// id, or `None` if the threads of the process can't be listed, e.g. not on Linux.
+pub(crate) fn read_thread_files(file_name: &str) -> Option<HashMap<u64, String>> {
+    let mut files = HashMap::new();
+    for entry in fs::read_dir("/proc/self/task").ok()? {
+        let entry = entry.ok()?;
+        let tid = match entry
+            .file_name()
+            .to_str()
+            .and_then(|s| s.parse::<u64>().ok())
+        {
+            Some(tid) => tid,
+            None => continue,
+        };
+        // Threads can exit between listing and reading, skip them.
+        if let Ok(contents) = fs::read_to_string(entry.path().join(file_name)) {
+            files.insert(tid, contents);
+        }
+    }
+    Some(files)
+}
+
+/// Parses the thread name, utime and stime out of a /proc stat line. The name is in parentheses
+/// and can itself contain spaces and parentheses, so fields are counted from the last ')'.
+pub(crate) fn parse_stat(stat: &str) -> Option<(String, u64)> {
+    let name_start = stat.find('(')?;
+    let name_end = stat.rfind(')')?;
+    let name = stat.get(name_start + 1..name_end)?.to_string();
+    let mut fields = stat.get(name_end + 1..)?.split_whitespace();
+    // utime and stime are fields 14 and 15 of the line, the first field after the name is 3.
+    let utime = fields.nth(11)?.parse::<u64>().ok()?;
+    let stime = fields.next()?.parse::<u64>().ok()?;
+    Some((name, utime + stime))
+}
+
+/// Parses the scheduling state (e.g. `R` running, `S` sleeping, `D` waiting on I/O) out of a
+/// /proc stat line, the first field after the name.
+pub(crate) fn parse_stat_state(stat: &str) -> Option<char> {
+    let name_end = stat.rfind(')')?;
+    stat.get(name_end + 1..)?
+        .split_whitespace()
+        .next()?
+        .chars()
+        .next()
+}
+
+/// Threads of a pool are named with a common prefix followed by their index.
+pub(crate) fn pool_name(thread_name: &str) -> String {
+    let trimmed =
+        thread_name.trim_end_matches(|c: char| c.is_ascii_digit() || c == '-' || c == '_');
+    if trimmed.is_empty() {
+        thread_name.to_string()
+    } else {
+        trimmed.to_string()
+    }
+}
+
+/// Logs the average and peak number of busy workers of each pool over the run.
+pub fn log_thread_utilization(samples: &[UtilizationSample]) {
+    let mut by_pool: BTreeMap<&str, Vec<&UtilizationSample>> = BTreeMap::new();
+    for sample in samples {
+        by_pool.entry(&sample.pool).or_default().push(sample);
+    }
+    for (pool, samples) in by_pool {
+        let avg_busy = samples.iter().map(|s| s.busy_threads).sum::<f64>() / samples.len() as f64;
+        let peak_busy = samples.iter().map(|s| s.busy_threads).fold(0.0, f64::max);
+        let max_threads = samples.iter().map(|s| s.num_threads).max().unwrap_or(0);
+        // Skip pools that were idle throughout, like the ones only used during setup.
+        if peak_busy > 0.0 {
+            info!(
+                "Thread utilization of {}: {:.2} busy of {} threads on average ({:.1}%), peak {:.2}",
+                pool,
+                avg_busy,
+                max_threads,
+                100.0 * avg_busy / max_threads.max(1) as f64,
+                peak_busy,
+            );
+        }
+    }
+}
+
+/// Writes the utilization timeline as CSV, one row per sampling interval and pool.
+pub fn write_thread_utilization_csv(path: &Path, samples: &[UtilizationSample]) -> io::Result<()> {
+    let mut file = BufWriter::new(fs::File::create(path)?);
+    writeln!(file, "elapsed_secs,pool,num_threads,busy_threads")?;
+    for sample in samples {
+        writeln!(
+            file,
+            "{:.3},{},{},{:.3}",
+            sample.elapsed_secs, sample.pool, sample.num_threads, sample.busy_threads
+        )?;
+    }
+    file.flush()
+}
+
+#[test]
+fn test_parse_stat_and_pool_name() {
+    let stat = "1234 (rayon-global-3) S 1 1234 1234 0 -1 4194368 100 0 0 0 250 40 0 0 20 0 48 0";
+    assert_eq!(parse_stat(stat), Some(("rayon-global-3".to_string(), 290)));
+    let stat = "1234 (a (weird) name) R 1 1234 1234 0 -1 4194368 100 0 0 0 7 3 0 0 20 0 48 0";
+    assert_eq!(parse_stat(stat), Some(("a (weird) name".to_string(), 10)));
+    assert_eq!(parse_stat_state(stat), Some('R'));
+
+    assert_eq!(pool_name("rayon-global-3"), "rayon-global");
+    assert_eq!(pool_name("exe_12"), "exe");
+    assert_eq!(pool_name("ledger_update"), "ledger_update");
+    assert_eq!(pool_name("42"), "42");
+}
@@ -2,7 +2,19 @@
 // Parts of the project are originally copyright © Meta Platforms, Inc.
 // SPDX-License-Identifier: Apache-2.0
 
-use crate::{metrics::NUM_TXNS, pipeline::CommitBlockMessage};
+use crate::{
+    backup_under_load::BackupUnderLoad,
+    block_retry::with_block_retries,
+    block_sidecar::BlockSidecarWriter,
+    block_snapshots::BlockSnapshotter,
+    metrics::{BLOCK_RETRIES, NUM_TXNS},
+    metrics_db::{BlockMetrics, MetricsDbRecorder},
+    output_exporter::ExportBlockMessage,
+    pipeline::CommitBlockMessage,
+    progress_events::{self, ProgressEvent},
+    shadow_verifier::ShadowVerifier,
+    starvation_detector,
+};
 use aptos_crypto::hash::HashValue;
 use aptos_db::metrics::API_LATENCY_SECONDS;
 use aptos_executor::{
@@ -51,6 +63,14 @@ pub struct TransactionCommitter<V> {
     executor: Arc<BlockExecutor<V>>,
     version: Version,
     block_receiver: mpsc::Receiver<CommitBlockMessage>,
+    export_sender: Option<mpsc::SyncSender<ExportBlockMessage>>,
+    post_commit_sender: Option<mpsc::Sender<ExportBlockMessage>>,
+    max_block_retries: usize,
+    sidecar_writer: Option<BlockSidecarWriter>,
+    snapshotter: Option<BlockSnapshotter>,
+    backup: Option<BackupUnderLoad>,
+    shadow_verifier: Option<ShadowVerifier>,
+    metrics_db_recorder: Option<MetricsDbRecorder>,
 }
 
 impl<V> TransactionCommitter<V>
@@ -61,11 +81,27 @@ where
         executor: Arc<BlockExecutor<V>>,
         version: Version,
         block_receiver: mpsc::Receiver<CommitBlockMessage>,
+        export_sender: Option<mpsc::SyncSender<ExportBlockMessage>>,
+        post_commit_sender: Option<mpsc::Sender<ExportBlockMessage>>,
+        max_block_retries: usize,
+        sidecar_writer: Option<BlockSidecarWriter>,
+        snapshotter: Option<BlockSnapshotter>,
+        backup: Option<BackupUnderLoad>,
+        shadow_verifier: Option<ShadowVerifier>,
+        metrics_db_recorder: Option<MetricsDbRecorder>,
     ) -> Self {
         Self {
             version,
             executor,
             block_receiver,
+            export_sender,
+            post_commit_sender,
+            max_block_retries,
+            sidecar_writer,
+            snapshotter,
+            backup,
+            shadow_verifier,
+            metrics_db_recorder,
         }
     }
 
@@ -73,6 +109,11 @@ where
         let start_version = self.version;
         info!("Start with version: {}", start_version);
 
+        let mut block_index = 0;
+        // Time spent taking snapshots of the DB, left out of the accumulative TPS.
+        let mut snapshot_time = Duration::ZERO;
+        // Time spent waiting for the output exporter to catch up.
+        let mut export_blocked_time = Duration::ZERO;
         while let Ok(msg) = self.block_receiver.recv() {
             let CommitBlockMessage {
                 block_id,
@@ -83,15 +124,71 @@ where
                 execution_time,
                 num_txns,
             } = msg;
+            let first_block_start_time = first_block_start_time + snapshot_time;
             NUM_TXNS
                 .with_label_values(&["commit"])
                 .inc_by(num_txns as u64);
             self.version += num_txns as u64;
             let commit_start = std::time::Instant::now();
             let ledger_info_with_sigs = gen_li_with_sigs(block_id, root_hash, self.version);
-            self.executor
-                .commit_blocks_ext(vec![block_id], ledger_info_with_sigs, false)
-                .unwrap();
+            let active_stage = starvation_detector::enter_stage("commit");
+            let commit_guard = self.backup.as_ref().map(BackupUnderLoad::lock_commit);
+            with_block_retries(
+                "commit",
+                block_id,
+                self.max_block_retries,
+                |attempt_number| {
+                    // A failed attempt may have committed the block nevertheless, don't commit
+                    // it twice.
+                    if attempt_number > 0 && self.is_committed(block_id) {
+                        BLOCK_RETRIES
+                            .with_label_values(&["commit_already_applied"])
+                            .inc();
+                        warn!("Block {} was already committed, not retrying.", block_id);
+                        return Ok(());
+                    }
+                    self.executor.commit_blocks_ext(
+                        vec![block_id],
+                        ledger_info_with_sigs.clone(),
+                        false,
+                    )
+                },
+            );
+            drop(commit_guard);
+            drop(active_stage);
+            let commit_time = Instant::now().duration_since(commit_start);
+            if let Some(sidecar_writer) = &mut self.sidecar_writer {
+                sidecar_writer.record_block(
+                    self.executor.db.reader.as_ref(),
+                    self.version + 1 - num_txns as u64,
+                    self.version,
+                    root_hash,
+                );
+            }
+            if let Some(backup) = &mut self.backup {
+                backup.record_block(&self.executor.db, num_txns);
+            }
+            if let Some(shadow_verifier) = &mut self.shadow_verifier {
+                shadow_verifier.record_block(&self.executor, self.version + 1 - num_txns as u64);
+            }
+            if let Some(export_sender) = &self.export_sender {
+                let export_start = Instant::now();
+                export_sender
+                    .send(ExportBlockMessage {
+                        first_version: self.version + 1 - num_txns as u64,
+                        num_txns,
+                    })
+                    .expect("Output exporter must be running.");
+                export_blocked_time += export_start.elapsed();
+            }
+            if let Some(post_commit_sender) = &self.post_commit_sender {
+                post_commit_sender
+                    .send(ExportBlockMessage {
+                        first_version: self.version + 1 - num_txns as u64,
+                        num_txns,
+                    })
+                    .expect("Post-commit plugins must be running.");
+            }
 
             report_block(
                 start_version,
@@ -100,10 +197,60 @@ where
                 current_block_start_time,
                 partition_time,
                 execution_time,
-                Instant::now().duration_since(commit_start),
+                commit_time,
                 num_txns,
             );
+            progress_events::emit(ProgressEvent::BlockCommitted {
+                block_index,
+                version: self.version,
+                num_txns,
+                latency_ms: current_block_start_time.elapsed().as_millis() as u64,
+                accumulative_tps: (self.version - start_version) as f64
+                    / first_block_start_time.elapsed().as_secs_f64(),
+            });
+            if let Some(metrics_db_recorder) = &self.metrics_db_recorder {
+                metrics_db_recorder.record_block(BlockMetrics {
+                    version: self.version,
+                    num_txns: num_txns as u64,
+                    latency_us: current_block_start_time.elapsed().as_micros() as u64,
+                    partition_us: partition_time.as_micros() as u64,
+                    execution_us: execution_time.as_micros() as u64,
+                    commit_us: commit_time.as_micros() as u64,
+                    elapsed_us: first_block_start_time.elapsed().as_micros() as u64,
+                });
+            }
+            // Taken once the block is reported, so that the snapshot is not part of its latency.
+            if let Some(snapshotter) = &mut self.snapshotter {
+                snapshot_time +=
+                    snapshotter.record_block(self.executor.db.writer.as_ref(), self.version);
+            }
+            block_index += 1;
+        }
+        if self.export_sender.is_some() {
+            info!(
+                "Commit stage was blocked on the output exporter for {:.3} s.",
+                export_blocked_time.as_secs_f64()
+            );
         }
+        if let Some(backup) = self.backup.take() {
+            backup.report();
+        }
+        if let Some(shadow_verifier) = self.shadow_verifier.take() {
+            shadow_verifier.finish();
+        }
+        if let Some(metrics_db_recorder) = self.metrics_db_recorder.take() {
+            metrics_db_recorder.finish();
+        }
+    }
+
+    fn is_committed(&self, block_id: HashValue) -> bool {
+        self.executor.committed_block_id() == block_id
+            || self
+                .executor
+                .db
+                .reader
+                .get_latest_version()
+                .map_or(false, |latest_version| latest_version >= self.version)
     }
 }
 
@@ -2,7 +2,9 @@
 // Parts of the project are originally copyright © Meta Platforms, Inc.
 // SPDX-License-Identifier: Apache-2.0
 
-use crate::pipeline::LedgerUpdateMessage;
+use crate::{
+    block_retry::with_block_retries, block_watchdog::BlockWatchdog, pipeline::LedgerUpdateMessage,
+};
 use aptos_crypto::hash::HashValue;
 use aptos_executor::block_executor::{BlockExecutor, TransactionBlockExecutor};
 use aptos_executor_types::BlockExecutorTrait;
@@ -19,6 +21,8 @@ pub struct TransactionExecutor<V> {
     parent_block_id: HashValue,
     maybe_first_block_start_time: Option<Instant>,
     ledger_update_sender: mpsc::SyncSender<LedgerUpdateMessage>,
+    max_block_retries: usize,
+    watchdog: Option<BlockWatchdog>,
 }
 
 impl<V> TransactionExecutor<V>
@@ -29,6 +33,8 @@ where
         executor: Arc<BlockExecutor<V>>,
         parent_block_id: HashValue,
         ledger_update_sender: mpsc::SyncSender<LedgerUpdateMessage>,
+        max_block_retries: usize,
+        watchdog: Option<BlockWatchdog>,
     ) -> Self {
         Self {
             num_blocks_processed: 0,
@@ -36,6 +42,8 @@ where
             parent_block_id,
             maybe_first_block_start_time: None,
             ledger_update_sender,
+            max_block_retries,
+            watchdog,
         }
     }
 
@@ -44,6 +52,7 @@ where
         current_block_start_time: Instant,
         partition_time: Duration,
         executable_block: ExecutableBlock,
+        retry_block: Option<ExecutableBlock>,
     ) {
         let execution_start_time = Instant::now();
         if self.maybe_first_block_start_time.is_none() {
@@ -55,10 +64,29 @@ where
             self.num_blocks_processed, block_id
         );
         let num_txns = executable_block.transactions.num_transactions();
-        let output = self
-            .executor
-            .execute_and_state_checkpoint(executable_block, self.parent_block_id, None)
-            .unwrap();
+        let mut maybe_block = Some(executable_block);
+        let watched_block = self
+            .watchdog
+            .as_ref()
+            .map(|watchdog| watchdog.watch_block(block_id));
+        let output = with_block_retries(
+            "execution",
+            block_id,
+            self.max_block_retries,
+            |_attempt_number| {
+                // The block is only copied to retry it, the retry copy itself is kept for the
+                // ledger update.
+                let block = match maybe_block.take() {
+                    Some(block) => block,
+                    None => retry_block
+                        .clone()
+                        .expect("Retried blocks must have a retry copy."),
+                };
+                self.executor
+                    .execute_and_state_checkpoint(block, self.parent_block_id, None)
+            },
+        );
+        drop(watched_block);
 
         assert_eq!(output.txn_statuses().len(), num_txns);
 
@@ -70,6 +98,7 @@ where
             block_id,
             parent_block_id: self.parent_block_id,
             state_checkpoint_output: output,
+            retry_block,
         };
         self.ledger_update_sender.send(msg).unwrap();
         self.parent_block_id = block_id;
@@ -4,8 +4,14 @@
 
 use crate::{
     account_generator::{AccountCache, AccountGenerator},
+    adaptive_block_size::AdaptiveBlockSize,
+    block_size_limiter::BlockSizeLimiter,
+    mempool_capture::{BlockCaptureWriter, MempoolCaptureReader, TimestampWindowBatcher},
     metrics::{NUM_TXNS, TIMER},
+    pipeline::PipelineConfig,
+    txns_per_sender::TxnsPerSenderPolicy,
 };
+use anyhow::{Context, Result};
 use aptos_crypto::{ed25519::Ed25519PrivateKey, HashValue};
 use aptos_logger::info;
 use aptos_sdk::{transaction_builder::TransactionFactory, types::LocalAccount};
@@ -19,9 +25,8 @@ use aptos_types::{
 use chrono::Local;
 use indicatif::{ProgressBar, ProgressStyle};
 use itertools::Itertools;
-#[cfg(test)]
-use rand::SeedableRng;
-use rand::{rngs::StdRng, seq::SliceRandom, thread_rng, Rng};
+use once_cell::sync::OnceCell;
+use rand::{rngs::StdRng, seq::SliceRandom, thread_rng, Rng, SeedableRng};
 use rayon::{
     iter::{IntoParallelRefIterator, ParallelIterator},
     ThreadPool, ThreadPoolBuilder,
@@ -36,13 +41,31 @@ use std::{
     io::{Read, Write},
     iter::once,
     path::Path,
-    sync::{mpsc, Arc},
+    sync::{mpsc, Arc, Mutex},
+    time::Duration,
 };
 use thread_local::ThreadLocal;
 
-const META_FILENAME: &str = "metadata.toml";
+pub(crate) const META_FILENAME: &str = "metadata.toml";
 pub const MAX_ACCOUNTS_INVOLVED_IN_P2P: usize = 1_000_000;
 
+static SEED: OnceCell<u64> = OnceCell::new();
+
+/// Seeds the random choices of the workload generator: the senders and receivers of the blocks,
+/// and how many transactions each sender sends.
+pub fn set_seed_once(seed: u64) {
+    SEED.set(seed).ok();
+}
+
+/// The seed of the workload generator, drawn at random (and logged) on first use if none was set.
+pub fn get_seed() -> u64 {
+    *SEED.get_or_init(|| {
+        let seed = thread_rng().gen();
+        info!("Seeding the workload generator with {}", seed);
+        seed
+    })
+}
+
 pub(crate) fn get_progress_bar(num_accounts: usize) -> ProgressBar {
     let bar = ProgressBar::new(num_accounts as u64);
     bar.set_style(ProgressStyle::default_bar().template(
@@ -108,6 +131,20 @@ pub struct TransactionGenerator {
     // TODO(grao): Use a different pool, and pin threads to dedicate cores to avoid affecting the
     // rest parts of benchmark.
     worker_pool: ThreadPool,
+
+    /// Decides the size of each generated block, and skips blocks when memory runs low.
+    block_size_limiter: BlockSizeLimiter,
+
+    /// Number of senders of the generated blocks, by the number of transactions they sent in
+    /// their block.
+    txns_per_sender_distribution: BTreeMap<usize, u64>,
+
+    /// Source of the random choices of the workload, seeded with `get_seed()`.
+    rng: StdRng,
+
+    /// If set, the user transactions of the generated blocks are also written to a capture, until
+    /// writing fails.
+    workload_recorder: Option<Mutex<Result<BlockCaptureWriter>>>,
 }
 
 impl TransactionGenerator {
@@ -183,9 +220,11 @@ impl TransactionGenerator {
         block_sender: mpsc::SyncSender<Vec<Transaction>>,
         db_dir: P,
         num_main_signer_accounts: Option<usize>,
-        num_workers: usize,
+        pipeline_config: &PipelineConfig,
     ) -> Self {
-        let num_existing_accounts = TransactionGenerator::read_meta(&db_dir);
+        let num_existing_accounts = TransactionGenerator::read_meta(&db_dir)
+            .unwrap_or_else(|err| panic!("Cannot read the accounts metadata: {:#}", err));
+        let num_workers = pipeline_config.num_generator_workers;
 
         Self {
             seed_accounts_cache: None,
@@ -207,6 +246,10 @@ impl TransactionGenerator {
                 .num_threads(num_workers)
                 .build()
                 .unwrap(),
+            block_size_limiter: BlockSizeLimiter::new(pipeline_config),
+            txns_per_sender_distribution: BTreeMap::new(),
+            rng: StdRng::seed_from_u64(get_seed()),
+            workload_recorder: None,
         }
     }
 
@@ -220,32 +263,105 @@ impl TransactionGenerator {
 
     // Write metadata
     pub fn write_meta<P: AsRef<Path>>(self, path: &P, num_new_accounts: usize) {
-        let metadata = TestCase::P2p(P2pTestCase {
-            num_accounts: self.num_existing_accounts + num_new_accounts,
-        });
+        Self::write_num_accounts(path, self.num_existing_accounts + num_new_accounts);
+    }
+
+    /// Writes the metadata of a DB with `num_accounts` user accounts.
+    pub fn write_num_accounts<P: AsRef<Path>>(path: &P, num_accounts: usize) {
+        let metadata = TestCase::P2p(P2pTestCase { num_accounts });
         let serialized = toml::ser::to_string(&metadata).unwrap();
         let meta_file = path.as_ref().join(META_FILENAME);
         let mut file = File::create(meta_file).unwrap();
         file.write_all(serialized.as_bytes()).unwrap();
     }
 
-    pub fn read_meta<P: AsRef<Path>>(path: &P) -> usize {
+    /// Reads the number of user accounts from the metadata of the DB in `path`, 0 if it has no
+    /// metadata.
+    pub fn read_meta<P: AsRef<Path>>(path: &P) -> Result<usize> {
         let filename = path.as_ref().join(META_FILENAME);
-        File::open(filename).map_or(0, |mut file| {
-            let mut contents = vec![];
-            file.read_to_end(&mut contents).unwrap();
-            let test_case: TestCase =
-                toml::from_str(&String::from_utf8(contents).expect("Must be UTF8"))
-                    .expect("Must exist.");
-            let TestCase::P2p(P2pTestCase { num_accounts }) = test_case;
-            num_accounts
-        })
+        let mut file = match File::open(&filename) {
+            Ok(file) => file,
+            Err(_) => return Ok(0),
+        };
+        let mut contents = String::new();
+        file.read_to_string(&mut contents)
+            .with_context(|| format!("Cannot read {:?}", filename))?;
+        let test_case: TestCase = toml::from_str(&contents)
+            .with_context(|| format!("Invalid DB metadata in {:?}", filename))?;
+        let TestCase::P2p(P2pTestCase { num_accounts }) = test_case;
+        Ok(num_accounts)
+    }
+
+    /// Number of the first `num_accounts` user accounts that exist in the DB, e.g. after it was
+    /// truncated. User accounts are created in the order they are generated in, so the ones that
+    /// exist are a prefix of them, found by bisecting.
+    pub fn count_existing_accounts(reader: Arc<dyn DbReader>, num_accounts: usize) -> usize {
+        // The accounts before `low` exist, the ones from `high` on don't.
+        let (mut low, mut high) = (0, num_accounts);
+        while low < high {
+            let mid = low + (high - low) / 2;
+            let address = AccountGenerator::new_for_user_accounts(mid as u64)
+                .generate()
+                .address();
+            let exists = reader
+                .latest_state_checkpoint_view()
+                .unwrap()
+                .as_account_with_state_view(&address)
+                .get_account_resource()
+                .unwrap()
+                .is_some();
+            if exists {
+                low = mid + 1;
+            } else {
+                high = mid;
+            }
+        }
+        low
     }
 
     pub fn num_existing_accounts(&self) -> usize {
         self.num_existing_accounts
     }
 
+    /// Number of blocks that were not generated because the memory guardrail was hit.
+    pub fn num_skipped_blocks(&self) -> usize {
+        self.block_size_limiter.num_skipped_blocks()
+    }
+
+    pub fn txns_per_sender_distribution(&self) -> &BTreeMap<usize, u64> {
+        &self.txns_per_sender_distribution
+    }
+
+    fn record_sender_counts(&mut self, sender_counts: &[usize]) {
+        for count in sender_counts {
+            *self.txns_per_sender_distribution.entry(*count).or_default() += 1;
+        }
+    }
+
+    /// Writes the user transactions of the blocks generated from now on to `path`, as a mempool
+    /// capture that `run_mempool_capture` replays the same blocks from.
+    pub fn record_workload(&mut self, path: &Path) -> Result<()> {
+        self.workload_recorder = Some(Mutex::new(Ok(BlockCaptureWriter::create(path)?)));
+        Ok(())
+    }
+
+    /// Finishes the recording of the workload, if any, or returns why it stopped.
+    pub fn finish_workload_recording(&mut self) -> Result<()> {
+        match self.workload_recorder.take() {
+            Some(workload_recorder) => workload_recorder
+                .into_inner()
+                .unwrap()?
+                .finish()
+                .context("Failed to finish the workload recording"),
+            None => Ok(()),
+        }
+    }
+
+    pub fn set_adaptive_block_size(&mut self, adaptive_block_size: Arc<AdaptiveBlockSize>) {
+        self.block_size_limiter
+            .set_adaptive_block_size(adaptive_block_size);
+    }
+
     pub fn run_mint(
         &mut self,
         reader: Arc<dyn DbReader>,
@@ -275,7 +391,7 @@ impl TransactionGenerator {
         &mut self,
         block_size: usize,
         num_transfer_blocks: usize,
-        transactions_per_sender: usize,
+        transactions_per_sender: &TxnsPerSenderPolicy,
         connected_tx_grps: usize,
         shuffle_connected_txns: bool,
         hotspot_probability: Option<f32>,
@@ -296,22 +412,36 @@ impl TransactionGenerator {
         block_size: usize,
         num_blocks: usize,
         transaction_generator_creator: Box<dyn TransactionGeneratorCreator>,
-        transactions_per_sender: usize,
+        transactions_per_sender: &TxnsPerSenderPolicy,
     ) {
         assert!(self.block_sender.is_some());
-        let num_senders_per_block =
-            (block_size + transactions_per_sender - 1) / transactions_per_sender;
         let account_pool_size = self.main_signer_accounts.as_ref().unwrap().accounts.len();
         let transaction_generator = ThreadLocal::with_capacity(self.num_workers);
-        for _ in 0..num_blocks {
-            let sender_indices = rand::seq::index::sample(
-                &mut thread_rng(),
-                account_pool_size,
-                num_senders_per_block,
-            )
-            .into_iter()
-            .flat_map(|sender_idx| vec![sender_idx; transactions_per_sender])
-            .collect();
+        for block_index in 0..num_blocks {
+            let min_per_sender = transactions_per_sender.min_per_sender(block_index, num_blocks);
+            let block_size = match self
+                .block_size_limiter
+                .next_block_size(block_size, account_pool_size * min_per_sender)
+            {
+                Some(block_size) => block_size,
+                None => continue,
+            };
+            // Every sender but the last sends at least `min_per_sender`, so the block needs no
+            // more senders than the pool has.
+            let sender_counts = transactions_per_sender.sender_counts(
+                block_index,
+                num_blocks,
+                block_size,
+                usize::MAX,
+                &mut self.rng,
+            );
+            self.record_sender_counts(&sender_counts);
+            let sender_indices =
+                rand::seq::index::sample(&mut self.rng, account_pool_size, sender_counts.len())
+                    .into_iter()
+                    .zip(sender_counts)
+                    .flat_map(|(sender_idx, count)| vec![sender_idx; count])
+                    .collect();
             self.generate_and_send_block(
                 self.main_signer_accounts.as_ref().unwrap(),
                 sender_indices,
@@ -336,6 +466,56 @@ impl TransactionGenerator {
         }
     }
 
+    /// Feeds the transactions of a mempool capture into the pipeline, in broadcast order, cut
+    /// into blocks of the transactions broadcast within `window` of each other, so that blocks
+    /// are as well (or badly) ordered as the ones consensus gets. Stops after `num_blocks`
+    /// blocks, or at the end of the capture.
+    pub fn run_mempool_capture(
+        &mut self,
+        capture_path: &Path,
+        window: Duration,
+        max_block_size: usize,
+        num_blocks: usize,
+    ) {
+        assert!(self.block_sender.is_some());
+        let reader = MempoolCaptureReader::open(capture_path).unwrap();
+        let captured_txns = reader.map(|captured| {
+            let captured = captured.expect("Failed to read mempool capture.");
+            (captured.timestamp_usecs, captured.txn)
+        });
+        let mut num_sent_blocks = 0;
+        let mut num_sent_txns = 0;
+        for block in
+            TimestampWindowBatcher::new(captured_txns, window, max_block_size).take(num_blocks)
+        {
+            num_sent_blocks += 1;
+            num_sent_txns += block.len();
+            let mut transactions = block
+                .into_iter()
+                .map(Transaction::UserTransaction)
+                .collect::<Vec<_>>();
+            transactions.push(Transaction::StateCheckpoint(HashValue::random()));
+            NUM_TXNS
+                .with_label_values(&["generation_done"])
+                .inc_by(transactions.len() as u64);
+            if let Some(sender) = &self.block_sender {
+                sender.send(transactions).unwrap();
+            }
+        }
+        if num_sent_blocks < num_blocks {
+            info!(
+                "Mempool capture {:?} ran out after {} of {} blocks.",
+                capture_path, num_sent_blocks, num_blocks
+            );
+        }
+        info!(
+            "Replayed {} captured transactions in {} blocks, {:.1} per block on average.",
+            num_sent_txns,
+            num_sent_blocks,
+            num_sent_txns as f64 / (num_sent_blocks as f64).max(1.0)
+        );
+    }
+
     pub fn create_seed_accounts(
         &mut self,
         reader: Arc<dyn DbReader>,
@@ -416,7 +596,7 @@ impl TransactionGenerator {
                     )
                 })
                 .collect();
-            self.generate_and_send_block(
+            let transactions = self.generate_block(
                 self.seed_accounts_cache.as_ref().unwrap(),
                 input,
                 |(sender_idx, new_account), account_cache| {
@@ -432,6 +612,9 @@ impl TransactionGenerator {
                 },
                 |(sender_idx, _)| *sender_idx,
             );
+            // Account creations are not part of the workload, keep them out of the transaction
+            // size estimate.
+            self.send_block(transactions);
             bar.inc(block_size as u64);
         }
         bar.finish();
@@ -443,11 +626,27 @@ impl TransactionGenerator {
         &mut self,
         block_size: usize,
         num_blocks: usize,
-        transactions_per_sender: usize,
+        transactions_per_sender: &TxnsPerSenderPolicy,
     ) {
-        for _ in 0..num_blocks {
-            let transfer_indices =
-                self.get_random_transfer_indices(block_size, transactions_per_sender);
+        let account_pool_size = self.main_signer_accounts.as_ref().unwrap().accounts.len();
+        for block_index in 0..num_blocks {
+            let block_size = match self
+                .block_size_limiter
+                .next_block_size(block_size, usize::MAX)
+            {
+                Some(block_size) => block_size,
+                None => continue,
+            };
+            // The receivers of a sender are distinct accounts other than the sender.
+            let sender_counts = transactions_per_sender.sender_counts(
+                block_index,
+                num_blocks,
+                block_size,
+                account_pool_size - 1,
+                &mut self.rng,
+            );
+            self.record_sender_counts(&sender_counts);
+            let transfer_indices = self.get_random_transfer_indices(&sender_counts);
             self.generate_and_send_transfer_block(
                 self.main_signer_accounts.as_ref().unwrap(),
                 transfer_indices,
@@ -455,19 +654,15 @@ impl TransactionGenerator {
         }
     }
 
-    fn get_random_transfer_indices(
-        &mut self,
-        block_size: usize,
-        transactions_per_sender: usize,
-    ) -> Vec<(usize, usize)> {
-        // TODO: handle when block_size isn't divisible by transactions_per_sender
-        (0..(block_size / transactions_per_sender))
-            .flat_map(|_| {
+    fn get_random_transfer_indices(&mut self, sender_counts: &[usize]) -> Vec<(usize, usize)> {
+        sender_counts
+            .iter()
+            .flat_map(|count| {
                 let (sender, receivers) = self
                     .main_signer_accounts
                     .as_mut()
                     .unwrap()
-                    .get_random_transfer_batch(transactions_per_sender);
+                    .get_random_transfer_batch(*count);
                 receivers
                     .into_iter()
                     .map(|receiver| (sender, receiver))
@@ -491,6 +686,13 @@ impl TransactionGenerator {
     ) {
         assert!((0.5..1.0).contains(&hotspot_probability));
         for _ in 0..num_blocks {
+            let block_size = match self
+                .block_size_limiter
+                .next_block_size(block_size, usize::MAX)
+            {
+                Some(block_size) => block_size,
+                None => continue,
+            };
             let transfer_indices =
                 self.get_random_with_hotspot_transfer_indices(block_size, hotspot_probability);
             self.generate_and_send_transfer_block(
@@ -508,12 +710,12 @@ impl TransactionGenerator {
         let num_accounts = self.main_signer_accounts.as_ref().unwrap().len();
         let num_hotspot_accounts =
             ((1.0 - hotspot_probability) * num_accounts as f32).ceil() as usize;
-        let mut rng = thread_rng();
+        let rng = &mut self.rng;
         (0..block_size)
             .map(|_| {
                 (
-                    rand_with_hotspot(&mut rng, num_accounts, num_hotspot_accounts),
-                    rand_with_hotspot(&mut rng, num_accounts, num_hotspot_accounts),
+                    rand_with_hotspot(rng, num_accounts, num_hotspot_accounts),
+                    rand_with_hotspot(rng, num_accounts, num_hotspot_accounts),
                 )
             })
             .collect()
@@ -593,6 +795,14 @@ impl TransactionGenerator {
         shuffle_connected_txns: bool,
     ) {
         for _ in 0..num_blocks {
+            let block_size = match self
+                .block_size_limiter
+                .next_block_size(block_size, usize::MAX)
+            {
+                // Every group needs at least one transaction.
+                Some(block_size) => block_size.max(connected_tx_grps),
+                None => continue,
+            };
             let num_signer_accounts = self.main_signer_accounts.as_ref().unwrap().accounts.len();
             let rng = &mut self.main_signer_accounts.as_mut().unwrap().rng;
             let transfer_indices: Vec<_> =
@@ -639,6 +849,23 @@ impl TransactionGenerator {
         T: Send,
         F: Fn(T, &AccountCache) -> Transaction + Send + Sync,
         S: Fn(&T) -> usize,
+    {
+        let transactions = self.generate_block(account_cache, inputs, func, sender_func);
+        self.block_size_limiter.record_block(&transactions);
+        self.send_block(transactions);
+    }
+
+    fn generate_block<T, F, S>(
+        &self,
+        account_cache: &AccountCache,
+        inputs: Vec<T>,
+        func: F,
+        sender_func: S,
+    ) -> Vec<Transaction>
+    where
+        T: Send,
+        F: Fn(T, &AccountCache) -> Transaction + Send + Sync,
+        S: Fn(&T) -> usize,
     {
         let _timer = TIMER.with_label_values(&["generate_block"]).start_timer();
         let block_size = inputs.len();
@@ -671,6 +898,25 @@ impl TransactionGenerator {
         }
 
         transactions.push(Transaction::StateCheckpoint(HashValue::random()));
+        transactions
+    }
+
+    fn send_block(&self, transactions: Vec<Transaction>) {
+        if let Some(workload_recorder) = &self.workload_recorder {
+            let mut workload_recorder = workload_recorder.lock().unwrap();
+            // Recording stops at the first failure, which the run reports once it's over.
+            let result = match &mut *workload_recorder {
+                Ok(writer) => writer.write_block(
+                    transactions
+                        .iter()
+                        .filter_map(Transaction::try_as_signed_user_txn),
+                ),
+                Err(_) => Ok(()),
+            };
+            if let Err(err) = result {
+                *workload_recorder = Err(err.context("Failed to record the workload"));
+            }
+        }
 
         NUM_TXNS
             .with_label_values(&["generation_done"])
@@ -685,7 +931,7 @@ impl TransactionGenerator {
         &mut self,
         block_size: usize,
         num_blocks: usize,
-        transactions_per_sender: usize,
+        transactions_per_sender: &TxnsPerSenderPolicy,
         connected_tx_grps: usize,
         shuffle_connected_txns: bool,
         hotspot_probability: Option<f32>,
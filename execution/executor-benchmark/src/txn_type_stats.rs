@@ -0,0 +1,169 @@
+// Copyright © Aptos Foundation
+// SPDX-License-Identifier: Apache-2.0
+
+use anyhow::Result;
+use aptos_logger::info;
+use aptos_storage_interface::{DbReader, MAX_REQUEST_LIMIT};
+use aptos_transaction_generator_lib::{TransactionType, TxnTypeTags};
+use aptos_types::transaction::{Transaction, TransactionInfo, Version};
+use serde::Serialize;
+use std::collections::BTreeMap;
+
+/// Throughput, gas and failures of the transactions of one type within a (mixed) run.
+#[derive(Debug, Default, Serialize)]
+pub struct TxnTypeStats {
+    pub num_txns: u64,
+    pub num_failed: u64,
+    /// Transactions generated, but never committed.
+    pub num_discarded: u64,
+    pub total_gas: u64,
+    /// Share of the overall throughput of the run.
+    pub tps: f64,
+    pub avg_gas: f64,
+    pub failure_rate: f64,
+    pub discard_rate: f64,
+}
+
+/// Labels of the types of `transaction_mix`, by index: the entry point for custom module calls
+/// and the transaction type otherwise, without their parameters.
+pub fn txn_type_labels(transaction_mix: &[(TransactionType, usize)]) -> Vec<String> {
+    let mut labels: Vec<String> = vec![];
+    for (type_index, (transaction_type, _)) in transaction_mix.iter().enumerate() {
+        let label = match transaction_type {
+            TransactionType::CallCustomModules { entry_point, .. } => format!("{:?}", entry_point),
+            transaction_type => format!("{:?}", transaction_type),
+        };
+        let mut label = label
+            .split(|c: char| !c.is_alphanumeric())
+            .next()
+            .unwrap_or_default()
+            .to_string();
+        // The same type with different parameters.
+        if labels.contains(&label) {
+            label = format!("{}#{}", label, type_index);
+        }
+        labels.push(label);
+    }
+    labels
+}
+
+fn record(
+    stats: &mut [TxnTypeStats],
+    tags: &TxnTypeTags,
+    txn: &Transaction,
+    info: &TransactionInfo,
+) {
+    let type_index = match txn
+        .try_as_signed_user_txn()
+        .and_then(|txn| tags.get(txn.sender(), txn.sequence_number()))
+    {
+        Some(type_index) => type_index,
+        // Not generated by the mix.
+        None => return,
+    };
+    let type_stats = &mut stats[type_index];
+    type_stats.num_txns += 1;
+    type_stats.total_gas += info.gas_used();
+    if !info.status().is_success() {
+        type_stats.num_failed += 1;
+    }
+}
+
+fn finalize(stats: &mut [TxnTypeStats], num_generated: &[u64], elapsed_secs: f64) {
+    for (type_stats, num_generated) in stats.iter_mut().zip(num_generated) {
+        type_stats.num_discarded = num_generated.saturating_sub(type_stats.num_txns);
+        let num_txns = type_stats.num_txns as f64;
+        type_stats.tps = num_txns / elapsed_secs;
+        type_stats.avg_gas = type_stats.total_gas as f64 / num_txns.max(1.0);
+        type_stats.failure_rate = type_stats.num_failed as f64 / num_txns.max(1.0);
+        type_stats.discard_rate =
+            type_stats.num_discarded as f64 / (*num_generated as f64).max(1.0);
+    }
+}
+
+/// Attributes the committed transactions in `(start_version, end_version]` to the types of the
+/// mix they were tagged with when generated, labeled with `labels`.
+///
+/// Committed transactions are read back from the DB after the run, so that attribution doesn't
+/// affect the measured pipeline. Discarded transactions are never committed, and are counted as
+/// the generated transactions of each type that were not.
+pub fn compute_txn_type_stats(
+    db: &dyn DbReader,
+    start_version: Version,
+    end_version: Version,
+    elapsed_secs: f64,
+    tags: &TxnTypeTags,
+    labels: &[String],
+) -> Result<BTreeMap<String, TxnTypeStats>> {
+    let mut stats = labels
+        .iter()
+        .map(|_| TxnTypeStats::default())
+        .collect::<Vec<_>>();
+    let mut version = start_version + 1;
+    while version <= end_version {
+        let limit = (end_version - version + 1).min(MAX_REQUEST_LIMIT);
+        let txns = db.get_transaction_iterator(version, limit)?;
+        let infos = db.get_transaction_info_iterator(version, limit)?;
+        for (txn, info) in txns.zip(infos) {
+            record(&mut stats, tags, &txn?, &info?);
+        }
+        version += limit;
+    }
+    finalize(&mut stats, &tags.num_generated(labels.len()), elapsed_secs);
+    Ok(labels.iter().cloned().zip(stats).collect())
+}
+
+pub fn log_txn_type_stats(stats: &BTreeMap<String, TxnTypeStats>) {
+    for (txn_type, type_stats) in stats {
+        info!(
+            "Per type {}: {} txns, TPS: {:.0} txn/s, avg gas: {:.1}, failure rate: {:.4}, discard rate: {:.4}",
+            txn_type,
+            type_stats.num_txns,
+            type_stats.tps,
+            type_stats.avg_gas,
+            type_stats.failure_rate,
+            type_stats.discard_rate
+        );
+    }
+}
+
+#[test]
+fn test_finalize() {
+    let mut stats = vec![
+        TxnTypeStats {
+            num_txns: 100,
+            num_failed: 5,
+            total_gas: 1000,
+            ..Default::default()
+        },
+        TxnTypeStats::default(),
+    ];
+    finalize(&mut stats, &[125, 10], 2.0);
+    assert_eq!(stats[0].tps, 50.0);
+    assert_eq!(stats[0].avg_gas, 10.0);
+    assert_eq!(stats[0].failure_rate, 0.05);
+    assert_eq!(stats[0].num_discarded, 25);
+    assert_eq!(stats[0].discard_rate, 0.2);
+    // All of its transactions were discarded.
+    assert_eq!(stats[1].num_discarded, 10);
+    assert_eq!(stats[1].discard_rate, 1.0);
+    assert_eq!(stats[1].failure_rate, 0.0);
+}
+
+#[test]
+fn test_txn_type_labels() {
+    use aptos_transaction_generator_lib::EntryPoints;
+
+    let call = |entry_point| TransactionType::CallCustomModules {
+        entry_point,
+        num_modules: 1,
+        use_account_pool: false,
+    };
+    let labels = txn_type_labels(&[
+        (TransactionType::default(), 1),
+        (call(EntryPoints::Nop), 1),
+        (call(EntryPoints::StepDst), 1),
+        (call(EntryPoints::Nop), 1),
+    ]);
+    assert_eq!(labels, ["CoinTransfer", "Nop", "StepDst", "Nop#3"]);
+}
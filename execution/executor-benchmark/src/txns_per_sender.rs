@@ -0,0 +1,179 @@
+// Copyright © Aptos Foundation
+// SPDX-License-Identifier: Apache-2.0
+
+use anyhow::{bail, ensure, Result};
+use aptos_logger::info;
+use rand::Rng;
+use std::{collections::BTreeMap, fmt, str::FromStr};
+
+/// How many transactions each sender of a block sends, for the workloads that pick the senders of
+/// a block at random. With a fixed number, every sender of every block sends the same number of
+/// transactions in a row, a sequence number locality that real blocks don't have. Parsed from:
+///
+/// * `5` or `fixed:5`: every sender sends 5;
+/// * `uniform:1-8`: every sender sends a number drawn uniformly from 1 to 8;
+/// * `geometric:2.5`: every sender sends a number drawn from a geometric distribution with mean
+///   2.5, i.e. most send one and a few send many, like on a real chain;
+/// * `ramp:1-16`: every sender of a block sends the same number, ramping linearly from 1 on the
+///   first block to 16 on the last.
+#[derive(Clone, Debug, PartialEq)]
+pub enum TxnsPerSenderPolicy {
+    Fixed(usize),
+    Uniform { min: usize, max: usize },
+    Geometric { mean: f64 },
+    Ramp { start: usize, end: usize },
+}
+
+impl TxnsPerSenderPolicy {
+    /// The fewest transactions a sender of the block with `block_index` sends, which bounds the
+    /// number of senders the block needs.
+    pub fn min_per_sender(&self, block_index: usize, num_blocks: usize) -> usize {
+        match self {
+            Self::Fixed(count) => *count,
+            Self::Uniform { min, .. } => *min,
+            Self::Geometric { .. } => 1,
+            Self::Ramp { start, end } => ramp(*start, *end, block_index, num_blocks),
+        }
+    }
+
+    /// Returns how many transactions each sender of a block of `block_size` transactions sends,
+    /// at most `max_per_sender` each. The last sender sends what is left, so that the counts add
+    /// up to `block_size`.
+    pub fn sender_counts(
+        &self,
+        block_index: usize,
+        num_blocks: usize,
+        block_size: usize,
+        max_per_sender: usize,
+        rng: &mut impl Rng,
+    ) -> Vec<usize> {
+        let mut counts = vec![];
+        let mut remaining = block_size;
+        while remaining > 0 {
+            let count = match self {
+                Self::Fixed(count) => *count,
+                Self::Uniform { min, max } => rng.gen_range(*min, *max + 1),
+                Self::Geometric { mean } => {
+                    // Inverse transform sampling of the number of trials until the first success.
+                    let p = 1.0 / mean;
+                    let u: f64 = rng.gen_range(f64::EPSILON, 1.0);
+                    1 + (u.ln() / (1.0 - p).ln()).floor() as usize
+                },
+                Self::Ramp { start, end } => ramp(*start, *end, block_index, num_blocks),
+            }
+            .clamp(1, max_per_sender.max(1))
+            .min(remaining);
+            counts.push(count);
+            remaining -= count;
+        }
+        counts
+    }
+}
+
+fn ramp(start: usize, end: usize, block_index: usize, num_blocks: usize) -> usize {
+    if num_blocks <= 1 {
+        return start;
+    }
+    let progress = block_index.min(num_blocks - 1) as f64 / (num_blocks - 1) as f64;
+    (start as f64 + (end as f64 - start as f64) * progress).round() as usize
+}
+
+impl FromStr for TxnsPerSenderPolicy {
+    type Err = anyhow::Error;
+
+    fn from_str(s: &str) -> Result<Self> {
+        let (kind, params) = s.split_once(':').unwrap_or(("fixed", s));
+        let range = |params: &str| -> Result<(usize, usize)> {
+            match params.split_once('-') {
+                Some((first, last)) => Ok((first.parse()?, last.parse()?)),
+                None => bail!("Expected a range like 1-8, got {}", params),
+            }
+        };
+        let policy = match kind {
+            "fixed" => Self::Fixed(params.parse()?),
+            "uniform" => {
+                let (min, max) = range(params)?;
+                ensure!(min <= max, "Empty range {}", params);
+                Self::Uniform { min, max }
+            },
+            "geometric" => {
+                let mean: f64 = params.parse()?;
+                ensure!(mean >= 1.0, "The mean has to be at least 1, got {}", mean);
+                Self::Geometric { mean }
+            },
+            "ramp" => {
+                let (start, end) = range(params)?;
+                Self::Ramp { start, end }
+            },
+            _ => bail!(
+                "Unknown policy {}, expected fixed, uniform, geometric or ramp",
+                kind
+            ),
+        };
+        ensure!(
+            policy.min_per_sender(0, 1) > 0 && policy.min_per_sender(1, 2) > 0,
+            "Every sender has to send at least one transaction"
+        );
+        Ok(policy)
+    }
+}
+
+impl fmt::Display for TxnsPerSenderPolicy {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match self {
+            Self::Fixed(count) => write!(f, "{}", count),
+            Self::Uniform { min, max } => write!(f, "uniform:{}-{}", min, max),
+            Self::Geometric { mean } => write!(f, "geometric:{}", mean),
+            Self::Ramp { start, end } => write!(f, "ramp:{}-{}", start, end),
+        }
+    }
+}
+
+/// Logs the number of senders by the number of transactions they sent in a block, over the run.
+pub fn log_txns_per_sender(distribution: &BTreeMap<usize, u64>) {
+    let num_senders = distribution.values().sum::<u64>();
+    if num_senders == 0 {
+        return;
+    }
+    let num_txns = distribution
+        .iter()
+        .map(|(count, senders)| *count as u64 * senders)
+        .sum::<u64>();
+    info!(
+        "Transactions per sender: mean {:.2} over {} senders, by count: {:?}",
+        num_txns as f64 / num_senders as f64,
+        num_senders,
+        distribution
+    );
+}
+
+#[test]
+fn test_txns_per_sender_policy() {
+    use rand::{rngs::StdRng, SeedableRng};
+
+    assert_eq!(
+        "5".parse::<TxnsPerSenderPolicy>().unwrap(),
+        TxnsPerSenderPolicy::Fixed(5)
+    );
+    assert_eq!(
+        "uniform:1-8".parse::<TxnsPerSenderPolicy>().unwrap(),
+        TxnsPerSenderPolicy::Uniform { min: 1, max: 8 }
+    );
+    assert!("0".parse::<TxnsPerSenderPolicy>().is_err());
+    assert!("uniform:8-1".parse::<TxnsPerSenderPolicy>().is_err());
+    assert!("ramp:0-4".parse::<TxnsPerSenderPolicy>().is_err());
+    assert!("geometric:0.5".parse::<TxnsPerSenderPolicy>().is_err());
+
+    let mut rng = StdRng::seed_from_u64(0);
+    for policy in ["3", "uniform:1-8", "geometric:2.5", "ramp:1-16"] {
+        let policy = policy.parse::<TxnsPerSenderPolicy>().unwrap();
+        for block_index in 0..10 {
+            let counts = policy.sender_counts(block_index, 10, 1000, 10, &mut rng);
+            assert_eq!(counts.iter().sum::<usize>(), 1000);
+            assert!(counts.iter().all(|count| (1..=10).contains(count)));
+        }
+    }
+    let ramp = TxnsPerSenderPolicy::Ramp { start: 1, end: 16 };
+    assert_eq!(ramp.sender_counts(0, 10, 4, 100, &mut rng), vec![1; 4]);
+    assert_eq!(ramp.sender_counts(9, 10, 32, 100, &mut rng), vec![16; 2]);
+}
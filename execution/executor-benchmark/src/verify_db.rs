@@ -0,0 +1,405 @@
+// Copyright © Aptos Foundation
+// SPDX-License-Identifier: Apache-2.0
+
+//! Verifies an existing benchmark DB without executing anything on it, so that archived DBs can
+//! be validated independently of the runs that produced them.
+
+use crate::{
+    account_generator::{AccountCache, AccountGenerator},
+    account_pool::load_saved_accounts,
+    db_access::{CoinStore, DbAccessUtil},
+    post_commit::aptos_coin_store_path,
+    transaction_generator::TransactionGenerator,
+};
+use anyhow::{anyhow, ensure, Context, Result};
+use aptos_config::config::{
+    RocksdbConfigs, StorageDirPaths, BUFFERED_STATE_TARGET_ITEMS,
+    DEFAULT_MAX_NUM_NODES_PER_LRU_CACHE_SHARD, NO_OP_STORAGE_PRUNER_CONFIG,
+};
+use aptos_crypto::{hash::CryptoHash, HashValue};
+use aptos_db::AptosDB;
+use aptos_logger::{info, warn};
+use aptos_sdk::types::LocalAccount;
+use aptos_state_view::account_with_state_view::AsAccountWithStateView;
+use aptos_storage_interface::{state_view::DbStateViewAtVersion, DbReader};
+use aptos_types::{
+    account_view::AccountView,
+    ledger_info::LedgerInfo,
+    state_store::{
+        state_key::{StateKey, StateKeyInner},
+        state_value::StateValue,
+    },
+    transaction::Version,
+};
+use rand::{thread_rng, Rng};
+use rayon::prelude::*;
+use std::{path::Path, sync::Arc};
+
+/// Number of state values read from the DB at a time, when scanning the state snapshot.
+const STATE_SCAN_CHUNK_SIZE: usize = 4000;
+
+/// What the scan of the latest state snapshot found, shared by the checks.
+struct StateScan {
+    num_items: usize,
+    total_balance: u128,
+    max_balance: u64,
+    /// State values picked uniformly at random, to spot-check their proofs.
+    samples: Vec<(StateKey, StateValue)>,
+}
+
+struct VerifyContext {
+    db: Arc<dyn DbReader>,
+    ledger_info: LedgerInfo,
+    snapshot_version: Version,
+    snapshot_root_hash: HashValue,
+}
+
+/// Opens the DB in `data_dir` read-only, without pruning it.
+pub(crate) fn open_readonly_db(
+    data_dir: &Path,
+    enable_storage_sharding: bool,
+) -> Result<Arc<dyn DbReader>> {
+    let rocksdb_configs = RocksdbConfigs {
+        enable_storage_sharding,
+        ..Default::default()
+    };
+    let db = AptosDB::open(
+        StorageDirPaths::from_path(data_dir),
+        true, /* readonly */
+        NO_OP_STORAGE_PRUNER_CONFIG,
+        rocksdb_configs,
+        false, /* indexer */
+        BUFFERED_STATE_TARGET_ITEMS,
+        DEFAULT_MAX_NUM_NODES_PER_LRU_CACHE_SHARD,
+    )
+    .with_context(|| format!("Cannot open the DB in {:?}", data_dir))?;
+    Ok(Arc::new(db))
+}
+
+/// Runs the sequence number check, the balance conservation check, proof spot-checks of
+/// `num_proof_samples` transactions and state values, and the storage audit on the DB in
+/// `data_dir`, which is opened read-only. Returns whether all checks passed.
+pub fn verify_db(data_dir: &Path, enable_storage_sharding: bool, num_proof_samples: usize) -> bool {
+    let context =
+        match open_readonly_db(data_dir, enable_storage_sharding).and_then(VerifyContext::new) {
+            Ok(context) => context,
+            Err(err) => {
+                warn!("Cannot verify the DB in {:?}: {:#}", data_dir, err);
+                return false;
+            },
+        };
+    info!(
+        "Verifying the DB in {:?} at version {}, state snapshot at version {}",
+        data_dir,
+        context.ledger_info.version(),
+        context.snapshot_version
+    );
+
+    let scan = match context.scan_state(num_proof_samples) {
+        Ok(scan) => scan,
+        Err(err) => {
+            warn!("Cannot scan the state snapshot: {}", err);
+            return false;
+        },
+    };
+    let results = [
+        ("storage audit", context.audit_storage(&scan)),
+        ("sequence numbers", context.check_sequence_numbers(data_dir)),
+        ("balance conservation", context.check_balances(&scan)),
+        (
+            "proof spot-checks",
+            context.spot_check_proofs(&scan, num_proof_samples),
+        ),
+    ];
+    let num_checks = results.len();
+    let mut num_failed = 0;
+    for (check, result) in results {
+        match result {
+            Ok(()) => info!("Check {}: passed", check),
+            Err(err) => {
+                num_failed += 1;
+                warn!("Check {}: FAILED: {}", check, err);
+            },
+        }
+    }
+    info!(
+        "{} of {} checks of the DB in {:?} passed",
+        num_checks - num_failed,
+        num_checks,
+        data_dir
+    );
+    num_failed == 0
+}
+
+impl VerifyContext {
+    fn new(db: Arc<dyn DbReader>) -> Result<Self> {
+        let ledger_info = db.get_latest_ledger_info()?.ledger_info().clone();
+        let (snapshot_version, snapshot_root_hash) = db
+            .get_state_snapshot_before(ledger_info.version() + 1)?
+            .ok_or_else(|| anyhow!("No state snapshot in the DB"))?;
+        Ok(Self {
+            db,
+            ledger_info,
+            snapshot_version,
+            snapshot_root_hash,
+        })
+    }
+
+    /// Reads the whole state snapshot chunk by chunk, summing up the coin balances and sampling
+    /// state values on the way.
+    fn scan_state(&self, num_samples: usize) -> Result<StateScan> {
+        let coin_store_path = aptos_coin_store_path();
+        let num_leaves = self.db.get_state_leaf_count(self.snapshot_version)?;
+        let mut rng = thread_rng();
+        let mut scan = StateScan {
+            num_items: 0,
+            total_balance: 0,
+            max_balance: 0,
+            samples: Vec::with_capacity(num_samples),
+        };
+        while scan.num_items < num_leaves {
+            let chunk = self.db.get_state_value_chunk_with_proof(
+                self.snapshot_version,
+                scan.num_items,
+                STATE_SCAN_CHUNK_SIZE,
+            )?;
+            ensure!(
+                chunk.root_hash == self.snapshot_root_hash,
+                "Chunk at index {} has root hash {}, expected {}",
+                scan.num_items,
+                chunk.root_hash,
+                self.snapshot_root_hash
+            );
+            ensure!(
+                chunk.first_index as usize == scan.num_items && !chunk.raw_values.is_empty(),
+                "Chunk at index {} starts at index {} with {} items",
+                scan.num_items,
+                chunk.first_index,
+                chunk.raw_values.len()
+            );
+            for (state_key, state_value) in chunk.raw_values {
+                if matches!(
+                    state_key.inner(),
+                    StateKeyInner::AccessPath(access_path) if access_path.path == coin_store_path
+                ) {
+                    let balance = bcs::from_bytes::<CoinStore>(state_value.bytes())?.coin;
+                    scan.total_balance += balance as u128;
+                    scan.max_balance = scan.max_balance.max(balance);
+                }
+                // Reservoir sampling.
+                scan.num_items += 1;
+                if scan.samples.len() < num_samples {
+                    scan.samples.push((state_key, state_value));
+                } else if num_samples > 0 {
+                    let index = rng.gen_range(0, scan.num_items);
+                    if index < num_samples {
+                        scan.samples[index] = (state_key, state_value);
+                    }
+                }
+            }
+        }
+        Ok(scan)
+    }
+
+    /// Checks that the ledger, the state merkle tree and the state KV DB agree with each other.
+    fn audit_storage(&self, scan: &StateScan) -> Result<()> {
+        let version = self.ledger_info.version();
+        let accumulator_root_hash = self.db.get_accumulator_root_hash(version)?;
+        ensure!(
+            accumulator_root_hash == self.ledger_info.transaction_accumulator_hash(),
+            "Transaction accumulator root hash {} at version {} doesn't match the ledger info ({})",
+            accumulator_root_hash,
+            version,
+            self.ledger_info.transaction_accumulator_hash()
+        );
+
+        let txn_with_proof =
+            self.db
+                .get_transaction_by_version(self.snapshot_version, version, false)?;
+        txn_with_proof
+            .proof
+            .verify(&self.ledger_info, self.snapshot_version)?;
+        let state_checkpoint_hash = txn_with_proof
+            .proof
+            .transaction_info()
+            .state_checkpoint_hash();
+        ensure!(
+            state_checkpoint_hash == Some(self.snapshot_root_hash),
+            "State snapshot root hash {} at version {} doesn't match the transaction info ({:?})",
+            self.snapshot_root_hash,
+            self.snapshot_version,
+            state_checkpoint_hash
+        );
+
+        let usage = self
+            .db
+            .get_state_storage_usage(Some(self.snapshot_version))?;
+        if !usage.is_untracked() {
+            ensure!(
+                usage.items() == scan.num_items,
+                "State storage usage counts {} items at version {}, but the state snapshot has {}",
+                usage.items(),
+                self.snapshot_version,
+                scan.num_items
+            );
+        }
+        info!(
+            "Storage audit: {} state items at version {}, {} transactions",
+            scan.num_items,
+            self.snapshot_version,
+            version + 1
+        );
+        Ok(())
+    }
+
+    /// Checks that the sequence number of every account of the DB matches the transactions it
+    /// sent: the transaction with the previous sequence number is committed, and none with the
+    /// sequence number itself.
+    fn check_sequence_numbers(&self, data_dir: &Path) -> Result<()> {
+        let num_accounts = TransactionGenerator::read_meta(&data_dir)?;
+        let mut accounts: Vec<LocalAccount> =
+            AccountCache::new(AccountGenerator::new_for_user_accounts(0), num_accounts)
+                .accounts
+                .into();
+        accounts.extend(load_saved_accounts(data_dir, self.db.clone())?);
+        // With the ledger pruned, the last transaction of an account may be gone.
+        let ledger_pruned = self.db.get_first_txn_version()?.map_or(false, |v| v > 0);
+        let state_view = self.db.state_view_at_version(Some(self.snapshot_version))?;
+
+        accounts.par_iter().try_for_each(|account| {
+            let address = account.address();
+            let sequence_number = state_view
+                .as_account_with_state_view(&address)
+                .get_account_resource()?
+                .ok_or_else(|| anyhow!("Account {} doesn't exist", address))?
+                .sequence_number();
+            ensure!(
+                self.db
+                    .get_account_transaction(
+                        address,
+                        sequence_number,
+                        false,
+                        self.snapshot_version
+                    )?
+                    .is_none(),
+                "Account {} has sequence number {}, but a transaction with it is committed",
+                address,
+                sequence_number
+            );
+            if sequence_number == 0 {
+                return Ok(());
+            }
+            match self.db.get_account_transaction(
+                address,
+                sequence_number - 1,
+                false,
+                self.snapshot_version,
+            )? {
+                Some(txn_with_proof) => txn_with_proof.verify_user_txn(
+                    &self.ledger_info,
+                    txn_with_proof.version,
+                    address,
+                    sequence_number - 1,
+                ),
+                None if ledger_pruned => Ok(()),
+                None => Err(anyhow!(
+                    "Account {} has sequence number {}, but no transaction with {} is committed",
+                    address,
+                    sequence_number,
+                    sequence_number - 1
+                )),
+            }
+        })?;
+        info!(
+            "Sequence numbers of {} accounts match their transactions",
+            accounts.len()
+        );
+        Ok(())
+    }
+
+    /// Checks that no coins were created out of thin air: the balances can't add up to more than
+    /// the total supply. They can add up to less, since coins are also held outside of coin
+    /// stores, e.g. staked by the validators.
+    fn check_balances(&self, scan: &StateScan) -> Result<()> {
+        let state_view = self.db.state_view_at_version(Some(self.snapshot_version))?;
+        let total_supply = DbAccessUtil::get_total_supply(&state_view)?
+            .ok_or_else(|| anyhow!("Total supply is not tracked"))?;
+        ensure!(
+            scan.max_balance as u128 <= total_supply,
+            "A balance of {} exceeds the total supply of {}, it went below zero",
+            scan.max_balance,
+            total_supply
+        );
+        ensure!(
+            scan.total_balance <= total_supply,
+            "Balances add up to {}, more than the total supply of {}",
+            scan.total_balance,
+            total_supply
+        );
+        info!(
+            "Balances add up to {} of the total supply of {}",
+            scan.total_balance, total_supply
+        );
+        Ok(())
+    }
+
+    /// Verifies the proofs of randomly picked transactions against the latest ledger info, and of
+    /// randomly picked state values against the state snapshot.
+    fn spot_check_proofs(&self, scan: &StateScan, num_samples: usize) -> Result<()> {
+        let version = self.ledger_info.version();
+        let first_version = self.db.get_first_txn_version()?.unwrap_or(0);
+        let mut rng = thread_rng();
+        for _ in 0..num_samples {
+            let txn_version = rng.gen_range(first_version, version + 1);
+            let txn_with_proof = self
+                .db
+                .get_transaction_by_version(txn_version, version, false)?;
+            let txn_hash = txn_with_proof.transaction.hash();
+            ensure!(
+                txn_hash == txn_with_proof.proof.transaction_info().transaction_hash(),
+                "Transaction at version {} has hash {}, but its transaction info {}",
+                txn_version,
+                txn_hash,
+                txn_with_proof.proof.transaction_info().transaction_hash()
+            );
+            txn_with_proof
+                .proof
+                .verify(&self.ledger_info, txn_version)?;
+        }
+
+        for (state_key, state_value) in &scan.samples {
+            let (value, proof) = self
+                .db
+                .get_state_value_with_proof_by_version(state_key, self.snapshot_version)?;
+            ensure!(
+                value.as_ref() == Some(state_value),
+                "State value of {:?} differs from the one in the state snapshot",
+                state_key
+            );
+            proof.verify(self.snapshot_root_hash, state_key.hash(), value.as_ref())?;
+        }
+        info!(
+            "Proofs of {} transactions and {} state values verified",
+            num_samples,
+            scan.samples.len()
+        );
+        Ok(())
+    }
+}
+
+#[test]
+fn test_verify_db() {
+    use crate::{create_temp_db, transaction_generator::META_FILENAME};
+    use aptos_vm::AptosVM;
+
+    let db_dir = create_temp_db::<AptosVM>(10, 100_000_000, 5);
+    assert!(verify_db(db_dir.path(), false, 4));
+
+    // Neither a missing DB nor invalid metadata panics.
+    let empty_dir = aptos_temppath::TempPath::new();
+    empty_dir.create_as_dir().unwrap();
+    assert!(!verify_db(empty_dir.path(), false, 4));
+    std::fs::write(db_dir.path().join(META_FILENAME), "not toml").unwrap();
+    assert!(TransactionGenerator::read_meta(&db_dir.path()).is_err());
+    assert!(!verify_db(db_dir.path(), false, 4));
+}
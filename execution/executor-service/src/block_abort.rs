@@ -0,0 +1,171 @@
+// Copyright © Aptos Foundation
+// SPDX-License-Identifier: Apache-2.0
+
+//! Aborting the blocks a shard rejects, so that the other shards don't wait for it forever.
+//!
+//! A shard that rejects a block (e.g. over its memory budget, or because it cannot decode the
+//! command) doesn't execute any of it, and so never sends the cross-shard messages the other
+//! shards wait for. Shards of protocol version `REJECTION_PROTOCOL_VERSION` and later answer such
+//! blocks with a rejected result. As soon as one arrives, the coordinator sends the other shards
+//! the writes of the rejected shard as aborted (without a value), the way an aborted transaction
+//! would, so that they finish the block, and then fails the whole block.
+//!
+//! The coordinator fails a shard the same way when it cannot relay a cross-shard message to it,
+//! since the shard would wait for the message forever.
+
+use crate::{
+    cross_shard_relay::CrossShardSenders,
+    versioning::{self, REJECTED_RESULT_VARIANT},
+    RemoteExecutionResult,
+};
+use aptos_logger::warn;
+use aptos_secure_net::network_controller::Message;
+use aptos_types::{
+    block_executor::partitioner::{RoundId, ShardId, SubBlocksForShard, GLOBAL_ROUND_ID},
+    state_store::state_key::StateKey,
+    transaction::analyzed_transaction::AnalyzedTransaction,
+    vm_status::{StatusCode, VMStatus},
+};
+use aptos_vm::sharded_block_executor::messages::{CrossShardMsg, RemoteTxnWrite};
+use crossbeam_channel::{Receiver, Sender};
+use std::{
+    collections::HashSet,
+    sync::{Arc, Mutex},
+    thread,
+};
+
+/// The cross-shard writes of the command of a shard, as (shard, round) they are sent to and the
+/// state key they write.
+pub(crate) type AbortedWrites = Vec<(ShardId, RoundId, StateKey)>;
+
+pub(crate) fn aborted_writes(sub_blocks: &SubBlocksForShard<AnalyzedTransaction>) -> AbortedWrites {
+    let mut writes = HashSet::new();
+    for txn in sub_blocks.iter() {
+        for (dependent, storage_locations) in txn.cross_shard_dependencies.dependent_edges().iter()
+        {
+            // Global transactions are not supported by remote execution.
+            if dependent.round_id == GLOBAL_ROUND_ID {
+                continue;
+            }
+            for storage_location in storage_locations {
+                writes.insert((
+                    dependent.shard_id,
+                    dependent.round_id,
+                    storage_location.clone().into_state_key(),
+                ));
+            }
+        }
+    }
+    writes.into_iter().collect()
+}
+
+/// The cross-shard writes of the commands of the block in flight, to abort the ones of the shards
+/// that reject it.
+pub(crate) struct BlockAborts {
+    message_txs: CrossShardSenders,
+    writes: Vec<Mutex<Option<AbortedWrites>>>,
+    /// Where the results of every shard are forwarded to, once `watch_results` is called.
+    result_txs: Vec<Mutex<Option<Sender<Message>>>>,
+}
+
+impl BlockAborts {
+    pub(crate) fn new(message_txs: CrossShardSenders) -> Self {
+        let writes = (0..message_txs.len()).map(|_| Mutex::new(None)).collect();
+        let result_txs = (0..message_txs.len()).map(|_| Mutex::new(None)).collect();
+        Self {
+            message_txs,
+            writes,
+            result_txs,
+        }
+    }
+
+    /// Senders of the cross-shard messages of every round to every shard.
+    pub(crate) fn message_txs(&self) -> &CrossShardSenders {
+        &self.message_txs
+    }
+
+    /// Fails the block in flight on `shard_id` as if the shard rejected it: the other shards get
+    /// its writes as aborted, and the coordinator gets a rejected result of the shard.
+    pub(crate) fn fail(&self, shard_id: ShardId, reason: String) {
+        warn!("Failing the block on shard {}: {}", shard_id, reason);
+        self.abort(shard_id);
+        let result = RemoteExecutionResult::rejected(VMStatus::error(
+            StatusCode::UNKNOWN_STATUS,
+            Some(reason),
+        ));
+        if let Some(result_tx) = &*self.result_txs[shard_id].lock().unwrap() {
+            result_tx
+                .send(Message::new(versioning::encode(&result).unwrap()))
+                .ok();
+        }
+    }
+
+    /// Sets the writes of the command of `shard_id`, before it is dispatched.
+    pub(crate) fn set(&self, shard_id: ShardId, writes: AbortedWrites) {
+        *self.writes[shard_id].lock().unwrap() = Some(writes);
+    }
+
+    /// Forgets the writes of the block, once all of its results arrived.
+    pub(crate) fn clear(&self) {
+        for writes in &self.writes {
+            writes.lock().unwrap().take();
+        }
+    }
+
+    fn abort(&self, shard_id: ShardId) {
+        let writes = match self.writes[shard_id].lock().unwrap().take() {
+            Some(writes) => writes,
+            None => return,
+        };
+        warn!(
+            "Shard {} rejected the block, aborting its {} cross-shard writes",
+            shard_id,
+            writes.len()
+        );
+        for (dst_shard, round, state_key) in writes {
+            let msg = CrossShardMsg::RemoteTxnWriteMsg(RemoteTxnWrite::new(state_key, None));
+            let bytes = bcs::to_bytes(&msg).unwrap();
+            if self.message_txs[dst_shard][round]
+                .lock()
+                .unwrap()
+                .send(Message::new(bytes))
+                .is_err()
+            {
+                warn!(
+                    "Failed to abort cross-shard write of shard {} to shard {}, the network is shut down",
+                    shard_id, dst_shard
+                );
+                return;
+            }
+        }
+    }
+
+    /// Forwards the results of `shard_id` from `result_rx`, aborting the writes of the block as
+    /// soon as a rejected result arrives, rather than when the coordinator gets to it.
+    pub(crate) fn watch_results(
+        self: &Arc<Self>,
+        shard_id: ShardId,
+        result_rx: Receiver<Message>,
+    ) -> Receiver<Message> {
+        let (forward_tx, forward_rx) = crossbeam_channel::unbounded();
+        *self.result_txs[shard_id].lock().unwrap() = Some(forward_tx.clone());
+        let block_aborts = self.clone();
+        thread::Builder::new()
+            .name(format!("block-abort-{}", shard_id))
+            // Exits once the network controller drops the inbound channel.
+            .spawn(move || {
+                while let Ok(message) = result_rx.recv() {
+                    if versioning::envelope_header(&message.data)
+                        .map_or(false, |(_, variant)| variant == REJECTED_RESULT_VARIANT)
+                    {
+                        block_aborts.abort(shard_id);
+                    }
+                    if forward_tx.send(message).is_err() {
+                        break;
+                    }
+                }
+            })
+            .expect("Failed to spawn block abort thread");
+        forward_rx
+    }
+}
@@ -0,0 +1,404 @@
+// Copyright © Aptos Foundation
+// SPDX-License-Identifier: Apache-2.0
+
+//! Supported features and limits of the executor shards.
+//!
+//! The coordinator sends a `GetCapabilities` request to every shard when it starts, without
+//! waiting for the answers, and checks every block against them before dispatching it, so that a
+//! block a shard cannot take (e.g. a command above the message size limit) fails up front with a
+//! clear reason, instead of half way through the dispatch or on the shard.
+//!
+//! Requests and answers go over a pair of channels of their own, so that they are answered while
+//! the shard is busy executing a block. A shard that hasn't answered yet (e.g. it started after
+//! the coordinator) is asked again while a block waits for it, and blocks are not dispatched
+//! until every shard answered. Shards that predate capabilities drop the request, so they are
+//! never dispatched to.
+
+use crate::{
+    error::Error,
+    resource_limits::ResourceLimits,
+    versioning::{self, Decoded, VersionedMessage},
+    wire_trace::{self, Direction, WireMessage},
+};
+use aptos_infallible::Mutex;
+use aptos_logger::{info, warn};
+use aptos_secure_net::{
+    grpc_network_service::MAX_MESSAGE_SIZE,
+    network_controller::{Message, NetworkController},
+};
+use aptos_types::block_executor::partitioner::ShardId;
+use crossbeam_channel::{Receiver, RecvTimeoutError, Sender};
+use once_cell::sync::OnceCell;
+use serde::{Deserialize, Serialize};
+use std::{
+    net::SocketAddr,
+    thread,
+    time::{Duration, Instant},
+};
+
+/// Transactions executed by the Move VM.
+pub const TRANSACTION_TYPE_VM: &str = "vm";
+/// Transactions executed natively, without the VM.
+pub const TRANSACTION_TYPE_NATIVE: &str = "native";
+
+pub const FEATURE_HEARTBEAT: &str = "heartbeat";
+pub const FEATURE_SPECULATIVE_CROSS_SHARD_PREFETCH: &str = "speculative_cross_shard_prefetch";
+pub const FEATURE_MEMORY_BUDGET: &str = "memory_budget";
+/// The shard reassembles requests sent in fragments, see `fragmentation`.
+pub const FEATURE_FRAGMENTATION: &str = "fragmentation";
+
+/// How long a block waits for the shards that didn't report their capabilities yet, before it is
+/// failed.
+pub const DEFAULT_QUERY_TIMEOUT: Duration = Duration::from_secs(10);
+/// How often a shard that didn't report its capabilities is asked again.
+const QUERY_RETRY_INTERVAL: Duration = Duration::from_secs(1);
+
+fn request_message_type(shard_id: ShardId) -> String {
+    format!("get_capabilities_{}", shard_id)
+}
+
+fn response_message_type(shard_id: ShardId) -> String {
+    format!("capabilities_{}", shard_id)
+}
+
+#[derive(Clone, Debug, Deserialize, Serialize)]
+pub struct GetCapabilities;
+
+impl VersionedMessage for GetCapabilities {
+    fn variant(&self) -> u32 {
+        0
+    }
+
+    fn encode_payload(&self) -> Result<Vec<u8>, Error> {
+        Ok(bcs::to_bytes(self)?)
+    }
+
+    fn decode_payload(variant: u32, payload: &[u8]) -> Option<Result<Self, Error>> {
+        match variant {
+            0 => Some(bcs::from_bytes(payload).map_err(Error::from)),
+            _ => None,
+        }
+    }
+}
+
+/// Answer of a shard to `GetCapabilities`. Transaction types and features are strings rather
+/// than enums, so that the ones added by newer shards don't fail decoding on older coordinators.
+#[derive(Clone, Debug, Deserialize, Serialize)]
+pub struct ServiceCapabilities {
+    pub shard_id: ShardId,
+    pub num_shards: usize,
+    pub protocol_version: u32,
+    pub min_compatible_protocol_version: u32,
+    /// Largest message the shard can receive, in bytes.
+    pub max_payload_bytes: u64,
+    pub transaction_types: Vec<String>,
+    pub protocol_features: Vec<String>,
+    /// Number of threads the shard executes blocks with.
+    pub num_execution_threads: usize,
+    /// Upper bound on the number of threads of a block, regardless of the requested concurrency.
+    pub max_execution_threads: Option<usize>,
+}
+
+impl ServiceCapabilities {
+    pub fn new(
+        shard_id: ShardId,
+        num_shards: usize,
+        num_execution_threads: usize,
+        resource_limits: &ResourceLimits,
+        transaction_types: Vec<String>,
+        speculative_cross_shard_prefetch: bool,
+        heartbeat_interval: Option<Duration>,
+    ) -> Self {
+        let mut protocol_features = vec![FEATURE_FRAGMENTATION.to_string()];
+        if heartbeat_interval.is_some() {
+            protocol_features.push(FEATURE_HEARTBEAT.to_string());
+        }
+        if speculative_cross_shard_prefetch {
+            protocol_features.push(FEATURE_SPECULATIVE_CROSS_SHARD_PREFETCH.to_string());
+        }
+        if resource_limits.max_memory_bytes.is_some() {
+            protocol_features.push(FEATURE_MEMORY_BUDGET.to_string());
+        }
+        Self {
+            shard_id,
+            num_shards,
+            protocol_version: versioning::PROTOCOL_VERSION,
+            min_compatible_protocol_version: versioning::MIN_COMPATIBLE_PROTOCOL_VERSION,
+            max_payload_bytes: MAX_MESSAGE_SIZE as u64,
+            transaction_types,
+            protocol_features,
+            num_execution_threads,
+            max_execution_threads: resource_limits.max_execution_threads,
+        }
+    }
+
+    pub fn supports_transaction_type(&self, transaction_type: &str) -> bool {
+        self.transaction_types.iter().any(|t| t == transaction_type)
+    }
+
+    pub fn has_feature(&self, feature: &str) -> bool {
+        self.protocol_features.iter().any(|f| f == feature)
+    }
+
+    /// Checks whether the shard can take a command of `command_bytes` serialized bytes, with
+    /// transactions of `transaction_type`, of a coordinator with `num_shards` shards. Commands
+    /// above the max payload of the shard are fine if it reassembles fragments.
+    pub fn validate_command(
+        &self,
+        num_shards: usize,
+        command_bytes: usize,
+        transaction_type: &str,
+    ) -> Result<(), String> {
+        if self.num_shards != num_shards {
+            return Err(format!(
+                "Shard {} is configured with {} shards, but the coordinator has {}",
+                self.shard_id, self.num_shards, num_shards
+            ));
+        }
+        if command_bytes as u64 > self.max_payload_bytes && !self.has_feature(FEATURE_FRAGMENTATION)
+        {
+            return Err(format!(
+                "Command of {} bytes exceeds the max payload of {} bytes of shard {}, which doesn't reassemble fragments",
+                command_bytes, self.max_payload_bytes, self.shard_id
+            ));
+        }
+        if !self.supports_transaction_type(transaction_type) {
+            return Err(format!(
+                "Shard {} doesn't support {} transactions (supported: {:?})",
+                self.shard_id, transaction_type, self.transaction_types
+            ));
+        }
+        Ok(())
+    }
+}
+
+impl VersionedMessage for ServiceCapabilities {
+    fn variant(&self) -> u32 {
+        0
+    }
+
+    fn encode_payload(&self) -> Result<Vec<u8>, Error> {
+        Ok(bcs::to_bytes(self)?)
+    }
+
+    fn decode_payload(variant: u32, payload: &[u8]) -> Option<Result<Self, Error>> {
+        match variant {
+            0 => Some(bcs::from_bytes(payload).map_err(Error::from)),
+            _ => None,
+        }
+    }
+}
+
+/// Answers the `GetCapabilities` requests of the coordinator, for as long as the process lives.
+pub fn start_capabilities_responder(
+    controller: &mut NetworkController,
+    coordinator_address: SocketAddr,
+    capabilities: ServiceCapabilities,
+) {
+    let shard_id = capabilities.shard_id;
+    let request_rx = controller.create_inbound_channel(request_message_type(shard_id));
+    let response_tx =
+        controller.create_outbound_channel(coordinator_address, response_message_type(shard_id));
+    let response = versioning::encode(&capabilities).expect("Capabilities must serialize.");
+    thread::Builder::new()
+        .name(format!("capabilities-{}", shard_id))
+        .spawn(move || {
+            while let Ok(message) = request_rx.recv() {
+                wire_trace::trace(
+                    Direction::Receive,
+                    WireMessage::GetCapabilities,
+                    shard_id,
+                    &message.data,
+                );
+                // There is nothing to tell from the request, apart from the coordinator asking.
+                if let Ok(Decoded::Unknown { version, variant }) =
+                    versioning::decode::<GetCapabilities>(&message.data)
+                {
+                    warn!(
+                        "Shard {} answering capabilities request variant {} of protocol version {} as of its own version",
+                        shard_id, variant, version
+                    );
+                }
+                wire_trace::trace(
+                    Direction::Send,
+                    WireMessage::Capabilities,
+                    shard_id,
+                    &response,
+                );
+                if response_tx.send(Message::new(response.clone())).is_err() {
+                    // The network controller was shut down.
+                    break;
+                }
+            }
+        })
+        .expect("Failed to spawn capabilities responder thread.");
+}
+
+/// Coordinator side channels to query the capabilities of the shards, and the capabilities
+/// they reported.
+pub struct CapabilitiesClient {
+    request_txs: Vec<Sender<Message>>,
+    response_rxs: Vec<Receiver<Message>>,
+    capabilities: Vec<OnceCell<ServiceCapabilities>>,
+    // When each shard was last asked for its capabilities.
+    last_requested: Vec<Mutex<Option<Instant>>>,
+}
+
+impl CapabilitiesClient {
+    /// Creates the channels, has to be called before the network controller is started.
+    pub fn new(controller: &mut NetworkController, remote_shard_addresses: &[SocketAddr]) -> Self {
+        let (request_txs, response_rxs) = remote_shard_addresses
+            .iter()
+            .enumerate()
+            .map(|(shard_id, address)| {
+                (
+                    controller.create_outbound_channel(*address, request_message_type(shard_id)),
+                    controller.create_inbound_channel(response_message_type(shard_id)),
+                )
+            })
+            .unzip();
+        Self {
+            request_txs,
+            response_rxs,
+            capabilities: remote_shard_addresses
+                .iter()
+                .map(|_| OnceCell::new())
+                .collect(),
+            last_requested: remote_shard_addresses
+                .iter()
+                .map(|_| Mutex::new(None))
+                .collect(),
+        }
+    }
+
+    pub fn num_shards(&self) -> usize {
+        self.request_txs.len()
+    }
+
+    /// Asks every shard for its capabilities, without waiting for the answers.
+    pub fn request_all(&self) {
+        for shard_id in 0..self.num_shards() {
+            self.request(shard_id);
+        }
+    }
+
+    /// Capabilities reported by the shard, `None` if it didn't report them yet.
+    pub fn get(&self, shard_id: ShardId) -> Option<&ServiceCapabilities> {
+        if self.capabilities[shard_id].get().is_none() {
+            while let Ok(message) = self.response_rxs[shard_id].try_recv() {
+                self.record_response(shard_id, message);
+            }
+        }
+        self.capabilities[shard_id].get()
+    }
+
+    /// Waits up to `timeout` for the shard to report its capabilities, asking it again every
+    /// `QUERY_RETRY_INTERVAL`.
+    pub fn wait(&self, shard_id: ShardId, timeout: Duration) -> Option<&ServiceCapabilities> {
+        let deadline = Instant::now() + timeout;
+        loop {
+            if let Some(capabilities) = self.get(shard_id) {
+                return Some(capabilities);
+            }
+            let retry_at = match *self.last_requested[shard_id].lock() {
+                Some(requested_at) if requested_at.elapsed() < QUERY_RETRY_INTERVAL => {
+                    requested_at + QUERY_RETRY_INTERVAL
+                },
+                _ => {
+                    self.request(shard_id);
+                    Instant::now() + QUERY_RETRY_INTERVAL
+                },
+            };
+            match self.response_rxs[shard_id].recv_deadline(retry_at.min(deadline)) {
+                Ok(message) => self.record_response(shard_id, message),
+                Err(RecvTimeoutError::Timeout) if Instant::now() < deadline => {},
+                Err(_) => {
+                    warn!(
+                        "Shard {} didn't report its capabilities within {:?}",
+                        shard_id, timeout
+                    );
+                    return None;
+                },
+            }
+        }
+    }
+
+    fn request(&self, shard_id: ShardId) {
+        let request = versioning::encode(&GetCapabilities).expect("Request must serialize.");
+        wire_trace::trace(
+            Direction::Send,
+            WireMessage::GetCapabilities,
+            shard_id,
+            &request,
+        );
+        self.request_txs[shard_id]
+            .send(Message::new(request))
+            .unwrap();
+        *self.last_requested[shard_id].lock() = Some(Instant::now());
+    }
+
+    fn record_response(&self, shard_id: ShardId, message: Message) {
+        wire_trace::trace(
+            Direction::Receive,
+            WireMessage::Capabilities,
+            shard_id,
+            &message.data,
+        );
+        match versioning::decode::<ServiceCapabilities>(&message.data) {
+            Ok(Decoded::Known(capabilities)) => {
+                info!("Capabilities of shard {}: {:?}", shard_id, capabilities);
+                // Answers to repeated requests are all the same.
+                self.capabilities[shard_id].set(capabilities).ok();
+            },
+            Ok(Decoded::Unknown { .. }) | Err(_) => {
+                warn!("Cannot decode the capabilities of shard {}", shard_id);
+            },
+        }
+    }
+}
+
+#[test]
+fn test_validate_command() {
+    let capabilities = ServiceCapabilities::new(
+        1,
+        2,
+        8,
+        &ResourceLimits {
+            max_memory_bytes: Some(1 << 30),
+            max_execution_threads: Some(8),
+        },
+        vec![TRANSACTION_TYPE_VM.to_string()],
+        false,
+        Some(Duration::from_secs(1)),
+    );
+    assert!(capabilities.has_feature(FEATURE_HEARTBEAT));
+    assert!(capabilities.has_feature(FEATURE_MEMORY_BUDGET));
+    assert!(!capabilities.has_feature(FEATURE_SPECULATIVE_CROSS_SHARD_PREFETCH));
+    assert!(capabilities.has_feature(FEATURE_FRAGMENTATION));
+
+    assert!(capabilities
+        .validate_command(2, 1000, TRANSACTION_TYPE_VM)
+        .is_ok());
+    assert!(capabilities
+        .validate_command(3, 1000, TRANSACTION_TYPE_VM)
+        .is_err());
+    assert!(capabilities
+        .validate_command(2, MAX_MESSAGE_SIZE + 1, TRANSACTION_TYPE_VM)
+        .is_ok());
+    let mut unfragmented = capabilities.clone();
+    unfragmented
+        .protocol_features
+        .retain(|feature| feature != FEATURE_FRAGMENTATION);
+    assert!(unfragmented
+        .validate_command(2, MAX_MESSAGE_SIZE + 1, TRANSACTION_TYPE_VM)
+        .is_err());
+    assert!(capabilities
+        .validate_command(2, 1000, TRANSACTION_TYPE_NATIVE)
+        .is_err());
+
+    let encoded = versioning::encode(&capabilities).unwrap();
+    match versioning::decode::<ServiceCapabilities>(&encoded).unwrap() {
+        Decoded::Known(decoded) => assert_eq!(decoded.max_execution_threads, Some(8)),
+        Decoded::Unknown { .. } => panic!("Capabilities variant must be known"),
+    }
+}
@@ -0,0 +1,241 @@
+// Copyright © Aptos Foundation
+// SPDX-License-Identifier: Apache-2.0
+
+//! Circuit breakers around the connections of the coordinator to the remote executor shards.
+//!
+//! A block needs every shard, so while the breaker of any shard is open, blocks are failed right
+//! away instead of being dispatched, so that a flapping shard doesn't keep the coordinator busy
+//! retrying blocks against it. A breaker opens after a number of consecutive failures of its
+//! shard, or right away when the heartbeat monitor marks the shard degraded, or when the shard
+//! doesn't return its result of a block within the result timeout (a shard that hangs without
+//! erroring would stall every block). Once open for long enough, it lets a single probe block
+//! through (half-open), and closes again if the shard executes it.
+
+use crate::{
+    heartbeat,
+    metrics::{REMOTE_EXECUTOR_CIRCUIT_BREAKER, REMOTE_EXECUTOR_RESULT_TIMEOUTS},
+};
+use aptos_infallible::Mutex;
+use aptos_logger::{info, warn};
+use aptos_types::block_executor::partitioner::ShardId;
+use std::time::{Duration, Instant};
+
+#[derive(Clone, Copy, Debug)]
+pub struct CircuitBreakerConfig {
+    /// Number of consecutive failures of a shard after which its breaker opens.
+    pub failure_threshold: usize,
+    /// How long a breaker stays open before letting a probe block through.
+    pub open_duration: Duration,
+    /// How long to wait for the result of a shard on a block before failing the block and
+    /// opening the breaker of the shard, `None` to wait forever.
+    pub result_timeout: Option<Duration>,
+}
+
+#[derive(Clone, Copy, Debug, Eq, PartialEq)]
+pub enum BreakerState {
+    Closed,
+    Open,
+    HalfOpen,
+}
+
+impl BreakerState {
+    fn name(&self) -> &'static str {
+        match self {
+            Self::Closed => "closed",
+            Self::Open => "open",
+            Self::HalfOpen => "half_open",
+        }
+    }
+}
+
+struct ShardBreaker {
+    state: BreakerState,
+    consecutive_failures: usize,
+    opened_at: Instant,
+}
+
+pub struct CircuitBreakers {
+    config: CircuitBreakerConfig,
+    breakers: Vec<Mutex<ShardBreaker>>,
+}
+
+impl CircuitBreakers {
+    pub fn new(config: CircuitBreakerConfig, num_shards: usize) -> Self {
+        Self {
+            config,
+            breakers: (0..num_shards)
+                .map(|_| {
+                    Mutex::new(ShardBreaker {
+                        state: BreakerState::Closed,
+                        consecutive_failures: 0,
+                        opened_at: Instant::now(),
+                    })
+                })
+                .collect(),
+        }
+    }
+
+    pub fn state(&self, shard_id: ShardId) -> BreakerState {
+        self.breakers[shard_id].lock().state
+    }
+
+    pub fn result_timeout(&self) -> Option<Duration> {
+        self.config.result_timeout
+    }
+
+    /// Checks whether a block can be dispatched, moving breakers that were open for long enough to
+    /// half-open. Returns the shards whose breakers are open otherwise.
+    pub fn try_dispatch(&self) -> Result<(), Vec<ShardId>> {
+        let open_shards = self
+            .breakers
+            .iter()
+            .enumerate()
+            .filter(|(shard_id, breaker)| {
+                let mut breaker = breaker.lock();
+                if heartbeat::is_shard_degraded(*shard_id) && breaker.state != BreakerState::Open {
+                    // The heartbeats of the shard stopped, don't wait for its blocks to fail.
+                    self.transition(*shard_id, &mut breaker, BreakerState::Open);
+                }
+                if breaker.state == BreakerState::Open
+                    && breaker.opened_at.elapsed() >= self.config.open_duration
+                    && !heartbeat::is_shard_degraded(*shard_id)
+                {
+                    self.transition(*shard_id, &mut breaker, BreakerState::HalfOpen);
+                }
+                breaker.state == BreakerState::Open
+            })
+            .map(|(shard_id, _)| shard_id)
+            .collect::<Vec<_>>();
+        if open_shards.is_empty() {
+            Ok(())
+        } else {
+            Err(open_shards)
+        }
+    }
+
+    pub fn record_success(&self, shard_id: ShardId) {
+        let mut breaker = self.breakers[shard_id].lock();
+        breaker.consecutive_failures = 0;
+        if breaker.state != BreakerState::Closed {
+            self.transition(shard_id, &mut breaker, BreakerState::Closed);
+        }
+    }
+
+    pub fn record_failure(&self, shard_id: ShardId) {
+        let mut breaker = self.breakers[shard_id].lock();
+        breaker.consecutive_failures += 1;
+        let should_open = match breaker.state {
+            // The probe failed, back off again.
+            BreakerState::HalfOpen => true,
+            BreakerState::Closed => breaker.consecutive_failures >= self.config.failure_threshold,
+            BreakerState::Open => false,
+        };
+        if should_open {
+            self.transition(shard_id, &mut breaker, BreakerState::Open);
+        }
+    }
+
+    /// Records that the shard didn't return its result within the result timeout. Its breaker
+    /// opens right away: a hung shard would otherwise stall every block until the threshold.
+    pub fn record_timeout(&self, shard_id: ShardId) {
+        REMOTE_EXECUTOR_RESULT_TIMEOUTS
+            .with_label_values(&[&shard_id.to_string()])
+            .inc();
+        let mut breaker = self.breakers[shard_id].lock();
+        breaker.consecutive_failures += 1;
+        if breaker.state != BreakerState::Open {
+            self.transition(shard_id, &mut breaker, BreakerState::Open);
+        }
+    }
+
+    fn transition(&self, shard_id: ShardId, breaker: &mut ShardBreaker, to: BreakerState) {
+        let from = breaker.state;
+        breaker.state = to;
+        if to == BreakerState::Open {
+            breaker.opened_at = Instant::now();
+            warn!(
+                "Circuit breaker of shard {} opened (was {}, {} consecutive failures), failing blocks for {:.1} secs",
+                shard_id,
+                from.name(),
+                breaker.consecutive_failures,
+                self.config.open_duration.as_secs_f64()
+            );
+        } else {
+            info!(
+                "Circuit breaker of shard {} is {} (was {})",
+                shard_id,
+                to.name(),
+                from.name()
+            );
+        }
+        REMOTE_EXECUTOR_CIRCUIT_BREAKER
+            .with_label_values(&[&shard_id.to_string(), to.name()])
+            .inc();
+    }
+}
+
+#[test]
+fn test_circuit_breaker_transitions() {
+    let breakers = CircuitBreakers::new(
+        CircuitBreakerConfig {
+            failure_threshold: 2,
+            open_duration: Duration::ZERO,
+            result_timeout: None,
+        },
+        2,
+    );
+    assert_eq!(breakers.try_dispatch(), Ok(()));
+
+    // Only consecutive failures count.
+    breakers.record_failure(1);
+    breakers.record_success(1);
+    breakers.record_failure(1);
+    assert_eq!(breakers.state(1), BreakerState::Closed);
+    breakers.record_failure(1);
+    assert_eq!(breakers.state(1), BreakerState::Open);
+
+    // Open for long enough, a probe goes through.
+    assert_eq!(breakers.try_dispatch(), Ok(()));
+    assert_eq!(breakers.state(1), BreakerState::HalfOpen);
+    // A failed probe opens the breaker again right away.
+    breakers.record_failure(1);
+    assert_eq!(breakers.state(1), BreakerState::Open);
+    assert_eq!(breakers.try_dispatch(), Ok(()));
+    breakers.record_success(1);
+    assert_eq!(breakers.state(1), BreakerState::Closed);
+    assert_eq!(breakers.state(0), BreakerState::Closed);
+}
+
+#[test]
+fn test_open_circuit_breaker_fails_dispatch() {
+    let breakers = CircuitBreakers::new(
+        CircuitBreakerConfig {
+            failure_threshold: 1,
+            open_duration: Duration::from_secs(3600),
+            result_timeout: None,
+        },
+        3,
+    );
+    breakers.record_failure(2);
+    assert_eq!(breakers.try_dispatch(), Err(vec![2]));
+}
+
+#[test]
+fn test_timeout_opens_circuit_breaker() {
+    let breakers = CircuitBreakers::new(
+        CircuitBreakerConfig {
+            failure_threshold: 3,
+            open_duration: Duration::from_secs(3600),
+            result_timeout: Some(Duration::from_secs(1)),
+        },
+        2,
+    );
+    // A timeout opens the breaker regardless of the failure threshold.
+    breakers.record_timeout(0);
+    assert_eq!(breakers.state(0), BreakerState::Open);
+    assert_eq!(breakers.try_dispatch(), Err(vec![0]));
+    // Failing the block the shard timed out on doesn't change anything.
+    breakers.record_failure(0);
+    assert_eq!(breakers.state(0), BreakerState::Open);
+    assert_eq!(breakers.state(1), BreakerState::Closed);
+}
@@ -0,0 +1,193 @@
+// Copyright © Aptos Foundation
+// SPDX-License-Identifier: Apache-2.0
+
+use crate::{
+    cross_shard_relay::CrossShardRouting,
+    error::Error,
+    versioning::{self, Decoded, MIN_COMPATIBLE_PROTOCOL_VERSION, PROTOCOL_VERSION},
+    ExecuteBlockCommand, ExecutionStats, RemoteExecutionRequest, RemoteExecutionResult,
+    ShardTopology,
+};
+use aptos_types::{
+    block_executor::partitioner::SubBlocksForShard,
+    vm_status::{StatusCode, VMStatus},
+};
+
+fn execute_block_request() -> RemoteExecutionRequest {
+    RemoteExecutionRequest::ExecuteBlock(ExecuteBlockCommand {
+        sub_blocks: SubBlocksForShard::empty(3),
+        concurrency_level: 4,
+        maybe_block_gas_limit: Some(1000),
+    })
+}
+
+#[test]
+fn test_request_round_trip() {
+    let bytes = versioning::encode(&execute_block_request()).unwrap();
+    match versioning::decode::<RemoteExecutionRequest>(&bytes).unwrap() {
+        Decoded::Known(RemoteExecutionRequest::ExecuteBlock(command)) => {
+            let (sub_blocks, concurrency_level, maybe_block_gas_limit) = command.into();
+            assert_eq!(sub_blocks.shard_id, 3);
+            assert_eq!(concurrency_level, 4);
+            assert_eq!(maybe_block_gas_limit, Some(1000));
+        },
+        decoded => panic!("Unexpected decoded request {:?}", decoded),
+    }
+}
+
+#[test]
+fn test_request_with_topology_round_trip() {
+    let topology = ShardTopology {
+        shard_id: 3,
+        num_shards: 4,
+        num_rounds: 0,
+        cross_shard_routing: CrossShardRouting::ViaCoordinator,
+    };
+    let request = match execute_block_request() {
+        RemoteExecutionRequest::ExecuteBlock(command) => {
+            RemoteExecutionRequest::ExecuteBlockInTopology(command, topology)
+        },
+        request => panic!("Unexpected request {:?}", request),
+    };
+    let bytes = versioning::encode(&request).unwrap();
+    // Requests with a topology are a new variant, older shards report it as unknown.
+    assert_eq!(
+        versioning::envelope_header(&bytes),
+        Some((PROTOCOL_VERSION, 1))
+    );
+    match versioning::decode::<RemoteExecutionRequest>(&bytes).unwrap() {
+        Decoded::Known(RemoteExecutionRequest::ExecuteBlockInTopology(command, decoded)) => {
+            assert_eq!(command.into().0.shard_id, 3);
+            assert_eq!(decoded, topology);
+        },
+        decoded => panic!("Unexpected decoded request {:?}", decoded),
+    }
+}
+
+#[test]
+fn test_result_round_trip() {
+    let result = RemoteExecutionResult::new(Err(VMStatus::error(
+        StatusCode::MEMORY_LIMIT_EXCEEDED,
+        None,
+    )));
+    let bytes = versioning::encode(&result).unwrap();
+    match versioning::decode::<RemoteExecutionResult>(&bytes).unwrap() {
+        Decoded::Known(decoded) => assert_eq!(decoded.inner, result.inner),
+        decoded => panic!("Unexpected decoded result {:?}", decoded),
+    }
+}
+
+#[test]
+fn test_result_with_stats_round_trip() {
+    let stats = ExecutionStats {
+        execution_ms: 12,
+        num_threads: 8,
+        peak_memory_delta_bytes: 1 << 20,
+        num_state_reads: 300,
+    };
+    let result = RemoteExecutionResult::with_stats(Ok(vec![vec![]]), stats.clone());
+    let bytes = versioning::encode(&result).unwrap();
+    assert_eq!(
+        versioning::envelope_header(&bytes),
+        Some((PROTOCOL_VERSION, 1))
+    );
+    match versioning::decode::<RemoteExecutionResult>(&bytes).unwrap() {
+        Decoded::Known(decoded) => {
+            assert_eq!(decoded.inner, result.inner);
+            assert_eq!(decoded.stats, Some(stats));
+        },
+        decoded => panic!("Unexpected decoded result {:?}", decoded),
+    }
+
+    // Results without stats keep the variant older coordinators know about.
+    let bytes = versioning::encode(&RemoteExecutionResult::new(Ok(vec![]))).unwrap();
+    assert_eq!(
+        versioning::envelope_header(&bytes),
+        Some((PROTOCOL_VERSION, 0))
+    );
+}
+
+#[test]
+fn test_rejected_result_round_trip() {
+    let status = VMStatus::error(
+        StatusCode::MEMORY_LIMIT_EXCEEDED,
+        Some("Over budget".into()),
+    );
+    let bytes = versioning::encode(&RemoteExecutionResult::rejected(status.clone())).unwrap();
+    assert_eq!(
+        versioning::envelope_header(&bytes),
+        Some((PROTOCOL_VERSION, 2))
+    );
+    match versioning::decode::<RemoteExecutionResult>(&bytes).unwrap() {
+        Decoded::Known(decoded) => {
+            assert!(decoded.rejected);
+            assert_eq!(decoded.inner, Err(status));
+        },
+        decoded => panic!("Unexpected decoded result {:?}", decoded),
+    }
+}
+
+#[test]
+fn test_wire_format_is_stable() {
+    // Changing these bytes breaks interoperability with deployed binaries: add a new variant
+    // instead.
+    let bytes = versioning::encode_as_peer(&RemoteExecutionResult::new(Ok(vec![])), 1).unwrap();
+    assert_eq!(bytes, vec![1, 0, 0, 0, 0, 0, 0, 0, 2, 0, 0]);
+}
+
+#[test]
+fn test_newer_peer_messages() {
+    // A newer peer still sending a variant we know about is understood.
+    let bytes = versioning::encode_as_peer(&execute_block_request(), PROTOCOL_VERSION + 1).unwrap();
+    assert!(matches!(
+        versioning::decode::<RemoteExecutionRequest>(&bytes).unwrap(),
+        Decoded::Known(RemoteExecutionRequest::ExecuteBlock(_))
+    ));
+
+    // A variant added by a newer peer is reported, rather than failing to decode.
+    let bytes = versioning::encode_raw(PROTOCOL_VERSION + 1, 42, vec![1, 2, 3]);
+    match versioning::decode::<RemoteExecutionRequest>(&bytes).unwrap() {
+        Decoded::Unknown { version, variant } => {
+            assert_eq!(version, PROTOCOL_VERSION + 1);
+            assert_eq!(variant, 42);
+        },
+        decoded => panic!("Unexpected decoded request {:?}", decoded),
+    }
+    let bytes = versioning::encode_raw(PROTOCOL_VERSION + 1, 42, vec![]);
+    assert!(matches!(
+        versioning::decode::<RemoteExecutionResult>(&bytes).unwrap(),
+        Decoded::Unknown { variant: 42, .. }
+    ));
+}
+
+#[test]
+fn test_incompatible_and_corrupt_messages() {
+    if MIN_COMPATIBLE_PROTOCOL_VERSION > 0 {
+        let bytes = versioning::encode_as_peer(
+            &execute_block_request(),
+            MIN_COMPATIBLE_PROTOCOL_VERSION - 1,
+        )
+        .unwrap();
+        assert_eq!(
+            versioning::decode::<RemoteExecutionRequest>(&bytes).unwrap_err(),
+            Error::IncompatibleVersion(MIN_COMPATIBLE_PROTOCOL_VERSION - 1)
+        );
+    }
+
+    // A known variant with a payload that doesn't match it is an error.
+    let bytes = versioning::encode_raw(PROTOCOL_VERSION, 0, vec![0xFF]);
+    assert!(matches!(
+        versioning::decode::<RemoteExecutionRequest>(&bytes),
+        Err(Error::SerializationError(_))
+    ));
+}
+
+#[test]
+fn test_envelope_header() {
+    let bytes = versioning::encode_raw(PROTOCOL_VERSION + 1, 42, vec![1, 2, 3]);
+    assert_eq!(
+        versioning::envelope_header(&bytes),
+        Some((PROTOCOL_VERSION + 1, 42))
+    );
+    assert_eq!(versioning::envelope_header(&bytes[..6]), None);
+}
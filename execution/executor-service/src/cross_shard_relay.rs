@@ -0,0 +1,244 @@
+// Copyright © Aptos Foundation
+// SPDX-License-Identifier: Apache-2.0
+
+//! Routing of the cross-shard messages between the remote shards.
+//!
+//! By default the shards send the cross-shard messages of a round straight to each other. With
+//! `CrossShardRouting::ViaCoordinator` they send them to the coordinator instead, which forwards
+//! them to the shard they are meant for. That costs a hop, but only needs the shards to be able to
+//! reach the coordinator, not each other. The routing is chosen by the coordinator and sent along
+//! with every command, in its `ShardTopology`.
+
+use crate::{
+    block_abort::BlockAborts,
+    metrics::REMOTE_EXECUTOR_CROSS_SHARD_RELAYED,
+    wire_trace::{self, Direction, WireMessage},
+};
+use aptos_logger::warn;
+use aptos_secure_net::network_controller::{Message, NetworkController};
+use aptos_types::block_executor::partitioner::{RoundId, ShardId, MAX_ALLOWED_PARTITIONING_ROUNDS};
+use clap::ValueEnum;
+use crossbeam_channel::Sender;
+use serde::{Deserialize, Serialize};
+use std::{
+    net::SocketAddr,
+    sync::{Arc, Mutex},
+    thread,
+};
+
+#[derive(Clone, Copy, Debug, Default, Deserialize, Eq, PartialEq, Serialize, ValueEnum)]
+pub enum CrossShardRouting {
+    /// Shards send cross-shard messages straight to each other.
+    #[default]
+    Direct,
+    /// Shards send cross-shard messages to the coordinator, which forwards them.
+    ViaCoordinator,
+}
+
+/// Routing of the command a shard is executing, set by the coordinator client when it receives
+/// the command and followed by the cross-shard client.
+#[derive(Default)]
+pub(crate) struct CurrentRouting(Mutex<CrossShardRouting>);
+
+impl CurrentRouting {
+    pub(crate) fn set(&self, routing: CrossShardRouting) {
+        *self.0.lock().unwrap() = routing;
+    }
+
+    pub(crate) fn get(&self) -> CrossShardRouting {
+        *self.0.lock().unwrap()
+    }
+}
+
+/// A cross-shard message on its way through the coordinator. The message itself is forwarded as
+/// is, without decoding it.
+#[derive(Debug, Deserialize, Serialize)]
+pub(crate) struct RelayedCrossShardMsg {
+    pub(crate) dst_shard: ShardId,
+    pub(crate) round: RoundId,
+    pub(crate) msg: Vec<u8>,
+}
+
+/// Message type of the cross-shard messages of `round`, on the shard receiving them.
+pub(crate) fn cross_shard_message_type(round: RoundId) -> String {
+    format!("cross_shard_{}", round)
+}
+
+/// Message type of the cross-shard messages sent by `shard_id` to the coordinator.
+pub(crate) fn relay_message_type(shard_id: ShardId) -> String {
+    format!("cross_shard_relay_{}", shard_id)
+}
+
+/// Senders of the cross-shard messages of every round to every shard, from the coordinator.
+pub(crate) type CrossShardSenders = Arc<Vec<Vec<Mutex<Sender<Message>>>>>;
+
+/// Creates the channels the coordinator sends cross-shard messages to the shards on, the relayed
+/// ones and the ones aborting the blocks shards rejected.
+pub(crate) fn create_cross_shard_senders(
+    controller: &mut NetworkController,
+    remote_shard_addresses: &[SocketAddr],
+) -> CrossShardSenders {
+    Arc::new(
+        remote_shard_addresses
+            .iter()
+            .map(|address| {
+                (0..MAX_ALLOWED_PARTITIONING_ROUNDS)
+                    .map(|round| {
+                        Mutex::new(
+                            controller
+                                .create_outbound_channel(*address, cross_shard_message_type(round)),
+                        )
+                    })
+                    .collect::<Vec<_>>()
+            })
+            .collect::<Vec<_>>(),
+    )
+}
+
+/// Starts forwarding the cross-shard messages the shards send to the coordinator, on a thread per
+/// shard. The messages arrive at their shard on the same channels as messages sent directly.
+/// A shard that cannot be sent a relayed message fails its block.
+pub(crate) fn start_cross_shard_relay(
+    controller: &mut NetworkController,
+    num_shards: usize,
+    block_aborts: Arc<BlockAborts>,
+) {
+    for src_shard in 0..num_shards {
+        let rx = controller.create_inbound_channel(relay_message_type(src_shard));
+        let block_aborts = block_aborts.clone();
+        thread::Builder::new()
+            .name(format!("cross-shard-relay-{}", src_shard))
+            // Exits once the network controller drops the inbound channel.
+            .spawn(move || {
+                while let Ok(message) = rx.recv() {
+                    wire_trace::trace(
+                        Direction::Receive,
+                        WireMessage::CrossShardRelay,
+                        src_shard,
+                        &message.data,
+                    );
+                    if let Err(dst_shard) =
+                        relay(src_shard, &message.data, block_aborts.message_txs())
+                    {
+                        block_aborts.fail(
+                            dst_shard,
+                            format!(
+                                "Cannot relay a cross-shard message of shard {} to shard {}, the network is shut down",
+                                src_shard, dst_shard
+                            ),
+                        );
+                    }
+                }
+            })
+            .expect("Failed to spawn cross shard relay thread");
+    }
+}
+
+/// Forwards a message relayed by `src_shard` to the shard it is meant for. Messages that cannot
+/// be decoded or routed are dropped, and the shard a message cannot be sent to is returned.
+fn relay(
+    src_shard: ShardId,
+    bytes: &[u8],
+    message_txs: &[Vec<Mutex<Sender<Message>>>],
+) -> Result<(), ShardId> {
+    let relayed: RelayedCrossShardMsg = match bcs::from_bytes(bytes) {
+        Ok(relayed) => relayed,
+        Err(err) => {
+            warn!(
+                "Dropping undecodable cross-shard message relayed by shard {}: {}",
+                src_shard, err
+            );
+            return Ok(());
+        },
+    };
+    let tx = match message_txs
+        .get(relayed.dst_shard)
+        .and_then(|txs| txs.get(relayed.round))
+    {
+        Some(tx) => tx,
+        None => {
+            warn!(
+                "Dropping cross-shard message relayed by shard {} to shard {} for round {}, which don't exist",
+                src_shard, relayed.dst_shard, relayed.round
+            );
+            return Ok(());
+        },
+    };
+    REMOTE_EXECUTOR_CROSS_SHARD_RELAYED
+        .with_label_values(&[&src_shard.to_string(), &relayed.dst_shard.to_string()])
+        .inc();
+    wire_trace::trace(
+        Direction::Send,
+        WireMessage::CrossShard,
+        relayed.dst_shard,
+        &relayed.msg,
+    );
+    tx.lock()
+        .unwrap()
+        .send(Message::new(relayed.msg))
+        .map_err(|_| relayed.dst_shard)
+}
+
+#[cfg(test)]
+fn test_message_txs(
+    num_shards: usize,
+    num_rounds: usize,
+) -> (
+    Vec<Vec<Mutex<Sender<Message>>>>,
+    Vec<Vec<crossbeam_channel::Receiver<Message>>>,
+) {
+    (0..num_shards)
+        .map(|_| {
+            (0..num_rounds)
+                .map(|_| {
+                    let (tx, rx) = crossbeam_channel::unbounded();
+                    (Mutex::new(tx), rx)
+                })
+                .unzip::<_, _, Vec<_>, Vec<_>>()
+        })
+        .unzip()
+}
+
+#[test]
+fn test_relay_routes_to_shard_and_round() {
+    let (message_txs, message_rxs) = test_message_txs(3, 2);
+    let relayed = RelayedCrossShardMsg {
+        dst_shard: 2,
+        round: 1,
+        msg: vec![1, 2, 3],
+    };
+    relay(0, &bcs::to_bytes(&relayed).unwrap(), &message_txs).unwrap();
+    assert_eq!(message_rxs[2][1].try_recv().unwrap().data, vec![1, 2, 3]);
+    assert!(message_rxs
+        .iter()
+        .flatten()
+        .all(|rx| rx.try_recv().is_err()));
+
+    // Messages that can't be decoded or routed are dropped.
+    relay(0, &[0xFF], &message_txs).unwrap();
+    let relayed = RelayedCrossShardMsg {
+        dst_shard: 3,
+        round: 0,
+        msg: vec![1],
+    };
+    relay(0, &bcs::to_bytes(&relayed).unwrap(), &message_txs).unwrap();
+    assert!(message_rxs
+        .iter()
+        .flatten()
+        .all(|rx| rx.try_recv().is_err()));
+}
+
+#[test]
+fn test_relay_returns_unreachable_shard() {
+    let (message_txs, mut message_rxs) = test_message_txs(2, 1);
+    message_rxs[1].clear();
+    let relayed = RelayedCrossShardMsg {
+        dst_shard: 1,
+        round: 0,
+        msg: vec![1],
+    };
+    assert_eq!(
+        relay(0, &bcs::to_bytes(&relayed).unwrap(), &message_txs),
+        Err(1)
+    );
+}
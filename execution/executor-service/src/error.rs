@@ -12,6 +12,8 @@ pub enum Error {
     InternalError(String),
     #[error("Serialization error: {0}")]
     SerializationError(String),
+    #[error("Incompatible protocol version: {0}")]
+    IncompatibleVersion(u32),
 }
 
 impl From<bcs::Error> for Error {
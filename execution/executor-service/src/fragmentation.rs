@@ -0,0 +1,311 @@
+// Copyright © Aptos Foundation
+// SPDX-License-Identifier: Apache-2.0
+
+//! Fragmentation of the messages that exceed the largest message a peer takes.
+//!
+//! The transport drops messages above its limit with an error that tells neither which message
+//! nor why, and the commands of large blocks can get there. Shards of protocol version
+//! `FRAGMENTATION_PROTOCOL_VERSION` and later report `FEATURE_FRAGMENTATION` along with their max
+//! payload. The coordinator sends the requests to them that exceed the smaller of its own and the
+//! shard's max payload in fragments, which the shard reassembles before decoding. Requests to other
+//! shards are never fragmented, and the ones that don't fit are rejected before dispatch.
+//!
+//! The results of large blocks can get there as well. Shards send results to coordinators of
+//! protocol version `RESULT_FRAGMENTATION_PROTOCOL_VERSION` and later in fragments, which the
+//! coordinator reassembles as they arrive. The state values served to the shards and mirrored to
+//! the warm standbys are lists of independent items instead, which are split into messages that
+//! fit, and need no reassembly.
+
+use crate::{
+    capabilities::{ServiceCapabilities, FEATURE_FRAGMENTATION},
+    error::Error,
+    versioning::{self, RESULT_FRAGMENT_VARIANT},
+    RemoteExecutionResult,
+};
+use aptos_logger::warn;
+use aptos_secure_net::{grpc_network_service::MAX_MESSAGE_SIZE, network_controller::Message};
+use aptos_types::{
+    block_executor::partitioner::ShardId,
+    state_store::{state_key::StateKey, state_value::StateValue},
+    vm_status::{StatusCode, VMStatus},
+};
+use crossbeam_channel::Receiver;
+use serde::{Deserialize, Serialize};
+use std::{mem, thread};
+
+/// Smallest max payload the coordinator can be configured with, below which the fragments would
+/// mostly be overhead.
+pub const MIN_PAYLOAD_BYTES: usize = 64 * 1024;
+/// Largest max payload the coordinator can be configured with, the limit of the transport.
+pub const MAX_PAYLOAD_BYTES: usize = MAX_MESSAGE_SIZE;
+/// Room left in every fragment for its envelope and header, and the framing of the transport.
+const FRAGMENT_OVERHEAD_BYTES: usize = 1024;
+
+#[derive(Debug, Deserialize, Serialize)]
+struct Fragment {
+    /// Id of the request, unique per coordinator.
+    request_id: u64,
+    index: u32,
+    count: u32,
+    bytes: Vec<u8>,
+}
+
+/// Largest message the coordinator sends to a shard, the smaller of `max_payload_bytes` and the
+/// max payload of the shard. `None` if the shard can't reassemble fragments.
+pub fn negotiate_max_payload_bytes(
+    max_payload_bytes: usize,
+    capabilities: Option<&ServiceCapabilities>,
+) -> Option<usize> {
+    let capabilities = capabilities.filter(|c| c.has_feature(FEATURE_FRAGMENTATION))?;
+    Some(max_payload_bytes.min(capabilities.max_payload_bytes as usize))
+}
+
+/// Returns the messages to send an encoded request (or result) in, the message itself if it fits
+/// in `max_payload_bytes` along with the framing of the transport. The fragments are encoded as
+/// `fragment_variant`.
+pub(crate) fn fragment(
+    request: Vec<u8>,
+    request_id: u64,
+    max_payload_bytes: usize,
+    fragment_variant: u32,
+) -> Result<Vec<Vec<u8>>, Error> {
+    let fragment_bytes = max_payload_bytes
+        .saturating_sub(FRAGMENT_OVERHEAD_BYTES)
+        .max(1);
+    if request.len() <= fragment_bytes {
+        return Ok(vec![request]);
+    }
+    let count = ((request.len() + fragment_bytes - 1) / fragment_bytes) as u32;
+    request
+        .chunks(fragment_bytes)
+        .enumerate()
+        .map(|(index, bytes)| {
+            let fragment = Fragment {
+                request_id,
+                index: index as u32,
+                count,
+                bytes: bytes.to_vec(),
+            };
+            versioning::encode_variant(fragment_variant, bcs::to_bytes(&fragment)?)
+        })
+        .collect()
+}
+
+/// Splits state values into lists that each encode within `max_payload_bytes`, along with the
+/// framing of the transport. A single state value too large for any message gets a list of its
+/// own.
+pub(crate) fn split_state_values(
+    state_values: Vec<(StateKey, Option<StateValue>)>,
+    max_payload_bytes: usize,
+) -> Vec<Vec<(StateKey, Option<StateValue>)>> {
+    let max_bytes = max_payload_bytes.saturating_sub(FRAGMENT_OVERHEAD_BYTES);
+    let mut lists = vec![];
+    let mut list = vec![];
+    let mut list_bytes = 0;
+    for item in state_values {
+        let item_bytes = bcs::serialized_size(&item).expect("State values must serialize.");
+        if !list.is_empty() && list_bytes + item_bytes > max_bytes {
+            lists.push(mem::take(&mut list));
+            list_bytes = 0;
+        }
+        list_bytes += item_bytes;
+        list.push(item);
+    }
+    if !list.is_empty() || lists.is_empty() {
+        lists.push(list);
+    }
+    lists
+}
+
+/// Reassembles the results of `shard_id` that arrive on `result_rx` in fragments, and forwards
+/// every result on the returned channel. A result that cannot be reassembled is forwarded as an
+/// error, so that its block fails rather than waits for it forever.
+pub(crate) fn reassemble_results(
+    shard_id: ShardId,
+    result_rx: Receiver<Message>,
+) -> Receiver<Message> {
+    let (forward_tx, forward_rx) = crossbeam_channel::unbounded();
+    thread::Builder::new()
+        .name(format!("result-reassembly-{}", shard_id))
+        // Exits once the network controller drops the inbound channel.
+        .spawn(move || {
+            let mut reassembler = Reassembler::new(RESULT_FRAGMENT_VARIANT);
+            while let Ok(message) = result_rx.recv() {
+                let result_bytes = match reassembler.push(message.to_bytes()) {
+                    Ok(Some(result_bytes)) => result_bytes,
+                    Ok(None) => continue,
+                    Err(err) => {
+                        let reason = format!(
+                            "Failed to reassemble the result of shard {}: {}",
+                            shard_id, err
+                        );
+                        warn!("{}", reason);
+                        let result = RemoteExecutionResult::new(Err(VMStatus::error(
+                            StatusCode::UNKNOWN_STATUS,
+                            Some(reason),
+                        )));
+                        versioning::encode(&result).unwrap()
+                    },
+                };
+                if forward_tx.send(Message::new(result_bytes)).is_err() {
+                    break;
+                }
+            }
+        })
+        .expect("Failed to spawn result reassembly thread");
+    forward_rx
+}
+
+/// Reassembles the messages a peer receives in fragments. The fragments of a message arrive in
+/// order, on the channel of the peer.
+pub(crate) struct Reassembler {
+    /// Variant the fragments are encoded as.
+    fragment_variant: u32,
+    /// Request being reassembled, and its number of fragments.
+    in_progress: Option<(u64, u32)>,
+    bytes: Vec<u8>,
+    next_index: u32,
+    /// Request whose remaining fragments are dropped, after one of them arrived out of order.
+    abandoned_request_id: Option<u64>,
+}
+
+impl Reassembler {
+    pub(crate) fn new(fragment_variant: u32) -> Self {
+        Self {
+            fragment_variant,
+            in_progress: None,
+            bytes: vec![],
+            next_index: 0,
+            abandoned_request_id: None,
+        }
+    }
+
+    /// Takes the next message received by the peer, and returns the request once all of its
+    /// fragments arrived. Messages that aren't fragments are returned as they are.
+    pub(crate) fn push(&mut self, message: Vec<u8>) -> Result<Option<Vec<u8>>, Error> {
+        match versioning::envelope_header(&message) {
+            Some((_, variant)) if variant == self.fragment_variant => {},
+            _ => return Ok(Some(message)),
+        }
+        let fragment: Fragment = bcs::from_bytes(&versioning::decode_payload(&message)?)?;
+        if self.abandoned_request_id == Some(fragment.request_id) {
+            return Ok(None);
+        }
+        if fragment.index == 0 {
+            if let Some((request_id, count)) = self.in_progress {
+                warn!(
+                    "Dropping request {} after {} of its {} fragments, request {} started",
+                    request_id, self.next_index, count, fragment.request_id
+                );
+            }
+            self.in_progress = Some((fragment.request_id, fragment.count));
+            self.bytes.clear();
+            self.next_index = 0;
+        }
+        if self.in_progress != Some((fragment.request_id, fragment.count))
+            || fragment.index != self.next_index
+            || fragment.index >= fragment.count
+        {
+            self.in_progress = None;
+            self.abandoned_request_id = Some(fragment.request_id);
+            return Err(Error::InternalError(format!(
+                "Fragment {} of {} of request {} arrived out of order",
+                fragment.index, fragment.count, fragment.request_id
+            )));
+        }
+        self.bytes.extend(fragment.bytes);
+        self.next_index += 1;
+        if self.next_index < fragment.count {
+            return Ok(None);
+        }
+        self.in_progress = None;
+        Ok(Some(mem::take(&mut self.bytes)))
+    }
+}
+
+#[test]
+fn test_fragment_and_reassemble() {
+    use crate::versioning::REQUEST_FRAGMENT_VARIANT;
+
+    let request = (0..(3 * MIN_PAYLOAD_BYTES))
+        .map(|i| i as u8)
+        .collect::<Vec<_>>();
+    let max_payload_bytes = request.len() + FRAGMENT_OVERHEAD_BYTES;
+    assert_eq!(
+        fragment(
+            request.clone(),
+            0,
+            max_payload_bytes,
+            REQUEST_FRAGMENT_VARIANT
+        )
+        .unwrap(),
+        vec![request.clone()]
+    );
+    // Requests that only fit without the framing of the transport are fragmented too.
+    assert_eq!(
+        fragment(request.clone(), 0, request.len(), REQUEST_FRAGMENT_VARIANT)
+            .unwrap()
+            .len(),
+        2
+    );
+
+    let fragments = fragment(
+        request.clone(),
+        1,
+        MIN_PAYLOAD_BYTES,
+        REQUEST_FRAGMENT_VARIANT,
+    )
+    .unwrap();
+    assert_eq!(fragments.len(), 4);
+    assert!(fragments.iter().all(|f| f.len() <= MIN_PAYLOAD_BYTES));
+    // Fragments of requests are not fragments of results.
+    assert_eq!(
+        Reassembler::new(RESULT_FRAGMENT_VARIANT)
+            .push(fragments[0].clone())
+            .unwrap(),
+        Some(fragments[0].clone())
+    );
+    let mut reassembler = Reassembler::new(REQUEST_FRAGMENT_VARIANT);
+    for f in &fragments[..3] {
+        assert_eq!(reassembler.push(f.clone()).unwrap(), None);
+    }
+    assert_eq!(
+        reassembler.push(fragments[3].clone()).unwrap(),
+        Some(request)
+    );
+    // Requests that weren't fragmented go through as they are.
+    assert_eq!(
+        reassembler.push(vec![1, 2, 3]).unwrap(),
+        Some(vec![1, 2, 3])
+    );
+
+    // A missing fragment fails the request, and its remaining fragments are dropped.
+    assert!(reassembler.push(fragments[0].clone()).is_ok());
+    assert!(reassembler.push(fragments[2].clone()).is_err());
+    assert_eq!(reassembler.push(fragments[3].clone()).unwrap(), None);
+}
+
+#[test]
+fn test_split_state_values() {
+    let item = |byte: u8, len: usize| {
+        (
+            StateKey::raw(vec![byte]),
+            Some(StateValue::from(vec![byte; len])),
+        )
+    };
+    let state_values = (0..10)
+        .map(|i| item(i, MIN_PAYLOAD_BYTES / 4))
+        .chain([
+            item(10, 2 * MIN_PAYLOAD_BYTES),
+            (StateKey::raw(vec![11]), None),
+        ])
+        .collect::<Vec<_>>();
+    let lists = split_state_values(state_values.clone(), MIN_PAYLOAD_BYTES);
+    assert_eq!(lists.concat(), state_values);
+    // Three values of a quarter of the payload fit along with the framing, but not four. The one
+    // larger than the payload gets a list of its own.
+    assert_eq!(lists.iter().map(Vec::len).collect::<Vec<_>>(), vec![
+        3, 3, 3, 1, 1, 1
+    ]);
+    assert_eq!(split_state_values(vec![], MIN_PAYLOAD_BYTES), vec![vec![]]);
+}
@@ -0,0 +1,254 @@
+// Copyright © Aptos Foundation
+// SPDX-License-Identifier: Apache-2.0
+
+//! Liveness of the remote executor shards.
+//!
+//! Every shard periodically sends a heartbeat to the coordinator, on a channel of its own next to
+//! the command and result channels. The coordinator marks a shard as degraded when no heartbeat
+//! arrived within the timeout, and as healthy again once heartbeats resume, so that a stuck or
+//! dead shard shows up in the logs and metrics instead of as an unexplained stall.
+
+use crate::{
+    error::Error,
+    metrics::REMOTE_EXECUTOR_SHARD_HEARTBEAT,
+    versioning::{self, Decoded, VersionedMessage},
+    wire_trace::{self, Direction, WireMessage},
+};
+use aptos_infallible::Mutex;
+use aptos_logger::{info, warn};
+use aptos_secure_net::network_controller::{Message, NetworkController};
+use aptos_types::block_executor::partitioner::ShardId;
+use crossbeam_channel::{Receiver, RecvTimeoutError};
+use once_cell::sync::Lazy;
+use serde::{Deserialize, Serialize};
+use std::{
+    collections::HashSet,
+    net::SocketAddr,
+    sync::{
+        atomic::{AtomicU64, Ordering},
+        Arc,
+    },
+    thread,
+    time::{Duration, Instant},
+};
+
+pub const DEFAULT_HEARTBEAT_INTERVAL: Duration = Duration::from_secs(1);
+
+static DEGRADED_SHARDS: Lazy<Mutex<HashSet<ShardId>>> = Lazy::new(|| Mutex::new(HashSet::new()));
+
+/// Returns whether the coordinator hasn't heard from the shard within the heartbeat timeout.
+pub fn is_shard_degraded(shard_id: ShardId) -> bool {
+    DEGRADED_SHARDS.lock().contains(&shard_id)
+}
+
+pub fn degraded_shards(num_shards: usize) -> Vec<ShardId> {
+    (0..num_shards)
+        .filter(|shard_id| is_shard_degraded(*shard_id))
+        .collect()
+}
+
+fn heartbeat_message_type(shard_id: ShardId) -> String {
+    format!("heartbeat_{}", shard_id)
+}
+
+#[derive(Clone, Debug, Deserialize, Serialize)]
+pub struct Heartbeat {
+    pub shard_id: ShardId,
+    pub uptime_secs: u64,
+    pub num_blocks_executed: u64,
+    /// Seconds since the shard sent its last execution result, `None` before the first one.
+    pub secs_since_last_block: Option<u64>,
+    /// Number of commands received but not picked up for execution yet.
+    pub queue_depth: u64,
+}
+
+impl VersionedMessage for Heartbeat {
+    fn variant(&self) -> u32 {
+        0
+    }
+
+    fn encode_payload(&self) -> Result<Vec<u8>, Error> {
+        Ok(bcs::to_bytes(self)?)
+    }
+
+    fn decode_payload(variant: u32, payload: &[u8]) -> Option<Result<Self, Error>> {
+        match variant {
+            0 => Some(bcs::from_bytes(payload).map_err(Error::from)),
+            _ => None,
+        }
+    }
+}
+
+/// Progress of a shard, updated by the coordinator client as it executes blocks.
+pub struct ShardStatus {
+    start_time: Instant,
+    num_blocks_executed: AtomicU64,
+    // Milliseconds since `start_time`, plus one, or 0 if no block was executed yet.
+    last_block_millis: AtomicU64,
+}
+
+impl ShardStatus {
+    pub fn new() -> Self {
+        Self {
+            start_time: Instant::now(),
+            num_blocks_executed: AtomicU64::new(0),
+            last_block_millis: AtomicU64::new(0),
+        }
+    }
+
+    pub fn record_block_executed(&self) {
+        self.num_blocks_executed.fetch_add(1, Ordering::Relaxed);
+        self.last_block_millis.store(
+            self.start_time.elapsed().as_millis() as u64 + 1,
+            Ordering::Relaxed,
+        );
+    }
+
+    fn heartbeat(&self, shard_id: ShardId, queue_depth: usize) -> Heartbeat {
+        let uptime = self.start_time.elapsed();
+        let last_block_millis = self.last_block_millis.load(Ordering::Relaxed);
+        Heartbeat {
+            shard_id,
+            uptime_secs: uptime.as_secs(),
+            num_blocks_executed: self.num_blocks_executed.load(Ordering::Relaxed),
+            secs_since_last_block: (last_block_millis > 0)
+                .then(|| (uptime.as_millis() as u64).saturating_sub(last_block_millis - 1) / 1000),
+            queue_depth: queue_depth as u64,
+        }
+    }
+}
+
+impl Default for ShardStatus {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+/// Sends a heartbeat to the coordinator every `interval`, for as long as the process lives.
+pub fn start_heartbeat_publisher(
+    shard_id: ShardId,
+    controller: &mut NetworkController,
+    coordinator_address: SocketAddr,
+    interval: Duration,
+    status: Arc<ShardStatus>,
+    command_rx: Receiver<Message>,
+) {
+    let heartbeat_tx =
+        controller.create_outbound_channel(coordinator_address, heartbeat_message_type(shard_id));
+    thread::Builder::new()
+        .name(format!("heartbeat-{}", shard_id))
+        .spawn(move || loop {
+            thread::sleep(interval);
+            let heartbeat = status.heartbeat(shard_id, command_rx.len());
+            let message = versioning::encode(&heartbeat).expect("Heartbeat must serialize.");
+            wire_trace::trace(Direction::Send, WireMessage::Heartbeat, shard_id, &message);
+            if heartbeat_tx.send(Message::new(message)).is_err() {
+                // The network controller was shut down.
+                break;
+            }
+        })
+        .expect("Failed to spawn heartbeat publisher thread.");
+}
+
+/// Watches the heartbeats of every shard, on a thread per shard, and marks shards that stop
+/// sending them as degraded.
+pub fn start_heartbeat_monitor(
+    controller: &mut NetworkController,
+    num_shards: usize,
+    timeout: Duration,
+) {
+    for shard_id in 0..num_shards {
+        let heartbeat_rx = controller.create_inbound_channel(heartbeat_message_type(shard_id));
+        thread::Builder::new()
+            .name(format!("heartbeat-monitor-{}", shard_id))
+            .spawn(move || monitor_shard(shard_id, heartbeat_rx, timeout))
+            .expect("Failed to spawn heartbeat monitor thread.");
+    }
+}
+
+fn monitor_shard(shard_id: ShardId, heartbeat_rx: Receiver<Message>, timeout: Duration) {
+    let shard_label = shard_id.to_string();
+    let set_gauge = |name: &str, value: f64| {
+        REMOTE_EXECUTOR_SHARD_HEARTBEAT
+            .with_label_values(&[&shard_label, name])
+            .set(value);
+    };
+    let set_degraded = |degraded: bool| {
+        if degraded {
+            DEGRADED_SHARDS.lock().insert(shard_id);
+        } else {
+            DEGRADED_SHARDS.lock().remove(&shard_id);
+        }
+        set_gauge("degraded", if degraded { 1.0 } else { 0.0 });
+    };
+    let mut last_heartbeat = Instant::now();
+    loop {
+        match heartbeat_rx.recv_timeout(timeout) {
+            Ok(message) => {
+                // Even a heartbeat we cannot decode (e.g. from a newer shard) proves it is alive.
+                if is_shard_degraded(shard_id) {
+                    info!(
+                        "Shard {} is sending heartbeats again after {:.1} secs, marking it healthy",
+                        shard_id,
+                        last_heartbeat.elapsed().as_secs_f64(),
+                    );
+                    set_degraded(false);
+                }
+                last_heartbeat = Instant::now();
+                wire_trace::trace(
+                    Direction::Receive,
+                    WireMessage::Heartbeat,
+                    shard_id,
+                    &message.data,
+                );
+                let heartbeat = match versioning::decode::<Heartbeat>(&message.data) {
+                    Ok(Decoded::Known(heartbeat)) => heartbeat,
+                    Ok(Decoded::Unknown { .. }) | Err(_) => {
+                        warn!("Cannot decode heartbeat of shard {}", shard_id);
+                        continue;
+                    },
+                };
+                set_gauge("uptime_secs", heartbeat.uptime_secs as f64);
+                set_gauge("num_blocks_executed", heartbeat.num_blocks_executed as f64);
+                set_gauge("queue_depth", heartbeat.queue_depth as f64);
+                if let Some(secs) = heartbeat.secs_since_last_block {
+                    set_gauge("secs_since_last_block", secs as f64);
+                }
+            },
+            Err(RecvTimeoutError::Timeout) => {
+                if !is_shard_degraded(shard_id) {
+                    warn!(
+                        "No heartbeat from shard {} for {:.1} secs, marking it degraded",
+                        shard_id,
+                        last_heartbeat.elapsed().as_secs_f64()
+                    );
+                    set_degraded(true);
+                }
+            },
+            Err(RecvTimeoutError::Disconnected) => break,
+        }
+    }
+}
+
+#[test]
+fn test_heartbeat_roundtrip() {
+    let status = ShardStatus::new();
+    let heartbeat = status.heartbeat(3, 2);
+    assert_eq!(heartbeat.num_blocks_executed, 0);
+    assert_eq!(heartbeat.secs_since_last_block, None);
+
+    status.record_block_executed();
+    let heartbeat = status.heartbeat(3, 2);
+    assert_eq!(heartbeat.num_blocks_executed, 1);
+    assert_eq!(heartbeat.secs_since_last_block, Some(0));
+
+    let encoded = versioning::encode(&heartbeat).unwrap();
+    match versioning::decode::<Heartbeat>(&encoded).unwrap() {
+        Decoded::Known(decoded) => {
+            assert_eq!(decoded.shard_id, 3);
+            assert_eq!(decoded.queue_depth, 2);
+            assert_eq!(decoded.num_blocks_executed, 1);
+        },
+        Decoded::Unknown { .. } => panic!("Heartbeat variant must be known"),
+    }
+}
@@ -0,0 +1,129 @@
+// Copyright © Aptos Foundation
+// SPDX-License-Identifier: Apache-2.0
+
+//! Hedged requests to standby executors, to cut the tail latency of multi-shard runs.
+//!
+//! Every shard can have a standby executor service, started with `--standby-address`. When a
+//! shard hasn't returned its result within a delay (a percentile of the recent shard latencies),
+//! the coordinator sends the same command to the standby of the shard, and takes whichever
+//! result arrives first. The result of the other one is discarded when it arrives.
+//!
+//! On the network, the standby of shard `i` is addressed as shard `num_shards + i`, so that its
+//! commands, results and state value requests don't mix with those of the primary. Only commands
+//! without cross-shard dependencies are hedged, as the cross-shard messages of a shard are only
+//! exchanged with the primaries of the other shards.
+
+use crate::metrics::REMOTE_EXECUTOR_HEDGED_REQUESTS;
+use aptos_infallible::Mutex;
+use aptos_types::{
+    block_executor::partitioner::{ShardId, SubBlocksForShard},
+    transaction::analyzed_transaction::AnalyzedTransaction,
+};
+use std::{collections::VecDeque, net::SocketAddr, time::Duration};
+
+/// Number of recent shard latencies the hedge delay is computed from.
+const LATENCY_WINDOW: usize = 100;
+/// Below this many latencies, the hedge delay is `min_delay`.
+const MIN_LATENCY_SAMPLES: usize = 10;
+
+#[derive(Clone, Debug)]
+pub struct HedgingConfig {
+    /// Address of the standby executor service of every shard, in shard order.
+    pub standby_addresses: Vec<SocketAddr>,
+    /// Percentile (in (0, 1]) of the recent shard latencies, after which a command is hedged.
+    pub delay_percentile: f64,
+    /// Commands are never hedged earlier than this.
+    pub min_delay: Duration,
+}
+
+/// Shard id the standby of `shard_id` is addressed by on the network.
+pub fn standby_network_id(num_shards: usize, shard_id: ShardId) -> usize {
+    num_shards + shard_id
+}
+
+/// Whether the command of a shard can be executed by its standby, i.e. it neither waits for nor
+/// sends cross-shard messages.
+pub fn is_hedgeable(sub_blocks: &SubBlocksForShard<AnalyzedTransaction>) -> bool {
+    sub_blocks.iter().all(|txn| {
+        txn.cross_shard_dependencies.required_edges().is_empty()
+            && txn.cross_shard_dependencies.dependent_edges().is_empty()
+    })
+}
+
+/// Recent latencies of the shards, from dispatching a command to receiving its result.
+pub struct HedgeDelay {
+    config: HedgingConfig,
+    latencies: Mutex<VecDeque<Duration>>,
+}
+
+impl HedgeDelay {
+    pub fn new(config: HedgingConfig) -> Self {
+        Self {
+            config,
+            latencies: Mutex::new(VecDeque::with_capacity(LATENCY_WINDOW)),
+        }
+    }
+
+    pub fn config(&self) -> &HedgingConfig {
+        &self.config
+    }
+
+    pub fn record_latency(&self, latency: Duration) {
+        let mut latencies = self.latencies.lock();
+        if latencies.len() == LATENCY_WINDOW {
+            latencies.pop_front();
+        }
+        latencies.push_back(latency);
+    }
+
+    /// How long to wait for the result of a shard, before hedging its command.
+    pub fn delay(&self) -> Duration {
+        let mut latencies = self.latencies.lock().iter().copied().collect::<Vec<_>>();
+        if latencies.len() < MIN_LATENCY_SAMPLES {
+            return self.config.min_delay;
+        }
+        latencies.sort();
+        let index = ((latencies.len() as f64 * self.config.delay_percentile).ceil() as usize)
+            .clamp(1, latencies.len())
+            - 1;
+        latencies[index].max(self.config.min_delay)
+    }
+}
+
+pub(crate) fn record_hedging_outcome(shard_id: ShardId, outcome: &str) {
+    REMOTE_EXECUTOR_HEDGED_REQUESTS
+        .with_label_values(&[&shard_id.to_string(), outcome])
+        .inc();
+}
+
+/// Returns the number of (dispatched, hedged, won by the standby) commands, over all shards.
+pub fn hedging_stats(num_shards: usize) -> (u64, u64, u64) {
+    let count = |outcome: &str| {
+        (0..num_shards)
+            .map(|shard_id| {
+                REMOTE_EXECUTOR_HEDGED_REQUESTS
+                    .with_label_values(&[&shard_id.to_string(), outcome])
+                    .get()
+            })
+            .sum()
+    };
+    (count("dispatched"), count("hedged"), count("standby_won"))
+}
+
+#[test]
+fn test_hedge_delay() {
+    let hedge_delay = HedgeDelay::new(HedgingConfig {
+        standby_addresses: vec![],
+        delay_percentile: 0.9,
+        min_delay: Duration::from_millis(5),
+    });
+    for millis in 1..=5 {
+        hedge_delay.record_latency(Duration::from_millis(millis));
+    }
+    // Too few samples.
+    assert_eq!(hedge_delay.delay(), Duration::from_millis(5));
+    for millis in 6..=20 {
+        hedge_delay.record_latency(Duration::from_millis(millis));
+    }
+    assert_eq!(hedge_delay.delay(), Duration::from_millis(18));
+}
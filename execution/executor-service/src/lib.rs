@@ -1,5 +1,6 @@
 // Copyright © Aptos Foundation
 // SPDX-License-Identifier: Apache-2.0
+use crate::cross_shard_relay::CrossShardRouting;
 use aptos_types::{
     block_executor::partitioner::{ShardId, SubBlocksForShard},
     state_store::{state_key::StateKey, state_value::StateValue},
@@ -8,7 +9,16 @@ use aptos_types::{
 };
 use serde::{Deserialize, Serialize};
 
+mod block_abort;
+pub mod capabilities;
+pub mod circuit_breaker;
+#[cfg(test)]
+mod compatibility_tests;
+pub mod cross_shard_relay;
 mod error;
+pub mod fragmentation;
+pub mod heartbeat;
+pub mod hedging;
 pub mod local_executor_helper;
 mod metrics;
 pub mod process_executor_service;
@@ -18,27 +28,97 @@ pub mod remote_executor_client;
 pub mod remote_executor_service;
 mod remote_state_view;
 mod remote_state_view_service;
+pub mod replay_bundle;
+pub mod resource_limits;
+pub mod saturation;
 #[cfg(test)]
 mod test_utils;
 #[cfg(test)]
 mod tests;
 #[cfg(test)]
 mod thread_executor_service;
+pub mod transport;
+pub mod versioning;
+pub mod warm_standby;
+pub mod wire_trace;
 
 #[derive(Clone, Debug, Deserialize, Serialize)]
 pub struct RemoteExecutionResult {
     pub inner: Result<Vec<Vec<TransactionOutput>>, VMStatus>,
+    /// Resource usage of the shard on the block, if the shard reports it.
+    pub stats: Option<ExecutionStats>,
+    /// Whether the shard rejected the block without executing any of it, so that the coordinator
+    /// has to abort the cross-shard messages the other shards wait for from it.
+    pub rejected: bool,
 }
 
 impl RemoteExecutionResult {
     pub fn new(inner: Result<Vec<Vec<TransactionOutput>>, VMStatus>) -> Self {
-        Self { inner }
+        Self {
+            inner,
+            stats: None,
+            rejected: false,
+        }
+    }
+
+    pub fn rejected(status: VMStatus) -> Self {
+        Self {
+            inner: Err(status),
+            stats: None,
+            rejected: true,
+        }
+    }
+
+    pub fn with_stats(
+        inner: Result<Vec<Vec<TransactionOutput>>, VMStatus>,
+        stats: ExecutionStats,
+    ) -> Self {
+        Self {
+            inner,
+            stats: Some(stats),
+            rejected: false,
+        }
     }
 }
 
+/// Resource usage of a shard on a block, measured by the shard from receiving the command to
+/// sending the result, so that the coordinator can tell the time spent on the shard apart from
+/// the time spent on the network.
+#[derive(Clone, Debug, Default, Deserialize, Eq, PartialEq, Serialize)]
+pub struct ExecutionStats {
+    pub execution_ms: u64,
+    pub num_threads: usize,
+    /// Growth of the peak resident set size of the shard process while executing the block.
+    pub peak_memory_delta_bytes: u64,
+    /// Number of state values read through the remote state view.
+    pub num_state_reads: u64,
+}
+
 #[derive(Clone, Debug, Deserialize, Serialize)]
 pub enum RemoteExecutionRequest {
     ExecuteBlock(ExecuteBlockCommand),
+    /// Sent to shards of protocol version `TOPOLOGY_PROTOCOL_VERSION` and later, which check the
+    /// topology against their own configuration and route their cross-shard messages as told.
+    ExecuteBlockInTopology(ExecuteBlockCommand, ShardTopology),
+}
+
+impl RemoteExecutionRequest {
+    pub(crate) fn command(&self) -> &ExecuteBlockCommand {
+        match self {
+            RemoteExecutionRequest::ExecuteBlock(command)
+            | RemoteExecutionRequest::ExecuteBlockInTopology(command, _) => command,
+        }
+    }
+}
+
+/// Where a command fits in the sharded execution of its block.
+#[derive(Clone, Copy, Debug, Deserialize, Eq, PartialEq, Serialize)]
+pub struct ShardTopology {
+    pub shard_id: ShardId,
+    pub num_shards: usize,
+    /// Number of partitioning rounds of the block, i.e. sub-blocks of every shard.
+    pub num_rounds: usize,
+    pub cross_shard_routing: CrossShardRouting,
 }
 
 #[derive(Clone, Debug, Deserialize, Serialize)]
@@ -1,12 +1,64 @@
 // Copyright © Aptos Foundation
 // SPDX-License-Identifier: Apache-2.0
 
-use aptos_executor_service::process_executor_service::ProcessExecutorService;
+use aptos_executor_service::{
+    process_executor_service::ProcessExecutorService,
+    replay_bundle::ReplayBundle,
+    resource_limits::ResourceLimits,
+    saturation::{generate_transfer_block_streams, SaturationBenchmark},
+    transport::Transport,
+    wire_trace::{self, WireTraceConfig},
+};
 use aptos_logger::info;
-use clap::Parser;
-use std::net::SocketAddr;
+use clap::{error::ErrorKind, CommandFactory, Parser, Subcommand};
+use std::{net::SocketAddr, path::PathBuf, sync::Arc, time::Duration};
 
 #[derive(Debug, Parser)]
+#[clap(args_conflicts_with_subcommands = true)]
+struct Cli {
+    #[clap(subcommand)]
+    command: Option<Command>,
+
+    #[clap(flatten)]
+    service: Option<Args>,
+}
+
+#[derive(Debug, Subcommand)]
+enum Command {
+    /// Execute the command of a replay bundle, written by the coordinator when a shard failed, on
+    /// the state values recorded in it. Exits with 0 if the failure is reproduced.
+    ReplayBundle {
+        bundle: PathBuf,
+
+        #[clap(long, default_value_t = 8)]
+        num_executor_threads: usize,
+    },
+    /// Hammer a single shard with several clients at once, each with an independent stream of
+    /// transfer blocks, and report the throughput and latency of every client, to find the
+    /// saturation point of the shard and how fairly it serves the clients. The shard has to be
+    /// started separately, as shard 0 out of 1, with this --coordinator-address.
+    Saturation {
+        #[clap(long)]
+        coordinator_address: SocketAddr,
+
+        #[clap(long)]
+        shard_address: SocketAddr,
+
+        #[clap(long, default_value_t = 4)]
+        num_clients: usize,
+
+        #[clap(long, default_value_t = 10)]
+        blocks_per_client: usize,
+
+        #[clap(long, default_value_t = 1000)]
+        block_size: usize,
+
+        #[clap(long, default_value_t = 8)]
+        concurrency_level: usize,
+    },
+}
+
+#[derive(Debug, clap::Args)]
 struct Args {
     #[clap(long, default_value_t = 8)]
     pub num_executor_threads: usize,
@@ -22,11 +74,126 @@ struct Args {
 
     #[clap(long)]
     pub coordinator_address: SocketAddr,
+
+    /// Reject blocks that would push the memory use of this service above this budget.
+    #[clap(long)]
+    pub max_memory_mb: Option<u64>,
+
+    /// Upper bound on the number of threads used to execute a block on this service.
+    #[clap(long)]
+    pub max_execution_threads: Option<usize>,
+
+    /// Transport used to talk to the coordinator and the other shards.
+    #[clap(long, value_enum, default_value_t = Transport::Tcp, ignore_case = true)]
+    pub transport: Transport,
+
+    /// Receive and decode the cross shard messages of later rounds in the background, while
+    /// earlier rounds execute.
+    #[clap(long)]
+    pub speculative_cross_shard_prefetch: bool,
+
+    /// How often to send a heartbeat (uptime, blocks executed, queue depth) to the coordinator,
+    /// 0 disables heartbeats.
+    #[clap(long, default_value_t = 1000)]
+    pub heartbeat_interval_ms: u64,
+
+    /// Run as the standby of --shard-id on this address, executing the commands the coordinator
+    /// hedges when the shard is slow, instead of as the shard itself.
+    #[clap(long)]
+    pub standby_address: Option<SocketAddr>,
+
+    /// As a standby, also receive the block stream of the shard and the state values it reads,
+    /// without executing them, so that the coordinator can fail the shard over to this service
+    /// without a cold start when it dies.
+    #[clap(long, requires = "standby_address")]
+    pub warm_standby: bool,
+
+    /// Log the type, size, checksum and time of every protocol message sent or received.
+    #[clap(long)]
+    pub trace_wire: bool,
+
+    /// With --trace-wire, also hex dump messages up to this many bytes.
+    #[clap(long, default_value_t = 0, requires = "trace_wire")]
+    pub trace_wire_hex_dump_max_bytes: usize,
 }
 
 fn main() {
-    let args = Args::parse();
+    let cli = Cli::parse();
     aptos_logger::Logger::new().init();
+    match (cli.command, cli.service) {
+        (
+            Some(Command::ReplayBundle {
+                bundle,
+                num_executor_threads,
+            }),
+            _,
+        ) => replay_bundle(bundle, num_executor_threads),
+        (
+            Some(Command::Saturation {
+                coordinator_address,
+                shard_address,
+                num_clients,
+                blocks_per_client,
+                block_size,
+                concurrency_level,
+            }),
+            _,
+        ) => {
+            let (state_view, block_streams) =
+                generate_transfer_block_streams(num_clients, blocks_per_client, block_size);
+            let mut benchmark = SaturationBenchmark::new(coordinator_address, shard_address);
+            benchmark
+                .run(Arc::new(state_view), block_streams, concurrency_level)
+                .print();
+            benchmark.shutdown();
+        },
+        (None, Some(args)) => run_service(args),
+        (None, None) => Cli::command()
+            .error(
+                ErrorKind::MissingRequiredArgument,
+                "--shard-id, --num-shards and --coordinator-address are required to run the service",
+            )
+            .exit(),
+    }
+}
+
+fn replay_bundle(path: PathBuf, num_executor_threads: usize) {
+    let bundle = ReplayBundle::load(&path).expect("Failed to load the replay bundle.");
+    let result = bundle
+        .replay(num_executor_threads)
+        .expect("Failed to replay the bundle.");
+    match result {
+        Err(error) if error == bundle.error => {
+            println!(
+                "Reproduced the failure of shard {}: {:?}",
+                bundle.shard_id, error
+            );
+        },
+        Err(error) => {
+            println!(
+                "Replay failed with {:?}, but shard {} failed with {:?}",
+                error, bundle.shard_id, bundle.error
+            );
+            std::process::exit(1);
+        },
+        Ok(outputs) => {
+            println!(
+                "Replay succeeded with {} transaction outputs, but shard {} failed with {:?}",
+                outputs.iter().map(Vec::len).sum::<usize>(),
+                bundle.shard_id,
+                bundle.error
+            );
+            std::process::exit(1);
+        },
+    }
+}
+
+fn run_service(args: Args) {
+    if args.trace_wire {
+        wire_trace::enable(WireTraceConfig {
+            hex_dump_max_bytes: args.trace_wire_hex_dump_max_bytes,
+        });
+    }
 
     let (tx, rx) = crossbeam_channel::unbounded();
     ctrlc::set_handler(move || {
@@ -40,6 +207,15 @@ fn main() {
         args.num_executor_threads,
         args.coordinator_address,
         args.remote_executor_addresses,
+        ResourceLimits {
+            max_memory_bytes: args.max_memory_mb.map(|mb| mb * 1024 * 1024),
+            max_execution_threads: args.max_execution_threads,
+        },
+        args.speculative_cross_shard_prefetch,
+        (args.heartbeat_interval_ms > 0).then(|| Duration::from_millis(args.heartbeat_interval_ms)),
+        args.standby_address,
+        args.warm_standby,
+        args.transport,
     );
 
     rx.recv()
@@ -49,6 +225,5 @@ fn main() {
 
 #[test]
 fn verify_tool() {
-    use clap::CommandFactory;
-    Args::command().debug_assert()
+    Cli::command().debug_assert()
 }
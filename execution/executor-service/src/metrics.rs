@@ -2,8 +2,8 @@
 // SPDX-License-Identifier: Apache-2.0
 
 use aptos_metrics_core::{
-    exponential_buckets, register_histogram_vec, register_int_counter_vec, HistogramVec,
-    IntCounterVec,
+    exponential_buckets, register_gauge_vec, register_histogram_vec, register_int_counter_vec,
+    GaugeVec, HistogramVec, IntCounterVec,
 };
 use once_cell::sync::Lazy;
 
@@ -22,7 +22,8 @@ pub static REMOTE_EXECUTOR_TIMER: Lazy<HistogramVec> = Lazy::new(|| {
          7. non_prefetch_wait: waiting for the remote state values that were not prefetched; \
          8. kv_req_deser: deserializing the remote key value requests; \
          9. kv_requests: processing the remote key value requests; \
-         10. kv_resp_ser: serializing the remote key value responses;",
+         10. kv_resp_ser: serializing the remote key value responses; \
+         11. cross_shard_wait: waiting for cross shard messages that were not prefetched yet;",
         // metric labels (dimensions)
         &["shard_id", "name"],
         exponential_buckets(/*start=*/ 1e-3, /*factor=*/ 2.0, /*count=*/ 20).unwrap(),
@@ -38,7 +39,196 @@ pub static REMOTE_EXECUTOR_REMOTE_KV_COUNT: Lazy<IntCounterVec> = Lazy::new(|| {
         "KV counts on a shard for: \
          1. kv_responses: the number of remote key value responses received on a shard; \
          2. non_prefetch_kv: the number of remote key value responses received on a shard that were not prefetched; \
-         3. prefetch_kv: the number of remote key value responses received on a shard that were prefetched; ",
+         3. prefetch_kv: the number of remote key value responses received on a shard that were prefetched; \
+         4. warm_kv: the number of state values a promoted warm standby already had mirrored; ",
+        // metric labels (dimensions)
+        &["shard_id", "name"],
+    )
+    .unwrap()
+});
+
+pub static REMOTE_EXECUTOR_CROSS_SHARD_PREFETCH: Lazy<IntCounterVec> = Lazy::new(|| {
+    register_int_counter_vec!(
+        // metric name
+        "remote_executor_cross_shard_prefetch",
+        // metric description
+        "Cross shard messages received by a round with speculative prefetch enabled: \
+         1. hit: the message was already received and decoded, hiding its latency; \
+         2. miss: the round had to wait for the message; ",
+        // metric labels (dimensions)
+        &["shard_id", "name"],
+    )
+    .unwrap()
+});
+
+pub static REMOTE_EXECUTOR_REQUEST_FRAGMENTS: Lazy<IntCounterVec> = Lazy::new(|| {
+    register_int_counter_vec!(
+        // metric name
+        "remote_executor_request_fragments",
+        // metric description
+        "The number of fragments the requests to a shard that exceed its max payload were split into",
+        // metric labels (dimensions)
+        &["shard_id"],
+    )
+    .unwrap()
+});
+
+pub static REMOTE_EXECUTOR_CROSS_SHARD_RELAYED: Lazy<IntCounterVec> = Lazy::new(|| {
+    register_int_counter_vec!(
+        // metric name
+        "remote_executor_cross_shard_relayed",
+        // metric description
+        "The number of cross-shard messages the coordinator forwarded from a shard to another",
+        // metric labels (dimensions)
+        &["src_shard_id", "dst_shard_id"],
+    )
+    .unwrap()
+});
+
+pub static REMOTE_EXECUTOR_RESULT_AGGREGATION_SECONDS: Lazy<HistogramVec> = Lazy::new(|| {
+    register_histogram_vec!(
+        // metric name
+        "remote_executor_result_aggregation_seconds",
+        // metric description
+        "The time spent on the coordinator on: \
+         1. get_results: waiting for and decoding the results of all shards, after dispatching the block; \
+         2. post_last_result: decoding results after the last shard result arrived, i.e. on the critical path;",
+        // metric labels (dimensions)
+        &["name"],
+        exponential_buckets(/*start=*/ 1e-3, /*factor=*/ 2.0, /*count=*/ 20).unwrap(),
+    )
+    .unwrap()
+});
+
+pub static REMOTE_EXECUTOR_SERIALIZATION_SECONDS: Lazy<HistogramVec> = Lazy::new(|| {
+    register_histogram_vec!(
+        // metric name
+        "remote_executor_serialization_seconds",
+        // metric description
+        "The time spent on the coordinator, per shard, on: \
+         1. encode_request: encoding the command of the shard, on the serialization pool; \
+         2. decode_result: decoding the result of the shard, on the serialization pool; \
+         3. dispatch_wait: waiting for the command of the shard to be encoded, when dispatching it;",
+        // metric labels (dimensions)
+        &["shard_id", "name"],
+        exponential_buckets(/*start=*/ 1e-5, /*factor=*/ 2.0, /*count=*/ 24).unwrap(),
+    )
+    .unwrap()
+});
+
+pub static REMOTE_EXECUTOR_RESOURCE_UTILIZATION: Lazy<GaugeVec> = Lazy::new(|| {
+    register_gauge_vec!(
+        // metric name
+        "remote_executor_resource_utilization",
+        // metric description
+        "Resource utilization of a shard when receiving a block: \
+         1. memory_bytes: the resident set size of the shard process; \
+         2. memory_budget_fraction: the resident set size as a fraction of the memory budget; \
+         3. execution_threads: the number of threads used to execute the block; ",
+        // metric labels (dimensions)
+        &["shard_id", "name"],
+    )
+    .unwrap()
+});
+
+pub static REMOTE_EXECUTOR_SHARD_HEARTBEAT: Lazy<GaugeVec> = Lazy::new(|| {
+    register_gauge_vec!(
+        // metric name
+        "remote_executor_shard_heartbeat",
+        // metric description
+        "Liveness of a shard, as seen by the coordinator from its heartbeats: \
+         1. degraded: 1 if no heartbeat arrived within the timeout, 0 otherwise; \
+         2. uptime_secs: the uptime of the shard process; \
+         3. num_blocks_executed: the number of blocks the shard executed; \
+         4. secs_since_last_block: the time since the shard sent its last execution result; \
+         5. queue_depth: the number of commands waiting to be executed on the shard; ",
+        // metric labels (dimensions)
+        &["shard_id", "name"],
+    )
+    .unwrap()
+});
+
+pub static REMOTE_EXECUTOR_REJECTED_BLOCKS: Lazy<IntCounterVec> = Lazy::new(|| {
+    register_int_counter_vec!(
+        // metric name
+        "remote_executor_rejected_blocks",
+        // metric description
+        "The number of blocks a shard rejected because they would exceed its memory budget",
+        // metric labels (dimensions)
+        &["shard_id"],
+    )
+    .unwrap()
+});
+
+pub static REMOTE_EXECUTOR_CIRCUIT_BREAKER: Lazy<IntCounterVec> = Lazy::new(|| {
+    register_int_counter_vec!(
+        // metric name
+        "remote_executor_circuit_breaker",
+        // metric description
+        "The number of transitions of the circuit breaker of a shard into each state: \
+         1. open: blocks are failed without being dispatched; \
+         2. half_open: a probe block is dispatched; \
+         3. closed: blocks are dispatched normally; ",
+        // metric labels (dimensions)
+        &["shard_id", "state"],
+    )
+    .unwrap()
+});
+
+pub static REMOTE_EXECUTOR_RESULT_TIMEOUTS: Lazy<IntCounterVec> = Lazy::new(|| {
+    register_int_counter_vec!(
+        // metric name
+        "remote_executor_result_timeouts",
+        // metric description
+        "The number of blocks on which a shard didn't return its result within the result \
+         timeout of its circuit breaker",
+        // metric labels (dimensions)
+        &["shard_id"],
+    )
+    .unwrap()
+});
+
+pub static REMOTE_EXECUTOR_HEDGED_REQUESTS: Lazy<IntCounterVec> = Lazy::new(|| {
+    register_int_counter_vec!(
+        // metric name
+        "remote_executor_hedged_requests",
+        // metric description
+        "The number of commands of a shard, by outcome: \
+         1. dispatched: commands dispatched to the primary of the shard; \
+         2. hedged: commands also sent to the standby of the shard; \
+         3. standby_won: hedged commands whose result came from the standby first; \
+         4. not_hedgeable: slow commands not hedged for having cross-shard dependencies; ",
+        // metric labels (dimensions)
+        &["shard_id", "outcome"],
+    )
+    .unwrap()
+});
+
+pub static REMOTE_EXECUTOR_SHARD_LATENCY_BREAKDOWN_SECONDS: Lazy<HistogramVec> = Lazy::new(|| {
+    register_histogram_vec!(
+        // metric name
+        "remote_executor_shard_latency_breakdown_seconds",
+        // metric description
+        "The latency of a block on a shard, as seen by the coordinator, for shards reporting execution stats: \
+         1. end_to_end: from dispatching the command to receiving the result; \
+         2. shard: from the shard receiving the command to it sending the result, as reported by the shard; \
+         3. network: the rest of end_to_end, i.e. transfer and queueing of the command and the result;",
+        // metric labels (dimensions)
+        &["shard_id", "name"],
+        exponential_buckets(/*start=*/ 1e-3, /*factor=*/ 2.0, /*count=*/ 20).unwrap(),
+    )
+    .unwrap()
+});
+
+pub static REMOTE_EXECUTOR_SHARD_BLOCK_STATS: Lazy<GaugeVec> = Lazy::new(|| {
+    register_gauge_vec!(
+        // metric name
+        "remote_executor_shard_block_stats",
+        // metric description
+        "Resource usage of a shard on the last block, as reported by the shard: \
+         1. num_threads: the number of threads the block was executed with; \
+         2. peak_memory_delta_bytes: the growth of the peak resident set size of the shard; \
+         3. num_state_reads: the number of state values read by the block; ",
         // metric labels (dimensions)
         &["shard_id", "name"],
     )
@@ -1,11 +1,13 @@
 // Copyright © Aptos Foundation
 
-use crate::remote_executor_service::ExecutorService;
+use crate::{
+    remote_executor_service::ExecutorService, resource_limits::ResourceLimits, transport::Transport,
+};
 use aptos_logger::info;
 use aptos_push_metrics::MetricsPusher;
 use aptos_types::block_executor::partitioner::ShardId;
 use aptos_vm::AptosVM;
-use std::net::SocketAddr;
+use std::{net::SocketAddr, time::Duration};
 
 /// An implementation of the remote executor service that runs in a standalone process.
 pub struct ProcessExecutorService {
@@ -19,11 +21,18 @@ impl ProcessExecutorService {
         num_threads: usize,
         coordinator_address: SocketAddr,
         remote_shard_addresses: Vec<SocketAddr>,
+        resource_limits: ResourceLimits,
+        speculative_cross_shard_prefetch: bool,
+        heartbeat_interval: Option<Duration>,
+        standby_address: Option<SocketAddr>,
+        warm_standby: bool,
+        transport: Transport,
     ) -> Self {
-        let self_address = remote_shard_addresses[shard_id];
+        let self_address = standby_address.unwrap_or(remote_shard_addresses[shard_id]);
+        let num_threads = resource_limits.num_threads(num_threads);
         info!(
-            "Starting process remote executor service on {}; coordinator address: {}, other shard addresses: {:?}; num threads: {}",
-            self_address, coordinator_address, remote_shard_addresses, num_threads
+            "Starting process remote executor service on {}{} over {:?}; coordinator address: {}, other shard addresses: {:?}; num threads: {}; resource limits: {:?}; speculative cross shard prefetch: {}; heartbeat interval: {:?}",
+            self_address, match (standby_address, warm_standby) { (Some(_), true) => " (warm standby)", (Some(_), false) => " (standby)", _ => "" }, transport, coordinator_address, remote_shard_addresses, num_threads, resource_limits, speculative_cross_shard_prefetch, heartbeat_interval
         );
         aptos_node_resource_metrics::register_node_metrics_collector();
         let _mp = MetricsPusher::start_for_local_run(
@@ -38,6 +47,12 @@ impl ProcessExecutorService {
             self_address,
             coordinator_address,
             remote_shard_addresses,
+            resource_limits,
+            speculative_cross_shard_prefetch,
+            heartbeat_interval,
+            standby_address.is_some(),
+            warm_standby,
+            transport,
         );
         executor_service.start();
         Self { executor_service }
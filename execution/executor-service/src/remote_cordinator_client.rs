@@ -1,51 +1,120 @@
 // Copyright © Aptos Foundation
 // SPDX-License-Identifier: Apache-2.0
 use crate::{
-    metrics::REMOTE_EXECUTOR_TIMER, remote_state_view::RemoteStateViewClient, ExecuteBlockCommand,
-    RemoteExecutionRequest, RemoteExecutionResult,
+    cross_shard_relay::{CrossShardRouting, CurrentRouting},
+    error::Error,
+    fragmentation::{self, Reassembler, MAX_PAYLOAD_BYTES},
+    heartbeat::ShardStatus,
+    metrics::REMOTE_EXECUTOR_TIMER,
+    remote_state_view::RemoteStateViewClient,
+    resource_limits::{self, ResourceLimits},
+    versioning::{self, Decoded, REQUEST_FRAGMENT_VARIANT, RESULT_FRAGMENT_VARIANT},
+    warm_standby::{self, WarmCache},
+    wire_trace::{self, Direction, WireMessage},
+    ExecuteBlockCommand, ExecutionStats, RemoteExecutionRequest, RemoteExecutionResult,
+    ShardTopology,
 };
+use aptos_infallible::Mutex;
+use aptos_logger::warn;
 use aptos_secure_net::network_controller::{Message, NetworkController};
 use aptos_types::{
-    block_executor::partitioner::ShardId, state_store::state_key::StateKey,
-    transaction::TransactionOutput, vm_status::VMStatus,
+    block_executor::partitioner::{ShardId, MAX_ALLOWED_PARTITIONING_ROUNDS},
+    state_store::state_key::StateKey,
+    transaction::TransactionOutput,
+    vm_status::{StatusCode, VMStatus},
 };
 use aptos_vm::sharded_block_executor::{
     coordinator_client::CoordinatorClient, ExecutorShardCommand,
 };
 use crossbeam_channel::{Receiver, Sender};
 use rayon::prelude::*;
-use std::{net::SocketAddr, sync::Arc};
+use std::{
+    net::SocketAddr,
+    sync::{
+        atomic::{AtomicU64, Ordering},
+        Arc,
+    },
+    time::Instant,
+};
+
+/// The block the shard is executing, to report its resource usage along with its result.
+struct BlockInProgress {
+    received_at: Instant,
+    num_threads: usize,
+    start_rss_bytes: Option<u64>,
+    /// Protocol version of the coordinator, stats are only sent to coordinators that know them.
+    coordinator_version: u32,
+}
 
 pub struct RemoteCoordinatorClient {
     state_view_client: Arc<RemoteStateViewClient>,
     command_rx: Receiver<Message>,
     result_tx: Sender<Message>,
     shard_id: ShardId,
+    num_shards: usize,
+    resource_limits: ResourceLimits,
+    // Routing of the cross-shard messages of the command being executed.
+    cross_shard_routing: Arc<CurrentRouting>,
+    status: Arc<ShardStatus>,
+    block_in_progress: Mutex<Option<BlockInProgress>>,
+    /// Fragments of the request being received, if it is sent in fragments.
+    reassembler: Mutex<Reassembler>,
+    /// Id of the next result sent in fragments.
+    next_result_id: AtomicU64,
+    /// The block stream mirrored to this service, if it is a warm standby.
+    warm_cache: Option<Arc<Mutex<WarmCache>>>,
 }
 
 impl RemoteCoordinatorClient {
+    /// `network_id` is the shard id the coordinator addresses this service by, which differs from
+    /// `shard_id` for standby services. Warm standbys also receive the block stream of the shard.
     pub fn new(
         shard_id: ShardId,
+        num_shards: usize,
+        network_id: usize,
         controller: &mut NetworkController,
         coordinator_address: SocketAddr,
+        resource_limits: ResourceLimits,
+        cross_shard_routing: Arc<CurrentRouting>,
+        warm_standby: bool,
     ) -> Self {
-        let execute_command_type = format!("execute_command_{}", shard_id);
-        let execute_result_type = format!("execute_result_{}", shard_id);
+        resource_limits.check_supported();
+        let execute_command_type = format!("execute_command_{}", network_id);
+        let execute_result_type = format!("execute_result_{}", network_id);
         let command_rx = controller.create_inbound_channel(execute_command_type);
         let result_tx =
             controller.create_outbound_channel(coordinator_address, execute_result_type);
 
         let state_view_client =
-            RemoteStateViewClient::new(shard_id, controller, coordinator_address);
+            RemoteStateViewClient::new(network_id, controller, coordinator_address);
+        let warm_cache = warm_standby
+            .then(|| warm_standby::start_mirror_receiver(shard_id, network_id, controller));
 
         Self {
             state_view_client: Arc::new(state_view_client),
             command_rx,
             result_tx,
             shard_id,
+            num_shards,
+            resource_limits,
+            cross_shard_routing,
+            status: Arc::new(ShardStatus::new()),
+            block_in_progress: Mutex::new(None),
+            reassembler: Mutex::new(Reassembler::new(REQUEST_FRAGMENT_VARIANT)),
+            next_result_id: AtomicU64::new(0),
+            warm_cache,
         }
     }
 
+    pub(crate) fn status(&self) -> Arc<ShardStatus> {
+        self.status.clone()
+    }
+
+    /// Receiver of the commands from the coordinator, to tell how many are queued up.
+    pub(crate) fn command_rx(&self) -> Receiver<Message> {
+        self.command_rx.clone()
+    }
+
     // Extract all the state keys from the execute block command. It is possible that there are duplicate state keys.
     // We are not de-duplicating them here to avoid the overhead of deduplication. The state view server will deduplicate
     // the state keys.
@@ -74,47 +143,260 @@ impl RemoteCoordinatorClient {
             })
             .collect::<Vec<StateKey>>()
     }
-}
 
-impl CoordinatorClient<RemoteStateViewClient> for RemoteCoordinatorClient {
-    fn receive_execute_command(&self) -> ExecutorShardCommand<RemoteStateViewClient> {
-        match self.command_rx.recv() {
-            Ok(message) => {
+    /// Checks that a command was meant for this shard, and fits the rounds the cross-shard client
+    /// has channels for.
+    fn check_topology(
+        &self,
+        command: &ExecuteBlockCommand,
+        topology: &ShardTopology,
+    ) -> Result<(), String> {
+        if topology.shard_id != self.shard_id || topology.num_shards != self.num_shards {
+            return Err(format!(
+                "Shard {} of {} received a command for shard {} of {}",
+                self.shard_id, self.num_shards, topology.shard_id, topology.num_shards
+            ));
+        }
+        if topology.num_rounds != command.sub_blocks.num_sub_blocks()
+            || topology.num_rounds > MAX_ALLOWED_PARTITIONING_ROUNDS
+        {
+            return Err(format!(
+                "Shard {} received a command with {} sub-blocks for {} rounds, at most {} rounds are supported",
+                self.shard_id,
+                command.sub_blocks.num_sub_blocks(),
+                topology.num_rounds,
+                MAX_ALLOWED_PARTITIONING_ROUNDS
+            ));
+        }
+        Ok(())
+    }
+
+    fn execution_stats(&self, block: &BlockInProgress) -> ExecutionStats {
+        let peak_memory_delta_bytes =
+            match (block.start_rss_bytes, resource_limits::peak_rss_bytes()) {
+                (Some(start), Some(peak)) => peak.saturating_sub(start),
+                _ => 0,
+            };
+        ExecutionStats {
+            execution_ms: block.received_at.elapsed().as_millis() as u64,
+            num_threads: block.num_threads,
+            peak_memory_delta_bytes,
+            num_state_reads: self.state_view_client.num_state_reads(),
+        }
+    }
+
+    /// Receives the next request, reassembled if it was sent in fragments. `None` once the
+    /// coordinator is gone. Failures come with the protocol version of the coordinator.
+    fn recv_request(&self) -> Option<Result<Vec<u8>, (Error, u32)>> {
+        loop {
+            let message = self.command_rx.recv().ok()?;
+            wire_trace::trace(
+                Direction::Receive,
+                WireMessage::ExecuteCommand,
+                self.shard_id,
+                &message.data,
+            );
+            let coordinator_version =
+                versioning::envelope_header(&message.data).map_or(0, |(version, _)| version);
+            match self.reassembler.lock().push(message.to_bytes()) {
+                Ok(Some(request_bytes)) => return Some(Ok(request_bytes)),
+                Ok(None) => continue,
+                Err(err) => return Some(Err((err, coordinator_version))),
+            }
+        }
+    }
+
+    fn try_receive_execute_command(&self) -> Option<ExecutorShardCommand<RemoteStateViewClient>> {
+        match self.recv_request() {
+            Some(Err((err, coordinator_version))) => {
+                let reason = format!(
+                    "Shard {} failed to reassemble request: {}",
+                    self.shard_id, err
+                );
+                warn!("{}", reason);
+                self.send_rejection(
+                    VMStatus::error(StatusCode::UNKNOWN_STATUS, Some(reason)),
+                    coordinator_version,
+                );
+                None
+            },
+            Some(Ok(request_bytes)) => {
+                let received_at = Instant::now();
                 let _rx_timer = REMOTE_EXECUTOR_TIMER
                     .with_label_values(&[&self.shard_id.to_string(), "cmd_rx"])
                     .start_timer();
                 let bcs_deser_timer = REMOTE_EXECUTOR_TIMER
                     .with_label_values(&[&self.shard_id.to_string(), "cmd_rx_bcs_deser"])
                     .start_timer();
-                let request: RemoteExecutionRequest = bcs::from_bytes(&message.data).unwrap();
+                let decoded = versioning::decode::<RemoteExecutionRequest>(&request_bytes);
                 drop(bcs_deser_timer);
+                let coordinator_version =
+                    versioning::envelope_header(&request_bytes).map_or(0, |(version, _)| version);
 
-                match request {
+                // Requests we cannot handle (e.g. sent by a newer coordinator) are rejected, so
+                // that neither the coordinator nor the other shards wait for this one forever.
+                let request = match decoded {
+                    Ok(Decoded::Known(request)) => request,
+                    Ok(Decoded::Unknown { version, variant }) => {
+                        let reason = format!(
+                            "Shard {} cannot handle request variant {} of protocol version {} (own version {})",
+                            self.shard_id,
+                            variant,
+                            version,
+                            versioning::PROTOCOL_VERSION
+                        );
+                        warn!("{}", reason);
+                        self.send_rejection(
+                            VMStatus::error(StatusCode::UNKNOWN_STATUS, Some(reason)),
+                            coordinator_version,
+                        );
+                        return None;
+                    },
+                    Err(err) => {
+                        let reason =
+                            format!("Shard {} failed to decode request: {}", self.shard_id, err);
+                        warn!("{}", reason);
+                        self.send_rejection(
+                            VMStatus::error(StatusCode::UNKNOWN_STATUS, Some(reason)),
+                            coordinator_version,
+                        );
+                        return None;
+                    },
+                };
+
+                // Commands of coordinators that don't send a topology use direct routing.
+                let (command, routing) = match request {
                     RemoteExecutionRequest::ExecuteBlock(command) => {
-                        let init_prefetch_timer = REMOTE_EXECUTOR_TIMER
-                            .with_label_values(&[&self.shard_id.to_string(), "init_prefetch"])
-                            .start_timer();
-                        let state_keys = Self::extract_state_keys(&command);
-                        self.state_view_client.init_for_block(state_keys);
-                        drop(init_prefetch_timer);
-
-                        let (sub_blocks, concurrency, gas_limit) = command.into();
-                        ExecutorShardCommand::ExecuteSubBlocks(
-                            self.state_view_client.clone(),
-                            sub_blocks,
-                            concurrency,
-                            gas_limit,
-                        )
+                        (command, CrossShardRouting::Direct)
+                    },
+                    RemoteExecutionRequest::ExecuteBlockInTopology(command, topology) => {
+                        if let Err(reason) = self.check_topology(&command, &topology) {
+                            warn!("{}", reason);
+                            self.send_rejection(
+                                VMStatus::error(StatusCode::UNKNOWN_STATUS, Some(reason)),
+                                coordinator_version,
+                            );
+                            return None;
+                        }
+                        (command, topology.cross_shard_routing)
                     },
+                };
+                self.cross_shard_routing.set(routing);
+
+                let concurrency = self.resource_limits.num_threads(command.concurrency_level);
+                if let Err((status_code, reason)) = self.resource_limits.admit_block(
+                    self.shard_id,
+                    request_bytes.len(),
+                    concurrency,
+                ) {
+                    self.send_rejection(
+                        VMStatus::error(status_code, Some(reason)),
+                        coordinator_version,
+                    );
+                    return None;
                 }
+                resource_limits::reset_peak_rss();
+                *self.block_in_progress.lock() = Some(BlockInProgress {
+                    received_at,
+                    num_threads: concurrency,
+                    start_rss_bytes: resource_limits::process_rss_bytes(),
+                    coordinator_version,
+                });
+
+                let init_prefetch_timer = REMOTE_EXECUTOR_TIMER
+                    .with_label_values(&[&self.shard_id.to_string(), "init_prefetch"])
+                    .start_timer();
+                let state_keys = Self::extract_state_keys(&command);
+                // A promoted warm standby already has the state values of the block that
+                // was in flight on the primary.
+                match self
+                    .warm_cache
+                    .as_ref()
+                    .and_then(|warm_cache| warm_cache.lock().take_if_mirrored(&request_bytes))
+                {
+                    Some(state_values) => self
+                        .state_view_client
+                        .init_for_block_with_values(state_keys, state_values),
+                    None => self.state_view_client.init_for_block(state_keys),
+                }
+                drop(init_prefetch_timer);
+
+                let (sub_blocks, _, gas_limit) = command.into();
+                Some(ExecutorShardCommand::ExecuteSubBlocks(
+                    self.state_view_client.clone(),
+                    sub_blocks,
+                    concurrency,
+                    gas_limit,
+                ))
             },
-            Err(_) => ExecutorShardCommand::Stop,
+            None => Some(ExecutorShardCommand::Stop),
+        }
+    }
+
+    /// Answers a block rejected before executing any of it. Coordinators that know rejected
+    /// results abort the cross-shard messages the other shards wait for from this one, the others
+    /// only get the error.
+    fn send_rejection(&self, status: VMStatus, coordinator_version: u32) {
+        if coordinator_version >= versioning::REJECTION_PROTOCOL_VERSION {
+            self.send_result(RemoteExecutionResult::rejected(status), coordinator_version);
+        } else {
+            self.send_result(RemoteExecutionResult::new(Err(status)), coordinator_version);
+        }
+    }
+
+    /// Sends a result to the coordinator, in fragments if it exceeds the max payload of the
+    /// transport and the coordinator reassembles them.
+    fn send_result(&self, result: RemoteExecutionResult, coordinator_version: u32) {
+        let output_message = versioning::encode(&result).unwrap();
+        let messages = if coordinator_version >= versioning::RESULT_FRAGMENTATION_PROTOCOL_VERSION {
+            fragmentation::fragment(
+                output_message,
+                self.next_result_id.fetch_add(1, Ordering::Relaxed),
+                MAX_PAYLOAD_BYTES,
+                RESULT_FRAGMENT_VARIANT,
+            )
+            .expect("Fragments must serialize.")
+        } else {
+            vec![output_message]
+        };
+        for message in messages {
+            wire_trace::trace(
+                Direction::Send,
+                WireMessage::ExecuteResult,
+                self.shard_id,
+                &message,
+            );
+            self.result_tx.send(Message::new(message)).unwrap();
+        }
+    }
+}
+
+impl CoordinatorClient<RemoteStateViewClient> for RemoteCoordinatorClient {
+    fn receive_execute_command(&self) -> ExecutorShardCommand<RemoteStateViewClient> {
+        // Blocks rejected for exceeding the resource limits are answered with an error right
+        // away, and we keep waiting for the next command.
+        loop {
+            if let Some(command) = self.try_receive_execute_command() {
+                return command;
+            }
         }
     }
 
     fn send_execution_result(&self, result: Result<Vec<Vec<TransactionOutput>>, VMStatus>) {
-        let remote_execution_result = RemoteExecutionResult::new(result);
-        let output_message = bcs::to_bytes(&remote_execution_result).unwrap();
-        self.result_tx.send(Message::new(output_message)).unwrap();
+        if result.is_ok() {
+            self.status.record_block_executed();
+        }
+        let block = self.block_in_progress.lock().take();
+        let coordinator_version = block.as_ref().map_or(0, |block| block.coordinator_version);
+        let remote_execution_result = match block {
+            Some(block)
+                if block.coordinator_version >= versioning::EXECUTION_STATS_PROTOCOL_VERSION =>
+            {
+                let stats = self.execution_stats(&block);
+                RemoteExecutionResult::with_stats(result, stats)
+            },
+            _ => RemoteExecutionResult::new(result),
+        };
+        self.send_result(remote_execution_result, coordinator_version);
     }
 }
@@ -1,32 +1,63 @@
 // Copyright © Aptos Foundation
 // SPDX-License-Identifier: Apache-2.0
+use crate::{
+    cross_shard_relay::{self, CrossShardRouting, CurrentRouting, RelayedCrossShardMsg},
+    metrics::{REMOTE_EXECUTOR_CROSS_SHARD_PREFETCH, REMOTE_EXECUTOR_TIMER},
+    wire_trace::{self, Direction, WireMessage},
+};
 use aptos_secure_net::network_controller::{Message, NetworkController};
 use aptos_types::block_executor::partitioner::{RoundId, ShardId, MAX_ALLOWED_PARTITIONING_ROUNDS};
 use aptos_vm::sharded_block_executor::{
     cross_shard_client::CrossShardClient, messages::CrossShardMsg,
 };
-use crossbeam_channel::{Receiver, Sender};
+use crossbeam_channel::{Receiver, Sender, TryRecvError};
 use std::{
     net::SocketAddr,
     sync::{Arc, Mutex},
+    thread,
 };
 
+/// Inbound cross-shard messages of a round.
+enum InboundMessages {
+    /// Messages are decoded when the round asks for them.
+    Raw(Receiver<Message>),
+    /// Messages are received and decoded ahead of time by a prefetch thread.
+    Prefetched(Receiver<CrossShardMsg>),
+}
+
 pub struct RemoteCrossShardClient {
+    shard_id: ShardId,
     // The senders of cross-shard messages to other shards per round.
     message_txs: Arc<Vec<Vec<Mutex<Sender<Message>>>>>,
     // The receivers of cross shard messages from other shards per round.
-    message_rxs: Arc<Vec<Mutex<Receiver<Message>>>>,
+    message_rxs: Arc<Vec<Mutex<InboundMessages>>>,
+    // The sender of cross shard messages to the coordinator, to relay to other shards.
+    relay_tx: Mutex<Sender<Message>>,
+    routing: Arc<CurrentRouting>,
 }
 
 impl RemoteCrossShardClient {
-    pub fn new(controller: &mut NetworkController, shard_addresses: Vec<SocketAddr>) -> Self {
+    /// If `speculative_prefetch` is set, the cross-shard messages of every round are received and
+    /// decoded in the background as soon as they arrive, so that the values later rounds depend on
+    /// are ready by the time those rounds start, instead of being decoded on their critical path.
+    ///
+    /// Outbound messages are sent to their shard directly or through the coordinator, as `routing`
+    /// says for the command being executed. Inbound messages arrive the same way either way.
+    pub fn new(
+        shard_id: ShardId,
+        controller: &mut NetworkController,
+        coordinator_address: SocketAddr,
+        shard_addresses: Vec<SocketAddr>,
+        speculative_prefetch: bool,
+        routing: Arc<CurrentRouting>,
+    ) -> Self {
         let mut message_txs = vec![];
         let mut message_rxs = vec![];
         // Create outbound channels for each shard per round.
         for remote_address in shard_addresses.iter() {
             let mut txs = vec![];
             for round in 0..MAX_ALLOWED_PARTITIONING_ROUNDS {
-                let message_type = format!("cross_shard_{}", round);
+                let message_type = cross_shard_relay::cross_shard_message_type(round);
                 let tx = controller.create_outbound_channel(*remote_address, message_type);
                 txs.push(Mutex::new(tx));
             }
@@ -35,16 +66,55 @@ impl RemoteCrossShardClient {
 
         // Create inbound channels for each round
         for round in 0..MAX_ALLOWED_PARTITIONING_ROUNDS {
-            let message_type = format!("cross_shard_{}", round);
+            let message_type = cross_shard_relay::cross_shard_message_type(round);
             let rx = controller.create_inbound_channel(message_type);
-            message_rxs.push(Mutex::new(rx));
+            let inbound = if speculative_prefetch {
+                InboundMessages::Prefetched(Self::spawn_prefetcher(shard_id, round, rx))
+            } else {
+                InboundMessages::Raw(rx)
+            };
+            message_rxs.push(Mutex::new(inbound));
         }
+        let relay_tx = controller.create_outbound_channel(
+            coordinator_address,
+            cross_shard_relay::relay_message_type(shard_id),
+        );
 
         Self {
+            shard_id,
             message_txs: Arc::new(message_txs),
             message_rxs: Arc::new(message_rxs),
+            relay_tx: Mutex::new(relay_tx),
+            routing,
         }
     }
+
+    fn spawn_prefetcher(
+        shard_id: ShardId,
+        round: RoundId,
+        rx: Receiver<Message>,
+    ) -> Receiver<CrossShardMsg> {
+        let (decoded_tx, decoded_rx) = crossbeam_channel::unbounded();
+        thread::Builder::new()
+            .name(format!("cross-shard-prefetch-{}-{}", shard_id, round))
+            // Exits once the network controller drops the inbound channel.
+            .spawn(move || {
+                while let Ok(message) = rx.recv() {
+                    wire_trace::trace(
+                        Direction::Receive,
+                        WireMessage::CrossShard,
+                        shard_id,
+                        &message.data,
+                    );
+                    let msg: CrossShardMsg = bcs::from_bytes(&message.to_bytes()).unwrap();
+                    if decoded_tx.send(msg).is_err() {
+                        break;
+                    }
+                }
+            })
+            .expect("Failed to spawn cross shard prefetch thread");
+        decoded_rx
+    }
 }
 
 impl CrossShardClient for RemoteCrossShardClient {
@@ -54,14 +124,74 @@ impl CrossShardClient for RemoteCrossShardClient {
 
     fn send_cross_shard_msg(&self, shard_id: ShardId, round: RoundId, msg: CrossShardMsg) {
         let input_message = bcs::to_bytes(&msg).unwrap();
+        if self.routing.get() == CrossShardRouting::ViaCoordinator {
+            let relayed = bcs::to_bytes(&RelayedCrossShardMsg {
+                dst_shard: shard_id,
+                round,
+                msg: input_message,
+            })
+            .unwrap();
+            wire_trace::trace(
+                Direction::Send,
+                WireMessage::CrossShardRelay,
+                self.shard_id,
+                &relayed,
+            );
+            self.relay_tx
+                .lock()
+                .unwrap()
+                .send(Message::new(relayed))
+                .unwrap();
+            return;
+        }
+        wire_trace::trace(
+            Direction::Send,
+            WireMessage::CrossShard,
+            shard_id,
+            &input_message,
+        );
         let tx = self.message_txs[shard_id][round].lock().unwrap();
         tx.send(Message::new(input_message)).unwrap();
     }
 
     fn receive_cross_shard_msg(&self, current_round: RoundId) -> CrossShardMsg {
+        let shard_id = self.shard_id.to_string();
         let rx = self.message_rxs[current_round].lock().unwrap();
-        let message = rx.recv().unwrap();
-        let msg: CrossShardMsg = bcs::from_bytes(&message.to_bytes()).unwrap();
-        msg
+        match &*rx {
+            InboundMessages::Raw(rx) => {
+                let message = rx.recv().unwrap();
+                wire_trace::trace(
+                    Direction::Receive,
+                    WireMessage::CrossShard,
+                    self.shard_id,
+                    &message.data,
+                );
+                let msg: CrossShardMsg = bcs::from_bytes(&message.to_bytes()).unwrap();
+                msg
+            },
+            InboundMessages::Prefetched(rx) => match rx.try_recv() {
+                Ok(msg) => {
+                    REMOTE_EXECUTOR_CROSS_SHARD_PREFETCH
+                        .with_label_values(&[&shard_id, "hit"])
+                        .inc();
+                    msg
+                },
+                Err(TryRecvError::Empty) => {
+                    REMOTE_EXECUTOR_CROSS_SHARD_PREFETCH
+                        .with_label_values(&[&shard_id, "miss"])
+                        .inc();
+                    let _timer = REMOTE_EXECUTOR_TIMER
+                        .with_label_values(&[&shard_id, "cross_shard_wait"])
+                        .start_timer();
+                    rx.recv().unwrap()
+                },
+                Err(TryRecvError::Disconnected) => {
+                    panic!(
+                        "Cross shard prefetch thread for round {} exited",
+                        current_round
+                    )
+                },
+            },
+        }
     }
 }
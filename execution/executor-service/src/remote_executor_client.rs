@@ -2,33 +2,82 @@
 // Copyright © Aptos Foundation
 // SPDX-License-Identifier: Apache-2.0
 use crate::{
-    remote_state_view_service::RemoteStateViewService, ExecuteBlockCommand, RemoteExecutionRequest,
-    RemoteExecutionResult,
+    block_abort::{self, BlockAborts},
+    capabilities::{self, CapabilitiesClient, ServiceCapabilities},
+    circuit_breaker::{CircuitBreakerConfig, CircuitBreakers},
+    cross_shard_relay::{self, CrossShardRouting},
+    fragmentation::{self, MAX_PAYLOAD_BYTES, MIN_PAYLOAD_BYTES},
+    heartbeat,
+    hedging::{self, HedgeDelay, HedgingConfig},
+    metrics::{
+        REMOTE_EXECUTOR_REQUEST_FRAGMENTS, REMOTE_EXECUTOR_RESULT_AGGREGATION_SECONDS,
+        REMOTE_EXECUTOR_SERIALIZATION_SECONDS, REMOTE_EXECUTOR_SHARD_BLOCK_STATS,
+        REMOTE_EXECUTOR_SHARD_LATENCY_BREAKDOWN_SECONDS,
+    },
+    remote_state_view_service::RemoteStateViewService,
+    replay_bundle::ReplayBundle,
+    transport::Transport,
+    versioning::{self, Decoded, REQUEST_FRAGMENT_VARIANT},
+    warm_standby::{self, Failover, Mirrors, WarmStandbyConfig},
+    wire_trace::{self, Direction, WireMessage},
+    ExecuteBlockCommand, ExecutionStats, RemoteExecutionRequest, RemoteExecutionResult,
+    ShardTopology,
 };
-use aptos_logger::{info, trace};
+use aptos_logger::{info, sample, sample::SampleRate, trace, warn};
 use aptos_secure_net::network_controller::{Message, NetworkController};
 use aptos_state_view::StateView;
 use aptos_storage_interface::cached_state_view::CachedStateView;
 use aptos_types::{
-    block_executor::partitioner::PartitionedTransactions, transaction::TransactionOutput,
-    vm_status::VMStatus,
+    block_executor::partitioner::{PartitionedTransactions, ShardId},
+    transaction::TransactionOutput,
+    vm_status::{StatusCode, VMStatus},
 };
 use aptos_vm::sharded_block_executor::{
     executor_client::{ExecutorClient, ShardedExecutionOutput},
     ShardedBlockExecutor,
 };
-use crossbeam_channel::{Receiver, Sender};
+use crossbeam_channel::{Receiver, RecvTimeoutError, Select, Sender};
 use once_cell::sync::{Lazy, OnceCell};
 use std::{
     net::{IpAddr, Ipv4Addr, SocketAddr},
-    sync::{Arc, Mutex},
+    sync::{
+        atomic::{AtomicU64, Ordering},
+        Arc, Mutex,
+    },
     thread,
+    time::{Duration, Instant},
 };
 
 pub static COORDINATOR_PORT: u16 = 52200;
 
+/// Shard id, decoded result of the shard, its execution stats, and when the result arrived.
+type DecodedShardResult = (
+    usize,
+    Result<Vec<Vec<TransactionOutput>>, VMStatus>,
+    Option<ExecutionStats>,
+    Instant,
+);
+
 static REMOTE_ADDRESSES: OnceCell<Vec<SocketAddr>> = OnceCell::new();
 static COORDINATOR_ADDRESS: OnceCell<SocketAddr> = OnceCell::new();
+static ASYNC_RESULT_AGGREGATION: OnceCell<bool> = OnceCell::new();
+static HEARTBEAT_TIMEOUT: OnceCell<Option<Duration>> = OnceCell::new();
+static CIRCUIT_BREAKER: OnceCell<Option<CircuitBreakerConfig>> = OnceCell::new();
+static HEDGING: OnceCell<Option<HedgingConfig>> = OnceCell::new();
+static WARM_STANDBY: OnceCell<Option<WarmStandbyConfig>> = OnceCell::new();
+static CROSS_SHARD_ROUTING: OnceCell<CrossShardRouting> = OnceCell::new();
+static MAX_PAYLOAD: OnceCell<usize> = OnceCell::new();
+static SERIALIZATION_THREADS: OnceCell<usize> = OnceCell::new();
+static TRANSPORT: OnceCell<Transport> = OnceCell::new();
+
+/// How long the coordinator waits for a heartbeat of a shard before marking it degraded.
+const DEFAULT_HEARTBEAT_TIMEOUT: Duration = Duration::from_secs(5);
+/// How often the coordinator checks whether a shard it waits for was marked degraded, to fail it
+/// over to its warm standby.
+const FAILOVER_CHECK_INTERVAL: Duration = Duration::from_millis(100);
+/// How long the coordinator waits for the result of a promoted warm standby, before failing the
+/// block. Standbys send no heartbeats, so there is nothing to fail them over to anyway.
+const STANDBY_RESULT_TIMEOUT: Duration = Duration::from_secs(60);
 
 pub fn set_remote_addresses(addresses: Vec<SocketAddr>) {
     REMOTE_ADDRESSES.set(addresses).ok();
@@ -52,6 +101,128 @@ pub fn get_coordinator_address() -> SocketAddr {
     }
 }
 
+/// If enabled, shard results are received and decoded on the serialization pool as soon as they
+/// arrive, overlapping with the dispatch of the block to the remaining shards and with waiting for
+/// the slower shards, instead of sequentially in shard order after dispatching.
+///
+/// This doesn't overlap the aggregation of a block with the dispatch of the next block (which
+/// reads the state the block writes) or of its next round: shards send the outputs of all rounds
+/// of a command at once, so overlapping rounds would need per-round results on the wire.
+pub fn set_async_result_aggregation(enabled: bool) {
+    ASYNC_RESULT_AGGREGATION.set(enabled).ok();
+}
+
+pub fn get_async_result_aggregation() -> bool {
+    ASYNC_RESULT_AGGREGATION.get().copied().unwrap_or(false)
+}
+
+/// Sets how long the coordinator waits for a heartbeat of a shard, before marking it as degraded.
+/// `None` disables monitoring the heartbeats.
+pub fn set_heartbeat_timeout(timeout: Option<Duration>) {
+    HEARTBEAT_TIMEOUT.set(timeout).ok();
+}
+
+pub fn get_heartbeat_timeout() -> Option<Duration> {
+    HEARTBEAT_TIMEOUT
+        .get()
+        .copied()
+        .unwrap_or(Some(DEFAULT_HEARTBEAT_TIMEOUT))
+}
+
+/// Sets the circuit breakers around the shard connections, `None` (the default) disables them.
+/// Their result timeout receives the shard results in shard order, and takes precedence over async
+/// result aggregation. It doesn't apply with standbys, which take over slow or dead shards.
+pub fn set_circuit_breaker(config: Option<CircuitBreakerConfig>) {
+    CIRCUIT_BREAKER.set(config).ok();
+}
+
+pub fn get_circuit_breaker() -> Option<CircuitBreakerConfig> {
+    CIRCUIT_BREAKER.get().copied().flatten()
+}
+
+/// Sets the standby executors slow shards are hedged with, `None` (the default) disables hedging.
+/// Hedging receives the shard results in shard order, and takes precedence over async result
+/// aggregation.
+pub fn set_hedging(config: Option<HedgingConfig>) {
+    HEDGING.set(config).ok();
+}
+
+pub fn get_hedging() -> Option<HedgingConfig> {
+    HEDGING.get().cloned().flatten()
+}
+
+/// Sets the warm standbys the shards fail over to when they die, `None` (the default) disables
+/// failover. Failover needs the heartbeats of the shards, receives the shard results in shard
+/// order, and takes precedence over async result aggregation.
+pub fn set_warm_standby(config: Option<WarmStandbyConfig>) {
+    WARM_STANDBY.set(config).ok();
+}
+
+pub fn get_warm_standby() -> Option<WarmStandbyConfig> {
+    WARM_STANDBY.get().cloned().flatten()
+}
+
+/// Sets how the shards route their cross-shard messages to each other, straight to each other
+/// (the default) or through the coordinator, for shards that can't reach each other.
+pub fn set_cross_shard_routing(routing: CrossShardRouting) {
+    CROSS_SHARD_ROUTING.set(routing).ok();
+}
+
+pub fn get_cross_shard_routing() -> CrossShardRouting {
+    CROSS_SHARD_ROUTING.get().copied().unwrap_or_default()
+}
+
+/// Sets the largest message the coordinator sends to a shard, which defaults to the limit of the
+/// transport. Larger requests are sent in fragments to the shards that reassemble them, and are
+/// rejected before dispatch for the others.
+pub fn set_max_payload_bytes(max_payload_bytes: usize) {
+    assert!(
+        (MIN_PAYLOAD_BYTES..=MAX_PAYLOAD_BYTES).contains(&max_payload_bytes),
+        "Max payload of {} bytes is outside of [{}, {}].",
+        max_payload_bytes,
+        MIN_PAYLOAD_BYTES,
+        MAX_PAYLOAD_BYTES
+    );
+    MAX_PAYLOAD.set(max_payload_bytes).ok();
+}
+
+pub fn get_max_payload_bytes() -> usize {
+    MAX_PAYLOAD.get().copied().unwrap_or(MAX_PAYLOAD_BYTES)
+}
+
+/// Sets the number of threads encoding the commands and decoding the results of the shards off
+/// the dispatching thread, which defaults to one per shard. With async result aggregation, the
+/// pool has a thread per shard more, receiving the results.
+pub fn set_serialization_threads(num_threads: usize) {
+    assert!(num_threads > 0, "Serialization needs at least one thread.");
+    SERIALIZATION_THREADS.set(num_threads).ok();
+}
+
+pub fn get_serialization_threads() -> Option<usize> {
+    SERIALIZATION_THREADS.get().copied()
+}
+
+/// Sets the transport to the shards, which has to be the one the shards use. Defaults to TCP.
+pub fn set_transport(transport: Transport) {
+    TRANSPORT.set(transport).ok();
+}
+
+pub fn get_transport() -> Transport {
+    TRANSPORT.get().copied().unwrap_or_default()
+}
+
+/// Returns the accumulated (get_results, post_last_result) seconds of result aggregation.
+pub fn result_aggregation_seconds() -> (f64, f64) {
+    (
+        REMOTE_EXECUTOR_RESULT_AGGREGATION_SECONDS
+            .with_label_values(&["get_results"])
+            .get_sample_sum(),
+        REMOTE_EXECUTOR_RESULT_AGGREGATION_SECONDS
+            .with_label_values(&["post_last_result"])
+            .get_sample_sum(),
+    )
+}
+
 pub static REMOTE_SHARDED_BLOCK_EXECUTOR: Lazy<
     Arc<
         aptos_infallible::Mutex<
@@ -65,6 +236,7 @@ pub static REMOTE_SHARDED_BLOCK_EXECUTOR: Lazy<
             get_coordinator_address(),
             get_remote_addresses(),
             None,
+            get_transport(),
         ),
     ))
 });
@@ -82,6 +254,32 @@ pub struct RemoteExecutorClient<S: StateView + Sync + Send + 'static> {
     result_rxs: Vec<Receiver<Message>>,
     // Thread pool used to pre-fetch the state values for the block in parallel and create an in-memory state view.
     thread_pool: Arc<rayon::ThreadPool>,
+    // Thread pool used to encode the commands and decode the results of the shards, so that the
+    // dispatching thread only sends and receives them. If async result aggregation is enabled, it
+    // has a thread more per shard, receiving the results of the shard as they arrive.
+    serialization_pool: rayon::ThreadPool,
+    async_result_aggregation: bool,
+    // Circuit breakers of the shard connections, if enabled.
+    circuit_breakers: Option<CircuitBreakers>,
+    // Hedge delay, if hedging is enabled. The channels to the standby executors of the shards are
+    // used by hedging and by failover to warm standbys.
+    hedge_delay: Option<HedgeDelay>,
+    standby_command_txs: Vec<Mutex<Sender<Message>>>,
+    standby_result_rxs: Vec<Receiver<Message>>,
+    // Number of results of each shard still to arrive from the (primary, standby) that lost the
+    // race for a hedged command, to be discarded.
+    stale_results: Mutex<Vec<(usize, usize)>>,
+    // Mirrors of the block stream to the warm standbys of the shards, if failover is enabled.
+    mirrors: Option<Arc<Mirrors>>,
+    // Cross-shard writes of the commands of the block in flight, to abort them for the other
+    // shards if a shard rejects the block. `None` with a single shard.
+    block_aborts: Option<Arc<BlockAborts>>,
+    // Capabilities reported by the shards, queried until every shard reported them.
+    capabilities_client: CapabilitiesClient,
+    // Commands of the block in flight, to write replay bundles of the failed ones, if enabled.
+    replay_requests: Mutex<Option<Vec<Vec<u8>>>>,
+    cross_shard_routing: CrossShardRouting,
+    next_request_id: AtomicU64,
 
     phantom: std::marker::PhantomData<S>,
     _join_handle: Option<thread::JoinHandle<()>>,
@@ -101,8 +299,33 @@ impl<S: StateView + Sync + Send + 'static> RemoteExecutorClient<S> {
                 .build()
                 .unwrap(),
         );
+        let async_result_aggregation = get_async_result_aggregation();
+        // Receiving blocks, so the receivers need a thread per shard on top of the ones encoding
+        // and decoding.
+        let num_receiver_threads = if async_result_aggregation {
+            remote_shard_addresses.len()
+        } else {
+            0
+        };
+        let serialization_pool = rayon::ThreadPoolBuilder::new()
+            .num_threads(
+                get_serialization_threads().unwrap_or_else(|| remote_shard_addresses.len().max(1))
+                    + num_receiver_threads,
+            )
+            .thread_name(|index| format!("remote-serde-{}", index))
+            .build()
+            .unwrap();
+        let circuit_breakers = get_circuit_breaker()
+            .map(|config| CircuitBreakers::new(config, remote_shard_addresses.len()));
+        let hedging = get_hedging();
+        let warm_standby = get_warm_standby();
+        assert!(
+            hedging.is_none() || warm_standby.is_none(),
+            "Hedging and warm standbys cannot share the standby executors."
+        );
+        let num_shards = remote_shard_addresses.len();
         let controller_mut_ref = &mut controller;
-        let (command_txs, result_rxs) = remote_shard_addresses
+        let (command_txs, result_rxs): (Vec<_>, Vec<_>) = remote_shard_addresses
             .iter()
             .enumerate()
             .map(|(shard_id, address)| {
@@ -111,16 +334,99 @@ impl<S: StateView + Sync + Send + 'static> RemoteExecutorClient<S> {
                 let command_tx = Mutex::new(
                     controller_mut_ref.create_outbound_channel(*address, execute_command_type),
                 );
-                let result_rx = controller_mut_ref.create_inbound_channel(execute_result_type);
+                let result_rx = fragmentation::reassemble_results(
+                    shard_id,
+                    controller_mut_ref.create_inbound_channel(execute_result_type),
+                );
                 (command_tx, result_rx)
             })
             .unzip();
+        let standby_addresses = match (&hedging, &warm_standby) {
+            (Some(config), _) => config.standby_addresses.clone(),
+            (_, Some(config)) => config.standby_addresses.clone(),
+            (None, None) => vec![],
+        };
+        if hedging.is_some() || warm_standby.is_some() {
+            assert_eq!(
+                standby_addresses.len(),
+                num_shards,
+                "Hedging and warm standbys need a standby executor for every shard."
+            );
+        }
+        let (standby_command_txs, standby_result_rxs) = standby_addresses
+            .iter()
+            .enumerate()
+            .map(|(shard_id, address)| {
+                let network_id = hedging::standby_network_id(num_shards, shard_id);
+                let command_tx =
+                    Mutex::new(controller_mut_ref.create_outbound_channel(
+                        *address,
+                        format!("execute_command_{}", network_id),
+                    ));
+                let result_rx = fragmentation::reassemble_results(
+                    network_id,
+                    controller_mut_ref
+                        .create_inbound_channel(format!("execute_result_{}", network_id)),
+                );
+                (command_tx, result_rx)
+            })
+            .unzip();
+        let mirrors = warm_standby.map(|_| {
+            Arc::new(Mirrors::new(
+                controller_mut_ref,
+                &standby_addresses,
+                (0..num_shards).map(|shard_id| hedging::standby_network_id(num_shards, shard_id)),
+            ))
+        });
 
-        let state_view_service = Arc::new(RemoteStateViewService::new(
-            controller_mut_ref,
-            remote_shard_addresses,
-            None,
-        ));
+        let capabilities_client =
+            CapabilitiesClient::new(controller_mut_ref, &remote_shard_addresses);
+        let cross_shard_routing = get_cross_shard_routing();
+        let cross_shard_senders = (num_shards > 1).then(|| {
+            cross_shard_relay::create_cross_shard_senders(
+                controller_mut_ref,
+                &remote_shard_addresses,
+            )
+        });
+        let block_aborts = cross_shard_senders.map(|senders| Arc::new(BlockAborts::new(senders)));
+        if let Some(block_aborts) = &block_aborts {
+            if cross_shard_routing == CrossShardRouting::ViaCoordinator {
+                cross_shard_relay::start_cross_shard_relay(
+                    controller_mut_ref,
+                    num_shards,
+                    block_aborts.clone(),
+                );
+            }
+        }
+        let result_rxs = match &block_aborts {
+            Some(block_aborts) => result_rxs
+                .into_iter()
+                .enumerate()
+                .map(|(shard_id, result_rx)| block_aborts.watch_results(shard_id, result_rx))
+                .collect(),
+            None => result_rxs,
+        };
+
+        if let Some(timeout) = get_heartbeat_timeout() {
+            heartbeat::start_heartbeat_monitor(
+                controller_mut_ref,
+                remote_shard_addresses.len(),
+                timeout,
+            );
+        }
+
+        // The standbys request state values as shards `num_shards..2 * num_shards`.
+        let state_view_service = Arc::new(
+            RemoteStateViewService::new(
+                controller_mut_ref,
+                remote_shard_addresses
+                    .into_iter()
+                    .chain(standby_addresses)
+                    .collect(),
+                None,
+            )
+            .with_mirrors(mirrors.clone()),
+        );
 
         let state_view_service_clone = state_view_service.clone();
 
@@ -130,6 +436,8 @@ impl<S: StateView + Sync + Send + 'static> RemoteExecutorClient<S> {
             .unwrap();
 
         controller.start();
+        // Shards that don't answer right away are asked again by the first blocks.
+        capabilities_client.request_all();
 
         Self {
             network_controller: controller,
@@ -138,6 +446,19 @@ impl<S: StateView + Sync + Send + 'static> RemoteExecutorClient<S> {
             command_txs: Arc::new(command_txs),
             result_rxs,
             thread_pool,
+            serialization_pool,
+            async_result_aggregation,
+            circuit_breakers,
+            hedge_delay: hedging.map(HedgeDelay::new),
+            standby_command_txs,
+            standby_result_rxs,
+            stale_results: Mutex::new(vec![(0, 0); num_shards]),
+            mirrors,
+            block_aborts,
+            capabilities_client,
+            replay_requests: Mutex::new(None),
+            cross_shard_routing,
+            next_request_id: AtomicU64::new(0),
             phantom: std::marker::PhantomData,
         }
     }
@@ -146,27 +467,738 @@ impl<S: StateView + Sync + Send + 'static> RemoteExecutorClient<S> {
         coordinator_address: SocketAddr,
         remote_shard_addresses: Vec<SocketAddr>,
         num_threads: Option<usize>,
+        transport: Transport,
     ) -> ShardedBlockExecutor<S, RemoteExecutorClient<S>> {
         ShardedBlockExecutor::new(RemoteExecutorClient::new(
             remote_shard_addresses,
-            NetworkController::new(
+            NetworkController::new_with_transport(
                 "remote-executor-coordinator".to_string(),
                 coordinator_address,
                 5000,
+                transport.into(),
             ),
             num_threads,
         ))
     }
 
-    fn get_output_from_shards(&self) -> Result<Vec<Vec<Vec<TransactionOutput>>>, VMStatus> {
+    /// Capabilities reported by every shard, `None` for shards that didn't report them yet.
+    pub fn capabilities(&self) -> Vec<Option<ServiceCapabilities>> {
+        (0..self.num_shards())
+            .map(|shard_id| self.capabilities_client.get(shard_id).cloned())
+            .collect()
+    }
+
+    /// Waits for the shards that didn't report their capabilities yet, and fails the block if
+    /// any of them doesn't within `capabilities::DEFAULT_QUERY_TIMEOUT`: its limits are unknown.
+    fn wait_for_capabilities(&self) -> Result<(), VMStatus> {
+        let deadline = Instant::now() + capabilities::DEFAULT_QUERY_TIMEOUT;
+        let unknown_shards = (0..self.num_shards())
+            .filter(|shard_id| {
+                self.capabilities_client
+                    .wait(
+                        *shard_id,
+                        deadline.saturating_duration_since(Instant::now()),
+                    )
+                    .is_none()
+            })
+            .collect::<Vec<_>>();
+        if unknown_shards.is_empty() {
+            return Ok(());
+        }
+        let reason = format!(
+            "Shards {:?} didn't report their capabilities",
+            unknown_shards
+        );
+        warn!("Not dispatching block: {}", reason);
+        Err(VMStatus::error(StatusCode::UNKNOWN_STATUS, Some(reason)))
+    }
+
+    /// Largest message sent to a shard (and its standby), `None` for shards that don't reassemble
+    /// fragments, which are sent requests up to the configured max payload in one piece.
+    fn fragment_payload_bytes(&self, shard_id: ShardId) -> Option<usize> {
+        fragmentation::negotiate_max_payload_bytes(
+            get_max_payload_bytes(),
+            self.capabilities_client.get(shard_id),
+        )
+    }
+
+    /// Checks the encoded commands of a block against the capabilities of their shards, so that
+    /// a block no shard can take is failed before any of it is dispatched. Without the commands,
+    /// their sizes aren't checked, which is only right if every shard reassembles fragments.
+    fn validate_dispatch(&self, requests: Option<&[Vec<u8>]>) -> Result<(), VMStatus> {
+        for shard_id in 0..self.num_shards() {
+            let request_len = requests.map_or(0, |requests| requests[shard_id].len());
+            if self.fragment_payload_bytes(shard_id).is_none()
+                && request_len > get_max_payload_bytes()
+            {
+                let reason = format!(
+                    "Command of {} bytes exceeds the max payload of {} bytes, and shard {} doesn't reassemble fragments",
+                    request_len,
+                    get_max_payload_bytes(),
+                    shard_id
+                );
+                warn!("Not dispatching block: {}", reason);
+                return Err(VMStatus::error(StatusCode::UNKNOWN_STATUS, Some(reason)));
+            }
+            if let Some(capabilities) = self.capabilities_client.get(shard_id) {
+                capabilities
+                    .validate_command(
+                        self.num_shards(),
+                        request_len,
+                        capabilities::TRANSACTION_TYPE_VM,
+                    )
+                    .map_err(|reason| {
+                        warn!("Not dispatching block: {}", reason);
+                        VMStatus::error(StatusCode::UNKNOWN_STATUS, Some(reason))
+                    })?;
+                if self.cross_shard_routing == CrossShardRouting::ViaCoordinator
+                    && capabilities.protocol_version < versioning::TOPOLOGY_PROTOCOL_VERSION
+                {
+                    let reason = format!(
+                        "Shard {} of protocol version {} cannot relay cross-shard messages through the coordinator",
+                        shard_id, capabilities.protocol_version
+                    );
+                    warn!("Not dispatching block: {}", reason);
+                    return Err(VMStatus::error(StatusCode::UNKNOWN_STATUS, Some(reason)));
+                }
+            }
+        }
+        Ok(())
+    }
+
+    /// Whether the command of a shard is sent with its topology, i.e. the shard reported a
+    /// protocol version that knows it. Blocks relayed through the coordinator are not dispatched
+    /// to the other shards at all.
+    fn sends_topology(&self, shard_id: ShardId) -> bool {
+        self.capabilities_client
+            .get(shard_id)
+            .map_or(false, |capabilities| {
+                capabilities.protocol_version >= versioning::TOPOLOGY_PROTOCOL_VERSION
+            })
+    }
+
+    /// Whether the commands of a block are dispatched as they are encoded, rather than once all of
+    /// them are. Only if every shard reassembles fragments, so that none of the commands can be
+    /// rejected for its size after others were dispatched.
+    fn dispatches_pipelined(&self) -> bool {
+        (0..self.num_shards()).all(|shard_id| self.fragment_payload_bytes(shard_id).is_some())
+    }
+
+    /// Encodes the commands of a block on the serialization pool, and returns them (with their
+    /// shard ids) in the order they are encoded.
+    fn encode_requests(
+        &self,
+        requests: Vec<RemoteExecutionRequest>,
+    ) -> Receiver<(ShardId, Vec<u8>)> {
+        let (encoded_tx, encoded_rx) = crossbeam_channel::unbounded();
+        for (shard_id, request) in requests.into_iter().enumerate() {
+            let encoded_tx = encoded_tx.clone();
+            let block_aborts = self.block_aborts.clone();
+            self.serialization_pool.spawn(move || {
+                // Set before the command can be dispatched, and so rejected.
+                if let Some(block_aborts) = block_aborts {
+                    block_aborts.set(
+                        shard_id,
+                        block_abort::aborted_writes(&request.command().sub_blocks),
+                    );
+                }
+                let timer = REMOTE_EXECUTOR_SERIALIZATION_SECONDS
+                    .with_label_values(&[&shard_id.to_string(), "encode_request"])
+                    .start_timer();
+                let request_bytes = versioning::encode(&request).unwrap();
+                drop(timer);
+                // The block may have been failed before all of its commands were encoded.
+                encoded_tx.send((shard_id, request_bytes)).ok();
+            });
+        }
+        encoded_rx
+    }
+
+    /// Waits for all the commands of a block to be encoded, and returns them in shard order.
+    fn recv_encoded_requests(&self, encoded_rx: &Receiver<(ShardId, Vec<u8>)>) -> Vec<Vec<u8>> {
+        let mut requests = vec![vec![]; self.num_shards()];
+        for _ in 0..self.num_shards() {
+            let (shard_id, request_bytes) =
+                encoded_rx.recv().expect("Every command must be encoded.");
+            requests[shard_id] = request_bytes;
+        }
+        requests
+    }
+
+    /// Sends the command of a shard to the shard, or to its standby if it was promoted.
+    fn dispatch_request(&self, shard_id: ShardId, request_bytes: Vec<u8>) {
+        // Mirrored before dispatching, so that the state values the shard reads follow it.
+        if let Some(mirrors) = &self.mirrors {
+            mirrors.mirror_block(shard_id, &request_bytes);
+        }
+        let promoted = self
+            .mirrors
+            .as_ref()
+            .map_or(false, |mirrors| mirrors.is_promoted(shard_id));
+        let (senders, network_id) = if promoted {
+            (
+                &self.standby_command_txs,
+                hedging::standby_network_id(self.num_shards(), shard_id),
+            )
+        } else {
+            (&*self.command_txs, shard_id)
+        };
+        self.send_request(&senders[shard_id], shard_id, network_id, request_bytes);
+    }
+
+    /// Sends the request of a shard to the executor `network_id` (the shard or its standby), in
+    /// fragments if it exceeds the max payload of the shard.
+    fn send_request(
+        &self,
+        sender: &Mutex<Sender<Message>>,
+        shard_id: ShardId,
+        network_id: usize,
+        request_bytes: Vec<u8>,
+    ) {
+        let messages = match self.fragment_payload_bytes(shard_id) {
+            Some(max_payload_bytes) => fragmentation::fragment(
+                request_bytes,
+                self.next_request_id.fetch_add(1, Ordering::Relaxed),
+                max_payload_bytes,
+                REQUEST_FRAGMENT_VARIANT,
+            )
+            .expect("Fragments must serialize."),
+            None => vec![request_bytes],
+        };
+        if messages.len() > 1 {
+            REMOTE_EXECUTOR_REQUEST_FRAGMENTS
+                .with_label_values(&[&shard_id.to_string()])
+                .inc_by(messages.len() as u64);
+        }
+        let sender = sender.lock().unwrap();
+        for message in messages {
+            wire_trace::trace(
+                Direction::Send,
+                WireMessage::ExecuteCommand,
+                network_id,
+                &message,
+            );
+            sender.send(Message::new(message)).unwrap();
+        }
+    }
+
+    /// Returns the result of a shard, and its execution stats if the shard reported them.
+    fn decode_result(
+        shard_id: ShardId,
+        received_bytes: &[u8],
+    ) -> (
+        Result<Vec<Vec<TransactionOutput>>, VMStatus>,
+        Option<ExecutionStats>,
+    ) {
+        wire_trace::trace(
+            Direction::Receive,
+            WireMessage::ExecuteResult,
+            shard_id,
+            received_bytes,
+        );
+        match versioning::decode::<RemoteExecutionResult>(received_bytes)
+            .expect("Failed to decode execution result.")
+        {
+            Decoded::Known(result) => (result.inner, result.stats),
+            Decoded::Unknown { version, variant } => (
+                Err(VMStatus::error(
+                    StatusCode::UNKNOWN_STATUS,
+                    Some(format!(
+                        "Unknown execution result variant {} of protocol version {}",
+                        variant, version
+                    )),
+                )),
+                None,
+            ),
+        }
+    }
+
+    /// Decodes the result of a shard that arrived at `arrival`, and sends it to `decoded_tx`.
+    fn decode_and_send(
+        shard_id: ShardId,
+        received_bytes: &[u8],
+        arrival: Instant,
+        decoded_tx: &Sender<DecodedShardResult>,
+    ) {
+        let timer = REMOTE_EXECUTOR_SERIALIZATION_SECONDS
+            .with_label_values(&[&shard_id.to_string(), "decode_result"])
+            .start_timer();
+        let (result, stats) = Self::decode_result(shard_id, received_bytes);
+        drop(timer);
+        decoded_tx.send((shard_id, result, stats, arrival)).unwrap();
+    }
+
+    /// Decodes the result of a shard that arrived at `arrival` on the serialization pool, so that
+    /// the results of the other shards are received meanwhile.
+    fn spawn_decode(
+        &self,
+        shard_id: ShardId,
+        received_bytes: Vec<u8>,
+        arrival: Instant,
+        decoded_tx: &Sender<DecodedShardResult>,
+    ) {
+        let decoded_tx = decoded_tx.clone();
+        self.serialization_pool.spawn(move || {
+            Self::decode_and_send(shard_id, &received_bytes, arrival, &decoded_tx);
+        });
+    }
+
+    /// Splits the latency of a shard on a block, from dispatching its command to receiving its
+    /// result, into the time spent on the shard and on the network, for shards reporting stats.
+    fn record_shard_latency(shard_id: ShardId, stats: Option<&ExecutionStats>, latency: Duration) {
+        let stats = match stats {
+            Some(stats) => stats,
+            None => return,
+        };
+        let shard_label = shard_id.to_string();
+        let shard_time = Duration::from_millis(stats.execution_ms);
+        for (name, duration) in [
+            ("end_to_end", latency),
+            ("shard", shard_time),
+            ("network", latency.saturating_sub(shard_time)),
+        ] {
+            REMOTE_EXECUTOR_SHARD_LATENCY_BREAKDOWN_SECONDS
+                .with_label_values(&[&shard_label, name])
+                .observe(duration.as_secs_f64());
+        }
+        for (name, value) in [
+            ("num_threads", stats.num_threads as f64),
+            (
+                "peak_memory_delta_bytes",
+                stats.peak_memory_delta_bytes as f64,
+            ),
+            ("num_state_reads", stats.num_state_reads as f64),
+        ] {
+            REMOTE_EXECUTOR_SHARD_BLOCK_STATS
+                .with_label_values(&[&shard_label, name])
+                .set(value);
+        }
+        trace!(
+            "Shard {} took {:?} end to end, {:?} on the shard: {:?}",
+            shard_id,
+            latency,
+            shard_time,
+            stats
+        );
+    }
+
+    fn get_output_from_shards(
+        &self,
+        dispatch_time: Instant,
+    ) -> Result<Vec<Vec<Vec<TransactionOutput>>>, VMStatus> {
         trace!("RemoteExecutorClient Waiting for results");
-        let mut results = vec![];
-        for rx in self.result_rxs.iter() {
-            let received_bytes = rx.recv().unwrap().to_bytes();
-            let result: RemoteExecutionResult = bcs::from_bytes(&received_bytes).unwrap();
-            results.push(result.inner?);
+        let get_results_timer = REMOTE_EXECUTOR_RESULT_AGGREGATION_SECONDS
+            .with_label_values(&["get_results"])
+            .start_timer();
+        // Receive the results of all shards before failing on an error (e.g. a shard rejecting
+        // the block), so that no stale results are left behind for the next block. The results
+        // of the shards that time out are discarded when they arrive.
+        let result_deadline = self
+            .circuit_breakers
+            .as_ref()
+            .and_then(CircuitBreakers::result_timeout)
+            .map(|timeout| dispatch_time + timeout);
+        let mut stale_results = self.stale_results.lock().unwrap();
+        let (decoded_tx, decoded_rx) = crossbeam_channel::unbounded();
+        for (shard_id, rx) in self.result_rxs.iter().enumerate() {
+            let (stale_primary, _) = &mut stale_results[shard_id];
+            match Self::recv_fresh(rx, stale_primary, result_deadline) {
+                Some(received_bytes) => {
+                    self.spawn_decode(shard_id, received_bytes, Instant::now(), &decoded_tx);
+                },
+                None => {
+                    *stale_primary += 1;
+                    self.record_result_timeout(shard_id, dispatch_time, &decoded_tx);
+                },
+            }
+        }
+        drop(stale_results);
+        let results = self.collect_decoded_results(decoded_rx, dispatch_time);
+        drop(get_results_timer);
+        self.record_shard_results(&results);
+        results.into_iter().collect()
+    }
+
+    /// Fails the block on a shard that didn't return its result within the result timeout of
+    /// the circuit breakers, opening the breaker of the shard.
+    fn record_result_timeout(
+        &self,
+        shard_id: ShardId,
+        dispatch_time: Instant,
+        decoded_tx: &Sender<DecodedShardResult>,
+    ) {
+        let waited = dispatch_time.elapsed();
+        warn!(
+            "Shard {} didn't return its result within {:?}, failing the block",
+            shard_id, waited
+        );
+        if let Some(circuit_breakers) = &self.circuit_breakers {
+            circuit_breakers.record_timeout(shard_id);
+        }
+        let result = Err(VMStatus::error(
+            StatusCode::UNKNOWN_STATUS,
+            Some(format!(
+                "Shard {} didn't return its result within {:?}",
+                shard_id, waited
+            )),
+        ));
+        decoded_tx
+            .send((shard_id, result, None, Instant::now()))
+            .unwrap();
+    }
+
+    /// Spawns a task per shard on the serialization pool, that receives and decodes the result
+    /// of the shard as soon as it arrives.
+    fn spawn_result_receivers(&self) -> Receiver<DecodedShardResult> {
+        let (decoded_tx, decoded_rx) = crossbeam_channel::unbounded();
+        for (shard_id, rx) in self.result_rxs.iter().enumerate() {
+            let rx = rx.clone();
+            let decoded_tx = decoded_tx.clone();
+            self.serialization_pool.spawn(move || {
+                let received_bytes = rx.recv().unwrap().to_bytes();
+                Self::decode_and_send(shard_id, &received_bytes, Instant::now(), &decoded_tx);
+            });
+        }
+        decoded_rx
+    }
+
+    fn collect_spawned_results(
+        &self,
+        decoded_rx: Receiver<DecodedShardResult>,
+        dispatch_time: Instant,
+    ) -> Result<Vec<Vec<Vec<TransactionOutput>>>, VMStatus> {
+        trace!("RemoteExecutorClient Waiting for decoded results");
+        let get_results_timer = REMOTE_EXECUTOR_RESULT_AGGREGATION_SECONDS
+            .with_label_values(&["get_results"])
+            .start_timer();
+        let results = self.collect_decoded_results(decoded_rx, dispatch_time);
+        drop(get_results_timer);
+        self.record_shard_results(&results);
+        results.into_iter().collect()
+    }
+
+    /// Waits for the decoded results of all shards, and returns them in shard order.
+    fn collect_decoded_results(
+        &self,
+        decoded_rx: Receiver<DecodedShardResult>,
+        dispatch_time: Instant,
+    ) -> Vec<Result<Vec<Vec<TransactionOutput>>, VMStatus>> {
+        let mut results = (0..self.result_rxs.len()).map(|_| None).collect::<Vec<_>>();
+        let mut last_arrival = Instant::now();
+        for _ in 0..self.result_rxs.len() {
+            let (shard_id, result, stats, arrival) = decoded_rx.recv().unwrap();
+            last_arrival = arrival.max(last_arrival);
+            // The result may have been decoded before the last command was dispatched.
+            Self::record_shard_latency(
+                shard_id,
+                stats.as_ref(),
+                arrival.saturating_duration_since(dispatch_time),
+            );
+            results[shard_id] = Some(result);
+        }
+        REMOTE_EXECUTOR_RESULT_AGGREGATION_SECONDS
+            .with_label_values(&["post_last_result"])
+            .observe(last_arrival.elapsed().as_secs_f64());
+        results
+            .into_iter()
+            .map(|result| result.expect("Result of every shard must be received."))
+            .collect()
+    }
+
+    /// Receives the results in the order they arrive, and sends the commands of the shards that
+    /// are slower than the hedge delay to their standbys as well, taking the result that arrives
+    /// first. The latency of every shard is recorded when its result arrives, so that the hedge
+    /// delay isn't skewed by the shards waited for before it.
+    fn get_output_with_hedging(
+        &self,
+        hedge_delay: &HedgeDelay,
+        requests: Vec<Vec<u8>>,
+        hedgeable: &[bool],
+        dispatch_time: Instant,
+    ) -> Result<Vec<Vec<Vec<TransactionOutput>>>, VMStatus> {
+        trace!("RemoteExecutorClient Waiting for results, hedging slow shards");
+        let get_results_timer = REMOTE_EXECUTOR_RESULT_AGGREGATION_SECONDS
+            .with_label_values(&["get_results"])
+            .start_timer();
+        let hedge_deadline = dispatch_time + hedge_delay.delay();
+        let mut stale_results = self.stale_results.lock().unwrap();
+        let (decoded_tx, decoded_rx) = crossbeam_channel::unbounded();
+        for shard_id in 0..self.num_shards() {
+            hedging::record_hedging_outcome(shard_id, "dispatched");
+        }
+        // Shards whose result is still to arrive, with whether their command was hedged.
+        let mut outstanding = vec![Some(false); self.num_shards()];
+        let mut requests = requests.into_iter().map(Some).collect::<Vec<_>>();
+        let mut deadline = Some(hedge_deadline);
+        while outstanding.iter().any(Option::is_some) {
+            let (shard_id, received_bytes, from_standby) =
+                match self.recv_first_result(&outstanding, &mut stale_results, deadline) {
+                    Some(received) => received,
+                    None => {
+                        // Past the hedge delay, hedge the shards still outstanding.
+                        deadline = None;
+                        for shard_id in 0..self.num_shards() {
+                            if outstanding[shard_id].is_none() {
+                                continue;
+                            }
+                            if !hedgeable[shard_id] {
+                                hedging::record_hedging_outcome(shard_id, "not_hedgeable");
+                                continue;
+                            }
+                            hedging::record_hedging_outcome(shard_id, "hedged");
+                            self.send_request(
+                                &self.standby_command_txs[shard_id],
+                                shard_id,
+                                hedging::standby_network_id(self.num_shards(), shard_id),
+                                requests[shard_id]
+                                    .take()
+                                    .expect("A command is hedged only once."),
+                            );
+                            outstanding[shard_id] = Some(true);
+                        }
+                        continue;
+                    },
+                };
+            let arrival = Instant::now();
+            if outstanding[shard_id] == Some(true) {
+                let (stale_primary, stale_standby) = &mut stale_results[shard_id];
+                if from_standby {
+                    hedging::record_hedging_outcome(shard_id, "standby_won");
+                    *stale_primary += 1;
+                } else {
+                    *stale_standby += 1;
+                }
+            }
+            outstanding[shard_id] = None;
+            hedge_delay.record_latency(arrival.duration_since(dispatch_time));
+            self.spawn_decode(shard_id, received_bytes, arrival, &decoded_tx);
+        }
+        drop(stale_results);
+        let results = self.collect_decoded_results(decoded_rx, dispatch_time);
+        drop(get_results_timer);
+        self.record_shard_results(&results);
+        results.into_iter().collect()
+    }
+
+    /// Returns the first fresh result of the `outstanding` shards, from their primaries or, for
+    /// the hedged ones, their standbys, with whether it came from the standby. Discards the
+    /// `stale_results` of commands that lost a hedging race first. `None` if no result arrived
+    /// until `deadline`.
+    fn recv_first_result(
+        &self,
+        outstanding: &[Option<bool>],
+        stale_results: &mut [(usize, usize)],
+        deadline: Option<Instant>,
+    ) -> Option<(ShardId, Vec<u8>, bool)> {
+        loop {
+            let mut select = Select::new();
+            let mut channels = vec![];
+            for (shard_id, hedged) in outstanding.iter().enumerate() {
+                let hedged = match hedged {
+                    Some(hedged) => *hedged,
+                    None => continue,
+                };
+                select.recv(&self.result_rxs[shard_id]);
+                channels.push((shard_id, false));
+                if hedged {
+                    select.recv(&self.standby_result_rxs[shard_id]);
+                    channels.push((shard_id, true));
+                }
+            }
+            let operation = match deadline {
+                Some(deadline) => select.select_deadline(deadline).ok()?,
+                None => select.select(),
+            };
+            let (shard_id, from_standby) = channels[operation.index()];
+            let (stale_primary, stale_standby) = &mut stale_results[shard_id];
+            let (rx, stale) = if from_standby {
+                (&self.standby_result_rxs[shard_id], stale_standby)
+            } else {
+                (&self.result_rxs[shard_id], stale_primary)
+            };
+            let message = operation.recv(rx).unwrap();
+            if *stale == 0 {
+                return Some((shard_id, message.to_bytes(), from_standby));
+            }
+            *stale -= 1;
+        }
+    }
+
+    /// Receives the results in shard order, like `get_output_from_shards`, but promotes the warm
+    /// standby of a shard that dies while its result is outstanding, and takes the result of the
+    /// shard from its standby.
+    fn get_output_with_failover(
+        &self,
+        mirrors: &Mirrors,
+        requests: Vec<Vec<u8>>,
+        hedgeable: &[bool],
+        dispatch_time: Instant,
+    ) -> Result<Vec<Vec<Vec<TransactionOutput>>>, VMStatus> {
+        trace!("RemoteExecutorClient Waiting for results, failing over dead shards");
+        let get_results_timer = REMOTE_EXECUTOR_RESULT_AGGREGATION_SECONDS
+            .with_label_values(&["get_results"])
+            .start_timer();
+        let (decoded_tx, decoded_rx) = crossbeam_channel::unbounded();
+        for (shard_id, request_bytes) in requests.into_iter().enumerate() {
+            let received_bytes = if mirrors.is_promoted(shard_id) {
+                self.recv_from_standby(shard_id)
+            } else {
+                self.recv_or_fail_over(mirrors, shard_id, request_bytes, hedgeable[shard_id])
+            };
+            match received_bytes {
+                Ok(received_bytes) => {
+                    self.spawn_decode(shard_id, received_bytes, Instant::now(), &decoded_tx)
+                },
+                // Fails the block once the results of the other shards are in.
+                Err(status) => decoded_tx
+                    .send((shard_id, Err(status), None, Instant::now()))
+                    .unwrap(),
+            }
+        }
+        let results = self.collect_decoded_results(decoded_rx, dispatch_time);
+        drop(get_results_timer);
+        self.record_shard_results(&results);
+        results.into_iter().collect()
+    }
+
+    /// Waits for the result of the primary of a shard, until the heartbeat monitor marks it
+    /// degraded. The warm standby of the shard is promoted then, and the command is resent to it.
+    fn recv_or_fail_over(
+        &self,
+        mirrors: &Mirrors,
+        shard_id: ShardId,
+        request_bytes: Vec<u8>,
+        can_fail_over: bool,
+    ) -> Result<Vec<u8>, VMStatus> {
+        let mut warned = false;
+        loop {
+            match self.result_rxs[shard_id].recv_timeout(FAILOVER_CHECK_INTERVAL) {
+                Ok(message) => return Ok(message.to_bytes()),
+                Err(RecvTimeoutError::Timeout) if heartbeat::is_shard_degraded(shard_id) => {
+                    if can_fail_over {
+                        break;
+                    }
+                    if !warned {
+                        warn!(
+                            "Shard {} is degraded, but its command has cross-shard dependencies and cannot fail over",
+                            shard_id
+                        );
+                        warned = true;
+                    }
+                },
+                Err(RecvTimeoutError::Timeout) => {},
+                Err(RecvTimeoutError::Disconnected) => {
+                    panic!("Result channel of shard {} disconnected.", shard_id)
+                },
+            }
+        }
+        mirrors.promote(shard_id);
+        let promoted_at = Instant::now();
+        self.send_request(
+            &self.standby_command_txs[shard_id],
+            shard_id,
+            hedging::standby_network_id(self.num_shards(), shard_id),
+            request_bytes,
+        );
+        let received_bytes = self.recv_from_standby(shard_id)?;
+        warm_standby::record_failover(Failover {
+            shard_id,
+            failover_secs: promoted_at.elapsed().as_secs_f64(),
+        });
+        Ok(received_bytes)
+    }
+
+    /// Waits for the result of the promoted warm standby of a shard, up to
+    /// `STANDBY_RESULT_TIMEOUT`.
+    fn recv_from_standby(&self, shard_id: ShardId) -> Result<Vec<u8>, VMStatus> {
+        let reason = match self.standby_result_rxs[shard_id].recv_timeout(STANDBY_RESULT_TIMEOUT) {
+            Ok(message) => return Ok(message.to_bytes()),
+            Err(RecvTimeoutError::Timeout) => format!(
+                "The warm standby of shard {} returned no result within {:?}",
+                shard_id, STANDBY_RESULT_TIMEOUT
+            ),
+            Err(RecvTimeoutError::Disconnected) => format!(
+                "Result channel of the warm standby of shard {} disconnected",
+                shard_id
+            ),
+        };
+        warn!("{}", reason);
+        Err(VMStatus::error(StatusCode::UNKNOWN_STATUS, Some(reason)))
+    }
+
+    /// Receives the next result from `rx`, until `deadline` if given, discarding the `stale`
+    /// results of commands that lost a hedging race first.
+    fn recv_fresh(
+        rx: &Receiver<Message>,
+        stale: &mut usize,
+        deadline: Option<Instant>,
+    ) -> Option<Vec<u8>> {
+        loop {
+            let message = match deadline {
+                Some(deadline) => rx.recv_deadline(deadline).ok()?,
+                None => rx.recv().unwrap(),
+            };
+            if *stale == 0 {
+                return Some(message.to_bytes());
+            }
+            *stale -= 1;
+        }
+    }
+
+    fn has_stale_results(&self) -> bool {
+        self.stale_results
+            .lock()
+            .unwrap()
+            .iter()
+            .any(|(stale_primary, stale_standby)| *stale_primary + *stale_standby > 0)
+    }
+
+    fn record_shard_results(&self, results: &[Result<Vec<Vec<TransactionOutput>>, VMStatus>]) {
+        if let Some(circuit_breakers) = &self.circuit_breakers {
+            for (shard_id, result) in results.iter().enumerate() {
+                if result.is_ok() {
+                    circuit_breakers.record_success(shard_id);
+                } else {
+                    circuit_breakers.record_failure(shard_id);
+                }
+            }
+        }
+        if let Some(requests) = self.replay_requests.lock().unwrap().take() {
+            for (shard_id, result) in results.iter().enumerate() {
+                if let Err(error) = result {
+                    self.write_replay_bundle(shard_id, &requests[shard_id], error);
+                }
+            }
+        }
+    }
+
+    fn write_replay_bundle(&self, shard_id: ShardId, request: &[u8], error: &VMStatus) {
+        let served_state_values = match self.state_view_service.served_state_values() {
+            Some(served_state_values) => served_state_values,
+            None => return,
+        };
+        // The result may have come from the standby of the shard.
+        let mut network_ids = vec![shard_id];
+        if self.hedge_delay.is_some() || self.mirrors.is_some() {
+            network_ids.push(hedging::standby_network_id(self.num_shards(), shard_id));
+        }
+        let bundle = ReplayBundle {
+            shard_id,
+            num_shards: self.num_shards(),
+            request: request.to_vec(),
+            state_values: served_state_values.get(&network_ids),
+            error: error.clone(),
+        };
+        match bundle.write() {
+            Ok(path) => warn!(
+                "Shard {} failed with {:?}, wrote a replay bundle to {:?}",
+                shard_id, error, path
+            ),
+            Err(err) => warn!(
+                "Failed to write the replay bundle of shard {}: {:?}",
+                shard_id, err
+            ),
         }
-        Ok(results)
     }
 }
 
@@ -183,29 +1215,164 @@ impl<S: StateView + Sync + Send + 'static> ExecutorClient<S> for RemoteExecutorC
         maybe_block_gas_limit: Option<u64>,
     ) -> Result<ShardedExecutionOutput, VMStatus> {
         trace!("RemoteExecutorClient Sending block to shards");
-        self.state_view_service.set_state_view(state_view);
+        if let Some(circuit_breakers) = &self.circuit_breakers {
+            if let Err(open_shards) = circuit_breakers.try_dispatch() {
+                return Err(VMStatus::error(
+                    StatusCode::UNKNOWN_STATUS,
+                    Some(format!(
+                        "Circuit breakers of shards {:?} are open, not dispatching the block",
+                        open_shards
+                    )),
+                ));
+            }
+        }
+        self.wait_for_capabilities()?;
+        // Shards that failed over don't depend on their primaries anymore.
+        let degraded_shards = heartbeat::degraded_shards(self.num_shards())
+            .into_iter()
+            .filter(|shard_id| {
+                !self
+                    .mirrors
+                    .as_ref()
+                    .map_or(false, |mirrors| mirrors.is_promoted(*shard_id))
+            })
+            .collect::<Vec<_>>();
+        if !degraded_shards.is_empty() {
+            sample!(
+                SampleRate::Duration(Duration::from_secs(10)),
+                warn!(
+                    "Dispatching block to degraded shards {:?}, it may stall until they recover",
+                    degraded_shards
+                );
+            );
+        }
         let (sub_blocks, global_txns) = transactions.into();
         if !global_txns.is_empty() {
             panic!("Global transactions are not supported yet");
         }
-        for (shard_id, sub_blocks) in sub_blocks.into_iter().enumerate() {
-            let senders = self.command_txs.clone();
-            let execution_request = RemoteExecutionRequest::ExecuteBlock(ExecuteBlockCommand {
-                sub_blocks,
-                concurrency_level: concurrency_level_per_shard,
-                maybe_block_gas_limit,
+        let uses_standbys = self.hedge_delay.is_some() || self.mirrors.is_some();
+        let hedgeable = sub_blocks
+            .iter()
+            .map(|sub_blocks| uses_standbys && hedging::is_hedgeable(sub_blocks))
+            .collect::<Vec<_>>();
+        if let Some(mirrors) = &self.mirrors {
+            if let Some(shard_id) =
+                (0..self.num_shards()).find(|i| mirrors.is_promoted(*i) && !hedgeable[*i])
+            {
+                return Err(VMStatus::error(
+                    StatusCode::UNKNOWN_STATUS,
+                    Some(format!(
+                        "Shard {} failed over to its warm standby, which cannot execute commands with cross-shard dependencies",
+                        shard_id
+                    )),
+                ));
+            }
+        }
+        let requests = sub_blocks
+            .into_iter()
+            .enumerate()
+            .map(|(shard_id, sub_blocks)| {
+                let num_rounds = sub_blocks.num_sub_blocks();
+                let command = ExecuteBlockCommand {
+                    sub_blocks,
+                    concurrency_level: concurrency_level_per_shard,
+                    maybe_block_gas_limit,
+                };
+                if self.sends_topology(shard_id) {
+                    RemoteExecutionRequest::ExecuteBlockInTopology(command, ShardTopology {
+                        shard_id,
+                        num_shards: self.num_shards(),
+                        num_rounds,
+                        cross_shard_routing: self.cross_shard_routing,
+                    })
+                } else {
+                    RemoteExecutionRequest::ExecuteBlock(command)
+                }
+            })
+            .collect::<Vec<_>>();
+        // The commands are encoded on the serialization pool. When pipelined, every command is
+        // dispatched as soon as it is encoded, while the others still are.
+        let pipelined = self.dispatches_pipelined();
+        let encoded_rx = self.encode_requests(requests);
+        let encoded_requests = if pipelined {
+            self.validate_dispatch(None)?;
+            None
+        } else {
+            let encoded_requests = self.recv_encoded_requests(&encoded_rx);
+            self.validate_dispatch(Some(&encoded_requests))?;
+            Some(encoded_requests)
+        };
+        self.state_view_service.set_state_view(state_view);
+        // Keep the commands around, to send them to the standbys of slow or dead shards, or to
+        // write replay bundles of the failed ones.
+        let keep_requests =
+            uses_standbys || self.state_view_service.served_state_values().is_some();
+        let mut kept_requests = vec![vec![]; self.num_shards()];
+        // Start receiving before dispatching, so that results of the shards that finish first are
+        // decoded while the block is still being dispatched to the others. Results that may time
+        // out are received in shard order, to discard the late ones.
+        let results_time_out = self
+            .circuit_breakers
+            .as_ref()
+            .map_or(false, |circuit_breakers| {
+                circuit_breakers.result_timeout().is_some()
             });
-
-            senders[shard_id]
-                .lock()
-                .unwrap()
-                .send(Message::new(bcs::to_bytes(&execution_request).unwrap()))
-                .unwrap();
+        let decoded_rx = (self.async_result_aggregation && !uses_standbys && !results_time_out)
+            .then(|| self.spawn_result_receivers());
+        let dispatch_time = Instant::now();
+        let mut dispatch = |shard_id: ShardId, request_bytes: Vec<u8>| {
+            if keep_requests {
+                kept_requests[shard_id] = request_bytes.clone();
+            }
+            self.dispatch_request(shard_id, request_bytes);
+        };
+        match encoded_requests {
+            Some(encoded_requests) => {
+                for (shard_id, request_bytes) in encoded_requests.into_iter().enumerate() {
+                    dispatch(shard_id, request_bytes);
+                }
+            },
+            None => {
+                for _ in 0..self.num_shards() {
+                    let wait_start = Instant::now();
+                    let (shard_id, request_bytes) =
+                        encoded_rx.recv().expect("Every command must be encoded.");
+                    REMOTE_EXECUTOR_SERIALIZATION_SECONDS
+                        .with_label_values(&[&shard_id.to_string(), "dispatch_wait"])
+                        .observe(wait_start.elapsed().as_secs_f64());
+                    dispatch(shard_id, request_bytes);
+                }
+            },
+        }
+        if self.state_view_service.served_state_values().is_some() {
+            *self.replay_requests.lock().unwrap() = Some(kept_requests.clone());
         }
+        let standby_requests = uses_standbys.then_some(kept_requests);
 
-        let execution_results = self.get_output_from_shards()?;
+        let execution_results = match (&self.hedge_delay, &self.mirrors, standby_requests) {
+            (Some(hedge_delay), _, Some(standby_requests)) => self.get_output_with_hedging(
+                hedge_delay,
+                standby_requests,
+                &hedgeable,
+                dispatch_time,
+            ),
+            (_, Some(mirrors), Some(standby_requests)) => {
+                self.get_output_with_failover(mirrors, standby_requests, &hedgeable, dispatch_time)
+            },
+            _ => match decoded_rx {
+                Some(decoded_rx) => self.collect_spawned_results(decoded_rx, dispatch_time),
+                None => self.get_output_from_shards(dispatch_time),
+            },
+        };
+        if let Some(block_aborts) = &self.block_aborts {
+            block_aborts.clear();
+        }
+        let execution_results = execution_results?;
 
-        self.state_view_service.drop_state_view();
+        // Shards that lost a hedging race may still read state values of this block.
+        if !self.has_stale_results() {
+            self.state_view_service.drop_state_view();
+        }
         Ok(ShardedExecutionOutput::new(execution_results, vec![]))
     }
 
@@ -2,13 +2,19 @@
 // SPDX-License-Identifier: Apache-2.0
 
 use crate::{
+    capabilities::{self, ServiceCapabilities},
+    cross_shard_relay::CurrentRouting,
+    heartbeat, hedging,
     remote_cordinator_client::RemoteCoordinatorClient,
-    remote_cross_shard_client::RemoteCrossShardClient, remote_state_view::RemoteStateViewClient,
+    remote_cross_shard_client::RemoteCrossShardClient,
+    remote_state_view::RemoteStateViewClient,
+    resource_limits::ResourceLimits,
+    transport::Transport,
 };
 use aptos_secure_net::network_controller::NetworkController;
 use aptos_types::block_executor::partitioner::ShardId;
 use aptos_vm::sharded_block_executor::sharded_executor_service::ShardedExecutorService;
-use std::{net::SocketAddr, sync::Arc, thread};
+use std::{net::SocketAddr, sync::Arc, thread, time::Duration};
 
 /// A service that provides support for remote execution. Essentially, it reads a request from
 /// the remote executor client and executes the block locally and returns the result.
@@ -25,18 +31,76 @@ impl ExecutorService {
         num_threads: usize,
         self_address: SocketAddr,
         coordinator_address: SocketAddr,
-        remote_shard_addresses: Vec<SocketAddr>,
+        mut remote_shard_addresses: Vec<SocketAddr>,
+        resource_limits: ResourceLimits,
+        speculative_cross_shard_prefetch: bool,
+        heartbeat_interval: Option<Duration>,
+        standby: bool,
+        warm_standby: bool,
+        transport: Transport,
     ) -> Self {
-        let service_name = format!("executor_service-{}", shard_id);
-        let mut controller = NetworkController::new(service_name, self_address, 5000);
+        let num_threads = resource_limits.num_threads(num_threads);
+        let service_name = if standby {
+            format!("executor_service-{}-standby", shard_id)
+        } else {
+            format!("executor_service-{}", shard_id)
+        };
+        let mut controller = NetworkController::new_with_transport(
+            service_name,
+            self_address,
+            5000,
+            transport.into(),
+        );
+        let network_id = if standby {
+            // Cross-shard messages to the own shard stay within the standby.
+            remote_shard_addresses[shard_id] = self_address;
+            hedging::standby_network_id(num_shards, shard_id)
+        } else {
+            // The coordinator only queries and monitors the primaries.
+            capabilities::start_capabilities_responder(
+                &mut controller,
+                coordinator_address,
+                ServiceCapabilities::new(
+                    shard_id,
+                    num_shards,
+                    num_threads,
+                    &resource_limits,
+                    // `ShardedExecutorService` executes the sub-blocks with the VM.
+                    vec![capabilities::TRANSACTION_TYPE_VM.to_string()],
+                    speculative_cross_shard_prefetch,
+                    heartbeat_interval,
+                ),
+            );
+            shard_id
+        };
+        let cross_shard_routing = Arc::new(CurrentRouting::default());
         let coordinator_client = Arc::new(RemoteCoordinatorClient::new(
             shard_id,
+            num_shards,
+            network_id,
             &mut controller,
             coordinator_address,
+            resource_limits,
+            cross_shard_routing.clone(),
+            standby && warm_standby,
         ));
+        if let Some(interval) = heartbeat_interval.filter(|_| !standby) {
+            heartbeat::start_heartbeat_publisher(
+                shard_id,
+                &mut controller,
+                coordinator_address,
+                interval,
+                coordinator_client.status(),
+                coordinator_client.command_rx(),
+            );
+        }
         let cross_shard_client = Arc::new(RemoteCrossShardClient::new(
+            shard_id,
             &mut controller,
+            coordinator_address,
             remote_shard_addresses,
+            speculative_cross_shard_prefetch,
+            cross_shard_routing,
         ));
 
         let executor_service = Arc::new(ShardedExecutorService::new(
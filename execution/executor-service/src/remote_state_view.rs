@@ -1,13 +1,20 @@
 // Copyright © Aptos Foundation
 // SPDX-License-Identifier: Apache-2.0
-use crate::{RemoteKVRequest, RemoteKVResponse};
+use crate::{
+    wire_trace::{self, Direction, WireMessage},
+    RemoteKVRequest, RemoteKVResponse,
+};
 use aptos_secure_net::network_controller::{Message, NetworkController};
 use aptos_types::state_store::state_key::StateKey;
 use aptos_vm::sharded_block_executor::remote_state_value::RemoteStateValue;
 use crossbeam_channel::{Receiver, Sender};
 use std::{
+    collections::HashMap,
     net::SocketAddr,
-    sync::{Arc, RwLock},
+    sync::{
+        atomic::{AtomicU64, Ordering},
+        Arc, RwLock,
+    },
     thread,
 };
 
@@ -53,6 +60,13 @@ impl RemoteStateView {
             .or_insert(RemoteStateValue::waiting());
     }
 
+    /// Inserts a state value that is already known, e.g. mirrored to a warm standby.
+    pub fn insert_state_value(&self, state_key: StateKey, state_value: Option<StateValue>) {
+        let value = RemoteStateValue::waiting();
+        value.set_value(state_value);
+        self.state_values.insert(state_key, value);
+    }
+
     pub fn get_state_value(&self, state_key: &StateKey) -> Result<Option<StateValue>> {
         if let Some(value) = self.state_values.get(state_key) {
             let value_clone = value.clone();
@@ -71,6 +85,8 @@ pub struct RemoteStateViewClient {
     kv_tx: Arc<Sender<Message>>,
     state_view: Arc<RwLock<RemoteStateView>>,
     thread_pool: Arc<rayon::ThreadPool>,
+    /// Number of state values read by the current block.
+    num_state_reads: AtomicU64,
     _join_handle: Option<thread::JoinHandle<()>>,
 }
 
@@ -110,18 +126,52 @@ impl RemoteStateViewClient {
             kv_tx: Arc::new(command_tx),
             state_view,
             thread_pool,
+            num_state_reads: AtomicU64::new(0),
             _join_handle: Some(join_handle),
         }
     }
 
     pub fn init_for_block(&self, state_keys: Vec<StateKey>) {
         *self.state_view.write().unwrap() = RemoteStateView::new();
+        self.num_state_reads.store(0, Ordering::Relaxed);
+        REMOTE_EXECUTOR_REMOTE_KV_COUNT
+            .with_label_values(&[&self.shard_id.to_string(), "prefetch_kv"])
+            .inc_by(state_keys.len() as u64);
+        self.pre_fetch_state_values(state_keys, false);
+    }
+
+    /// Like `init_for_block`, but only fetches the state values missing from `state_values`, the
+    /// values a warm standby already received for the block.
+    pub fn init_for_block_with_values(
+        &self,
+        state_keys: Vec<StateKey>,
+        state_values: HashMap<StateKey, Option<StateValue>>,
+    ) {
+        let state_view = RemoteStateView::new();
+        let num_warm = state_values.len();
+        for (state_key, state_value) in state_values {
+            state_view.insert_state_value(state_key, state_value);
+        }
+        let state_keys = state_keys
+            .into_iter()
+            .filter(|state_key| !state_view.has_state_key(state_key))
+            .collect::<Vec<_>>();
+        *self.state_view.write().unwrap() = state_view;
+        self.num_state_reads.store(0, Ordering::Relaxed);
+        REMOTE_EXECUTOR_REMOTE_KV_COUNT
+            .with_label_values(&[&self.shard_id.to_string(), "warm_kv"])
+            .inc_by(num_warm as u64);
         REMOTE_EXECUTOR_REMOTE_KV_COUNT
             .with_label_values(&[&self.shard_id.to_string(), "prefetch_kv"])
             .inc_by(state_keys.len() as u64);
         self.pre_fetch_state_values(state_keys, false);
     }
 
+    /// Number of state values read since the current block was initialized.
+    pub fn num_state_reads(&self) -> u64 {
+        self.num_state_reads.load(Ordering::Relaxed)
+    }
+
     fn insert_keys_and_fetch_values(
         state_view_clone: Arc<RwLock<RemoteStateView>>,
         thread_pool: Arc<ThreadPool>,
@@ -175,6 +225,12 @@ impl RemoteStateViewClient {
     ) {
         let request = RemoteKVRequest::new(shard_id, state_keys);
         let request_message = bcs::to_bytes(&request).unwrap();
+        wire_trace::trace(
+            Direction::Send,
+            WireMessage::KvRequest,
+            shard_id,
+            &request_message,
+        );
         sender.send(Message::new(request_message)).unwrap();
     }
 }
@@ -183,6 +239,7 @@ impl TStateView for RemoteStateViewClient {
     type Key = StateKey;
 
     fn get_state_value(&self, state_key: &StateKey) -> Result<Option<StateValue>> {
+        self.num_state_reads.fetch_add(1, Ordering::Relaxed);
         let state_view_reader = self.state_view.read().unwrap();
         if state_view_reader.has_state_key(state_key) {
             // If the key is already in the cache then we return it.
@@ -250,6 +307,12 @@ impl RemoteStateValueReceiver {
         let bcs_deser_timer = REMOTE_EXECUTOR_TIMER
             .with_label_values(&[&shard_id.to_string(), "kv_resp_deser"])
             .start_timer();
+        wire_trace::trace(
+            Direction::Receive,
+            WireMessage::KvResponse,
+            shard_id,
+            &message.data,
+        );
         let response: RemoteKVResponse = bcs::from_bytes(&message.data).unwrap();
         drop(bcs_deser_timer);
 
@@ -1,6 +1,12 @@
 // Copyright © Aptos Foundation
 // SPDX-License-Identifier: Apache-2.0
-use crate::{RemoteKVRequest, RemoteKVResponse};
+use crate::{
+    fragmentation, remote_executor_client,
+    replay_bundle::{self, ServedStateValues},
+    warm_standby::Mirrors,
+    wire_trace::{self, Direction, WireMessage},
+    RemoteKVRequest, RemoteKVResponse,
+};
 use aptos_secure_net::network_controller::{Message, NetworkController};
 use crossbeam_channel::{Receiver, Sender};
 use std::{
@@ -19,6 +25,10 @@ pub struct RemoteStateViewService<S: StateView + Sync + Send + 'static> {
     kv_tx: Arc<Vec<Sender<Message>>>,
     thread_pool: Arc<rayon::ThreadPool>,
     state_view: Arc<RwLock<Option<Arc<S>>>>,
+    // State values served for the current block, recorded if replay bundles are enabled.
+    served_state_values: Option<Arc<ServedStateValues>>,
+    // Warm standbys the state values served to their shards are mirrored to, if enabled.
+    mirrors: Option<Arc<Mirrors>>,
 }
 
 impl<S: StateView + Sync + Send + 'static> RemoteStateViewService<S> {
@@ -43,15 +53,31 @@ impl<S: StateView + Sync + Send + 'static> RemoteStateViewService<S> {
                 controller.create_outbound_channel(*address, kv_response_type.to_string())
             })
             .collect_vec();
+        let served_state_values = replay_bundle::get_replay_bundle_dir()
+            .map(|_| Arc::new(ServedStateValues::new(remote_shard_addresses.len())));
         Self {
             kv_rx: result_rx,
             kv_tx: Arc::new(command_txs),
             thread_pool,
             state_view: Arc::new(RwLock::new(None)),
+            served_state_values,
+            mirrors: None,
         }
     }
 
+    pub(crate) fn with_mirrors(mut self, mirrors: Option<Arc<Mirrors>>) -> Self {
+        self.mirrors = mirrors;
+        self
+    }
+
+    pub(crate) fn served_state_values(&self) -> Option<&ServedStateValues> {
+        self.served_state_values.as_deref()
+    }
+
     pub fn set_state_view(&self, state_view: Arc<S>) {
+        if let Some(served_state_values) = &self.served_state_values {
+            served_state_values.clear();
+        }
         let mut state_view_lock = self.state_view.write().unwrap();
         *state_view_lock = Some(state_view);
     }
@@ -65,8 +91,10 @@ impl<S: StateView + Sync + Send + 'static> RemoteStateViewService<S> {
         while let Ok(message) = self.kv_rx.recv() {
             let state_view = self.state_view.clone();
             let kv_txs = self.kv_tx.clone();
+            let served_state_values = self.served_state_values.clone();
+            let mirrors = self.mirrors.clone();
             self.thread_pool.spawn(move || {
-                Self::handle_message(message, state_view, kv_txs);
+                Self::handle_message(message, state_view, kv_txs, served_state_values, mirrors);
             });
         }
     }
@@ -75,6 +103,8 @@ impl<S: StateView + Sync + Send + 'static> RemoteStateViewService<S> {
         message: Message,
         state_view: Arc<RwLock<Option<Arc<S>>>>,
         kv_tx: Arc<Vec<Sender<Message>>>,
+        served_state_values: Option<Arc<ServedStateValues>>,
+        mirrors: Option<Arc<Mirrors>>,
     ) {
         // we don't know the shard id until we deserialize the message, so lets default it to 0
         let _timer = REMOTE_EXECUTOR_TIMER
@@ -87,6 +117,12 @@ impl<S: StateView + Sync + Send + 'static> RemoteStateViewService<S> {
         drop(bcs_deser_timer);
 
         let (shard_id, state_keys) = req.into();
+        wire_trace::trace(
+            Direction::Receive,
+            WireMessage::KvRequest,
+            shard_id,
+            &message.data,
+        );
         trace!(
             "remote state view service - received request for shard {} with {} keys",
             shard_id,
@@ -105,19 +141,34 @@ impl<S: StateView + Sync + Send + 'static> RemoteStateViewService<S> {
                 (state_key, state_value)
             })
             .collect_vec();
-        let len = resp.len();
-        let resp = RemoteKVResponse::new(resp);
-        let bcs_ser_timer = REMOTE_EXECUTOR_TIMER
-            .with_label_values(&["0", "kv_resp_ser"])
-            .start_timer();
-        let resp = bcs::to_bytes(&resp).unwrap();
-        drop(bcs_ser_timer);
-        trace!(
-            "remote state view service - sending response for shard {} with {} keys",
-            shard_id,
-            len
-        );
-        let message = Message::new(resp);
-        kv_tx[shard_id].send(message).unwrap();
+        if let Some(served_state_values) = &served_state_values {
+            served_state_values.record(shard_id, &resp);
+        }
+        // Mirrored before the shard gets them, so that they reach the standby before the next
+        // block is mirrored.
+        if let Some(mirrors) = &mirrors {
+            mirrors.mirror_state_values(shard_id, &resp);
+        }
+        // Large responses are sent as several responses that fit, each of which the shard
+        // handles on its own.
+        for resp in
+            fragmentation::split_state_values(resp, remote_executor_client::get_max_payload_bytes())
+        {
+            let len = resp.len();
+            let resp = RemoteKVResponse::new(resp);
+            let bcs_ser_timer = REMOTE_EXECUTOR_TIMER
+                .with_label_values(&["0", "kv_resp_ser"])
+                .start_timer();
+            let resp = bcs::to_bytes(&resp).unwrap();
+            drop(bcs_ser_timer);
+            trace!(
+                "remote state view service - sending response for shard {} with {} keys",
+                shard_id,
+                len
+            );
+            wire_trace::trace(Direction::Send, WireMessage::KvResponse, shard_id, &resp);
+            let message = Message::new(resp);
+            kv_tx[shard_id].send(message).unwrap();
+        }
     }
 }
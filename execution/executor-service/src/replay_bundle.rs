@@ -0,0 +1,265 @@
+// Copyright © Aptos Foundation
+// SPDX-License-Identifier: Apache-2.0
+
+//! Replay bundles of failed shard commands, to reproduce a failure of sharded execution locally.
+//!
+//! When enabled on the coordinator, with a directory to write them to, every shard command that
+//! comes back with an error is written to a bundle, along with the state values the shard read
+//! from the coordinator while executing it, and the error. `aptos-executor-service replay-bundle`
+//! executes the command of a bundle again, on the recorded state values, in a single process.
+//!
+//! The cross-shard messages a shard exchanges with the other shards don't go through the
+//! coordinator, and are not part of the bundle, so only commands that don't wait for values of
+//! other shards can be replayed. The messages a replayed command sends to other shards are
+//! dropped.
+
+use crate::{
+    versioning::{self, Decoded},
+    RemoteExecutionRequest,
+};
+use anyhow::{anyhow, bail, Context, Result};
+use aptos_infallible::Mutex;
+use aptos_logger::{info, warn};
+use aptos_state_view::in_memory_state_view::InMemoryStateView;
+use aptos_types::{
+    block_executor::partitioner::{RoundId, ShardId, MAX_ALLOWED_PARTITIONING_ROUNDS},
+    state_store::{state_key::StateKey, state_value::StateValue},
+    transaction::TransactionOutput,
+    vm_status::VMStatus,
+};
+use aptos_vm::sharded_block_executor::{
+    coordinator_client::CoordinatorClient, cross_shard_client::CrossShardClient,
+    messages::CrossShardMsg, sharded_executor_service::ShardedExecutorService,
+    ExecutorShardCommand,
+};
+use crossbeam_channel::{Receiver, Sender};
+use once_cell::sync::OnceCell;
+use serde::{Deserialize, Serialize};
+use std::{
+    collections::HashMap,
+    fs,
+    path::{Path, PathBuf},
+    sync::Arc,
+    time::{SystemTime, UNIX_EPOCH},
+};
+
+static REPLAY_BUNDLE_DIR: OnceCell<PathBuf> = OnceCell::new();
+
+/// Enables writing a replay bundle into `dir` for every shard command that fails.
+pub fn set_replay_bundle_dir_once(dir: PathBuf) {
+    REPLAY_BUNDLE_DIR.set(dir).ok();
+}
+
+pub fn get_replay_bundle_dir() -> Option<&'static Path> {
+    REPLAY_BUNDLE_DIR.get().map(PathBuf::as_path)
+}
+
+#[derive(Debug, Deserialize, Serialize)]
+pub struct ReplayBundle {
+    pub shard_id: ShardId,
+    pub num_shards: usize,
+    /// The command sent to the shard, as it was sent on the wire.
+    pub request: Vec<u8>,
+    /// The state values the shard read from the coordinator while executing the command, `None`
+    /// for keys without a value.
+    pub state_values: Vec<(StateKey, Option<StateValue>)>,
+    /// The error the shard returned.
+    pub error: VMStatus,
+}
+
+impl ReplayBundle {
+    /// Writes the bundle into the replay bundle directory, and returns its path.
+    pub(crate) fn write(&self) -> Result<PathBuf> {
+        let dir = get_replay_bundle_dir().ok_or_else(|| anyhow!("Replay bundles are disabled"))?;
+        fs::create_dir_all(dir)?;
+        let timestamp_ms = SystemTime::now()
+            .duration_since(UNIX_EPOCH)
+            .map_or(0, |duration| duration.as_millis());
+        let path = dir.join(format!(
+            "replay_shard_{}_{}.bcs",
+            self.shard_id, timestamp_ms
+        ));
+        fs::write(&path, bcs::to_bytes(self)?)?;
+        Ok(path)
+    }
+
+    pub fn load(path: &Path) -> Result<Self> {
+        let bytes =
+            fs::read(path).with_context(|| format!("Cannot read replay bundle {:?}", path))?;
+        Ok(bcs::from_bytes(&bytes)?)
+    }
+
+    /// Executes the command of the bundle on the recorded state values, with `num_threads`
+    /// threads, and returns its result.
+    pub fn replay(
+        &self,
+        num_threads: usize,
+    ) -> Result<Result<Vec<Vec<TransactionOutput>>, VMStatus>> {
+        let command = match versioning::decode::<RemoteExecutionRequest>(&self.request)? {
+            Decoded::Known(RemoteExecutionRequest::ExecuteBlock(command))
+            | Decoded::Known(RemoteExecutionRequest::ExecuteBlockInTopology(command, _)) => command,
+            Decoded::Unknown { version, variant } => bail!(
+                "Cannot replay request variant {} of protocol version {}",
+                variant,
+                version
+            ),
+        };
+        let (sub_blocks, concurrency_level, maybe_block_gas_limit) = command.into();
+        if sub_blocks
+            .iter()
+            .any(|txn| !txn.cross_shard_dependencies.required_edges().is_empty())
+        {
+            bail!(
+                "The command waits for values of other shards, which replay bundles don't record"
+            );
+        }
+        info!(
+            "Replaying {} transactions of shard {} of {}, on {} recorded state values",
+            sub_blocks.num_txns(),
+            self.shard_id,
+            self.num_shards,
+            self.state_values.len()
+        );
+        let state_view = InMemoryStateView::new(
+            self.state_values
+                .iter()
+                .filter_map(|(key, value)| Some((key.clone(), value.clone()?)))
+                .collect(),
+        );
+        let coordinator_client = Arc::new(ReplayCoordinatorClient {
+            command: Mutex::new(Some(ExecutorShardCommand::ExecuteSubBlocks(
+                Arc::new(state_view),
+                sub_blocks,
+                concurrency_level,
+                maybe_block_gas_limit,
+            ))),
+            result: Mutex::new(None),
+        });
+        let executor_service = ShardedExecutorService::new(
+            self.shard_id,
+            self.num_shards,
+            num_threads,
+            coordinator_client.clone(),
+            Arc::new(LoopbackCrossShardClient::new(self.shard_id)),
+        );
+        // Returns once the command is executed, as the coordinator client stops it then.
+        executor_service.start();
+        let result = coordinator_client.result.lock().take();
+        result.ok_or_else(|| anyhow!("The replayed command returned no result"))
+    }
+}
+
+/// Hands the recorded command to the shard, and then stops it.
+struct ReplayCoordinatorClient {
+    command: Mutex<Option<ExecutorShardCommand<InMemoryStateView>>>,
+    result: Mutex<Option<Result<Vec<Vec<TransactionOutput>>, VMStatus>>>,
+}
+
+impl CoordinatorClient<InMemoryStateView> for ReplayCoordinatorClient {
+    fn receive_execute_command(&self) -> ExecutorShardCommand<InMemoryStateView> {
+        self.command
+            .lock()
+            .take()
+            .unwrap_or(ExecutorShardCommand::Stop)
+    }
+
+    fn send_execution_result(&self, result: Result<Vec<Vec<TransactionOutput>>, VMStatus>) {
+        *self.result.lock() = Some(result);
+    }
+}
+
+/// Delivers the messages the shard sends to itself, e.g. to stop receiving the messages of a
+/// round. The other shards are not part of a replay, the messages to them are dropped.
+struct LoopbackCrossShardClient {
+    shard_id: ShardId,
+    // Messages to the shard itself, per round.
+    messages: Vec<(Sender<CrossShardMsg>, Receiver<CrossShardMsg>)>,
+}
+
+impl LoopbackCrossShardClient {
+    fn new(shard_id: ShardId) -> Self {
+        Self {
+            shard_id,
+            messages: (0..MAX_ALLOWED_PARTITIONING_ROUNDS)
+                .map(|_| crossbeam_channel::unbounded())
+                .collect(),
+        }
+    }
+}
+
+impl CrossShardClient for LoopbackCrossShardClient {
+    fn send_global_msg(&self, _msg: CrossShardMsg) {}
+
+    fn send_cross_shard_msg(&self, shard_id: ShardId, round: RoundId, msg: CrossShardMsg) {
+        if shard_id == self.shard_id {
+            self.messages[round].0.send(msg).unwrap();
+        }
+    }
+
+    fn receive_cross_shard_msg(&self, current_round: RoundId) -> CrossShardMsg {
+        self.messages[current_round].1.recv().unwrap()
+    }
+}
+
+/// State values served to every shard (by the id it is addressed by on the network) for the
+/// current block, to put them into the replay bundle of the shard if it fails.
+pub(crate) struct ServedStateValues {
+    shards: Vec<Mutex<HashMap<StateKey, Option<StateValue>>>>,
+}
+
+impl ServedStateValues {
+    pub(crate) fn new(num_network_ids: usize) -> Self {
+        Self {
+            shards: (0..num_network_ids)
+                .map(|_| Mutex::new(HashMap::new()))
+                .collect(),
+        }
+    }
+
+    pub(crate) fn clear(&self) {
+        for shard in &self.shards {
+            shard.lock().clear();
+        }
+    }
+
+    pub(crate) fn record(&self, network_id: usize, values: &[(StateKey, Option<StateValue>)]) {
+        match self.shards.get(network_id) {
+            Some(shard) => shard.lock().extend(values.iter().cloned()),
+            None => warn!("State values served to unknown shard {}", network_id),
+        }
+    }
+
+    /// Returns the state values served to all of `network_ids`, e.g. a shard and its standby.
+    pub(crate) fn get(&self, network_ids: &[usize]) -> Vec<(StateKey, Option<StateValue>)> {
+        let mut values = HashMap::new();
+        for network_id in network_ids {
+            if let Some(shard) = self.shards.get(*network_id) {
+                values.extend(shard.lock().iter().map(|(k, v)| (k.clone(), v.clone())));
+            }
+        }
+        values.into_iter().collect()
+    }
+}
+
+#[test]
+fn test_served_state_values() {
+    let served_state_values = ServedStateValues::new(4);
+    let key = |byte: u8| StateKey::raw(vec![byte]);
+    served_state_values.record(0, &[
+        (key(1), Some(StateValue::from(vec![1]))),
+        (key(2), None),
+    ]);
+    served_state_values.record(2, &[(key(3), Some(StateValue::from(vec![3])))]);
+
+    let mut values = served_state_values.get(&[0, 2]);
+    values.sort_by_key(|(key, _)| key.clone());
+    assert_eq!(values, vec![
+        (key(1), Some(StateValue::from(vec![1]))),
+        (key(2), None),
+        (key(3), Some(StateValue::from(vec![3]))),
+    ]);
+    assert_eq!(served_state_values.get(&[1]), vec![]);
+
+    served_state_values.clear();
+    assert_eq!(served_state_values.get(&[0, 2]), vec![]);
+}
@@ -0,0 +1,129 @@
+// Copyright © Aptos Foundation
+// SPDX-License-Identifier: Apache-2.0
+
+use crate::metrics::{REMOTE_EXECUTOR_REJECTED_BLOCKS, REMOTE_EXECUTOR_RESOURCE_UTILIZATION};
+use aptos_logger::{info, warn};
+use aptos_types::{block_executor::partitioner::ShardId, vm_status::StatusCode};
+use std::fs;
+
+/// The in-memory footprint of a block while it executes (deserialized transactions, state values
+/// and outputs) is estimated as this multiple of its serialized command size.
+const BLOCK_MEMORY_AMPLIFICATION: u64 = 4;
+
+fn read_status_bytes(field: &str) -> Option<u64> {
+    let status = fs::read_to_string("/proc/self/status").ok()?;
+    status
+        .lines()
+        .find(|line| line.starts_with(field))
+        .and_then(|line| line.split_whitespace().nth(1))
+        .and_then(|kb| kb.parse::<u64>().ok())
+        .map(|kb| kb * 1024)
+}
+
+/// Returns the resident set size of the current process, if it can be determined.
+pub fn process_rss_bytes() -> Option<u64> {
+    read_status_bytes("VmRSS:")
+}
+
+/// Returns the peak resident set size of the current process since it started, or since the
+/// last `reset_peak_rss`, if it can be determined.
+pub(crate) fn peak_rss_bytes() -> Option<u64> {
+    read_status_bytes("VmHWM:")
+}
+
+/// Resets the peak resident set size to the current one, where the kernel supports it.
+pub(crate) fn reset_peak_rss() {
+    // Writing 5 to clear_refs resets the peak RSS of the process.
+    fs::write("/proc/self/clear_refs", "5").ok();
+}
+
+/// Resource budget of a single executor service instance, so that several shard processes
+/// co-located on one host don't destabilize each other.
+#[derive(Clone, Copy, Debug, Default)]
+pub struct ResourceLimits {
+    /// Blocks that would push the process RSS above this budget are rejected.
+    pub max_memory_bytes: Option<u64>,
+    /// Upper bound on the number of threads used to execute a block, regardless of the
+    /// concurrency level requested by the coordinator.
+    pub max_execution_threads: Option<usize>,
+}
+
+impl ResourceLimits {
+    /// Returns the number of threads to use when `requested` threads are asked for.
+    pub fn num_threads(&self, requested: usize) -> usize {
+        match self.max_execution_threads {
+            Some(max_threads) => requested.min(max_threads).max(1),
+            None => requested,
+        }
+    }
+
+    /// Checks whether a block, received as a command of `command_bytes` serialized bytes, fits in
+    /// the memory budget, and reports the current utilization of the shard.
+    pub fn admit_block(
+        &self,
+        shard_id: ShardId,
+        command_bytes: usize,
+        concurrency_level: usize,
+    ) -> Result<(), (StatusCode, String)> {
+        let shard_label = shard_id.to_string();
+        let rss = process_rss_bytes();
+        if let Some(rss) = rss {
+            REMOTE_EXECUTOR_RESOURCE_UTILIZATION
+                .with_label_values(&[&shard_label, "memory_bytes"])
+                .set(rss as f64);
+        }
+        REMOTE_EXECUTOR_RESOURCE_UTILIZATION
+            .with_label_values(&[&shard_label, "execution_threads"])
+            .set(concurrency_level as f64);
+
+        let (max_memory_bytes, rss) = match (self.max_memory_bytes, rss) {
+            (Some(max_memory_bytes), Some(rss)) => (max_memory_bytes, rss),
+            _ => return Ok(()),
+        };
+        REMOTE_EXECUTOR_RESOURCE_UTILIZATION
+            .with_label_values(&[&shard_label, "memory_budget_fraction"])
+            .set(rss as f64 / max_memory_bytes as f64);
+        let estimated_bytes = rss + command_bytes as u64 * BLOCK_MEMORY_AMPLIFICATION;
+        info!(
+            "Shard {} memory utilization: {} / {} bytes, estimated {} bytes with the next block",
+            shard_id, rss, max_memory_bytes, estimated_bytes
+        );
+        if !fits_memory_budget(rss, command_bytes, max_memory_bytes) {
+            REMOTE_EXECUTOR_REJECTED_BLOCKS
+                .with_label_values(&[&shard_label])
+                .inc();
+            let message = format!(
+                "Shard {} rejected block: estimated memory {} bytes exceeds the budget of {} bytes",
+                shard_id, estimated_bytes, max_memory_bytes
+            );
+            warn!("{}", message);
+            return Err((StatusCode::MEMORY_LIMIT_EXCEEDED, message));
+        }
+        Ok(())
+    }
+
+    /// Logs a warning if the memory budget cannot be enforced on this platform.
+    pub fn check_supported(&self) {
+        if self.max_memory_bytes.is_some() && process_rss_bytes().is_none() {
+            warn!("Cannot read process RSS on this platform, memory budget is not enforced.");
+        }
+    }
+}
+
+fn fits_memory_budget(rss: u64, command_bytes: usize, max_memory_bytes: u64) -> bool {
+    rss + command_bytes as u64 * BLOCK_MEMORY_AMPLIFICATION <= max_memory_bytes
+}
+
+#[test]
+fn test_resource_limits() {
+    let limits = ResourceLimits {
+        max_memory_bytes: None,
+        max_execution_threads: Some(4),
+    };
+    assert_eq!(limits.num_threads(8), 4);
+    assert_eq!(limits.num_threads(2), 2);
+    assert_eq!(ResourceLimits::default().num_threads(8), 8);
+
+    assert!(fits_memory_budget(100, 25, 200));
+    assert!(!fits_memory_budget(100, 26, 200));
+}
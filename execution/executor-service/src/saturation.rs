@@ -0,0 +1,380 @@
+// Copyright © Aptos Foundation
+// SPDX-License-Identifier: Apache-2.0
+
+//! Saturation benchmark of a single executor shard.
+//!
+//! Several clients, each with an independent stream of blocks, hammer one shard concurrently, to
+//! find the point at which the shard saturates and how fairly it serves the clients. The clients
+//! share a single coordinator endpoint: the shard executes commands one at a time and answers them
+//! in the order they arrived, so results are matched to clients in dispatch order, without any
+//! changes to the wire format.
+
+use crate::{
+    heartbeat,
+    remote_state_view_service::RemoteStateViewService,
+    versioning::{self, Decoded},
+    wire_trace::{self, Direction, WireMessage},
+    ExecuteBlockCommand, RemoteExecutionRequest, RemoteExecutionResult,
+};
+use aptos_block_partitioner::{v2::config::PartitionerV2Config, PartitionerConfig};
+use aptos_language_e2e_tests::{
+    common_transactions::peer_to_peer_txn, data_store::FakeDataStore, executor::FakeExecutor,
+};
+use aptos_logger::info;
+use aptos_secure_net::network_controller::{Message, NetworkController};
+use aptos_state_view::StateView;
+use aptos_types::{
+    block_executor::partitioner::SubBlocksForShard,
+    transaction::{analyzed_transaction::AnalyzedTransaction, Transaction},
+};
+use crossbeam_channel::{Receiver, Sender};
+use std::{
+    collections::VecDeque,
+    net::SocketAddr,
+    sync::{Arc, Mutex},
+    thread,
+    time::{Duration, Instant},
+};
+
+/// The saturation benchmark talks to a single shard.
+const SHARD_ID: usize = 0;
+
+/// A request sent to the shard and not answered yet.
+struct PendingRequest {
+    sent_at: Instant,
+    /// Number of requests ahead of this one at the shard when it was sent.
+    queue_depth: usize,
+    reply_tx: Sender<Completion>,
+}
+
+/// Timing of a request, as seen by the client that sent it.
+struct Completion {
+    succeeded: bool,
+    latency: Duration,
+    /// Time the request spent queued at the shard behind requests of other clients.
+    queueing_delay: Duration,
+    queue_depth: usize,
+}
+
+#[derive(Debug)]
+pub struct ClientReport {
+    pub client_id: usize,
+    pub num_blocks: usize,
+    pub num_txns: usize,
+    pub num_failed_blocks: usize,
+    pub tps: f64,
+    pub avg_latency: Duration,
+    pub p50_latency: Duration,
+    pub p99_latency: Duration,
+    pub avg_queueing_delay: Duration,
+    pub max_queue_depth: usize,
+}
+
+#[derive(Debug)]
+pub struct SaturationReport {
+    pub clients: Vec<ClientReport>,
+    pub elapsed: Duration,
+}
+
+impl SaturationReport {
+    pub fn total_tps(&self) -> f64 {
+        self.clients.iter().map(|client| client.tps).sum()
+    }
+
+    /// Jain's fairness index of the client throughputs: 1 if all clients got the same throughput,
+    /// down to 1/n if a single client got all of it.
+    pub fn fairness_index(&self) -> f64 {
+        let sum: f64 = self.clients.iter().map(|client| client.tps).sum();
+        let sum_of_squares: f64 = self.clients.iter().map(|client| client.tps.powi(2)).sum();
+        if sum_of_squares == 0.0 {
+            return 1.0;
+        }
+        sum.powi(2) / (self.clients.len() as f64 * sum_of_squares)
+    }
+
+    pub fn print(&self) {
+        for client in &self.clients {
+            info!(
+                "Client {}: {} blocks ({} failed), {} txns, {:.0} TPS, latency avg {} ms, p50 {} ms, p99 {} ms, queueing delay avg {} ms, max queue depth {}",
+                client.client_id,
+                client.num_blocks,
+                client.num_failed_blocks,
+                client.num_txns,
+                client.tps,
+                client.avg_latency.as_millis(),
+                client.p50_latency.as_millis(),
+                client.p99_latency.as_millis(),
+                client.avg_queueing_delay.as_millis(),
+                client.max_queue_depth,
+            );
+        }
+        info!(
+            "Shard saturation: {} clients, total {:.0} TPS in {:.1} s, fairness index {:.3}",
+            self.clients.len(),
+            self.total_tps(),
+            self.elapsed.as_secs_f64(),
+            self.fairness_index(),
+        );
+    }
+}
+
+/// A stream of `num_blocks` blocks of `block_size` transfers between fresh accounts for each of
+/// `num_clients` clients, none of them conflicting, along with the state to execute them on.
+pub fn generate_transfer_block_streams(
+    num_clients: usize,
+    num_blocks: usize,
+    block_size: usize,
+) -> (
+    FakeDataStore,
+    Vec<Vec<SubBlocksForShard<AnalyzedTransaction>>>,
+) {
+    let mut executor = FakeExecutor::from_head_genesis();
+    let partitioner = PartitionerV2Config::default().build();
+    let block_streams = (0..num_clients)
+        .map(|_| {
+            (0..num_blocks)
+                .map(|_| {
+                    let transactions = (0..block_size)
+                        .map(|_| {
+                            let sender = executor.create_raw_account_data(3_000_000_000, 0);
+                            let receiver = executor.create_raw_account_data(3_000_000_000, 0);
+                            executor.add_account_data(&sender);
+                            executor.add_account_data(&receiver);
+                            Transaction::UserTransaction(peer_to_peer_txn(
+                                sender.account(),
+                                receiver.account(),
+                                sender.sequence_number(),
+                                1_000,
+                                100,
+                            ))
+                            .into()
+                        })
+                        .collect();
+                    let (mut sub_blocks, _) = partitioner.partition(transactions, 1).into();
+                    sub_blocks.remove(0)
+                })
+                .collect()
+        })
+        .collect();
+    (executor.data_store().clone(), block_streams)
+}
+
+/// Coordinator endpoint shared by all the clients of the saturation benchmark.
+pub struct SaturationBenchmark<S: StateView + Sync + Send + 'static> {
+    network_controller: NetworkController,
+    state_view_service: Arc<RemoteStateViewService<S>>,
+    command_tx: Sender<Message>,
+    result_rx: Receiver<Message>,
+    // Requests in the order they were sent, guarded together with sending, so that the order
+    // matches the order the shard receives them in.
+    pending: Arc<Mutex<VecDeque<PendingRequest>>>,
+}
+
+impl<S: StateView + Sync + Send + 'static> SaturationBenchmark<S> {
+    /// `shard_address` is the address of a shard started with shard id 0, out of 1 shard.
+    pub fn new(coordinator_address: SocketAddr, shard_address: SocketAddr) -> Self {
+        let mut controller = NetworkController::new(
+            "saturation-coordinator".to_string(),
+            coordinator_address,
+            5000,
+        );
+        let command_tx = controller
+            .create_outbound_channel(shard_address, format!("execute_command_{}", SHARD_ID));
+        let result_rx = controller.create_inbound_channel(format!("execute_result_{}", SHARD_ID));
+        if let Some(timeout) = crate::remote_executor_client::get_heartbeat_timeout() {
+            heartbeat::start_heartbeat_monitor(&mut controller, 1, timeout);
+        }
+        let state_view_service = Arc::new(RemoteStateViewService::new(
+            &mut controller,
+            vec![shard_address],
+            None,
+        ));
+        let state_view_service_clone = state_view_service.clone();
+        thread::Builder::new()
+            .name("saturation-state_view-service".to_string())
+            .spawn(move || state_view_service_clone.start())
+            .expect("Failed to spawn state view service thread.");
+        controller.start();
+
+        Self {
+            network_controller: controller,
+            state_view_service,
+            command_tx,
+            result_rx,
+            pending: Arc::new(Mutex::new(VecDeque::new())),
+        }
+    }
+
+    /// Runs every block stream on a client thread of its own, each keeping one block in flight,
+    /// and reports the throughput and latency of every client.
+    /// All blocks are executed on top of `state_view`, nothing is committed.
+    pub fn run(
+        &self,
+        state_view: Arc<S>,
+        block_streams: Vec<Vec<SubBlocksForShard<AnalyzedTransaction>>>,
+        concurrency_level: usize,
+    ) -> SaturationReport {
+        self.state_view_service.set_state_view(state_view);
+        let num_requests: usize = block_streams.iter().map(|blocks| blocks.len()).sum();
+        let start_time = Instant::now();
+
+        let result_rx = self.result_rx.clone();
+        let pending = self.pending.clone();
+        let router = thread::Builder::new()
+            .name("saturation-result-router".to_string())
+            .spawn(move || route_results(result_rx, pending, num_requests))
+            .expect("Failed to spawn saturation result router thread.");
+        let client_handles = block_streams
+            .into_iter()
+            .enumerate()
+            .map(|(client_id, blocks)| {
+                let command_tx = self.command_tx.clone();
+                let pending = self.pending.clone();
+                thread::Builder::new()
+                    .name(format!("saturation-client-{}", client_id))
+                    .spawn(move || {
+                        run_client(
+                            client_id,
+                            blocks,
+                            concurrency_level,
+                            command_tx,
+                            pending,
+                            start_time,
+                        )
+                    })
+                    .expect("Failed to spawn saturation client thread.")
+            })
+            .collect::<Vec<_>>();
+        let clients = client_handles
+            .into_iter()
+            .map(|handle| handle.join().unwrap())
+            .collect();
+        router.join().unwrap();
+
+        self.state_view_service.drop_state_view();
+        SaturationReport {
+            clients,
+            elapsed: start_time.elapsed(),
+        }
+    }
+
+    pub fn shutdown(&mut self) {
+        self.network_controller.shutdown();
+    }
+}
+
+fn run_client(
+    client_id: usize,
+    blocks: Vec<SubBlocksForShard<AnalyzedTransaction>>,
+    concurrency_level: usize,
+    command_tx: Sender<Message>,
+    pending: Arc<Mutex<VecDeque<PendingRequest>>>,
+    start_time: Instant,
+) -> ClientReport {
+    let (reply_tx, reply_rx) = crossbeam_channel::bounded(1);
+    let mut num_txns = 0;
+    let mut num_failed_blocks = 0;
+    let mut latencies = Vec::with_capacity(blocks.len());
+    let mut total_queueing_delay = Duration::ZERO;
+    let mut max_queue_depth = 0;
+    let num_blocks = blocks.len();
+    for sub_blocks in blocks {
+        let block_size = sub_blocks.num_txns();
+        let request = RemoteExecutionRequest::ExecuteBlock(ExecuteBlockCommand {
+            sub_blocks,
+            concurrency_level,
+            maybe_block_gas_limit: None,
+        });
+        let request_bytes = versioning::encode(&request).unwrap();
+        wire_trace::trace(
+            Direction::Send,
+            WireMessage::ExecuteCommand,
+            SHARD_ID,
+            &request_bytes,
+        );
+        let message = Message::new(request_bytes);
+        {
+            let mut pending = pending.lock().unwrap();
+            let queue_depth = pending.len();
+            pending.push_back(PendingRequest {
+                sent_at: Instant::now(),
+                queue_depth,
+                reply_tx: reply_tx.clone(),
+            });
+            command_tx.send(message).unwrap();
+        }
+        let completion = reply_rx.recv().unwrap();
+        if completion.succeeded {
+            num_txns += block_size;
+        } else {
+            num_failed_blocks += 1;
+        }
+        latencies.push(completion.latency);
+        total_queueing_delay += completion.queueing_delay;
+        max_queue_depth = max_queue_depth.max(completion.queue_depth);
+    }
+
+    let elapsed = start_time.elapsed();
+    latencies.sort();
+    let percentile = |p: usize| {
+        latencies
+            .get((latencies.len() * p / 100).min(latencies.len().saturating_sub(1)))
+            .copied()
+            .unwrap_or_default()
+    };
+    ClientReport {
+        client_id,
+        num_blocks,
+        num_txns,
+        num_failed_blocks,
+        tps: num_txns as f64 / elapsed.as_secs_f64(),
+        avg_latency: latencies.iter().sum::<Duration>() / num_blocks.max(1) as u32,
+        p50_latency: percentile(50),
+        p99_latency: percentile(99),
+        avg_queueing_delay: total_queueing_delay / num_blocks.max(1) as u32,
+        max_queue_depth,
+    }
+}
+
+/// Matches the results of the shard, which arrive in request order, to the clients.
+fn route_results(
+    result_rx: Receiver<Message>,
+    pending: Arc<Mutex<VecDeque<PendingRequest>>>,
+    num_requests: usize,
+) {
+    let mut last_result_time: Option<Instant> = None;
+    for _ in 0..num_requests {
+        let received_bytes = result_rx.recv().unwrap().to_bytes();
+        let received_at = Instant::now();
+        wire_trace::trace(
+            Direction::Receive,
+            WireMessage::ExecuteResult,
+            SHARD_ID,
+            &received_bytes,
+        );
+        let request = pending
+            .lock()
+            .unwrap()
+            .pop_front()
+            .expect("Shard sent a result for a request that was never sent.");
+        let succeeded = matches!(
+            versioning::decode::<RemoteExecutionResult>(&received_bytes),
+            Ok(Decoded::Known(RemoteExecutionResult { inner: Ok(_), .. }))
+        );
+        // The shard executes one block at a time, so a request waits at least until the
+        // previous one was answered.
+        let queueing_delay = last_result_time.map_or(Duration::ZERO, |time| {
+            time.saturating_duration_since(request.sent_at)
+        });
+        last_result_time = Some(received_at);
+        request
+            .reply_tx
+            .send(Completion {
+                succeeded,
+                latency: received_at.duration_since(request.sent_at),
+                queueing_delay,
+                queue_depth: request.queue_depth,
+            })
+            .unwrap();
+    }
+}
@@ -3,7 +3,7 @@
 
 use crate::{
     remote_executor_client::RemoteExecutorClient, test_utils,
-    thread_executor_service::ThreadExecutorService,
+    thread_executor_service::ThreadExecutorService, transport::Transport,
 };
 use aptos_config::utils;
 use aptos_language_e2e_tests::data_store::FakeDataStore;
@@ -14,6 +14,8 @@ use std::net::{IpAddr, Ipv4Addr, SocketAddr};
 pub fn create_thread_remote_executor_shards(
     num_shards: usize,
     num_threads: Option<usize>,
+    speculative_cross_shard_prefetch: bool,
+    transport: Transport,
 ) -> (
     RemoteExecutorClient<FakeDataStore>,
     Vec<ThreadExecutorService>,
@@ -21,10 +23,11 @@ pub fn create_thread_remote_executor_shards(
     // First create the coordinator.
     let listen_port = utils::get_available_port();
     let coordinator_address = SocketAddr::new(IpAddr::V4(Ipv4Addr::LOCALHOST), listen_port);
-    let controller = NetworkController::new(
+    let controller = NetworkController::new_with_transport(
         "remote-executor-coordinator".to_string(),
         coordinator_address,
         5000,
+        transport.into(),
     );
     let remote_shard_addresses = (0..num_shards)
         .map(|_| {
@@ -44,6 +47,8 @@ pub fn create_thread_remote_executor_shards(
                 num_threads,
                 coordinator_address,
                 remote_shard_addresses.clone(),
+                speculative_cross_shard_prefetch,
+                transport,
             )
         })
         .collect::<Vec<_>>();
@@ -59,7 +64,7 @@ fn test_sharded_block_executor_no_conflict() {
 
     let num_shards = 8;
     let (executor_client, mut executor_services) =
-        create_thread_remote_executor_shards(num_shards, Some(2));
+        create_thread_remote_executor_shards(num_shards, Some(2), false, Transport::Tcp);
     let sharded_block_executor = ShardedBlockExecutor::new(executor_client);
 
     // wait for the servers to be ready before sending messages
@@ -79,7 +84,7 @@ fn test_sharded_block_executor_with_conflict() {
 
     let num_shards = 8;
     let (executor_client, mut executor_services) =
-        create_thread_remote_executor_shards(num_shards, Some(2));
+        create_thread_remote_executor_shards(num_shards, Some(2), false, Transport::Tcp);
     let sharded_block_executor = ShardedBlockExecutor::new(executor_client);
 
     // wait for the servers to be ready before sending messages
@@ -92,3 +97,133 @@ fn test_sharded_block_executor_with_conflict() {
         executor_service.shutdown();
     });
 }
+
+#[test]
+fn test_sharded_block_executor_with_conflict_quic() {
+    let num_shards = 8;
+    let (executor_client, mut executor_services) =
+        create_thread_remote_executor_shards(num_shards, Some(2), false, Transport::Quic);
+    let sharded_block_executor = ShardedBlockExecutor::new(executor_client);
+
+    // The shards listen once started, no need to wait for them.
+    test_utils::sharded_block_executor_with_conflict(sharded_block_executor, 2);
+
+    executor_services.iter_mut().for_each(|executor_service| {
+        executor_service.shutdown();
+    });
+}
+
+#[test]
+fn test_sharded_block_executor_with_conflict_speculative_prefetch() {
+    use crate::metrics::REMOTE_EXECUTOR_CROSS_SHARD_PREFETCH;
+    use std::thread;
+
+    let num_shards = 8;
+    let num_prefetched = || {
+        (0..num_shards)
+            .flat_map(|shard_id| {
+                ["hit", "miss"].map(|name| {
+                    REMOTE_EXECUTOR_CROSS_SHARD_PREFETCH
+                        .with_label_values(&[&shard_id.to_string(), name])
+                        .get()
+                })
+            })
+            .sum::<u64>()
+    };
+    let num_prefetched_before = num_prefetched();
+    let (executor_client, mut executor_services) =
+        create_thread_remote_executor_shards(num_shards, Some(2), true, Transport::Tcp);
+    let sharded_block_executor = ShardedBlockExecutor::new(executor_client);
+
+    // wait for the servers to be ready before sending messages
+    // TODO: We need to pass this test without this sleep
+    thread::sleep(std::time::Duration::from_millis(10));
+
+    test_utils::sharded_block_executor_with_conflict(sharded_block_executor, 2);
+    // The cross-shard messages went through the prefetch threads.
+    assert!(num_prefetched() > num_prefetched_before);
+
+    executor_services.iter_mut().for_each(|executor_service| {
+        executor_service.shutdown();
+    });
+}
+
+#[test]
+fn test_saturation_benchmark_multiple_clients() {
+    use crate::saturation::{generate_transfer_block_streams, SaturationBenchmark};
+    use std::{sync::Arc, thread};
+
+    let num_clients = 3;
+    let num_blocks_per_client = 4;
+    let coordinator_address =
+        SocketAddr::new(IpAddr::V4(Ipv4Addr::LOCALHOST), utils::get_available_port());
+    let shard_address =
+        SocketAddr::new(IpAddr::V4(Ipv4Addr::LOCALHOST), utils::get_available_port());
+    let mut executor_service = ThreadExecutorService::new(
+        0,
+        1,
+        2,
+        coordinator_address,
+        vec![shard_address],
+        false,
+        Transport::Tcp,
+    );
+    let mut benchmark =
+        SaturationBenchmark::<FakeDataStore>::new(coordinator_address, shard_address);
+
+    // wait for the servers to be ready before sending messages
+    thread::sleep(std::time::Duration::from_millis(10));
+
+    let (state_view, block_streams) =
+        generate_transfer_block_streams(num_clients, num_blocks_per_client, 20);
+    let report = benchmark.run(Arc::new(state_view), block_streams, 2);
+    assert_eq!(report.clients.len(), num_clients);
+    for client in &report.clients {
+        assert_eq!(client.num_blocks, num_blocks_per_client);
+        assert_eq!(client.num_failed_blocks, 0);
+        assert_eq!(client.num_txns, num_blocks_per_client * 20);
+        assert!(client.max_queue_depth < num_clients);
+    }
+
+    benchmark.shutdown();
+    executor_service.shutdown();
+}
+
+#[test]
+fn test_sharded_block_executor_pipelined_dispatch() {
+    use crate::metrics::REMOTE_EXECUTOR_SERIALIZATION_SECONDS;
+    use std::thread;
+
+    let num_shards = 4;
+    // Only pipelined dispatch waits for the commands of the shards as they are encoded.
+    let num_dispatch_waits = || {
+        (0..num_shards)
+            .map(|shard_id| {
+                REMOTE_EXECUTOR_SERIALIZATION_SECONDS
+                    .with_label_values(&[&shard_id.to_string(), "dispatch_wait"])
+                    .get_sample_count()
+            })
+            .collect::<Vec<_>>()
+    };
+    let num_dispatch_waits_before = num_dispatch_waits();
+    let (executor_client, mut executor_services) =
+        create_thread_remote_executor_shards(num_shards, Some(2), false, Transport::Tcp);
+    let sharded_block_executor = ShardedBlockExecutor::new(executor_client);
+
+    // wait for the servers to be ready before sending messages
+    thread::sleep(std::time::Duration::from_millis(10));
+
+    // The shards of this version reassemble fragments, so the commands are dispatched as they are
+    // encoded, and the outputs are the same as unsharded.
+    test_utils::sharded_block_executor_with_conflict(sharded_block_executor, 2);
+    for (after, before) in num_dispatch_waits()
+        .into_iter()
+        .zip(num_dispatch_waits_before)
+    {
+        assert!(after > before);
+    }
+
+    executor_services.iter_mut().for_each(|executor_service| {
+        executor_service.shutdown();
+    });
+}
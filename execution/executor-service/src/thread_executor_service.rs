@@ -1,6 +1,9 @@
 // Copyright © Aptos Foundation
 // SPDX-License-Identifier: Apache-2.0
-use crate::remote_executor_service::ExecutorService;
+use crate::{
+    heartbeat::DEFAULT_HEARTBEAT_INTERVAL, remote_executor_service::ExecutorService,
+    resource_limits::ResourceLimits, transport::Transport,
+};
 use aptos_types::block_executor::partitioner::ShardId;
 use std::net::SocketAddr;
 
@@ -18,6 +21,8 @@ impl ThreadExecutorService {
         num_threads: usize,
         coordinator_address: SocketAddr,
         remote_shard_addresses: Vec<SocketAddr>,
+        speculative_cross_shard_prefetch: bool,
+        transport: Transport,
     ) -> Self {
         let self_address = remote_shard_addresses[shard_id];
         let mut executor_service = ExecutorService::new(
@@ -27,6 +32,12 @@ impl ThreadExecutorService {
             self_address,
             coordinator_address,
             remote_shard_addresses,
+            ResourceLimits::default(),
+            speculative_cross_shard_prefetch,
+            Some(DEFAULT_HEARTBEAT_INTERVAL),
+            false,
+            false,
+            transport,
         );
         executor_service.start();
         Self {
@@ -0,0 +1,33 @@
+// Copyright © Aptos Foundation
+// SPDX-License-Identifier: Apache-2.0
+
+use aptos_secure_net::network_controller::NetworkTransport;
+use clap::ValueEnum;
+
+/// Transport carrying the traffic between the coordinator and the executor shards, which all have
+/// to use the same one.
+#[derive(Clone, Copy, Debug, Default, Eq, PartialEq, ValueEnum)]
+pub enum Transport {
+    /// gRPC over TCP, a call per message.
+    #[default]
+    Tcp,
+    /// QUIC, experimental: a connection per pair of nodes, with a stream per message type (e.g.
+    /// the sub-blocks of each shard), so that a large message doesn't hold back the others.
+    Quic,
+}
+
+impl From<Transport> for NetworkTransport {
+    fn from(transport: Transport) -> Self {
+        match transport {
+            Transport::Tcp => NetworkTransport::Grpc,
+            Transport::Quic => NetworkTransport::Quic,
+        }
+    }
+}
+
+#[test]
+fn test_transport_values() {
+    assert_eq!(Transport::from_str("TCP", true), Ok(Transport::Tcp));
+    assert_eq!(Transport::from_str("quic", true), Ok(Transport::Quic));
+    assert!(Transport::from_str("uds", true).is_err());
+}
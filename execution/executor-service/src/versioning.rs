@@ -0,0 +1,230 @@
+// Copyright © Aptos Foundation
+// SPDX-License-Identifier: Apache-2.0
+
+//! Versioned wire format of the messages between the coordinator and the shards, so that binaries
+//! from adjacent releases can interoperate during rolling upgrades.
+//!
+//! Every message is wrapped in an envelope that carries the protocol version of the sender and
+//! the variant of the message, separately from the bcs encoded variant payload. This way a
+//! receiver can tell apart a variant it doesn't know about (sent by a newer peer) from a corrupt
+//! message, and handle it gracefully instead of failing to decode.
+//!
+//! Compatibility rules:
+//! * variants are never removed or renumbered, and the payload of an existing variant never
+//!   changes; changes to a payload are made by adding a new variant;
+//! * `PROTOCOL_VERSION` is bumped whenever a variant is added;
+//! * `MIN_COMPATIBLE_PROTOCOL_VERSION` is only bumped when support for talking to old peers is
+//!   dropped.
+
+use crate::{
+    error::Error, ExecuteBlockCommand, ExecutionStats, RemoteExecutionRequest,
+    RemoteExecutionResult, ShardTopology,
+};
+use serde::{Deserialize, Serialize};
+
+/// Protocol version of this binary.
+pub const PROTOCOL_VERSION: u32 = 6;
+/// Oldest protocol version of a peer this binary can still talk to.
+pub const MIN_COMPATIBLE_PROTOCOL_VERSION: u32 = 1;
+/// First protocol version that knows execution results with stats (variant 1).
+pub const EXECUTION_STATS_PROTOCOL_VERSION: u32 = 2;
+/// First protocol version that knows execute block requests with a shard topology (variant 1),
+/// and relays cross-shard messages through the coordinator.
+pub const TOPOLOGY_PROTOCOL_VERSION: u32 = 3;
+/// First protocol version that reassembles requests sent in fragments (variant 2).
+pub const FRAGMENTATION_PROTOCOL_VERSION: u32 = 4;
+/// First protocol version that knows the results of rejected blocks (variant 2), see
+/// `block_abort`.
+pub const REJECTION_PROTOCOL_VERSION: u32 = 5;
+/// First protocol version that reassembles results sent in fragments (variant 3).
+pub const RESULT_FRAGMENTATION_PROTOCOL_VERSION: u32 = 6;
+
+/// Variant of the requests that carry a fragment of a larger request, see `fragmentation`. They
+/// are reassembled before decoding, so `RemoteExecutionRequest` has no variant of its own for them.
+pub(crate) const REQUEST_FRAGMENT_VARIANT: u32 = 2;
+/// Variant of the results of blocks a shard rejected without executing any of them.
+pub(crate) const REJECTED_RESULT_VARIANT: u32 = 2;
+/// Variant of the results that carry a fragment of a larger result, see `fragmentation`. Like the
+/// fragments of requests, they are reassembled before decoding.
+pub(crate) const RESULT_FRAGMENT_VARIANT: u32 = 3;
+
+#[derive(Debug, Deserialize, Serialize)]
+struct Envelope {
+    version: u32,
+    variant: u32,
+    payload: Vec<u8>,
+}
+
+/// Result of decoding a message.
+#[derive(Debug)]
+pub enum Decoded<T> {
+    Known(T),
+    /// A variant added in a newer protocol version than ours.
+    Unknown {
+        version: u32,
+        variant: u32,
+    },
+}
+
+/// A message with a stable variant numbering on the wire.
+pub trait VersionedMessage: Sized {
+    fn variant(&self) -> u32;
+
+    fn encode_payload(&self) -> Result<Vec<u8>, Error>;
+
+    /// Returns `None` if `variant` is not known to this binary.
+    fn decode_payload(variant: u32, payload: &[u8]) -> Option<Result<Self, Error>>;
+}
+
+pub fn encode<T: VersionedMessage>(message: &T) -> Result<Vec<u8>, Error> {
+    encode_with_version(message, PROTOCOL_VERSION)
+}
+
+fn encode_with_version<T: VersionedMessage>(message: &T, version: u32) -> Result<Vec<u8>, Error> {
+    Ok(bcs::to_bytes(&Envelope {
+        version,
+        variant: message.variant(),
+        payload: message.encode_payload()?,
+    })?)
+}
+
+/// Encodes a raw `payload` as `variant`, for messages that are not `VersionedMessage`s of their
+/// own, like the fragments of a request.
+pub(crate) fn encode_variant(variant: u32, payload: Vec<u8>) -> Result<Vec<u8>, Error> {
+    Ok(bcs::to_bytes(&Envelope {
+        version: PROTOCOL_VERSION,
+        variant,
+        payload,
+    })?)
+}
+
+/// Returns the raw payload of an encoded message, checking that the sender is compatible.
+pub(crate) fn decode_payload(bytes: &[u8]) -> Result<Vec<u8>, Error> {
+    let Envelope {
+        version, payload, ..
+    } = bcs::from_bytes(bytes)?;
+    if version < MIN_COMPATIBLE_PROTOCOL_VERSION {
+        return Err(Error::IncompatibleVersion(version));
+    }
+    Ok(payload)
+}
+
+pub fn decode<T: VersionedMessage>(bytes: &[u8]) -> Result<Decoded<T>, Error> {
+    let Envelope {
+        version,
+        variant,
+        payload,
+    } = bcs::from_bytes(bytes)?;
+    if version < MIN_COMPATIBLE_PROTOCOL_VERSION {
+        return Err(Error::IncompatibleVersion(version));
+    }
+    match T::decode_payload(variant, &payload) {
+        Some(message) => Ok(Decoded::Known(message?)),
+        None => Ok(Decoded::Unknown { version, variant }),
+    }
+}
+
+/// Returns the protocol version and variant of an encoded message, without decoding it.
+pub(crate) fn envelope_header(bytes: &[u8]) -> Option<(u32, u32)> {
+    // bcs encodes the leading u32 fields of the envelope as fixed size little endian integers.
+    let version = u32::from_le_bytes(bytes.get(0..4)?.try_into().ok()?);
+    let variant = u32::from_le_bytes(bytes.get(4..8)?.try_into().ok()?);
+    Some((version, variant))
+}
+
+impl VersionedMessage for RemoteExecutionRequest {
+    fn variant(&self) -> u32 {
+        match self {
+            RemoteExecutionRequest::ExecuteBlock(_) => 0,
+            RemoteExecutionRequest::ExecuteBlockInTopology(_, _) => 1,
+        }
+    }
+
+    fn encode_payload(&self) -> Result<Vec<u8>, Error> {
+        match self {
+            RemoteExecutionRequest::ExecuteBlock(command) => Ok(bcs::to_bytes(command)?),
+            RemoteExecutionRequest::ExecuteBlockInTopology(command, topology) => {
+                Ok(bcs::to_bytes(&(command, topology))?)
+            },
+        }
+    }
+
+    fn decode_payload(variant: u32, payload: &[u8]) -> Option<Result<Self, Error>> {
+        match variant {
+            0 => Some(
+                bcs::from_bytes::<ExecuteBlockCommand>(payload)
+                    .map(RemoteExecutionRequest::ExecuteBlock)
+                    .map_err(Error::from),
+            ),
+            1 => Some(
+                bcs::from_bytes::<(ExecuteBlockCommand, ShardTopology)>(payload)
+                    .map(|(command, topology)| {
+                        RemoteExecutionRequest::ExecuteBlockInTopology(command, topology)
+                    })
+                    .map_err(Error::from),
+            ),
+            _ => None,
+        }
+    }
+}
+
+impl VersionedMessage for RemoteExecutionResult {
+    fn variant(&self) -> u32 {
+        match (self.rejected, &self.stats) {
+            (true, _) => REJECTED_RESULT_VARIANT,
+            (false, None) => 0,
+            (false, Some(_)) => 1,
+        }
+    }
+
+    fn encode_payload(&self) -> Result<Vec<u8>, Error> {
+        match (self.rejected, &self.stats) {
+            (true, _) => Ok(bcs::to_bytes(
+                self.inner
+                    .as_ref()
+                    .expect_err("Rejected blocks have no outputs."),
+            )?),
+            (false, None) => Ok(bcs::to_bytes(&self.inner)?),
+            (false, Some(stats)) => Ok(bcs::to_bytes(&(&self.inner, stats))?),
+        }
+    }
+
+    fn decode_payload(variant: u32, payload: &[u8]) -> Option<Result<Self, Error>> {
+        match variant {
+            0 => Some(
+                bcs::from_bytes(payload)
+                    .map(RemoteExecutionResult::new)
+                    .map_err(Error::from),
+            ),
+            1 => Some(
+                bcs::from_bytes::<(_, ExecutionStats)>(payload)
+                    .map(|(inner, stats)| RemoteExecutionResult::with_stats(inner, stats))
+                    .map_err(Error::from),
+            ),
+            REJECTED_RESULT_VARIANT => Some(
+                bcs::from_bytes(payload)
+                    .map(RemoteExecutionResult::rejected)
+                    .map_err(Error::from),
+            ),
+            _ => None,
+        }
+    }
+}
+
+#[cfg(test)]
+pub(crate) fn encode_as_peer<T: VersionedMessage>(
+    message: &T,
+    version: u32,
+) -> Result<Vec<u8>, Error> {
+    encode_with_version(message, version)
+}
+
+#[cfg(test)]
+pub(crate) fn encode_raw(version: u32, variant: u32, payload: Vec<u8>) -> Vec<u8> {
+    bcs::to_bytes(&Envelope {
+        version,
+        variant,
+        payload,
+    })
+    .unwrap()
+}
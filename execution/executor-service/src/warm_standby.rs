@@ -0,0 +1,300 @@
+// Copyright © Aptos Foundation
+// SPDX-License-Identifier: Apache-2.0
+
+//! Warm standbys of the remote executor shards, to fail over quickly when a shard dies mid-run.
+//!
+//! A warm standby (started with `--standby-address` and `--warm-standby`) receives the block
+//! stream of its shard without executing it: the coordinator mirrors every command it sends to
+//! the shard, and every batch of state values it serves to the shard, to the standby. The standby
+//! keeps the command and the state values of the block in flight, so that when the coordinator
+//! promotes it, it executes the block without fetching the state values the primary already read.
+//!
+//! The coordinator promotes the standby of a shard when the heartbeat monitor marks the shard
+//! degraded while its result is outstanding, resends it the command of the block, and sends it
+//! the commands of the shard from then on. As with hedging, the standby exchanges no cross-shard
+//! messages with the other shards, so only commands without cross-shard dependencies fail over.
+//! The other shards keep sending the cross-shard messages of the shard to its dead primary, so
+//! once the standby is promoted, every block in which the shard has cross-shard dependencies
+//! fails, instead of waiting forever for messages that never arrive.
+//!
+//! Commands and state values are mirrored on a single channel, so that the standby receives the
+//! state values of a block after its command, and never mixes them up with those of other blocks.
+//! Like the messages to the shards, large commands are mirrored in fragments, and large batches
+//! of state values in several batches.
+
+use crate::{
+    fragmentation::{self, Reassembler},
+    remote_executor_client,
+    versioning::REQUEST_FRAGMENT_VARIANT,
+    wire_trace::{self, Direction, WireMessage},
+};
+use aptos_infallible::Mutex;
+use aptos_logger::{info, warn};
+use aptos_secure_net::network_controller::{Message, NetworkController};
+use aptos_types::{
+    block_executor::partitioner::ShardId,
+    state_store::{state_key::StateKey, state_value::StateValue},
+};
+use crossbeam_channel::Sender;
+use once_cell::sync::Lazy;
+use serde::{Deserialize, Serialize};
+use std::{
+    collections::HashMap,
+    net::SocketAddr,
+    sync::{
+        atomic::{AtomicBool, AtomicU64, Ordering},
+        Arc,
+    },
+    thread,
+};
+
+static FAILOVERS: Lazy<Mutex<Vec<Failover>>> = Lazy::new(|| Mutex::new(vec![]));
+
+#[derive(Clone, Debug)]
+pub struct WarmStandbyConfig {
+    /// Address of the warm standby executor service of every shard, in shard order.
+    pub standby_addresses: Vec<SocketAddr>,
+}
+
+/// A shard that failed over to its warm standby.
+#[derive(Clone, Debug, Serialize)]
+pub struct Failover {
+    pub shard_id: ShardId,
+    /// Time from promoting the standby to receiving its result of the block in flight.
+    pub failover_secs: f64,
+}
+
+/// Returns the failovers so far, in the order they happened.
+pub fn failovers() -> Vec<Failover> {
+    FAILOVERS.lock().clone()
+}
+
+pub(crate) fn record_failover(failover: Failover) {
+    info!(
+        "Shard {} failed over to its warm standby in {:.3} secs",
+        failover.shard_id, failover.failover_secs
+    );
+    FAILOVERS.lock().push(failover);
+}
+
+fn mirror_message_type(network_id: usize) -> String {
+    format!("mirror_{}", network_id)
+}
+
+#[derive(Debug, Deserialize, Serialize)]
+enum MirrorMessage {
+    /// The command of a block, as sent to the primary.
+    Block(Vec<u8>),
+    /// State values served to the primary for the last mirrored block.
+    StateValues(Vec<(StateKey, Option<StateValue>)>),
+    /// A fragment of the command of a block too large to mirror in one message, as fragmented
+    /// for the shards.
+    BlockFragment(Vec<u8>),
+}
+
+/// The coordinator side: mirrors the block stream to the warm standbys of the shards, until they
+/// are promoted.
+pub(crate) struct Mirrors {
+    mirror_txs: Vec<Mutex<Sender<Message>>>,
+    promoted: Vec<AtomicBool>,
+    /// Id of the next command mirrored in fragments.
+    next_block_id: AtomicU64,
+}
+
+impl Mirrors {
+    /// `network_ids` are the ids the standbys are addressed by on the network, in shard order.
+    pub(crate) fn new(
+        controller: &mut NetworkController,
+        standby_addresses: &[SocketAddr],
+        network_ids: impl Iterator<Item = usize>,
+    ) -> Self {
+        let mirror_txs = standby_addresses
+            .iter()
+            .zip(network_ids)
+            .map(|(address, network_id)| {
+                Mutex::new(
+                    controller.create_outbound_channel(*address, mirror_message_type(network_id)),
+                )
+            })
+            .collect::<Vec<_>>();
+        Self {
+            promoted: mirror_txs.iter().map(|_| AtomicBool::new(false)).collect(),
+            mirror_txs,
+            next_block_id: AtomicU64::new(0),
+        }
+    }
+
+    pub(crate) fn is_promoted(&self, shard_id: ShardId) -> bool {
+        self.promoted[shard_id].load(Ordering::Relaxed)
+    }
+
+    /// Makes the standby the shard, the block stream isn't mirrored to it anymore.
+    pub(crate) fn promote(&self, shard_id: ShardId) {
+        warn!("Promoting the warm standby of shard {}", shard_id);
+        self.promoted[shard_id].store(true, Ordering::Relaxed);
+    }
+
+    pub(crate) fn mirror_block(&self, shard_id: ShardId, request: &[u8]) {
+        let fragments = fragmentation::fragment(
+            request.to_vec(),
+            self.next_block_id.fetch_add(1, Ordering::Relaxed),
+            remote_executor_client::get_max_payload_bytes(),
+            REQUEST_FRAGMENT_VARIANT,
+        )
+        .expect("Fragments must serialize.");
+        if fragments.len() == 1 {
+            self.send(shard_id, MirrorMessage::Block(request.to_vec()));
+            return;
+        }
+        for fragment in fragments {
+            self.send(shard_id, MirrorMessage::BlockFragment(fragment));
+        }
+    }
+
+    /// Mirrors the state values served to `network_id`, if it is a shard with a warm standby.
+    pub(crate) fn mirror_state_values(
+        &self,
+        network_id: usize,
+        state_values: &[(StateKey, Option<StateValue>)],
+    ) {
+        if network_id < self.mirror_txs.len() {
+            for state_values in fragmentation::split_state_values(
+                state_values.to_vec(),
+                remote_executor_client::get_max_payload_bytes(),
+            ) {
+                self.send(network_id, MirrorMessage::StateValues(state_values));
+            }
+        }
+    }
+
+    fn send(&self, shard_id: ShardId, message: MirrorMessage) {
+        if self.is_promoted(shard_id) {
+            return;
+        }
+        let bytes = bcs::to_bytes(&message).expect("Mirror message must serialize.");
+        wire_trace::trace(Direction::Send, WireMessage::Mirror, shard_id, &bytes);
+        // A dead standby must not take the shard down with it.
+        if self.mirror_txs[shard_id]
+            .lock()
+            .send(Message::new(bytes))
+            .is_err()
+        {
+            warn!("Failed to mirror to the warm standby of shard {}", shard_id);
+        }
+    }
+}
+
+/// The standby side: the command and the state values of the last mirrored block.
+pub(crate) struct WarmCache {
+    request: Option<Vec<u8>>,
+    state_values: HashMap<StateKey, Option<StateValue>>,
+    /// Fragments of the command being mirrored, if it is mirrored in fragments.
+    reassembler: Reassembler,
+}
+
+impl Default for WarmCache {
+    fn default() -> Self {
+        Self {
+            request: None,
+            state_values: HashMap::new(),
+            reassembler: Reassembler::new(REQUEST_FRAGMENT_VARIANT),
+        }
+    }
+}
+
+impl WarmCache {
+    fn apply(&mut self, message: MirrorMessage) {
+        match message {
+            MirrorMessage::BlockFragment(fragment) => match self.reassembler.push(fragment) {
+                Ok(Some(request)) => self.apply(MirrorMessage::Block(request)),
+                Ok(None) => {},
+                Err(err) => warn!("Cannot reassemble mirrored block: {}", err),
+            },
+            MirrorMessage::Block(request) => {
+                self.request = Some(request);
+                self.state_values.clear();
+            },
+            MirrorMessage::StateValues(state_values) => {
+                self.state_values.extend(state_values);
+            },
+        }
+    }
+
+    /// Returns the state values mirrored for `request`, if it is the last mirrored block.
+    pub(crate) fn take_if_mirrored(
+        &mut self,
+        request: &[u8],
+    ) -> Option<HashMap<StateKey, Option<StateValue>>> {
+        if self.request.as_deref() != Some(request) {
+            return None;
+        }
+        self.request = None;
+        Some(std::mem::take(&mut self.state_values))
+    }
+}
+
+/// Receives the block stream mirrored to the standby addressed as `network_id` into a warm cache.
+pub(crate) fn start_mirror_receiver(
+    shard_id: ShardId,
+    network_id: usize,
+    controller: &mut NetworkController,
+) -> Arc<Mutex<WarmCache>> {
+    let mirror_rx = controller.create_inbound_channel(mirror_message_type(network_id));
+    let warm_cache = Arc::new(Mutex::new(WarmCache::default()));
+    let warm_cache_clone = warm_cache.clone();
+    thread::Builder::new()
+        .name(format!("warm-standby-{}", shard_id))
+        .spawn(move || {
+            while let Ok(message) = mirror_rx.recv() {
+                wire_trace::trace(
+                    Direction::Receive,
+                    WireMessage::Mirror,
+                    shard_id,
+                    &message.data,
+                );
+                match bcs::from_bytes::<MirrorMessage>(&message.data) {
+                    Ok(message) => warm_cache_clone.lock().apply(message),
+                    Err(err) => warn!("Cannot decode mirror message: {}", err),
+                }
+            }
+        })
+        .expect("Failed to spawn warm standby thread.");
+    warm_cache
+}
+
+#[test]
+fn test_warm_cache() {
+    let key = |byte: u8| StateKey::raw(vec![byte]);
+    let mut warm_cache = WarmCache::default();
+    warm_cache.apply(MirrorMessage::Block(vec![1]));
+    warm_cache.apply(MirrorMessage::StateValues(vec![(
+        key(1),
+        Some(StateValue::from(vec![1])),
+    )]));
+    warm_cache.apply(MirrorMessage::Block(vec![2]));
+    warm_cache.apply(MirrorMessage::StateValues(vec![(key(2), None)]));
+
+    // Only the state values of the last mirrored block are kept.
+    assert_eq!(warm_cache.take_if_mirrored(&[1]), None);
+    assert_eq!(
+        warm_cache.take_if_mirrored(&[2]),
+        Some(HashMap::from([(key(2), None)]))
+    );
+
+    // Blocks mirrored in fragments are reassembled.
+    let request = vec![3; 2 * fragmentation::MIN_PAYLOAD_BYTES];
+    let fragments = fragmentation::fragment(
+        request.clone(),
+        0,
+        fragmentation::MIN_PAYLOAD_BYTES,
+        REQUEST_FRAGMENT_VARIANT,
+    )
+    .unwrap();
+    assert_eq!(fragments.len(), 3);
+    for fragment in fragments {
+        warm_cache.apply(MirrorMessage::BlockFragment(fragment));
+    }
+    assert_eq!(warm_cache.take_if_mirrored(&request), Some(HashMap::new()));
+    // Taken once.
+    assert_eq!(warm_cache.take_if_mirrored(&[2]), None);
+}
@@ -0,0 +1,115 @@
+// Copyright © Aptos Foundation
+// SPDX-License-Identifier: Apache-2.0
+
+//! Wire-level tracing of the protocol messages between the coordinator and the shards.
+//!
+//! When enabled (`--trace-wire` on both the client and the service), every message sent or
+//! received is logged with its type, size, checksum and time, and versioned messages also with
+//! the protocol version and variant of their envelope. Comparing the traces of both ends makes it
+//! easy to tell whether a message was corrupted in transit or encoded differently by the two
+//! versions. Small messages can optionally be hex dumped in full.
+
+use crate::versioning;
+use aptos_crypto::HashValue;
+use aptos_logger::info;
+use once_cell::sync::{Lazy, OnceCell};
+use std::time::Instant;
+
+static WIRE_TRACE: OnceCell<WireTraceConfig> = OnceCell::new();
+static TRACE_START: Lazy<Instant> = Lazy::new(Instant::now);
+
+#[derive(Clone, Copy, Debug, Default)]
+pub struct WireTraceConfig {
+    /// Messages up to this size are also hex dumped, 0 disables dumps.
+    pub hex_dump_max_bytes: usize,
+}
+
+pub fn enable(config: WireTraceConfig) {
+    WIRE_TRACE.set(config).ok();
+    Lazy::force(&TRACE_START);
+}
+
+pub fn is_enabled() -> bool {
+    WIRE_TRACE.get().is_some()
+}
+
+#[derive(Clone, Copy, Debug)]
+pub enum Direction {
+    Send,
+    Receive,
+}
+
+#[derive(Clone, Copy, Debug)]
+pub enum WireMessage {
+    ExecuteCommand,
+    ExecuteResult,
+    Heartbeat,
+    GetCapabilities,
+    Capabilities,
+    KvRequest,
+    KvResponse,
+    CrossShard,
+    CrossShardRelay,
+    Mirror,
+}
+
+impl WireMessage {
+    fn name(&self) -> &'static str {
+        match self {
+            Self::ExecuteCommand => "execute_command",
+            Self::ExecuteResult => "execute_result",
+            Self::Heartbeat => "heartbeat",
+            Self::GetCapabilities => "get_capabilities",
+            Self::Capabilities => "capabilities",
+            Self::KvRequest => "kv_request",
+            Self::KvResponse => "kv_response",
+            Self::CrossShard => "cross_shard",
+            Self::CrossShardRelay => "cross_shard_relay",
+            Self::Mirror => "mirror",
+        }
+    }
+
+    /// Whether the message is wrapped in a versioned envelope.
+    fn is_versioned(&self) -> bool {
+        matches!(
+            self,
+            Self::ExecuteCommand
+                | Self::ExecuteResult
+                | Self::Heartbeat
+                | Self::GetCapabilities
+                | Self::Capabilities
+        )
+    }
+}
+
+/// Logs a message sent to or received from `shard_id`, if tracing is enabled.
+pub fn trace(direction: Direction, message: WireMessage, shard_id: usize, bytes: &[u8]) {
+    let config = match WIRE_TRACE.get() {
+        Some(config) => config,
+        None => return,
+    };
+    let envelope = if message.is_versioned() {
+        match versioning::envelope_header(bytes) {
+            Some((version, variant)) => format!(" version={} variant={}", version, variant),
+            None => " envelope=truncated".to_string(),
+        }
+    } else {
+        String::new()
+    };
+    let dump = if bytes.len() <= config.hex_dump_max_bytes {
+        format!(" hex={}", hex::encode(bytes))
+    } else {
+        String::new()
+    };
+    info!(
+        "wire {:?} {} shard={} t={:.3}ms size={} checksum={}{}{}",
+        direction,
+        message.name(),
+        shard_id,
+        TRACE_START.elapsed().as_secs_f64() * 1000.0,
+        bytes.len(),
+        HashValue::sha3_256_of(bytes),
+        envelope,
+        dump,
+    );
+}
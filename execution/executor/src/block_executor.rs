@@ -28,7 +28,8 @@ use aptos_logger::prelude::*;
 use aptos_scratchpad::SparseMerkleTree;
 use aptos_state_view::StateViewId;
 use aptos_storage_interface::{
-    async_proof_fetcher::AsyncProofFetcher, cached_state_view::CachedStateView, DbReaderWriter,
+    async_proof_fetcher::AsyncProofFetcher, cached_state_view::CachedStateView,
+    state_delta::StateDelta, DbReaderWriter,
 };
 use aptos_types::{
     block_executor::partitioner::{ExecutableBlock, ExecutableTransactions},
@@ -37,8 +38,36 @@ use aptos_types::{
 };
 use aptos_vm::AptosVM;
 use fail::fail_point;
+use once_cell::sync::OnceCell;
+use rayon::ThreadPool;
 use std::{marker::PhantomData, sync::Arc};
 
+static CHECKPOINT_POOL: OnceCell<ThreadPool> = OnceCell::new();
+
+/// Computes the state checkpoints (the in-memory state tree updates and their hashing) on a
+/// dedicated pool of `num_workers` threads, instead of the execution pool, whose size is tuned for
+/// the VM. Only the first call has an effect.
+pub fn set_checkpoint_workers_once(num_workers: usize) {
+    assert!(
+        num_workers > 0,
+        "Number of checkpoint workers must be positive."
+    );
+    let pool = CHECKPOINT_POOL.get_or_init(|| {
+        rayon::ThreadPoolBuilder::new()
+            .num_threads(num_workers)
+            .thread_name(|index| format!("checkpoint-{}", index))
+            .build()
+            .expect("Failed to create the checkpoint thread pool.")
+    });
+    aptos_scratchpad::set_update_pool_once(pool);
+}
+
+fn checkpoint_pool() -> &'static ThreadPool {
+    CHECKPOINT_POOL
+        .get()
+        .unwrap_or_else(|| THREAD_MANAGER.get_exe_cpu_pool())
+}
+
 pub trait TransactionBlockExecutor: Send + Sync {
     fn execute_transaction_block(
         transactions: ExecutableTransactions,
@@ -85,6 +114,16 @@ where
             .root_smt()
     }
 
+    /// The in-memory state of the last committed block, which the execution of its children
+    /// reads from before falling back to the DB.
+    pub fn root_state(&self) -> StateDelta {
+        self.inner
+            .read()
+            .as_ref()
+            .expect("BlockExecutor is not reset")
+            .root_state()
+    }
+
     fn maybe_initialize(&self) -> Result<()> {
         if self.inner.read().is_none() {
             self.reset()?;
@@ -179,6 +218,10 @@ where
     fn root_smt(&self) -> SparseMerkleTree<StateValue> {
         self.block_tree.root_block().output.state().current.clone()
     }
+
+    fn root_state(&self) -> StateDelta {
+        self.block_tree.root_block().output.state().clone()
+    }
 }
 
 impl<V> BlockExecutorInner<V>
@@ -253,7 +296,7 @@ where
                     .with_label_values(&["state_checkpoint"])
                     .start_timer();
 
-                THREAD_MANAGER.get_exe_cpu_pool().install(|| {
+                checkpoint_pool().install(|| {
                     chunk_output.into_state_checkpoint_output(
                         parent_output.state(),
                         maybe_block_gas_limit.map(|_| block_id),
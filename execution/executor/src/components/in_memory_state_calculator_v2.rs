@@ -1,7 +1,10 @@
 // Copyright © Aptos Foundation
 // SPDX-License-Identifier: Apache-2.0
 
-use crate::metrics::APTOS_EXECUTOR_OTHER_TIMERS_SECONDS;
+use crate::metrics::{
+    APTOS_EXECUTOR_CALCULATE_UPDATES_SHARD_TIMERS, APTOS_EXECUTOR_CALCULATE_USAGE_SHARD_TIMERS,
+    APTOS_EXECUTOR_OTHER_TIMERS_SECONDS,
+};
 use anyhow::{anyhow, ensure, Result};
 use aptos_crypto::{hash::CryptoHash, HashValue};
 use aptos_drop_helper::DEFAULT_DROPPER;
@@ -308,6 +311,7 @@ impl InMemoryStateCalculatorV2 {
             .par_iter_mut()
             .enumerate()
             .for_each(|(i, per_shard_update)| {
+                let _timer = APTOS_EXECUTOR_CALCULATE_UPDATES_SHARD_TIMERS[i].start_timer();
                 per_shard_update.extend(
                     state_updates_vec
                         .iter()
@@ -356,6 +360,7 @@ impl InMemoryStateCalculatorV2 {
             .enumerate()
             .map(
                 |(i, (shard_updates_before_checkpoint, shard_updates_after_checkpoint))| {
+                    let _timer = APTOS_EXECUTOR_CALCULATE_USAGE_SHARD_TIMERS[i].start_timer();
                     let mut items_delta = 0i64;
                     let mut bytes_delta = 0i64;
                     let num_updates_before_checkpoint = shard_updates_before_checkpoint.len();
@@ -64,6 +64,35 @@ pub static APTOS_EXECUTOR_OTHER_TIMERS_SECONDS: Lazy<HistogramVec> = Lazy::new(|
     .unwrap()
 });
 
+pub static APTOS_EXECUTOR_STATE_CHECKPOINT_SHARD_SECONDS: Lazy<HistogramVec> = Lazy::new(|| {
+    register_histogram_vec!(
+        // metric name
+        "aptos_executor_state_checkpoint_shard_seconds",
+        // metric description
+        "The time spent in seconds on each state shard when computing a state checkpoint",
+        &["name", "shard_id"],
+        exponential_buckets(/*start=*/ 1e-5, /*factor=*/ 2.0, /*count=*/ 24).unwrap(),
+    )
+    .unwrap()
+});
+
+/// `APTOS_EXECUTOR_STATE_CHECKPOINT_SHARD_SECONDS` of each of the 16 state shards, looked up once
+/// instead of for every block.
+fn state_checkpoint_shard_timers(name: &str) -> Vec<Histogram> {
+    (0..16)
+        .map(|shard_id| {
+            APTOS_EXECUTOR_STATE_CHECKPOINT_SHARD_SECONDS
+                .with_label_values(&[name, &shard_id.to_string()])
+        })
+        .collect()
+}
+
+pub static APTOS_EXECUTOR_CALCULATE_UPDATES_SHARD_TIMERS: Lazy<Vec<Histogram>> =
+    Lazy::new(|| state_checkpoint_shard_timers("calculate_updates"));
+
+pub static APTOS_EXECUTOR_CALCULATE_USAGE_SHARD_TIMERS: Lazy<Vec<Histogram>> =
+    Lazy::new(|| state_checkpoint_shard_timers("calculate_usage"));
+
 pub static APTOS_EXECUTOR_ERRORS: Lazy<IntCounter> = Lazy::new(|| {
     register_int_counter!("aptos_executor_error_total", "Cumulative number of errors").unwrap()
 });
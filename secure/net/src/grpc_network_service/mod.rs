@@ -20,7 +20,8 @@ use tonic::{
     Request, Response, Status,
 };
 
-const MAX_MESSAGE_SIZE: usize = 1024 * 1024 * 80;
+/// Largest message that can be received, in bytes.
+pub const MAX_MESSAGE_SIZE: usize = 1024 * 1024 * 80;
 
 pub struct GRPCNetworkMessageServiceServerWrapper {
     inbound_handlers: Arc<Mutex<HashMap<MessageType, Sender<Message>>>>,
@@ -18,6 +18,7 @@
 
 pub mod grpc_network_service;
 pub mod network_controller;
+pub mod quic_network_service;
 
 use aptos_logger::{info, trace, warn, Schema};
 use aptos_metrics_core::{register_int_counter_vec, IntCounterVec};
@@ -2,7 +2,8 @@
 
 use crate::{
     grpc_network_service::GRPCNetworkMessageServiceServerWrapper,
-    network_controller::{Message, MessageType},
+    network_controller::{Message, MessageType, NetworkTransport},
+    quic_network_service::QuicNetworkMessageServiceServerWrapper,
 };
 use aptos_logger::warn;
 use crossbeam_channel::Sender;
@@ -17,15 +18,22 @@ pub struct InboundHandler {
     service: String,
     listen_addr: SocketAddr,
     rpc_timeout_ms: u64,
+    transport: NetworkTransport,
     inbound_handlers: Arc<Mutex<HashMap<MessageType, Sender<Message>>>>,
 }
 
 impl InboundHandler {
-    pub fn new(service: String, listen_addr: SocketAddr, rpc_timeout_ms: u64) -> Self {
+    pub fn new(
+        service: String,
+        listen_addr: SocketAddr,
+        rpc_timeout_ms: u64,
+        transport: NetworkTransport,
+    ) -> Self {
         Self {
             service: service.clone(),
             listen_addr,
             rpc_timeout_ms,
+            transport,
             inbound_handlers: Arc::new(Mutex::new(HashMap::new())),
         }
     }
@@ -47,17 +55,24 @@ impl InboundHandler {
 
         let (server_shutdown_tx, server_shutdown_rx) = oneshot::channel();
         // The server is started in a separate task
-        GRPCNetworkMessageServiceServerWrapper::new(
-            self.inbound_handlers.clone(),
-            self.listen_addr,
-        )
-        .start(
-            rt,
-            self.service.clone(),
-            self.listen_addr,
-            self.rpc_timeout_ms,
-            server_shutdown_rx,
-        );
+        match self.transport {
+            NetworkTransport::Grpc => GRPCNetworkMessageServiceServerWrapper::new(
+                self.inbound_handlers.clone(),
+                self.listen_addr,
+            )
+            .start(
+                rt,
+                self.service.clone(),
+                self.listen_addr,
+                self.rpc_timeout_ms,
+                server_shutdown_rx,
+            ),
+            NetworkTransport::Quic => QuicNetworkMessageServiceServerWrapper::new(
+                self.inbound_handlers.clone(),
+                self.listen_addr,
+            )
+            .start(rt, server_shutdown_rx),
+        }
         Some(server_shutdown_tx)
     }
 
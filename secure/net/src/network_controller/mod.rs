@@ -51,6 +51,16 @@ impl NetworkMessage {
     }
 }
 
+/// Protocol carrying the messages between the nodes, which all have to use the same one.
+#[derive(Clone, Copy, Debug, Default, Eq, PartialEq)]
+pub enum NetworkTransport {
+    /// A gRPC call per message.
+    #[default]
+    Grpc,
+    /// A QUIC stream per message type, see `quic_network_service`.
+    Quic,
+}
+
 #[derive(Clone, Debug, Deserialize, Serialize)]
 #[allow(dead_code)]
 pub struct Message {
@@ -91,13 +101,27 @@ pub struct NetworkController {
 
 impl NetworkController {
     pub fn new(service: String, listen_addr: SocketAddr, timeout_ms: u64) -> Self {
+        Self::new_with_transport(service, listen_addr, timeout_ms, NetworkTransport::Grpc)
+    }
+
+    pub fn new_with_transport(
+        service: String,
+        listen_addr: SocketAddr,
+        timeout_ms: u64,
+        transport: NetworkTransport,
+    ) -> Self {
         let inbound_handler = Arc::new(Mutex::new(InboundHandler::new(
             service.clone(),
             listen_addr,
             timeout_ms,
+            transport,
         )));
-        let outbound_handler = OutboundHandler::new(service, listen_addr, inbound_handler.clone());
-        info!("Network controller created for node {}", listen_addr);
+        let outbound_handler =
+            OutboundHandler::new(service, listen_addr, inbound_handler.clone(), transport);
+        info!(
+            "Network controller created for node {} over {:?}",
+            listen_addr, transport
+        );
         Self {
             inbound_handler,
             outbound_handler,
@@ -166,7 +190,7 @@ impl NetworkController {
 
 #[cfg(test)]
 mod tests {
-    use crate::network_controller::{Message, NetworkController};
+    use crate::network_controller::{Message, NetworkController, NetworkTransport};
     use aptos_config::utils;
     use std::{
         net::{IpAddr, Ipv4Addr, SocketAddr},
@@ -175,16 +199,33 @@ mod tests {
 
     #[test]
     fn test_basic_send_receive() {
+        send_receive(NetworkTransport::Grpc);
+    }
+
+    #[test]
+    fn test_basic_send_receive_quic() {
+        send_receive(NetworkTransport::Quic);
+    }
+
+    fn send_receive(transport: NetworkTransport) {
         let server_port1 = utils::get_available_port();
         let server_addr1 = SocketAddr::new(IpAddr::V4(Ipv4Addr::LOCALHOST), server_port1);
 
         let server_port2 = utils::get_available_port();
         let server_addr2 = SocketAddr::new(IpAddr::V4(Ipv4Addr::LOCALHOST), server_port2);
 
-        let mut network_controller1 =
-            NetworkController::new("test1".to_string(), server_addr1, 1000);
-        let mut network_controller2 =
-            NetworkController::new("test2".to_string(), server_addr2, 1000);
+        let mut network_controller1 = NetworkController::new_with_transport(
+            "test1".to_string(),
+            server_addr1,
+            1000,
+            transport,
+        );
+        let mut network_controller2 = NetworkController::new_with_transport(
+            "test2".to_string(),
+            server_addr2,
+            1000,
+            transport,
+        );
 
         let test1_sender =
             network_controller2.create_outbound_channel(server_addr1, "test1".to_string());
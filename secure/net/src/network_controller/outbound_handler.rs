@@ -4,7 +4,9 @@ use crate::{
     grpc_network_service::GRPCNetworkMessageServiceClientWrapper,
     network_controller::{
         inbound_handler::InboundHandler, metrics::NETWORK_HANDLER_TIMER, Message, MessageType,
+        NetworkTransport,
     },
+    quic_network_service::QuicNetworkMessageServiceClientWrapper,
 };
 use aptos_logger::{info, warn};
 use crossbeam_channel::{unbounded, Receiver, Select, Sender};
@@ -23,6 +25,7 @@ pub struct OutboundHandler {
     // Used to route outgoing messages to correct network client with the correct message type
     handlers: Vec<(Receiver<Message>, SocketAddr, MessageType)>,
     inbound_handler: Arc<Mutex<InboundHandler>>,
+    transport: NetworkTransport,
 }
 
 impl OutboundHandler {
@@ -30,6 +33,7 @@ impl OutboundHandler {
         service: String,
         listen_addr: SocketAddr,
         inbound_handler: Arc<Mutex<InboundHandler>>,
+        transport: NetworkTransport,
     ) -> Self {
         Self {
             _service: service,
@@ -37,6 +41,7 @@ impl OutboundHandler {
             address: listen_addr,
             handlers: Vec::new(),
             inbound_handler,
+            transport,
         }
     }
 
@@ -64,13 +69,12 @@ impl OutboundHandler {
             MessageType::new("stop_task".to_string()),
         ));
 
-        // Create a grpc client for each remote address
-        let mut grpc_clients: HashMap<SocketAddr, GRPCNetworkMessageServiceClientWrapper> =
-            HashMap::new();
+        // Create a client for each remote address
+        let mut clients: HashMap<SocketAddr, NetworkClient> = HashMap::new();
         self.remote_addresses.iter().for_each(|remote_addr| {
-            grpc_clients.insert(
+            clients.insert(
                 *remote_addr,
-                GRPCNetworkMessageServiceClientWrapper::new(rt, *remote_addr),
+                NetworkClient::new(self.transport, rt, *remote_addr),
             );
         });
 
@@ -91,7 +95,7 @@ impl OutboundHandler {
                 outbound_handlers,
                 &address,
                 inbound_handler.clone(),
-                &mut grpc_clients,
+                &mut clients,
             )
             .await;
             info!("Stopping outbound handler at {}", address.to_string());
@@ -103,7 +107,7 @@ impl OutboundHandler {
         outbound_handlers: Vec<(Receiver<Message>, SocketAddr, MessageType)>,
         socket_addr: &SocketAddr,
         inbound_handler: Arc<Mutex<InboundHandler>>,
-        grpc_clients: &mut HashMap<SocketAddr, GRPCNetworkMessageServiceClientWrapper>,
+        clients: &mut HashMap<SocketAddr, NetworkClient>,
     ) {
         loop {
             let mut select = Select::new();
@@ -151,7 +155,7 @@ impl OutboundHandler {
                     .unwrap()
                     .send_incoming_message_to_handler(message_type, msg);
             } else {
-                grpc_clients
+                clients
                     .get_mut(remote_addr)
                     .unwrap()
                     .send_message(*socket_addr, msg, message_type)
@@ -160,3 +164,29 @@ impl OutboundHandler {
         }
     }
 }
+
+/// Client of a remote node, over the transport of the `NetworkController`.
+enum NetworkClient {
+    Grpc(GRPCNetworkMessageServiceClientWrapper),
+    Quic(QuicNetworkMessageServiceClientWrapper),
+}
+
+impl NetworkClient {
+    fn new(transport: NetworkTransport, rt: &Runtime, remote_addr: SocketAddr) -> Self {
+        match transport {
+            NetworkTransport::Grpc => {
+                Self::Grpc(GRPCNetworkMessageServiceClientWrapper::new(rt, remote_addr))
+            },
+            NetworkTransport::Quic => {
+                Self::Quic(QuicNetworkMessageServiceClientWrapper::new(remote_addr))
+            },
+        }
+    }
+
+    async fn send_message(&mut self, sender_addr: SocketAddr, message: Message, mt: &MessageType) {
+        match self {
+            Self::Grpc(client) => client.send_message(sender_addr, message, mt).await,
+            Self::Quic(client) => client.send_message(sender_addr, message, mt).await,
+        }
+    }
+}
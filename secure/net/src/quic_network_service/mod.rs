@@ -0,0 +1,388 @@
+// Copyright © Aptos Foundation
+// SPDX-License-Identifier: Apache-2.0
+
+//! Carries the messages of the `NetworkController` over QUIC instead of gRPC. A node keeps a
+//! single connection to each remote node, on which every message type gets its own unidirectional
+//! stream: the messages of a type stay in order, while a large message (e.g. the sub-blocks sent
+//! to a shard) doesn't hold back the messages of the other types, as it does on a TCP connection.
+//!
+//! A stream starts with the message type, followed by the messages, all of them length prefixed.
+//! Like the gRPC service, the transport isn't authenticated: QUIC requires TLS, so the server
+//! presents a self-signed certificate, which the client doesn't verify.
+
+use crate::{
+    grpc_network_service::MAX_MESSAGE_SIZE,
+    network_controller::{metrics::NETWORK_HANDLER_TIMER, Message, MessageType},
+};
+use anyhow::{ensure, Result};
+use aptos_logger::{error, info, warn};
+use crossbeam_channel::Sender;
+use quinn::{
+    ClientConfig, Connecting, Connection, Endpoint, ReadExactError, RecvStream, SendStream,
+    ServerConfig, TransportConfig, VarInt,
+};
+use std::{
+    collections::HashMap,
+    net::{IpAddr, Ipv4Addr, Ipv6Addr, SocketAddr},
+    sync::{Arc, Mutex},
+    time::{Duration, SystemTime},
+};
+use tokio::{runtime::Runtime, sync::oneshot};
+
+/// Server name the connections are made to, the certificate presented for it isn't verified.
+const SERVER_NAME: &str = "localhost";
+
+/// Largest number of message types a remote node can open streams for.
+const MAX_STREAMS: u32 = 1024;
+
+/// Keeps the connections open between blocks, which can be further apart than the idle timeout.
+const KEEP_ALIVE_INTERVAL: Duration = Duration::from_secs(5);
+
+pub struct QuicNetworkMessageServiceServerWrapper {
+    inbound_handlers: Arc<Mutex<HashMap<MessageType, Sender<Message>>>>,
+    self_addr: SocketAddr,
+}
+
+impl QuicNetworkMessageServiceServerWrapper {
+    pub fn new(
+        inbound_handlers: Arc<Mutex<HashMap<MessageType, Sender<Message>>>>,
+        self_addr: SocketAddr,
+    ) -> Self {
+        Self {
+            inbound_handlers,
+            self_addr,
+        }
+    }
+
+    // Note: The object is consumed here. Unlike the gRPC server, the socket is bound before
+    //       returning, so the server can be connected to right away.
+    pub fn start(self, rt: &Runtime, server_shutdown_rx: oneshot::Receiver<()>) {
+        let endpoint = {
+            let _guard = rt.enter();
+            Endpoint::server(server_config(), self.self_addr).unwrap_or_else(|e| {
+                panic!("Failed to start QUIC server at {}: {}", self.self_addr, e)
+            })
+        };
+        rt.spawn(async move {
+            Arc::new(self).serve(endpoint, server_shutdown_rx).await;
+        });
+    }
+
+    async fn serve(
+        self: Arc<Self>,
+        endpoint: Endpoint,
+        mut server_shutdown_rx: oneshot::Receiver<()>,
+    ) {
+        info!("Starting QUIC server at {:?}", self.self_addr);
+        loop {
+            tokio::select! {
+                connecting = endpoint.accept() => match connecting {
+                    Some(connecting) => {
+                        tokio::spawn(self.clone().handle_connection(connecting));
+                    },
+                    None => break,
+                },
+                _ = &mut server_shutdown_rx => {
+                    info!("Received signal to shutdown server at {:?}", self.self_addr);
+                    break;
+                },
+            }
+        }
+        endpoint.close(VarInt::from_u32(0), b"shutdown");
+        info!("Server shutdown at {:?}", self.self_addr);
+    }
+
+    async fn handle_connection(self: Arc<Self>, connecting: Connecting) {
+        let connection = match connecting.await {
+            Ok(connection) => connection,
+            Err(e) => {
+                warn!("Failed to accept connection at {:?}: {}", self.self_addr, e);
+                return;
+            },
+        };
+        let remote_addr = connection.remote_address();
+        // Streams are accepted until the remote node closes the connection.
+        while let Ok(stream) = connection.accept_uni().await {
+            tokio::spawn(self.clone().handle_stream(remote_addr, stream));
+        }
+    }
+
+    async fn handle_stream(self: Arc<Self>, remote_addr: SocketAddr, mut stream: RecvStream) {
+        let message_type = match read_frame(&mut stream).await {
+            Ok(Some(message_type)) => {
+                MessageType::new(String::from_utf8_lossy(&message_type).into_owned())
+            },
+            Ok(None) => return,
+            Err(e) => {
+                warn!("Failed to read stream from {:?}: {}", remote_addr, e);
+                return;
+            },
+        };
+        let handler = self
+            .inbound_handlers
+            .lock()
+            .unwrap()
+            .get(&message_type)
+            .cloned();
+        let Some(handler) = handler else {
+            error!(
+                "No handler registered for sender: {:?} and msg type {:?}",
+                remote_addr, message_type
+            );
+            return;
+        };
+        loop {
+            match read_frame(&mut stream).await {
+                Ok(Some(data)) => {
+                    let _timer = NETWORK_HANDLER_TIMER
+                        .with_label_values(&[&self.self_addr.to_string(), "inbound_msgs"])
+                        .start_timer();
+                    if handler.send(Message::new(data)).is_err() {
+                        return;
+                    }
+                },
+                Ok(None) => return,
+                Err(e) => {
+                    warn!(
+                        "Failed to read {:?} message from {:?}: {}",
+                        message_type, remote_addr, e
+                    );
+                    return;
+                },
+            }
+        }
+    }
+}
+
+pub struct QuicNetworkMessageServiceClientWrapper {
+    remote_addr: SocketAddr,
+    // Drives the endpoint, as the runtime of the outbound handler blocks its threads while it
+    // waits for messages to send. `None` once shut down.
+    driver_rt: Option<Runtime>,
+    endpoint: Endpoint,
+    // Connected on the first message, as the gRPC channel.
+    connection: Option<Connection>,
+    streams: HashMap<MessageType, SendStream>,
+}
+
+impl QuicNetworkMessageServiceClientWrapper {
+    pub fn new(remote_addr: SocketAddr) -> Self {
+        let unspecified_ip: IpAddr = if remote_addr.is_ipv4() {
+            Ipv4Addr::UNSPECIFIED.into()
+        } else {
+            Ipv6Addr::UNSPECIFIED.into()
+        };
+        let driver_rt = tokio::runtime::Builder::new_multi_thread()
+            .worker_threads(1)
+            .thread_name("quic-client")
+            .enable_all()
+            .build()
+            .unwrap();
+        let mut endpoint = {
+            let _guard = driver_rt.enter();
+            Endpoint::client(SocketAddr::new(unspecified_ip, 0))
+                .expect("Failed to bind QUIC client socket")
+        };
+        endpoint.set_default_client_config(client_config());
+        Self {
+            remote_addr,
+            driver_rt: Some(driver_rt),
+            endpoint,
+            connection: None,
+            streams: HashMap::new(),
+        }
+    }
+
+    pub async fn send_message(
+        &mut self,
+        sender_addr: SocketAddr,
+        message: Message,
+        mt: &MessageType,
+    ) {
+        if let Err(e) = self.try_send_message(message, mt).await {
+            panic!(
+                "Error '{}' sending message to {} on node {:?}",
+                e, self.remote_addr, sender_addr
+            );
+        }
+    }
+
+    async fn try_send_message(&mut self, message: Message, mt: &MessageType) -> Result<()> {
+        if !self.streams.contains_key(mt) {
+            let connection = match &self.connection {
+                Some(connection) => connection.clone(),
+                None => {
+                    info!(
+                        "Trying to connect to remote server at {:?}",
+                        self.remote_addr
+                    );
+                    // The connection is driven on the runtime it is created on.
+                    let connecting = {
+                        let _guard = self.driver_rt.as_ref().expect("Shut down").enter();
+                        self.endpoint.connect(self.remote_addr, SERVER_NAME)?
+                    };
+                    let connection = connecting.await?;
+                    self.connection = Some(connection.clone());
+                    connection
+                },
+            };
+            let mut stream = connection.open_uni().await?;
+            write_frame(&mut stream, mt.get_type().as_bytes()).await?;
+            self.streams.insert(mt.clone(), stream);
+        }
+        let stream = self.streams.get_mut(mt).expect("Stream was just opened");
+        write_frame(stream, &message.data).await
+    }
+}
+
+impl Drop for QuicNetworkMessageServiceClientWrapper {
+    fn drop(&mut self) {
+        // Dropped by the outbound handler task, where the runtime can't wait for its threads.
+        if let Some(driver_rt) = self.driver_rt.take() {
+            driver_rt.shutdown_background();
+        }
+    }
+}
+
+async fn write_frame(stream: &mut SendStream, data: &[u8]) -> Result<()> {
+    ensure!(
+        data.len() <= MAX_MESSAGE_SIZE,
+        "Message of {} bytes exceeds the limit of {} bytes",
+        data.len(),
+        MAX_MESSAGE_SIZE
+    );
+    stream.write_all(&(data.len() as u32).to_be_bytes()).await?;
+    stream.write_all(data).await?;
+    Ok(())
+}
+
+/// Reads the next length prefixed frame of the stream, `None` once the stream is finished.
+async fn read_frame(stream: &mut RecvStream) -> Result<Option<Vec<u8>>> {
+    let mut len_bytes = [0u8; 4];
+    match stream.read_exact(&mut len_bytes).await {
+        Ok(()) => {},
+        Err(ReadExactError::FinishedEarly) => return Ok(None),
+        Err(e) => return Err(e.into()),
+    }
+    let len = u32::from_be_bytes(len_bytes) as usize;
+    ensure!(
+        len <= MAX_MESSAGE_SIZE,
+        "Message of {} bytes exceeds the limit of {} bytes",
+        len,
+        MAX_MESSAGE_SIZE
+    );
+    let mut data = vec![0u8; len];
+    stream.read_exact(&mut data).await?;
+    Ok(Some(data))
+}
+
+fn transport_config() -> Arc<TransportConfig> {
+    let mut config = TransportConfig::default();
+    config
+        .max_concurrent_uni_streams(VarInt::from_u32(MAX_STREAMS))
+        .keep_alive_interval(Some(KEEP_ALIVE_INTERVAL));
+    Arc::new(config)
+}
+
+fn server_config() -> ServerConfig {
+    let cert = rcgen::generate_simple_self_signed(vec![SERVER_NAME.to_string()])
+        .expect("Failed to generate certificate");
+    let mut config = ServerConfig::with_single_cert(
+        vec![rustls::Certificate(
+            cert.serialize_der()
+                .expect("Failed to serialize certificate"),
+        )],
+        rustls::PrivateKey(cert.serialize_private_key_der()),
+    )
+    .expect("Failed to create QUIC server config");
+    config.transport_config(transport_config());
+    config
+}
+
+fn client_config() -> ClientConfig {
+    let crypto = rustls::ClientConfig::builder()
+        .with_safe_defaults()
+        .with_custom_certificate_verifier(Arc::new(SkipServerVerification))
+        .with_no_client_auth();
+    let mut config = ClientConfig::new(Arc::new(crypto));
+    config.transport_config(transport_config());
+    config
+}
+
+/// Accepts the self-signed certificates of the servers, see the module doc.
+struct SkipServerVerification;
+
+impl rustls::client::ServerCertVerifier for SkipServerVerification {
+    fn verify_server_cert(
+        &self,
+        _end_entity: &rustls::Certificate,
+        _intermediates: &[rustls::Certificate],
+        _server_name: &rustls::ServerName,
+        _scts: &mut dyn Iterator<Item = &[u8]>,
+        _ocsp_response: &[u8],
+        _now: SystemTime,
+    ) -> Result<rustls::client::ServerCertVerified, rustls::Error> {
+        Ok(rustls::client::ServerCertVerified::assertion())
+    }
+}
+
+#[test]
+fn basic_test() {
+    use aptos_config::utils;
+
+    let server_addr = SocketAddr::new(IpAddr::V4(Ipv4Addr::LOCALHOST), utils::get_available_port());
+    let client_addr = SocketAddr::new(IpAddr::V4(Ipv4Addr::LOCALHOST), utils::get_available_port());
+    let server_handlers: Arc<Mutex<HashMap<MessageType, Sender<Message>>>> =
+        Arc::new(Mutex::new(HashMap::new()));
+    let (small_tx, small_rx) = crossbeam_channel::unbounded();
+    let (large_tx, large_rx) = crossbeam_channel::unbounded();
+    server_handlers
+        .lock()
+        .unwrap()
+        .insert(MessageType::new("small".to_string()), small_tx);
+    server_handlers
+        .lock()
+        .unwrap()
+        .insert(MessageType::new("large".to_string()), large_tx);
+
+    let rt = Runtime::new().unwrap();
+    let (server_shutdown_tx, server_shutdown_rx) = oneshot::channel();
+    QuicNetworkMessageServiceServerWrapper::new(server_handlers, server_addr)
+        .start(&rt, server_shutdown_rx);
+    let mut client = QuicNetworkMessageServiceClientWrapper::new(server_addr);
+
+    // Messages of a type arrive in order, each type on its own stream of the connection.
+    let large_message = vec![7u8; 4 * 1024 * 1024];
+    rt.block_on(async {
+        for i in 0..3u8 {
+            client
+                .send_message(
+                    client_addr,
+                    Message::new(large_message.clone()),
+                    &MessageType::new("large".to_string()),
+                )
+                .await;
+            client
+                .send_message(
+                    client_addr,
+                    Message::new(vec![i]),
+                    &MessageType::new("small".to_string()),
+                )
+                .await;
+        }
+    });
+    for i in 0..3u8 {
+        assert_eq!(small_rx.recv().unwrap().data, vec![i]);
+        assert_eq!(large_rx.recv().unwrap().data, large_message);
+    }
+    assert_eq!(client.streams.len(), 2);
+
+    // Empty messages (which the gRPC service can send as well) are delivered.
+    rt.block_on(client.send_message(
+        client_addr,
+        Message::new(vec![]),
+        &MessageType::new("small".to_string()),
+    ));
+    assert!(small_rx.recv().unwrap().data.is_empty());
+    server_shutdown_tx.send(()).unwrap();
+}
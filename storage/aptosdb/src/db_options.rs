@@ -2,10 +2,10 @@
 // SPDX-License-Identifier: Apache-2.0
 
 use crate::schema::*;
-use aptos_config::config::RocksdbConfig;
+use aptos_config::config::{RocksdbConfig, RocksdbDurability};
 use aptos_schemadb::{
     BlockBasedOptions, Cache, ColumnFamilyDescriptor, ColumnFamilyName, DBCompressionType, Options,
-    SliceTransform, DEFAULT_COLUMN_FAMILY_NAME,
+    SliceTransform, WriteDurability, DEFAULT_COLUMN_FAMILY_NAME,
 };
 use aptos_types::transaction::Version;
 
@@ -192,6 +192,14 @@ pub(super) fn gen_state_kv_cfds(rocksdb_config: &RocksdbConfig) -> Vec<ColumnFam
     gen_cfds(rocksdb_config, cfs, with_state_key_extractor_processor)
 }
 
+pub(super) fn gen_write_durability(rocksdb_config: &RocksdbConfig) -> WriteDurability {
+    match rocksdb_config.durability {
+        RocksdbDurability::Strict => WriteDurability::Sync,
+        RocksdbDurability::Relaxed => WriteDurability::NoSync,
+        RocksdbDurability::None => WriteDurability::NoWal,
+    }
+}
+
 fn state_key_extractor(state_value_raw_key: &[u8]) -> &[u8] {
     &state_value_raw_key[..(state_value_raw_key.len() - VERSION_SIZE)]
 }
@@ -8,9 +8,10 @@ use crate::{
     db_options::{
         event_db_column_families, gen_event_cfds, gen_ledger_cfds, gen_ledger_metadata_cfds,
         gen_transaction_accumulator_cfds, gen_transaction_cfds, gen_transaction_info_cfds,
-        gen_write_set_cfds, ledger_db_column_families, ledger_metadata_db_column_families,
-        transaction_accumulator_db_column_families, transaction_db_column_families,
-        transaction_info_db_column_families, write_set_db_column_families,
+        gen_write_durability, gen_write_set_cfds, ledger_db_column_families,
+        ledger_metadata_db_column_families, transaction_accumulator_db_column_families,
+        transaction_db_column_families, transaction_info_db_column_families,
+        write_set_db_column_families,
     },
     schema::db_metadata::{DbMetadataKey, DbMetadataSchema, DbMetadataValue},
 };
@@ -169,6 +170,15 @@ impl LedgerDb {
             ..Default::default()
         };
         let ledger_db = Self::new(db_root_path, rocksdb_configs, /*readonly=*/ false)?;
+        ledger_db.checkpoint_to(cp_root_path, sharding)
+    }
+
+    /// Creates a checkpoint of this (open) ledger db under `cp_root_path`.
+    pub(crate) fn checkpoint_to(
+        &self,
+        cp_root_path: impl AsRef<Path>,
+        sharding: bool,
+    ) -> Result<()> {
         let cp_ledger_db_folder = cp_root_path.as_ref().join(LEDGER_DB_FOLDER_NAME);
 
         info!(
@@ -181,25 +191,19 @@ impl LedgerDb {
             std::fs::create_dir_all(&cp_ledger_db_folder).unwrap_or(());
         }
 
-        ledger_db
-            .metadata_db()
+        self.metadata_db()
             .create_checkpoint(Self::metadata_db_path(cp_root_path.as_ref(), sharding))?;
 
         if sharding {
-            ledger_db
-                .event_db()
+            self.event_db()
                 .create_checkpoint(cp_ledger_db_folder.join(EVENT_DB_NAME))?;
-            ledger_db
-                .transaction_accumulator_db()
+            self.transaction_accumulator_db()
                 .create_checkpoint(cp_ledger_db_folder.join(TRANSACTION_ACCUMULATOR_DB_NAME))?;
-            ledger_db
-                .transaction_db()
+            self.transaction_db()
                 .create_checkpoint(cp_ledger_db_folder.join(TRANSACTION_DB_NAME))?;
-            ledger_db
-                .transaction_info_db()
+            self.transaction_info_db()
                 .create_checkpoint(cp_ledger_db_folder.join(TRANSACTION_INFO_DB_NAME))?;
-            ledger_db
-                .write_set_db()
+            self.write_set_db()
                 .create_checkpoint(cp_ledger_db_folder.join(WRITE_SET_DB_NAME))?;
         }
 
@@ -303,6 +307,7 @@ impl LedgerDb {
                 name,
                 Self::gen_cfds_by_name(db_config, name),
             )?
+            .with_write_durability(gen_write_durability(db_config))
         };
 
         info!("Opened {name} at {path:?}!");
@@ -2344,6 +2344,29 @@ impl DbWriter for AptosDB {
             Ok(())
         })
     }
+
+    fn create_db_checkpoint(&self, cp_path: &Path) -> Result<()> {
+        gauged_api("create_db_checkpoint", || {
+            let start = Instant::now();
+            let sharding = self.state_kv_db.enabled_sharding();
+            std::fs::create_dir_all(cp_path)?;
+            self.ledger_db.checkpoint_to(cp_path, sharding)?;
+            if sharding {
+                self.state_kv_db.checkpoint_to(cp_path)?;
+            }
+            self.state_store
+                .state_db
+                .state_merkle_db
+                .checkpoint_to(cp_path, sharding)?;
+
+            info!(
+                cp_path = cp_path,
+                time_ms = %start.elapsed().as_millis(),
+                "Made checkpoint of the open AptosDB."
+            );
+            Ok(())
+        })
+    }
 }
 
 // Convert requested range and order to a range in ascending order.
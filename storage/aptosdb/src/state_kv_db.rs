@@ -5,7 +5,7 @@
 
 use crate::{
     db_metadata::{DbMetadataKey, DbMetadataSchema, DbMetadataValue},
-    db_options::{gen_state_kv_cfds, state_kv_db_column_families},
+    db_options::{gen_state_kv_cfds, gen_write_durability, state_kv_db_column_families},
     metrics::OTHER_TIMERS_SECONDS,
     utils::truncation_helper::{get_state_kv_commit_progress, truncate_state_kv_db_shards},
     NUM_STATE_SHARDS,
@@ -156,6 +156,11 @@ impl StateKvDb {
             RocksdbConfig::default(),
             false,
         )?;
+        state_kv_db.checkpoint_to(cp_root_path)
+    }
+
+    /// Creates a checkpoint of this (open) state kv db under `cp_root_path`.
+    pub(crate) fn checkpoint_to(&self, cp_root_path: impl AsRef<Path>) -> Result<()> {
         let cp_state_kv_db_path = cp_root_path.as_ref().join(STATE_KV_DB_FOLDER_NAME);
 
         info!("Creating state_kv_db checkpoint at: {cp_state_kv_db_path:?}");
@@ -163,13 +168,11 @@ impl StateKvDb {
         std::fs::remove_dir_all(&cp_state_kv_db_path).unwrap_or(());
         std::fs::create_dir_all(&cp_state_kv_db_path).unwrap_or(());
 
-        state_kv_db
-            .metadata_db()
+        self.metadata_db()
             .create_checkpoint(Self::metadata_db_path(cp_root_path.as_ref()))?;
 
         for shard_id in 0..NUM_STATE_SHARDS {
-            state_kv_db
-                .db_shard(shard_id as u8)
+            self.db_shard(shard_id as u8)
                 .create_checkpoint(Self::db_shard_path(cp_root_path.as_ref(), shard_id as u8))?;
         }
 
@@ -244,6 +247,7 @@ impl StateKvDb {
                 name,
                 gen_state_kv_cfds(state_kv_db_config),
             )?
+            .with_write_durability(gen_write_durability(state_kv_db_config))
         })
     }
 
@@ -3,7 +3,7 @@
 
 use crate::{
     db_metadata::{DbMetadataKey, DbMetadataSchema, DbMetadataValue},
-    db_options::{gen_state_merkle_cfds, state_merkle_db_column_families},
+    db_options::{gen_state_merkle_cfds, gen_write_durability, state_merkle_db_column_families},
     lru_node_cache::LruNodeCache,
     metrics::NODE_CACHE_SECONDS,
     schema::jellyfish_merkle_node::JellyfishMerkleNodeSchema,
@@ -171,6 +171,15 @@ impl StateMerkleDb {
             /*readonly=*/ false,
             /*max_nodes_per_lru_cache_shard=*/ 0,
         )?;
+        state_merkle_db.checkpoint_to(cp_root_path, sharding)
+    }
+
+    /// Creates a checkpoint of this (open) state merkle db under `cp_root_path`.
+    pub(crate) fn checkpoint_to(
+        &self,
+        cp_root_path: impl AsRef<Path>,
+        sharding: bool,
+    ) -> Result<()> {
         let cp_state_merkle_db_path = cp_root_path.as_ref().join(STATE_MERKLE_DB_FOLDER_NAME);
 
         info!("Creating state_merkle_db checkpoint at: {cp_state_merkle_db_path:?}");
@@ -180,14 +189,12 @@ impl StateMerkleDb {
             std::fs::create_dir_all(&cp_state_merkle_db_path).unwrap_or(());
         }
 
-        state_merkle_db
-            .metadata_db()
+        self.metadata_db()
             .create_checkpoint(Self::metadata_db_path(cp_root_path.as_ref(), sharding))?;
 
         if sharding {
             for shard_id in 0..NUM_STATE_SHARDS {
-                state_merkle_db
-                    .db_shard(shard_id as u8)
+                self.db_shard(shard_id as u8)
                     .create_checkpoint(Self::db_shard_path(
                         cp_root_path.as_ref(),
                         shard_id as u8,
@@ -627,6 +634,7 @@ impl StateMerkleDb {
                 name,
                 gen_state_merkle_cfds(state_merkle_db_config),
             )?
+            .with_write_durability(gen_write_durability(state_merkle_db_config))
         })
     }
 
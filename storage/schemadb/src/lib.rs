@@ -43,6 +43,18 @@ use std::{collections::HashMap, iter::Iterator, path::Path};
 
 pub type ColumnFamilyName = &'static str;
 
+/// How durable the writes to a [`DB`] are once they return.
+#[derive(Clone, Copy, Debug, Default, Eq, PartialEq)]
+pub enum WriteDurability {
+    /// Fsync the WAL on every write.
+    #[default]
+    Sync,
+    /// Write the WAL, without fsyncing it.
+    NoSync,
+    /// Don't write the WAL, the writes are only persisted when the memtables are flushed.
+    NoWal,
+}
+
 #[derive(Debug)]
 enum WriteOp {
     Value { key: Vec<u8>, value: Vec<u8> },
@@ -102,6 +114,7 @@ impl SchemaBatch {
 pub struct DB {
     name: String, // for logging
     inner: rocksdb::DB,
+    write_durability: WriteDurability,
 }
 
 impl DB {
@@ -174,9 +187,22 @@ impl DB {
         DB {
             name: name.to_string(),
             inner,
+            write_durability: WriteDurability::default(),
         }
     }
 
+    /// Relaxes the durability of the writes, which are synchronous by default.
+    pub fn with_write_durability(mut self, write_durability: WriteDurability) -> Self {
+        if write_durability != WriteDurability::Sync {
+            info!(
+                rocksdb_name = self.name,
+                "Writing to RocksDB with {:?} durability.", write_durability
+            );
+        }
+        self.write_durability = write_durability;
+        self
+    }
+
     /// Reads single record by key.
     pub fn get<S: Schema>(&self, schema_key: &S::Key) -> Result<Option<S::Value>> {
         let _timer = APTOS_SCHEMADB_GET_LATENCY_SECONDS
@@ -256,7 +282,8 @@ impl DB {
         }
         let serialized_size = db_batch.size_in_bytes();
 
-        self.inner.write_opt(db_batch, &default_write_options())?;
+        self.inner
+            .write_opt(db_batch, &write_options(self.write_durability))?;
 
         // Bump counters only after DB write succeeds.
         if sampled_kv_bytes {
@@ -325,12 +352,16 @@ impl Drop for DB {
     }
 }
 
-/// For now we always use synchronous writes. This makes sure that once the operation returns
-/// `Ok(())` the data is persisted even if the machine crashes. In the future we might consider
-/// selectively turning this off for some non-critical writes to improve performance.
-fn default_write_options() -> rocksdb::WriteOptions {
+/// By default we use synchronous writes. This makes sure that once the operation returns
+/// `Ok(())` the data is persisted even if the machine crashes. The other modes trade that for
+/// lower commit latency, e.g. to measure the cost of durability in benchmarks.
+fn write_options(write_durability: WriteDurability) -> rocksdb::WriteOptions {
     let mut opts = rocksdb::WriteOptions::default();
-    opts.set_sync(true);
+    match write_durability {
+        WriteDurability::Sync => opts.set_sync(true),
+        WriteDurability::NoSync => opts.set_sync(false),
+        WriteDurability::NoWal => opts.disable_wal(true),
+    }
     opts
 }
 
@@ -5,7 +5,8 @@
 #![forbid(unsafe_code)]
 
 use aptos_metrics_core::{
-    exponential_buckets, register_histogram_vec, register_int_gauge_vec, HistogramVec, IntGaugeVec,
+    exponential_buckets, register_histogram_vec, register_int_gauge_vec, Histogram, HistogramVec,
+    IntGaugeVec,
 };
 use once_cell::sync::Lazy;
 
@@ -19,6 +20,23 @@ pub static TIMER: Lazy<HistogramVec> = Lazy::new(|| {
     .unwrap()
 });
 
+pub static SHARD_UPDATE_TIMER: Lazy<HistogramVec> = Lazy::new(|| {
+    register_histogram_vec!(
+        "aptos_scratchpad_smt_shard_update_seconds",
+        "Time spent updating the subtree of each state shard, i.e. of the keys sharing a first nibble.",
+        &["shard_id"],
+        exponential_buckets(/*start=*/ 1e-6, /*factor=*/ 2.0, /*count=*/ 22).unwrap(),
+    )
+    .unwrap()
+});
+
+/// `SHARD_UPDATE_TIMER` of each of the 16 state shards, looked up once instead of on every update.
+pub static SHARD_UPDATE_TIMERS: Lazy<Vec<Histogram>> = Lazy::new(|| {
+    (0..16)
+        .map(|shard_id| SHARD_UPDATE_TIMER.with_label_values(&[&shard_id.to_string()]))
+        .collect()
+});
+
 pub static GENERATION: Lazy<IntGaugeVec> = Lazy::new(|| {
     register_int_gauge_vec!(
         "aptos_scratchpad_smt_generation",
@@ -105,6 +105,7 @@ use std::{
     sync::{Arc, MutexGuard, Weak},
 };
 use thiserror::Error;
+pub use updater::set_update_pool_once;
 
 type NodePosition = bitvec::vec::BitVec<u8, bitvec::order::Msb0>;
 const BITS_IN_NIBBLE: usize = 4;
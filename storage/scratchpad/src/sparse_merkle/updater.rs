@@ -4,6 +4,7 @@
 
 use crate::{
     sparse_merkle::{
+        metrics::SHARD_UPDATE_TIMERS,
         node::{InternalNode, Node, NodeHandle, NodeInner},
         utils::{partition, swap_if},
         UpdateError,
@@ -16,10 +17,30 @@ use aptos_crypto::{
 };
 use aptos_experimental_runtimes::thread_manager::THREAD_MANAGER;
 use aptos_types::proof::{definition::NodeInProof, SparseMerkleLeafNode, SparseMerkleProofExt};
+use once_cell::sync::OnceCell;
+use rayon::ThreadPool;
 use std::cmp::Ordering;
 
 type Result<T> = std::result::Result<T, UpdateError>;
 
+static UPDATE_POOL: OnceCell<&'static ThreadPool> = OnceCell::new();
+
+/// Updates the trees on `pool` instead of the execution pool, e.g. on the pool computing the
+/// state checkpoints. Only the first call has an effect.
+pub fn set_update_pool_once(pool: &'static ThreadPool) {
+    UPDATE_POOL.set(pool).ok();
+}
+
+fn update_pool() -> &'static ThreadPool {
+    UPDATE_POOL
+        .get()
+        .copied()
+        .unwrap_or_else(|| THREAD_MANAGER.get_exe_cpu_pool())
+}
+
+/// Depth of the subtrees holding the keys of a state shard, the keys sharing their first nibble.
+const SHARD_DEPTH: usize = 4;
+
 type InMemSubTree<V> = super::node::SubTree<V>;
 type InMemInternal<V> = super::node::InternalNode<V>;
 
@@ -305,6 +326,8 @@ impl<'a, V: Send + Sync + 'static + Clone + CryptoHash> SubTreeUpdater<'a, V> {
 
         let generation = self.generation;
         let depth = self.depth;
+        let _timer = (depth == SHARD_DEPTH && !self.updates.is_empty())
+            .then(|| SHARD_UPDATE_TIMERS[self.updates[0].0.nibble(0) as usize].start_timer());
         match self.maybe_end_recursion()? {
             MaybeEndRecursion::End(ended) => Ok(ended),
             MaybeEndRecursion::Continue(myself) => {
@@ -313,9 +336,7 @@ impl<'a, V: Send + Sync + 'static + Clone + CryptoHash> SubTreeUpdater<'a, V> {
                     && left.updates.len() >= MIN_PARALLELIZABLE_SIZE
                     && right.updates.len() >= MIN_PARALLELIZABLE_SIZE
                 {
-                    THREAD_MANAGER
-                        .get_exe_cpu_pool()
-                        .join(|| left.run(proof_reader), || right.run(proof_reader))
+                    update_pool().join(|| left.run(proof_reader), || right.run(proof_reader))
                 } else {
                     (left.run(proof_reader), right.run(proof_reader))
                 };
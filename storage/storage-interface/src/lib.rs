@@ -36,7 +36,7 @@ use aptos_types::{
     write_set::WriteSet,
 };
 use serde::{Deserialize, Serialize};
-use std::{collections::HashMap, sync::Arc};
+use std::{collections::HashMap, path::Path, sync::Arc};
 use thiserror::Error;
 
 pub mod async_proof_fetcher;
@@ -558,6 +558,12 @@ pub trait DbWriter: Send + Sync {
     ) -> Result<()> {
         unimplemented!()
     }
+
+    /// Creates a checkpoint of the open DB under `cp_path`, which can be opened as a DB of its
+    /// own. Must not be called concurrently with `save_transactions`.
+    fn create_db_checkpoint(&self, cp_path: &Path) -> Result<()> {
+        unimplemented!()
+    }
 }
 
 #[derive(Clone)]
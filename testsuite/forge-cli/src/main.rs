@@ -1368,6 +1368,7 @@ fn individual_workload_tests(test_name: String) -> ForgeConfig {
                 let write_type = TransactionType::CallCustomModules {
                     entry_point: EntryPoints::BytesMakeOrChange {
                         data_length: Some(32),
+                        entropy: None,
                     },
                     num_modules: 1,
                     use_account_pool: true,
@@ -82,4 +82,23 @@ module 0xABCD::smart_table_picture {
             i = i + 1;
         };
     }
+
+    /// Remove elements from the vector, the ones not set are skipped.
+    public entry fun remove(
+        palette_addr: address,
+        palette_index: u64,
+        indices: vector<u64>,
+    ) acquires Palette, AllPalettes {
+        let all_palettes = borrow_global<AllPalettes>(palette_addr);
+        let palette_addr = vector::borrow(&all_palettes.all, palette_index);
+
+        let palette_ = borrow_global_mut<Palette>(*palette_addr);
+
+        while (!vector::is_empty(&indices)) {
+            let index = (vector::pop_back(&mut indices) as u32);
+            if (smart_table::contains(&palette_.pixels, index)) {
+                smart_table::remove(&mut palette_.pixels, index);
+            };
+        };
+    }
 }
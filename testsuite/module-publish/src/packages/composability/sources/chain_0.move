@@ -0,0 +1,31 @@
+// Copyright Aptos Foundation
+// SPDX-License-Identifier: Apache-2.0
+
+/// First link of a chain of modules, each of them published by a different account, to
+/// benchmark deep call stacks and loading many modules in a single transaction.
+/// The transaction generator rewrites the address of every link, and of the next link it
+/// calls into, to the account publishing it.
+module 0xABCD::chain_0 {
+    use 0xABCD::chain_1;
+
+    /// Calls `depth` frames deep into each of the first `num_modules` links of the chain.
+    public entry fun run(_account: &signer, num_modules: u64, depth: u64) {
+        call(num_modules, depth);
+    }
+
+    /// Recurses `depth` frames deep in this link, then calls into the next `num_modules - 1`
+    /// links. Returns the number of frames.
+    public fun call(num_modules: u64, depth: u64): u64 {
+        call_local(num_modules, depth, depth)
+    }
+
+    fun call_local(num_modules: u64, depth: u64, remaining: u64): u64 {
+        if (remaining > 1) {
+            call_local(num_modules, depth, remaining - 1) + 1
+        } else if (num_modules > 1) {
+            chain_1::call(num_modules - 1, depth) + 1
+        } else {
+            1
+        }
+    }
+}
@@ -0,0 +1,23 @@
+// Copyright Aptos Foundation
+// SPDX-License-Identifier: Apache-2.0
+
+/// Link 2 of the chain of modules, see `chain_0`.
+module 0xABCD::chain_2 {
+    use 0xABCD::chain_3;
+
+    /// Recurses `depth` frames deep in this link, then calls into the next `num_modules - 1`
+    /// links. Returns the number of frames.
+    public fun call(num_modules: u64, depth: u64): u64 {
+        call_local(num_modules, depth, depth)
+    }
+
+    fun call_local(num_modules: u64, depth: u64, remaining: u64): u64 {
+        if (remaining > 1) {
+            call_local(num_modules, depth, remaining - 1) + 1
+        } else if (num_modules > 1) {
+            chain_3::call(num_modules - 1, depth) + 1
+        } else {
+            1
+        }
+    }
+}
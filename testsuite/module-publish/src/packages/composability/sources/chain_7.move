@@ -0,0 +1,19 @@
+// Copyright Aptos Foundation
+// SPDX-License-Identifier: Apache-2.0
+
+/// Link 7, the last one of the chain of modules, see `chain_0`.
+module 0xABCD::chain_7 {
+    /// Recurses `depth` frames deep in this link, there is no further link to call into.
+    /// Returns the number of frames.
+    public fun call(_num_modules: u64, depth: u64): u64 {
+        call_local(depth)
+    }
+
+    fun call_local(remaining: u64): u64 {
+        if (remaining > 1) {
+            call_local(remaining - 1) + 1
+        } else {
+            1
+        }
+    }
+}
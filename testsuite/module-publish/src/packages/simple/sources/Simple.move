@@ -416,7 +416,7 @@ module 0xABCD::simple {
         table_entries: Table<u64, u64>
     }
 
-    fun make_or_change_table(owner: &signer, offset: u64, count: u64) acquires TableStore {
+    public entry fun make_or_change_table(owner: &signer, offset: u64, count: u64) acquires TableStore {
         let owner_address = signer::address_of(owner);
         if (!exists<TableStore>(owner_address)) {
             move_to<TableStore>(owner, TableStore {
@@ -432,6 +432,21 @@ module 0xABCD::simple {
         }
     }
 
+    public entry fun remove_from_table(owner: &signer, offset: u64, count: u64) acquires TableStore {
+        let owner_address = signer::address_of(owner);
+        if (!exists<TableStore>(owner_address)) {
+            return
+        };
+        let table_entries = &mut borrow_global_mut<TableStore>(owner_address).table_entries;
+
+        while (count > 0) {
+            count = count - 1;
+            if (table::contains(table_entries, offset+count)) {
+                table::remove(table_entries, offset+count);
+            };
+        }
+    }
+
 
     struct SimpleEvent has drop, store {
         event_id: u64
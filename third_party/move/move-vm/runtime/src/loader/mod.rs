@@ -4,7 +4,7 @@
 
 use crate::{
     config::VMConfig, data_cache::TransactionDataCache, logging::expect_no_verification_errors,
-    native_functions::NativeFunctions, session::LoadedFunctionInstantiation,
+    module_load_stats, native_functions::NativeFunctions, session::LoadedFunctionInstantiation,
 };
 use move_binary_format::{
     access::{ModuleAccess, ScriptAccess},
@@ -36,6 +36,7 @@ use std::{
     collections::{btree_map, BTreeMap, BTreeSet, HashMap},
     hash::Hash,
     sync::Arc,
+    time::Instant,
 };
 
 mod function;
@@ -828,6 +829,7 @@ impl Loader {
         allow_loading_failure: bool,
     ) -> VMResult<CompiledModule> {
         // bytes fetching, allow loading to fail if the flag is set
+        let fetch_start = Instant::now();
         let bytes = match data_store.load_module(id) {
             Ok(bytes) => bytes,
             Err(err) if allow_loading_failure => return Err(err),
@@ -836,8 +838,11 @@ impl Loader {
             },
         };
 
+        let fetch_time = fetch_start.elapsed();
+
         // for bytes obtained from the data store, they should always deserialize and verify.
         // It is an invariant violation if they don't.
+        let deserialize_start = Instant::now();
         let module =
             CompiledModule::deserialize_with_config(&bytes, &self.vm_config.deserializer_config)
                 .map_err(|err| {
@@ -848,6 +853,8 @@ impl Loader {
                 })
                 .map_err(expect_no_verification_errors)?;
 
+        let deserialize_time = deserialize_start.elapsed();
+
         fail::fail_point!("verifier-failpoint-2", |_| { Ok(module.clone()) });
 
         if self.vm_config.paranoid_type_checks && &module.self_id() != id {
@@ -859,10 +866,12 @@ impl Loader {
         }
 
         // bytecode verifier checks that can be performed with the module itself
+        let verify_start = Instant::now();
         move_bytecode_verifier::verify_module_with_config(&self.vm_config.verifier, &module)
             .map_err(expect_no_verification_errors)?;
         self.check_natives(&module)
             .map_err(expect_no_verification_errors)?;
+        module_load_stats::record_module_load(fetch_time, deserialize_time, verify_start.elapsed());
         Ok(module)
     }
 
@@ -0,0 +1,56 @@
+// Copyright (c) The Move Contributors
+// SPDX-License-Identifier: Apache-2.0
+
+//! Process-wide statistics on loading modules from storage into the code cache, so that the time
+//! spent fetching, deserializing and verifying modules can be told apart from execution time.
+
+use std::{
+    sync::atomic::{AtomicU64, Ordering},
+    time::Duration,
+};
+
+static NUM_MODULES: AtomicU64 = AtomicU64::new(0);
+static FETCH_NANOS: AtomicU64 = AtomicU64::new(0);
+static DESERIALIZE_NANOS: AtomicU64 = AtomicU64::new(0);
+static VERIFY_NANOS: AtomicU64 = AtomicU64::new(0);
+
+#[derive(Clone, Copy, Debug, Default, Eq, PartialEq)]
+pub struct ModuleLoadStats {
+    /// Number of modules loaded from storage, i.e. not found in the code cache.
+    pub num_modules: u64,
+    pub fetch: Duration,
+    pub deserialize: Duration,
+    pub verify: Duration,
+}
+
+impl ModuleLoadStats {
+    /// Totals since the start of the process.
+    pub fn snapshot() -> Self {
+        Self {
+            num_modules: NUM_MODULES.load(Ordering::Relaxed),
+            fetch: Duration::from_nanos(FETCH_NANOS.load(Ordering::Relaxed)),
+            deserialize: Duration::from_nanos(DESERIALIZE_NANOS.load(Ordering::Relaxed)),
+            verify: Duration::from_nanos(VERIFY_NANOS.load(Ordering::Relaxed)),
+        }
+    }
+
+    pub fn since(&self, earlier: &Self) -> Self {
+        Self {
+            num_modules: self.num_modules - earlier.num_modules,
+            fetch: self.fetch - earlier.fetch,
+            deserialize: self.deserialize - earlier.deserialize,
+            verify: self.verify - earlier.verify,
+        }
+    }
+
+    pub fn total(&self) -> Duration {
+        self.fetch + self.deserialize + self.verify
+    }
+}
+
+pub(crate) fn record_module_load(fetch: Duration, deserialize: Duration, verify: Duration) {
+    NUM_MODULES.fetch_add(1, Ordering::Relaxed);
+    FETCH_NANOS.fetch_add(fetch.as_nanos() as u64, Ordering::Relaxed);
+    DESERIALIZE_NANOS.fetch_add(deserialize.as_nanos() as u64, Ordering::Relaxed);
+    VERIFY_NANOS.fetch_add(verify.as_nanos() as u64, Ordering::Relaxed);
+}
@@ -429,6 +429,7 @@ impl<T: Clone> TransactionWithDependencies<T> {
     }
 }
 
+#[derive(Clone)]
 pub struct ExecutableBlock {
     pub block_id: HashValue,
     pub transactions: ExecutableTransactions,
@@ -545,6 +546,7 @@ impl PartitionedTransactions {
 }
 
 // Represents the transactions in a block that are ready to be executed.
+#[derive(Clone)]
 pub enum ExecutableTransactions {
     Unsharded(Vec<SignatureVerifiedTransaction>),
     Sharded(PartitionedTransactions),